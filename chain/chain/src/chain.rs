@@ -95,7 +95,7 @@ use near_primitives::views::{
 };
 use near_store::adapter::chain_store::ChainStoreAdapter;
 use near_store::get_genesis_state_roots;
-use near_store::{DBCol, StateSnapshotConfig};
+use near_store::{DBCol, StateSnapshotConfig, StoreContents};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::cell::Cell;
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -2825,7 +2825,7 @@ impl Chain {
     }
 
     /// Returns execution status based on the list of currently existing outcomes
-    fn get_execution_status(
+    pub fn get_execution_status(
         &self,
         outcomes: &[ExecutionOutcomeWithIdView],
         transaction_hash: &CryptoHash,
@@ -3826,6 +3826,40 @@ impl Chain {
     pub fn patch_state_in_progress(&self) -> bool {
         !self.pending_state_patch.is_empty()
     }
+
+    /// Replaces the contents of the store with ones read earlier with
+    /// `Store::read_contents` and reloads flat storage and memtries from it.
+    /// A pending state patch is dropped.
+    ///
+    /// The latest known height is preserved so that heights of the discarded
+    /// blocks are not produced again.
+    pub fn restore_store_contents(&mut self, contents: &StoreContents) -> Result<(), Error> {
+        if self.blocks_in_processing_len() > 0 {
+            return Err(Error::Other(
+                "cannot restore the store while blocks are being processed".to_string(),
+            ));
+        }
+        let latest_known = self.chain_store.get_latest_known()?;
+        let tries = self.runtime_adapter.get_tries();
+        let shard_layout = self.epoch_manager.get_shard_layout(&self.head()?.epoch_id)?;
+        let memtrie_shard_uids = shard_layout
+            .shard_uids()
+            .filter(|shard_uid| tries.get_memtries(*shard_uid).is_some())
+            .collect_vec();
+
+        self.chain_store.store().restore_contents(contents)?;
+        self.chain_store.save_latest_known(latest_known)?;
+        self.pending_state_patch.clear();
+
+        let flat_storage_manager = self.runtime_adapter.get_flat_storage_manager();
+        flat_storage_manager.unload_all_flat_storages();
+        self.init_flat_storage()?;
+        for shard_uid in memtrie_shard_uids {
+            tries.unload_memtrie(&shard_uid);
+            tries.load_memtrie(&shard_uid, None, true)?;
+        }
+        Ok(())
+    }
 }
 
 pub fn do_apply_chunks(
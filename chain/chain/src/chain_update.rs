@@ -483,6 +483,7 @@ impl<'a> ChainUpdate<'a> {
                 gas_limit,
                 last_validator_proposals: chunk_header.prev_validator_proposals(),
                 is_first_block_with_chunk_of_version,
+                trace_host_calls: false,
                 is_new_chunk: true,
             },
             ApplyChunkBlockContext {
@@ -600,6 +601,7 @@ impl<'a> ChainUpdate<'a> {
                 gas_limit: chunk_extra.gas_limit(),
                 is_new_chunk: false,
                 is_first_block_with_chunk_of_version: false,
+                trace_host_calls: false,
             },
             ApplyChunkBlockContext::from_header(
                 &block_header,
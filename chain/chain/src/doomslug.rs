@@ -11,7 +11,7 @@ use num_rational::Rational32;
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use time::ext::InstantExt as _;
-use tracing::{debug, debug_span, field, info};
+use tracing::{debug, debug_span, error, field, info};
 
 /// Have that many iterations in the timer instead of `loop` to prevent potential bugs from blocking
 /// the node
@@ -540,9 +540,14 @@ impl Doomslug {
         target_height: BlockHeight,
         signer: &Option<Arc<ValidatorSigner>>,
     ) -> Option<Approval> {
-        signer.as_ref().map(|signer| {
-            Approval::new(self.tip.block_hash, self.tip.height, target_height, &*signer)
-        })
+        let signer = signer.as_ref()?;
+        match Approval::try_new(self.tip.block_hash, self.tip.height, target_height, &*signer) {
+            Ok(approval) => Some(approval),
+            Err(err) => {
+                error!(target: "doomslug", target_height, ?err, "Failed to sign approval");
+                None
+            }
+        }
     }
 
     /// Determines whether a block has enough approvals to be produced.
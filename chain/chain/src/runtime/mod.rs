@@ -1,6 +1,6 @@
 use crate::Error;
 use crate::types::{
-    ApplyChunkBlockContext, ApplyChunkResult, ApplyChunkShardContext,
+    ApplyChunkBlockContext, ApplyChunkResult, ApplyChunkShardContext, BlockHeader,
     PrepareTransactionsBlockContext, PrepareTransactionsChunkContext, PrepareTransactionsLimit,
     PreparedTransactions, RuntimeAdapter, RuntimeStorageConfig, StorageDataSource, Tip,
 };
@@ -14,9 +14,13 @@ use near_epoch_manager::{EpochManagerAdapter, EpochManagerHandle};
 use near_parameters::{RuntimeConfig, RuntimeConfigStore};
 use near_pool::types::TransactionGroupIterator;
 use near_primitives::account::{AccessKey, Account};
+use near_primitives::action::GlobalContractIdentifier;
 use near_primitives::apply::ApplyChunkReason;
+use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
+use near_primitives::chunk_apply_stats::BalanceAudit;
 use near_primitives::congestion_info::{
-    CongestionControl, ExtendedCongestionInfo, RejectTransactionReason, ShardAcceptsTransactions,
+    BlockCongestionInfo, CongestionControl, ExtendedCongestionInfo, RejectTransactionReason,
+    ShardAcceptsTransactions,
 };
 use near_primitives::errors::{InvalidTxError, RuntimeError, StorageError};
 use near_primitives::hash::{CryptoHash, hash};
@@ -32,8 +36,8 @@ use near_primitives::types::{
 };
 use near_primitives::version::{ProtocolFeature, ProtocolVersion};
 use near_primitives::views::{
-    AccessKeyInfoView, CallResult, ContractCodeView, QueryRequest, QueryResponse,
-    QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, CallResult, ContractCodeView, HostFunctionCallView, QueryRequest,
+    QueryResponse, QueryResponseKind, ViewStateResult,
 };
 use near_store::adapter::{StoreAdapter, StoreUpdateAdapter};
 use near_store::db::metadata::DbKind;
@@ -48,8 +52,9 @@ use node_runtime::adapter::ViewRuntimeAdapter;
 use node_runtime::config::tx_cost;
 use node_runtime::state_viewer::{TrieViewer, ViewApplyState};
 use node_runtime::{
-    ApplyState, Runtime, SignedValidPeriodTransactions, ValidatorAccountsUpdate,
-    set_tx_state_changes, validate_transaction, verify_and_charge_tx_ephemeral,
+    ApplyState, Runtime, SignedValidPeriodTransactions, SimulationError, SimulationResult,
+    ValidatorAccountsUpdate, set_tx_state_changes, validate_transaction,
+    verify_and_charge_tx_ephemeral,
 };
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -78,6 +83,11 @@ pub struct NightshadeRuntime {
     epoch_manager: Arc<EpochManagerHandle>,
     migration_data: Arc<MigrationData>,
     gc_num_epochs_to_keep: u64,
+    /// Whether to compute the balance audit of the chunks applied to update a tracked shard.
+    audit_chunk_balances: bool,
+    /// Whether to profile the gas burnt by the functions of the called contracts, see
+    /// `ApplyState::profile_wasm_functions`.  Only set by debugging tools.
+    profile_wasm_functions: bool,
 }
 
 impl NightshadeRuntime {
@@ -92,6 +102,8 @@ impl NightshadeRuntime {
         gc_num_epochs_to_keep: u64,
         trie_config: TrieConfig,
         state_snapshot_config: StateSnapshotConfig,
+        audit_chunk_balances: bool,
+        profile_wasm_functions: bool,
     ) -> Arc<Self> {
         let runtime_config_store = match runtime_config_store {
             Some(store) => store,
@@ -131,6 +143,8 @@ impl NightshadeRuntime {
             epoch_manager,
             migration_data,
             gc_num_epochs_to_keep: gc_num_epochs_to_keep.max(MIN_GC_NUM_EPOCHS_TO_KEEP),
+            audit_chunk_balances,
+            profile_wasm_functions,
         })
     }
 
@@ -154,6 +168,51 @@ impl NightshadeRuntime {
         Ok(ShardUId::new(shard_version, shard_id))
     }
 
+    /// Computes the balance audit of an applied chunk, see
+    /// `node_runtime::balance_audit`.  Failures only affect the reporting, so they are logged
+    /// instead of being returned.
+    fn audit_chunk_balance(
+        &self,
+        config: &RuntimeConfig,
+        shard_id: ShardId,
+        prev_block_hash: &CryptoHash,
+        prev_state_root: StateRoot,
+        receipts: &[Receipt],
+        result: &ApplyChunkResult,
+    ) -> Option<BalanceAudit> {
+        let audit = self
+            .get_trie_for_shard(shard_id, prev_block_hash, prev_state_root, false)
+            .and_then(|trie| {
+                node_runtime::balance_audit::audit_chunk_balance(
+                    config,
+                    &trie,
+                    result.trie_changes.state_changes(),
+                    receipts,
+                    &result.outgoing_receipts,
+                )
+                .map_err(|err| Error::Other(err.to_string()))
+            });
+        match audit {
+            Ok(audit) => {
+                if !audit.is_balanced(&result.stats.balance) {
+                    tracing::warn!(
+                        target: "runtime",
+                        ?shard_id,
+                        ?prev_block_hash,
+                        ?audit,
+                        balance = ?result.stats.balance,
+                        "chunk balance audit does not add up"
+                    );
+                }
+                Some(audit)
+            }
+            Err(err) => {
+                tracing::warn!(target: "runtime", ?shard_id, ?err, "failed to audit chunk balance");
+                None
+            }
+        }
+    }
+
     /// Processes state update.
     #[instrument(target = "runtime", level = "debug", "process_state_update", skip_all)]
     fn process_state_update(
@@ -182,6 +241,7 @@ impl NightshadeRuntime {
             gas_limit,
             is_new_chunk,
             is_first_block_with_chunk_of_version,
+            trace_host_calls,
         } = chunk;
         let epoch_id = self.epoch_manager.get_epoch_id_from_prev_block(prev_block_hash)?;
         let validator_accounts_update = {
@@ -267,6 +327,8 @@ impl NightshadeRuntime {
             },
             congestion_info,
             bandwidth_requests,
+            trace_host_calls,
+            profile_wasm_functions: self.profile_wasm_functions,
         };
 
         let instant = Instant::now();
@@ -347,6 +409,8 @@ impl NightshadeRuntime {
             bandwidth_scheduler_state_hash: apply_result.bandwidth_scheduler_state_hash,
             contract_updates: apply_result.contract_updates,
             stats: apply_result.stats,
+            host_call_traces: apply_result.host_call_traces,
+            wasm_profiles: apply_result.wasm_profiles,
         };
 
         Ok(result)
@@ -393,6 +457,34 @@ impl NightshadeRuntime {
         Ok(epoch_start_height)
     }
 
+    fn query_global_contract_code(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+        block_height: BlockHeight,
+        block_hash: &CryptoHash,
+        identifier: &GlobalContractIdentifier,
+    ) -> Result<QueryResponse, crate::near_chain_primitives::error::QueryError> {
+        let contract_code =
+            self.view_global_contract_code(&shard_uid, *state_root, identifier).map_err(|err| {
+                crate::near_chain_primitives::error::QueryError::from_view_contract_code_error(
+                    err,
+                    block_height,
+                    *block_hash,
+                )
+            })?;
+        let hash = *contract_code.hash();
+        Ok(QueryResponse {
+            kind: QueryResponseKind::ViewCode(ContractCodeView {
+                hash,
+                code: contract_code.into_code(),
+            }),
+            block_height,
+            block_hash: *block_hash,
+            served_from: None,
+        })
+    }
+
     fn obtain_state_part_impl(
         &self,
         shard_id: ShardId,
@@ -782,6 +874,15 @@ impl RuntimeAdapter for NightshadeRuntime {
             .with_label_values(&[&apply_reason.to_string(), &shard_id.to_string()])
             .start_timer();
 
+        // Balances are only audited if enabled in the client config, and when the chunk is
+        // applied to update the state of the node, the previous state is then available in the
+        // database.
+        let audit_balance = self.audit_chunk_balances
+            && apply_reason == ApplyChunkReason::UpdateTrackedShard
+            && matches!(storage_config.source, StorageDataSource::Db);
+        let prev_block_hash = block.prev_block_hash;
+        let prev_state_root = storage_config.state_root;
+
         let mut trie = match storage_config.source {
             StorageDataSource::Db => self.get_trie_for_shard(
                 shard_id,
@@ -827,7 +928,19 @@ impl RuntimeAdapter for NightshadeRuntime {
             transactions,
             storage_config.state_patch,
         ) {
-            Ok(result) => Ok(result),
+            Ok(mut result) => {
+                if audit_balance {
+                    result.stats.balance_audit = self.audit_chunk_balance(
+                        config,
+                        shard_id,
+                        &prev_block_hash,
+                        prev_state_root,
+                        receipts,
+                        &result,
+                    );
+                }
+                Ok(result)
+            }
             Err(e) => match e {
                 Error::StorageError(err) => match &err {
                     StorageError::FlatStorageBlockNotSupported(_)
@@ -864,6 +977,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::ViewAccount(account.into()),
                     block_height,
                     block_hash: *block_hash,
+                    served_from: None,
                 })
             }
             QueryRequest::ViewCode { account_id } => {
@@ -876,10 +990,27 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::ViewCode(contract_code_view),
                     block_height,
                     block_hash: *block_hash,
+                    served_from: None,
                 })
             }
-            QueryRequest::CallFunction { account_id, method_name, args } => {
+            QueryRequest::ViewGlobalContractCode { code_hash } => self.query_global_contract_code(
+                shard_uid,
+                state_root,
+                block_height,
+                block_hash,
+                &GlobalContractIdentifier::CodeHash(*code_hash),
+            ),
+            QueryRequest::ViewGlobalContractCodeByAccountId { account_id } => self
+                .query_global_contract_code(
+                    shard_uid,
+                    state_root,
+                    block_height,
+                    block_hash,
+                    &GlobalContractIdentifier::AccountId(account_id.clone()),
+                ),
+            QueryRequest::CallFunction { account_id, method_name, args, trace } => {
                 let mut logs = vec![];
+                let mut host_calls = trace.then(Vec::new);
                 let (epoch_height, current_protocol_version) = {
                     let epoch_manager = self.epoch_manager.read();
                     let epoch_info = epoch_manager.get_epoch_info(epoch_id).map_err(|err| {
@@ -906,6 +1037,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                         method_name,
                         args.as_ref(),
                         &mut logs,
+                        host_calls.as_mut(),
                         self.epoch_manager.as_ref(),
                         current_protocol_version,
                     )
@@ -920,9 +1052,11 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::CallResult(CallResult {
                         result: call_function_result,
                         logs,
+                        host_calls,
                     }),
                     block_height,
                     block_hash: *block_hash,
+                    served_from: None,
                 })
             }
             QueryRequest::ViewState { account_id, prefix, include_proof } => {
@@ -945,6 +1079,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::ViewState(view_state_result),
                     block_height,
                     block_hash: *block_hash,
+                    served_from: None,
                 })
             }
             QueryRequest::ViewAccessKeyList { account_id } => {
@@ -968,6 +1103,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                     ),
                     block_height,
                     block_hash: *block_hash,
+                    served_from: None,
                 })
             }
             QueryRequest::ViewAccessKey { account_id, public_key } => {
@@ -984,11 +1120,70 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::AccessKey(access_key.into()),
                     block_height,
                     block_hash: *block_hash,
+                    served_from: None,
                 })
             }
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        prev_block: &BlockHeader,
+        state_roots: HashMap<ShardId, StateRoot>,
+        signed_tx: SignedTransaction,
+        check_signature: bool,
+        trace_host_calls: bool,
+    ) -> Result<SimulationResult, SimulationError> {
+        // The transaction is executed in the epoch of `prev_block` even if the
+        // next block starts a new one, so that the shard layout matches the
+        // state roots.
+        let epoch_id = *prev_block.epoch_id();
+        let epoch_info =
+            self.epoch_manager.get_epoch_info(&epoch_id).map_err(RuntimeError::from)?;
+        let shard_layout =
+            self.epoch_manager.get_shard_layout(&epoch_id).map_err(RuntimeError::from)?;
+        let current_protocol_version = epoch_info.protocol_version();
+        let tries = state_roots
+            .into_iter()
+            .map(|(shard_id, state_root)| {
+                let shard_uid = ShardUId::from_shard_id_and_layout(shard_id, &shard_layout);
+                (shard_id, self.tries.get_view_trie_for_shard(shard_uid, state_root))
+            })
+            .collect();
+        let apply_state = ApplyState {
+            apply_reason: ApplyChunkReason::ViewTrackedShard,
+            block_height: prev_block.height() + 1,
+            prev_block_hash: *prev_block.hash(),
+            // The block doesn't exist, the hash is only used to derive receipt ids.
+            block_hash: CryptoHash::default(),
+            shard_id: shard_layout.account_id_to_shard_id(signed_tx.transaction.signer_id()),
+            epoch_id,
+            epoch_height: epoch_info.epoch_height(),
+            gas_price: prev_block.next_gas_price(),
+            block_timestamp: prev_block.raw_timestamp(),
+            gas_limit: None,
+            random_seed: *prev_block.random_value(),
+            current_protocol_version,
+            config: self.runtime_config_store.get_config(current_protocol_version).clone(),
+            cache: Some(self.compiled_contract_cache.handle()),
+            is_new_chunk: true,
+            migration_data: Arc::clone(&self.migration_data),
+            migration_flags: MigrationFlags::default(),
+            congestion_info: BlockCongestionInfo::default(),
+            bandwidth_requests: BlockBandwidthRequests::empty(),
+            trace_host_calls,
+            profile_wasm_functions: false,
+        };
+        self.runtime.simulate_transaction(
+            &apply_state,
+            tries,
+            signed_tx,
+            check_signature,
+            self.trie_viewer.max_gas_burnt_view(),
+            self.epoch_manager.as_ref(),
+        )
+    }
+
     // Wrapper to get the metrics.
     fn obtain_state_part(
         &self,
@@ -1269,6 +1464,16 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         self.trie_viewer.view_contract_code(&state_update, account_id)
     }
 
+    fn view_global_contract_code(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        identifier: &GlobalContractIdentifier,
+    ) -> Result<ContractCode, node_runtime::state_viewer::errors::ViewContractCodeError> {
+        let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
+        self.trie_viewer.view_global_contract_code(&state_update, identifier)
+    }
+
     fn call_function(
         &self,
         shard_uid: &ShardUId,
@@ -1283,6 +1488,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        host_calls: Option<&mut Vec<HostFunctionCallView>>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, node_runtime::state_viewer::errors::CallFunctionError> {
@@ -1305,6 +1511,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             method_name,
             args,
             logs,
+            host_calls,
             epoch_info_provider,
         )
     }
@@ -18,7 +18,7 @@ use near_primitives::account::{AccessKey, Account, AccountContract};
 use near_primitives::apply::ApplyChunkReason;
 use near_primitives::bandwidth_scheduler::BandwidthRequests;
 use near_primitives::block::Tip;
-use near_primitives::chunk_apply_stats::ChunkApplyStatsV0;
+use near_primitives::chunk_apply_stats::ChunkApplyStatsV1;
 use near_primitives::congestion_info::{CongestionInfo, ExtendedCongestionInfo};
 use near_primitives::epoch_block_info::BlockInfo;
 use near_primitives::epoch_info::{EpochInfo, RngSeed};
@@ -51,7 +51,7 @@ use near_store::{
     set_genesis_height, set_genesis_state_roots,
 };
 use near_vm_runner::{ContractCode, ContractRuntimeCache, NoContractRuntimeCache};
-use node_runtime::SignedValidPeriodTransactions;
+use node_runtime::{SignedValidPeriodTransactions, SimulationError, SimulationResult};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, RwLock};
@@ -1207,7 +1207,9 @@ impl RuntimeAdapter for KeyValueRuntime {
             bandwidth_requests: BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
             bandwidth_scheduler_state_hash: CryptoHash::default(),
             contract_updates: Default::default(),
-            stats: ChunkApplyStatsV0::dummy(),
+            stats: ChunkApplyStatsV1::dummy(),
+            host_call_traces: vec![],
+            wasm_profiles: vec![],
         })
     }
 
@@ -1238,14 +1240,18 @@ impl RuntimeAdapter for KeyValueRuntime {
                 ),
                 block_height,
                 block_hash: *block_hash,
+                served_from: None,
             }),
-            QueryRequest::ViewCode { .. } => Ok(QueryResponse {
+            QueryRequest::ViewCode { .. }
+            | QueryRequest::ViewGlobalContractCode { .. }
+            | QueryRequest::ViewGlobalContractCodeByAccountId { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::ViewCode(ContractCodeView {
                     code: vec![],
                     hash: CryptoHash::default(),
                 }),
                 block_height,
                 block_hash: *block_hash,
+                served_from: None,
             }),
             QueryRequest::ViewAccessKeyList { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::AccessKeyList(AccessKeyList {
@@ -1256,11 +1262,13 @@ impl RuntimeAdapter for KeyValueRuntime {
                 }),
                 block_height,
                 block_hash: *block_hash,
+                served_from: None,
             }),
             QueryRequest::ViewAccessKey { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::AccessKey(AccessKey::full_access().into()),
                 block_height,
                 block_hash: *block_hash,
+                served_from: None,
             }),
             QueryRequest::ViewState { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::ViewState(ViewStateResult {
@@ -1269,18 +1277,32 @@ impl RuntimeAdapter for KeyValueRuntime {
                 }),
                 block_height,
                 block_hash: *block_hash,
+                served_from: None,
             }),
             QueryRequest::CallFunction { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::CallResult(CallResult {
                     result: Default::default(),
                     logs: Default::default(),
+                    host_calls: None,
                 }),
                 block_height,
                 block_hash: *block_hash,
+                served_from: None,
             }),
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        _prev_block: &BlockHeader,
+        _state_roots: HashMap<ShardId, StateRoot>,
+        _signed_tx: SignedTransaction,
+        _check_signature: bool,
+        _trace_host_calls: bool,
+    ) -> Result<SimulationResult, SimulationError> {
+        panic!("simulate_transaction not implemented for KeyValueRuntime");
+    }
+
     fn obtain_state_part(
         &self,
         _shard_id: ShardId,
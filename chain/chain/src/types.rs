@@ -12,7 +12,7 @@ use near_primitives::apply::ApplyChunkReason;
 use near_primitives::bandwidth_scheduler::BandwidthRequests;
 use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
 pub use near_primitives::block::{Block, BlockHeader, Tip};
-use near_primitives::chunk_apply_stats::ChunkApplyStatsV0;
+use near_primitives::chunk_apply_stats::ChunkApplyStatsV1;
 use near_primitives::congestion_info::BlockCongestionInfo;
 use near_primitives::congestion_info::CongestionInfo;
 use near_primitives::congestion_info::ExtendedCongestionInfo;
@@ -35,14 +35,16 @@ use near_primitives::types::{
 use near_primitives::utils::to_timestamp;
 use near_primitives::version::PROD_GENESIS_PROTOCOL_VERSION;
 use near_primitives::version::{MIN_GAS_PRICE_NEP_92_FIX, ProtocolVersion};
-use near_primitives::views::{QueryRequest, QueryResponse};
+use near_primitives::views::{QueryRequest, QueryResponse, ReceiptHostFunctionCallsView};
 use near_schema_checker_lib::ProtocolSchema;
 use near_store::flat::FlatStorageManager;
 use near_store::{PartialStorage, ShardTries, Store, Trie, WrappedTrieChanges};
 use near_vm_runner::ContractCode;
 use near_vm_runner::ContractRuntimeCache;
 use node_runtime::SignedValidPeriodTransactions;
+pub use node_runtime::{ReceiptWasmProfile, SimulationError, SimulationResult};
 use num_rational::Rational32;
+use std::collections::HashMap;
 use tracing::instrument;
 
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -114,7 +116,13 @@ pub struct ApplyChunkResult {
     /// Contracts accessed and deployed while applying the chunk.
     pub contract_updates: ContractUpdates,
     /// Extra information gathered during chunk application.
-    pub stats: ChunkApplyStatsV0,
+    pub stats: ChunkApplyStatsV1,
+    /// Host functions called by the executed receipts.  Empty unless
+    /// `ApplyChunkShardContext::trace_host_calls` is set.
+    pub host_call_traces: Vec<ReceiptHostFunctionCallsView>,
+    /// Gas burnt by the contract functions called by the executed receipts.
+    /// Empty unless the runtime was created with `profile_wasm_functions` set.
+    pub wasm_profiles: Vec<ReceiptWasmProfile>,
 }
 
 impl ApplyChunkResult {
@@ -320,6 +328,9 @@ pub struct ApplyChunkShardContext<'a> {
     pub gas_limit: Gas,
     pub is_new_chunk: bool,
     pub is_first_block_with_chunk_of_version: bool,
+    /// Whether to record host functions called by the contracts, see
+    /// `ApplyChunkResult::host_call_traces`.  Only used for debugging.
+    pub trace_host_calls: bool,
 }
 
 /// Contains transactions that were fetched from the transaction pool
@@ -468,6 +479,19 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Executes the transaction and all receipts it produces on top of the post
+    /// state of `prev_block` without persisting anything.  `state_roots` are
+    /// the post state roots of the shards tracked by the node.  The gas burnt
+    /// in total is limited like for view calls.
+    fn simulate_transaction(
+        &self,
+        prev_block: &BlockHeader,
+        state_roots: HashMap<ShardId, StateRoot>,
+        signed_tx: SignedTransaction,
+        check_signature: bool,
+        trace_host_calls: bool,
+    ) -> Result<SimulationResult, SimulationError>;
+
     /// Get part of the state corresponding to the given state root.
     /// `prev_hash` is a block whose post state root is `state_root`.
     /// Returns error when storage is inconsistent.
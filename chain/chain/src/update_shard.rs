@@ -153,6 +153,7 @@ pub fn apply_new_chunk(
             gas_limit,
             is_new_chunk: true,
             is_first_block_with_chunk_of_version,
+            trace_host_calls: false,
         },
         block,
         &receipts,
@@ -200,6 +201,7 @@ pub fn apply_old_chunk(
             gas_limit: prev_chunk_extra.gas_limit(),
             is_new_chunk: false,
             is_first_block_with_chunk_of_version: false,
+            trace_host_calls: false,
         },
         block,
         &[],
@@ -1,9 +1,15 @@
 use actix::Message;
 use itertools::Itertools;
-use near_pool::types::TransactionGroupIterator;
+use near_chain_configs::TransactionPoolConfig;
+use near_pool::types::{DropReason, TransactionGroupIterator};
 use near_pool::{InsertTransactionResult, PoolIteratorWrapper, TransactionPool};
+use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::{ShardLayout, ShardUId};
 use near_primitives::transaction::{SignedTransaction, ValidatedTransaction};
+use near_primitives::views::{
+    DroppedTransactionView, MempoolTransactionView, MempoolView, ShardMempoolView,
+    TransactionDropReasonView,
+};
 use near_primitives::{
     epoch_info::RngSeed,
     sharding::{EncodedShardChunk, PartialEncodedChunk, ShardChunk, ShardChunkHeader},
@@ -39,14 +45,22 @@ pub struct ShardedTransactionPool {
     /// while keeping the security of randomization of transactions in pool
     rng_seed: RngSeed,
 
-    /// If set, new transactions that bring the size of the pool over this limit will be rejected.
+    /// If set, new transactions that bring the size of the pool over this limit will evict
+    /// transactions with lower or equal priority or will be rejected.
     /// The size is tracked and enforced separately for each shard.
     pool_size_limit: Option<u64>,
+
+    /// Per-account quotas and ordering of the pool of each shard.
+    pool_config: TransactionPoolConfig,
 }
 
 impl ShardedTransactionPool {
-    pub fn new(rng_seed: RngSeed, pool_size_limit: Option<u64>) -> Self {
-        Self { tx_pools: HashMap::new(), rng_seed, pool_size_limit }
+    pub fn new(
+        rng_seed: RngSeed,
+        pool_size_limit: Option<u64>,
+        pool_config: TransactionPoolConfig,
+    ) -> Self {
+        Self { tx_pools: HashMap::new(), rng_seed, pool_size_limit, pool_config }
     }
 
     pub fn get_pool_iterator(&mut self, shard_uid: ShardUId) -> Option<PoolIteratorWrapper<'_>> {
@@ -85,6 +99,7 @@ impl ShardedTransactionPool {
             TransactionPool::new(
                 Self::random_seed(&self.rng_seed, shard_uid.shard_id()),
                 self.pool_size_limit,
+                self.pool_config.clone(),
                 &shard_uid.to_string(),
             )
         })
@@ -98,6 +113,107 @@ impl ShardedTransactionPool {
             .join("; ")
     }
 
+    /// Lists the transactions in the pools of all shards, optionally only the ones signed by the
+    /// given account.
+    ///
+    /// Transactions are listed in the order of their hashes, at most `limit` of them and only the
+    /// ones with a hash greater than `after`. If more transactions are left, the returned
+    /// `next_cursor` is the `after` of the next page. Dropped transactions are only listed on the
+    /// first page.
+    pub fn mempool_view(
+        &self,
+        signer_id: Option<&AccountId>,
+        after: Option<CryptoHash>,
+        limit: usize,
+    ) -> MempoolView {
+        let is_selected = |tx_signer_id: &AccountId| signer_id.is_none_or(|id| id == tx_signer_id);
+        let mut selected: Vec<_> = self
+            .tx_pools
+            .iter()
+            .flat_map(|(shard_uid, pool)| {
+                pool.transactions().map(|tx| (tx.get_hash(), shard_uid.shard_id(), tx))
+            })
+            .filter(|(hash, _, tx)| {
+                after.is_none_or(|after| *hash > after) && is_selected(tx.signer_id())
+            })
+            .collect();
+        selected.sort_unstable_by_key(|(hash, _, _)| *hash);
+        let next_cursor = if selected.len() > limit {
+            selected.truncate(limit);
+            selected.last().map(|(hash, _, _)| *hash).or(after)
+        } else {
+            None
+        };
+        let mut transactions: HashMap<ShardId, Vec<MempoolTransactionView>> = HashMap::new();
+        for (hash, shard_id, tx) in selected {
+            transactions.entry(shard_id).or_default().push(MempoolTransactionView {
+                hash,
+                signer_id: tx.signer_id().clone(),
+                public_key: tx.public_key().clone(),
+                receiver_id: tx.receiver_id().clone(),
+                nonce: tx.nonce(),
+                size: tx.get_size(),
+            });
+        }
+
+        let mut shards: Vec<_> = self
+            .tx_pools
+            .iter()
+            .map(|(shard_uid, pool)| ShardMempoolView {
+                shard_id: shard_uid.shard_id(),
+                total_count: pool.len() as u64,
+                total_size: pool.transaction_size(),
+                transactions: transactions.remove(&shard_uid.shard_id()).unwrap_or_default(),
+                dropped_transactions: pool
+                    .dropped_transactions()
+                    .filter(|tx| after.is_none() && is_selected(&tx.signer_id))
+                    .map(|tx| DroppedTransactionView {
+                        transaction: MempoolTransactionView {
+                            hash: tx.hash,
+                            signer_id: tx.signer_id.clone(),
+                            public_key: tx.public_key.clone(),
+                            receiver_id: tx.receiver_id.clone(),
+                            nonce: tx.nonce,
+                            size: tx.size,
+                        },
+                        reason: match tx.reason {
+                            DropReason::Evicted => TransactionDropReasonView::Evicted,
+                            DropReason::NoSpaceLeft => TransactionDropReasonView::NoSpaceLeft,
+                            DropReason::AccountQuotaExceeded => {
+                                TransactionDropReasonView::AccountQuotaExceeded
+                            }
+                        },
+                    })
+                    .collect(),
+            })
+            .collect();
+        shards.sort_by_key(|shard| shard.shard_id);
+        MempoolView { shards, next_cursor }
+    }
+
+    /// Returns the transactions in the pools of all shards.
+    pub fn all_transactions(&self) -> Vec<(ShardUId, ValidatedTransaction)> {
+        self.tx_pools
+            .iter()
+            .flat_map(|(shard_uid, pool)| pool.transactions().map(|tx| (*shard_uid, tx.clone())))
+            .collect()
+    }
+
+    /// Replaces the transactions in the pools of all shards with the given ones.
+    pub fn replace_transactions(
+        &mut self,
+        validated_txs: impl IntoIterator<Item = (ShardUId, ValidatedTransaction)>,
+    ) {
+        for pool in self.tx_pools.values_mut() {
+            let signed_txs: Vec<SignedTransaction> =
+                pool.transactions().map(|tx| tx.to_signed_tx().clone()).collect();
+            pool.remove_transactions(&signed_txs);
+        }
+        for (shard_uid, validated_tx) in validated_txs {
+            self.insert_transaction(shard_uid, validated_tx);
+        }
+    }
+
     /// Reintroduces transactions back during the chain reorg. Returns the number of transactions
     /// that were added or are already present in the pool.
     pub fn reintroduce_transactions(
@@ -110,7 +226,8 @@ impl ShardedTransactionPool {
         for validated_tx in validated_txs {
             reintroduced_count += match pool.insert_transaction(validated_tx) {
                 InsertTransactionResult::Success | InsertTransactionResult::Duplicate => 1,
-                InsertTransactionResult::NoSpaceLeft => 0,
+                InsertTransactionResult::NoSpaceLeft
+                | InsertTransactionResult::AccountQuotaExceeded => 0,
             }
         }
         reintroduced_count
@@ -152,6 +269,7 @@ impl ShardedTransactionPool {
 #[cfg(test)]
 mod tests {
     use crate::client::ShardedTransactionPool;
+    use near_chain_configs::TransactionPoolConfig;
     use near_crypto::{InMemorySigner, KeyType};
     use near_o11y::testonly::init_test_logger;
     use near_pool::types::TransactionGroupIterator;
@@ -193,7 +311,8 @@ mod tests {
         let old_shard_layout = ShardLayout::get_simple_nightshade_layout();
         let new_shard_layout = ShardLayout::get_simple_nightshade_layout_v2();
 
-        let mut pool = ShardedTransactionPool::new(TEST_SEED, None);
+        let mut pool =
+            ShardedTransactionPool::new(TEST_SEED, None, TransactionPoolConfig::default());
 
         let mut shard_id_to_accounts: HashMap<ShardId, _> = HashMap::new();
         shard_id_to_accounts.insert(ShardId::new(0), vec!["aaa", "abcd", "a-a-a-a-a"]);
@@ -276,4 +395,65 @@ mod tests {
         }
         tracing::info!("finished");
     }
+
+    #[test]
+    fn test_mempool_view() {
+        let mut pool =
+            ShardedTransactionPool::new(TEST_SEED, None, TransactionPoolConfig::default());
+        let shard_uid = ShardUId::new(0, ShardId::new(0));
+        for (signer_id, nonces) in [("alice.near", 1..=3), ("bob.near", 1..=2)] {
+            let signer_id = AccountId::from_str(signer_id).unwrap();
+            let signer = InMemorySigner::from_seed(signer_id.clone(), KeyType::ED25519, "seed");
+            for nonce in nonces {
+                let signed_tx = SignedTransaction::send_money(
+                    nonce,
+                    signer_id.clone(),
+                    signer_id.clone(),
+                    &signer,
+                    1,
+                    CryptoHash::default(),
+                );
+                pool.insert_transaction(shard_uid, ValidatedTransaction::new_for_test(signed_tx));
+            }
+        }
+
+        let view = pool.mempool_view(None, None, 10);
+        assert_eq!(view.shards.len(), 1);
+        assert_eq!(view.shards[0].shard_id, shard_uid.shard_id());
+        assert_eq!(view.shards[0].total_count, 5);
+        assert_eq!(view.shards[0].transactions.len(), 5);
+        assert_eq!(view.next_cursor, None);
+
+        // Pages list the transactions in the order of their hashes.
+        let mut hashes = vec![];
+        let mut cursor = None;
+        loop {
+            let view = pool.mempool_view(None, cursor, 2);
+            assert!(view.shards[0].transactions.len() <= 2);
+            hashes.extend(view.shards[0].transactions.iter().map(|tx| tx.hash));
+            cursor = view.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let mut expected_hashes: Vec<_> = pool.mempool_view(None, None, 10).shards[0]
+            .transactions
+            .iter()
+            .map(|tx| tx.hash)
+            .collect();
+        expected_hashes.sort();
+        assert_eq!(hashes, expected_hashes);
+
+        let alice = AccountId::from_str("alice.near").unwrap();
+        let view = pool.mempool_view(Some(&alice), None, 10);
+        assert_eq!(view.shards[0].total_count, 5);
+        let mut nonces: Vec<_> = view.shards[0]
+            .transactions
+            .iter()
+            .inspect(|tx| assert_eq!(tx.signer_id, alice))
+            .map(|tx| tx.nonce)
+            .collect();
+        nonces.sort();
+        assert_eq!(nonces, vec![1, 2, 3]);
+    }
 }
@@ -131,7 +131,7 @@ use near_primitives::types::{
 };
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::MaybeValidated;
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{ValidatorSigner, ValidatorSignerError};
 use near_primitives::version::ProtocolVersion;
 use near_store::adapter::StoreAdapter;
 use near_store::adapter::chunk_store::ChunkStoreAdapter;
@@ -1972,7 +1972,7 @@ impl ShardsManagerActor {
         signer: &ValidatorSigner,
         rs: &ReedSolomon,
         protocol_version: ProtocolVersion,
-    ) -> (EncodedShardChunk, Vec<MerklePath>) {
+    ) -> Result<(EncodedShardChunk, Vec<MerklePath>), ValidatorSignerError> {
         EncodedShardChunk::new(
             prev_block_hash,
             prev_state_root,
@@ -4,20 +4,22 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::network::PeerId;
 use near_primitives::sharding::{ChunkHash, ShardChunk};
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockReference, EpochId, EpochReference, MaybeBlockId, ShardId,
-    TransactionOrReceiptId,
+    StateChangeCause, StateChangeWithCause, TransactionOrReceiptId,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView, GasPriceView,
-    LightClientBlockLiteView, LightClientBlockView, MaintenanceWindowsView, QueryRequest,
-    QueryResponse, ReceiptView, SplitStorageInfoView, StateChangesKindsView,
-    StateChangesRequestView, StateChangesView, StateSyncStatusView, SyncStatusView, TxStatusView,
+    BlockView, ChunkBalanceAuditView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    GasPriceView, LightClientBlockLiteView, LightClientBlockView, MaintenanceWindowsView,
+    MempoolView, QueryRequest, QueryResponse, ReceiptView, SimulatedTransactionView,
+    SplitStorageInfoView, StateChangesKindsView, StateChangesRequestView, StateChangesView,
+    StateSyncStatusView, StorageSourceView, SyncStatusView, TxStatusView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 use near_time::Duration;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::debug_span;
 
@@ -360,6 +362,61 @@ impl From<near_chain_primitives::Error> for GetChunkError {
     }
 }
 
+/// Actor message requesting the balance audit of the chunk applied for the
+/// given shard in the given block.
+#[derive(Clone, Debug)]
+pub struct GetChunkBalanceAudit {
+    pub block_reference: BlockReference,
+    pub shard_id: ShardId,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetChunkBalanceAuditError {
+    #[error("IO Error: {error_message}")]
+    IOError { error_message: String },
+    #[error(
+        "Block either has never been observed on the node or has been garbage collected: {error_message}"
+    )]
+    UnknownBlock { error_message: String },
+    #[error("There are no fully synchronized blocks yet")]
+    NotSyncedYet,
+    #[error("Shard ID {shard_id} is invalid")]
+    InvalidShardId { shard_id: ShardId },
+    #[error(
+        "The balance audit of shard {shard_id} in block {block_hash} is not available on this node"
+    )]
+    UnavailableAudit { block_hash: CryptoHash, shard_id: ShardId },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error(
+        "It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}"
+    )]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetChunkBalanceAuditError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => {
+                Self::IOError { error_message: error.to_string() }
+            }
+            near_chain_primitives::Error::DBNotFoundErr(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            near_chain_primitives::Error::InvalidShardId(shard_id) => {
+                Self::InvalidShardId { shard_id }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+impl Message for GetChunkBalanceAudit {
+    type Result = Result<ChunkBalanceAuditView, GetChunkBalanceAuditError>;
+}
+
 /// Queries client for given path / data.
 #[derive(Clone, Debug)]
 pub struct Query {
@@ -403,6 +460,14 @@ pub enum QueryError {
         block_height: near_primitives::types::BlockHeight,
         block_hash: near_primitives::hash::CryptoHash,
     },
+    #[error(
+        "Global contract code {identifier:?} has never been observed on the node at block #{block_height}"
+    )]
+    NoGlobalContractCode {
+        identifier: near_primitives::action::GlobalContractIdentifier,
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    },
     #[error("State of contract {contract_account_id} is too large to be viewed")]
     TooLargeContractState {
         contract_account_id: near_primitives::types::AccountId,
@@ -728,8 +793,15 @@ impl From<near_chain_primitives::Error> for GetStateChangesError {
     }
 }
 
+pub struct GetStateChangesResponse {
+    pub changes: StateChangesView,
+    /// Store which served the changes.  Only set on archival nodes with split
+    /// storage.
+    pub served_from: Option<StorageSourceView>,
+}
+
 impl Message for GetStateChanges {
-    type Result = Result<StateChangesView, GetStateChangesError>;
+    type Result = Result<GetStateChangesResponse, GetStateChangesError>;
 }
 
 #[derive(Debug)]
@@ -754,6 +826,47 @@ impl Message for GetStateChangesWithCauseInBlock {
 pub struct GetStateChangesWithCauseInBlockForTrackedShards {
     pub block_hash: CryptoHash,
     pub epoch_id: EpochId,
+    /// If set, only the state changes kept by the filter are returned.
+    pub filter: Option<StateChangesFilter>,
+}
+
+/// Narrows down the state changes returned by
+/// [`GetStateChangesWithCauseInBlockForTrackedShards`] before they are
+/// converted into views.
+///
+/// A state change is kept if it was caused by one of the `causes`
+/// transactions or receipts or if `accounts` accepts the affected account.
+pub struct StateChangesFilter {
+    pub causes: HashSet<CryptoHash>,
+    pub accounts: Box<dyn Fn(&AccountId) -> bool + Send>,
+}
+
+impl StateChangesFilter {
+    pub fn keeps(&self, state_change: &StateChangeWithCause) -> bool {
+        let cause_hash = match &state_change.cause {
+            StateChangeCause::TransactionProcessing { tx_hash } => Some(tx_hash),
+            StateChangeCause::ActionReceiptProcessingStarted { receipt_hash }
+            | StateChangeCause::ActionReceiptGasReward { receipt_hash }
+            | StateChangeCause::ReceiptProcessing { receipt_hash }
+            | StateChangeCause::PostponedReceipt { receipt_hash } => Some(receipt_hash),
+            StateChangeCause::NotWritableToDisk
+            | StateChangeCause::InitialState
+            | StateChangeCause::UpdatedDelayedReceipts
+            | StateChangeCause::ValidatorAccountsUpdate
+            | StateChangeCause::Migration
+            | StateChangeCause::BandwidthSchedulerStateUpdate => None,
+        };
+        cause_hash.is_some_and(|hash| self.causes.contains(hash))
+            || (self.accounts)(state_change.value.affected_account_id())
+    }
+}
+
+impl std::fmt::Debug for StateChangesFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateChangesFilter")
+            .field("causes", &self.causes.len())
+            .finish_non_exhaustive()
+    }
 }
 
 impl Message for GetStateChangesWithCauseInBlockForTrackedShards {
@@ -923,6 +1036,48 @@ impl Message for GetReceipt {
     type Result = Result<Option<ReceiptView>, GetReceiptError>;
 }
 
+/// Simulates the transaction on top of the state at the chain head.
+#[derive(Debug)]
+pub struct SimulateTransaction {
+    pub signed_transaction: SignedTransaction,
+    pub check_signature: bool,
+    /// Whether to record the host functions called by the contracts.
+    pub trace_host_calls: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulateTransactionError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error("Invalid transaction: {0:?}")]
+    InvalidTransaction(near_primitives::errors::InvalidTxError),
+    #[error("The node does not track the shard ID {0}")]
+    UnavailableShard(ShardId),
+    #[error("Simulation failed: {0}")]
+    SimulationFailed(String),
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error(
+        "It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {0}"
+    )]
+    Unreachable(String),
+}
+
+impl From<near_chain_primitives::Error> for SimulateTransactionError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => Self::IOError(error.to_string()),
+            _ => Self::Unreachable(error.to_string()),
+        }
+    }
+}
+
+impl Message for SimulateTransaction {
+    type Result = Result<SimulatedTransactionView, SimulateTransactionError>;
+}
+
 #[derive(Debug)]
 pub struct GetProtocolConfig(pub BlockReference);
 
@@ -1014,6 +1169,22 @@ impl From<near_chain_primitives::Error> for GetClientConfigError {
     }
 }
 
+/// Lists the transactions in the transaction pools of the node.
+#[derive(Debug)]
+pub struct GetMempool {
+    /// If set, only the transactions signed by this account are listed.
+    pub signer_id: Option<AccountId>,
+    /// If set, only the transactions with a greater hash are listed. Used to page through the
+    /// pools with the `next_cursor` of the previous response.
+    pub after: Option<CryptoHash>,
+    /// Maximum number of listed transactions.
+    pub limit: usize,
+}
+
+impl Message for GetMempool {
+    type Result = MempoolView;
+}
+
 #[derive(Debug)]
 pub struct GetSplitStorageInfo {}
 
@@ -1057,6 +1228,11 @@ pub enum SandboxMessage {
     SandboxPatchStateStatus,
     SandboxFastForward(near_primitives::types::BlockHeightDelta),
     SandboxFastForwardStatus,
+    SandboxSnapshot,
+    /// Reverts to the snapshot with given id.  The revert happens before the
+    /// next block is produced, like with `SandboxFastForward`.
+    SandboxRevert(u64),
+    SandboxRevertStatus,
 }
 
 #[cfg(feature = "sandbox")]
@@ -1065,6 +1241,10 @@ pub enum SandboxResponse {
     SandboxPatchStateFinished(bool),
     SandboxFastForwardFinished(bool),
     SandboxFastForwardFailed(String),
+    SandboxSnapshotCreated(u64),
+    SandboxSnapshotFailed(String),
+    SandboxRevertFinished(bool),
+    SandboxRevertFailed(String),
     SandboxNoResponse,
 }
 #[cfg(feature = "sandbox")]
@@ -6,7 +6,7 @@ use near_chain::types::{
     PrepareTransactionsChunkContext, PreparedTransactions, RuntimeAdapter, RuntimeStorageConfig,
 };
 use near_chain::{Block, Chain, ChainStore};
-use near_chain_configs::MutableConfigValue;
+use near_chain_configs::{MutableConfigValue, TransactionPoolConfig};
 use near_chunks::client::ShardedTransactionPool;
 use near_chunks::shards_manager_actor::ShardsManagerActor;
 use near_client_primitives::debug::ChunkProduction;
@@ -85,6 +85,7 @@ impl ChunkProducer {
         runtime_adapter: Arc<dyn RuntimeAdapter>,
         rng_seed: RngSeed,
         transaction_pool_size_limit: Option<u64>,
+        transaction_pool_config: TransactionPoolConfig,
     ) -> Self {
         let data_parts = epoch_manager.num_data_parts();
         let parity_parts = epoch_manager.num_total_parts() - data_parts;
@@ -104,6 +105,7 @@ impl ChunkProducer {
             sharded_tx_pool: Arc::new(Mutex::new(ShardedTransactionPool::new(
                 rng_seed,
                 transaction_pool_size_limit,
+                transaction_pool_config,
             ))),
             reed_solomon_encoder: ReedSolomon::new(data_parts, parity_parts).unwrap(),
             chunk_production_info: lru::LruCache::new(
@@ -308,7 +310,8 @@ impl ChunkProducer {
             &*validator_signer,
             &mut self.reed_solomon_encoder,
             protocol_version,
-        );
+        )
+        .map_err(|err| Error::ChunkProducer(format!("Failed to sign chunk header: {err}")))?;
 
         span.record("chunk_hash", tracing::field::debug(encoded_chunk.chunk_hash()));
         debug!(target: "client",
@@ -115,6 +115,9 @@ pub struct Client {
     /// Fast Forward accrued delta height used to calculate fast forwarded timestamps for each block.
     #[cfg(feature = "sandbox")]
     pub(crate) accrued_fastforward_delta: near_primitives::types::BlockHeightDelta,
+    /// Snapshots created with `sandbox_snapshot`, indexed by their id.
+    #[cfg(feature = "sandbox")]
+    pub(crate) sandbox_snapshots: Vec<crate::sandbox::SandboxSnapshot>,
 
     pub clock: Clock,
     pub config: ClientConfig,
@@ -313,6 +316,7 @@ impl Client {
             runtime_adapter.clone(),
             rng_seed,
             config.transaction_pool_size_limit,
+            config.transaction_pool.clone(),
         );
         let chunk_validator = ChunkValidator::new(
             epoch_manager.clone(),
@@ -327,6 +331,8 @@ impl Client {
             adv_produce_blocks: None,
             #[cfg(feature = "sandbox")]
             accrued_fastforward_delta: 0,
+            #[cfg(feature = "sandbox")]
+            sandbox_snapshots: vec![],
             clock: clock.clone(),
             config: config.clone(),
             chain,
@@ -661,7 +667,8 @@ impl Client {
             &*validator_signer,
             self.clock.clone(),
             sandbox_delta_time,
-        );
+        )
+        .map_err(|err| Error::BlockProducer(err.to_string()))?;
 
         metrics::OPTIMISTIC_BLOCK_PRODUCED_TOTAL.inc();
 
@@ -920,7 +927,8 @@ impl Client {
             self.clock.clone(),
             sandbox_delta_time,
             optimistic_block,
-        );
+        )
+        .map_err(|err| Error::BlockProducer(err.to_string()))?;
 
         // Update latest known even before returning block out, to prevent race conditions.
         self.chain
@@ -939,7 +947,13 @@ impl Client {
     ) {
         if let Some(validator_signer) = &signer {
             for body in challenges {
-                let challenge = Challenge::produce(body, &**validator_signer);
+                let challenge = match Challenge::produce(body, &**validator_signer) {
+                    Ok(challenge) => challenge,
+                    Err(err) => {
+                        error!(target: "client", ?err, "Failed to sign challenge");
+                        continue;
+                    }
+                };
                 self.challenges.insert(challenge.hash, challenge.clone());
                 self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
                     NetworkRequests::Challenge(challenge),
@@ -1199,26 +1213,23 @@ impl Client {
 
         // Send out challenge if the block was found to be invalid.
         if let Some(signer) = signer {
-            if let Err(e) = &result {
-                match e {
-                    near_chain::Error::InvalidChunkProofs(chunk_proofs) => {
-                        self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-                            NetworkRequests::Challenge(Challenge::produce(
-                                ChallengeBody::ChunkProofs(*chunk_proofs.clone()),
-                                &*signer,
-                            )),
-                        ));
-                    }
-                    near_chain::Error::InvalidChunkState(chunk_state) => {
-                        self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-                            NetworkRequests::Challenge(Challenge::produce(
-                                ChallengeBody::ChunkState(*chunk_state.clone()),
-                                &*signer,
-                            )),
-                        ));
-                    }
-                    _ => {}
+            let body = match &result {
+                Err(near_chain::Error::InvalidChunkProofs(chunk_proofs)) => {
+                    Some(ChallengeBody::ChunkProofs(*chunk_proofs.clone()))
+                }
+                Err(near_chain::Error::InvalidChunkState(chunk_state)) => {
+                    Some(ChallengeBody::ChunkState(*chunk_state.clone()))
+                }
+                _ => None,
+            };
+            match body.map(|body| Challenge::produce(body, &*signer)) {
+                Some(Ok(challenge)) => {
+                    self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
+                        NetworkRequests::Challenge(challenge),
+                    ));
                 }
+                Some(Err(err)) => error!(target: "client", ?err, "Failed to sign challenge"),
+                None => {}
             }
         }
 
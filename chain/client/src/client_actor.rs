@@ -256,6 +256,12 @@ pub struct ClientActorInner {
 
     #[cfg(feature = "sandbox")]
     fastforward_delta: near_primitives::types::BlockHeightDelta,
+    /// Sandbox snapshot to revert to before producing the next block.
+    #[cfg(feature = "sandbox")]
+    sandbox_pending_revert: Option<u64>,
+    /// Error of the last failed sandbox revert, reported by the next status request.
+    #[cfg(feature = "sandbox")]
+    sandbox_revert_error: Option<String>,
 
     /// Synchronization measure to allow graceful shutdown.
     /// Informs the system when a ClientActor gets dropped.
@@ -371,6 +377,10 @@ impl ClientActorInner {
             sync_started: false,
             #[cfg(feature = "sandbox")]
             fastforward_delta: 0,
+            #[cfg(feature = "sandbox")]
+            sandbox_pending_revert: None,
+            #[cfg(feature = "sandbox")]
+            sandbox_revert_error: None,
             shutdown_signal,
             config_updater,
             sync_jobs_sender,
@@ -669,6 +679,45 @@ impl Handler<near_client_primitives::types::SandboxMessage> for ClientActorInner
                     self.fastforward_delta == 0,
                 )
             }
+            near_client_primitives::types::SandboxMessage::SandboxSnapshot => {
+                match self.client.sandbox_snapshot() {
+                    Ok(snapshot_id) => {
+                        near_client_primitives::types::SandboxResponse::SandboxSnapshotCreated(
+                            snapshot_id,
+                        )
+                    }
+                    Err(err) => {
+                        near_client_primitives::types::SandboxResponse::SandboxSnapshotFailed(
+                            err.to_string(),
+                        )
+                    }
+                }
+            }
+            near_client_primitives::types::SandboxMessage::SandboxRevert(snapshot_id) => {
+                if self.sandbox_pending_revert.is_some() {
+                    return near_client_primitives::types::SandboxResponse::SandboxRevertFailed(
+                        "Consecutive revert requests cannot be made while a current one is going on.".to_string());
+                }
+                if !self.client.sandbox_has_snapshot(snapshot_id) {
+                    return near_client_primitives::types::SandboxResponse::SandboxRevertFailed(
+                        format!("unknown sandbox snapshot {snapshot_id}"),
+                    );
+                }
+
+                self.sandbox_pending_revert = Some(snapshot_id);
+                self.sandbox_revert_error = None;
+                near_client_primitives::types::SandboxResponse::SandboxNoResponse
+            }
+            near_client_primitives::types::SandboxMessage::SandboxRevertStatus => {
+                if let Some(err) = self.sandbox_revert_error.take() {
+                    return near_client_primitives::types::SandboxResponse::SandboxRevertFailed(
+                        err,
+                    );
+                }
+                near_client_primitives::types::SandboxResponse::SandboxRevertFinished(
+                    self.sandbox_pending_revert.is_none(),
+                )
+            }
         }
     }
 }
@@ -951,11 +1000,14 @@ impl ClientActorInner {
             debug!(target: "client", "Sending announce account for {}", signer.validator_id());
             self.last_validator_announce_time = Some(now);
 
-            let announce_account =
-                AnnounceAccount::new(signer.as_ref(), self.node_id.clone(), next_epoch_id);
-            self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-                NetworkRequests::AnnounceAccount(announce_account),
-            ));
+            match AnnounceAccount::new(signer.as_ref(), self.node_id.clone(), next_epoch_id) {
+                Ok(announce_account) => {
+                    self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
+                        NetworkRequests::AnnounceAccount(announce_account),
+                    ));
+                }
+                Err(err) => error!(target: "client", ?err, "Failed to sign account announcement"),
+            }
         }
     }
 
@@ -1013,6 +1065,14 @@ impl ClientActorInner {
     }
 
     fn pre_block_production(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "sandbox")]
+        if self.client.chain.blocks_in_processing_len() == 0 {
+            if let Some(snapshot_id) = self.sandbox_pending_revert.take() {
+                if let Err(err) = self.client.sandbox_revert(snapshot_id) {
+                    self.sandbox_revert_error = Some(err.to_string());
+                }
+            }
+        }
         #[cfg(feature = "sandbox")]
         {
             let latest_known = self.client.chain.mut_chain_store().get_latest_known()?;
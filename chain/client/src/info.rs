@@ -19,7 +19,7 @@ use near_primitives::types::{
     ValidatorInfoIdentifier,
 };
 use near_primitives::unwrap_or_return;
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{ValidatorMessage, ValidatorSigner};
 use near_primitives::version::{PROTOCOL_VERSION, Version};
 use near_primitives::views::{
     CatchupStatusView, ChunkProcessingStatus, CurrentEpochValidatorInfo, EpochValidatorInfo,
@@ -618,7 +618,10 @@ impl InfoHelper {
         // Sign telemetry if there is a signer present.
         if let Some(signer) = signer {
             let content = serde_json::to_string(&json).expect("Telemetry must serialize to JSON");
-            json["signature"] = signer.sign_bytes(content.as_bytes()).to_string().into();
+            match signer.sign_message(&ValidatorMessage::Telemetry { content }) {
+                Ok(signature) => json["signature"] = signature.to_string().into(),
+                Err(err) => tracing::warn!(target: "stats", ?err, "Failed to sign telemetry"),
+            }
         }
         json
     }
@@ -1,11 +1,13 @@
 pub use near_client_primitives::types::{
     Error, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk,
-    GetClientConfig, GetExecutionOutcome, GetExecutionOutcomeResponse,
-    GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
-    GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetShardChunk, GetSplitStorageInfo,
-    GetStateChanges, GetStateChangesInBlock, GetStateChangesWithCauseInBlock,
+    GetChunkBalanceAudit, GetChunkBalanceAuditError, GetClientConfig, GetExecutionOutcome,
+    GetExecutionOutcomeResponse, GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows,
+    GetMempool, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt,
+    GetShardChunk, GetSplitStorageInfo, GetStateChanges, GetStateChangesInBlock,
+    GetStateChangesResponse, GetStateChangesWithCauseInBlock,
     GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfo, GetValidatorOrdered, Query,
-    QueryError, Status, StatusResponse, SyncStatus, TxStatus, TxStatusError,
+    QueryError, SimulateTransaction, StateChangesFilter, Status, StatusResponse, SyncStatus,
+    TxStatus, TxStatusError,
 };
 
 pub use crate::client::Client;
@@ -17,7 +19,7 @@ pub use crate::stateless_validation::chunk_validator::orphan_witness_handling::H
 pub use crate::tx_request_handler::{
     TxRequestHandler, TxRequestHandlerActor, TxRequestHandlerConfig, spawn_tx_request_handler_actor,
 };
-pub use crate::view_client_actor::{ViewClientActor, ViewClientActorInner};
+pub use crate::view_client_actor::{ColdStoreFallback, ViewClientActor, ViewClientActorInner};
 pub use chunk_producer::ProduceChunkResult;
 pub use near_chain::stateless_validation::processing_tracker::{
     ProcessingDoneTracker, ProcessingDoneWaiter,
@@ -42,6 +44,8 @@ pub mod debug;
 pub mod gc_actor;
 mod info;
 pub mod metrics;
+#[cfg(feature = "sandbox")]
+mod sandbox;
 mod stateless_validation;
 pub mod sync;
 pub mod sync_jobs_actor;
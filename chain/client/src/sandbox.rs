@@ -0,0 +1,87 @@
+//! Snapshots of the sandbox node.
+//!
+//! A snapshot is a copy of the whole store together with the contents of the
+//! transaction pool.  Reverting to a snapshot writes the copy back, which rolls
+//! back the chain head, and reloads flat storage and memtries from it.  Block
+//! production carries on from the snapshot head at heights above the ones
+//! already produced, so the discarded blocks are never produced again.
+//!
+//! The store is copied into memory, which is only reasonable for the small
+//! databases of sandbox nodes.
+use crate::Client;
+use crate::chunk_inclusion_tracker::ChunkInclusionTracker;
+use near_async::messaging::CanSend;
+use near_chain::{ChainStoreAccess, Error};
+use near_chunks::adapter::ShardsManagerRequestFromClient;
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::transaction::ValidatedTransaction;
+use near_store::StoreContents;
+
+/// State of the node captured by `Client::sandbox_snapshot`.
+pub(crate) struct SandboxSnapshot {
+    block_hash: CryptoHash,
+    contents: StoreContents,
+    transactions: Vec<(ShardUId, ValidatedTransaction)>,
+}
+
+impl Client {
+    /// Captures the store and the transactions in the pool.  Returns the id of
+    /// the snapshot.
+    pub fn sandbox_snapshot(&mut self) -> Result<u64, Error> {
+        let block_hash = self.chain.head()?.last_block_hash;
+        let contents = self.chain.chain_store().store().read_contents()?;
+        let transactions = self.chunk_producer.sharded_tx_pool.lock().unwrap().all_transactions();
+        self.sandbox_snapshots.push(SandboxSnapshot { block_hash, contents, transactions });
+        let snapshot_id = self.sandbox_snapshots.len() as u64 - 1;
+        tracing::debug!(target: "client", snapshot_id, ?block_hash, "created sandbox snapshot");
+        Ok(snapshot_id)
+    }
+
+    /// Returns whether a snapshot with the given id exists.
+    pub fn sandbox_has_snapshot(&self, snapshot_id: u64) -> bool {
+        (snapshot_id as usize) < self.sandbox_snapshots.len()
+    }
+
+    /// Reverts the node to the given snapshot.  The snapshot is kept, so it is
+    /// possible to revert to it again later.
+    ///
+    /// Must not be called while blocks are being processed.  Reverting across
+    /// a change of the shard layout is not supported.
+    pub fn sandbox_revert(&mut self, snapshot_id: u64) -> Result<(), Error> {
+        let Some(snapshot) = self.sandbox_snapshots.get(snapshot_id as usize) else {
+            return Err(Error::Other(format!("unknown sandbox snapshot {snapshot_id}")));
+        };
+        let head = self.chain.head()?;
+        let snapshot_epoch_id = self.epoch_manager.get_epoch_id(&snapshot.block_hash)?;
+        if self.epoch_manager.get_shard_layout(&snapshot_epoch_id)?
+            != self.epoch_manager.get_shard_layout(&head.epoch_id)?
+        {
+            return Err(Error::Other(
+                "cannot revert to a snapshot with a different shard layout".to_string(),
+            ));
+        }
+        tracing::debug!(target: "client", snapshot_id, snapshot_block_hash = ?snapshot.block_hash, head = ?head.last_block_hash, "reverting to sandbox snapshot");
+
+        self.chain.restore_store_contents(&snapshot.contents)?;
+        self.chunk_producer
+            .sharded_tx_pool
+            .lock()
+            .unwrap()
+            .replace_transactions(snapshot.transactions.iter().cloned());
+        // Chunks ready for inclusion may build on the discarded blocks.
+        self.chunk_inclusion_tracker = ChunkInclusionTracker::new();
+
+        // Move past the heights of the discarded blocks, like fast forward does.
+        let mut latest_known = self.chain.chain_store().get_latest_known()?;
+        latest_known.height += 1;
+        self.chain.mut_chain_store().save_latest_known(latest_known.clone())?;
+        self.sandbox_update_tip(latest_known.height)?;
+
+        let head = self.chain.head()?;
+        let header_head = self.chain.header_head()?;
+        self.shards_manager_adapter
+            .send(ShardsManagerRequestFromClient::UpdateChainHeads { head, header_head });
+        Ok(())
+    }
+}
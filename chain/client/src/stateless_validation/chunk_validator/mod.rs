@@ -225,7 +225,13 @@ pub(crate) fn send_chunk_endorsement_to_block_producers(
         "send_chunk_endorsement",
     );
 
-    let endorsement = ChunkEndorsement::new(epoch_id, chunk_header, signer);
+    let endorsement = match ChunkEndorsement::try_new(epoch_id, chunk_header, signer) {
+        Ok(endorsement) => endorsement,
+        Err(err) => {
+            tracing::error!(target: "client", ?chunk_hash, ?err, "Failed to sign chunk endorsement");
+            return None;
+        }
+    };
     let mut send_to_itself = None;
     for block_producer in block_producers {
         if &block_producer == signer.validator_id() {
@@ -35,7 +35,7 @@ use near_primitives::stateless_validation::state_witness::{
 };
 use near_primitives::stateless_validation::stored_chunk_state_transition_data::StoredChunkStateTransitionData;
 use near_primitives::types::{AccountId, EpochId, ShardId};
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{ValidatorSigner, ValidatorSignerError};
 use near_store::adapter::trie_store::TrieStoreAdapter;
 use near_store::{DBCol, StorageError, TrieDBStorage, TrieStorage};
 use near_vm_runner::{ContractCode, ContractRuntimeCache, get_contract_cache_key};
@@ -259,7 +259,7 @@ impl PartialWitnessActor {
         witness_bytes: EncodedChunkStateWitness,
         chunk_validators: &[AccountId],
         signer: &ValidatorSigner,
-    ) -> Vec<(AccountId, PartialEncodedStateWitness)> {
+    ) -> Result<Vec<(AccountId, PartialEncodedStateWitness)>, ValidatorSignerError> {
         tracing::debug!(
             target: "client",
             chunk_hash=?chunk_header.chunk_hash(),
@@ -271,24 +271,20 @@ impl PartialWitnessActor {
         let encoder = self.witness_encoders.entry(chunk_validators.len());
         let (parts, encoded_length) = encoder.encode(&witness_bytes);
 
-        chunk_validators
-            .iter()
-            .zip_eq(parts)
+        // It's fine to unwrap part here as we just constructed the parts above and we expect
+        // all of them to be present.
+        let parts = parts
+            .into_iter()
             .enumerate()
-            .map(|(part_ord, (chunk_validator, part))| {
-                // It's fine to unwrap part here as we just constructed the parts above and we expect
-                // all of them to be present.
-                let partial_witness = PartialEncodedStateWitness::new(
-                    epoch_id,
-                    chunk_header.clone(),
-                    part_ord,
-                    part.unwrap().to_vec(),
-                    encoded_length,
-                    signer,
-                );
-                (chunk_validator.clone(), partial_witness)
-            })
-            .collect_vec()
+            .map(|(part_ord, part)| (part_ord, part.unwrap().to_vec()));
+        let partial_witnesses = PartialEncodedStateWitness::try_new_parts(
+            epoch_id,
+            chunk_header,
+            parts.collect(),
+            encoded_length,
+            signer,
+        )?;
+        Ok(chunk_validators.iter().cloned().zip_eq(partial_witnesses).collect())
     }
 
     fn generate_contract_deploys_parts(
@@ -307,7 +303,7 @@ impl PartialWitnessActor {
         let (parts, encoded_length) = encoder.encode(&deploys);
         let signer = self.my_validator_signer()?;
 
-        Ok(validators
+        validators
             .into_iter()
             .zip_eq(parts)
             .enumerate()
@@ -320,10 +316,11 @@ impl PartialWitnessActor {
                         encoded_length,
                     },
                     &signer,
-                );
-                (validator, partial_deploys)
+                )
+                .map_err(|err| Error::ValidatorError(err.to_string()))?;
+                Ok((validator, partial_deploys))
             })
-            .collect_vec())
+            .collect()
     }
 
     // Break the state witness into parts and send each part to the corresponding chunk validator owner.
@@ -346,13 +343,19 @@ impl PartialWitnessActor {
         let encode_timer = metrics::PARTIAL_WITNESS_ENCODE_TIME
             .with_label_values(&[shard_id_label.as_str()])
             .start_timer();
-        let validator_witness_tuple = self.generate_state_witness_parts(
+        let validator_witness_tuple = match self.generate_state_witness_parts(
             epoch_id,
             chunk_header,
             witness_bytes,
             chunk_validators,
             signer,
-        );
+        ) {
+            Ok(validator_witness_tuple) => validator_witness_tuple,
+            Err(err) => {
+                tracing::error!(target: "client", ?chunk_hash, ?err, "Failed to sign state witness parts");
+                return;
+            }
+        };
         encode_timer.observe_duration();
 
         // Record the witness in order to match the incoming acks for measuring round-trip times.
@@ -637,7 +640,8 @@ impl PartialWitnessActor {
             missing_contract_hashes,
             accesses.main_transition().clone(),
             &signer,
-        );
+        )
+        .map_err(|err| Error::ValidatorError(err.to_string()))?;
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
             NetworkRequests::ContractCodeRequest(random_chunk_producer, request),
         ));
@@ -668,11 +672,16 @@ impl PartialWitnessActor {
             .filter(|validator| !chunk_producers.contains(*validator))
             .cloned()
             .collect();
+        let accesses =
+            match ChunkContractAccesses::new(key, contract_accesses, main_transition, my_signer) {
+                Ok(accesses) => accesses,
+                Err(err) => {
+                    tracing::error!(target: "client", ?err, "Failed to sign contract accesses");
+                    return;
+                }
+            };
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-            NetworkRequests::ChunkContractAccesses(
-                target_chunk_validators,
-                ChunkContractAccesses::new(key, contract_accesses, main_transition, my_signer),
-            ),
+            NetworkRequests::ChunkContractAccesses(target_chunk_validators, accesses),
         ));
     }
 
@@ -14,26 +14,22 @@ use near_network::types::{
     HighestHeightPeerInfo, NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest,
 };
 use near_performance_metrics_macros::perf;
-use near_primitives::block::{Approval, ApprovalInner, compute_bp_hash_from_validator_stakes};
 use near_primitives::epoch_block_info::BlockInfo;
-use near_primitives::epoch_info::EpochInfo;
 use near_primitives::epoch_sync::{
     CompressedEpochSyncProof, EpochSyncProof, EpochSyncProofCurrentEpochData,
     EpochSyncProofEpochData, EpochSyncProofLastEpochData, EpochSyncProofV1,
-    should_use_versioned_bp_hash_format,
+    get_epoch_info_block_producers, should_use_versioned_bp_hash_format,
 };
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
 use near_primitives::types::validator_stake::ValidatorStake;
-use near_primitives::types::{
-    AccountId, ApprovalStake, Balance, BlockHeight, BlockHeightDelta, EpochId,
-};
+use near_primitives::types::{AccountId, ApprovalStake, BlockHeight, BlockHeightDelta, EpochId};
 use near_primitives::utils::compression::CompressedData;
 use near_store::Store;
 use near_store::adapter::{StoreAdapter, StoreUpdateAdapter};
 use rand::seq::SliceRandom;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tracing::instrument;
 
@@ -380,9 +376,8 @@ impl EpochSync {
                     Error::Other(format!("Could not find epoch info for epoch {:?}", next_epoch_id))
                 })?;
 
-                let this_epoch_block_producers = Self::get_epoch_info_block_producers(epoch_info);
-                let next_epoch_block_producers =
-                    Self::get_epoch_info_block_producers(next_epoch_info);
+                let this_epoch_block_producers = get_epoch_info_block_producers(epoch_info);
+                let next_epoch_block_producers = get_epoch_info_block_producers(next_epoch_info);
                 let approvals_for_this_epoch_block_producers =
                     Self::get_approvals_for_this_epoch_block_producers(
                         &approvals_for_last_final_block,
@@ -393,7 +388,7 @@ impl EpochSync {
                     should_use_versioned_bp_hash_format(prev_epoch_info.protocol_version());
 
                 Ok(EpochSyncProofEpochData {
-                    block_producers: Self::get_epoch_info_block_producers(epoch_info),
+                    block_producers: get_epoch_info_block_producers(epoch_info),
                     use_versioned_bp_hash_format,
                     last_final_block_header,
                     this_epoch_endorsements_for_last_final_block:
@@ -404,21 +399,6 @@ impl EpochSync {
         Ok(epochs)
     }
 
-    /// Gets the ordered list of block producers and their stake from the EpochInfo.
-    fn get_epoch_info_block_producers(epoch_info: &EpochInfo) -> Vec<ValidatorStake> {
-        // The block producers settlement can actually have duplicates.
-        // The list of block producers used to compute bp_hash is the deduplicated version
-        // of this list, keeping the order of first appearance.
-        let mut block_producers = Vec::new();
-        let mut seen_validators = HashSet::new();
-        for bp_index in epoch_info.block_producers_settlement() {
-            if seen_validators.insert(*bp_index) {
-                block_producers.push(epoch_info.get_validator(*bp_index));
-            }
-        }
-        block_producers
-    }
-
     /// Gets the ordered list of signatures within the approvals list in a block that correspond
     /// to this epoch's block producers. The given block is expected to require both the current
     /// and the next epoch's signatures. The returned list has the exact same length as this
@@ -658,213 +638,23 @@ impl EpochSync {
         proof: &EpochSyncProofV1,
         epoch_manager: &dyn EpochManagerAdapter,
     ) -> Result<(), Error> {
-        let EpochSyncProofV1 { all_epochs, last_epoch, current_epoch } = proof;
-        if all_epochs.len() < 2 {
-            return Err(Error::InvalidEpochSyncProof(
-                "need at least two epochs in all_epochs".to_string(),
-            ));
-        }
+        let Some(first_epoch) = proof.all_epochs.first() else {
+            return Err(Error::InvalidEpochSyncProof("all_epochs is empty".to_string()));
+        };
 
         // Verify block producer handoff to the second epoch after genesis.
         let second_next_epoch_id_after_genesis = EpochId(*self.genesis.hash());
         let second_next_epoch_info_after_genesis =
             epoch_manager.get_epoch_info(&second_next_epoch_id_after_genesis)?;
-        if all_epochs[0].block_producers
-            != Self::get_epoch_info_block_producers(&second_next_epoch_info_after_genesis)
+        if first_epoch.block_producers
+            != get_epoch_info_block_producers(&second_next_epoch_info_after_genesis)
         {
             return Err(Error::InvalidEpochSyncProof(
                 "invalid block producers for second epoch after genesis".to_string(),
             ));
         }
-        Self::verify_final_block_endorsement(&all_epochs[0])?;
-
-        // Verify the data of each epoch, in chronological order. When verifying each epoch,
-        // we assume that the previous epoch has been verified (thereby giving correctness of all
-        // epochs by induction.) For each epoch, we need to verify the following:
-        //
-        // - Its block producers. To verify this, we compare the previous epoch's last final block's
-        //   next_bp_hash against the hash of the current epoch's block producers, taking into
-        //   account the use_versioned_bp_hash_format flag.
-        // - Its last final block. To verify this, we use the endorsements provided for the final
-        //   block. What we verify is that more than 2/3 of the block producers of the current epoch
-        //   have endorsed the final block.
-        //
-        // See the comments in `EpochSyncProofEpochData` for more detailed information.
-        for epoch_index in 1..all_epochs.len() {
-            let epoch = &all_epochs[epoch_index];
-            let prev_epoch = &all_epochs[epoch_index - 1];
-            if !Self::verify_block_producer_handoff(
-                &epoch.block_producers,
-                epoch.use_versioned_bp_hash_format,
-                prev_epoch.last_final_block_header.next_bp_hash(),
-            )? {
-                return Err(Error::InvalidEpochSyncProof(format!(
-                    "invalid block producer handoff to epoch index {}",
-                    epoch_index
-                )));
-            }
-            Self::verify_final_block_endorsement(epoch)?;
-        }
 
-        Self::verify_epoch_sync_data_hash(&last_epoch, &current_epoch.first_block_header_in_epoch)?;
-
-        Self::verify_current_epoch_data(
-            current_epoch,
-            &all_epochs.last().unwrap().last_final_block_header,
-        )?;
-        Ok(())
-    }
-
-    fn verify_current_epoch_data(
-        current_epoch: &EpochSyncProofCurrentEpochData,
-        current_epoch_final_block_header: &BlockHeader,
-    ) -> Result<(), Error> {
-        // Verify first_block_header_in_epoch
-        let first_block_header = &current_epoch.first_block_header_in_epoch;
-        if !near_primitives::merkle::verify_hash(
-            *current_epoch_final_block_header.block_merkle_root(),
-            &current_epoch.merkle_proof_for_first_block,
-            *first_block_header.hash(),
-        ) {
-            return Err(Error::InvalidEpochSyncProof(
-                "invalid merkle_proof_for_first_block".to_string(),
-            ));
-        }
-
-        // Verify partial_merkle_tree_for_first_block. The size needs to match to ensure that
-        // the partial merkle tree is for the right block ordinal, and the partial tree itself
-        // needs to be valid and have the correct root.
-        //
-        // Note that the block_ordinal in the header is 1-based, so we need to add 1 to the size.
-        if current_epoch.partial_merkle_tree_for_first_block.size() + 1
-            != first_block_header.block_ordinal()
-        {
-            return Err(Error::InvalidEpochSyncProof(
-                "invalid size in partial_merkle_tree_for_first_block".to_string(),
-            ));
-        }
-
-        if !current_epoch.partial_merkle_tree_for_first_block.is_well_formed()
-            || current_epoch.partial_merkle_tree_for_first_block.root()
-                != *first_block_header.block_merkle_root()
-        {
-            return Err(Error::InvalidEpochSyncProof(
-                "invalid path in partial_merkle_tree_for_first_block".to_string(),
-            ));
-        }
-
-        // Verify the two headers before the first block.
-        if current_epoch.last_block_header_in_prev_epoch.hash()
-            != current_epoch.first_block_header_in_epoch.prev_hash()
-        {
-            return Err(Error::InvalidEpochSyncProof(
-                "invalid last_block_header_in_prev_epoch".to_string(),
-            ));
-        }
-        if current_epoch.second_last_block_header_in_prev_epoch.hash()
-            != current_epoch.last_block_header_in_prev_epoch.prev_hash()
-        {
-            return Err(Error::InvalidEpochSyncProof(
-                "invalid second_last_block_header_in_prev_epoch".to_string(),
-            ));
-        }
-
-        Ok(())
-    }
-
-    /// Verify epoch_sync_data_hash matches current_epoch_first_block_header's epoch_sync_data_hash.
-    fn verify_epoch_sync_data_hash(
-        last_epoch: &EpochSyncProofLastEpochData,
-        current_epoch_first_block_header: &BlockHeader,
-    ) -> Result<(), Error> {
-        let epoch_sync_data_hash = CryptoHash::hash_borsh(&(
-            &last_epoch.first_block_in_epoch,
-            &last_epoch.second_last_block_in_epoch,
-            &last_epoch.last_block_in_epoch,
-            &last_epoch.epoch_info,
-            &last_epoch.next_epoch_info,
-            &last_epoch.next_next_epoch_info,
-        ));
-        let expected_epoch_sync_data_hash =
-            current_epoch_first_block_header.epoch_sync_data_hash().ok_or_else(|| {
-                Error::InvalidEpochSyncProof("missing epoch_sync_data_hash".to_string())
-            })?;
-        if epoch_sync_data_hash != expected_epoch_sync_data_hash {
-            return Err(Error::InvalidEpochSyncProof("invalid epoch_sync_data_hash".to_string()));
-        }
-
-        Ok(())
-    }
-
-    /// Verifies that EpochSyncProofPastEpochData's block_producers is valid,
-    /// returning true if it is.
-    fn verify_block_producer_handoff(
-        block_producers: &Vec<ValidatorStake>,
-        use_versioned_bp_hash_format: bool,
-        prev_epoch_next_bp_hash: &CryptoHash,
-    ) -> Result<bool, Error> {
-        let bp_hash =
-            compute_bp_hash_from_validator_stakes(block_producers, use_versioned_bp_hash_format);
-        Ok(bp_hash == *prev_epoch_next_bp_hash)
-    }
-
-    /// Verifies that the epoch's last_final_block_header is sufficiently endorsed by the current
-    /// epoch's block producers.
-    fn verify_final_block_endorsement(epoch: &EpochSyncProofEpochData) -> Result<(), Error> {
-        Self::verify_block_endorsements(
-            *(&epoch.last_final_block_header).hash(),
-            (&epoch.last_final_block_header).height(),
-            &epoch.block_producers,
-            &epoch.this_epoch_endorsements_for_last_final_block,
-        )
-    }
-
-    /// Verifies that the given block is endorsed properly, and with enough stake.
-    fn verify_block_endorsements(
-        prev_block_hash: CryptoHash,
-        block_height: BlockHeight,
-        block_producers: &[ValidatorStake],
-        endorsements: &[Option<Box<Signature>>],
-    ) -> Result<(), near_chain::Error> {
-        if endorsements.len() != block_producers.len() {
-            return Err(near_chain::Error::InvalidEpochSyncProof(format!(
-                "Block {} should be provided with {} endorsements but has {}",
-                block_height,
-                block_producers.len(),
-                endorsements.len()
-            )));
-        }
-
-        let message_to_sign = Approval::get_data_for_sig(
-            &ApprovalInner::Endorsement(prev_block_hash),
-            block_height + 1,
-        );
-
-        let mut total_stake: Balance = 0;
-        let mut endorsed_stake: Balance = 0;
-
-        for (validator, may_be_signature) in block_producers.iter().zip(endorsements.iter()) {
-            if let Some(signature) = may_be_signature {
-                if !signature.verify(&message_to_sign, validator.public_key()) {
-                    return Err(near_chain::Error::InvalidEpochSyncProof(format!(
-                        "Invalid signature for block {} from validator {:?}",
-                        block_height,
-                        validator.account_id()
-                    )));
-                }
-                endorsed_stake += validator.stake();
-            }
-            total_stake += validator.stake();
-        }
-
-        if endorsed_stake <= total_stake * 2 / 3 {
-            return Err(near_chain::Error::InvalidEpochSyncProof(format!(
-                "Block {} does not have enough endorsements",
-                block_height
-            )));
-        }
-
-        Ok(())
+        proof.verify().map_err(Error::InvalidEpochSyncProof)
     }
 }
 
@@ -5,14 +5,19 @@ use near_primitives::types::{EpochId, ShardId};
 use object_store::{ObjectStore as _, PutPayload};
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub enum StateFileType {
-    StatePart { part_id: u64, num_parts: u64 },
+    StatePart {
+        part_id: u64,
+        num_parts: u64,
+    },
     StateHeader,
+    /// Signed hashes and sizes of the parts, see `StatePartsManifest`.
+    StatePartsManifest,
 }
 
 impl ToString for StateFileType {
@@ -20,6 +25,7 @@ impl ToString for StateFileType {
         match self {
             StateFileType::StatePart { .. } => StateFileType::part_str(),
             StateFileType::StateHeader => StateFileType::header_str(),
+            StateFileType::StatePartsManifest => StateFileType::manifest_str(),
         }
     }
 }
@@ -33,12 +39,17 @@ impl StateFileType {
         String::from("header")
     }
 
+    pub fn manifest_str() -> String {
+        String::from("manifest")
+    }
+
     pub fn filename(&self) -> String {
         match self {
             StateFileType::StatePart { part_id, num_parts } => {
                 format!("state_part_{:06}_of_{:06}", part_id, num_parts)
             }
             StateFileType::StateHeader => "header".to_string(),
+            StateFileType::StatePartsManifest => "manifest".to_string(),
         }
     }
 }
@@ -60,6 +71,127 @@ pub enum ExternalConnection {
         reqwest_client: Arc<reqwest::Client>,
         bucket: String,
     },
+    /// Read-only HTTP(S) mirrors of the dumped state, used for downloads only.
+    Http {
+        mirrors: Arc<HttpMirrors>,
+    },
+}
+
+/// Mirrors which are not tried before the healthy ones for this long after a
+/// failed request.
+const HTTP_MIRROR_FAILURE_PENALTY: Duration = Duration::from_secs(60);
+
+/// An ordered list of HTTP(S) servers serving the same state dumps, e.g. a
+/// bucket behind several CDNs.  The file at `location` is downloaded from
+/// `<base_url>/<location>`.
+///
+/// A request tries the mirrors one after another until one of them returns
+/// the file.  Mirrors which failed recently are tried last, the ones with
+/// fewer consecutive failures first, so a mirror which is down or lags behind
+/// the dump is skipped until the penalty expires.  A mirror serving a file
+/// which fails the check of the caller is blacklisted, i.e. tried after all
+/// others, for as long.  Retrying the whole request with backoff is left to
+/// the caller.
+pub struct HttpMirrors {
+    client: reqwest::Client,
+    mirrors: Vec<HttpMirror>,
+}
+
+struct HttpMirror {
+    base_url: String,
+    health: Mutex<HttpMirrorHealth>,
+}
+
+#[derive(Default)]
+struct HttpMirrorHealth {
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl HttpMirrors {
+    pub fn new(urls: &[String], timeout: Duration) -> Result<Self, anyhow::Error> {
+        anyhow::ensure!(!urls.is_empty(), "at least one mirror URL is required");
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        let mirrors = urls
+            .iter()
+            .map(|url| HttpMirror {
+                base_url: url.trim_end_matches('/').to_string(),
+                health: Mutex::new(HttpMirrorHealth::default()),
+            })
+            .collect();
+        Ok(Self { client, mirrors })
+    }
+
+    /// Mirrors in the order in which the next request tries them.
+    fn ordered(&self) -> Vec<&HttpMirror> {
+        let now = Instant::now();
+        let mut mirrors: Vec<_> = self
+            .mirrors
+            .iter()
+            .map(|mirror| {
+                let health = mirror.health.lock().unwrap();
+                let penalty = match health.last_failure {
+                    Some(at) if now.duration_since(at) < HTTP_MIRROR_FAILURE_PENALTY => {
+                        health.consecutive_failures
+                    }
+                    _ => 0,
+                };
+                (penalty, mirror)
+            })
+            .collect();
+        // The sort is stable, equally healthy mirrors keep the configured order.
+        mirrors.sort_by_key(|(penalty, _)| *penalty);
+        mirrors.into_iter().map(|(_, mirror)| mirror).collect()
+    }
+
+    async fn get(
+        &self,
+        shard_id: ShardId,
+        location: &str,
+        check: &FileCheck<'_>,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let mut last_error = None;
+        for mirror in self.ordered() {
+            let url = format!("{}/{}", mirror.base_url, location);
+            let bytes = match http_get(&self.client, &url).await {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    tracing::debug!(target: "sync", %shard_id, %url, ?err, "HTTP mirror request failed");
+                    let mut health = mirror.health.lock().unwrap();
+                    health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+                    health.last_failure = Some(Instant::now());
+                    last_error = Some(err);
+                    continue;
+                }
+            };
+            match check(&bytes) {
+                Ok(()) => {
+                    *mirror.health.lock().unwrap() = HttpMirrorHealth::default();
+                    return Ok(bytes);
+                }
+                Err(err) => {
+                    tracing::warn!(target: "sync", %shard_id, %url, ?err, "HTTP mirror served a bad file, blacklisting it");
+                    *mirror.health.lock().unwrap() = HttpMirrorHealth {
+                        consecutive_failures: u32::MAX,
+                        last_failure: Some(Instant::now()),
+                    };
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error
+            .expect("at least one mirror is configured")
+            .context(format!("all {} HTTP mirrors failed to serve {location}", self.mirrors.len())))
+    }
+}
+
+/// Validation of a downloaded file, see [`ExternalConnection::get_file_checked`].
+pub type FileCheck<'a> = dyn Fn(&[u8]) -> Result<(), anyhow::Error> + Send + Sync + 'a;
+
+/// Downloads the file at `url`, treating error status codes as failures.
+async fn http_get(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
 }
 
 const GCS_ENCODE_SET: &percent_encoding::AsciiSet =
@@ -98,15 +230,12 @@ impl ExternalConnection {
                     percent_encoding::percent_encode(bucket.as_bytes(), GCS_ENCODE_SET),
                     percent_encoding::percent_encode(location.as_bytes(), GCS_ENCODE_SET),
                 );
-                let response = reqwest_client.get(&url).send().await?.error_for_status();
-
-                match response {
+                match http_get(reqwest_client, &url).await {
                     Err(e) => {
                         tracing::debug!(target: "sync", %shard_id, location, error = ?e, "GCS state_part request failed");
-                        Err(e.into())
+                        Err(e)
                     }
-                    Ok(r) => {
-                        let bytes = r.bytes().await?.to_vec();
+                    Ok(bytes) => {
                         tracing::debug!(target: "sync", %shard_id, location, num_bytes = bytes.len(), "GCS state_part request finished");
                         metrics::STATE_SYNC_EXTERNAL_PARTS_SIZE_DOWNLOADED
                             .with_label_values(&[&shard_id.to_string(), &file_type.to_string()])
@@ -115,9 +244,49 @@ impl ExternalConnection {
                     }
                 }
             }
+            ExternalConnection::Http { mirrors } => {
+                Self::get_file_from_mirrors(mirrors, shard_id, location, file_type, &|_| Ok(()))
+                    .await
+            }
         }
     }
 
+    /// Downloads the file like [`Self::get_file`] and rejects it if `check`
+    /// fails.  HTTP mirrors serving a file which fails the check are
+    /// blacklisted and the file is downloaded from the next mirror instead.
+    pub async fn get_file_checked(
+        &self,
+        shard_id: ShardId,
+        location: &str,
+        file_type: &StateFileType,
+        check: &FileCheck<'_>,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let ExternalConnection::Http { mirrors } = self else {
+            let data = self.get_file(shard_id, location, file_type).await?;
+            check(&data)?;
+            return Ok(data);
+        };
+        let _timer = metrics::STATE_SYNC_EXTERNAL_PARTS_REQUEST_DELAY
+            .with_label_values(&[&shard_id.to_string(), &file_type.to_string()])
+            .start_timer();
+        Self::get_file_from_mirrors(mirrors, shard_id, location, file_type, check).await
+    }
+
+    async fn get_file_from_mirrors(
+        mirrors: &HttpMirrors,
+        shard_id: ShardId,
+        location: &str,
+        file_type: &StateFileType,
+        check: &FileCheck<'_>,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let bytes = mirrors.get(shard_id, location, check).await?;
+        tracing::debug!(target: "sync", %shard_id, location, num_bytes = bytes.len(), "HTTP mirror request finished");
+        metrics::STATE_SYNC_EXTERNAL_PARTS_SIZE_DOWNLOADED
+            .with_label_values(&[&shard_id.to_string(), &file_type.to_string()])
+            .inc_by(bytes.len() as u64);
+        Ok(bytes)
+    }
+
     /// Uploads the given state part or header to external storage.
     /// Wrapper for adding is_ok to the metric labels.
     pub async fn put_file(
@@ -172,6 +341,9 @@ impl ExternalConnection {
                 tracing::debug!(target: "state_sync_dump", ?shard_id, part_length = data.len(), ?location, ?file_type, "Wrote a state part to GCS");
                 Ok(())
             }
+            ExternalConnection::Http { .. } => {
+                anyhow::bail!("HTTP mirrors are read-only, can't upload {location}")
+            }
         }
     }
 
@@ -233,6 +405,9 @@ impl ExternalConnection {
                     .map(|object| object.location.filename().unwrap().into())
                     .collect())
             }
+            ExternalConnection::Http { .. } => {
+                anyhow::bail!("HTTP mirrors don't support listing {directory_path}")
+            }
         }
     }
 
@@ -304,7 +479,9 @@ pub fn location_prefix(
             "chain_id={}/epoch_height={}/epoch_id={}/shard_id={}",
             chain_id, epoch_height, epoch_id.0, shard_id
         ),
-        StateFileType::StateHeader => format!(
+        // The manifest is kept next to the header because the parts directory
+        // is expected to contain only parts.
+        StateFileType::StateHeader | StateFileType::StatePartsManifest => format!(
             "chain_id={}/epoch_height={}/epoch_id={}/headers/shard_id={}",
             chain_id, epoch_height, epoch_id.0, shard_id
         ),
@@ -401,12 +578,17 @@ fn create_bucket(
 #[cfg(test)]
 mod test {
     use crate::sync::external::{
-        ExternalConnection, StateFileType, get_num_parts_from_filename, get_part_id_from_filename,
-        is_part_filename,
+        ExternalConnection, HttpMirrors, StateFileType, get_num_parts_from_filename,
+        get_part_id_from_filename, is_part_filename,
     };
     use near_o11y::testonly::init_test_logger;
     use near_primitives::types::ShardId;
     use rand::distributions::{Alphanumeric, DistString};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::time::Duration;
 
     fn random_string(rand_len: usize) -> String {
         Alphanumeric.sample_string(&mut rand::thread_rng(), rand_len)
@@ -496,4 +678,109 @@ mod test {
         });
         assert!(download_data.is_err(), "{:?}", download_data);
     }
+
+    /// Starts a minimal HTTP server responding to GET requests for the given
+    /// paths and returns its base URL.
+    fn serve_files(files: HashMap<String, Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                // Skip the headers, GET requests have no body.
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = match files.get(path) {
+                    Some(body) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", &[][..]),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        base_url
+    }
+
+    #[test]
+    fn test_http_mirrors_failover() {
+        init_test_logger();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let location =
+            "chain_id=test/epoch_height=1/epoch_id=test/shard_id=0/state_part_000000_of_000001";
+        // Nothing listens on the port of a dropped listener.
+        let down =
+            format!("http://{}", TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap());
+        let lagging = serve_files(HashMap::new());
+        let synced = serve_files(HashMap::from([(format!("/{location}"), b"part".to_vec())]));
+        let mirrors = Arc::new(
+            HttpMirrors::new(
+                &[down.clone(), lagging.clone(), format!("{synced}/")],
+                Duration::from_secs(5),
+            )
+            .unwrap(),
+        );
+        let ordered_urls = || -> Vec<String> {
+            mirrors.ordered().into_iter().map(|mirror| mirror.base_url.clone()).collect()
+        };
+        assert_eq!(ordered_urls(), vec![down.clone(), lagging.clone(), synced.clone()]);
+
+        let connection = ExternalConnection::Http { mirrors: mirrors.clone() };
+        let file_type = StateFileType::StatePart { part_id: 0, num_parts: 1 };
+        let data = rt.block_on(connection.get_file(ShardId::new(0), location, &file_type)).unwrap();
+        assert_eq!(data, b"part");
+        // The mirrors which failed are now tried after the one which served the file.
+        assert_eq!(ordered_urls(), vec![synced.clone(), down.clone(), lagging.clone()]);
+
+        let missing = rt.block_on(connection.get_file(ShardId::new(0), "missing", &file_type));
+        assert!(missing.is_err(), "{:?}", missing);
+        assert_eq!(ordered_urls(), vec![synced.clone(), down, lagging]);
+
+        // Mirrors are read-only.
+        let upload =
+            rt.block_on(connection.put_file(file_type, b"part", ShardId::new(0), location));
+        assert!(upload.is_err());
+    }
+
+    #[test]
+    fn test_http_mirrors_blacklist_bad_file() {
+        init_test_logger();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let location = "chain_id=test/epoch_height=1/epoch_id=test/headers/shard_id=0/manifest";
+        let corrupted = serve_files(HashMap::from([(format!("/{location}"), b"bad".to_vec())]));
+        let honest = serve_files(HashMap::from([(format!("/{location}"), b"good".to_vec())]));
+        let mirrors = Arc::new(
+            HttpMirrors::new(&[corrupted.clone(), honest.clone()], Duration::from_secs(5)).unwrap(),
+        );
+        let connection = ExternalConnection::Http { mirrors: mirrors.clone() };
+        let file_type = StateFileType::StatePartsManifest;
+        let check = |data: &[u8]| {
+            anyhow::ensure!(data == b"good", "unexpected data");
+            Ok(())
+        };
+
+        // Without a check the first mirror is used.
+        let data = rt.block_on(connection.get_file(ShardId::new(0), location, &file_type)).unwrap();
+        assert_eq!(data, b"bad");
+
+        let data = rt
+            .block_on(connection.get_file_checked(ShardId::new(0), location, &file_type, &check))
+            .unwrap();
+        assert_eq!(data, b"good");
+        let ordered_urls: Vec<String> =
+            mirrors.ordered().into_iter().map(|mirror| mirror.base_url.clone()).collect();
+        assert_eq!(ordered_urls, vec![honest, corrupted]);
+    }
 }
@@ -820,7 +820,8 @@ mod test {
                 clock.clock(),
                 None,
                 None,
-            );
+            )
+            .unwrap();
             block_merkle_tree.insert(*block.hash());
             chain2.process_block_header(block.header(), &mut Vec::new()).unwrap(); // just to validate
             process_block_sync(
@@ -1,5 +1,6 @@
 use super::StateSyncDownloadSource;
 use super::chain_requests::StateHeaderValidationRequest;
+use super::manifest::StatePartsManifestSource;
 use super::task_tracker::TaskTracker;
 use super::util::get_state_header_if_exists_in_storage;
 use futures::FutureExt;
@@ -9,11 +10,12 @@ use near_async::time::{Clock, Duration};
 use near_chain::types::RuntimeAdapter;
 use near_primitives::hash::CryptoHash;
 use near_primitives::state_part::PartId;
-use near_primitives::state_sync::{ShardStateSyncResponseHeader, StatePartKey};
+use near_primitives::state_sync::{ShardStateSyncResponseHeader, StatePartKey, StatePartsManifest};
 use near_primitives::types::ShardId;
 use near_store::{DBCol, Store};
-use std::sync::Arc;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
 
@@ -21,6 +23,9 @@ use tracing::Instrument;
 ///  - caching of the header / part in rocksdb.
 ///  - validation of the header / part before persisting into rocksdb.
 ///  - retrying, if the download fails, or validation fails.
+///  - checking the parts against the signed manifest of the dump nodes, if
+///    available, and downloading the parts which the preferred source served
+///    wrong from the fallback source.
 ///
 /// As a result, the user of this API only needs to request the header or ensure the
 /// part exists on disk, and the downloader will take care of the rest.
@@ -35,6 +40,10 @@ pub(super) struct StateSyncDownloader {
     pub runtime: Arc<dyn RuntimeAdapter>,
     pub retry_backoff: Duration,
     pub task_tracker: TaskTracker,
+    pub manifest_source: Option<Arc<StatePartsManifestSource>>,
+    /// Parts for which the preferred source returned data not matching the
+    /// manifest, indexed by (sync hash, shard ID, part ID).
+    pub parts_rejected_from_preferred_source: Arc<Mutex<HashSet<(CryptoHash, ShardId, u64)>>>,
 }
 
 impl StateSyncDownloader {
@@ -122,6 +131,31 @@ impl StateSyncDownloader {
         .boxed()
     }
 
+    /// Downloads the manifest of the parts of the shard if trusted dump nodes
+    /// are configured.  Returns None if the manifest is not available (yet),
+    /// in which case the parts are only validated against the state root.
+    pub fn download_manifest(
+        &self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        header: ShardStateSyncResponseHeader,
+        cancel: CancellationToken,
+    ) -> BoxFuture<'static, Option<Arc<StatePartsManifest>>> {
+        let manifest_source = self.manifest_source.clone();
+        async move {
+            let manifest_source = manifest_source?;
+            match manifest_source.download_manifest(shard_id, sync_hash, &header, cancel).await {
+                Ok(manifest) => Some(Arc::new(manifest)),
+                Err(err) => {
+                    tracing::debug!(target: "sync", %shard_id, %sync_hash, ?err, "State parts manifest is not available");
+                    None
+                }
+            }
+        }
+        .instrument(tracing::debug_span!("StateSyncDownloader::download_manifest"))
+        .boxed()
+    }
+
     /// Attempts once to ensure that the shard part is downloaded and validated.
     /// If the part exists on disk, just returns. Otherwise, makes one attempt
     /// to download the part and validate it.
@@ -135,6 +169,7 @@ impl StateSyncDownloader {
         num_state_parts: u64,
         part_id: u64,
         num_prior_attempts: usize,
+        manifest: Option<Arc<StatePartsManifest>>,
         cancel: CancellationToken,
     ) -> BoxFuture<'static, Result<(), near_chain::Error>> {
         let store = self.store.clone();
@@ -145,6 +180,7 @@ impl StateSyncDownloader {
         let clock = self.clock.clone();
         let task_tracker = self.task_tracker.clone();
         let retry_backoff = self.retry_backoff;
+        let rejected_parts = self.parts_rejected_from_preferred_source.clone();
         async move {
             if cancel.is_cancelled() {
                 return Err(near_chain::Error::Other("Cancelled".to_owned()));
@@ -157,9 +193,11 @@ impl StateSyncDownloader {
             }
 
             let attempt = || async {
-                let source = if fallback_source.is_some()
-                    && num_prior_attempts >= num_attempts_before_fallback
-                {
+                let part_key = (sync_hash, shard_id, part_id);
+                let use_fallback = fallback_source.is_some()
+                    && (num_prior_attempts >= num_attempts_before_fallback
+                        || rejected_parts.lock().unwrap().contains(&part_key));
+                let source = if use_fallback {
                     fallback_source.as_ref().unwrap().as_ref()
                 } else {
                     preferred_source.as_ref()
@@ -170,10 +208,23 @@ impl StateSyncDownloader {
                         shard_id,
                         sync_hash,
                         part_id,
+                        manifest.clone(),
                         handle.clone(),
                         cancel.clone(),
                     )
                     .await?;
+                // The fallback source is external storage, which checks the
+                // parts against the manifest itself.
+                if !use_fallback
+                    && manifest
+                        .as_ref()
+                        .is_some_and(|manifest| !manifest.part_matches(part_id, &part))
+                {
+                    rejected_parts.lock().unwrap().insert(part_key);
+                    return Err(near_chain::Error::Other(
+                        "Part data doesn't match the manifest".to_owned(),
+                    ));
+                }
                 if runtime_adapter.validate_state_part(
                     &state_root,
                     PartId { idx: part_id, total: num_state_parts },
@@ -220,3 +271,141 @@ fn does_state_part_exist_on_disk(
         &borsh::to_vec(&StatePartKey(sync_hash, shard_id, part_id)).unwrap(),
     )?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::StateSyncDownloader;
+    use crate::sync::state::StateSyncDownloadSource;
+    use crate::sync::state::task_tracker::{TaskHandle, TaskTracker};
+    use futures::FutureExt;
+    use futures::future::BoxFuture;
+    use near_async::messaging::{IntoSender, noop};
+    use near_async::time::{Clock, Duration};
+    use near_chain::test_utils::{KeyValueRuntime, MockEpochManager};
+    use near_crypto::{KeyType, SecretKey};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::state_sync::{
+        ShardStateSyncResponseHeader, StatePartInfo, StatePartKey, StatePartsManifest,
+    };
+    use near_primitives::types::{EpochId, ShardId};
+    use near_store::DBCol;
+    use near_store::test_utils::create_test_store;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio_util::sync::CancellationToken;
+
+    /// Serves the given parts and counts the requests.
+    struct TestPartsSource {
+        parts: HashMap<u64, Vec<u8>>,
+        num_requests: AtomicUsize,
+    }
+
+    impl TestPartsSource {
+        fn new(parts: HashMap<u64, Vec<u8>>) -> Arc<Self> {
+            Arc::new(Self { parts, num_requests: AtomicUsize::new(0) })
+        }
+    }
+
+    impl StateSyncDownloadSource for TestPartsSource {
+        fn download_shard_header(
+            &self,
+            _shard_id: ShardId,
+            _sync_hash: CryptoHash,
+            _handle: Arc<TaskHandle>,
+            _cancel: CancellationToken,
+        ) -> BoxFuture<Result<ShardStateSyncResponseHeader, near_chain::Error>> {
+            async { Err(near_chain::Error::Other("No headers".to_owned())) }.boxed()
+        }
+
+        fn download_shard_part(
+            &self,
+            _shard_id: ShardId,
+            _sync_hash: CryptoHash,
+            part_id: u64,
+            _manifest: Option<Arc<StatePartsManifest>>,
+            _handle: Arc<TaskHandle>,
+            _cancel: CancellationToken,
+        ) -> BoxFuture<Result<Vec<u8>, near_chain::Error>> {
+            self.num_requests.fetch_add(1, Ordering::Relaxed);
+            let part = self.parts.get(&part_id).cloned();
+            async move { part.ok_or_else(|| near_chain::Error::Other("No part".to_owned())) }
+                .boxed()
+        }
+    }
+
+    /// A part served by a peer which doesn't match the manifest signed by a
+    /// trusted dump node is downloaded from external storage instead, without
+    /// waiting for `num_attempts_before_fallback` failures.
+    #[test]
+    fn test_part_not_matching_manifest_falls_back_to_external_storage() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let store = create_test_store();
+        let epoch_manager = MockEpochManager::new(store.clone(), 5);
+        let runtime = KeyValueRuntime::new(store.clone(), &epoch_manager);
+
+        let shard_id = ShardId::new(0);
+        let sync_hash = CryptoHash::hash_bytes(b"sync");
+        let state_root = CryptoHash::hash_bytes(b"state");
+        let parts = vec![b"part 0".to_vec(), b"part 1".to_vec()];
+        let dump_node_key = SecretKey::from_seed(KeyType::ED25519, "dump node");
+        let manifest = Arc::new(StatePartsManifest::new(
+            EpochId::default(),
+            shard_id,
+            state_root,
+            parts.iter().map(|part| StatePartInfo::new(part)).collect(),
+            &dump_node_key,
+        ));
+        assert!(manifest.verify_signature());
+
+        let peers = TestPartsSource::new(HashMap::from([
+            (0, b"corrupted part 0".to_vec()),
+            (1, parts[1].clone()),
+        ]));
+        let external = TestPartsSource::new(
+            parts.iter().cloned().enumerate().map(|(i, p)| (i as u64, p)).collect(),
+        );
+        let downloader = StateSyncDownloader {
+            clock: Clock::real(),
+            store: store.clone(),
+            preferred_source: peers.clone(),
+            fallback_source: Some(external.clone()),
+            num_attempts_before_fallback: 10,
+            header_validation_sender: noop().into_sender(),
+            runtime,
+            retry_backoff: Duration::milliseconds(1),
+            task_tracker: TaskTracker::new(1),
+            manifest_source: None,
+            parts_rejected_from_preferred_source: Default::default(),
+        };
+        let download = |part_id, num_prior_attempts| {
+            rt.block_on(downloader.ensure_shard_part_downloaded_single_attempt(
+                shard_id,
+                sync_hash,
+                state_root,
+                parts.len() as u64,
+                part_id,
+                num_prior_attempts,
+                Some(manifest.clone()),
+                CancellationToken::new(),
+            ))
+        };
+        let stored_part = |part_id| {
+            let key = borsh::to_vec(&StatePartKey(sync_hash, shard_id, part_id)).unwrap();
+            store.get(DBCol::StateParts, &key).unwrap().map(|part| part.to_vec())
+        };
+
+        download(1, 0).unwrap();
+        assert_eq!(stored_part(1), Some(parts[1].clone()));
+
+        assert!(download(0, 0).is_err());
+        assert_eq!(stored_part(0), None);
+        assert_eq!(peers.num_requests.load(Ordering::Relaxed), 2);
+        assert_eq!(external.num_requests.load(Ordering::Relaxed), 0);
+
+        download(0, 1).unwrap();
+        assert_eq!(stored_part(0), Some(parts[0].clone()));
+        assert_eq!(peers.num_requests.load(Ordering::Relaxed), 2);
+        assert_eq!(external.num_requests.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,14 +1,16 @@
 use super::StateSyncDownloadSource;
 use super::task_tracker::TaskHandle;
 use super::util::{get_state_header_if_exists_in_storage, query_epoch_id_and_height_for_block};
-use crate::sync::external::{ExternalConnection, StateFileType, external_storage_location};
+use crate::sync::external::{
+    ExternalConnection, FileCheck, StateFileType, external_storage_location,
+};
 use crate::sync::state::util::increment_download_count;
 use borsh::BorshDeserialize;
 use futures::FutureExt;
 use futures::future::BoxFuture;
 use near_async::time::{Clock, Duration};
 use near_primitives::hash::CryptoHash;
-use near_primitives::state_sync::ShardStateSyncResponseHeader;
+use near_primitives::state_sync::{ShardStateSyncResponseHeader, StatePartsManifest};
 use near_primitives::types::ShardId;
 use near_store::Store;
 use std::sync::Arc;
@@ -35,12 +37,14 @@ impl StateSyncDownloadSourceExternal {
         shard_id: ShardId,
         location: String,
         file_type: StateFileType,
+        check: Box<FileCheck<'static>>,
     ) -> Result<Vec<u8>, near_chain::Error> {
-        let fut = conn.get_file(shard_id, &location, &file_type);
+        let fut = conn.get_file_checked(shard_id, &location, &file_type, &*check);
         let deadline = clock.now() + timeout;
         let typ = match &file_type {
             StateFileType::StateHeader => "header",
             StateFileType::StatePart { .. } => "part",
+            StateFileType::StatePartsManifest => "manifest",
         };
         tokio::select! {
             _ = clock.sleep_until(deadline) => {
@@ -106,6 +110,7 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
                 shard_id,
                 location,
                 StateFileType::StateHeader,
+                Box::new(|_| Ok(())),
             )
             .await?;
             let header = ShardStateSyncResponseHeader::try_from_slice(&data).map_err(|e| {
@@ -125,6 +130,7 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
         shard_id: ShardId,
         sync_hash: CryptoHash,
         part_id: u64,
+        manifest: Option<Arc<StatePartsManifest>>,
         handle: Arc<TaskHandle>,
         cancel: CancellationToken,
     ) -> BoxFuture<Result<Vec<u8>, near_chain::Error>> {
@@ -150,6 +156,18 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
                 &StateFileType::StatePart { part_id, num_parts },
             );
             handle.set_status("Downloading file");
+            // Checking the part here rather than in the downloader lets an
+            // HTTP mirror serving bad parts be skipped right away.
+            let check: Box<FileCheck<'static>> = match manifest {
+                Some(manifest) => Box::new(move |part: &[u8]| {
+                    anyhow::ensure!(
+                        manifest.part_matches(part_id, part),
+                        "part doesn't match the manifest"
+                    );
+                    Ok(())
+                }),
+                None => Box::new(|_| Ok(())),
+            };
             let data = Self::get_file_with_timeout(
                 clock,
                 timeout,
@@ -159,6 +177,7 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
                 shard_id,
                 location,
                 StateFileType::StatePart { part_id, num_parts },
+                check,
             )
             .await?;
             increment_download_count(shard_id, "part", "external", "success");
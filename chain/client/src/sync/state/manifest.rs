@@ -0,0 +1,88 @@
+use super::util::{increment_download_count, query_epoch_id_and_height_for_block};
+use crate::sync::external::{ExternalConnection, StateFileType, external_storage_location};
+use borsh::BorshDeserialize;
+use near_async::time::{Clock, Duration};
+use near_crypto::PublicKey;
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_sync::{ShardStateSyncResponseHeader, StatePartsManifest};
+use near_primitives::types::ShardId;
+use near_store::Store;
+use tokio_util::sync::CancellationToken;
+
+/// Downloads the manifests of the state parts published to external storage
+/// by the dump nodes, see `StatePartsManifest`.
+pub(super) struct StatePartsManifestSource {
+    pub clock: Clock,
+    pub store: Store,
+    pub chain_id: String,
+    pub conn: ExternalConnection,
+    pub timeout: Duration,
+    /// Node keys of the dump nodes whose manifests are trusted.
+    pub trusted_signers: Vec<PublicKey>,
+}
+
+impl StatePartsManifestSource {
+    /// Downloads the manifest of the parts of the shard and checks that it is
+    /// signed by a trusted dump node and lists the parts of `header`.
+    pub async fn download_manifest(
+        &self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        header: &ShardStateSyncResponseHeader,
+        cancel: CancellationToken,
+    ) -> Result<StatePartsManifest, near_chain::Error> {
+        let (epoch_id, epoch_height) = query_epoch_id_and_height_for_block(&self.store, sync_hash)?;
+        let file_type = StateFileType::StatePartsManifest;
+        let location = external_storage_location(
+            &self.chain_id,
+            &epoch_id,
+            epoch_height,
+            shard_id,
+            &file_type,
+        );
+        let deadline = self.clock.now() + self.timeout;
+        let data = tokio::select! {
+            _ = self.clock.sleep_until(deadline) => {
+                increment_download_count(shard_id, "manifest", "external", "timeout");
+                return Err(near_chain::Error::Other("Timeout".to_owned()));
+            }
+            _ = cancel.cancelled() => {
+                increment_download_count(shard_id, "manifest", "external", "cancelled");
+                return Err(near_chain::Error::Other("Cancelled".to_owned()));
+            }
+            result = self.conn.get_file(shard_id, &location, &file_type) => {
+                result.map_err(|err| {
+                    increment_download_count(shard_id, "manifest", "external", "download_error");
+                    near_chain::Error::Other(format!("Failed to download: {}", err))
+                })?
+            }
+        };
+        let manifest = StatePartsManifest::try_from_slice(&data).map_err(|err| {
+            increment_download_count(shard_id, "manifest", "external", "parse_error");
+            near_chain::Error::Other(format!("Failed to parse manifest: {}", err))
+        })?;
+        if !self.trusted_signers.contains(&manifest.signer) {
+            increment_download_count(shard_id, "manifest", "external", "untrusted_signer");
+            return Err(near_chain::Error::Other(format!(
+                "Manifest is signed by untrusted key {}",
+                manifest.signer
+            )));
+        }
+        if !manifest.verify_signature() {
+            increment_download_count(shard_id, "manifest", "external", "invalid_signature");
+            return Err(near_chain::Error::Other("Invalid manifest signature".to_owned()));
+        }
+        if manifest.epoch_id != epoch_id
+            || manifest.shard_id != shard_id
+            || manifest.state_root != header.chunk_prev_state_root()
+            || manifest.parts.len() as u64 != header.num_state_parts()
+        {
+            increment_download_count(shard_id, "manifest", "external", "header_mismatch");
+            return Err(near_chain::Error::Other(
+                "Manifest doesn't match the state header".to_owned(),
+            ));
+        }
+        increment_download_count(shard_id, "manifest", "external", "success");
+        Ok(manifest)
+    }
+}
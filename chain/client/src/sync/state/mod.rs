@@ -1,17 +1,19 @@
 pub mod chain_requests;
 mod downloader;
 mod external;
+mod manifest;
 mod network;
 mod shard;
 mod task_tracker;
 mod util;
 
 use crate::metrics;
-use crate::sync::external::{ExternalConnection, create_bucket_readonly};
+use crate::sync::external::{ExternalConnection, HttpMirrors, create_bucket_readonly};
 use chain_requests::ChainSenderForStateSync;
 use downloader::StateSyncDownloader;
 use external::StateSyncDownloadSourceExternal;
 use futures::future::BoxFuture;
+use manifest::StatePartsManifestSource;
 use near_async::futures::{FutureSpawner, FutureSpawnerExt};
 use near_async::messaging::{AsyncSender, IntoSender};
 use near_async::time::{Clock, Duration};
@@ -25,7 +27,9 @@ use near_network::types::{
 };
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
-use near_primitives::state_sync::{ShardStateSyncResponse, ShardStateSyncResponseHeader};
+use near_primitives::state_sync::{
+    ShardStateSyncResponse, ShardStateSyncResponseHeader, StatePartsManifest,
+};
 use near_primitives::types::ShardId;
 use near_store::Store;
 use network::{StateSyncDownloadSourcePeer, StateSyncDownloadSourcePeerSharedState};
@@ -108,12 +112,14 @@ impl StateSync {
             request_timeout: p2p_timeout,
             state: peer_source_state.clone(),
         }) as Arc<dyn StateSyncDownloadSource>;
+        let mut manifest_source = None;
         let (fallback_source, num_attempts_before_fallback, num_concurrent_requests) =
             if let SyncConfig::ExternalStorage(ExternalStorageConfig {
                 location,
                 num_concurrent_requests,
                 num_concurrent_requests_during_catchup,
                 external_storage_fallback_threshold,
+                manifest_signers,
             }) = sync_config
             {
                 let external = match location {
@@ -141,12 +147,32 @@ impl StateSync {
                         reqwest_client: Arc::new(reqwest::Client::default()),
                         bucket: bucket.clone(),
                     },
+                    ExternalStorageLocation::Http { urls } => {
+                        let mirrors = HttpMirrors::new(
+                            urls,
+                            external_timeout.max(Duration::ZERO).unsigned_abs(),
+                        );
+                        if let Err(err) = mirrors {
+                            panic!("Failed to create the HTTP mirrors: {}", err);
+                        }
+                        ExternalConnection::Http { mirrors: Arc::new(mirrors.unwrap()) }
+                    }
                 };
                 let num_concurrent_requests = if catchup {
                     *num_concurrent_requests_during_catchup
                 } else {
                     *num_concurrent_requests
                 } as usize;
+                if !manifest_signers.is_empty() {
+                    manifest_source = Some(Arc::new(StatePartsManifestSource {
+                        clock: clock.clone(),
+                        store: store.clone(),
+                        chain_id: chain_id.to_string(),
+                        conn: external.clone(),
+                        timeout: external_timeout,
+                        trusted_signers: manifest_signers.clone(),
+                    }));
+                }
                 let fallback_source = Arc::new(StateSyncDownloadSourceExternal {
                     clock: clock.clone(),
                     store: store.clone(),
@@ -175,6 +201,8 @@ impl StateSync {
             runtime: runtime.clone(),
             retry_backoff,
             task_tracker: downloading_task_tracker.clone(),
+            manifest_source,
+            parts_rejected_from_preferred_source: Default::default(),
         });
 
         let num_concurrent_computations = if catchup {
@@ -322,11 +350,15 @@ pub(self) trait StateSyncDownloadSource: Send + Sync + 'static {
         cancel: CancellationToken,
     ) -> BoxFuture<Result<ShardStateSyncResponseHeader, near_chain::Error>>;
 
+    /// `manifest`, if known, lists the expected hashes of the parts.  The
+    /// external source only returns matching parts, skipping the mirrors which
+    /// serve bad ones, while the peer source leaves the check to the caller.
     fn download_shard_part(
         &self,
         shard_id: ShardId,
         sync_hash: CryptoHash,
         part_id: u64,
+        manifest: Option<Arc<StatePartsManifest>>,
         handle: Arc<TaskHandle>,
         cancel: CancellationToken,
     ) -> BoxFuture<Result<Vec<u8>, near_chain::Error>>;
@@ -12,7 +12,9 @@ use near_network::types::{
 };
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
-use near_primitives::state_sync::{ShardStateSyncResponse, ShardStateSyncResponseHeader};
+use near_primitives::state_sync::{
+    ShardStateSyncResponse, ShardStateSyncResponseHeader, StatePartsManifest,
+};
 use near_primitives::types::ShardId;
 use near_store::{DBCol, Store};
 use rand::seq::SliceRandom;
@@ -273,6 +275,7 @@ impl StateSyncDownloadSource for StateSyncDownloadSourcePeer {
         shard_id: ShardId,
         sync_hash: CryptoHash,
         part_id: u64,
+        _manifest: Option<Arc<StatePartsManifest>>,
         handle: Arc<TaskHandle>,
         cancel: CancellationToken,
     ) -> BoxFuture<'static, Result<Vec<u8>, near_chain::Error>> {
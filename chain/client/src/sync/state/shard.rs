@@ -5,6 +5,7 @@ use crate::sync::state::chain_requests::ChainFinalizationRequest;
 use futures::{StreamExt, TryStreamExt};
 use near_async::futures::{FutureSpawner, respawn_for_parallelism};
 use near_async::messaging::AsyncSender;
+use near_async::time::Duration;
 use near_chain::BlockHeader;
 use near_chain::types::RuntimeAdapter;
 use near_client_primitives::types::ShardSyncStatus;
@@ -48,6 +49,10 @@ impl Drop for StateSyncShardHandle {
 /// balancing the shards a little.
 const MAX_PARALLELISM_PER_SHARD_FOR_FAIRNESS: usize = 6;
 
+/// The upper bound of the backoff between attempts to download the manifest of
+/// the state parts, which doubles after every failed attempt.
+const MAX_MANIFEST_RETRY_BACKOFF: Duration = Duration::seconds(60);
+
 macro_rules! return_if_cancelled {
     ($cancel:expr) => {
         if $cancel.is_cancelled() {
@@ -98,8 +103,21 @@ pub(super) async fn run_state_sync_for_shard(
         parts_to_download.shuffle(&mut rng);
     }
     let mut attempt_count = 0;
+    let mut manifest = None;
+    let mut manifest_retry_backoff = downloader.retry_backoff;
+    let mut next_manifest_attempt = downloader.clock.now();
     while !parts_to_download.is_empty() {
         return_if_cancelled!(cancel);
+        // The manifest is published once all parts are dumped, so keep looking
+        // for it while some parts are missing, backing off not to poll the
+        // external storage on every round of part downloads.
+        if manifest.is_none() && downloader.clock.now() >= next_manifest_attempt {
+            manifest = downloader
+                .download_manifest(shard_id, sync_hash, header.clone(), cancel.clone())
+                .await;
+            next_manifest_attempt = downloader.clock.now() + manifest_retry_backoff;
+            manifest_retry_backoff = (manifest_retry_backoff * 2).min(MAX_MANIFEST_RETRY_BACKOFF);
+        }
         let results = tokio_stream::iter(parts_to_download.clone())
             .map(|part_id| {
                 let future = downloader.ensure_shard_part_downloaded_single_attempt(
@@ -109,6 +127,7 @@ pub(super) async fn run_state_sync_for_shard(
                     num_parts,
                     part_id,
                     attempt_count,
+                    manifest.clone(),
                     cancel.clone(),
                 );
                 respawn_for_parallelism(&*future_spawner, "state sync download part", future)
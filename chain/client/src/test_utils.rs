@@ -230,7 +230,8 @@ pub fn create_chunk(
             header.bandwidth_requests().cloned(),
             &*signer,
             PROTOCOL_VERSION,
-        );
+        )
+        .unwrap();
         swap(&mut chunk, &mut encoded_chunk);
         swap(&mut merkle_paths, &mut new_merkle_paths);
     }
@@ -274,7 +275,8 @@ pub fn create_chunk(
         client.clock.clone(),
         None,
         None,
-    );
+    )
+    .unwrap();
     (ProduceChunkResult { chunk, encoded_chunk_parts_paths: merkle_paths, receipts }, block)
 }
 
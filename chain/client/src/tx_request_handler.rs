@@ -7,6 +7,7 @@ use near_chain::types::RuntimeAdapter;
 use near_chain::types::Tip;
 use near_chain_configs::MutableValidatorSigner;
 use near_chunks::client::ShardedTransactionPool;
+use near_client_primitives::types::GetMempool;
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_assignment::account_id_to_shard_id;
 use near_epoch_manager::shard_tracker::ShardTracker;
@@ -23,6 +24,7 @@ use near_primitives::types::EpochId;
 use near_primitives::types::ShardId;
 use near_primitives::unwrap_or_return;
 use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::views::MempoolView;
 use near_store::adapter::StoreAdapter;
 use near_store::adapter::chain_store::ChainStoreAdapter;
 use std::collections::HashSet;
@@ -40,6 +42,12 @@ impl Handler<ProcessTxRequest> for TxRequestHandler {
     }
 }
 
+impl Handler<GetMempool> for TxRequestHandler {
+    fn handle(&mut self, msg: GetMempool) -> MempoolView {
+        self.tx_pool.lock().unwrap().mempool_view(msg.signer_id.as_ref(), msg.after, msg.limit)
+    }
+}
+
 impl messaging::Actor for TxRequestHandler {}
 
 pub fn spawn_tx_request_handler_actor(
@@ -227,6 +235,13 @@ impl TxRequestHandler {
                             tracing::trace!(target: "client", ?shard_uid, tx_hash = ?signed_tx.get_hash(), "Transaction pool is full, trying to forward the transaction.");
                         }
                     }
+                    InsertTransactionResult::AccountQuotaExceeded => {
+                        if is_forwarded {
+                            tracing::trace!(target: "client", ?shard_uid, tx_hash = ?signed_tx.get_hash(), "Account transaction pool quota exceeded, dropping the transaction.");
+                        } else {
+                            tracing::trace!(target: "client", ?shard_uid, tx_hash = ?signed_tx.get_hash(), "Account transaction pool quota exceeded, trying to forward the transaction.");
+                        }
+                    }
                 }
             }
 
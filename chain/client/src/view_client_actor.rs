@@ -2,14 +2,15 @@
 //! Useful for querying from RPC.
 
 use crate::{
-    GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetShardChunk, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, metrics, sync,
+    GetChunk, GetChunkBalanceAudit, GetExecutionOutcomeResponse, GetNextLightClientBlock,
+    GetShardChunk, GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
+    metrics, sync,
 };
 use actix::{Addr, SyncArbiter};
 use near_async::actix_wrapper::SyncActixWrapper;
 use near_async::messaging::{Actor, CanSend, Handler};
 use near_async::time::{Clock, Duration, Instant};
-use near_chain::types::{RuntimeAdapter, Tip};
+use near_chain::types::{RuntimeAdapter, SimulationError, Tip};
 use near_chain::{
     Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode, MerkleProofAccess,
     get_epoch_block_producers_view, retrieve_headers,
@@ -19,12 +20,13 @@ use near_chain_configs::{ClientConfig, MutableValidatorSigner, ProtocolConfigVie
 use near_chain_primitives::error::EpochErrorResultToChainError;
 use near_client_primitives::types::{
     Error, GetBlock, GetBlockError, GetBlockProof, GetBlockProofError, GetBlockProofResponse,
-    GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome, GetExecutionOutcomeError,
-    GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetMaintenanceWindows,
-    GetMaintenanceWindowsError, GetNextLightClientBlockError, GetProtocolConfig,
-    GetProtocolConfigError, GetReceipt, GetReceiptError, GetSplitStorageInfo,
-    GetSplitStorageInfoError, GetStateChangesError, GetStateChangesWithCauseInBlock,
-    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfoError, Query, QueryError,
+    GetBlockWithMerkleTree, GetChunkBalanceAuditError, GetChunkError, GetExecutionOutcome,
+    GetExecutionOutcomeError, GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError,
+    GetMaintenanceWindows, GetMaintenanceWindowsError, GetNextLightClientBlockError,
+    GetProtocolConfig, GetProtocolConfigError, GetReceipt, GetReceiptError, GetSplitStorageInfo,
+    GetSplitStorageInfoError, GetStateChangesError, GetStateChangesResponse,
+    GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
+    GetValidatorInfoError, Query, QueryError, SimulateTransaction, SimulateTransactionError,
     TxStatus, TxStatusError,
 };
 use near_epoch_manager::EpochManagerAdapter;
@@ -40,6 +42,7 @@ use near_network::types::{
 };
 use near_performance_metrics_macros::perf;
 use near_primitives::block::{Block, BlockHeader};
+use near_primitives::chunk_apply_stats::ChunkApplyStats;
 use near_primitives::epoch_info::EpochInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{PartialMerkleTree, merklize};
@@ -50,19 +53,21 @@ use near_primitives::state_sync::{
     ShardStateSyncResponse, ShardStateSyncResponseHeader, ShardStateSyncResponseV3,
 };
 use near_primitives::stateless_validation::ChunkProductionKey;
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
 use near_primitives::types::{
     AccountId, BlockHeight, BlockId, BlockReference, EpochReference, Finality, MaybeBlockId,
-    ShardId, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
+    ShardId, StateChanges, StateChangesExt, SyncCheckpoint, TransactionOrReceiptId,
+    ValidatorInfoIdentifier,
 };
 use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView, ExecutionStatusView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView,
-    LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView,
-    SignedTransactionView, SplitStorageInfoView, StateChangesKindsView, StateChangesView,
-    TxExecutionStatus, TxStatusView,
+    BlockView, ChunkBalanceAuditView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    ExecutionStatusView, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
+    FinalExecutionOutcomeWithReceiptView, FinalExecutionStatus, GasPriceView, LightClientBlockView,
+    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, SignedTransactionView,
+    SimulatedTransactionView, SplitStorageInfoView, StateChangesKindsView, StateChangesView,
+    StorageSourceView, TxExecutionStatus, TxStatusView,
 };
 use near_store::flat::{FlatStorageReadyStatus, FlatStorageStatus};
 use near_store::{COLD_HEAD_KEY, DBCol, FINAL_HEAD_KEY, HEAD_KEY};
@@ -107,6 +112,27 @@ pub struct ViewClientActorInner {
     pub config: ClientConfig,
     request_manager: Arc<RwLock<ViewClientRequestManager>>,
     state_request_cache: Arc<Mutex<VecDeque<Instant>>>,
+    /// View of the split storage used for the blocks garbage collected from
+    /// the hot store.  Only set on archival nodes with split storage.
+    cold_store_view: Option<ColdStoreView>,
+}
+
+/// Epoch manager and runtime reading from the split storage, i.e. from the hot
+/// store falling back to the cold store.
+///
+/// Passed to the view client of archival nodes with split storage so that
+/// `query`, block and state changes requests about blocks garbage collected
+/// from the hot store can be answered from the cold store.
+#[derive(Clone)]
+pub struct ColdStoreFallback {
+    pub epoch_manager: Arc<dyn EpochManagerAdapter>,
+    pub runtime: Arc<dyn RuntimeAdapter>,
+}
+
+struct ColdStoreView {
+    chain: Chain,
+    epoch_manager: Arc<dyn EpochManagerAdapter>,
+    runtime: Arc<dyn RuntimeAdapter>,
 }
 
 impl ViewClientRequestManager {
@@ -134,6 +160,7 @@ impl ViewClientActorInner {
         network_adapter: PeerManagerAdapter,
         config: ClientConfig,
         adv: crate::adversarial::Controls,
+        cold_store_fallback: Option<ColdStoreFallback>,
     ) -> Addr<ViewClientActor> {
         SyncArbiter::start(config.view_client_threads, move || {
             let view_client_actor = ViewClientActorInner::new(
@@ -146,6 +173,7 @@ impl ViewClientActorInner {
                 network_adapter.clone(),
                 config.clone(),
                 adv.clone(),
+                cold_store_fallback.clone(),
             )
             .unwrap();
             SyncActixWrapper::new(view_client_actor)
@@ -162,6 +190,7 @@ impl ViewClientActorInner {
         network_adapter: PeerManagerAdapter,
         config: ClientConfig,
         adv: crate::adversarial::Controls,
+        cold_store_fallback: Option<ColdStoreFallback>,
     ) -> Result<Self, Error> {
         // TODO: should we create shared ChainStore that is passed to both Client and ViewClient?
         let chain = Chain::new_for_view_client(
@@ -173,6 +202,20 @@ impl ViewClientActorInner {
             DoomslugThresholdMode::TwoThirds,
             config.save_trie_changes,
         )?;
+        let cold_store_view = cold_store_fallback
+            .map(|ColdStoreFallback { epoch_manager, runtime }| -> Result<_, Error> {
+                let chain = Chain::new_for_view_client(
+                    clock.clone(),
+                    epoch_manager.clone(),
+                    shard_tracker.clone(),
+                    runtime.clone(),
+                    &chain_genesis,
+                    DoomslugThresholdMode::TwoThirds,
+                    config.save_trie_changes,
+                )?;
+                Ok(ColdStoreView { chain, epoch_manager, runtime })
+            })
+            .transpose()?;
         Ok(Self {
             clock,
             adv,
@@ -185,9 +228,40 @@ impl ViewClientActorInner {
             config,
             request_manager: Arc::new(RwLock::new(ViewClientRequestManager::new())),
             state_request_cache: Arc::new(Mutex::new(VecDeque::default())),
+            cold_store_view,
         })
     }
 
+    /// Returns which store serves the data of the block at given height or
+    /// `None` if the node doesn't have split storage.
+    ///
+    /// The blocks below the tail of the hot store were garbage collected and
+    /// their data is only available in the cold store.
+    fn storage_source(
+        &self,
+        block_height: BlockHeight,
+    ) -> Result<Option<StorageSourceView>, near_chain::Error> {
+        if self.cold_store_view.is_none() {
+            return Ok(None);
+        }
+        // BlockMisc is never copied to the cold store so this is the tail of
+        // the hot store even if the view client reads from the split storage.
+        let hot_tail = self.chain.tail()?;
+        Ok(Some(if block_height < hot_tail {
+            StorageSourceView::Cold
+        } else {
+            StorageSourceView::Hot
+        }))
+    }
+
+    /// Returns the chain reading from the given store.
+    fn chain_for(&self, source: Option<StorageSourceView>) -> &Chain {
+        match (source, &self.cold_store_view) {
+            (Some(StorageSourceView::Cold), Some(cold_store_view)) => &cold_store_view.chain,
+            _ => &self.chain,
+        }
+    }
+
     fn maybe_block_id_to_block_header(
         &self,
         block_id: MaybeBlockId,
@@ -364,23 +438,41 @@ impl ViewClientActorInner {
         }?;
 
         let account_id = match &msg.request {
-            QueryRequest::ViewAccount { account_id, .. } => account_id,
-            QueryRequest::ViewState { account_id, .. } => account_id,
-            QueryRequest::ViewAccessKey { account_id, .. } => account_id,
-            QueryRequest::ViewAccessKeyList { account_id, .. } => account_id,
-            QueryRequest::CallFunction { account_id, .. } => account_id,
-            QueryRequest::ViewCode { account_id, .. } => account_id,
+            QueryRequest::ViewAccount { account_id, .. } => Some(account_id),
+            QueryRequest::ViewState { account_id, .. } => Some(account_id),
+            QueryRequest::ViewAccessKey { account_id, .. } => Some(account_id),
+            QueryRequest::ViewAccessKeyList { account_id, .. } => Some(account_id),
+            QueryRequest::CallFunction { account_id, .. } => Some(account_id),
+            QueryRequest::ViewCode { account_id, .. } => Some(account_id),
+            QueryRequest::ViewGlobalContractCode { .. }
+            | QueryRequest::ViewGlobalContractCodeByAccountId { .. } => None,
+        };
+
+        let served_from = self
+            .storage_source(header.height())
+            .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
+        let (chain, epoch_manager, runtime) = match (served_from, &self.cold_store_view) {
+            (Some(StorageSourceView::Cold), Some(cold_store_view)) => (
+                &cold_store_view.chain,
+                cold_store_view.epoch_manager.as_ref(),
+                cold_store_view.runtime.as_ref(),
+            ),
+            _ => (&self.chain, self.epoch_manager.as_ref(), self.runtime.as_ref()),
         };
-        let shard_id =
-            account_id_to_shard_id(self.epoch_manager.as_ref(), account_id, header.epoch_id())
-                .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
-        let shard_uid =
-            shard_id_to_uid(self.epoch_manager.as_ref(), shard_id, header.epoch_id())
-                .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
+
+        let shard_id = match account_id {
+            Some(account_id) => {
+                account_id_to_shard_id(epoch_manager, account_id, header.epoch_id())
+                    .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?
+            }
+            None => self.global_contract_shard_id(epoch_manager, &header)?,
+        };
+        let shard_uid = shard_id_to_uid(epoch_manager, shard_id, header.epoch_id())
+            .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
 
         let tip = self.chain.head();
         let chunk_extra =
-            self.chain.get_chunk_extra(header.hash(), &shard_uid).map_err(|err| match err {
+            chain.get_chunk_extra(header.hash(), &shard_uid).map_err(|err| match err {
                 near_chain::near_chain_primitives::Error::DBNotFoundErr(_) => match tip {
                     Ok(tip) => {
                         let gc_stop_height = self.runtime.get_gc_stop_height(&tip.last_block_hash);
@@ -402,7 +494,7 @@ impl ViewClientActorInner {
             })?;
 
         let state_root = chunk_extra.state_root();
-        match self.runtime.query(
+        match runtime.query(
             shard_uid,
             state_root,
             header.height(),
@@ -412,7 +504,7 @@ impl ViewClientActorInner {
             header.epoch_id(),
             &msg.request,
         ) {
-            Ok(query_response) => Ok(query_response),
+            Ok(query_response) => Ok(QueryResponse { served_from, ..query_response }),
             Err(query_error) => Err(match query_error {
                 near_chain::near_chain_primitives::error::QueryError::InternalError {
                     error_message,
@@ -433,6 +525,11 @@ impl ViewClientActorInner {
                     block_height,
                     block_hash,
                 } => QueryError::NoContractCode { contract_account_id, block_height, block_hash },
+                near_chain::near_chain_primitives::error::QueryError::NoGlobalContractCode {
+                    identifier,
+                    block_height,
+                    block_hash,
+                } => QueryError::NoGlobalContractCode { identifier, block_height, block_hash },
                 near_chain::near_chain_primitives::error::QueryError::UnknownAccessKey {
                     public_key,
                     block_height,
@@ -460,6 +557,26 @@ impl ViewClientActorInner {
         }
     }
 
+    /// Returns the shard to read global contract code from.
+    ///
+    /// Global contracts are distributed to all the shards so the code can be
+    /// read from any shard tracked by the node.
+    fn global_contract_shard_id(
+        &self,
+        epoch_manager: &dyn EpochManagerAdapter,
+        header: &BlockHeader,
+    ) -> Result<ShardId, QueryError> {
+        let shard_ids = epoch_manager
+            .shard_ids(header.epoch_id())
+            .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
+        let tracked_shard_id = shard_ids.iter().copied().find(|shard_id| {
+            self.shard_tracker.cares_about_shard(None, header.prev_hash(), *shard_id, true)
+        });
+        // If the node doesn't track any shard, reading the state fails with
+        // `UnavailableShard` for the first one.
+        Ok(tracked_shard_id.unwrap_or(shard_ids[0]))
+    }
+
     // Return the lowest status the node can proof
     fn get_tx_execution_status(
         &self,
@@ -724,7 +841,16 @@ impl Handler<GetBlock> for ViewClientActorInner {
         tracing::debug!(target: "client", ?msg);
         let _timer =
             metrics::VIEW_CLIENT_MESSAGE_TIME.with_label_values(&["GetBlock"]).start_timer();
-        let block = self.get_block_by_reference(&msg.0)?.ok_or(GetBlockError::NotSyncedYet)?;
+        let block = match (self.get_block_by_reference(&msg.0), &self.cold_store_view) {
+            // Only the block headers are kept in the hot store for the garbage
+            // collected blocks.
+            (Err(near_chain::Error::DBNotFoundErr(_)), Some(cold_store_view)) => self
+                .get_block_header_by_reference(&msg.0)?
+                .map(|header| cold_store_view.chain.get_block(header.hash()))
+                .transpose()?,
+            (result, _) => result?,
+        };
+        let block = block.ok_or(GetBlockError::NotSyncedYet)?;
         let block_author = self
             .epoch_manager
             .get_block_producer(block.header().epoch_id(), block.header().height())
@@ -839,6 +965,38 @@ impl Handler<GetChunk> for ViewClientActorInner {
     }
 }
 
+impl Handler<GetChunkBalanceAudit> for ViewClientActorInner {
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: GetChunkBalanceAudit,
+    ) -> Result<ChunkBalanceAuditView, GetChunkBalanceAuditError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["GetChunkBalanceAudit"])
+            .start_timer();
+        let header = self
+            .get_block_header_by_reference(&msg.block_reference)?
+            .ok_or(GetChunkBalanceAuditError::NotSyncedYet)?;
+        let shard_layout =
+            self.epoch_manager.get_shard_layout(header.epoch_id()).into_chain_error()?;
+        if !shard_layout.shard_ids().any(|shard_id| shard_id == msg.shard_id) {
+            return Err(GetChunkBalanceAuditError::InvalidShardId { shard_id: msg.shard_id });
+        }
+        let unavailable = || GetChunkBalanceAuditError::UnavailableAudit {
+            block_hash: *header.hash(),
+            shard_id: msg.shard_id,
+        };
+        match self.chain.chain_store().get_chunk_apply_stats(header.hash(), &msg.shard_id)? {
+            Some(ChunkApplyStats::V1(stats)) => {
+                ChunkBalanceAuditView::from_stats(&stats).ok_or_else(unavailable)
+            }
+            // Stats stored before balance audits were introduced.
+            Some(ChunkApplyStats::V0(_)) | None => Err(unavailable()),
+        }
+    }
+}
+
 impl Handler<TxStatus> for ViewClientActorInner {
     #[perf]
     fn handle(&mut self, msg: TxStatus) -> Result<TxStatusView, TxStatusError> {
@@ -936,17 +1094,23 @@ impl Handler<GetStateChangesInBlock> for ViewClientActorInner {
 /// Returns a list of changes in a store for a given block filtering by the state changes request.
 impl Handler<GetStateChanges> for ViewClientActorInner {
     #[perf]
-    fn handle(&mut self, msg: GetStateChanges) -> Result<StateChangesView, GetStateChangesError> {
+    fn handle(
+        &mut self,
+        msg: GetStateChanges,
+    ) -> Result<GetStateChangesResponse, GetStateChangesError> {
         tracing::debug!(target: "client", ?msg);
         let _timer =
             metrics::VIEW_CLIENT_MESSAGE_TIME.with_label_values(&["GetStateChanges"]).start_timer();
-        Ok(self
-            .chain
+        let block_height = self.chain.get_block_header(&msg.block_hash)?.height();
+        let served_from = self.storage_source(block_height)?;
+        let changes = self
+            .chain_for(served_from)
             .chain_store()
             .get_state_changes(&msg.block_hash, &msg.state_changes_request.into())?
             .into_iter()
             .map(Into::into)
-            .collect())
+            .collect();
+        Ok(GetStateChangesResponse { changes, served_from })
     }
 }
 
@@ -989,6 +1153,9 @@ impl Handler<GetStateChangesWithCauseInBlockForTrackedShards> for ViewClientActo
         let mut state_changes_with_cause_split_by_shard_id: HashMap<ShardId, StateChangesView> =
             HashMap::new();
         for state_change_with_cause in state_changes_with_cause_in_block {
+            if msg.filter.as_ref().is_some_and(|filter| !filter.keeps(&state_change_with_cause)) {
+                continue;
+            }
             let account_id = state_change_with_cause.value.affected_account_id();
             let shard_id = match account_id_to_shard_id(
                 self.epoch_manager.as_ref(),
@@ -1185,6 +1352,88 @@ impl Handler<GetReceipt> for ViewClientActorInner {
     }
 }
 
+impl Handler<SimulateTransaction> for ViewClientActorInner {
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: SimulateTransaction,
+    ) -> Result<SimulatedTransactionView, SimulateTransactionError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["SimulateTransaction"])
+            .start_timer();
+        let head = self.chain.head()?;
+        let header = self.chain.get_block_header(&head.last_block_hash)?;
+        let shard_layout =
+            self.epoch_manager.get_shard_layout(header.epoch_id()).into_chain_error()?;
+        let mut state_roots = HashMap::new();
+        for shard_uid in shard_layout.shard_uids() {
+            match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => {
+                    state_roots.insert(shard_uid.shard_id(), *chunk_extra.state_root());
+                }
+                // The shard is not tracked.
+                Err(near_chain::Error::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let tx_hash = msg.signed_transaction.get_hash();
+        let transaction = SignedTransactionView::from(msg.signed_transaction.clone());
+        let result = self
+            .runtime
+            .simulate_transaction(
+                &header,
+                state_roots,
+                msg.signed_transaction,
+                msg.check_signature,
+                msg.trace_host_calls,
+            )
+            .map_err(|err| match err {
+                SimulationError::InvalidTransaction(err) => {
+                    SimulateTransactionError::InvalidTransaction(err)
+                }
+                SimulationError::UnavailableShard(shard_id) => {
+                    SimulateTransactionError::UnavailableShard(shard_id)
+                }
+                err @ (SimulationError::TooManyReceipts(_)
+                | SimulationError::GasLimitExceeded(_)
+                | SimulationError::Runtime(_)) => {
+                    SimulateTransactionError::SimulationFailed(err.to_string())
+                }
+            })?;
+
+        let outcome_view = |outcome: ExecutionOutcomeWithId| ExecutionOutcomeWithIdView {
+            proof: vec![],
+            block_hash: *header.hash(),
+            id: outcome.id,
+            outcome: outcome.outcome.into(),
+        };
+        let mut outcomes = vec![outcome_view(result.transaction_outcome)];
+        outcomes.extend(result.receipt_outcomes.into_iter().map(outcome_view));
+        let status = self.chain.get_execution_status(&outcomes, &tx_hash);
+        let receipts_outcome = outcomes.split_off(1);
+        let transaction_outcome = outcomes.pop().unwrap();
+        let state_changes = StateChanges::from_changes(result.state_changes.into_iter().map(Ok))
+            .map_err(|err| SimulateTransactionError::IOError(err.to_string()))?;
+        Ok(SimulatedTransactionView {
+            block_hash: *header.hash(),
+            block_height: header.height(),
+            outcome: FinalExecutionOutcomeWithReceiptView {
+                final_outcome: FinalExecutionOutcomeView {
+                    status,
+                    transaction,
+                    transaction_outcome,
+                    receipts_outcome,
+                },
+                receipts: result.receipts.into_iter().map(Into::into).collect(),
+            },
+            state_changes: state_changes.into_iter().map(Into::into).collect(),
+            host_calls: result.host_calls,
+        })
+    }
+}
+
 impl Handler<GetBlockProof> for ViewClientActorInner {
     #[perf]
     fn handle(&mut self, msg: GetBlockProof) -> Result<GetBlockProofResponse, GetBlockProofError> {
@@ -40,4 +40,8 @@ pub struct IndexerShard {
     pub chunk: Option<IndexerChunkView>,
     pub receipt_execution_outcomes: Vec<IndexerExecutionOutcomeWithReceipt>,
     pub state_changes: views::StateChangesView,
+    /// Balances moved by the chunk applied in this block, `None` if the shard
+    /// is not tracked or the audit is not available.
+    #[serde(default)]
+    pub balance_audit: Option<views::ChunkBalanceAuditView>,
 }
@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use near_client::StateChangesFilter;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::AccountId;
+use near_primitives::views::{ActionView, ReceiptEnumView, ReceiptView, SignedTransactionView};
+
+/// Pattern matched against account ids in [`IndexerFilter`].
+///
+/// Parsed from a string which is either `*` (matches any account), `*.suffix`
+/// (matches any sub-account of `suffix`, e.g. `*.sweat` matches
+/// `token.sweat` and `a.b.sweat` but not `sweat` itself) or an exact account
+/// id.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AccountPattern {
+    Any,
+    SubAccountOf(AccountId),
+    Exact(AccountId),
+}
+
+impl AccountPattern {
+    pub fn matches(&self, account_id: &AccountId) -> bool {
+        match self {
+            Self::Any => true,
+            Self::SubAccountOf(parent) => account_id
+                .as_str()
+                .strip_suffix(parent.as_str())
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+            Self::Exact(expected) => account_id == expected,
+        }
+    }
+}
+
+impl FromStr for AccountPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |account_id: &str| {
+            AccountId::from_str(account_id)
+                .map_err(|err| format!("invalid account pattern `{s}`: {err}"))
+        };
+        match s {
+            "*" => Ok(Self::Any),
+            _ => match s.strip_prefix("*.") {
+                Some(parent) => Ok(Self::SubAccountOf(parse(parent)?)),
+                None => Ok(Self::Exact(parse(s)?)),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for AccountPattern {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AccountPattern> for String {
+    fn from(pattern: AccountPattern) -> Self {
+        match pattern {
+            AccountPattern::Any => "*".to_string(),
+            AccountPattern::SubAccountOf(parent) => format!("*.{parent}"),
+            AccountPattern::Exact(account_id) => account_id.into(),
+        }
+    }
+}
+
+/// Kind of an action, used to filter transactions and receipts by the actions
+/// they contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    CreateAccount,
+    DeployContract,
+    FunctionCall,
+    Transfer,
+    Stake,
+    AddKey,
+    DeleteKey,
+    DeleteAccount,
+    Delegate,
+    DeployGlobalContract,
+    UseGlobalContract,
+}
+
+impl From<&ActionView> for ActionKind {
+    fn from(action: &ActionView) -> Self {
+        match action {
+            ActionView::CreateAccount => Self::CreateAccount,
+            ActionView::DeployContract { .. } => Self::DeployContract,
+            ActionView::FunctionCall { .. } => Self::FunctionCall,
+            ActionView::Transfer { .. } => Self::Transfer,
+            ActionView::Stake { .. } => Self::Stake,
+            ActionView::AddKey { .. } => Self::AddKey,
+            ActionView::DeleteKey { .. } => Self::DeleteKey,
+            ActionView::DeleteAccount { .. } => Self::DeleteAccount,
+            ActionView::Delegate { .. } => Self::Delegate,
+            ActionView::DeployGlobalContract { .. }
+            | ActionView::DeployGlobalContractByAccountId { .. } => Self::DeployGlobalContract,
+            ActionView::UseGlobalContract { .. }
+            | ActionView::UseGlobalContractByAccountId { .. } => Self::UseGlobalContract,
+        }
+    }
+}
+
+/// Filter applied by the streamer to every `StreamerMessage` before it is sent
+/// to the consumer.
+///
+/// Each non-empty list narrows down the matching transactions and receipts:
+/// the receiver has to match one of `receivers`, the signer has to match one of
+/// `signers`, at least one action has to be of one of `action_kinds` and at
+/// least one function call has to call one of `method_names`.  Data receipts
+/// have neither a signer nor actions so they only match if `signers`,
+/// `action_kinds` and `method_names` are all empty.
+///
+/// Transactions, receipts and execution outcomes which don't match are
+/// removed from the message.  State changes are kept if they were caused by
+/// a kept transaction or receipt or if they touch an account matching
+/// `receivers`.  The default filter is empty and keeps everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct IndexerFilter {
+    pub receivers: Vec<AccountPattern>,
+    pub signers: Vec<AccountPattern>,
+    pub action_kinds: Vec<ActionKind>,
+    pub method_names: Vec<String>,
+}
+
+impl IndexerFilter {
+    /// Returns whether the filter keeps everything.
+    pub fn is_empty(&self) -> bool {
+        self.receivers.is_empty()
+            && self.signers.is_empty()
+            && self.action_kinds.is_empty()
+            && self.method_names.is_empty()
+    }
+
+    pub fn matches_transaction(&self, transaction: &SignedTransactionView) -> bool {
+        self.matches_accounts(&transaction.receiver_id, Some(&transaction.signer_id))
+            && self.matches_actions(Some(transaction.actions.as_slice()))
+    }
+
+    pub fn matches_receipt(&self, receipt: &ReceiptView) -> bool {
+        let (signer_id, actions) = match &receipt.receipt {
+            ReceiptEnumView::Action { signer_id, actions, .. } => {
+                (Some(signer_id), Some(actions.as_slice()))
+            }
+            ReceiptEnumView::Data { .. } | ReceiptEnumView::GlobalContractDistribution { .. } => {
+                (None, None)
+            }
+        };
+        self.matches_accounts(&receipt.receiver_id, signer_id) && self.matches_actions(actions)
+    }
+
+    /// Returns whether a receipt sent to `receiver_id` may match the filter.
+    /// Used to leave out receipts before they are fetched.
+    pub(crate) fn matches_receiver(&self, receiver_id: &AccountId) -> bool {
+        self.receivers.is_empty() || matches_any(&self.receivers, receiver_id)
+    }
+
+    /// Returns the filter passed to the view client so that only the state
+    /// changes caused by `causes` or touching an account matching `receivers`
+    /// are fetched.
+    pub(crate) fn state_changes_filter(&self, causes: HashSet<CryptoHash>) -> StateChangesFilter {
+        let receivers = self.receivers.clone();
+        StateChangesFilter {
+            causes,
+            accounts: Box::new(move |account_id| matches_any(&receivers, account_id)),
+        }
+    }
+
+    fn matches_accounts(&self, receiver_id: &AccountId, signer_id: Option<&AccountId>) -> bool {
+        if !self.matches_receiver(receiver_id) {
+            return false;
+        }
+        if self.signers.is_empty() {
+            return true;
+        }
+        signer_id.is_some_and(|signer_id| matches_any(&self.signers, signer_id))
+    }
+
+    fn matches_actions(&self, actions: Option<&[ActionView]>) -> bool {
+        if self.action_kinds.is_empty() && self.method_names.is_empty() {
+            return true;
+        }
+        let Some(actions) = actions else {
+            return false;
+        };
+        let kind_matches = self.action_kinds.is_empty()
+            || actions.iter().any(|action| self.action_kinds.contains(&action.into()));
+        let method_matches = self.method_names.is_empty()
+            || actions.iter().any(|action| match action {
+                ActionView::FunctionCall { method_name, .. } => {
+                    self.method_names.contains(method_name)
+                }
+                _ => false,
+            });
+        kind_matches && method_matches
+    }
+}
+
+fn matches_any(patterns: &[AccountPattern], account_id: &AccountId) -> bool {
+    patterns.iter().any(|pattern| pattern.matches(account_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_account_pattern() {
+        let pattern: AccountPattern = "*.sweat".parse().unwrap();
+        assert!(pattern.matches(&account("token.sweat")));
+        assert!(pattern.matches(&account("a.b.sweat")));
+        assert!(!pattern.matches(&account("sweat")));
+        assert!(!pattern.matches(&account("nosweat")));
+
+        let pattern: AccountPattern = "alice.near".parse().unwrap();
+        assert!(pattern.matches(&account("alice.near")));
+        assert!(!pattern.matches(&account("bob.alice.near")));
+
+        assert!(AccountPattern::Any.matches(&account("anything.near")));
+        assert!("*.".parse::<AccountPattern>().is_err());
+        assert!("Invalid Account".parse::<AccountPattern>().is_err());
+    }
+
+    #[test]
+    fn test_filter_serde() {
+        let filter: IndexerFilter = serde_json::from_value(serde_json::json!({
+            "receivers": ["*.sweat", "alice.near"],
+            "action_kinds": ["function_call"],
+        }))
+        .unwrap();
+        assert_eq!(
+            filter,
+            IndexerFilter {
+                receivers: vec![
+                    AccountPattern::SubAccountOf(account("sweat")),
+                    AccountPattern::Exact(account("alice.near"))
+                ],
+                signers: vec![],
+                action_kinds: vec![ActionKind::FunctionCall],
+                method_names: vec![],
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&filter.receivers).unwrap(),
+            serde_json::json!(["*.sweat", "alice.near"])
+        );
+    }
+
+    #[test]
+    fn test_matches_actions() {
+        let function_call = ActionView::FunctionCall {
+            method_name: "ft_transfer".to_string(),
+            args: Vec::<u8>::new().into(),
+            gas: 0,
+            deposit: 0,
+        };
+        let transfer = ActionView::Transfer { deposit: 1 };
+
+        let filter = IndexerFilter {
+            action_kinds: vec![ActionKind::FunctionCall],
+            method_names: vec!["ft_transfer".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches_actions(Some(&[transfer.clone(), function_call][..])));
+        assert!(!filter.matches_actions(Some(&[transfer][..])));
+        assert!(!filter.matches_actions(None));
+        assert!(IndexerFilter::default().matches_actions(None));
+    }
+}
@@ -1,12 +1,15 @@
 #![doc = include_str!("../README.md")]
 
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
 use anyhow::Context;
 use near_config_utils::DownloadConfigType;
 use tokio::sync::mpsc;
 
 use near_chain_configs::GenesisValidationMode;
 pub use near_primitives;
-use near_primitives::types::{Finality, Gas};
+use near_primitives::types::{BlockHeight, Finality, Gas};
 pub use nearcore::{NearConfig, get_default_home, init_configs};
 
 pub use near_indexer_primitives::{
@@ -15,9 +18,11 @@ pub use near_indexer_primitives::{
     StreamerMessage,
 };
 
+pub use filter::{AccountPattern, ActionKind, IndexerFilter};
 use near_epoch_manager::shard_tracker::ShardTracker;
-pub use streamer::build_streamer_message;
+pub use streamer::{AckHandle, build_streamer_message};
 
+mod filter;
 mod streamer;
 
 pub const INDEXER: &str = "indexer";
@@ -59,7 +64,9 @@ pub struct InitConfigArgs {
 pub enum SyncModeEnum {
     /// Real-time syncing, always taking the latest finalized block to stream
     LatestSynced,
-    /// Starts syncing from the block NEAR Indexer was interrupted last time
+    /// Starts syncing right after the last block acknowledged via [`AckHandle`]
+    /// or, if nothing was ever acknowledged, from the block NEAR Indexer was
+    /// interrupted last time
     FromInterruption,
     /// Specific block height to start syncing from
     BlockHeight(u64),
@@ -87,6 +94,9 @@ pub struct IndexerConfig {
     pub finality: Finality,
     /// Tells whether to validate the genesis file before starting
     pub validate_genesis: bool,
+    /// Prunes the streamed messages down to the matching transactions,
+    /// receipts, outcomes and state changes (keeps everything by default)
+    pub filter: IndexerFilter,
 }
 
 /// This is the core component, which handles `nearcore` and internal `streamer`.
@@ -97,6 +107,8 @@ pub struct Indexer {
     client: actix::Addr<near_client::ClientActor>,
     tx_processor: actix::Addr<near_client::TxRequestHandlerActor>,
     shard_tracker: ShardTracker,
+    db: Arc<rocksdb::DB>,
+    ack_handle: AckHandle,
 }
 
 impl Indexer {
@@ -127,7 +139,18 @@ impl Indexer {
         let nearcore::NearNode { client, view_client, tx_processor, shard_tracker, .. } =
             nearcore::start_with_config(&indexer_config.home_dir, near_config.clone())
                 .with_context(|| "start_with_config")?;
-        Ok(Self { view_client, client, tx_processor, near_config, indexer_config, shard_tracker })
+        let db = streamer::open_indexer_db(&indexer_config.home_dir, &near_config.config.store);
+        let ack_handle = AckHandle::new(db.clone());
+        Ok(Self {
+            view_client,
+            client,
+            tx_processor,
+            near_config,
+            indexer_config,
+            shard_tracker,
+            db,
+            ack_handle,
+        })
     }
 
     /// Boots up `near_indexer::streamer`, so it monitors the new blocks with chunks, transactions, receipts, and execution outcomes inside. The returned stream handler should be drained and handled on the user side.
@@ -138,7 +161,29 @@ impl Indexer {
             self.client.clone(),
             self.shard_tracker.clone(),
             self.indexer_config.clone(),
-            self.near_config.config.store.clone(),
+            self.db.clone(),
+            sender,
+        ));
+        receiver
+    }
+
+    /// Returns the handle through which the consumer acknowledges the handled
+    /// `StreamerMessage`s so that streaming resumes after them on restart.
+    pub fn ack_handle(&self) -> AckHandle {
+        self.ack_handle.clone()
+    }
+
+    /// Streams the blocks in given range of heights once, e.g. to reprocess
+    /// them after a bug in the consumer got fixed.  The stream ends after the
+    /// last block of the range.  Replaying doesn't affect the main stream nor
+    /// the acknowledged height.
+    pub fn replay(&self, heights: RangeInclusive<BlockHeight>) -> mpsc::Receiver<StreamerMessage> {
+        let (sender, receiver) = mpsc::channel(100);
+        actix::spawn(streamer::replay(
+            self.view_client.clone(),
+            self.shard_tracker.clone(),
+            self.indexer_config.filter.clone(),
+            heights,
             sender,
         ));
         receiver
@@ -182,5 +227,6 @@ pub fn indexer_init_configs(
         params.download_config_url.as_deref(),
         params.boot_nodes.as_deref(),
         params.max_gas_burnt_view,
+        None,
     )
 }
@@ -0,0 +1,132 @@
+use std::sync::{Arc, Mutex};
+
+use near_primitives::types::BlockHeight;
+use rocksdb::{DB, WriteOptions};
+
+use super::metrics;
+
+/// Height of the last block the streamer has fetched.
+pub(crate) const LAST_SYNCED_BLOCK_HEIGHT_KEY: &[u8] = b"last_synced_block_height";
+/// Height of the last block the consumer has acknowledged.
+const LAST_ACKED_BLOCK_HEIGHT_KEY: &[u8] = b"last_acked_block_height";
+
+/// Opens the indexer db which lives next to the node's database.
+pub(crate) fn open_indexer_db(
+    home_dir: &std::path::Path,
+    store_config: &near_store::StoreConfig,
+) -> Arc<DB> {
+    let indexer_db_path =
+        near_store::NodeStorage::opener(home_dir, store_config, None).path().join("indexer");
+    match DB::open_default(indexer_db_path) {
+        Ok(db) => Arc::new(db),
+        Err(err) => panic!("Unable to open indexer db: {:?}", err),
+    }
+}
+
+/// Reads a block height stored under given key in the indexer db.
+fn read_height(db: &DB, key: &[u8]) -> Option<BlockHeight> {
+    db.get(key).unwrap().map(|value| String::from_utf8(value).unwrap().parse().unwrap())
+}
+
+/// Returns the height of the last block acknowledged by the consumer.
+pub(crate) fn last_acked_height(db: &DB) -> Option<BlockHeight> {
+    read_height(db, LAST_ACKED_BLOCK_HEIGHT_KEY)
+}
+
+/// Returns the height [`crate::SyncModeEnum::FromInterruption`] resumes from:
+/// right after the last block acknowledged by the consumer.  Consumers which
+/// never acknowledge anything resume from the last block the streamer has
+/// fetched.
+pub(crate) fn resume_height(db: &DB, latest_block_height: BlockHeight) -> BlockHeight {
+    match last_acked_height(db) {
+        Some(last_acked_height) => last_acked_height + 1,
+        None => read_height(db, LAST_SYNCED_BLOCK_HEIGHT_KEY).unwrap_or(latest_block_height),
+    }
+}
+
+/// Handle through which the consumer acknowledges the `StreamerMessage`s it
+/// has finished handling.
+///
+/// The last acknowledged height is persisted in the indexer db and with
+/// [`crate::SyncModeEnum::FromInterruption`] the streaming resumes right after
+/// it.  Acknowledging a height implies that all the messages streamed before
+/// it were handled as well.  Acknowledging the height only once the effects of
+/// handling the message are durably stored gives exactly-once processing
+/// across restarts.
+#[derive(Clone)]
+pub struct AckHandle {
+    db: Arc<DB>,
+    last_acked_height: Arc<Mutex<Option<BlockHeight>>>,
+}
+
+impl AckHandle {
+    pub(crate) fn new(db: Arc<DB>) -> Self {
+        let last_acked_height = last_acked_height(&db);
+        Self { db, last_acked_height: Arc::new(Mutex::new(last_acked_height)) }
+    }
+
+    /// Acknowledges that the `StreamerMessage` for the block at given height
+    /// has been handled.
+    ///
+    /// Acknowledging a height not greater than the last acknowledged one (e.g.
+    /// while handling a replay) is a no-op.  The height is written with a
+    /// synced write so it runs on the blocking thread pool.
+    pub async fn ack(&self, height: BlockHeight) -> Result<(), rocksdb::Error> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.ack_blocking(height))
+            .await
+            .expect("the acknowledging task panicked")
+    }
+
+    /// Same as [`Self::ack`] but blocks the current thread.  Must not be
+    /// called from an async context.
+    pub fn ack_blocking(&self, height: BlockHeight) -> Result<(), rocksdb::Error> {
+        let mut last_acked_height = self.last_acked_height.lock().unwrap();
+        if last_acked_height.is_some_and(|last_acked_height| last_acked_height >= height) {
+            return Ok(());
+        }
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(true);
+        self.db.put_opt(LAST_ACKED_BLOCK_HEIGHT_KEY, height.to_string(), &write_options)?;
+        *last_acked_height = Some(height);
+        metrics::LAST_ACKED_BLOCK_HEIGHT.set(height as i64);
+        Ok(())
+    }
+
+    /// Returns the height of the last acknowledged block if any.
+    pub fn last_acked_height(&self) -> Option<BlockHeight> {
+        *self.last_acked_height.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_db(dir: &tempfile::TempDir) -> Arc<DB> {
+        Arc::new(DB::open_default(dir.path()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_ack_persists_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open_db(&dir);
+        db.put(LAST_SYNCED_BLOCK_HEIGHT_KEY, "20").unwrap();
+        // Without acknowledgements the streaming resumes from the last
+        // fetched block.
+        assert_eq!(resume_height(&db, 30), 20);
+
+        let ack_handle = AckHandle::new(db.clone());
+        ack_handle.ack(10).await.unwrap();
+        ack_handle.ack(12).await.unwrap();
+        // Acknowledging an older height doesn't move the checkpoint back.
+        ack_handle.ack(11).await.unwrap();
+        assert_eq!(ack_handle.last_acked_height(), Some(12));
+        drop(ack_handle);
+        drop(db);
+
+        let db = open_db(&dir);
+        assert_eq!(AckHandle::new(db.clone()).last_acked_height(), Some(12));
+        assert_eq!(resume_height(&db, 30), 13);
+    }
+}
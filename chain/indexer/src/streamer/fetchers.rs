@@ -73,16 +73,23 @@ pub(crate) async fn fetch_block(
         .map_err(|err| FailedToFetchData::String(err.to_string()))
 }
 
+/// Fetches the state changes of the given block split by shard.  If `filter`
+/// is set the view client leaves out the state changes it doesn't keep.
 pub(crate) async fn fetch_state_changes(
     client: &Addr<near_client::ViewClientActor>,
     block_hash: CryptoHash,
     epoch_id: near_primitives::types::EpochId,
+    filter: Option<near_client::StateChangesFilter>,
 ) -> Result<HashMap<near_primitives::types::ShardId, views::StateChangesView>, FailedToFetchData> {
     tracing::debug!(target: INDEXER, "Fetching state changes for block: {}, epoch_id: {:?}", block_hash, epoch_id);
     client
         .send(
-            near_client::GetStateChangesWithCauseInBlockForTrackedShards { block_hash, epoch_id }
-                .with_span_context(),
+            near_client::GetStateChangesWithCauseInBlockForTrackedShards {
+                block_hash,
+                epoch_id,
+                filter,
+            }
+            .with_span_context(),
         )
         .await?
         .map_err(|err| FailedToFetchData::String(err.to_string()))
@@ -90,9 +97,12 @@ pub(crate) async fn fetch_state_changes(
 
 /// Fetch all ExecutionOutcomeWithId for current block
 /// Returns a HashMap where the key is shard id IndexerExecutionOutcomeWithOptionalReceipt
+/// Outcomes for which `keep_outcome` returns false are left out and their
+/// receipts are not fetched.
 pub(crate) async fn fetch_outcomes(
     client: &Addr<near_client::ViewClientActor>,
     block_hash: CryptoHash,
+    keep_outcome: impl Fn(&views::ExecutionOutcomeWithIdView) -> bool,
 ) -> Result<
     HashMap<near_primitives::types::ShardId, Vec<IndexerExecutionOutcomeWithOptionalReceipt>>,
     FailedToFetchData,
@@ -111,6 +121,9 @@ pub(crate) async fn fetch_outcomes(
         tracing::debug!(target: INDEXER, "Fetching outcomes with receipts for shard: {}", shard_id);
         let mut outcomes_with_receipts: Vec<IndexerExecutionOutcomeWithOptionalReceipt> = vec![];
         for outcome in shard_outcomes {
+            if !keep_outcome(&outcome) {
+                continue;
+            }
             let receipt = match fetch_receipt_by_id(&client, outcome.id).await {
                 Ok(res) => res,
                 Err(e) => {
@@ -182,6 +195,36 @@ pub(crate) async fn fetch_block_new_chunks(
     Ok(chunks)
 }
 
+/// Fetches the balance audits of the chunks applied in the given block for the
+/// tracked shards.  Audits which are not available, e.g. because the block was
+/// applied by an older version of the node or without `audit_chunk_balances`,
+/// are left out.
+pub(crate) async fn fetch_balance_audits(
+    client: &Addr<near_client::ViewClientActor>,
+    block: &views::BlockView,
+    shard_tracker: &ShardTracker,
+) -> Result<HashMap<types::ShardId, views::ChunkBalanceAuditView>, FailedToFetchData> {
+    tracing::debug!(target: INDEXER, "Fetching balance audits for block #{}", block.header.height);
+    let mut balance_audits = HashMap::new();
+    for chunk in &block.chunks {
+        if !shard_tracker.cares_about_shard(None, &block.header.prev_hash, chunk.shard_id, false) {
+            continue;
+        }
+        let request = near_client::GetChunkBalanceAudit {
+            block_reference: types::BlockId::Hash(block.header.hash).into(),
+            shard_id: chunk.shard_id,
+        };
+        match client.send(request.with_span_context()).await? {
+            Ok(balance_audit) => {
+                balance_audits.insert(chunk.shard_id, balance_audit);
+            }
+            Err(near_client::GetChunkBalanceAuditError::UnavailableAudit { .. }) => {}
+            Err(err) => return Err(FailedToFetchData::String(err.to_string())),
+        }
+    }
+    Ok(balance_audits)
+}
+
 pub(crate) async fn fetch_protocol_config(
     client: &Addr<near_client::ViewClientActor>,
     block_hash: near_primitives::hash::CryptoHash,
@@ -28,6 +28,14 @@ pub(crate) static CURRENT_BLOCK_HEIGHT: LazyLock<IntGauge> = LazyLock::new(|| {
     .unwrap()
 });
 
+pub(crate) static LAST_ACKED_BLOCK_HEIGHT: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_indexer_last_acked_block_height",
+        "Height of the last block acknowledged by the consumer",
+    )
+    .unwrap()
+});
+
 pub(crate) static NUM_STREAMER_MESSAGES_SENT: LazyLock<IntCounter> = LazyLock::new(|| {
     try_create_int_counter(
         "near_indexer_num_streamer_messages_sent",
@@ -1,4 +1,5 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
@@ -15,24 +16,30 @@ use near_indexer_primitives::{
 };
 use near_parameters::RuntimeConfig;
 use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
 use near_primitives::views;
 
+use self::checkpoint::LAST_SYNCED_BLOCK_HEIGHT_KEY;
 use self::errors::FailedToFetchData;
 use self::fetchers::{
-    fetch_block, fetch_block_by_height, fetch_block_new_chunks, fetch_latest_block, fetch_outcomes,
-    fetch_state_changes, fetch_status,
+    fetch_balance_audits, fetch_block, fetch_block_by_height, fetch_block_new_chunks,
+    fetch_latest_block, fetch_outcomes, fetch_state_changes, fetch_status,
 };
 use self::utils::convert_transactions_sir_into_local_receipts;
 use crate::INDEXER;
 use crate::streamer::fetchers::fetch_protocol_config;
-use crate::{AwaitForNodeSyncedEnum, IndexerConfig};
+use crate::{AwaitForNodeSyncedEnum, IndexerConfig, IndexerFilter};
 use near_epoch_manager::shard_tracker::ShardTracker;
 
+mod checkpoint;
 mod errors;
 mod fetchers;
 mod metrics;
 mod utils;
 
+pub use self::checkpoint::AckHandle;
+pub(crate) use self::checkpoint::open_indexer_db;
+
 static DELAYED_LOCAL_RECEIPTS_CACHE: std::sync::LazyLock<
     Arc<RwLock<HashMap<CryptoHash, views::ReceiptView>>>,
 > = std::sync::LazyLock::new(|| Arc::new(RwLock::new(HashMap::new())));
@@ -76,6 +83,17 @@ pub async fn build_streamer_message(
     client: &Addr<near_client::ViewClientActor>,
     block: views::BlockView,
     shard_tracker: &ShardTracker,
+) -> Result<StreamerMessage, FailedToFetchData> {
+    build_filtered_streamer_message(client, block, shard_tracker, &IndexerFilter::default()).await
+}
+
+/// Same as [`build_streamer_message`] but leaves out the transactions,
+/// receipts, outcomes and state changes not matching the `filter`.
+pub(crate) async fn build_filtered_streamer_message(
+    client: &Addr<near_client::ViewClientActor>,
+    block: views::BlockView,
+    shard_tracker: &ShardTracker,
+    filter: &IndexerFilter,
 ) -> Result<StreamerMessage, FailedToFetchData> {
     let _timer = metrics::BUILD_STREAMER_MESSAGE_TIME.start_timer();
     let chunks = fetch_block_new_chunks(&client, &block, shard_tracker).await?;
@@ -86,19 +104,22 @@ pub async fn build_streamer_message(
     let runtime_config_store = near_parameters::RuntimeConfigStore::new(None);
     let runtime_config = runtime_config_store.get_config(protocol_config_view.protocol_version);
 
-    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash).await?;
-    let mut state_changes = fetch_state_changes(
-        &client,
-        block.header.hash,
-        near_primitives::types::EpochId(block.header.epoch_id),
-    )
+    let transaction_hashes = chunks
+        .iter()
+        .flat_map(|chunk| chunk.transactions.iter().map(|transaction| transaction.hash))
+        .collect::<HashSet<_>>();
+    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash, |outcome| {
+        keeps_outcome(filter, &transaction_hashes, outcome)
+    })
     .await?;
+    let mut balance_audits = fetch_balance_audits(&client, &block, shard_tracker).await?;
     let mut indexer_shards = shard_ids
         .map(|shard_id| IndexerShard {
             shard_id,
             chunk: None,
             receipt_execution_outcomes: vec![],
-            state_changes: state_changes.remove(&shard_id).unwrap_or_default(),
+            state_changes: vec![],
+            balance_audit: balance_audits.remove(&shard_id),
         })
         .collect::<Vec<_>>();
 
@@ -115,7 +136,7 @@ pub async fn build_streamer_message(
             .map(|outcome| (outcome.execution_outcome.id, outcome))
             .collect::<BTreeMap<_, _>>();
         debug_assert_eq!(outcomes.len(), outcome_count);
-        let indexer_transactions = transactions
+        let mut indexer_transactions = transactions
             .into_iter()
             .filter_map(|transaction| {
                 let outcome = outcomes.remove(&transaction.hash)?;
@@ -142,7 +163,9 @@ pub async fn build_streamer_message(
             if let Some(outcome) = receipt_outcomes.get_mut(&receipt.receipt_id) {
                 debug_assert!(outcome.receipt.is_none());
                 outcome.receipt = Some(receipt.clone());
-            } else {
+            } else if filter.matches_receiver(&receipt.receiver_id) {
+                // Outcomes of receipts to other receivers are never fetched so
+                // there is nothing to cache their receipts for.
                 if let Ok(mut cache) = DELAYED_LOCAL_RECEIPTS_CACHE.write() {
                     cache.insert(receipt.receipt_id, receipt.clone());
                 } else {
@@ -155,6 +178,10 @@ pub async fn build_streamer_message(
             }
         }
 
+        // The local receipts are needed for all the outcomes so the
+        // transactions can only be pruned once they are converted.
+        indexer_transactions.retain(|tx| filter.matches_transaction(&tx.transaction));
+
         let mut chunk_receipts = chunk_local_receipts;
 
         let mut receipt_execution_outcomes: Vec<IndexerExecutionOutcomeWithReceipt> = vec![];
@@ -204,6 +231,9 @@ pub async fn build_streamer_message(
                     .await?
                 }
             };
+            if !filter.matches_receipt(&receipt) {
+                continue;
+            }
             receipt_execution_outcomes
                 .push(IndexerExecutionOutcomeWithReceipt { execution_outcome, receipt });
         }
@@ -232,6 +262,7 @@ pub async fn build_streamer_message(
         }
 
         chunk_receipts.extend(chunk_non_local_receipts);
+        chunk_receipts.retain(|receipt| filter.matches_receipt(receipt));
 
         // Find the shard index for the chunk by shard_id
         let shard_index = protocol_config_view
@@ -262,17 +293,64 @@ pub async fn build_streamer_message(
             continue;
         };
 
-        indexer_shards[shard_index].receipt_execution_outcomes.extend(outcomes.into_iter().map(
-            |outcome| IndexerExecutionOutcomeWithReceipt {
-                execution_outcome: outcome.execution_outcome,
-                receipt: outcome.receipt.expect("`receipt` must be present at this moment"),
-            },
-        ))
+        indexer_shards[shard_index].receipt_execution_outcomes.extend(
+            outcomes
+                .into_iter()
+                .map(|outcome| IndexerExecutionOutcomeWithReceipt {
+                    execution_outcome: outcome.execution_outcome,
+                    receipt: outcome.receipt.expect("`receipt` must be present at this moment"),
+                })
+                .filter(|outcome| filter.matches_receipt(&outcome.receipt)),
+        )
+    }
+
+    // The state changes are fetched last so that the view client only
+    // returns the ones caused by the transactions and receipts kept above.
+    let state_changes_filter = (!filter.is_empty())
+        .then(|| filter.state_changes_filter(state_change_causes(&indexer_shards)));
+    let mut state_changes = fetch_state_changes(
+        &client,
+        block.header.hash,
+        near_primitives::types::EpochId(block.header.epoch_id),
+        state_changes_filter,
+    )
+    .await?;
+    for shard in &mut indexer_shards {
+        shard.state_changes = state_changes.remove(&shard.shard_id).unwrap_or_default();
     }
 
     Ok(StreamerMessage { block, shards: indexer_shards })
 }
 
+/// Returns whether the outcome has to be fetched for a message filtered by
+/// `filter`.  The outcomes of the block's transactions are always needed to
+/// build the transactions.  Receipts are executed by their receiver so the
+/// other outcomes are only needed if the executor may match the filter.
+fn keeps_outcome(
+    filter: &IndexerFilter,
+    transaction_hashes: &HashSet<CryptoHash>,
+    outcome: &views::ExecutionOutcomeWithIdView,
+) -> bool {
+    transaction_hashes.contains(&outcome.id)
+        || filter.matches_receiver(&outcome.outcome.executor_id)
+}
+
+/// Returns the hashes of the transactions and receipts kept in the shards.
+/// The state changes caused by them are kept by the filter.
+fn state_change_causes(shards: &[IndexerShard]) -> HashSet<CryptoHash> {
+    let mut causes = HashSet::new();
+    for shard in shards {
+        if let Some(chunk) = &shard.chunk {
+            causes.extend(chunk.transactions.iter().map(|tx| tx.transaction.hash));
+            causes.extend(chunk.receipts.iter().map(|receipt| receipt.receipt_id));
+        }
+        causes.extend(
+            shard.receipt_execution_outcomes.iter().map(|outcome| outcome.receipt.receipt_id),
+        );
+    }
+    causes
+}
+
 // Receipt might be missing only in case of delayed local receipt
 // that appeared in some of the previous blocks
 // we will be iterating over previous blocks until we found the receipt
@@ -340,7 +418,7 @@ async fn find_local_receipt_by_id_in_block(
 ) -> Result<Option<views::ReceiptView>, FailedToFetchData> {
     let chunks = fetch_block_new_chunks(&client, &block, shard_tracker).await?;
 
-    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash).await?;
+    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash, |_| true).await?;
 
     for chunk in chunks {
         let views::ChunkView { header, transactions, .. } = chunk;
@@ -375,6 +453,45 @@ async fn find_local_receipt_by_id_in_block(
     Ok(None)
 }
 
+/// Builds the `StreamerMessage` for the block at given height and sends it to
+/// the listener.  Blocks which are missing or can't be built are skipped.
+///
+/// Returns `false` if the listener has gone away and streaming should stop.
+async fn stream_block(
+    view_client: &Addr<near_client::ViewClientActor>,
+    shard_tracker: &ShardTracker,
+    filter: &IndexerFilter,
+    block_height: BlockHeight,
+    blocks_sink: &mpsc::Sender<StreamerMessage>,
+) -> bool {
+    metrics::CURRENT_BLOCK_HEIGHT.set(block_height as i64);
+    let Ok(block) = fetch_block_by_height(view_client, block_height).await else {
+        return true;
+    };
+    let response = build_filtered_streamer_message(view_client, block, shard_tracker, filter).await;
+    match response {
+        Ok(streamer_message) => {
+            debug!(target: INDEXER, "Sending streamer message for block #{} to the listener", streamer_message.block.header.height);
+            if blocks_sink.send(streamer_message).await.is_err() {
+                error!(
+                    target: INDEXER,
+                    "Unable to send StreamerMessage to listener, listener doesn't listen. terminating..."
+                );
+                return false;
+            }
+            metrics::NUM_STREAMER_MESSAGES_SENT.inc();
+        }
+        Err(err) => {
+            debug!(
+                target: INDEXER,
+                "Missing data, skipping block #{}...", block_height
+            );
+            debug!(target: INDEXER, "{:#?}", err);
+        }
+    }
+    true
+}
+
 /// Function that starts Streamer's busy loop. Every half a seconds it fetches the status
 /// compares to already fetched block height and in case it differs fetches new block of given height.
 ///
@@ -384,21 +501,11 @@ pub(crate) async fn start(
     client: Addr<near_client::ClientActor>,
     shard_tracker: ShardTracker,
     indexer_config: IndexerConfig,
-    store_config: near_store::StoreConfig,
+    db: Arc<DB>,
     blocks_sink: mpsc::Sender<StreamerMessage>,
 ) {
     info!(target: INDEXER, "Starting Streamer...");
-    let indexer_db_path =
-        near_store::NodeStorage::opener(&indexer_config.home_dir, &store_config, None)
-            .path()
-            .join("indexer");
-
-    let db = match DB::open_default(indexer_db_path) {
-        Ok(db) => db,
-        Err(err) => panic!("Unable to open indexer db: {:?}", err),
-    };
-
-    let mut last_synced_block_height: Option<near_primitives::types::BlockHeight> = None;
+    let mut last_synced_block_height: Option<BlockHeight> = None;
 
     'main: loop {
         time::sleep(INTERVAL).await;
@@ -422,22 +529,18 @@ pub(crate) async fn start(
             };
 
         let latest_block_height = block.header.height;
-        let start_syncing_block_height = if let Some(last_synced_block_height) =
-            last_synced_block_height
-        {
-            last_synced_block_height + 1
-        } else {
-            match indexer_config.sync_mode {
-                crate::SyncModeEnum::FromInterruption => {
-                    match db.get(b"last_synced_block_height").unwrap() {
-                        Some(value) => String::from_utf8(value).unwrap().parse::<u64>().unwrap(),
-                        None => latest_block_height,
+        let start_syncing_block_height =
+            if let Some(last_synced_block_height) = last_synced_block_height {
+                last_synced_block_height + 1
+            } else {
+                match indexer_config.sync_mode {
+                    crate::SyncModeEnum::FromInterruption => {
+                        checkpoint::resume_height(&db, latest_block_height)
                     }
+                    crate::SyncModeEnum::LatestSynced => latest_block_height,
+                    crate::SyncModeEnum::BlockHeight(height) => height,
                 }
-                crate::SyncModeEnum::LatestSynced => latest_block_height,
-                crate::SyncModeEnum::BlockHeight(height) => height,
-            }
-        };
+            };
 
         debug!(
             target: INDEXER,
@@ -448,34 +551,135 @@ pub(crate) async fn start(
         metrics::START_BLOCK_HEIGHT.set(start_syncing_block_height as i64);
         metrics::LATEST_BLOCK_HEIGHT.set(latest_block_height as i64);
         for block_height in start_syncing_block_height..=latest_block_height {
-            metrics::CURRENT_BLOCK_HEIGHT.set(block_height as i64);
-            if let Ok(block) = fetch_block_by_height(&view_client, block_height).await {
-                let response = build_streamer_message(&view_client, block, &shard_tracker).await;
-
-                match response {
-                    Ok(streamer_message) => {
-                        debug!(target: INDEXER, "Sending streamer message for block #{} to the listener", streamer_message.block.header.height);
-                        if blocks_sink.send(streamer_message).await.is_err() {
-                            error!(
-                                target: INDEXER,
-                                "Unable to send StreamerMessage to listener, listener doesn't listen. terminating..."
-                            );
-                            break 'main;
-                        } else {
-                            metrics::NUM_STREAMER_MESSAGES_SENT.inc();
-                        }
-                    }
-                    Err(err) => {
-                        debug!(
-                            target: INDEXER,
-                            "Missing data, skipping block #{}...", block_height
-                        );
-                        debug!(target: INDEXER, "{:#?}", err);
-                    }
-                }
+            if !stream_block(
+                &view_client,
+                &shard_tracker,
+                &indexer_config.filter,
+                block_height,
+                &blocks_sink,
+            )
+            .await
+            {
+                break 'main;
             }
-            db.put(b"last_synced_block_height", &block_height.to_string()).unwrap();
+            db.put(LAST_SYNCED_BLOCK_HEIGHT_KEY, &block_height.to_string()).unwrap();
             last_synced_block_height = Some(block_height);
         }
     }
 }
+
+/// Streams the blocks in given range of heights once and stops.
+///
+/// Unlike [`start`] this neither waits for new blocks nor touches the persisted
+/// heights so it can run alongside the main stream.
+pub(crate) async fn replay(
+    view_client: Addr<near_client::ViewClientActor>,
+    shard_tracker: ShardTracker,
+    filter: IndexerFilter,
+    heights: RangeInclusive<BlockHeight>,
+    blocks_sink: mpsc::Sender<StreamerMessage>,
+) {
+    info!(target: INDEXER, "Replaying blocks #{} to #{}...", heights.start(), heights.end());
+    for block_height in heights {
+        if !stream_block(&view_client, &shard_tracker, &filter, block_height, &blocks_sink).await {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+    use near_primitives::types::{
+        ShardId, StateChangeCause, StateChangeValue, StateChangeWithCause,
+    };
+
+    fn outcome(id: CryptoHash, executor_id: &str) -> views::ExecutionOutcomeWithIdView {
+        views::ExecutionOutcomeWithIdView {
+            proof: vec![],
+            block_hash: CryptoHash::default(),
+            id,
+            outcome: views::ExecutionOutcomeView {
+                logs: vec![],
+                receipt_ids: vec![],
+                gas_burnt: 0,
+                tokens_burnt: 0,
+                executor_id: executor_id.parse().unwrap(),
+                status: views::ExecutionStatusView::Unknown,
+                metadata: Default::default(),
+            },
+        }
+    }
+
+    fn receipt(receipt_id: CryptoHash, receiver_id: &str) -> views::ReceiptView {
+        views::ReceiptView {
+            predecessor_id: "system".parse().unwrap(),
+            receiver_id: receiver_id.parse().unwrap(),
+            receipt_id,
+            receipt: views::ReceiptEnumView::Data {
+                data_id: CryptoHash::default(),
+                data: None,
+                is_promise_resume: false,
+            },
+            priority: 0,
+        }
+    }
+
+    fn receivers_filter(receiver: &str) -> IndexerFilter {
+        IndexerFilter { receivers: vec![receiver.parse().unwrap()], ..Default::default() }
+    }
+
+    #[test]
+    fn test_keeps_outcome() {
+        let filter = receivers_filter("*.sweat");
+        let tx_hash = hash(b"tx");
+        let transaction_hashes = HashSet::from([tx_hash]);
+
+        // Transactions are executed by the signer but their outcomes are
+        // always needed to build the transactions.
+        assert!(keeps_outcome(&filter, &transaction_hashes, &outcome(tx_hash, "alice.near")));
+        assert!(keeps_outcome(&filter, &transaction_hashes, &outcome(hash(b"a"), "token.sweat")));
+        assert!(!keeps_outcome(&filter, &transaction_hashes, &outcome(hash(b"b"), "alice.near")));
+        assert!(keeps_outcome(
+            &IndexerFilter::default(),
+            &transaction_hashes,
+            &outcome(hash(b"b"), "alice.near")
+        ));
+    }
+
+    #[test]
+    fn test_filtered_state_changes() {
+        let kept = hash(b"kept");
+        let other = hash(b"other");
+        let shards = vec![IndexerShard {
+            shard_id: ShardId::new(0),
+            chunk: None,
+            receipt_execution_outcomes: vec![IndexerExecutionOutcomeWithReceipt {
+                execution_outcome: outcome(kept, "token.sweat"),
+                receipt: receipt(kept, "token.sweat"),
+            }],
+            state_changes: vec![],
+            balance_audit: None,
+        }];
+        let causes = state_change_causes(&shards);
+        assert_eq!(causes, HashSet::from([kept]));
+
+        let state_changes_filter = receivers_filter("*.sweat").state_changes_filter(causes);
+        let keeps = |cause, account_id: &str| {
+            state_changes_filter.keeps(&StateChangeWithCause {
+                cause,
+                value: StateChangeValue::AccountDeletion {
+                    account_id: account_id.parse().unwrap(),
+                },
+            })
+        };
+        // Caused by a kept receipt.
+        assert!(keeps(StateChangeCause::ReceiptProcessing { receipt_hash: kept }, "alice.near"));
+        // Touching an account matching the receivers.
+        assert!(keeps(StateChangeCause::ReceiptProcessing { receipt_hash: other }, "token.sweat"));
+        assert!(keeps(StateChangeCause::ValidatorAccountsUpdate, "token.sweat"));
+        assert!(!keeps(StateChangeCause::ReceiptProcessing { receipt_hash: other }, "alice.near"));
+        assert!(!keeps(StateChangeCause::ValidatorAccountsUpdate, "alice.near"));
+    }
+}
@@ -0,0 +1,57 @@
+use near_primitives::types::{BlockReference, ShardId};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcChunkBalanceAuditRequest {
+    #[serde(flatten)]
+    pub block_reference: BlockReference,
+    pub shard_id: ShardId,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcChunkBalanceAuditResponse {
+    #[serde(flatten)]
+    pub balance_audit: near_primitives::views::ChunkBalanceAuditView,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcChunkBalanceAuditError {
+    #[error(
+        "Block either has never been observed on the node or has been garbage collected: {error_message}"
+    )]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NotSyncedYet,
+    #[error("Shard id {shard_id} does not exist")]
+    InvalidShardId { shard_id: ShardId },
+    #[error(
+        "The balance audit of shard {shard_id} in block {block_hash} is not available, the node either does not track the shard or did not audit balances when it applied the block"
+    )]
+    UnavailableAudit { block_hash: near_primitives::hash::CryptoHash, shard_id: ShardId },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcChunkBalanceAuditError> for crate::errors::RpcError {
+    fn from(error: RpcChunkBalanceAuditError) -> Self {
+        let error_data = match &error {
+            RpcChunkBalanceAuditError::UnknownBlock { error_message } => {
+                Some(serde_json::Value::String(format!("DB Not Found Error: {}", error_message)))
+            }
+            _ => Some(serde_json::Value::String(error.to_string())),
+        };
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcChunkBalanceAuditError: {:?}", err),
+                );
+            }
+        };
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
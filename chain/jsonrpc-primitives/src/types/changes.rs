@@ -8,6 +8,10 @@ pub struct RpcStateChangesInBlockRequest {
 pub struct RpcStateChangesInBlockResponse {
     pub block_hash: near_primitives::hash::CryptoHash,
     pub changes: near_primitives::views::StateChangesView,
+    /// Store which served the changes, only reported by archival nodes with
+    /// split storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_from: Option<near_primitives::views::StorageSourceView>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
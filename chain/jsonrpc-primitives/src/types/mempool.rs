@@ -0,0 +1,55 @@
+use near_primitives::views::MempoolView;
+use serde_json::Value;
+
+/// Maximum number of transactions listed by a single request.
+pub const MAX_MEMPOOL_LIMIT: u32 = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+pub struct RpcMempoolRequest {
+    /// If set, only the transactions signed by this account are listed.
+    #[serde(default)]
+    pub signer_id: Option<near_primitives::types::AccountId>,
+    /// The `next_cursor` of the previous response to list the next page.
+    #[serde(default)]
+    pub cursor: Option<near_primitives::hash::CryptoHash>,
+    /// Maximum number of listed transactions, `MAX_MEMPOOL_LIMIT` if not set.
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcMempoolResponse {
+    #[serde(flatten)]
+    pub result: MempoolView,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcMempoolError {
+    #[error("The mempool is only served with `enable_debug_rpc` set in the RPC config")]
+    DebugRpcDisabled,
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcMempoolError> for crate::errors::RpcError {
+    fn from(error: RpcMempoolError) -> Self {
+        let error_data = match &error {
+            RpcMempoolError::DebugRpcDisabled | RpcMempoolError::InternalError { .. } => {
+                Some(Value::String(error.to_string()))
+            }
+        };
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcMempoolError: {:?}", err),
+                );
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
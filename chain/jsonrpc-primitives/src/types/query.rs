@@ -44,6 +44,12 @@ pub enum RpcQueryError {
         block_height: near_primitives::types::BlockHeight,
         block_hash: near_primitives::hash::CryptoHash,
     },
+    #[error("Global contract code {identifier:?} has never been observed on the node")]
+    NoGlobalContractCode {
+        identifier: near_primitives::action::GlobalContractIdentifier,
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    },
     #[error("State of contract {contract_account_id} is too large to be viewed")]
     TooLargeContractState {
         contract_account_id: near_primitives::types::AccountId,
@@ -62,6 +68,10 @@ pub enum RpcQueryError {
         block_height: near_primitives::types::BlockHeight,
         block_hash: near_primitives::hash::CryptoHash,
     },
+    #[error(
+        "Host call tracing is only served with `enable_host_call_tracing` set in the RPC config"
+    )]
+    HostCallTracingDisabled,
     #[error("The node reached its limits. Try again later. More details: {error_message}")]
     InternalError { error_message: String },
 }
@@ -72,6 +82,10 @@ pub struct RpcQueryResponse {
     pub kind: QueryResponseKind,
     pub block_height: near_primitives::types::BlockHeight,
     pub block_hash: near_primitives::hash::CryptoHash,
+    /// Store which served the data, only reported by archival nodes with split
+    /// storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_from: Option<near_primitives::views::StorageSourceView>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
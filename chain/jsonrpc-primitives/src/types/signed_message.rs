@@ -0,0 +1,82 @@
+use near_crypto::{PublicKey, Signature};
+use near_primitives::signable_message::SignMessagePayload;
+use near_primitives::types::{AccountId, BlockReference};
+
+/// Request to verify an off-chain message signed by an account as specified by
+/// NEP-413.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcVerifySignedMessageRequest {
+    #[serde(flatten)]
+    pub block_reference: BlockReference,
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    pub signature: Signature,
+    #[serde(flatten)]
+    pub payload: SignMessagePayload,
+}
+
+/// The message is signed with a full access key of the account at the returned
+/// block.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcVerifySignedMessageResponse {
+    pub block_height: near_primitives::types::BlockHeight,
+    pub block_hash: near_primitives::hash::CryptoHash,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcVerifySignedMessageError {
+    #[error("The signature doesn't match the message and the public key")]
+    InvalidSignature,
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error(
+        "The data for block #{block_height} is garbage collected on this node, use an archival node to fetch historical data"
+    )]
+    GarbageCollectedBlock {
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    },
+    #[error(
+        "Block either has never been observed on the node or has been garbage collected: {block_reference:?}"
+    )]
+    UnknownBlock { block_reference: near_primitives::types::BlockReference },
+    #[error("account {requested_account_id} does not exist while viewing")]
+    UnknownAccount {
+        requested_account_id: near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    },
+    #[error("Access key for public key {public_key} does not exist while viewing")]
+    UnknownAccessKey {
+        public_key: near_crypto::PublicKey,
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    },
+    #[error("Access key for public key {public_key} is not a full access key")]
+    NotFullAccessKey {
+        public_key: near_crypto::PublicKey,
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcVerifySignedMessageError> for crate::errors::RpcError {
+    fn from(error: RpcVerifySignedMessageError) -> Self {
+        let error_data = Some(serde_json::Value::String(error.to_string()));
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcVerifySignedMessageError: {:?}", err),
+                );
+            }
+        };
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
@@ -0,0 +1,101 @@
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, Finality};
+use near_primitives::views::{BlockHeaderView, StateChangesRequestView, TxExecutionStatus};
+use serde_json::Value;
+
+use crate::errors::RpcError;
+use crate::types::changes::RpcStateChangesInBlockResponse;
+use crate::types::transactions::RpcTransactionResponse;
+
+/// Identifier of a subscription, unique within a single WebSocket connection.
+pub type RpcSubscriptionId = u64;
+
+/// Parameters of the `subscribe` method of the WebSocket endpoint.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RpcSubscribeRequest {
+    /// Notifies about every new block header at the given finality.
+    NewHeads {
+        #[serde(default)]
+        finality: Finality,
+    },
+    /// Notifies about the matching state changes in every new block at the
+    /// given finality. Blocks without matching changes are skipped.
+    StateChanges {
+        #[serde(default)]
+        finality: Finality,
+        #[serde(flatten)]
+        state_changes_request: StateChangesRequestView,
+    },
+    /// Notifies about every `TxExecutionStatus` the transaction goes through
+    /// until `wait_until` is reached.
+    TxStatus {
+        tx_hash: CryptoHash,
+        sender_account_id: AccountId,
+        #[serde(default = "default_tx_wait_until")]
+        wait_until: TxExecutionStatus,
+    },
+}
+
+fn default_tx_wait_until() -> TxExecutionStatus {
+    TxExecutionStatus::Final
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct RpcSubscribeResponse {
+    pub subscription_id: RpcSubscriptionId,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct RpcUnsubscribeRequest {
+    pub subscription_id: RpcSubscriptionId,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct RpcUnsubscribeResponse {}
+
+/// Params of the `subscription` notification pushed to the WebSocket client.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct RpcSubscriptionNotification {
+    pub subscription_id: RpcSubscriptionId,
+    #[serde(flatten)]
+    pub event: RpcSubscriptionEvent,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", content = "result", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum RpcSubscriptionEvent {
+    NewHead(BlockHeaderView),
+    StateChanges(RpcStateChangesInBlockResponse),
+    TxStatus(RpcTransactionResponse),
+    /// The subscription has failed and no more notifications will follow.
+    Error(RpcError),
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSubscriptionError {
+    #[error("Subscriptions are only available over a WebSocket connection")]
+    NotAvailable,
+    #[error("The connection already has the maximum of {limit} active subscriptions")]
+    TooManySubscriptions { limit: usize },
+    #[error("Subscription {subscription_id} does not exist")]
+    UnknownSubscription { subscription_id: RpcSubscriptionId },
+}
+
+impl From<RpcSubscriptionError> for RpcError {
+    fn from(error: RpcSubscriptionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSubscriptionError: {:?}", err),
+                );
+            }
+        };
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
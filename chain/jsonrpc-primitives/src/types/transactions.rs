@@ -50,6 +50,14 @@ pub enum RpcTransactionError {
     InternalError { debug_info: String },
     #[error("Timeout")]
     TimeoutError,
+    #[error(
+        "Host call tracing is only served with `enable_host_call_tracing` set in the RPC config"
+    )]
+    HostCallTracingDisabled,
+    #[error(
+        "Transaction simulation is only served with `enable_tx_simulation` set in the RPC config"
+    )]
+    TxSimulationDisabled,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -64,6 +72,26 @@ pub struct RpcBroadcastTxSyncResponse {
     pub transaction_hash: near_primitives::hash::CryptoHash,
 }
 
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RpcSimulateTransactionRequest {
+    #[serde(rename = "signed_tx_base64")]
+    pub signed_transaction: near_primitives::transaction::SignedTransaction,
+    /// Simulate the transaction even if its signature is invalid, e.g. to
+    /// estimate the fees before the user signs it.
+    #[serde(default)]
+    pub skip_signature_check: bool,
+    /// Record the host functions called by the contracts, see
+    /// `SimulatedTransactionView::host_calls`.
+    #[serde(default)]
+    pub trace: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcSimulateTransactionResponse {
+    #[serde(flatten)]
+    pub result: near_primitives::views::SimulatedTransactionView,
+}
+
 impl TransactionInfo {
     pub fn from_signed_tx(tx: near_primitives::transaction::SignedTransaction) -> Self {
         Self::Transaction(SignedTransaction::SignedTransaction(tx))
@@ -236,6 +236,33 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_changes", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_chunk_balance_audit(
+        &self,
+        request: near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_chunk_balance_audit", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_light_client_proof(
+        &self,
+        request: near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofRequest,
+    ) -> RpcRequest<
+        near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse,
+    > {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_light_client_proof", request)
+    }
+
+    pub fn next_light_client_block(
+        &self,
+        request: near_jsonrpc_primitives::types::light_client::RpcLightClientNextBlockRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::light_client::RpcLightClientNextBlockResponse>
+    {
+        call_method(&self.client, &self.server_addr, "next_light_client_block", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_validators_ordered(
         &self,
@@ -260,6 +287,32 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_protocol_config", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_mempool(
+        &self,
+        request: near_jsonrpc_primitives::types::mempool::RpcMempoolRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::mempool::RpcMempoolResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_mempool", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_simulate_tx(
+        &self,
+        request: near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_simulate_tx", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_verify_signed_message(
+        &self,
+        request: near_jsonrpc_primitives::types::signed_message::RpcVerifySignedMessageRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::signed_message::RpcVerifySignedMessageResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_verify_signed_message", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_split_storage_info(
         &self,
@@ -57,7 +57,7 @@ pub fn start_all_with_validity_period(
 
     let addr = tcp::ListenerAddr::reserve_for_test();
     start_http(
-        RpcConfig::new(addr),
+        RpcConfig { enable_tx_simulation: true, ..RpcConfig::new(addr) },
         TEST_GENESIS_CONFIG.clone(),
         actor_handles.client_actor.clone().with_auto_span_context().into_multi_sender(),
         actor_handles.view_client_actor.clone().with_auto_span_context().into_multi_sender(),
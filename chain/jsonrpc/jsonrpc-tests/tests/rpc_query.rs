@@ -11,12 +11,17 @@ use serde_json::json;
 use near_actix_test_utils::run_actix;
 use near_crypto::{InMemorySigner, Signature};
 use near_jsonrpc::client::{ChunkId, JsonRpcClient, new_client};
+use near_jsonrpc_primitives::errors::RpcErrorKind;
+use near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditRequest;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_jsonrpc_primitives::types::signed_message::RpcVerifySignedMessageRequest;
+use near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionRequest;
 use near_jsonrpc_primitives::types::validator::RpcValidatorsOrderedRequest;
 use near_network::test_utils::wait_or_timeout;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::account::{AccessKey, AccessKeyPermission};
 use near_primitives::hash::CryptoHash;
+use near_primitives::signable_message::SignMessagePayload;
 use near_primitives::types::{
     AccountId, BlockId, BlockReference, EpochId, ShardId, SyncCheckpoint,
 };
@@ -300,6 +305,113 @@ fn test_query_access_key() {
     });
 }
 
+/// Verify an off-chain message signed with a full access key of an account.
+#[test]
+fn test_verify_signed_message() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let account: AccountId = "test".parse().unwrap();
+        let signer = InMemorySigner::test_signer(&account);
+        let payload = SignMessagePayload {
+            message: "log in".to_string(),
+            nonce: [7; 32],
+            recipient: "app.near".to_string(),
+            callback_url: None,
+        };
+        let request = |payload: SignMessagePayload| RpcVerifySignedMessageRequest {
+            block_reference: BlockReference::latest(),
+            account_id: account.clone(),
+            public_key: signer.public_key(),
+            signature: payload.sign(&signer),
+            payload,
+        };
+        let response =
+            client.EXPERIMENTAL_verify_signed_message(request(payload.clone())).await.unwrap();
+        assert_eq!(response.block_height, 0);
+
+        let mut request = request(payload);
+        request.payload.recipient = "evil.near".to_string();
+        client.EXPERIMENTAL_verify_signed_message(request).await.unwrap_err();
+    });
+}
+
+/// Simulate a transfer and check that it is not committed.
+#[test]
+fn test_simulate_tx() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let signer_id: AccountId = "test".parse().unwrap();
+        let receiver_id: AccountId = "test1".parse().unwrap();
+        let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+        let signer = InMemorySigner::test_signer(&signer_id);
+        let tx = SignedTransaction::send_money(
+            1,
+            signer_id.clone(),
+            receiver_id.clone(),
+            &signer,
+            100,
+            block_hash,
+        );
+        let request = |signed_transaction, skip_signature_check| RpcSimulateTransactionRequest {
+            signed_transaction,
+            skip_signature_check,
+            trace: false,
+        };
+        let response = client.EXPERIMENTAL_simulate_tx(request(tx.clone(), false)).await.unwrap();
+        assert_eq!(response.result.block_hash, block_hash);
+        assert_eq!(response.result.outcome.final_outcome.transaction.hash, tx.get_hash());
+        assert_eq!(
+            response.result.outcome.final_outcome.status,
+            FinalExecutionStatus::SuccessValue(Vec::new())
+        );
+        assert!(!response.result.outcome.receipts.is_empty());
+        assert!(!response.result.state_changes.is_empty());
+
+        // The transaction was not committed, so the nonce is still unused.
+        let access_key = client
+            .query(near_jsonrpc_primitives::types::query::RpcQueryRequest {
+                block_reference: BlockReference::latest(),
+                request: QueryRequest::ViewAccessKey {
+                    account_id: signer_id.clone(),
+                    public_key: signer.public_key(),
+                },
+            })
+            .await
+            .unwrap();
+        let QueryResponseKind::AccessKey(access_key) = access_key.kind else {
+            panic!("queried access key, but received something else: {:?}", access_key.kind);
+        };
+        assert_eq!(access_key.nonce, 0);
+
+        // A transaction with a wrong signature can be simulated only when
+        // the signature check is skipped.
+        let mut tx = tx;
+        tx.signature = Signature::empty(signer.public_key().key_type());
+        client.EXPERIMENTAL_simulate_tx(request(tx.clone(), false)).await.unwrap_err();
+        client.EXPERIMENTAL_simulate_tx(request(tx, true)).await.unwrap();
+    });
+}
+
+/// The genesis block doesn't apply any chunks, so there is nothing to audit.
+#[test]
+fn test_chunk_balance_audit_unavailable() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let request = |shard_id| RpcChunkBalanceAuditRequest {
+            block_reference: BlockReference::BlockId(BlockId::Height(0)),
+            shard_id,
+        };
+        let error_name = |error: near_jsonrpc_primitives::errors::RpcError| match error.error_struct
+        {
+            Some(RpcErrorKind::HandlerError(value)) => value["name"].clone(),
+            error_struct => panic!("unexpected error: {:?}", error_struct),
+        };
+        let error =
+            client.EXPERIMENTAL_chunk_balance_audit(request(ShardId::new(0))).await.unwrap_err();
+        assert_eq!(error_name(error), "UNAVAILABLE_AUDIT");
+        let error =
+            client.EXPERIMENTAL_chunk_balance_audit(request(ShardId::new(100))).await.unwrap_err();
+        assert_eq!(error_name(error), "INVALID_SHARD_ID");
+    });
+}
+
 /// Connect to json rpc and query state.
 #[test]
 fn test_query_state() {
@@ -340,6 +452,7 @@ fn test_query_call_function() {
                     account_id: "test".parse().unwrap(),
                     method_name: "run_test".to_string(),
                     args: vec![].into(),
+                    trace: false,
                 },
             })
             .await
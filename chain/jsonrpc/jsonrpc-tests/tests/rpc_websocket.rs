@@ -0,0 +1,287 @@
+use actix::System;
+use awc::ws;
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+
+use near_actix_test_utils::run_actix;
+use near_crypto::InMemorySigner;
+use near_jsonrpc_primitives::message::{Message, Notification, from_slice};
+use near_o11y::testonly::init_test_logger;
+use near_primitives::hash::CryptoHash;
+use near_primitives::serialize::to_base64;
+use near_primitives::transaction::SignedTransaction;
+use near_time::Clock;
+
+use near_jsonrpc_tests as test_utils;
+
+async fn send_request<S>(connection: &mut S, method: &str, params: serde_json::Value)
+where
+    S: futures::Sink<ws::Message, Error = ws::ProtocolError> + Unpin,
+{
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "id": "dontcare",
+        "params": params,
+    });
+    connection.send(ws::Message::Text(request.to_string().into())).await.unwrap();
+}
+
+async fn next_message<S>(connection: &mut S) -> Message
+where
+    S: futures::Stream<Item = Result<ws::Frame, ws::ProtocolError>> + Unpin,
+{
+    loop {
+        match connection.next().await.unwrap().unwrap() {
+            ws::Frame::Text(bytes) => return from_slice(&bytes).unwrap(),
+            ws::Frame::Ping(_) | ws::Frame::Pong(_) => continue,
+            frame => panic!("unexpected frame: {:?}", frame),
+        }
+    }
+}
+
+/// Subscribes to new heads, waits for a notification and unsubscribes.
+#[test]
+fn test_ws_subscribe_new_heads() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr, runtime_tempdir) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::Validator);
+
+        actix::spawn(async move {
+            let _runtime_tempdir = runtime_tempdir;
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+
+            send_request(
+                &mut connection,
+                "subscribe",
+                json!({"kind": "new_heads", "finality": "optimistic"}),
+            )
+            .await;
+            let subscription_id = match next_message(&mut connection).await {
+                Message::Response(response) => response.result.unwrap()["subscription_id"].clone(),
+                message => panic!("expected a response, got {:?}", message),
+            };
+
+            match next_message(&mut connection).await {
+                Message::Notification(notification) => {
+                    assert_eq!(notification.method, "subscription");
+                    assert_eq!(notification.params["subscription_id"], subscription_id);
+                    assert_eq!(notification.params["kind"], "new_head");
+                    assert!(notification.params["result"]["height"].is_u64());
+                }
+                message => panic!("expected a notification, got {:?}", message),
+            }
+
+            send_request(&mut connection, "unsubscribe", json!([subscription_id])).await;
+            loop {
+                match next_message(&mut connection).await {
+                    Message::Notification(_) => continue,
+                    Message::Response(response) => {
+                        response.result.unwrap();
+                        break;
+                    }
+                    message => panic!("expected a response, got {:?}", message),
+                }
+            }
+            System::current().stop();
+        });
+    });
+}
+
+/// Sends a request and returns its result, collecting the notifications which
+/// arrive in the meantime.
+async fn request<S>(
+    connection: &mut S,
+    method: &str,
+    params: serde_json::Value,
+    notifications: &mut Vec<Notification>,
+) -> serde_json::Value
+where
+    S: futures::Sink<ws::Message, Error = ws::ProtocolError>
+        + futures::Stream<Item = Result<ws::Frame, ws::ProtocolError>>
+        + Unpin,
+{
+    send_request(connection, method, params).await;
+    loop {
+        match next_message(connection).await {
+            Message::Notification(notification) => notifications.push(notification),
+            Message::Response(response) => return response.result.unwrap(),
+            message => panic!("expected a response, got {:?}", message),
+        }
+    }
+}
+
+/// Follows a transfer through the `tx_status` subscription and checks that the
+/// `state_changes` subscriptions only report the changes of the accounts they
+/// ask for, each block once.
+#[test]
+fn test_ws_subscribe_state_changes_and_tx_status() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr, runtime_tempdir) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::Validator);
+
+        actix::spawn(async move {
+            let _runtime_tempdir = runtime_tempdir;
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+            let mut notifications = vec![];
+
+            let receiver_subscription = request(
+                &mut connection,
+                "subscribe",
+                json!({
+                    "kind": "state_changes",
+                    "finality": "optimistic",
+                    "changes_type": "account_changes",
+                    "account_ids": ["test2"],
+                }),
+                &mut notifications,
+            )
+            .await["subscription_id"]
+                .clone();
+            let unrelated_subscription = request(
+                &mut connection,
+                "subscribe",
+                json!({
+                    "kind": "state_changes",
+                    "finality": "optimistic",
+                    "changes_type": "account_changes",
+                    "account_ids": ["unrelated"],
+                }),
+                &mut notifications,
+            )
+            .await["subscription_id"]
+                .clone();
+
+            let block =
+                request(&mut connection, "block", json!({"finality": "final"}), &mut notifications)
+                    .await;
+            let block_hash: CryptoHash =
+                serde_json::from_value(block["header"]["hash"].clone()).unwrap();
+            let signer = InMemorySigner::test_signer(&"test1".parse().unwrap());
+            let tx = SignedTransaction::send_money(
+                1,
+                "test1".parse().unwrap(),
+                "test2".parse().unwrap(),
+                &signer,
+                100,
+                block_hash,
+            );
+            let tx_subscription = request(
+                &mut connection,
+                "subscribe",
+                json!({
+                    "kind": "tx_status",
+                    "tx_hash": tx.get_hash(),
+                    "sender_account_id": "test1",
+                    "wait_until": "FINAL",
+                }),
+                &mut notifications,
+            )
+            .await["subscription_id"]
+                .clone();
+            request(
+                &mut connection,
+                "broadcast_tx_async",
+                json!([to_base64(&borsh::to_vec(&tx).unwrap())]),
+                &mut notifications,
+            )
+            .await;
+
+            let mut tx_statuses = vec![];
+            let mut receiver_blocks = vec![];
+            let mut notifications = notifications.into_iter();
+            while tx_statuses.last() != Some(&json!("FINAL")) || receiver_blocks.is_empty() {
+                let notification = match notifications.next() {
+                    Some(notification) => notification,
+                    None => match next_message(&mut connection).await {
+                        Message::Notification(notification) => notification,
+                        message => panic!("expected a notification, got {:?}", message),
+                    },
+                };
+                let params = notification.params;
+                let subscription_id = &params["subscription_id"];
+                assert_ne!(subscription_id, &unrelated_subscription);
+                if subscription_id == &tx_subscription {
+                    assert_eq!(params["kind"], "tx_status");
+                    tx_statuses.push(params["result"]["final_execution_status"].clone());
+                } else if subscription_id == &receiver_subscription {
+                    assert_eq!(params["kind"], "state_changes");
+                    let changes = params["result"]["changes"].as_array().unwrap();
+                    assert!(!changes.is_empty());
+                    for change in changes {
+                        assert_eq!(change["change"]["account_id"], "test2");
+                    }
+                    receiver_blocks.push(params["result"]["block_hash"].clone());
+                }
+            }
+            let mut unique_blocks = receiver_blocks.clone();
+            unique_blocks.dedup();
+            assert_eq!(unique_blocks, receiver_blocks);
+            let mut unique_statuses = tx_statuses.clone();
+            unique_statuses.dedup();
+            assert_eq!(unique_statuses, tx_statuses);
+            System::current().stop();
+        });
+    });
+}
+
+/// Regular requests are served over the WebSocket connection as well.
+#[test]
+fn test_ws_regular_request() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr, runtime_tempdir) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let _runtime_tempdir = runtime_tempdir;
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+
+            send_request(&mut connection, "block", json!({"block_id": 0})).await;
+            match next_message(&mut connection).await {
+                Message::Response(response) => {
+                    assert_eq!(response.result.unwrap()["header"]["height"], 0);
+                }
+                message => panic!("expected a response, got {:?}", message),
+            }
+            System::current().stop();
+        });
+    });
+}
+
+/// Subscriptions are rejected on the plain HTTP endpoint.
+#[test]
+fn test_subscribe_over_http() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr, runtime_tempdir) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let _runtime_tempdir = runtime_tempdir;
+            let client = awc::Client::new();
+            let result = test_utils::call_method::<serde_json::Value>(
+                &client,
+                &format!("http://{}", addr),
+                "subscribe",
+                json!({"kind": "new_heads"}),
+            )
+            .await;
+            let error = result.unwrap_err();
+            assert_eq!(
+                error.data,
+                Some(json!("Subscriptions are only available over a WebSocket connection"))
+            );
+            System::current().stop();
+        });
+    });
+}
@@ -0,0 +1,48 @@
+use near_async::messaging::AsyncSendError;
+use near_client_primitives::types::GetChunkBalanceAuditError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::balance_audit::{
+    RpcChunkBalanceAuditError, RpcChunkBalanceAuditRequest,
+};
+use serde_json::Value;
+
+use super::{Params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcChunkBalanceAuditRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcChunkBalanceAuditError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<GetChunkBalanceAuditError> for RpcChunkBalanceAuditError {
+    fn rpc_from(error: GetChunkBalanceAuditError) -> Self {
+        match error {
+            GetChunkBalanceAuditError::IOError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetChunkBalanceAuditError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            GetChunkBalanceAuditError::NotSyncedYet => Self::NotSyncedYet,
+            GetChunkBalanceAuditError::InvalidShardId { shard_id } => {
+                Self::InvalidShardId { shard_id }
+            }
+            GetChunkBalanceAuditError::UnavailableAudit { block_hash, shard_id } => {
+                Self::UnavailableAudit { block_hash, shard_id }
+            }
+            GetChunkBalanceAuditError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcChunkBalanceAuditError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
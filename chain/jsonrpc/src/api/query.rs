@@ -79,6 +79,7 @@ fn parse_path_data(path: String, data: String) -> Result<RpcQueryRequest, RpcPar
                 account_id,
                 method_name: method_name.to_string(),
                 args: parse_data()?.into(),
+                trace: false,
             },
             None => return Err(RpcParseError("Method name is missing".to_string())),
         },
@@ -115,6 +116,9 @@ impl RpcFrom<QueryError> for RpcQueryError {
             QueryError::NoContractCode { contract_account_id, block_height, block_hash } => {
                 Self::NoContractCode { contract_account_id, block_height, block_hash }
             }
+            QueryError::NoGlobalContractCode { identifier, block_height, block_hash } => {
+                Self::NoGlobalContractCode { identifier, block_height, block_hash }
+            }
             QueryError::UnknownAccessKey { public_key, block_height, block_hash } => {
                 Self::UnknownAccessKey { public_key, block_height, block_hash }
             }
@@ -141,6 +145,7 @@ impl RpcFrom<QueryResponse> for RpcQueryResponse {
             kind: RpcFrom::rpc_from(query_response.kind),
             block_hash: query_response.block_hash,
             block_height: query_response.block_height,
+            served_from: query_response.served_from,
         }
     }
 }
@@ -4,7 +4,8 @@ use serde_json::Value;
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::types::sandbox::{
     RpcSandboxFastForwardError, RpcSandboxFastForwardRequest, RpcSandboxPatchStateError,
-    RpcSandboxPatchStateRequest,
+    RpcSandboxPatchStateRequest, RpcSandboxRevertError, RpcSandboxRevertRequest,
+    RpcSandboxSnapshotError,
 };
 
 use super::{Params, RpcFrom, RpcRequest};
@@ -21,6 +22,12 @@ impl RpcRequest for RpcSandboxFastForwardRequest {
     }
 }
 
+impl RpcRequest for RpcSandboxRevertRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
 impl RpcFrom<AsyncSendError> for RpcSandboxPatchStateError {
     fn rpc_from(error: AsyncSendError) -> Self {
         Self::InternalError { error_message: error.to_string() }
@@ -32,3 +39,15 @@ impl RpcFrom<AsyncSendError> for RpcSandboxFastForwardError {
         Self::InternalError { error_message: error.to_string() }
     }
 }
+
+impl RpcFrom<AsyncSendError> for RpcSandboxSnapshotError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcSandboxRevertError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
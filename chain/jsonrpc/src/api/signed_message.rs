@@ -0,0 +1,57 @@
+use near_async::messaging::AsyncSendError;
+use near_client_primitives::types::QueryError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::signed_message::{
+    RpcVerifySignedMessageError, RpcVerifySignedMessageRequest,
+};
+use serde_json::Value;
+
+use super::{Params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcVerifySignedMessageRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcVerifySignedMessageError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<QueryError> for RpcVerifySignedMessageError {
+    fn rpc_from(error: QueryError) -> Self {
+        match error {
+            QueryError::NoSyncedBlocks => Self::NoSyncedBlocks,
+            QueryError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            QueryError::UnknownBlock { block_reference } => Self::UnknownBlock { block_reference },
+            QueryError::GarbageCollectedBlock { block_height, block_hash } => {
+                Self::GarbageCollectedBlock { block_height, block_hash }
+            }
+            QueryError::InvalidAccount { requested_account_id, block_height, block_hash }
+            | QueryError::UnknownAccount { requested_account_id, block_height, block_hash } => {
+                Self::UnknownAccount { requested_account_id, block_height, block_hash }
+            }
+            QueryError::UnknownAccessKey { public_key, block_height, block_hash } => {
+                Self::UnknownAccessKey { public_key, block_height, block_hash }
+            }
+            QueryError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcVerifySignedMessageError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+            QueryError::InternalError { .. }
+            | QueryError::NoContractCode { .. }
+            | QueryError::NoGlobalContractCode { .. }
+            | QueryError::TooLargeContractState { .. }
+            | QueryError::ContractExecutionError { .. } => {
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
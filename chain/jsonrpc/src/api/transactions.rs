@@ -1,10 +1,11 @@
 use near_async::messaging::AsyncSendError;
 use serde_json::Value;
 
-use near_client_primitives::types::TxStatusError;
+use near_client_primitives::types::{SimulateTransactionError, TxStatusError};
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::types::transactions::{
-    RpcSendTransactionRequest, RpcTransactionError, RpcTransactionStatusRequest, TransactionInfo,
+    RpcSendTransactionRequest, RpcSimulateTransactionRequest, RpcTransactionError,
+    RpcTransactionStatusRequest, TransactionInfo,
 };
 use near_primitives::borsh::BorshDeserialize;
 use near_primitives::transaction::SignedTransaction;
@@ -53,6 +54,12 @@ impl RpcRequest for RpcTransactionStatusRequest {
     }
 }
 
+impl RpcRequest for RpcSimulateTransactionRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
 impl RpcFrom<AsyncSendError> for RpcTransactionError {
     fn rpc_from(error: AsyncSendError) -> Self {
         Self::InternalError { debug_info: error.to_string() }
@@ -74,6 +81,28 @@ impl RpcFrom<TxStatusError> for RpcTransactionError {
     }
 }
 
+impl RpcFrom<SimulateTransactionError> for RpcTransactionError {
+    fn rpc_from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::InvalidTransaction(context) => {
+                Self::InvalidTransaction { context }
+            }
+            SimulateTransactionError::UnavailableShard(_) => Self::DoesNotTrackShard,
+            SimulateTransactionError::IOError(debug_info)
+            | SimulateTransactionError::SimulationFailed(debug_info) => {
+                Self::InternalError { debug_info }
+            }
+            SimulateTransactionError::Unreachable(ref error_message) => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcTransactionError"])
+                    .inc();
+                Self::InternalError { debug_info: error.to_string() }
+            }
+        }
+    }
+}
+
 fn decode_signed_transaction(value: String) -> Result<SignedTransaction, RpcParseError> {
     let bytes = near_primitives::serialize::from_base64(&value)
         .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))?;
@@ -11,10 +11,11 @@ use near_async::messaging::{
 };
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    DebugStatus, GetBlock, GetBlockProof, GetChunk, GetClientConfig, GetExecutionOutcome,
-    GetGasPrice, GetMaintenanceWindows, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig,
-    GetReceipt, GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
-    ProcessTxRequest, ProcessTxResponse, Query, Status, TxStatus,
+    DebugStatus, GetBlock, GetBlockProof, GetChunk, GetChunkBalanceAudit, GetClientConfig,
+    GetExecutionOutcome, GetGasPrice, GetMaintenanceWindows, GetMempool, GetNetworkInfo,
+    GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
+    GetStateChangesInBlock, GetStateChangesResponse, GetValidatorInfo, GetValidatorOrdered,
+    ProcessTxRequest, ProcessTxResponse, Query, SimulateTransaction, Status, TxStatus,
 };
 use near_client_primitives::debug::{DebugBlockStatusQuery, DebugBlocksStartingMode};
 use near_client_primitives::types::GetSplitStorageInfo;
@@ -29,6 +30,7 @@ use near_jsonrpc_primitives::types::query::RpcQueryRequest;
 use near_jsonrpc_primitives::types::split_storage::{
     RpcSplitStorageInfoRequest, RpcSplitStorageInfoResponse,
 };
+use near_jsonrpc_primitives::types::subscriptions::RpcSubscriptionError;
 use near_jsonrpc_primitives::types::transactions::{
     RpcSendTransactionRequest, RpcTransactionResponse,
 };
@@ -38,7 +40,9 @@ use near_o11y::metrics::{Encoder, TextEncoder, prometheus};
 use near_primitives::hash::CryptoHash;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockId, BlockReference};
-use near_primitives::views::{QueryRequest, TxExecutionStatus};
+use near_primitives::views::{
+    AccessKeyPermissionView, QueryRequest, QueryResponseKind, TxExecutionStatus,
+};
 use serde_json::{Value, json};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -48,6 +52,7 @@ use tracing::{error, info};
 
 mod api;
 mod metrics;
+mod websocket;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug)]
 pub struct RpcPollingConfig {
@@ -68,11 +73,31 @@ impl Default for RpcPollingConfig {
 pub struct RpcLimitsConfig {
     /// Maximum byte size of the json payload.
     pub json_payload_max_size: usize,
+    /// Maximum number of active subscriptions on a single WebSocket connection.
+    #[serde(default = "default_ws_max_subscriptions_per_connection")]
+    pub ws_max_subscriptions_per_connection: usize,
+    /// Maximum number of messages waiting to be sent on a single WebSocket
+    /// connection. Subscriptions of a connection whose queue is full are paused
+    /// until the client catches up.
+    #[serde(default = "default_ws_max_pending_messages")]
+    pub ws_max_pending_messages: usize,
+}
+
+fn default_ws_max_subscriptions_per_connection() -> usize {
+    32
+}
+
+fn default_ws_max_pending_messages() -> usize {
+    128
 }
 
 impl Default for RpcLimitsConfig {
     fn default() -> Self {
-        Self { json_payload_max_size: 10 * 1024 * 1024 }
+        Self {
+            json_payload_max_size: 10 * 1024 * 1024,
+            ws_max_subscriptions_per_connection: default_ws_max_subscriptions_per_connection(),
+            ws_max_pending_messages: default_ws_max_pending_messages(),
+        }
     }
 }
 
@@ -93,6 +118,14 @@ pub struct RpcConfig {
     // We disable it by default, as some of those endpoints might be quite CPU heavy.
     #[serde(default = "default_enable_debug_rpc")]
     pub enable_debug_rpc: bool,
+    // If true, `call_function` queries and transaction simulations may ask for a trace of the
+    // host functions called by contracts. Disabled by default, as traces are expensive to build.
+    #[serde(default)]
+    pub enable_host_call_tracing: bool,
+    // If true, serve `EXPERIMENTAL_simulate_tx`. Disabled by default, as simulations execute
+    // arbitrary transactions on the node.
+    #[serde(default)]
+    pub enable_tx_simulation: bool,
     // For node developers only: if specified, the HTML files used to serve the debug pages will
     // be read from this directory, instead of the contents compiled into the binary. This allows
     // for quick iterative development.
@@ -108,6 +141,8 @@ impl Default for RpcConfig {
             polling_config: Default::default(),
             limits_config: Default::default(),
             enable_debug_rpc: false,
+            enable_host_call_tracing: false,
+            enable_tx_simulation: false,
             experimental_debug_pages_src_path: None,
         }
     }
@@ -226,6 +261,7 @@ fn process_query_response(
 pub struct ProcessTxSenderForRpc(
     AsyncSender<ProcessTxRequest, ActixResult<ProcessTxRequest>>,
     Sender<ProcessTxRequest>,
+    AsyncSender<GetMempool, ActixResult<GetMempool>>,
 );
 
 #[derive(Clone, near_async::MultiSend, near_async::MultiSenderFrom)]
@@ -252,6 +288,7 @@ pub struct ViewClientSenderForRpc(
     AsyncSender<GetBlock, ActixResult<GetBlock>>,
     AsyncSender<GetBlockProof, ActixResult<GetBlockProof>>,
     AsyncSender<GetChunk, ActixResult<GetChunk>>,
+    AsyncSender<GetChunkBalanceAudit, ActixResult<GetChunkBalanceAudit>>,
     AsyncSender<GetExecutionOutcome, ActixResult<GetExecutionOutcome>>,
     AsyncSender<GetGasPrice, ActixResult<GetGasPrice>>,
     AsyncSender<GetMaintenanceWindows, ActixResult<GetMaintenanceWindows>>,
@@ -264,6 +301,7 @@ pub struct ViewClientSenderForRpc(
     AsyncSender<GetValidatorInfo, ActixResult<GetValidatorInfo>>,
     AsyncSender<GetValidatorOrdered, ActixResult<GetValidatorOrdered>>,
     AsyncSender<Query, ActixResult<Query>>,
+    AsyncSender<SimulateTransaction, ActixResult<SimulateTransaction>>,
     AsyncSender<TxStatus, ActixResult<TxStatus>>,
     #[cfg(feature = "test_features")] Sender<near_client::NetworkAdversarialMessage>,
 );
@@ -288,8 +326,11 @@ struct JsonRpcHandler {
     #[cfg(feature = "test_features")]
     gc_sender: GCSenderForRpc,
     polling_config: RpcPollingConfig,
+    limits_config: RpcLimitsConfig,
     genesis_config: GenesisConfig,
     enable_debug_rpc: bool,
+    enable_host_call_tracing: bool,
+    enable_tx_simulation: bool,
     debug_pages_src_path: Option<PathBuf>,
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
 }
@@ -352,6 +393,10 @@ impl JsonRpcHandler {
                 let metrics_name = match params.request {
                     QueryRequest::ViewAccount { .. } => "query_view_account",
                     QueryRequest::ViewCode { .. } => "query_view_code",
+                    QueryRequest::ViewGlobalContractCode { .. }
+                    | QueryRequest::ViewGlobalContractCodeByAccountId { .. } => {
+                        "query_view_global_contract_code"
+                    }
                     QueryRequest::ViewState { include_proof, .. } => {
                         if include_proof {
                             "query_view_state_with_proof"
@@ -416,6 +461,9 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_changes_in_block" => {
                 process_method_call(request, |params| self.changes_in_block(params)).await
             }
+            "EXPERIMENTAL_chunk_balance_audit" => {
+                process_method_call(request, |params| self.chunk_balance_audit(params)).await
+            }
             "EXPERIMENTAL_congestion_level" => {
                 process_method_call(request, |params| self.congestion_level(params)).await
             }
@@ -452,6 +500,16 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_split_storage_info" => {
                 process_method_call(request, |params| self.split_storage_info(params)).await
             }
+            "EXPERIMENTAL_mempool" => {
+                process_method_call(request, |params| self.mempool(params)).await
+            }
+            "EXPERIMENTAL_verify_signed_message" => {
+                process_method_call(request, |params| self.verify_signed_message(params)).await
+            }
+            "EXPERIMENTAL_simulate_tx" => {
+                process_method_call(request, |params| self.simulate_tx(params)).await
+            }
+            "subscribe" | "unsubscribe" => Err(RpcSubscriptionError::NotAvailable.into()),
             #[cfg(feature = "sandbox")]
             "sandbox_patch_state" => {
                 process_method_call(request, |params| self.sandbox_patch_state(params)).await
@@ -460,6 +518,14 @@ impl JsonRpcHandler {
             "sandbox_fast_forward" => {
                 process_method_call(request, |params| self.sandbox_fast_forward(params)).await
             }
+            #[cfg(feature = "sandbox")]
+            "sandbox_snapshot" => {
+                process_method_call(request, |_params: ()| self.sandbox_snapshot()).await
+            }
+            #[cfg(feature = "sandbox")]
+            "sandbox_revert" => {
+                process_method_call(request, |params| self.sandbox_revert(params)).await
+            }
             _ => return Err(request),
         })
     }
@@ -900,6 +966,12 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::query::RpcQueryResponse,
         near_jsonrpc_primitives::types::query::RpcQueryError,
     > {
+        let trace = matches!(request_data.request, QueryRequest::CallFunction { trace: true, .. });
+        if trace && !self.enable_host_call_tracing {
+            return Err(
+                near_jsonrpc_primitives::types::query::RpcQueryError::HostCallTracingDisabled,
+            );
+        }
         let query_response = self
             .view_client_send(Query::new(request_data.block_reference, request_data.request))
             .await?;
@@ -978,6 +1050,24 @@ impl JsonRpcHandler {
         })
     }
 
+    async fn chunk_balance_audit(
+        &self,
+        request_data: near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditResponse,
+        near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditError,
+    > {
+        let balance_audit = self
+            .view_client_send(GetChunkBalanceAudit {
+                block_reference: request_data.block_reference,
+                shard_id: request_data.shard_id,
+            })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::balance_audit::RpcChunkBalanceAuditResponse {
+            balance_audit,
+        })
+    }
+
     async fn receipt(
         &self,
         request_data: near_jsonrpc_primitives::types::receipts::RpcReceiptRequest,
@@ -1030,7 +1120,7 @@ impl JsonRpcHandler {
             self.view_client_send(GetBlock(request.block_reference)).await?;
 
         let block_hash = block.header.hash;
-        let changes = self
+        let GetStateChangesResponse { changes, served_from } = self
             .view_client_send(GetStateChanges {
                 block_hash,
                 state_changes_request: request.state_changes_request,
@@ -1040,6 +1130,7 @@ impl JsonRpcHandler {
         Ok(near_jsonrpc_primitives::types::changes::RpcStateChangesInBlockResponse {
             block_hash: block.header.hash,
             changes,
+            served_from,
         })
     }
 
@@ -1207,6 +1298,101 @@ impl JsonRpcHandler {
         let split_storage = self.view_client_send(GetSplitStorageInfo {}).await?;
         Ok(RpcSplitStorageInfoResponse { result: split_storage })
     }
+
+    pub async fn mempool(
+        &self,
+        request_data: near_jsonrpc_primitives::types::mempool::RpcMempoolRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::mempool::RpcMempoolResponse,
+        near_jsonrpc_primitives::types::mempool::RpcMempoolError,
+    > {
+        use near_jsonrpc_primitives::types::mempool::{MAX_MEMPOOL_LIMIT, RpcMempoolError};
+
+        // Listing the pools takes the pool lock, so like the other debug endpoints it's only
+        // served if explicitly enabled.
+        if !self.enable_debug_rpc {
+            return Err(RpcMempoolError::DebugRpcDisabled);
+        }
+        let limit = request_data.limit.unwrap_or(MAX_MEMPOOL_LIMIT).clamp(1, MAX_MEMPOOL_LIMIT);
+        let mempool = self
+            .process_tx_sender
+            .send_async(GetMempool {
+                signer_id: request_data.signer_id,
+                after: request_data.cursor,
+                limit: limit as usize,
+            })
+            .await
+            .map_err(RpcFrom::rpc_from)?;
+        Ok(near_jsonrpc_primitives::types::mempool::RpcMempoolResponse { result: mempool })
+    }
+
+    pub async fn verify_signed_message(
+        &self,
+        request_data: near_jsonrpc_primitives::types::signed_message::RpcVerifySignedMessageRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::signed_message::RpcVerifySignedMessageResponse,
+        near_jsonrpc_primitives::types::signed_message::RpcVerifySignedMessageError,
+    > {
+        use near_jsonrpc_primitives::types::signed_message::{
+            RpcVerifySignedMessageError, RpcVerifySignedMessageResponse,
+        };
+
+        if !request_data.payload.verify(&request_data.signature, &request_data.public_key) {
+            return Err(RpcVerifySignedMessageError::InvalidSignature);
+        }
+        let query_response = self
+            .view_client_send(Query::new(
+                request_data.block_reference,
+                QueryRequest::ViewAccessKey {
+                    account_id: request_data.account_id,
+                    public_key: request_data.public_key.clone(),
+                },
+            ))
+            .await?;
+        let QueryResponseKind::AccessKey(access_key) = query_response.kind else {
+            return Err(RpcVerifySignedMessageError::InternalError {
+                error_message: format!("unexpected query response: {:?}", query_response.kind),
+            });
+        };
+        if access_key.permission != AccessKeyPermissionView::FullAccess {
+            return Err(RpcVerifySignedMessageError::NotFullAccessKey {
+                public_key: request_data.public_key,
+                block_height: query_response.block_height,
+                block_hash: query_response.block_hash,
+            });
+        }
+        Ok(RpcVerifySignedMessageResponse {
+            block_height: query_response.block_height,
+            block_hash: query_response.block_hash,
+        })
+    }
+
+    pub async fn simulate_tx(
+        &self,
+        request_data: near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionResponse,
+        near_jsonrpc_primitives::types::transactions::RpcTransactionError,
+    > {
+        if !self.enable_tx_simulation {
+            return Err(
+                near_jsonrpc_primitives::types::transactions::RpcTransactionError::TxSimulationDisabled,
+            );
+        }
+        if request_data.trace && !self.enable_host_call_tracing {
+            return Err(
+                near_jsonrpc_primitives::types::transactions::RpcTransactionError::HostCallTracingDisabled,
+            );
+        }
+        let result = self
+            .view_client_send(SimulateTransaction {
+                signed_transaction: request_data.signed_transaction,
+                check_signature: !request_data.skip_signature_check,
+                trace_host_calls: request_data.trace,
+            })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionResponse { result })
+    }
 }
 
 #[cfg(feature = "sandbox")]
@@ -1300,6 +1486,97 @@ impl JsonRpcHandler {
 
         Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxFastForwardResponse {})
     }
+
+    async fn sandbox_snapshot(
+        &self,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotError,
+    > {
+        use near_client_primitives::types::SandboxResponse;
+
+        match self
+            .client_sender
+            .send_async(near_client_primitives::types::SandboxMessage::SandboxSnapshot)
+            .await
+            .map_err(RpcFrom::rpc_from)?
+        {
+            SandboxResponse::SandboxSnapshotCreated(snapshot_id) => {
+                Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotResponse {
+                    snapshot_id,
+                })
+            }
+            SandboxResponse::SandboxSnapshotFailed(error_message) => Err(
+                near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotError::InternalError {
+                    error_message,
+                },
+            ),
+            response => Err(
+                near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotError::InternalError {
+                    error_message: format!("unexpected response: {:?}", response),
+                },
+            ),
+        }
+    }
+
+    async fn sandbox_revert(
+        &self,
+        revert_request: near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError,
+    > {
+        use near_client_primitives::types::SandboxResponse;
+
+        let response = self
+            .client_sender
+            .send_async(near_client_primitives::types::SandboxMessage::SandboxRevert(
+                revert_request.snapshot_id,
+            ))
+            .await
+            .map_err(RpcFrom::rpc_from)?;
+        if let SandboxResponse::SandboxRevertFailed(error_message) = response {
+            return Err(
+                near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError::InternalError {
+                    error_message,
+                },
+            );
+        }
+
+        // The revert happens before the next block is produced.
+        timeout(self.polling_config.polling_timeout, async {
+            loop {
+                let revert_finished = self
+                    .client_sender
+                    .send_async(
+                        near_client_primitives::types::SandboxMessage::SandboxRevertStatus {},
+                    )
+                    .await;
+
+                match revert_finished {
+                    Ok(SandboxResponse::SandboxRevertFinished(true)) => break,
+                    Ok(SandboxResponse::SandboxRevertFailed(err)) => return Err(err),
+                    _ => (),
+                }
+
+                let _ = sleep(self.polling_config.polling_interval).await;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|_| {
+            near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError::InternalError {
+                error_message: "sandbox failed to revert the state in time".to_string(),
+            }
+        })?
+        .map_err(|err| {
+            near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError::InternalError {
+                error_message: format!("sandbox failed to revert due to: {:?}", err),
+            }
+        })?;
+
+        Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertResponse {})
+    }
 }
 
 #[cfg(feature = "test_features")]
@@ -1640,7 +1917,8 @@ async fn display_debug_html(
 
 /// Starts HTTP server(s) listening for RPC requests.
 ///
-/// Starts an HTTP server which handles JSON RPC calls as well as states
+/// Starts an HTTP server which handles JSON RPC calls (over plain HTTP and over
+/// WebSocket at `/ws`) as well as states
 /// endpoints such as `/status`, `/health`, `/metrics` etc.  Depending on
 /// configuration may also start another HTTP server just for providing
 /// Prometheus metrics (i.e. covering the `/metrics` path).
@@ -1666,6 +1944,8 @@ pub fn start_http(
         polling_config,
         limits_config,
         enable_debug_rpc,
+        enable_host_call_tracing,
+        enable_tx_simulation,
         experimental_debug_pages_src_path: debug_pages_src_path,
     } = config;
     let prometheus_addr = prometheus_addr.filter(|it| it != &addr.to_string());
@@ -1681,8 +1961,11 @@ pub fn start_http(
                 process_tx_sender: process_tx_sender.clone(),
                 peer_manager_sender: peer_manager_sender.clone(),
                 polling_config,
+                limits_config: limits_config.clone(),
                 genesis_config: genesis_config.clone(),
                 enable_debug_rpc,
+                enable_host_call_tracing,
+                enable_tx_simulation,
                 debug_pages_src_path: debug_pages_src_path.clone().map(Into::into),
                 entity_debug_handler: entity_debug_handler.clone(),
                 #[cfg(feature = "test_features")]
@@ -1691,6 +1974,7 @@ pub fn start_http(
             .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
+            .service(web::resource("/ws").route(web::get().to(websocket::ws_handler)))
             .service(
                 web::resource("/status")
                     .route(web::get().to(status_handler))
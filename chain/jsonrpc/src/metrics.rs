@@ -1,4 +1,4 @@
-use near_o11y::metrics::{HistogramVec, IntCounter, IntCounterVec, exponential_buckets};
+use near_o11y::metrics::{HistogramVec, IntCounter, IntCounterVec, IntGauge, exponential_buckets};
 use std::sync::LazyLock;
 
 pub static RPC_PROCESSING_TIME: LazyLock<HistogramVec> = LazyLock::new(|| {
@@ -55,3 +55,17 @@ pub static RPC_UNREACHABLE_ERROR_COUNT: LazyLock<IntCounterVec> = LazyLock::new(
     )
     .unwrap()
 });
+pub static WS_OPEN_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_gauge(
+        "near_rpc_ws_open_connections",
+        "Number of currently open WebSocket RPC connections",
+    )
+    .unwrap()
+});
+pub static WS_ACTIVE_SUBSCRIPTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_gauge(
+        "near_rpc_ws_active_subscriptions",
+        "Number of currently active WebSocket RPC subscriptions",
+    )
+    .unwrap()
+});
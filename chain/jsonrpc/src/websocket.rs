@@ -0,0 +1,392 @@
+//! WebSocket transport for the JSON-RPC server.
+//!
+//! A WebSocket connection accepts the same JSON-RPC requests as the HTTP
+//! endpoint plus two connection-scoped methods:
+//!
+//! * `subscribe` starts pushing `subscription` notifications for new heads,
+//!   state changes or transaction status transitions and returns the id of the
+//!   subscription;
+//! * `unsubscribe` stops the subscription with the given id.
+//!
+//! Every subscription is served by a task which polls the view client every
+//! `RpcPollingConfig::polling_interval`, so subscriptions are only a cheaper
+//! transport for the queries clients would otherwise issue themselves.
+//! Requests on a single connection are processed sequentially.
+
+use crate::{JsonRpcHandler, RpcRequest, metrics, tx_execution_status_meets_expectations};
+use actix_http::ws;
+use actix_web::body::{BodyStream, MessageBody};
+use actix_web::{Error as HttpError, HttpRequest, HttpResponse, web};
+use bytes::BytesMut;
+use futures::StreamExt;
+use near_client::TxStatus;
+use near_jsonrpc_primitives::errors::RpcError;
+use near_jsonrpc_primitives::message::{Message, Request};
+use near_jsonrpc_primitives::types::blocks::RpcBlockRequest;
+use near_jsonrpc_primitives::types::changes::RpcStateChangesInBlockByTypeRequest;
+use near_jsonrpc_primitives::types::subscriptions::{
+    RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError, RpcSubscriptionEvent,
+    RpcSubscriptionId, RpcSubscriptionNotification, RpcUnsubscribeRequest, RpcUnsubscribeResponse,
+};
+use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight, BlockId, BlockReference, Finality};
+use near_primitives::views::{BlockView, StateChangesRequestView, TxExecutionStatus, TxStatusView};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Maximum number of blocks a head subscription walks back to fill the gap
+/// between two polls. Older blocks are not reported.
+const MAX_CATCH_UP_BLOCKS: usize = 32;
+
+/// Name of the JSON-RPC notification carrying subscription events.
+const SUBSCRIPTION_NOTIFICATION: &str = "subscription";
+
+/// Upgrades the request to a WebSocket connection and serves it until either
+/// side closes it.
+pub(crate) async fn ws_handler(
+    req: HttpRequest,
+    payload: web::Payload,
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    let mut response = ws::handshake(req.head())?;
+    let limits = &handler.limits_config;
+    let codec = ws::Codec::new().max_size(limits.json_payload_max_size);
+    let (sender, receiver) = mpsc::channel(limits.ws_max_pending_messages);
+
+    let connection = Connection::new(handler.into_inner(), sender);
+    actix_web::rt::spawn(connection.run(payload, codec.clone()));
+
+    let body = BodyStream::new(encode_messages(receiver, codec));
+    Ok(response.message_body(body.boxed())?.into())
+}
+
+/// Turns the queue of outgoing messages into a stream of encoded frames.
+///
+/// The stream ends after the close frame has been sent or once the connection
+/// task has dropped its sender.
+fn encode_messages(
+    receiver: mpsc::Receiver<ws::Message>,
+    codec: ws::Codec,
+) -> impl futures::Stream<Item = Result<bytes::Bytes, ws::ProtocolError>> {
+    futures::stream::unfold(Some((receiver, codec)), |state| async move {
+        let (mut receiver, mut codec) = state?;
+        let message = receiver.recv().await?;
+        let is_close = matches!(message, ws::Message::Close(_));
+        let mut buf = BytesMut::new();
+        let result = codec.encode(message, &mut buf).map(|()| buf.freeze());
+        let next_state = if is_close || result.is_err() { None } else { Some((receiver, codec)) };
+        Some((result, next_state))
+    })
+}
+
+struct Connection {
+    handler: Arc<JsonRpcHandler>,
+    sender: mpsc::Sender<ws::Message>,
+    subscriptions: HashMap<RpcSubscriptionId, JoinHandle<()>>,
+    next_subscription_id: RpcSubscriptionId,
+}
+
+impl Connection {
+    fn new(handler: Arc<JsonRpcHandler>, sender: mpsc::Sender<ws::Message>) -> Self {
+        metrics::WS_OPEN_CONNECTIONS.inc();
+        Self { handler, sender, subscriptions: HashMap::new(), next_subscription_id: 0 }
+    }
+
+    async fn run(mut self, mut payload: web::Payload, mut codec: ws::Codec) {
+        let mut buf = BytesMut::new();
+        'connection: loop {
+            loop {
+                let frame = match codec.decode(&mut buf) {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => break,
+                    Err(err) => {
+                        tracing::debug!(target: "jsonrpc", ?err, "Malformed WebSocket frame");
+                        self.close(ws::CloseCode::Protocol).await;
+                        break 'connection;
+                    }
+                };
+                match frame {
+                    ws::Frame::Text(bytes) | ws::Frame::Binary(bytes) => {
+                        let response = self.process_message(&bytes).await;
+                        if self.sender.send(ws::Message::Text(response.into())).await.is_err() {
+                            break 'connection;
+                        }
+                    }
+                    ws::Frame::Ping(bytes) => {
+                        if self.sender.send(ws::Message::Pong(bytes)).await.is_err() {
+                            break 'connection;
+                        }
+                    }
+                    ws::Frame::Pong(_) => {}
+                    ws::Frame::Continuation(_) => {
+                        self.close(ws::CloseCode::Unsupported).await;
+                        break 'connection;
+                    }
+                    ws::Frame::Close(_) => {
+                        self.close(ws::CloseCode::Normal).await;
+                        break 'connection;
+                    }
+                }
+            }
+            match payload.next().await {
+                Some(Ok(bytes)) => buf.extend_from_slice(&bytes),
+                Some(Err(err)) => {
+                    tracing::debug!(target: "jsonrpc", ?err, "WebSocket connection failed");
+                    break;
+                }
+                None => break,
+            }
+        }
+    }
+
+    async fn close(&self, code: ws::CloseCode) {
+        let _ = self.sender.send(ws::Message::Close(Some(code.into()))).await;
+    }
+
+    /// Processes a single text frame and returns the serialized response.
+    async fn process_message(&mut self, bytes: &[u8]) -> String {
+        let response = match serde_json::from_slice::<Message>(bytes) {
+            Ok(Message::Request(request)) => {
+                let id = request.id.clone();
+                match request.method.as_ref() {
+                    "subscribe" => Message::response(id, self.subscribe(request)),
+                    "unsubscribe" => Message::response(id, self.unsubscribe(request)),
+                    _ => self.handler.process(Message::Request(request)).await,
+                }
+            }
+            Ok(_) => {
+                Message::error(RpcError::parse_error("JSON RPC Request format was expected".into()))
+            }
+            Err(err) => Message::error(RpcError::parse_error(err.to_string())),
+        };
+        serde_json::to_string(&response).expect("JSON RPC messages are always serializable")
+    }
+
+    fn subscribe(&mut self, request: Request) -> Result<Value, RpcError> {
+        let params = RpcSubscribeRequest::parse(request.params)?;
+        self.subscriptions.retain(|_, subscription| !subscription.is_finished());
+        let limit = self.handler.limits_config.ws_max_subscriptions_per_connection;
+        if self.subscriptions.len() >= limit {
+            return Err(RpcSubscriptionError::TooManySubscriptions { limit }.into());
+        }
+
+        let subscription_id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        let subscription =
+            Subscription::new(self.handler.clone(), self.sender.clone(), subscription_id);
+        let task = actix_web::rt::spawn(async move {
+            match params {
+                RpcSubscribeRequest::NewHeads { finality } => {
+                    subscription.follow_blocks(finality, None).await
+                }
+                RpcSubscribeRequest::StateChanges { finality, state_changes_request } => {
+                    subscription.follow_blocks(finality, Some(state_changes_request)).await
+                }
+                RpcSubscribeRequest::TxStatus { tx_hash, sender_account_id, wait_until } => {
+                    subscription.follow_tx(tx_hash, sender_account_id, wait_until).await
+                }
+            }
+        });
+        self.subscriptions.insert(subscription_id, task);
+        crate::serialize_response(RpcSubscribeResponse { subscription_id })
+    }
+
+    fn unsubscribe(&mut self, request: Request) -> Result<Value, RpcError> {
+        let RpcUnsubscribeRequest { subscription_id } =
+            RpcUnsubscribeRequest::parse(request.params)?;
+        match self.subscriptions.remove(&subscription_id) {
+            Some(subscription) => {
+                subscription.abort();
+                crate::serialize_response(RpcUnsubscribeResponse {})
+            }
+            None => Err(RpcSubscriptionError::UnknownSubscription { subscription_id }.into()),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for (_, subscription) in self.subscriptions.drain() {
+            subscription.abort();
+        }
+        metrics::WS_OPEN_CONNECTIONS.dec();
+    }
+}
+
+struct Subscription {
+    handler: Arc<JsonRpcHandler>,
+    sender: mpsc::Sender<ws::Message>,
+    subscription_id: RpcSubscriptionId,
+}
+
+impl Subscription {
+    fn new(
+        handler: Arc<JsonRpcHandler>,
+        sender: mpsc::Sender<ws::Message>,
+        subscription_id: RpcSubscriptionId,
+    ) -> Self {
+        metrics::WS_ACTIVE_SUBSCRIPTIONS.inc();
+        Self { handler, sender, subscription_id }
+    }
+
+    /// Sends a notification to the client.
+    ///
+    /// Waits while the outgoing queue of the connection is full, which pauses
+    /// the subscription until the client catches up.  Returns false if the
+    /// connection has been closed.
+    async fn notify(&self, event: RpcSubscriptionEvent) -> bool {
+        let notification =
+            RpcSubscriptionNotification { subscription_id: self.subscription_id, event };
+        let params = match serde_json::to_value(notification) {
+            Ok(params) => params,
+            Err(err) => {
+                tracing::warn!(target: "jsonrpc", ?err, "Failed to serialize subscription event");
+                return true;
+            }
+        };
+        let message = Message::notification(SUBSCRIPTION_NOTIFICATION.to_string(), params);
+        let text = serde_json::to_string(&message)
+            .expect("JSON RPC notifications are always serializable");
+        self.sender.send(ws::Message::Text(text.into())).await.is_ok()
+    }
+
+    /// Notifies about every new block at the given finality, or about the
+    /// matching state changes in it if `state_changes_request` is set.
+    async fn follow_blocks(
+        &self,
+        finality: Finality,
+        state_changes_request: Option<StateChangesRequestView>,
+    ) {
+        let polling_interval = self.handler.polling_config.polling_interval;
+        let mut last_block: Option<(CryptoHash, BlockHeight)> = None;
+        loop {
+            let head = self
+                .handler
+                .block(RpcBlockRequest {
+                    block_reference: BlockReference::Finality(finality.clone()),
+                })
+                .await;
+            match head {
+                Ok(head)
+                    if last_block.map(|(hash, _)| hash) != Some(head.block_view.header.hash) =>
+                {
+                    for block in self.blocks_since(head.block_view, last_block).await {
+                        last_block = Some((block.header.hash, block.header.height));
+                        let event = match &state_changes_request {
+                            None => RpcSubscriptionEvent::NewHead(block.header),
+                            Some(state_changes_request) => {
+                                let changes = self
+                                    .handler
+                                    .changes_in_block_by_type(RpcStateChangesInBlockByTypeRequest {
+                                        block_reference: BlockReference::BlockId(BlockId::Hash(
+                                            block.header.hash,
+                                        )),
+                                        state_changes_request: state_changes_request.clone(),
+                                    })
+                                    .await;
+                                match changes {
+                                    Ok(changes) if changes.changes.is_empty() => continue,
+                                    Ok(changes) => RpcSubscriptionEvent::StateChanges(changes),
+                                    Err(err) => RpcSubscriptionEvent::Error(err.into()),
+                                }
+                            }
+                        };
+                        let is_error = matches!(event, RpcSubscriptionEvent::Error(_));
+                        if !self.notify(event).await || is_error {
+                            return;
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::debug!(target: "jsonrpc", ?err, "Failed to fetch head for subscription");
+                }
+            }
+            sleep(polling_interval).await;
+        }
+    }
+
+    /// Returns the blocks between the last notified block and `head`, oldest
+    /// first.  Walks back at most `MAX_CATCH_UP_BLOCKS` blocks.
+    async fn blocks_since(
+        &self,
+        head: BlockView,
+        last_block: Option<(CryptoHash, BlockHeight)>,
+    ) -> Vec<BlockView> {
+        let mut blocks = vec![head];
+        if let Some((last_hash, last_height)) = last_block {
+            while blocks.len() < MAX_CATCH_UP_BLOCKS {
+                let prev_hash = blocks.last().unwrap().header.prev_hash;
+                if prev_hash == last_hash {
+                    break;
+                }
+                let prev_block = self
+                    .handler
+                    .block(RpcBlockRequest {
+                        block_reference: BlockReference::BlockId(BlockId::Hash(prev_hash)),
+                    })
+                    .await;
+                match prev_block {
+                    Ok(prev_block) if prev_block.block_view.header.height > last_height => {
+                        blocks.push(prev_block.block_view)
+                    }
+                    _ => break,
+                }
+            }
+        }
+        blocks.reverse();
+        blocks
+    }
+
+    /// Notifies about every status transition of the transaction until it
+    /// reaches `wait_until`.
+    async fn follow_tx(
+        &self,
+        tx_hash: CryptoHash,
+        signer_account_id: AccountId,
+        wait_until: TxExecutionStatus,
+    ) {
+        let polling_config = self.handler.polling_config;
+        let started = Instant::now();
+        let mut last_status = None;
+        loop {
+            let status: Result<TxStatusView, RpcTransactionError> = self
+                .handler
+                .view_client_send(TxStatus {
+                    tx_hash,
+                    signer_account_id: signer_account_id.clone(),
+                    fetch_receipt: false,
+                })
+                .await;
+            match status {
+                Ok(status) if last_status.as_ref() != Some(&status.status) => {
+                    last_status = Some(status.status.clone());
+                    let done = tx_execution_status_meets_expectations(&wait_until, &status.status);
+                    if !self.notify(RpcSubscriptionEvent::TxStatus(status.into())).await || done {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(RpcTransactionError::UnknownTransaction { .. })
+                    if started.elapsed() < polling_config.polling_timeout => {}
+                Err(err) => {
+                    self.notify(RpcSubscriptionEvent::Error(err.into())).await;
+                    return;
+                }
+            }
+            sleep(polling_config.polling_interval).await;
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        metrics::WS_ACTIVE_SUBSCRIPTIONS.dec();
+    }
+}
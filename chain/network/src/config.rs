@@ -193,6 +193,10 @@ pub struct NetworkConfig {
     //   * ignoring received deleted edges as well
     pub skip_tombstones: Option<time::Duration>,
 
+    /// Whether to reject TIER1 and TIER2 connections with peers which don't
+    /// negotiate an encrypted transport in the handshake.
+    pub require_encrypted_transport: bool,
+
     /// Configuration of rate limits for incoming messages.
     pub received_messages_rate_limits: messages_limits::Config,
 
@@ -383,6 +387,7 @@ impl NetworkConfig {
             } else {
                 None
             },
+            require_encrypted_transport: cfg.experimental.require_encrypted_transport,
             // Use a preset to configure rate limits and override entries with user defined values later.
             received_messages_rate_limits: messages_limits::Config::standard_preset(),
             #[cfg(test)]
@@ -462,6 +467,7 @@ impl NetworkConfig {
                 enable_outbound: true,
             }),
             skip_tombstones: None,
+            require_encrypted_transport: false,
             received_messages_rate_limits: messages_limits::Config::default(),
             #[cfg(test)]
             event_sink: near_async::messaging::IntoSender::into_sender(
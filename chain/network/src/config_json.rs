@@ -265,6 +265,10 @@ pub struct ExperimentalConfig {
     #[serde(default = "default_tier1_new_connections_per_attempt")]
     pub tier1_new_connections_per_attempt: u64,
 
+    /// See `NetworkConfig::require_encrypted_transport`.
+    #[serde(default)]
+    pub require_encrypted_transport: bool,
+
     /// See `NetworkConfig`.
     /// Fields set here will override the NetworkConfig fields.
     #[serde(default)]
@@ -299,6 +303,7 @@ impl Default for ExperimentalConfig {
             tier1_enable_outbound: default_tier1_enable_outbound(),
             tier1_connect_interval: default_tier1_connect_interval(),
             tier1_new_connections_per_attempt: default_tier1_new_connections_per_attempt(),
+            require_encrypted_transport: false,
             network_config_overrides: Default::default(),
         }
     }
@@ -55,7 +55,7 @@ use near_primitives::state_sync::{ShardStateSyncResponse, ShardStateSyncResponse
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::AccountId;
 use near_primitives::types::{BlockHeight, ShardId};
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{ValidatorMessage, ValidatorSigner, ValidatorSignerError};
 use near_primitives::views::FinalExecutionOutcomeView;
 use near_schema_checker_lib::ProtocolSchema;
 use protobuf::Message as _;
@@ -196,7 +196,8 @@ impl VersionedAccountData {
                 MAX_ACCOUNT_DATA_SIZE_BYTES
             );
         }
-        let signature = signer.sign_bytes(&payload);
+        let signature = signer
+            .sign_message(&ValidatorMessage::AccountKeyPayload { payload: payload.clone() })?;
         Ok(SignedAccountData {
             account_data: self,
             payload: AccountKeySignedPayload { payload, signature },
@@ -268,18 +269,22 @@ impl OwnedAccount {
     /// Serializes OwnedAccount to proto and signs it using `signer`.
     /// Panics if OwnedAccount.account_key doesn't match signer.public_key(),
     /// as this would likely be a bug.
-    pub fn sign(self, signer: &ValidatorSigner) -> SignedOwnedAccount {
+    pub fn sign(
+        self,
+        signer: &ValidatorSigner,
+    ) -> Result<SignedOwnedAccount, ValidatorSignerError> {
         assert_eq!(
             self.account_key,
             signer.public_key(),
             "OwnedAccount.account_key doesn't match the signer's account_key"
         );
         let payload = proto::AccountKeyPayload::from(&self).write_to_bytes().unwrap();
-        let signature = signer.sign_bytes(&payload);
-        SignedOwnedAccount {
+        let signature = signer
+            .sign_message(&ValidatorMessage::AccountKeyPayload { payload: payload.clone() })?;
+        Ok(SignedOwnedAccount {
             owned_account: self,
             payload: AccountKeySignedPayload { payload, signature },
-        }
+        })
     }
 }
 
@@ -367,6 +372,44 @@ pub struct Handshake {
     pub(crate) partial_edge_info: PartialEdgeInfo,
     /// Account owned by the sender.
     pub(crate) owned_account: Option<SignedOwnedAccount>,
+    /// Ephemeral key of the sender used to establish an encrypted transport.
+    /// Peers which don't support encryption leave it empty.
+    pub(crate) transport_key: Option<TransportKey>,
+}
+
+/// Ephemeral X25519 public key of a peer, signed with its node key.
+///
+/// If both sides of a connection send a `TransportKey` in their handshakes,
+/// all the frames sent after the handshakes are encrypted with keys derived
+/// from the two ephemeral keys.  The signature binds the ephemeral key to the
+/// `PeerId` of the sender and to the connection with the target peer.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TransportKey {
+    pub(crate) ephemeral_key: [u8; 32],
+    pub(crate) signature: Signature,
+}
+
+impl TransportKey {
+    pub fn new(
+        sender: &PeerId,
+        target: &PeerId,
+        ephemeral_key: [u8; 32],
+        secret_key: &near_crypto::SecretKey,
+    ) -> Self {
+        let hash = Self::build_hash(sender, target, &ephemeral_key);
+        Self { ephemeral_key, signature: secret_key.sign(hash.as_ref()) }
+    }
+
+    /// Verifies that the key was signed by `sender` for a connection with
+    /// `target`.
+    pub fn verify(&self, sender: &PeerId, target: &PeerId) -> bool {
+        let hash = Self::build_hash(sender, target, &self.ephemeral_key);
+        self.signature.verify(hash.as_ref(), sender.public_key())
+    }
+
+    fn build_hash(sender: &PeerId, target: &PeerId, ephemeral_key: &[u8; 32]) -> CryptoHash {
+        CryptoHash::hash_borsh(("transport_key", sender, target, ephemeral_key))
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, strum::IntoStaticStr)]
@@ -2,7 +2,7 @@
 use super::*;
 
 use crate::network_protocol::proto;
-use crate::network_protocol::{Handshake, HandshakeFailureReason};
+use crate::network_protocol::{Handshake, HandshakeFailureReason, TransportKey};
 use crate::network_protocol::{PeerChainInfoV2, PeerInfo};
 use near_primitives::genesis::GenesisId;
 use protobuf::MessageField as MF;
@@ -63,6 +63,40 @@ impl TryFrom<&proto::PeerChainInfo> for PeerChainInfoV2 {
 
 //////////////////////////////////////////
 
+#[derive(thiserror::Error, Debug)]
+pub enum ParseTransportKeyError {
+    #[error("ephemeral_key: invalid length {0}")]
+    EphemeralKey(usize),
+    #[error("signature {0}")]
+    Signature(ParseRequiredError<ParseSignatureError>),
+}
+
+impl From<&TransportKey> for proto::TransportKey {
+    fn from(x: &TransportKey) -> Self {
+        Self {
+            ephemeral_key: x.ephemeral_key.to_vec(),
+            signature: MF::some((&x.signature).into()),
+            ..Self::default()
+        }
+    }
+}
+
+impl TryFrom<&proto::TransportKey> for TransportKey {
+    type Error = ParseTransportKeyError;
+    fn try_from(p: &proto::TransportKey) -> Result<Self, Self::Error> {
+        Ok(Self {
+            ephemeral_key: p
+                .ephemeral_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| Self::Error::EphemeralKey(p.ephemeral_key.len()))?,
+            signature: try_from_required(&p.signature).map_err(Self::Error::Signature)?,
+        })
+    }
+}
+
+//////////////////////////////////////////
+
 #[derive(thiserror::Error, Debug)]
 pub enum ParseHandshakeError {
     #[error("sender_peer_id {0}")]
@@ -77,6 +111,8 @@ pub enum ParseHandshakeError {
     PartialEdgeInfo(ParseRequiredError<ParsePartialEdgeInfoError>),
     #[error("owned_account {0}")]
     OwnedAccount(ParseSignedOwnedAccountError),
+    #[error("transport_key {0}")]
+    TransportKey(ParseTransportKeyError),
 }
 
 impl From<&Handshake> for proto::Handshake {
@@ -90,6 +126,7 @@ impl From<&Handshake> for proto::Handshake {
             sender_chain_info: MF::some((&x.sender_chain_info).into()),
             partial_edge_info: MF::some((&x.partial_edge_info).into()),
             owned_account: x.owned_account.as_ref().map(Into::into).into(),
+            transport_key: x.transport_key.as_ref().map(Into::into).into(),
             ..Self::default()
         }
     }
@@ -116,6 +153,8 @@ impl TryFrom<&proto::Handshake> for Handshake {
                 .map_err(Self::Error::PartialEdgeInfo)?,
             owned_account: try_from_optional(&p.owned_account)
                 .map_err(Self::Error::OwnedAccount)?,
+            transport_key: try_from_optional(&p.transport_key)
+                .map_err(Self::Error::TransportKey)?,
         })
     }
 }
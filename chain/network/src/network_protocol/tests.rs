@@ -61,8 +61,16 @@ fn serialize_deserialize_protobuf_only() {
     let mut rng = make_rng(39521947542);
     let mut clock = time::FakeClock::default();
     let chain = data::Chain::make(&mut clock, &mut rng, 12);
+    // TransportKey is supported only by protobuf.
+    let mut handshake = data::make_handshake(&mut rng, &chain);
+    handshake.transport_key = Some(TransportKey::new(
+        &handshake.sender_peer_id,
+        &handshake.target_peer_id,
+        rng.r#gen(),
+        &data::make_secret_key(&mut rng),
+    ));
     let msgs = [
-        PeerMessage::Tier1Handshake(data::make_handshake(&mut rng, &chain)),
+        PeerMessage::Tier1Handshake(handshake),
         PeerMessage::SyncAccountsData(SyncAccountsData {
             accounts_data: (0..4)
                 .map(|_| Arc::new(data::make_signed_account_data(&mut rng, &clock.clock())))
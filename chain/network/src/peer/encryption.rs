@@ -0,0 +1,210 @@
+//! Encrypted transport negotiated during the handshake.
+//!
+//! Each side of a connection generates an ephemeral X25519 key and sends its
+//! public part, signed with the node key, in its `Handshake` (see
+//! `TransportKey`).  Once a peer has both ephemeral keys, it derives a pair of
+//! ChaCha20-Poly1305 keys (one per direction) from the X25519 shared secret
+//! with HKDF-SHA256.  Frames are then encrypted with a per-direction frame
+//! counter as the nonce and the frame length as associated data, so frames
+//! cannot be modified, reordered or replayed undetected.
+//!
+//! Peers which don't send a `TransportKey` (older nodes, or nodes forced to
+//! use borsh encoding) keep talking in plaintext, unless the node requires
+//! encryption with `require_encrypted_transport`, in which case it rejects
+//! them.
+use crate::network_protocol::TransportKey;
+use crate::tcp;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use near_crypto::SecretKey;
+use near_primitives::network::PeerId;
+use near_primitives::version::{PROTOCOL_VERSION, ProtocolFeature, ProtocolVersion};
+use rand::RngCore as _;
+
+/// Size of the authentication tag appended to every encrypted frame.
+pub(crate) const TAG_SIZE: usize = 16;
+
+/// Whether encryption applies to connections of given TIER.  TIER3
+/// connections, which only carry state parts requested by the node itself,
+/// stay in plaintext.
+pub(crate) fn applies_to(tier: tcp::Tier) -> bool {
+    tier != tcp::Tier::T3
+}
+
+/// Whether a connection of given TIER with a peer of given protocol version
+/// may be encrypted.  Peers negotiate an encrypted transport by default once
+/// both support [`ProtocolFeature::EncryptedTransport`], which is not enabled
+/// in stable releases yet, so these only encrypt connections when configured
+/// to require it.  A node requiring encryption negotiates it regardless of
+/// the protocol versions.
+pub(crate) fn is_supported(
+    tier: tcp::Tier,
+    protocol_version: ProtocolVersion,
+    required: bool,
+) -> bool {
+    applies_to(tier)
+        && (required
+            || ProtocolFeature::EncryptedTransport.enabled(PROTOCOL_VERSION.min(protocol_version)))
+}
+
+/// Ephemeral X25519 key generated for a single connection.
+pub(crate) struct EphemeralKey {
+    secret: [u8; 32],
+    public: [u8; 32],
+}
+
+impl EphemeralKey {
+    pub fn new() -> Self {
+        let mut secret = [0; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+        let public = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+        Self { secret, public }
+    }
+
+    /// Signs the public part of the key for a connection from `sender` to
+    /// `target`.
+    pub fn sign(&self, sender: &PeerId, target: &PeerId, node_key: &SecretKey) -> TransportKey {
+        TransportKey::new(sender, target, self.public, node_key)
+    }
+
+    /// Derives the ciphers for both directions of the connection from the
+    /// ephemeral key of the peer.  `initiator` tells whether this node opened
+    /// the connection.  Returns `None` if the key of the peer is a low order
+    /// point, which doesn't give a secret shared key.
+    pub fn derive_ciphers(
+        self,
+        peer_key: &[u8; 32],
+        initiator: bool,
+    ) -> Option<(FrameCipher, FrameCipher)> {
+        let shared = MontgomeryPoint(*peer_key).mul_clamped(self.secret).to_bytes();
+        if shared == [0; 32] {
+            return None;
+        }
+        let (initiator_key, responder_key) =
+            if initiator { (&self.public, peer_key) } else { (peer_key, &self.public) };
+        let salt = [&initiator_key[..], &responder_key[..]].concat();
+        let hkdf = hkdf::Hkdf::<sha2::Sha256>::new(Some(&salt), &shared);
+        let expand = |info: &[u8]| {
+            let mut key = Key::default();
+            hkdf.expand(info, &mut key).expect("32 bytes is a valid HKDF output length");
+            FrameCipher::new(&key)
+        };
+        let initiator_cipher = expand(b"near-network initiator");
+        let responder_cipher = expand(b"near-network responder");
+        Some(if initiator {
+            (initiator_cipher, responder_cipher)
+        } else {
+            (responder_cipher, initiator_cipher)
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("failed to decrypt frame")]
+pub(crate) struct DecryptError;
+
+/// Cipher encrypting or decrypting the frames sent in one direction of
+/// a connection.
+pub(crate) struct FrameCipher {
+    cipher: ChaCha20Poly1305,
+    /// Number of frames processed so far, used as the nonce.
+    counter: u64,
+}
+
+impl FrameCipher {
+    fn new(key: &Key) -> Self {
+        Self { cipher: ChaCha20Poly1305::new(key), counter: 0 }
+    }
+
+    fn nonce(&self) -> Nonce {
+        let mut nonce = Nonce::default();
+        nonce[4..].copy_from_slice(&self.counter.to_le_bytes());
+        nonce
+    }
+
+    /// Encrypts the frame.  The result is `TAG_SIZE` bytes longer than `msg`.
+    pub fn encrypt(&mut self, msg: &[u8]) -> Vec<u8> {
+        let aad = ((msg.len() + TAG_SIZE) as u32).to_le_bytes();
+        let frame = self
+            .cipher
+            .encrypt(&self.nonce(), Payload { msg, aad: &aad })
+            .expect("encryption fails only for messages exceeding the cipher limits");
+        self.counter += 1;
+        frame
+    }
+
+    pub fn decrypt(&mut self, frame: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        let aad = (frame.len() as u32).to_le_bytes();
+        let msg = self
+            .cipher
+            .decrypt(&self.nonce(), Payload { msg: frame, aad: &aad })
+            .map_err(|_| DecryptError)?;
+        self.counter += 1;
+        Ok(msg)
+    }
+}
+
+/// Checks the transport key of the peer and derives the ciphers for both
+/// directions of the connection.  Returns `None` if the key is invalid.
+pub(crate) fn negotiate(
+    ephemeral_key: EphemeralKey,
+    peer_transport_key: &TransportKey,
+    peer_id: &PeerId,
+    my_peer_id: &PeerId,
+    initiator: bool,
+) -> Option<(FrameCipher, FrameCipher)> {
+    if !peer_transport_key.verify(peer_id, my_peer_id) {
+        return None;
+    }
+    ephemeral_key.derive_ciphers(&peer_transport_key.ephemeral_key, initiator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network_protocol::testonly as data;
+    use crate::testonly::make_rng;
+
+    #[test]
+    fn test_encrypted_transport() {
+        let mut rng = make_rng(1234);
+        let a = data::make_secret_key(&mut rng);
+        let b = data::make_secret_key(&mut rng);
+        let a_id = PeerId::new(a.public_key());
+        let b_id = PeerId::new(b.public_key());
+
+        let a_ephemeral = EphemeralKey::new();
+        let b_ephemeral = EphemeralKey::new();
+        let a_transport_key = a_ephemeral.sign(&a_id, &b_id, &a);
+        let b_transport_key = b_ephemeral.sign(&b_id, &a_id, &b);
+
+        // The key has to be signed by the peer for a connection with us.
+        assert!(!a_transport_key.verify(&b_id, &a_id));
+        assert!(negotiate(EphemeralKey::new(), &a_transport_key, &a_id, &a_id, false).is_none());
+
+        let (mut a_send, mut a_recv) =
+            negotiate(a_ephemeral, &b_transport_key, &b_id, &a_id, true).unwrap();
+        let (mut b_send, mut b_recv) =
+            negotiate(b_ephemeral, &a_transport_key, &a_id, &b_id, false).unwrap();
+
+        for msg in [&b"first"[..], b"", b"third"] {
+            let frame = a_send.encrypt(msg);
+            assert_eq!(frame.len(), msg.len() + TAG_SIZE);
+            assert_eq!(b_recv.decrypt(&frame).unwrap(), msg);
+        }
+        let frame = b_send.encrypt(b"reply");
+        assert_ne!(&frame[..5], b"reply");
+        assert_eq!(a_recv.decrypt(&frame).unwrap(), b"reply");
+
+        // Modified, replayed and truncated frames are rejected.
+        let mut frame = a_send.encrypt(b"message");
+        frame[0] ^= 1;
+        b_recv.decrypt(&frame).unwrap_err();
+        let frame = b_send.encrypt(b"message");
+        a_recv.decrypt(&frame).unwrap();
+        a_recv.decrypt(&frame).unwrap_err();
+        let frame = b_send.encrypt(b"message");
+        a_recv.decrypt(&frame[..frame.len() - 1]).unwrap_err();
+    }
+}
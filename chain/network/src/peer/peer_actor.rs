@@ -13,8 +13,9 @@ use crate::network_protocol::{
     Edge, EdgeState, Encoding, OwnedAccount, ParsePeerMessageError, PartialEdgeInfo,
     PeerChainInfoV2, PeerIdOrHash, PeerInfo, PeersRequest, PeersResponse, RawRoutedMessage,
     RoutedMessageBody, RoutingTableUpdate, SnapshotHostInfoVerificationError, SyncAccountsData,
-    SyncSnapshotHosts,
+    SyncSnapshotHosts, TransportKey,
 };
+use crate::peer::encryption::{self, EphemeralKey, FrameCipher};
 use crate::peer::stream;
 use crate::peer::tracker::Tracker;
 use crate::peer_manager::connection;
@@ -95,6 +96,7 @@ pub struct HandshakeCompletedEvent {
     pub(crate) stream_id: tcp::StreamId,
     pub(crate) edge: Edge,
     pub(crate) tier: tcp::Tier,
+    pub(crate) encrypted: bool,
 }
 
 #[derive(thiserror::Error, Clone, PartialEq, Eq, Debug)]
@@ -124,6 +126,8 @@ pub(crate) enum ClosingReason {
     TooLargeClockSkew,
     #[error("owned_account.peer_id doesn't match handshake.sender_peer_id")]
     OwnedAccountMismatch,
+    #[error("peer didn't negotiate the required encrypted transport")]
+    EncryptionRequired,
     #[error("PeerActor stopped NOT via PeerActor::stop()")]
     Unknown,
 }
@@ -145,6 +149,7 @@ impl ClosingReason {
             ClosingReason::DisconnectMessage => false, // graceful disconnect
             ClosingReason::TooLargeClockSkew => true, // reconnect will fail for the same reason
             ClosingReason::OwnedAccountMismatch => true, // misbehaving peer
+            ClosingReason::EncryptionRequired => true, // reconnect will fail for the same reason
             ClosingReason::Unknown => false,        // only happens in tests
         }
     }
@@ -179,6 +184,12 @@ pub(crate) struct PeerActor {
     /// Whether the PeerActor should skip protobuf support detection and use
     /// a given encoding right away.
     force_encoding: Option<Encoding>,
+    /// Ephemeral key offered in the handshake of an outbound connection.
+    ephemeral_key: Option<EphemeralKey>,
+    /// Transport key to send in the handshake reply of an inbound connection,
+    /// together with the cipher for the frames sent after the reply.
+    /// Present when the peer offered an encrypted transport.
+    pending_encryption: Option<(TransportKey, FrameCipher)>,
 
     /// Peer status.
     peer_status: PeerStatus,
@@ -360,6 +371,8 @@ impl PeerActor {
                     ),
                     protocol_buffers_supported: false,
                     force_encoding,
+                    ephemeral_key: None,
+                    pending_encryption: None,
                     peer_info: match &stream_type {
                         tcp::StreamType::Inbound => None,
                         tcp::StreamType::Outbound { peer_id, .. } => Some(PeerInfo {
@@ -465,17 +478,36 @@ impl PeerActor {
             .inc_by(bytes_len as u64);
     }
 
-    fn send_handshake(&self, spec: HandshakeSpec) {
+    fn send_handshake(&mut self, spec: HandshakeSpec) {
         let (height, tracked_shards) =
             if let Some(chain_info) = self.network_state.chain_info.load().as_ref() {
                 (chain_info.block.header().height(), chain_info.tracked_shards.clone())
             } else {
                 (0, vec![])
             };
+        let node_id = self.network_state.config.node_id();
+        let (transport_key, send_cipher) = match self.peer_type {
+            // Offer an encrypted transport with a fresh key on every attempt.
+            // Borsh encoding doesn't support the transport key.
+            PeerType::Outbound => {
+                self.ephemeral_key = (self.encoding() != Some(Encoding::Borsh)
+                    && encryption::is_supported(
+                        spec.tier,
+                        spec.protocol_version,
+                        self.network_state.config.require_encrypted_transport,
+                    ))
+                .then(EphemeralKey::new);
+                let transport_key = self.ephemeral_key.as_ref().map(|key| {
+                    key.sign(&node_id, &spec.peer_id, &self.network_state.config.node_key)
+                });
+                (transport_key, None)
+            }
+            PeerType::Inbound => self.pending_encryption.take().unzip(),
+        };
         let handshake = Handshake {
             protocol_version: spec.protocol_version,
             oldest_supported_version: PEER_MIN_ALLOWED_PROTOCOL_VERSION,
-            sender_peer_id: self.network_state.config.node_id(),
+            sender_peer_id: node_id,
             target_peer_id: spec.peer_id,
             sender_listen_port: self.network_state.config.node_addr.as_ref().map(|a| a.port()),
             sender_chain_info: PeerChainInfoV2 {
@@ -486,14 +518,19 @@ impl PeerActor {
                 archival: self.network_state.config.archive,
             },
             partial_edge_info: spec.partial_edge_info,
-            owned_account: self.network_state.config.validator.signer.get().map(|signer| {
+            owned_account: self.network_state.config.validator.signer.get().and_then(|signer| {
                 OwnedAccount {
                     account_key: signer.public_key(),
                     peer_id: self.network_state.config.node_id(),
                     timestamp: self.clock.now_utc(),
                 }
                 .sign(&signer)
+                .inspect_err(|err| {
+                    tracing::error!(target: "network", ?err, "Failed to sign owned account");
+                })
+                .ok()
             }),
+            transport_key,
         };
         let msg = match spec.tier {
             tcp::Tier::T1 => PeerMessage::Tier1Handshake(handshake),
@@ -501,6 +538,10 @@ impl PeerActor {
             tcp::Tier::T3 => PeerMessage::Tier3Handshake(handshake),
         };
         self.send_message_or_log(&msg);
+        // The reply to the handshake is the last plaintext frame.
+        if let Some(cipher) = send_cipher {
+            self.framed.start_encrypting(cipher);
+        }
     }
 
     fn stop(&mut self, ctx: &mut actix::Context<PeerActor>, reason: ClosingReason) {
@@ -634,6 +675,76 @@ impl PeerActor {
             }
         }
 
+        // Negotiate the encrypted transport if both sides offered it.
+        let require_encryption = self.network_state.config.require_encrypted_transport;
+        let mut encrypted = false;
+        if let Some(peer_key) = &handshake.transport_key {
+            if encryption::is_supported(tier, handshake.protocol_version, require_encryption) {
+                let peer_id = &handshake.sender_peer_id;
+                let my_peer_id = &self.my_node_info.id;
+                // The inbound side generates its key only now, and sends it
+                // in the handshake reply.
+                let negotiation = match cs {
+                    ConnectingStatus::Outbound { .. } => {
+                        self.ephemeral_key.take().map(|ephemeral_key| {
+                            let ciphers = encryption::negotiate(
+                                ephemeral_key,
+                                peer_key,
+                                peer_id,
+                                my_peer_id,
+                                true,
+                            );
+                            (ciphers, None)
+                        })
+                    }
+                    ConnectingStatus::Inbound { .. } => {
+                        let ephemeral_key = EphemeralKey::new();
+                        let transport_key = ephemeral_key.sign(
+                            my_peer_id,
+                            peer_id,
+                            &self.network_state.config.node_key,
+                        );
+                        let ciphers = encryption::negotiate(
+                            ephemeral_key,
+                            peer_key,
+                            peer_id,
+                            my_peer_id,
+                            false,
+                        );
+                        Some((ciphers, Some(transport_key)))
+                    }
+                };
+                if let Some((ciphers, transport_key)) = negotiation {
+                    let Some((send_cipher, recv_cipher)) = ciphers else {
+                        tracing::debug!(target: "network", ?peer_id, "Invalid transport key, disconnecting");
+                        self.stop(ctx, ClosingReason::Ban(ReasonForBan::InvalidSignature));
+                        return;
+                    };
+                    // An outbound peer which hasn't detected our encoding yet
+                    // follows its handshake with a plaintext borsh copy of it.
+                    // Borsh encoding only supports TIER2 handshakes.
+                    let plaintext_frame =
+                        (transport_key.is_some() && tier == tcp::Tier::T2).then(|| {
+                            PeerMessage::Tier2Handshake(handshake.clone())
+                                .serialize(Encoding::Borsh)
+                        });
+                    self.framed.start_decrypting(recv_cipher, plaintext_frame);
+                    match transport_key {
+                        Some(transport_key) => {
+                            self.pending_encryption = Some((transport_key, send_cipher))
+                        }
+                        None => self.framed.start_encrypting(send_cipher),
+                    }
+                    encrypted = true;
+                }
+            }
+        }
+        if require_encryption && encryption::applies_to(tier) && !encrypted {
+            tracing::debug!(target: "network", peer_id = ?handshake.sender_peer_id, "Peer didn't negotiate an encrypted transport, disconnecting");
+            self.stop(ctx, ClosingReason::EncryptionRequired);
+            return;
+        }
+
         // Merge partial edges.
         let nonce = handshake.partial_edge_info.nonce;
         let partial_edge_info = match cs {
@@ -671,6 +782,7 @@ impl PeerActor {
             addr: ctx.address(),
             peer_info: peer_info.clone(),
             owned_account: handshake.owned_account.clone(),
+            encrypted,
             genesis_id: handshake.sender_chain_info.genesis_id.clone(),
             tracked_shards: handshake.sender_chain_info.tracked_shards.clone(),
             archival: handshake.sender_chain_info.archival,
@@ -844,6 +956,7 @@ impl PeerActor {
                             stream_id: act.stream_id,
                             edge: edge_clone,
                             tier: conn.tier,
+                            encrypted: conn.encrypted,
                         }));
                     },
                     Err(err) => {
@@ -1573,7 +1686,8 @@ impl actix::Actor for PeerActor {
         if let PeerStatus::Connecting(_, ConnectingStatus::Outbound { handshake_spec, .. }) =
             &self.peer_status
         {
-            self.send_handshake(handshake_spec.clone());
+            let spec = handshake_spec.clone();
+            self.send_handshake(spec);
         }
         #[cfg(test)]
         self.network_state
@@ -1,9 +1,11 @@
+use crate::peer::encryption::{self, FrameCipher};
 use crate::peer_manager::connection;
 use crate::stats::metrics;
 use crate::tcp;
 use actix::AsyncContext as _;
 use actix::fut::future::wrap_future;
 use bytesize::{GIB, MIB};
+use parking_lot::Mutex;
 use std::io;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -34,12 +36,29 @@ pub(crate) enum RecvError {
     IO(#[source] io::Error),
     #[error("message too large: got {got_bytes}B, want <={want_max_bytes}B")]
     MessageTooLarge { got_bytes: usize, want_max_bytes: usize },
+    #[error("{0}")]
+    Decrypt(#[source] encryption::DecryptError),
 }
 
 #[derive(actix::Message, PartialEq, Eq, Clone, Debug)]
 #[rtype(result = "()")]
 pub(crate) struct Frame(pub Vec<u8>);
 
+/// Item of the send queue.
+enum SendQueueItem {
+    Frame(Frame),
+    /// All the frames queued after this item are encrypted with the cipher.
+    StartEncryption(FrameCipher),
+}
+
+/// Cipher used by the recv loop.
+struct RecvCipher {
+    cipher: FrameCipher,
+    /// Plaintext frame which the peer may send before the first encrypted
+    /// one.  It is dropped if the next frame is exactly this one.
+    plaintext_frame: Option<Vec<u8>>,
+}
+
 /// Stream critical error.
 /// Actor is responsible for calling ctx.stop() after receiving stream::Error.
 /// Actor might receive more than 1 stream::Error, but should call ctx.stop() just after the
@@ -57,7 +76,10 @@ pub(crate) enum Error {
 }
 
 pub(crate) struct FramedStream<Actor: actix::Actor> {
-    queue_send: tokio::sync::mpsc::UnboundedSender<Frame>,
+    queue_send: tokio::sync::mpsc::UnboundedSender<SendQueueItem>,
+    /// Cipher which the recv loop picks up to decrypt all the frames it reads
+    /// from then on.
+    recv_cipher: Arc<Mutex<Option<RecvCipher>>>,
     stats: Arc<connection::Stats>,
     send_buf_size_metric: Arc<metrics::IntGaugeGuard>,
     addr: actix::Addr<Actor>,
@@ -76,6 +98,7 @@ where
     ) -> Self {
         let (tcp_recv, tcp_send) = tokio::io::split(stream.stream);
         let (queue_send, queue_recv) = tokio::sync::mpsc::unbounded_channel();
+        let recv_cipher = Arc::new(Mutex::new(None));
         let send_buf_size_metric = Arc::new(metrics::MetricGuard::new(
             &*metrics::PEER_DATA_WRITE_BUFFER_SIZE,
             vec![stream.peer_addr.to_string()],
@@ -93,15 +116,43 @@ where
         ctx.spawn(wrap_future({
             let addr = ctx.address();
             let stats = stats.clone();
+            let recv_cipher = recv_cipher.clone();
             async move {
-                if let Err(err) =
-                    Self::run_recv_loop(stream.peer_addr, tcp_recv, addr.clone(), stats).await
+                if let Err(err) = Self::run_recv_loop(
+                    stream.peer_addr,
+                    tcp_recv,
+                    addr.clone(),
+                    stats,
+                    recv_cipher,
+                )
+                .await
                 {
                     addr.do_send(Error::Recv(err));
                 }
             }
         }));
-        Self { queue_send, stats, send_buf_size_metric, addr: ctx.address() }
+        Self { queue_send, recv_cipher, stats, send_buf_size_metric, addr: ctx.address() }
+    }
+
+    /// Encrypts all the frames sent after this call.
+    pub fn start_encrypting(&self, cipher: FrameCipher) {
+        let _ = self.queue_send.send(SendQueueItem::StartEncryption(cipher));
+    }
+
+    /// Decrypts all the frames received after the frame which is currently
+    /// being handled by the Actor.
+    ///
+    /// The recv loop waits for the Actor to handle a frame before reading the
+    /// next one, so calling this while handling the last plaintext frame
+    /// guarantees that the following frames are decrypted.
+    ///
+    /// If `plaintext_frame` is set and the first of these frames is exactly
+    /// that frame, it is dropped instead of being decrypted.  This
+    /// accommodates the plaintext copy of a handshake which the peer sent
+    /// before learning that encryption has been negotiated.  Any other frame
+    /// which fails to decrypt closes the connection.
+    pub fn start_decrypting(&self, cipher: FrameCipher, plaintext_frame: Option<Vec<u8>>) {
+        *self.recv_cipher.lock() = Some(RecvCipher { cipher, plaintext_frame });
     }
 
     /// Pushes `msg` to the send queue.
@@ -125,7 +176,7 @@ where
                 want_max_bytes: MAX_WRITE_BUFFER_CAPACITY_BYTES,
             }));
         }
-        let _ = self.queue_send.send(frame);
+        let _ = self.queue_send.send(SendQueueItem::Frame(frame));
     }
 
     /// Event loop receiving and processing messages.
@@ -141,6 +192,7 @@ where
         read: ReadHalf,
         addr: actix::Addr<Actor>,
         stats: Arc<connection::Stats>,
+        recv_cipher: Arc<Mutex<Option<RecvCipher>>>,
     ) -> Result<(), RecvError> {
         const READ_BUFFER_CAPACITY: usize = 8 * 1024;
         let mut read = tokio::io::BufReader::with_capacity(READ_BUFFER_CAPACITY, read);
@@ -151,13 +203,18 @@ where
             &metrics::PEER_DATA_READ_BUFFER_SIZE,
             vec![peer_addr.to_string()],
         );
+        let mut cipher: Option<RecvCipher> = None;
         loop {
             let n = read.read_u32_le().await.map_err(RecvError::IO)? as usize;
-            if n > NETWORK_MESSAGE_MAX_SIZE_BYTES {
-                return Err(RecvError::MessageTooLarge {
-                    got_bytes: n,
-                    want_max_bytes: NETWORK_MESSAGE_MAX_SIZE_BYTES,
-                });
+            if cipher.is_none() {
+                cipher = recv_cipher.lock().take();
+            }
+            let max_size = match &cipher {
+                Some(_) => NETWORK_MESSAGE_MAX_SIZE_BYTES + encryption::TAG_SIZE,
+                None => NETWORK_MESSAGE_MAX_SIZE_BYTES,
+            };
+            if n > max_size {
+                return Err(RecvError::MessageTooLarge { got_bytes: n, want_max_bytes: max_size });
             }
             msg_size_metric.observe(n as f64);
             buf_size_metric.set(n as i64);
@@ -168,6 +225,12 @@ where
             buf_size_metric.set(0);
             stats.received_messages.fetch_add(1, Ordering::Relaxed);
             stats.received_bytes.fetch_add(n as u64, Ordering::Relaxed);
+            if let Some(RecvCipher { cipher, plaintext_frame }) = &mut cipher {
+                if plaintext_frame.take().is_some_and(|frame| frame == buf) {
+                    continue;
+                }
+                buf = cipher.decrypt(&buf).map_err(RecvError::Decrypt)?;
+            }
             if let Err(_) = addr.send(Frame(buf)).await {
                 // We got mailbox error, which means that Actor has stopped,
                 // so we should just close the stream.
@@ -177,28 +240,38 @@ where
     }
     async fn run_send_loop(
         tcp_send: WriteHalf,
-        mut queue_recv: tokio::sync::mpsc::UnboundedReceiver<Frame>,
+        mut queue_recv: tokio::sync::mpsc::UnboundedReceiver<SendQueueItem>,
         stats: Arc<connection::Stats>,
         buf_size_metric: Arc<metrics::IntGaugeGuard>,
     ) -> io::Result<()> {
         const WRITE_BUFFER_CAPACITY: usize = 8 * 1024;
         let mut writer = tokio::io::BufWriter::with_capacity(WRITE_BUFFER_CAPACITY, tcp_send);
-        while let Some(Frame(mut msg)) = queue_recv.recv().await {
+        let mut cipher: Option<FrameCipher> = None;
+        while let Some(mut item) = queue_recv.recv().await {
             // Try writing a batch of messages and flush once at the end.
             loop {
-                // TODO(gprusak): sending a too large message should probably be treated as a bug,
-                // since dropping messages may lead to hard-to-debug high-level issues.
-                if msg.len() > NETWORK_MESSAGE_MAX_SIZE_BYTES {
-                    metrics::MessageDropped::InputTooLong.inc_unknown_msg();
-                } else {
-                    writer.write_u32_le(msg.len() as u32).await?;
-                    writer.write_all(&msg[..]).await?;
+                match item {
+                    SendQueueItem::StartEncryption(it) => cipher = Some(it),
+                    SendQueueItem::Frame(Frame(msg)) => {
+                        // TODO(gprusak): sending a too large message should probably be treated as a bug,
+                        // since dropping messages may lead to hard-to-debug high-level issues.
+                        if msg.len() > NETWORK_MESSAGE_MAX_SIZE_BYTES {
+                            metrics::MessageDropped::InputTooLong.inc_unknown_msg();
+                        } else if let Some(cipher) = &mut cipher {
+                            let frame = cipher.encrypt(&msg);
+                            writer.write_u32_le(frame.len() as u32).await?;
+                            writer.write_all(&frame[..]).await?;
+                        } else {
+                            writer.write_u32_le(msg.len() as u32).await?;
+                            writer.write_all(&msg[..]).await?;
+                        }
+                        stats.messages_to_send.fetch_sub(1, Ordering::Release);
+                        stats.bytes_to_send.fetch_sub(msg.len() as u64, Ordering::Release);
+                        buf_size_metric.sub(msg.len() as i64);
+                    }
                 }
-                stats.messages_to_send.fetch_sub(1, Ordering::Release);
-                stats.bytes_to_send.fetch_sub(msg.len() as u64, Ordering::Release);
-                buf_size_metric.sub(msg.len() as i64);
-                msg = match queue_recv.try_recv() {
-                    Ok(Frame(it)) => it,
+                item = match queue_recv.try_recv() {
+                    Ok(it) => it,
                     Err(_) => break,
                 };
             }
@@ -3,6 +3,7 @@ use crate::network_protocol::{
     Encoding, Handshake, HandshakeFailureReason, PartialEdgeInfo, PeerMessage, PeersRequest,
     PeersResponse, RoutedMessageBody,
 };
+use crate::peer::peer_actor::ClosingReason;
 use crate::peer::testonly::{Event, PeerConfig, PeerHandle};
 use crate::peer_manager::peer_manager_actor::Event as PME;
 use crate::tcp;
@@ -211,6 +212,7 @@ async fn test_handshake(outbound_encoding: Option<Encoding>, inbound_encoding: O
         partial_edge_info: outbound_cfg
             .partial_edge_info(&inbound.cfg.id(), Edge::create_fresh_nonce(&clock.clock())),
         owned_account: None,
+        transport_key: None,
     };
     // We will also introduce chain_id mismatch, but ProtocolVersionMismatch is expected to take priority.
     handshake.sender_chain_info.genesis_id.chain_id = "unknown_chain".to_string();
@@ -267,3 +269,109 @@ async fn handshake() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// Waits for the handshake of the peer to complete and returns whether the
+/// connection is encrypted.
+async fn handshake_encrypted(peer: &mut PeerHandle) -> bool {
+    peer.events
+        .recv_until(|ev| match ev {
+            Event::Network(PME::HandshakeCompleted(ev)) => Some(ev.encrypted),
+            Event::Network(PME::ConnectionClosed(ev)) => panic!("handshake failed: {}", ev.reason),
+            _ => None,
+        })
+        .await
+}
+
+async fn test_encrypted_transport(outbound_encoding: Option<Encoding>) {
+    let mut rng = make_rng(89028037453);
+    let mut clock = time::FakeClock::default();
+
+    let chain = Arc::new(data::Chain::make(&mut clock, &mut rng, 12));
+    let mut inbound_cfg = PeerConfig {
+        chain: chain.clone(),
+        network: chain.make_config(&mut rng),
+        force_encoding: None,
+    };
+    inbound_cfg.network.require_encrypted_transport = true;
+    let mut outbound_cfg = PeerConfig {
+        chain: chain.clone(),
+        network: chain.make_config(&mut rng),
+        force_encoding: outbound_encoding,
+    };
+    outbound_cfg.network.require_encrypted_transport = true;
+    let (outbound_stream, inbound_stream) =
+        tcp::Stream::loopback(inbound_cfg.id(), tcp::Tier::T2).await;
+    let mut inbound = PeerHandle::start_endpoint(clock.clock(), inbound_cfg, inbound_stream).await;
+    let mut outbound =
+        PeerHandle::start_endpoint(clock.clock(), outbound_cfg, outbound_stream).await;
+
+    assert!(handshake_encrypted(&mut outbound).await);
+    assert!(handshake_encrypted(&mut inbound).await);
+
+    // Messages in both directions are decrypted by the other side.
+    let message_processed = |want| {
+        move |ev| match ev {
+            Event::Network(PME::MessageProcessed(_, got)) if got == want => Some(()),
+            _ => None,
+        }
+    };
+    let mut events = inbound.events.from_now();
+    let want = PeerMessage::PeersRequest(PeersRequest { max_peers: None, max_direct_peers: None });
+    outbound.send(want.clone()).await;
+    events.recv_until(message_processed(want)).await;
+
+    let mut events = outbound.events.from_now();
+    let want = PeerMessage::PeersResponse(PeersResponse {
+        peers: (0..5).map(|_| data::make_peer_info(&mut rng)).collect(),
+        direct_peers: vec![],
+    });
+    inbound.send(want.clone()).await;
+    events.recv_until(message_processed(want)).await;
+}
+
+#[tokio::test]
+// Verifies that peers requiring encryption negotiate an encrypted transport,
+// including when the outbound peer follows its handshake with a plaintext
+// borsh copy of it.
+async fn encrypted_transport() {
+    init_test_logger();
+    test_encrypted_transport(None).await;
+    test_encrypted_transport(Some(Encoding::Proto)).await;
+}
+
+#[tokio::test]
+// Verifies that a peer requiring encryption rejects a peer which doesn't
+// offer it.
+async fn encrypted_transport_required() {
+    init_test_logger();
+    let mut rng = make_rng(89028037453);
+    let mut clock = time::FakeClock::default();
+
+    let chain = Arc::new(data::Chain::make(&mut clock, &mut rng, 12));
+    let mut inbound_cfg = PeerConfig {
+        chain: chain.clone(),
+        network: chain.make_config(&mut rng),
+        force_encoding: None,
+    };
+    inbound_cfg.network.require_encrypted_transport = true;
+    // Borsh encoding doesn't support the transport key.
+    let outbound_cfg = PeerConfig {
+        chain: chain.clone(),
+        network: chain.make_config(&mut rng),
+        force_encoding: Some(Encoding::Borsh),
+    };
+    let (outbound_stream, inbound_stream) =
+        tcp::Stream::loopback(inbound_cfg.id(), tcp::Tier::T2).await;
+    let mut inbound = PeerHandle::start_endpoint(clock.clock(), inbound_cfg, inbound_stream).await;
+    let _outbound = PeerHandle::start_endpoint(clock.clock(), outbound_cfg, outbound_stream).await;
+
+    let reason = inbound
+        .events
+        .recv_until(|ev| match ev {
+            Event::Network(PME::ConnectionClosed(ev)) => Some(ev.reason),
+            Event::Network(PME::HandshakeCompleted(_)) => panic!("handshake completed"),
+            _ => None,
+        })
+        .await;
+    assert_eq!(reason, ClosingReason::EncryptionRequired);
+}
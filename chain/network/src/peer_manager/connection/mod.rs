@@ -134,6 +134,8 @@ pub(crate) struct Connection {
     pub peer_info: PeerInfo,
     /// AccountKey ownership proof.
     pub owned_account: Option<SignedOwnedAccount>,
+    /// Whether the frames sent over the connection are encrypted.
+    pub encrypted: bool,
     /// Chain Id and hash of genesis block.
     pub genesis_id: GenesisId,
     /// Shards that the peer is tracking.
@@ -107,6 +107,7 @@ async fn loop_connection() {
                 &pm.cfg.node_key,
             ),
             owned_account: None,
+            transport_key: None,
         }))
         .await;
     let reason = events
@@ -175,8 +176,10 @@ async fn owned_account_mismatch() {
                     peer_id: data::make_peer_id(rng),
                     timestamp: clock.now_utc(),
                 }
-                .sign(&signer),
+                .sign(&signer)
+                .unwrap(),
             ),
+            transport_key: None,
         }))
         .await;
     let reason = events
@@ -297,8 +300,10 @@ async fn invalid_edge() {
                         peer_id: cfg.node_id(),
                         timestamp: clock.now_utc(),
                     }
-                    .sign(&signer),
+                    .sign(&signer)
+                    .unwrap(),
                 ),
+                transport_key: None,
             };
             let handshake = match tier {
                 tcp::Tier::T1 => PeerMessage::Tier1Handshake(handshake),
@@ -11,7 +11,7 @@ use crate::testonly::{Rng, make_rng};
 use near_async::time;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::block_header::{Approval, ApprovalInner};
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{ValidatorMessage, ValidatorSigner};
 use near_store::db::TestDB;
 use rand::Rng as _;
 use std::collections::HashSet;
@@ -22,7 +22,9 @@ fn make_block_approval(rng: &mut Rng, signer: &ValidatorSigner) -> Approval {
     let inner = ApprovalInner::Endorsement(data::make_hash(rng));
     let target_height = rng.gen_range(0..100000);
     Approval {
-        signature: signer.sign_bytes(&Approval::get_data_for_sig(&inner, target_height)),
+        signature: signer
+            .sign_message(&ValidatorMessage::Approval { inner: inner.clone(), target_height })
+            .unwrap(),
         account_id: signer.validator_id().clone(),
         target_height,
         inner,
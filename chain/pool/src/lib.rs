@@ -1,5 +1,9 @@
-use crate::types::{PoolKey, TransactionGroup, TransactionGroupIterator};
+use crate::types::{
+    DropReason, DroppedTransaction, PoolKey, TransactionGroup, TransactionGroupIterator,
+};
+use near_chain_configs::{TransactionPoolConfig, TransactionPriority};
 use near_crypto::PublicKey;
+use near_o11y::metrics::IntCounter;
 use near_o11y::metrics::prometheus::core::{AtomicI64, GenericGauge};
 use near_primitives::epoch_info::RngSeed;
 use near_primitives::hash::{CryptoHash, hash};
@@ -12,14 +16,33 @@ use std::ops::Bound;
 mod metrics;
 pub mod types;
 
+/// Number of recently dropped transactions remembered by each pool.
+const MAX_DROPPED_TRANSACTIONS: usize = 1000;
+
 #[derive(Debug, PartialEq)]
 pub enum InsertTransactionResult {
     /// Transaction was successfully inserted.
     Success,
     /// Transaction is already in the pool.
     Duplicate,
-    /// Not enough space to fit the transaction.
+    /// Not enough space to fit the transaction. With `evict_when_full` set, not even after
+    /// evicting all the transactions with lower or equal priority.
     NoSpaceLeft,
+    /// Transactions of the signer or of the receiver already take up their quota of the pool.
+    AccountQuotaExceeded,
+}
+
+/// Position of a transaction in the eviction order: its priority and its insertion sequence
+/// number. Transactions with the smallest key are evicted first.
+type EvictionKey = (u64, u64);
+
+/// Bookkeeping of a transaction in the pool.
+struct TransactionInfo {
+    key: PoolKey,
+    signer_id: AccountId,
+    receiver_id: AccountId,
+    size: u64,
+    eviction_key: EvictionKey,
 }
 
 /// Transaction pool: keeps track of transactions that were not yet accepted into the block chain.
@@ -28,44 +51,70 @@ pub struct TransactionPool {
     /// NOTE: It's more efficient on average to keep transactions unsorted and with potentially
     /// conflicting nonce than to create a BTreeMap for every transaction.
     transactions: BTreeMap<PoolKey, Vec<ValidatedTransaction>>,
-    /// Set of all hashes to quickly check if the given transaction is in the pool.
-    unique_transactions: HashSet<CryptoHash>,
+    /// All hashes to quickly check if the given transaction is in the pool, mapped to the
+    /// bookkeeping of the transaction.
+    unique_transactions: HashMap<CryptoHash, TransactionInfo>,
+    /// Hashes of all transactions in the order they are evicted in once the pool is full.
+    eviction_order: BTreeMap<EvictionKey, CryptoHash>,
+    /// Sequence number of the next inserted transaction.
+    next_insertion_seq: u64,
+    /// Total size of transactions in the pool by signer account.
+    signer_sizes: HashMap<AccountId, u64>,
+    /// Total size of transactions in the pool by receiver account.
+    receiver_sizes: HashMap<AccountId, u64>,
+    /// Per-account quotas and ordering of the pool.
+    config: TransactionPoolConfig,
+    /// Recently evicted or rejected transactions, oldest first. Only kept for debugging.
+    dropped_transactions: VecDeque<DroppedTransaction>,
     /// A uniquely generated key seed to randomize PoolKey order.
     key_seed: RngSeed,
     /// The key after which the pool iterator starts. Doesn't have to be present in the pool.
     last_used_key: PoolKey,
-    /// If set, new transactions that bring the size of the pool over this limit will be rejected.
+    /// If set, new transactions that bring the size of the pool over this limit will be rejected,
+    /// or will evict transactions with lower or equal priority if `evict_when_full` is set.
     total_transaction_size_limit: Option<u64>,
     /// Total size of transactions in the pool measured in bytes.
     total_transaction_size: u64,
     /// Metrics tracked for transaction pool.
     transaction_pool_count_metric: GenericGauge<AtomicI64>,
     transaction_pool_size_metric: GenericGauge<AtomicI64>,
+    transaction_pool_evicted_metric: IntCounter,
 }
 
 impl TransactionPool {
     pub fn new(
         key_seed: RngSeed,
         total_transaction_size_limit: Option<u64>,
+        config: TransactionPoolConfig,
         metrics_label: &str,
     ) -> Self {
         let transaction_pool_count_metric =
             metrics::TRANSACTION_POOL_COUNT.with_label_values(&[metrics_label]);
         let transaction_pool_size_metric =
             metrics::TRANSACTION_POOL_SIZE.with_label_values(&[metrics_label]);
+        let transaction_pool_evicted_metric =
+            metrics::TRANSACTION_POOL_EVICTED.with_label_values(&[metrics_label]);
         // A `get()` call initializes a metric even if its value is zero.
         transaction_pool_count_metric.get();
         transaction_pool_size_metric.get();
+        transaction_pool_evicted_metric.get();
 
         Self {
             key_seed,
             transactions: BTreeMap::new(),
-            unique_transactions: HashSet::new(),
+            unique_transactions: HashMap::new(),
+            eviction_order: BTreeMap::new(),
+            next_insertion_seq: 0,
+            signer_sizes: HashMap::new(),
+            receiver_sizes: HashMap::new(),
+            config,
+            dropped_transactions: VecDeque::new(),
             last_used_key: CryptoHash::default(),
             total_transaction_size_limit,
             total_transaction_size: 0,
             transaction_pool_count_metric,
             transaction_pool_size_metric,
+            transaction_pool_evicted_metric,
         }
     }
 
@@ -76,42 +125,167 @@ impl TransactionPool {
         hash(&v)
     }
 
+    /// Returns the priority of the transaction according to the configured ordering.
+    fn priority(&self, validated_tx: &ValidatedTransaction) -> u64 {
+        match self.config.priority {
+            TransactionPriority::None => 0,
+            TransactionPriority::PriorityFee => validated_tx.to_tx().priority_fee().unwrap_or(0),
+            TransactionPriority::AccountTier => {
+                self.config.account_tiers.get(validated_tx.signer_id()).copied().unwrap_or(0)
+            }
+        }
+    }
+
+    /// Checks whether adding the transaction keeps its signer and receiver within their quotas.
+    fn fits_account_quotas(&self, validated_tx: &ValidatedTransaction) -> bool {
+        let fits = |sizes: &HashMap<AccountId, u64>, account_id: &AccountId, limit: Option<u64>| {
+            let Some(limit) = limit else {
+                return true;
+            };
+            let size = sizes.get(account_id).copied().unwrap_or(0);
+            size.saturating_add(validated_tx.get_size()) <= limit
+        };
+        fits(&self.signer_sizes, validated_tx.signer_id(), self.config.signer_size_limit)
+            && fits(
+                &self.receiver_sizes,
+                validated_tx.receiver_id(),
+                self.config.receiver_size_limit,
+            )
+    }
+
+    /// Evicts transactions with the lowest priority, oldest first, until a transaction of the
+    /// given size and priority fits into the pool. Transactions with a higher priority are never
+    /// evicted. If that isn't enough to make space or eviction is disabled, nothing is evicted
+    /// and `false` is returned.
+    fn evict_to_fit(&mut self, limit: u64, size: u64, priority: u64) -> bool {
+        // We never expect the total size to go over `u64` during real operation as that would
+        // be more than 10^9 GiB of RAM consumed for transaction pool, so panicking here is intended
+        // to catch a logic error in estimation of transaction size.
+        let new_total_transaction_size = self
+            .total_transaction_size
+            .checked_add(size)
+            .expect("Total transaction size is too large");
+        let Some(mut excess) = new_total_transaction_size.checked_sub(limit) else {
+            return true;
+        };
+        if excess > 0 && !self.config.evict_when_full {
+            return false;
+        }
+        let mut victims = vec![];
+        for (&(victim_priority, _), tx_hash) in &self.eviction_order {
+            if excess == 0 {
+                break;
+            }
+            if victim_priority > priority {
+                break;
+            }
+            excess = excess.saturating_sub(self.unique_transactions[tx_hash].size);
+            victims.push(*tx_hash);
+        }
+        if excess > 0 {
+            return false;
+        }
+        for tx_hash in victims {
+            self.evict_transaction(&tx_hash);
+        }
+        true
+    }
+
+    /// Removes the transaction from the pool to make space for another one.
+    fn evict_transaction(&mut self, tx_hash: &CryptoHash) {
+        let info =
+            self.forget_transaction(tx_hash).expect("evicted transaction must be in the pool");
+        let mut evicted_tx = None;
+        if let Entry::Occupied(mut entry) = self.transactions.entry(info.key) {
+            let group = entry.get_mut();
+            if let Some(index) = group.iter().position(|tx| tx.get_hash() == *tx_hash) {
+                // Transactions in the pool are kept unsorted so the order can change.
+                evicted_tx = Some(group.swap_remove(index));
+            }
+            if entry.get().is_empty() {
+                entry.remove_entry();
+            }
+        }
+        if let Some(evicted_tx) = evicted_tx {
+            self.record_dropped(&evicted_tx, DropReason::Evicted);
+        }
+        // See the comment in `evict_to_fit` for reasoning why panicking here catches a logic
+        // error.
+        self.total_transaction_size = self
+            .total_transaction_size
+            .checked_sub(info.size)
+            .expect("Total transaction size dropped below zero");
+        self.transaction_pool_evicted_metric.inc();
+    }
+
+    fn record_dropped(&mut self, validated_tx: &ValidatedTransaction, reason: DropReason) {
+        if self.dropped_transactions.len() == MAX_DROPPED_TRANSACTIONS {
+            self.dropped_transactions.pop_front();
+        }
+        self.dropped_transactions.push_back(DroppedTransaction::new(validated_tx, reason));
+    }
+
+    /// Drops the bookkeeping of the transaction with the given hash. Doesn't touch the
+    /// transaction groups and the total size, which the callers take care of.
+    fn forget_transaction(&mut self, tx_hash: &CryptoHash) -> Option<TransactionInfo> {
+        let info = self.unique_transactions.remove(tx_hash)?;
+        self.eviction_order.remove(&info.eviction_key);
+        decrease_account_size(&mut self.signer_sizes, &info.signer_id, info.size);
+        decrease_account_size(&mut self.receiver_sizes, &info.receiver_id, info.size);
+        Some(info)
+    }
+
     /// Inserts a signed transaction that passed validation into the pool.
+    ///
+    /// If the pool is full, the transaction is rejected unless `evict_when_full` is set, in which
+    /// case transactions with lower or equal priority are evicted to make space for the new one,
+    /// see `evict_to_fit`.
     pub fn insert_transaction(
         &mut self,
         validated_tx: ValidatedTransaction,
     ) -> InsertTransactionResult {
         let tx_hash = validated_tx.get_hash();
-        if self.unique_transactions.contains(&tx_hash) {
+        if self.unique_transactions.contains_key(&tx_hash) {
             return InsertTransactionResult::Duplicate;
         }
-        // We never expect the total size to go over `u64` during real operation as that would
-        // be more than 10^9 GiB of RAM consumed for transaction pool, so panicking here is intended
-        // to catch a logic error in estimation of transaction size.
-        let new_total_transaction_size = self
-            .total_transaction_size
-            .checked_add(validated_tx.get_size())
-            .expect("Total transaction size is too large");
+        if !self.fits_account_quotas(&validated_tx) {
+            self.record_dropped(&validated_tx, DropReason::AccountQuotaExceeded);
+            return InsertTransactionResult::AccountQuotaExceeded;
+        }
+        let size = validated_tx.get_size();
+        let priority = self.priority(&validated_tx);
         if let Some(limit) = self.total_transaction_size_limit {
-            if new_total_transaction_size > limit {
+            if !self.evict_to_fit(limit, size, priority) {
+                self.record_dropped(&validated_tx, DropReason::NoSpaceLeft);
                 return InsertTransactionResult::NoSpaceLeft;
             }
         }
 
         // At this point transaction is accepted to the pool.
 
-        // This is guaranteed to succeed because of the check above that the
-        // hashset does not contain this hash.  This can be improved once the
-        // entries API is stabilized
-        // (https://github.com/rust-lang/rust/issues/60896).
-        assert_eq!(self.unique_transactions.insert(tx_hash), true);
-        self.total_transaction_size = new_total_transaction_size;
+        self.total_transaction_size = self
+            .total_transaction_size
+            .checked_add(size)
+            .expect("Total transaction size is too large");
         let signer_id = validated_tx.signer_id();
-        let signer_public_key = validated_tx.public_key();
-        self.transactions
-            .entry(self.key(signer_id, signer_public_key))
-            .or_insert_with(Vec::new)
-            .push(validated_tx);
+        let receiver_id = validated_tx.receiver_id();
+        let key = self.key(signer_id, validated_tx.public_key());
+        let eviction_key = (priority, self.next_insertion_seq);
+        self.next_insertion_seq += 1;
+        self.eviction_order.insert(eviction_key, tx_hash);
+        *self.signer_sizes.entry(signer_id.clone()).or_default() += size;
+        *self.receiver_sizes.entry(receiver_id.clone()).or_default() += size;
+        self.unique_transactions.insert(
+            tx_hash,
+            TransactionInfo {
+                key,
+                signer_id: signer_id.clone(),
+                receiver_id: receiver_id.clone(),
+                size,
+                eviction_key,
+            },
+        );
+        self.transactions.entry(key).or_insert_with(Vec::new).push(validated_tx);
 
         self.transaction_pool_count_metric.inc();
         self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
@@ -133,7 +307,7 @@ impl TransactionPool {
         let mut grouped_transactions = HashMap::new();
         for signed_tx in signed_txs {
             // If transaction is not present in the pool, skip it.
-            if !self.unique_transactions.remove(&signed_tx.get_hash()) {
+            if self.forget_transaction(&signed_tx.get_hash()).is_none() {
                 continue;
             }
 
@@ -178,6 +352,40 @@ impl TransactionPool {
     pub fn transaction_size(&self) -> u64 {
         self.total_transaction_size
     }
+
+    /// Returns all transactions in the pool in no particular order.
+    pub fn transactions(&self) -> impl Iterator<Item = &ValidatedTransaction> {
+        self.transactions.values().flatten()
+    }
+
+    /// Returns recently evicted or rejected transactions, oldest first.
+    pub fn dropped_transactions(&self) -> impl Iterator<Item = &DroppedTransaction> {
+        self.dropped_transactions.iter()
+    }
+
+    /// Returns the keys of all the groups in the pool ordered by the highest priority of their
+    /// transactions, highest first. Groups with equal priority keep the randomized key order.
+    fn keys_by_priority(&self) -> VecDeque<PoolKey> {
+        let mut keys: Vec<_> = self
+            .transactions
+            .iter()
+            .map(|(key, txs)| {
+                let priority = txs.iter().map(|tx| self.priority(tx)).max().unwrap_or(0);
+                (priority, *key)
+            })
+            .collect();
+        keys.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+        keys.into_iter().map(|(_, key)| key).collect()
+    }
+}
+
+fn decrease_account_size(sizes: &mut HashMap<AccountId, u64>, account_id: &AccountId, size: u64) {
+    if let Entry::Occupied(mut entry) = sizes.entry(account_id.clone()) {
+        *entry.get_mut() = entry.get().saturating_sub(size);
+        if *entry.get() == 0 {
+            entry.remove_entry();
+        }
+    }
 }
 
 /// PoolIterator is a structure to pull transactions from the pool.
@@ -189,18 +397,51 @@ pub struct PoolIteratorWrapper<'a> {
 
     /// Queue of transaction groups. Each group there is sorted by nonce.
     sorted_groups: VecDeque<TransactionGroup>,
+
+    /// Keys of the groups left in the pool in the order of their priority. Only used if the pool
+    /// is configured with a priority ordering and computed on the first call to `next()`.
+    priority_order: Option<VecDeque<PoolKey>>,
 }
 
 impl<'a> PoolIteratorWrapper<'a> {
     pub fn new(pool: &'a mut TransactionPool) -> Self {
-        Self { pool, sorted_groups: Default::default() }
+        Self { pool, sorted_groups: Default::default(), priority_order: None }
+    }
+
+    /// Returns the key of the next group to take from the pool. The pool must not be empty.
+    fn next_key(&mut self) -> PoolKey {
+        if self.pool.config.priority != TransactionPriority::None {
+            let pool = &self.pool;
+            return self
+                .priority_order
+                .get_or_insert_with(|| pool.keys_by_priority())
+                .pop_front()
+                .expect("the groups can't be added to the pool while iterating");
+        }
+        let key = *self
+            .pool
+            .transactions
+            .range((Bound::Excluded(self.pool.last_used_key), Bound::Unbounded))
+            .next()
+            .map(|(k, _v)| k)
+            .unwrap_or_else(|| {
+                self.pool
+                    .transactions
+                    .keys()
+                    .next()
+                    .expect("we've just checked that the map is not empty")
+            });
+        self.pool.last_used_key = key;
+        key
     }
 }
 
 /// The iterator works with the following algorithm:
 /// On next(), the iterator tries to get a transaction group from the pool, sorts transactions in
 /// it, and add it to the back of the sorted groups queue.
-/// Remembers the last used key, so it can continue from the next key.
+/// Remembers the last used key, so it can continue from the next key. If the pool is configured
+/// with a priority ordering, the groups are taken from the pool in the order of their priority
+/// instead.
 ///
 /// If the pool is empty, the iterator gets the group from the front of the sorted groups queue.
 ///
@@ -217,20 +458,7 @@ impl<'a> PoolIteratorWrapper<'a> {
 impl<'a> TransactionGroupIterator for PoolIteratorWrapper<'a> {
     fn next(&mut self) -> Option<&mut TransactionGroup> {
         if !self.pool.transactions.is_empty() {
-            let key = *self
-                .pool
-                .transactions
-                .range((Bound::Excluded(self.pool.last_used_key), Bound::Unbounded))
-                .next()
-                .map(|(k, _v)| k)
-                .unwrap_or_else(|| {
-                    self.pool
-                        .transactions
-                        .keys()
-                        .next()
-                        .expect("we've just checked that the map is not empty")
-                });
-            self.pool.last_used_key = key;
+            let key = self.next_key();
             let mut validated_txs =
                 self.pool.transactions.remove(&key).expect("just checked existence");
             validated_txs.sort_by_key(|vt| std::cmp::Reverse(vt.nonce()));
@@ -245,7 +473,7 @@ impl<'a> TransactionGroupIterator for PoolIteratorWrapper<'a> {
             while let Some(sorted_group) = self.sorted_groups.pop_front() {
                 if sorted_group.transactions.is_empty() {
                     for hash in sorted_group.removed_transaction_hashes {
-                        self.pool.unique_transactions.remove(&hash);
+                        self.pool.forget_transaction(&hash);
                     }
                     // See the comment in `insert_transaction` where we increase the size for reasoning
                     // why panicking here catches a logic error.
@@ -276,7 +504,7 @@ impl<'a> Drop for PoolIteratorWrapper<'a> {
     fn drop(&mut self) {
         for group in self.sorted_groups.drain(..) {
             for hash in group.removed_transaction_hashes {
-                self.pool.unique_transactions.remove(&hash);
+                self.pool.forget_transaction(&hash);
             }
             // See the comment in `insert_transaction` where we increase the size for reasoning
             // why panicking here catches a logic error.
@@ -372,7 +600,7 @@ mod tests {
         mut validated_txs: Vec<ValidatedTransaction>,
         expected_weight: u32,
     ) -> (Vec<u64>, TransactionPool) {
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool = TransactionPool::new(TEST_SEED, None, TransactionPoolConfig::default(), "");
         let mut rng = thread_rng();
         validated_txs.shuffle(&mut rng);
         for validated_tx in validated_txs {
@@ -484,7 +712,7 @@ mod tests {
             })
             .collect::<Vec<_>>();
 
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool = TransactionPool::new(TEST_SEED, None, TransactionPoolConfig::default(), "");
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions.clone() {
@@ -599,7 +827,7 @@ mod tests {
 
     #[test]
     fn test_transaction_pool_size() {
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool = TransactionPool::new(TEST_SEED, None, TransactionPoolConfig::default(), "");
         let transactions = generate_transactions("alice.near", "alice.near", 1, 100);
         let mut total_transaction_size = 0;
         // Adding transactions increases the size.
@@ -623,7 +851,12 @@ mod tests {
         // Each transaction is at least 1 byte in size, so the last transaction will not fit.
         let pool_size_limit =
             transactions.iter().map(|tx| tx.get_size()).sum::<u64>().checked_sub(1).unwrap();
-        let mut pool = TransactionPool::new(TEST_SEED, Some(pool_size_limit), "");
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(pool_size_limit),
+            TransactionPoolConfig::default(),
+            "",
+        );
         for (i, tx) in transactions.iter().cloned().enumerate() {
             if i + 1 < transactions.len() {
                 assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
@@ -632,4 +865,164 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_transaction_pool_size_limit_eviction() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 100);
+        // Each transaction is at least 1 byte in size, so the last transaction will not fit
+        // without evicting the oldest one.
+        let pool_size_limit =
+            transactions.iter().map(|tx| tx.get_size()).sum::<u64>().checked_sub(1).unwrap();
+        let config = TransactionPoolConfig { evict_when_full: true, ..Default::default() };
+        let mut pool = TransactionPool::new(TEST_SEED, Some(pool_size_limit), config, "");
+        for tx in transactions.iter().cloned() {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        assert_eq!(pool.len(), transactions.len() - 1);
+        assert!(pool.transaction_size() <= pool_size_limit);
+        let nonces: Vec<u64> =
+            prepare_transactions(&mut pool, 100).iter().map(|tx| tx.transaction.nonce()).collect();
+        assert_eq!(nonces, (2..=100).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_transaction_pool_size_limit_too_large_transaction() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 2);
+        let pool_size_limit = transactions[0].get_size() - 1;
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(pool_size_limit),
+            TransactionPoolConfig::default(),
+            "",
+        );
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::NoSpaceLeft);
+        }
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.transaction_size(), 0);
+    }
+
+    fn tiered_config(tiers: &[(&str, u64)]) -> TransactionPoolConfig {
+        TransactionPoolConfig {
+            evict_when_full: true,
+            priority: TransactionPriority::AccountTier,
+            account_tiers: tiers
+                .iter()
+                .map(|(account_id, tier)| (account_id.parse().unwrap(), *tier))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Transactions are evicted lowest priority first and transactions with higher priority
+    /// than the inserted one are never evicted.
+    #[test]
+    fn test_eviction_respects_priority() {
+        let alice = generate_transactions("alice.near", "alice.near", 1, 10);
+        let bob = generate_transactions("bob.near", "bob.near", 1, 10);
+        let carol = generate_transactions("carol.near", "carol.near", 1, 1);
+        let pool_size_limit = alice.iter().chain(&bob).map(|tx| tx.get_size()).sum::<u64>();
+        let config = tiered_config(&[("alice.near", 1), ("bob.near", 2), ("carol.near", 3)]);
+        let mut pool = TransactionPool::new(TEST_SEED, Some(pool_size_limit), config, "");
+        for tx in alice.into_iter().chain(bob) {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+
+        // Dave has the lowest tier so he can't evict anyone.
+        let dave = generate_transactions("dave.near", "dave.near", 1, 1);
+        assert_eq!(pool.insert_transaction(dave[0].clone()), InsertTransactionResult::NoSpaceLeft);
+        assert_eq!(pool.len(), 20);
+
+        // Carol has the highest tier and evicts the oldest transaction of Alice.
+        assert_eq!(pool.insert_transaction(carol[0].clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 20);
+        let txs = prepare_transactions(&mut pool, 20);
+        let alice_nonces: Vec<u64> = txs
+            .iter()
+            .filter(|tx| tx.transaction.signer_id().as_str() == "alice.near")
+            .map(|tx| tx.transaction.nonce())
+            .collect();
+        assert_eq!(alice_nonces, (2..=10).collect::<Vec<u64>>());
+
+        let dropped: Vec<_> = pool
+            .dropped_transactions()
+            .map(|tx| (tx.signer_id.to_string(), tx.nonce, tx.reason))
+            .collect();
+        assert_eq!(
+            dropped,
+            vec![
+                ("dave.near".to_string(), 1, DropReason::NoSpaceLeft),
+                ("alice.near".to_string(), 1, DropReason::Evicted),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signer_size_limit() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 10);
+        let signer_size_limit = transactions.iter().take(5).map(|tx| tx.get_size()).sum::<u64>();
+        let config = TransactionPoolConfig {
+            signer_size_limit: Some(signer_size_limit),
+            ..Default::default()
+        };
+        let mut pool = TransactionPool::new(TEST_SEED, None, config, "");
+        for (i, tx) in transactions.into_iter().enumerate() {
+            let expected = if i < 5 {
+                InsertTransactionResult::Success
+            } else {
+                InsertTransactionResult::AccountQuotaExceeded
+            };
+            assert_eq!(pool.insert_transaction(tx), expected);
+        }
+        // Other signers are not affected.
+        for tx in generate_transactions("bob.near", "bob.near", 1, 5) {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        // The quota is freed once the transactions leave the pool.
+        assert_eq!(prepare_transactions(&mut pool, 10).len(), 10);
+        for tx in generate_transactions("alice.near", "alice.near", 11, 15) {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+    }
+
+    #[test]
+    fn test_receiver_size_limit() {
+        // All generated transactions are sent to bob.near.
+        let alice = generate_transactions("alice.near", "alice.near", 1, 5);
+        let carol = generate_transactions("carol.near", "carol.near", 1, 5);
+        let receiver_size_limit = alice.iter().map(|tx| tx.get_size()).sum::<u64>();
+        let config = TransactionPoolConfig {
+            receiver_size_limit: Some(receiver_size_limit),
+            ..Default::default()
+        };
+        let mut pool = TransactionPool::new(TEST_SEED, None, config, "");
+        for tx in alice {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        for tx in carol {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::AccountQuotaExceeded);
+        }
+        assert_eq!(pool.len(), 5);
+    }
+
+    /// With a priority ordering the groups with higher priority are visited first.
+    #[test]
+    fn test_priority_order() {
+        let mut transactions = vec![];
+        for account_id in ["alice.near", "bob.near", "carol.near", "dave.near"] {
+            transactions.extend(generate_transactions(account_id, account_id, 1, 2));
+        }
+        let config = tiered_config(&[("carol.near", 5), ("alice.near", 3), ("dave.near", 1)]);
+        let mut pool = TransactionPool::new(TEST_SEED, None, config, "");
+        transactions.shuffle(&mut thread_rng());
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        let signers: Vec<String> = prepare_transactions(&mut pool, 8)
+            .iter()
+            .map(|tx| tx.transaction.signer_id().to_string())
+            .collect();
+        let expected = ["carol.near", "alice.near", "dave.near", "bob.near"];
+        assert_eq!(signers, [expected, expected].concat());
+    }
 }
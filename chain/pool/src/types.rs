@@ -1,5 +1,7 @@
+use near_crypto::PublicKey;
 use near_primitives::hash::CryptoHash;
 use near_primitives::transaction::ValidatedTransaction;
+use near_primitives::types::{AccountId, Nonce};
 
 /// Trait acts like an iterator. It iterates over transactions groups by returning mutable
 /// references to them. Each transaction group implements a draining iterator to pull transactions.
@@ -41,3 +43,40 @@ impl TransactionGroup {
         self.transactions.last()
     }
 }
+
+/// Reason a transaction left the pool or wasn't let into it before being included in a chunk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropReason {
+    /// Evicted to make space for a transaction with higher or equal priority.
+    Evicted,
+    /// Rejected because the pool was full of transactions with higher priority.
+    NoSpaceLeft,
+    /// Rejected because the signer or the receiver used up its quota of the pool.
+    AccountQuotaExceeded,
+}
+
+/// A transaction recently evicted from the pool or rejected by it.
+#[derive(Clone, Debug)]
+pub struct DroppedTransaction {
+    pub hash: CryptoHash,
+    pub signer_id: AccountId,
+    pub public_key: PublicKey,
+    pub receiver_id: AccountId,
+    pub nonce: Nonce,
+    pub size: u64,
+    pub reason: DropReason,
+}
+
+impl DroppedTransaction {
+    pub(crate) fn new(validated_tx: &ValidatedTransaction, reason: DropReason) -> Self {
+        Self {
+            hash: validated_tx.get_hash(),
+            signer_id: validated_tx.signer_id().clone(),
+            public_key: validated_tx.public_key().clone(),
+            receiver_id: validated_tx.receiver_id().clone(),
+            nonce: validated_tx.nonce(),
+            size: validated_tx.get_size(),
+            reason,
+        }
+    }
+}
@@ -163,7 +163,8 @@ pub(crate) async fn convert_block_to_transactions(
             }
             .with_span_context(),
         )
-        .await??;
+        .await??
+        .changes;
 
     let runtime_config = crate::utils::query_protocol_config(block.header.hash, view_client_addr)
         .await?
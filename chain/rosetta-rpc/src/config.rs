@@ -8,6 +8,10 @@ pub struct RosettaRpcConfig {
     pub limits: RosettaRpcLimitsConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub currencies: Option<Vec<Currency>>,
+    /// If true, `/mempool` lists the transactions in the transaction pools of the node, otherwise
+    /// it returns an empty list. Disabled by default as listing the pools is expensive.
+    #[serde(default)]
+    pub enable_debug_rpc: bool,
 }
 
 impl Default for RosettaRpcConfig {
@@ -17,6 +21,7 @@ impl Default for RosettaRpcConfig {
             cors_allowed_origins: vec!["*".to_owned()],
             limits: RosettaRpcLimitsConfig::default(),
             currencies: None,
+            enable_debug_rpc: false,
         }
     }
 }
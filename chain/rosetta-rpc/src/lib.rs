@@ -30,6 +30,12 @@ pub const BASE_PATH: &str = "";
 pub const API_VERSION: &str = "1.4.4";
 pub const BLOCKCHAIN: &str = "nearprotocol";
 
+/// Maximum number of transactions listed by `/mempool` which has no pagination.
+const MEMPOOL_LIMIT: usize = 1000;
+
+/// Whether the endpoints which are expensive to serve are enabled.
+struct DebugRpcEnabled(bool);
+
 /// Genesis together with genesis block identifier.
 struct GenesisWithIdentifier {
     genesis: Genesis,
@@ -445,16 +451,39 @@ async fn account_balance(
 }
 
 #[api_v2_operation]
-/// Get All Mempool Transactions (not implemented)
+/// Get All Mempool Transactions
 ///
 /// Get all Transaction Identifiers in the mempool
 ///
-/// NOTE: The mempool is short-lived, so it is currently not implemented.
+/// NOTE: Only validators keep a mempool, other nodes return an empty list. The mempool is only
+/// listed with `enable_debug_rpc` set in the config and at most the first 1000 transactions are
+/// returned.
 async fn mempool(
-    _client_addr: web::Data<Addr<ClientActor>>,
-    _body: Json<models::NetworkRequest>,
+    client_addr: web::Data<Addr<ClientActor>>,
+    tx_handler_addr: web::Data<Addr<TxRequestHandlerActor>>,
+    debug_rpc_enabled: web::Data<DebugRpcEnabled>,
+    body: Json<models::NetworkRequest>,
 ) -> Result<Json<models::MempoolResponse>, models::Error> {
-    Ok(Json(models::MempoolResponse { transaction_identifiers: vec![] }))
+    let Json(models::NetworkRequest { network_identifier }) = body;
+
+    check_network_identifier(&client_addr, network_identifier).await?;
+
+    if !debug_rpc_enabled.0 {
+        return Ok(Json(models::MempoolResponse { transaction_identifiers: vec![] }));
+    }
+    let mempool = tx_handler_addr
+        .send(
+            near_client::GetMempool { signer_id: None, after: None, limit: MEMPOOL_LIMIT }
+                .with_span_context(),
+        )
+        .await?;
+    let transaction_identifiers = mempool
+        .shards
+        .iter()
+        .flat_map(|shard| &shard.transactions)
+        .map(|transaction| models::TransactionIdentifier::transaction(&transaction.hash))
+        .collect();
+    Ok(Json(models::MempoolResponse { transaction_identifiers }))
 }
 
 #[api_v2_operation]
@@ -844,7 +873,13 @@ pub fn start_rosetta_rpc(
     view_client_addr: Addr<ViewClientActor>,
     tx_handler_addr: Addr<TxRequestHandlerActor>,
 ) -> actix_web::dev::ServerHandle {
-    let crate::config::RosettaRpcConfig { addr, cors_allowed_origins, limits, currencies } = config;
+    let crate::config::RosettaRpcConfig {
+        addr,
+        cors_allowed_origins,
+        limits,
+        currencies,
+        enable_debug_rpc,
+    } = config;
     let block_id = models::BlockIdentifier::new(genesis.config.genesis_height, genesis_block_hash);
     let genesis = Arc::new(GenesisWithIdentifier { genesis, block_id });
     let server = HttpServer::new(move || {
@@ -868,6 +903,7 @@ pub fn start_rosetta_rpc(
             .app_data(web::Data::new(view_client_addr.clone()))
             .app_data(web::Data::new(tx_handler_addr.clone()))
             .app_data(web::Data::new(currencies.clone()))
+            .app_data(web::Data::new(DebugRpcEnabled(enable_debug_rpc)))
             .wrap(get_cors(&cors_allowed_origins))
             .wrap_api()
             .service(web::resource("/network/list").route(web::post().to(network_list)))
@@ -59,6 +59,16 @@
 //! timestamp are executed in FIFO order. For example, if the events are emitted in the
 //! following order: (A due 100ms), (B due 0ms), (C due 200ms), (D due 0ms), (E due 100ms)
 //! then the actual order of execution is B, D, A, E, C.
+//!
+//! FIFO order hides bugs which only show up when events that are ready at the same time
+//! are handled in a different order. With `TestLoopV2::randomize_event_order`, events due
+//! at the same timestamp are executed in an order picked by an RNG with the given seed
+//! instead, so the above may as well run as D, B, E, A, C. Only events handled by
+//! different actors are reordered though: the messages an actor receives are still
+//! handled in the order in which they were sent, as actors rely on that in production,
+//! where every sender delivers its messages to an actor in order. The seed is printed if
+//! the test panics, and setting the `NEAR_TEST_LOOP_SEED` environment variable to it
+//! makes any test loop use it, which reproduces the same order of execution.
 pub mod data;
 pub mod futures;
 pub mod pending_events_sender;
@@ -68,12 +78,24 @@ use data::TestLoopData;
 use futures::{TestLoopAsyncComputationSpawner, TestLoopFutureSpawner};
 use near_time::{Clock, Duration, FakeClock};
 use pending_events_sender::{CallbackEvent, PendingEventsSender, RawPendingEventsSender};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::Serialize;
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use time::ext::InstantExt;
 
+/// Environment variable with the seed to randomize the order of events with, see
+/// `TestLoopV2::randomize_event_order`.
+pub const TEST_LOOP_SEED_ENV_VAR: &str = "NEAR_TEST_LOOP_SEED";
+
+/// Returns the seed given by the `NEAR_TEST_LOOP_SEED` environment variable, if any.
+pub fn seed_from_env() -> Option<u64> {
+    let seed = std::env::var(TEST_LOOP_SEED_ENV_VAR).ok()?;
+    Some(seed.parse().unwrap_or_else(|_| panic!("invalid {TEST_LOOP_SEED_ENV_VAR}: {seed}")))
+}
+
 /// Main struct for the Test Loop framework.
 /// The `TestLoopData` should contain all the business logic state that is relevant
 /// to the test. All possible `Event` that are sent to the event loop are callbacks.
@@ -105,18 +127,55 @@ pub struct TestLoopV2 {
     every_event_callback: Option<Box<dyn FnMut(&TestLoopData)>>,
     /// All events with this identifier are ignored in testloop execution environment.
     denylisted_identifiers: HashSet<String>,
+    /// If present, events due at the same time are executed in random order rather than
+    /// in FIFO order.
+    random_event_order: Option<RandomEventOrder>,
+}
+
+struct RandomEventOrder {
+    seed: u64,
+    rng: ChaCha20Rng,
+    /// The largest tie breaker of the queued events per destination actor and due time.
+    /// Later events to the same actor due at the same time get a tie breaker which is not
+    /// lower, so that the actor handles them in FIFO order.
+    max_tie_breakers: HashMap<(usize, Duration), u64>,
+}
+
+impl RandomEventOrder {
+    fn new(seed: u64) -> Self {
+        Self { seed, rng: ChaCha20Rng::seed_from_u64(seed), max_tie_breakers: HashMap::new() }
+    }
+
+    fn tie_breaker(&mut self, destination: Option<usize>, due: Duration) -> u64 {
+        let tie_breaker = self.rng.r#gen();
+        let Some(destination) = destination else {
+            return tie_breaker;
+        };
+        let max_tie_breaker = self.max_tie_breakers.entry((destination, due)).or_default();
+        *max_tie_breaker = tie_breaker.max(*max_tie_breaker);
+        *max_tie_breaker
+    }
+
+    /// Forgets the tie breakers of the events due before `current_time`, as no new events
+    /// can be due then.
+    fn forget_past_events(&mut self, current_time: Duration) {
+        self.max_tie_breakers.retain(|(_, due), _| *due >= current_time);
+    }
 }
 
-/// An event waiting to be executed, ordered by the due time and then by ID.
+/// An event waiting to be executed, ordered by the due time, then by the tie breaker and
+/// finally by ID. The tie breaker is random if the event order is randomized and zero
+/// otherwise.
 struct EventInHeap {
     event: CallbackEvent,
     due: Duration,
+    tie_breaker: u64,
     id: usize,
 }
 
 impl PartialEq for EventInHeap {
     fn eq(&self, other: &Self) -> bool {
-        self.due == other.due && self.id == other.id
+        self.due == other.due && self.tie_breaker == other.tie_breaker && self.id == other.id
     }
 }
 
@@ -130,7 +189,9 @@ impl PartialOrd for EventInHeap {
 
 impl Ord for EventInHeap {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (self.due, self.id).cmp(&(other.due, other.id)).reverse()
+        (self.due, self.tie_breaker, self.id)
+            .cmp(&(other.due, other.tie_breaker, other.id))
+            .reverse()
     }
 }
 
@@ -202,7 +263,7 @@ impl TestLoopV2 {
         let shutting_down = Arc::new(AtomicBool::new(false));
         // Needed for the log visualizer to know when the test loop starts.
         tracing::info!(target: "test_loop", "TEST_LOOP_INIT");
-        Self {
+        let mut test_loop = Self {
             data: TestLoopData::new(raw_pending_events_sender.clone(), shutting_down.clone()),
             events: BinaryHeap::new(),
             pending_events,
@@ -213,7 +274,24 @@ impl TestLoopV2 {
             shutting_down,
             every_event_callback: None,
             denylisted_identifiers: HashSet::new(),
+            random_event_order: None,
+        };
+        if let Some(seed) = seed_from_env() {
+            test_loop.randomize_event_order(seed);
         }
+        test_loop
+    }
+
+    /// Executes the events due at the same time in an order determined by the seed instead
+    /// of the order in which they were sent. Only affects the events sent after the call.
+    pub fn randomize_event_order(&mut self, seed: u64) {
+        tracing::info!(target: "test_loop", seed, "randomizing the order of events");
+        self.random_event_order = Some(RandomEventOrder::new(seed));
+    }
+
+    /// Returns the seed the order of events is randomized with, if it is.
+    pub fn event_order_seed(&self) -> Option<u64> {
+        self.random_event_order.as_ref().map(|order| order.seed)
     }
 
     /// Returns a FutureSpawner that can be used to spawn futures into the loop.
@@ -275,12 +353,16 @@ impl TestLoopV2 {
 
     /// Helper to push events we have just received into the heap.
     fn queue_received_events(&mut self) {
+        if let Some(order) = &mut self.random_event_order {
+            order.forget_past_events(self.current_time);
+        }
         for event in self.pending_events.lock().unwrap().events.drain(..) {
-            self.events.push(EventInHeap {
-                due: self.current_time + event.delay,
-                id: self.next_event_index,
-                event,
-            });
+            let due = self.current_time + event.delay;
+            let tie_breaker = match &mut self.random_event_order {
+                Some(order) => order.tie_breaker(event.destination, due),
+                None => 0,
+            };
+            self.events.push(EventInHeap { due, tie_breaker, id: self.next_event_index, event });
             self.next_event_index += 1;
         }
     }
@@ -438,6 +520,14 @@ impl TestLoopV2 {
 
 impl Drop for TestLoopV2 {
     fn drop(&mut self) {
+        if std::thread::panicking() {
+            if let Some(seed) = self.event_order_seed() {
+                eprintln!(
+                    "TestLoop events were executed in random order with seed {seed}, \
+                    set {TEST_LOOP_SEED_ENV_VAR}={seed} to reproduce"
+                );
+            }
+        }
         self.queue_received_events();
         if let Some(event) = self.events.pop() {
             // Drop any references that may be held by the event callbacks. This can help
@@ -464,8 +554,9 @@ enum AdvanceDecision {
 mod tests {
     use crate::futures::FutureSpawnerExt;
     use crate::test_loop::TestLoopV2;
-    use std::sync::Arc;
+    use crate::test_loop::data::TestLoopData;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
     use time::Duration;
 
     // Tests that the TestLoop correctly handles futures that sleep on the fake clock.
@@ -506,4 +597,74 @@ mod tests {
         test_loop.run_for(Duration::seconds(30));
         assert_eq!(finished.load(Ordering::Relaxed), 2);
     }
+
+    /// Returns the order in which events sent in order 0..10 are executed. Event 0 is due
+    /// later than the others.
+    fn execution_order(seed: u64) -> Vec<usize> {
+        let mut test_loop = TestLoopV2::new();
+        test_loop.randomize_event_order(seed);
+        let order = Arc::new(Mutex::new(vec![]));
+        for i in 0..10 {
+            let order = order.clone();
+            let delay = if i == 0 { Duration::milliseconds(1) } else { Duration::ZERO };
+            test_loop.send_adhoc_event_with_delay(format!("event {i}"), delay, move |_| {
+                order.lock().unwrap().push(i)
+            });
+        }
+        test_loop.run_for(Duration::milliseconds(1));
+        order.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_random_event_order() {
+        let order = execution_order(42);
+        assert_eq!(order, execution_order(42));
+        // Events are still executed in the order of their due time.
+        assert_eq!(order.last(), Some(&0));
+        let mut sorted_order = order.clone();
+        sorted_order.sort();
+        assert_eq!(sorted_order, (0..10).collect::<Vec<_>>());
+        // Some seeds give a different order than FIFO.
+        assert!(
+            (0..10).any(|seed| execution_order(seed) != (1..10).chain([0]).collect::<Vec<_>>())
+        );
+    }
+
+    /// Returns the destinations of the events sent in order to alternating actors 0 and 1,
+    /// in the order in which they are executed, along with their index.
+    fn execution_order_per_actor(seed: u64) -> Vec<(usize, usize)> {
+        let mut test_loop = TestLoopV2::new();
+        test_loop.randomize_event_order(seed);
+        let senders = [0, 1].map(|actor_id| {
+            test_loop.raw_pending_events_sender.for_actor(&format!("actor {actor_id}"), actor_id)
+        });
+        let order = Arc::new(Mutex::new(vec![]));
+        for i in 0..20 {
+            let order = order.clone();
+            let actor_id = i % 2;
+            senders[actor_id].send(
+                format!("event {i}"),
+                Box::new(move |_: &mut TestLoopData| order.lock().unwrap().push((actor_id, i))),
+            );
+        }
+        test_loop.run_instant();
+        order.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_random_event_order_keeps_order_per_actor() {
+        let fifo: Vec<_> = (0..20).map(|i| (i % 2, i)).collect();
+        let mut reordered = false;
+        for seed in 0..10 {
+            let order = execution_order_per_actor(seed);
+            for actor_id in [0, 1] {
+                let events: Vec<_> =
+                    order.iter().filter(|(id, _)| *id == actor_id).map(|(_, i)| *i).collect();
+                assert_eq!(events, (actor_id..20).step_by(2).collect::<Vec<_>>());
+            }
+            reordered |= order != fifo;
+        }
+        // The events of different actors are still interleaved randomly.
+        assert!(reordered);
+    }
 }
@@ -5,7 +5,7 @@ use std::sync::atomic::AtomicBool;
 
 use crate::messaging::{Actor, LateBoundSender};
 
-use super::pending_events_sender::RawPendingEventsSender;
+use super::pending_events_sender::{PendingEventsSender, RawPendingEventsSender};
 use super::sender::TestLoopSender;
 
 /// TestLoopData is the container for all data that is stored and accessed by the test loop.
@@ -77,12 +77,14 @@ impl TestLoopData {
         A: Actor + 'static,
     {
         let actor_handle = self.register_data(actor);
+        let pending_events_sender =
+            self.raw_pending_events_sender.for_actor(identifier, actor_handle.id);
         let sender = TestLoopSender::new(
             actor_handle,
-            self.raw_pending_events_sender.for_identifier(identifier),
+            pending_events_sender.clone(),
             self.shutting_down.clone(),
         );
-        self.queue_start_actor_event(identifier, sender.clone());
+        Self::queue_start_actor_event(&pending_events_sender, sender.clone());
         if let Some(adapter) = adapter {
             adapter.bind(sender.clone());
         }
@@ -90,16 +92,17 @@ impl TestLoopData {
     }
 
     // Helper function to queue the start actor event on the test loop while registering an actor.
-    fn queue_start_actor_event<A>(&self, identifier: &str, mut sender: TestLoopSender<A>)
-    where
+    fn queue_start_actor_event<A>(
+        pending_events_sender: &PendingEventsSender,
+        mut sender: TestLoopSender<A>,
+    ) where
         A: Actor + 'static,
     {
         let callback = move |data: &mut TestLoopData| {
             let actor = data.get_mut(&sender.actor_handle());
             actor.start_actor(&mut sender);
         };
-        self.raw_pending_events_sender
-            .for_identifier(identifier)
+        pending_events_sender
             .send(format!("StartActor({:?})", type_name::<A>()), Box::new(callback));
     }
 
@@ -17,7 +17,20 @@ impl RawPendingEventsSender {
     }
 
     pub(crate) fn for_identifier(&self, identifier: &str) -> PendingEventsSender {
-        PendingEventsSender { identifier: identifier.to_string(), sender: self.clone() }
+        PendingEventsSender {
+            identifier: identifier.to_string(),
+            destination: None,
+            sender: self.clone(),
+        }
+    }
+
+    /// Like `for_identifier`, for the events handled by the actor with the given data ID.
+    pub(crate) fn for_actor(&self, identifier: &str, actor_id: usize) -> PendingEventsSender {
+        PendingEventsSender {
+            identifier: identifier.to_string(),
+            destination: Some(actor_id),
+            sender: self.clone(),
+        }
     }
 }
 
@@ -28,6 +41,7 @@ impl RawPendingEventsSender {
 #[derive(Clone)]
 pub struct PendingEventsSender {
     identifier: String,
+    destination: Option<usize>,
     sender: RawPendingEventsSender,
 }
 
@@ -45,7 +59,8 @@ impl PendingEventsSender {
         delay: Duration,
     ) {
         let identifier = self.identifier.clone();
-        (self.sender.0)(CallbackEvent { identifier, description, callback, delay });
+        let destination = self.destination;
+        (self.sender.0)(CallbackEvent { identifier, destination, description, callback, delay });
     }
 }
 
@@ -58,5 +73,8 @@ pub(crate) struct CallbackEvent {
     pub(crate) callback: TestLoopCallback,
     pub(crate) delay: Duration,
     pub(crate) identifier: String,
+    /// Data ID of the actor which handles the event, if any. The events handled by the
+    /// same actor are executed in FIFO order even if the order of events is randomized.
+    pub(crate) destination: Option<usize>,
     pub(crate) description: String,
 }
@@ -2,6 +2,7 @@
 use crate::ExternalStorageLocation::GCS;
 use crate::MutableConfigValue;
 use bytesize::ByteSize;
+use near_crypto::PublicKey;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockHeightDelta, Gas, NumBlocks, NumSeats, ShardId,
 };
@@ -9,6 +10,7 @@ use near_primitives::version::Version;
 use near_time::Duration;
 use num_rational::Rational32;
 use std::cmp::{max, min};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -102,6 +104,11 @@ pub struct ExternalStorageConfig {
     /// the network before it fetches from external storage.
     #[serde(default = "default_external_storage_fallback_threshold")]
     pub external_storage_fallback_threshold: u64,
+    /// Node keys of the dump nodes whose manifests of state parts are trusted.
+    /// Parts which don't match the manifest are rejected before they are
+    /// validated against the state root.  Manifests are ignored if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub manifest_signers: Vec<PublicKey>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -118,6 +125,13 @@ pub enum ExternalStorageLocation {
     GCS {
         bucket: String,
     },
+    /// Read-only HTTP(S) mirrors serving the same state dumps.  The mirrors
+    /// are tried in the given order, skipping those which failed recently.
+    /// Can't be used to dump state.
+    Http {
+        /// Base URLs of the mirrors.
+        urls: Vec<String>,
+    },
 }
 
 /// Configures how to dump state to external storage.
@@ -175,6 +189,7 @@ impl StateSyncConfig {
                 num_concurrent_requests_during_catchup:
                     DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_ON_CATCHUP_EXTERNAL,
                 external_storage_fallback_threshold: DEFAULT_EXTERNAL_STORAGE_FALLBACK_THRESHOLD,
+                manifest_signers: vec![],
             }),
         }
     }
@@ -227,6 +242,41 @@ impl Default for EpochSyncConfig {
     }
 }
 
+/// Order in which the chunk producer takes transactions from the transaction pool.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionPriority {
+    /// Transaction groups are visited round robin in a randomized order.
+    #[default]
+    None,
+    /// Groups with transactions paying a higher priority fee are visited first.
+    PriorityFee,
+    /// Groups of accounts with a higher tier in `account_tiers` are visited first.
+    AccountTier,
+}
+
+/// Limits and ordering of the per-shard transaction pools.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct TransactionPoolConfig {
+    /// If set, transactions signed by a single account may take at most this many bytes of
+    /// a shard pool.
+    pub signer_size_limit: Option<u64>,
+    /// If set, transactions sent to a single account may take at most this many bytes of
+    /// a shard pool.
+    pub receiver_size_limit: Option<u64>,
+    /// If set, a new transaction which doesn't fit into a full pool evicts the transactions with
+    /// the lowest priority, oldest first, instead of being rejected. Transactions with a higher
+    /// priority than the new one are never evicted.
+    pub evict_when_full: bool,
+    /// Order in which the chunk producer takes transactions from the pool. Also decides which
+    /// transactions are evicted first if `evict_when_full` is set.
+    pub priority: TransactionPriority,
+    /// Tiers of signer accounts used by `TransactionPriority::AccountTier`. Accounts which are
+    /// not listed have tier zero.
+    pub account_tiers: BTreeMap<AccountId, u64>,
+}
+
 // A handle that allows the main process to interrupt resharding if needed.
 // This typically happens when the main process is interrupted.
 #[derive(Clone, Debug)]
@@ -544,6 +594,8 @@ pub struct ClientConfig {
     /// Limit of the size of per-shard transaction pool measured in bytes. If not set, the size
     /// will be unbounded.
     pub transaction_pool_size_limit: Option<u64>,
+    /// Per-account quotas and priority ordering of the transaction pool.
+    pub transaction_pool: TransactionPoolConfig,
     // Allows more detailed logging, for example a list of orphaned blocks.
     pub enable_multiline_logging: bool,
     // Configuration for resharding.
@@ -577,6 +629,10 @@ pub struct ClientConfig {
     /// as a large number of incoming witnesses could cause denial of service.
     pub save_latest_witnesses: bool,
     pub transaction_request_handler_threads: usize,
+    /// Audit the balances of every chunk applied to update a tracked shard, see
+    /// `EXPERIMENTAL_chunk_balance_audit`.  Reading the balances from the state before the chunk
+    /// slows down chunk application, so it is disabled by default.
+    pub audit_chunk_balances: bool,
 }
 
 impl ClientConfig {
@@ -653,6 +709,7 @@ impl ClientConfig {
             state_sync: StateSyncConfig::default(),
             epoch_sync: EpochSyncConfig::default(),
             transaction_pool_size_limit: None,
+            transaction_pool: TransactionPoolConfig::default(),
             enable_multiline_logging: false,
             resharding_config: MutableConfigValue::new(
                 ReshardingConfig::default(),
@@ -668,6 +725,7 @@ impl ClientConfig {
             orphan_state_witness_max_size: default_orphan_state_witness_max_size(),
             save_latest_witnesses: false,
             transaction_request_handler_threads: 4,
+            audit_chunk_balances: false,
         }
     }
 }
@@ -1,9 +1,31 @@
 use crate::{PublicKey, SecretKey};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
 use near_account_id::AccountId;
 use std::fs::File;
 use std::io;
 use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::OnceLock;
+
+/// Environment variable from which the passphrase of encrypted key files is
+/// read unless one was set with [`set_key_passphrase`].
+pub const KEY_PASSPHRASE_ENV_VAR: &str = "NEAR_KEY_PASSPHRASE";
+
+static KEY_PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+/// Sets the passphrase used to unlock encrypted key files loaded with
+/// [`KeyFile::from_file`] for the rest of the process lifetime.
+///
+/// The passphrase is kept in memory so that keys can be reloaded, e.g. when
+/// the node reloads its validator key.  Only the first call has an effect.
+pub fn set_key_passphrase(passphrase: String) {
+    let _ = KEY_PASSPHRASE.set(passphrase);
+}
+
+fn key_passphrase() -> Option<String> {
+    KEY_PASSPHRASE.get().cloned().or_else(|| std::env::var(KEY_PASSPHRASE_ENV_VAR).ok())
+}
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct KeyFile {
@@ -16,11 +38,186 @@ pub struct KeyFile {
     pub secret_key: SecretKey,
 }
 
+/// Key file whose secret key is encrypted with a key derived from
+/// a passphrase.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EncryptedKeyFile {
+    account_id: AccountId,
+    public_key: PublicKey,
+    encrypted_secret_key: EncryptedSecretKey,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EncryptedSecretKey {
+    kdf: KdfParams,
+    cipher: Cipher,
+    #[serde(with = "hex")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex")]
+    ciphertext: Vec<u8>,
+}
+
+/// Parameters of the function deriving the encryption key from the
+/// passphrase.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+enum KdfParams {
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        #[serde(with = "hex")]
+        salt: Vec<u8>,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+enum Cipher {
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+/// Used to tell encrypted key files apart from plain ones without parsing
+/// the whole file.
+#[derive(serde::Deserialize)]
+struct KeyFileFormat {
+    #[serde(default)]
+    encrypted_secret_key: Option<serde::de::IgnoredAny>,
+}
+
+/// scrypt parameters recommended for interactive logins.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+/// Upper bounds of the scrypt parameters accepted when reading a key file, so
+/// that a crafted file cannot make the node allocate gigabytes of memory or
+/// spin for hours.  With these bounds scrypt uses at most 256 MiB.
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 16;
+const MAX_SCRYPT_P: u32 = 16;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+impl KdfParams {
+    fn derive_key(&self, passphrase: &str) -> io::Result<Key> {
+        let mut key = Key::default();
+        match self {
+            KdfParams::Scrypt { log_n, r, p, salt } => {
+                if *log_n > MAX_SCRYPT_LOG_N || *r > MAX_SCRYPT_R || *p > MAX_SCRYPT_P {
+                    return Err(invalid_data(format!(
+                        "scrypt parameters log_n={log_n}, r={r}, p={p} exceed the maximum of \
+                         log_n={MAX_SCRYPT_LOG_N}, r={MAX_SCRYPT_R}, p={MAX_SCRYPT_P}"
+                    )));
+                }
+                let params = scrypt::Params::new(*log_n, *r, *p, key.len())
+                    .map_err(|err| invalid_data(format!("invalid scrypt parameters: {err}")))?;
+                scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+                    .map_err(|err| invalid_data(format!("scrypt failed: {err}")))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl EncryptedKeyFile {
+    #[cfg(feature = "rand")]
+    fn encrypt(key_file: &KeyFile, passphrase: &str) -> io::Result<Self> {
+        use secp256k1::rand::RngCore;
+        use secp256k1::rand::rngs::OsRng;
+
+        let mut salt = vec![0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = vec![0; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let kdf = KdfParams::Scrypt { log_n: SCRYPT_LOG_N, r: SCRYPT_R, p: SCRYPT_P, salt };
+        let cipher = ChaCha20Poly1305::new(&kdf.derive_key(passphrase)?);
+        let secret_key = key_file.secret_key.to_string();
+        let aad = key_file.public_key.to_string();
+        let payload = Payload { msg: secret_key.as_bytes(), aad: aad.as_bytes() };
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| io::Error::other("failed to encrypt secret key"))?;
+        Ok(Self {
+            account_id: key_file.account_id.clone(),
+            public_key: key_file.public_key.clone(),
+            encrypted_secret_key: EncryptedSecretKey {
+                kdf,
+                cipher: Cipher::ChaCha20Poly1305,
+                nonce,
+                ciphertext,
+            },
+        })
+    }
+
+    fn decrypt(self, passphrase: &str) -> io::Result<KeyFile> {
+        let EncryptedSecretKey { kdf, cipher: Cipher::ChaCha20Poly1305, nonce, ciphertext } =
+            self.encrypted_secret_key;
+        if nonce.len() != NONCE_LEN {
+            return Err(invalid_data(format!("invalid nonce length {}", nonce.len())));
+        }
+        let cipher = ChaCha20Poly1305::new(&kdf.derive_key(passphrase)?);
+        let aad = self.public_key.to_string();
+        let payload = Payload { msg: &ciphertext, aad: aad.as_bytes() };
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| invalid_data("failed to decrypt secret key, wrong passphrase?"))?;
+        let secret_key: SecretKey = std::str::from_utf8(&plaintext)
+            .ok()
+            .and_then(|secret_key| secret_key.parse().ok())
+            .ok_or_else(|| invalid_data("decrypted secret key is malformed"))?;
+        if secret_key.public_key() != self.public_key {
+            return Err(invalid_data("decrypted secret key does not match the public key"));
+        }
+        Ok(KeyFile { account_id: self.account_id, public_key: self.public_key, secret_key })
+    }
+}
+
 impl KeyFile {
     pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
         let data = serde_json::to_string_pretty(self)?;
-        let mut file = Self::create(path)?;
-        file.write_all(data.as_bytes())
+        Self::write_atomically(path, data.as_bytes())
+    }
+
+    /// Writes the key file with the secret key encrypted with given
+    /// passphrase.
+    #[cfg(feature = "rand")]
+    pub fn write_encrypted_to_file(&self, path: &Path, passphrase: &str) -> io::Result<()> {
+        let data = serde_json::to_string_pretty(&EncryptedKeyFile::encrypt(self, passphrase)?)?;
+        Self::write_atomically(path, data.as_bytes())
+    }
+
+    /// Writes the data into a temporary file next to `path` and renames it
+    /// over `path`, so that an existing key file is never left truncated, e.g.
+    /// when it is converted in place and the write is interrupted.
+    fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut tmp_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?
+            .to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = dir.join(tmp_name);
+        // A leftover of an interrupted write may have been created with other
+        // permissions.
+        match std::fs::remove_file(&tmp_path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        let mut file = Self::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+        // The rename is only durable once the directory is synced.
+        #[cfg(unix)]
+        File::open(dir)?.sync_all()?;
+        Ok(())
     }
 
     #[cfg(unix)]
@@ -34,14 +231,46 @@ impl KeyFile {
         std::fs::File::create(path)
     }
 
+    /// Loads the key file.  Encrypted key files are unlocked with the
+    /// passphrase set with [`set_key_passphrase`] or taken from the
+    /// `NEAR_KEY_PASSPHRASE` environment variable.
     pub fn from_file(path: &Path) -> io::Result<Self> {
+        Self::from_file_with_passphrase(path, key_passphrase().as_deref())
+    }
+
+    pub fn from_file_with_passphrase(path: &Path, passphrase: Option<&str>) -> io::Result<Self> {
+        let json_str_without_comments = Self::read_json(path)?;
+        if !Self::is_encrypted_json(&json_str_without_comments)? {
+            return Ok(serde_json::from_str(&json_str_without_comments)?);
+        }
+        let encrypted: EncryptedKeyFile = serde_json::from_str(&json_str_without_comments)?;
+        let passphrase = passphrase.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is encrypted but no passphrase was provided, set {KEY_PASSPHRASE_ENV_VAR}",
+                    path.display()
+                ),
+            )
+        })?;
+        encrypted.decrypt(passphrase)
+    }
+
+    /// Returns whether the secret key in given key file is encrypted.
+    pub fn is_encrypted_file(path: &Path) -> io::Result<bool> {
+        Self::is_encrypted_json(&Self::read_json(path)?)
+    }
+
+    fn is_encrypted_json(json_str: &str) -> io::Result<bool> {
+        let format: KeyFileFormat = serde_json::from_str(json_str)?;
+        Ok(format.encrypted_secret_key.is_some())
+    }
+
+    fn read_json(path: &Path) -> io::Result<String> {
         let mut file = File::open(path)?;
         let mut json_config_str = String::new();
         file.read_to_string(&mut json_config_str)?;
-        let json_str_without_comments: String =
-            near_config_utils::strip_comments_from_json_str(&json_config_str)?;
-
-        Ok(serde_json::from_str(&json_str_without_comments)?)
+        near_config_utils::strip_comments_from_json_str(&json_config_str)
     }
 }
 
@@ -111,4 +340,53 @@ mod test {
         let inner_msg = err.into_inner().unwrap().to_string();
         assert!(inner_msg.contains("duplicate field"));
     }
+
+    #[test]
+    fn test_encrypted_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("key-file");
+
+        let account_id = ACCOUNT_ID.parse().unwrap();
+        let secret_key: SecretKey = SECRET_KEY.parse().unwrap();
+        let public_key = secret_key.public_key();
+        let key = KeyFile { account_id, public_key, secret_key };
+        key.write_encrypted_to_file(&path, "passphrase").unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(SECRET_KEY.strip_prefix("ed25519:").unwrap()));
+        assert!(KeyFile::is_encrypted_file(&path).unwrap());
+
+        let loaded = KeyFile::from_file_with_passphrase(&path, Some("passphrase")).unwrap();
+        assert_eq!(key.account_id, loaded.account_id);
+        assert_eq!(key.secret_key, loaded.secret_key);
+
+        let err = KeyFile::from_file_with_passphrase(&path, Some("wrong")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = KeyFile::from_file_with_passphrase(&path, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_encrypted_file_expensive_kdf() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("key-file");
+
+        let account_id = ACCOUNT_ID.parse().unwrap();
+        let secret_key: SecretKey = SECRET_KEY.parse().unwrap();
+        let public_key = secret_key.public_key();
+        let key = KeyFile { account_id, public_key, secret_key };
+        key.write_encrypted_to_file(&path, "passphrase").unwrap();
+
+        // A key file asking for an absurdly expensive key derivation is
+        // rejected before scrypt runs.
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let contents = contents.replace(
+            &format!("\"log_n\": {SCRYPT_LOG_N}"),
+            &format!("\"log_n\": {}", MAX_SCRYPT_LOG_N + 10),
+        );
+        std::fs::write(&path, contents).unwrap();
+        let err = KeyFile::from_file_with_passphrase(&path, Some("passphrase")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.into_inner().unwrap().to_string().contains("exceed the maximum"));
+    }
 }
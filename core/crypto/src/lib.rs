@@ -2,7 +2,7 @@
 #![deny(clippy::arithmetic_side_effects)]
 
 pub use errors::{ParseKeyError, ParseKeyTypeError, ParseSignatureError};
-pub use key_file::KeyFile;
+pub use key_file::{KEY_PASSPHRASE_ENV_VAR, KeyFile, set_key_passphrase};
 pub use signature::{
     ED25519PublicKey, ED25519SecretKey, KeyType, PublicKey, Secp256K1PublicKey, Secp256K1Signature,
     SecretKey, Signature,
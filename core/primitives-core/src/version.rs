@@ -276,6 +276,10 @@ pub enum ProtocolFeature {
     /// Enable optimistic block production.
     ProduceOptimisticBlock,
     GlobalContracts,
+    /// Encrypt peer connections by default.  This is not strictly a protocol
+    /// feature, but is included here to coordinate among nodes.  Nodes can
+    /// require encryption regardless of the protocol version.
+    EncryptedTransport,
 }
 
 impl ProtocolFeature {
@@ -372,6 +376,7 @@ impl ProtocolFeature {
             // that always enables this for mocknet (see config_mocknet function).
             ProtocolFeature::ShuffleShardAssignments => 143,
             ProtocolFeature::ExcludeExistingCodeFromWitnessForCodeLen => 148,
+            ProtocolFeature::EncryptedTransport => 149,
             // Place features that are not yet in Nightly below this line.
         }
     }
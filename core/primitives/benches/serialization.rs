@@ -84,6 +84,7 @@ fn create_block() -> Block {
         None,
         None,
     )
+    .unwrap()
 }
 
 fn create_account() -> Account {
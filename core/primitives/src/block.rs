@@ -102,6 +102,8 @@ impl Block {
     }
 
     /// Produces new block from header of previous block, current state root and set of transactions.
+    ///
+    /// Fails if the signer refuses to sign the block.
     #[cfg(feature = "clock")]
     pub fn produce(
         this_epoch_protocol_version: ProtocolVersion,
@@ -127,7 +129,7 @@ impl Block {
         clock: near_time::Clock,
         sandbox_delta_time: Option<near_time::Duration>,
         optimistic_block: Option<OptimisticBlock>,
-    ) -> Self {
+    ) -> Result<Self, crate::validator_signer::ValidatorSignerError> {
         // Collect aggregate of validators and gas usage/limits from chunks.
         let mut prev_validator_proposals = vec![];
         let mut gas_used = 0;
@@ -158,9 +160,8 @@ impl Block {
         let new_total_supply = prev.total_supply() + minted_amount.unwrap_or(0) - balance_burnt;
 
         // Use the optimistic block data if available, otherwise compute it.
-        let (time, vrf_value, vrf_proof, random_value) = optimistic_block
-            .as_ref()
-            .map(|ob| {
+        let (time, vrf_value, vrf_proof, random_value) = match optimistic_block.as_ref() {
+            Some(ob) => {
                 tracing::debug!(target: "client", "Taking metadata from optimistic block");
                 (
                     ob.inner.block_timestamp,
@@ -168,8 +169,9 @@ impl Block {
                     ob.inner.vrf_proof,
                     ob.inner.random_value,
                 )
-            })
-            .unwrap_or_else(|| get_block_metadata(prev, signer, clock, sandbox_delta_time));
+            }
+            None => get_block_metadata(prev, signer, clock, sandbox_delta_time)?,
+        };
 
         let last_ds_final_block =
             if height == prev.height() + 1 { prev.hash() } else { prev.last_ds_final_block() };
@@ -246,9 +248,9 @@ impl Block {
             block_merkle_root,
             prev.height(),
             chunk_endorsements_bitmap,
-        );
+        )?;
 
-        Self::new_block(header, body)
+        Ok(Self::new_block(header, body))
     }
 
     pub fn verify_total_supply(
@@ -5,7 +5,7 @@ use crate::network::PeerId;
 use crate::stateless_validation::chunk_endorsements_bitmap::ChunkEndorsementsBitmap;
 use crate::types::validator_stake::{ValidatorStake, ValidatorStakeIter, ValidatorStakeV1};
 use crate::types::{AccountId, Balance, BlockHeight, EpochId, MerkleHash, NumBlocks};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{ValidatorMessage, ValidatorSigner, ValidatorSignerError};
 use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{KeyType, PublicKey, Signature};
@@ -340,16 +340,29 @@ impl ApprovalInner {
 }
 
 impl Approval {
+    /// **Panics** if the signer refuses to sign the approval.  Use
+    /// [`Self::try_new`] when signing with a remote signer.
     pub fn new(
         parent_hash: CryptoHash,
         parent_height: BlockHeight,
         target_height: BlockHeight,
         signer: &ValidatorSigner,
     ) -> Self {
+        Self::try_new(parent_hash, parent_height, target_height, signer)
+            .expect("failed to sign approval")
+    }
+
+    pub fn try_new(
+        parent_hash: CryptoHash,
+        parent_height: BlockHeight,
+        target_height: BlockHeight,
+        signer: &ValidatorSigner,
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = ApprovalInner::new(&parent_hash, parent_height, target_height);
 
-        let signature = signer.sign_bytes(&Approval::get_data_for_sig(&inner, target_height));
-        Approval { inner, target_height, signature, account_id: signer.validator_id().clone() }
+        let signature = signer
+            .sign_message(&ValidatorMessage::Approval { inner: inner.clone(), target_height })?;
+        Ok(Approval { inner, target_height, signature, account_id: signer.validator_id().clone() })
     }
 
     pub fn get_data_for_sig(inner: &ApprovalInner, target_height: BlockHeight) -> Vec<u8> {
@@ -572,6 +585,8 @@ impl BlockHeader {
     }
 
     /// Creates BlockHeader for a newly produced block.
+    ///
+    /// Fails if the signer refuses to sign the header.
     pub fn new(
         this_epoch_protocol_version: ProtocolVersion,
         latest_protocol_version: ProtocolVersion,
@@ -603,7 +618,7 @@ impl BlockHeader {
         block_merkle_root: CryptoHash,
         prev_height: BlockHeight,
         chunk_endorsements: Option<ChunkEndorsementsBitmap>,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         Self::new_impl(
             this_epoch_protocol_version,
             latest_protocol_version,
@@ -702,7 +717,8 @@ impl BlockHeader {
             block_merkle_root,
             prev_height,
             chunk_endorsements,
-        );
+        )
+        .expect("using an existing signature cannot fail");
         // Note: We do not panic but only log if the hash of the created header does not match the expected hash (From the view)
         // because there are tests that check if we can downgrade a BlockHeader's view a previous version, in which case the hash
         // of the header changes.
@@ -744,7 +760,7 @@ impl BlockHeader {
         block_merkle_root: CryptoHash,
         prev_height: BlockHeight,
         chunk_endorsements: Option<ChunkEndorsementsBitmap>,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner_lite = BlockHeaderInnerLite {
             height,
             epoch_id,
@@ -782,14 +798,14 @@ impl BlockHeader {
                 chunk_endorsements,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV5(Arc::new(BlockHeaderV5 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV5(Arc::new(BlockHeaderV5 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         } else {
             let inner_rest = BlockHeaderInnerRestV4 {
                 block_body_hash,
@@ -812,14 +828,14 @@ impl BlockHeader {
                 latest_protocol_version,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV4(Arc::new(BlockHeaderV4 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV4(Arc::new(BlockHeaderV4 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         }
     }
 
@@ -832,19 +848,27 @@ impl BlockHeader {
         prev_hash: CryptoHash,
         inner_lite: &BlockHeaderInnerLite,
         inner_rest: &T,
-    ) -> (CryptoHash, Signature)
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError>
     where
         T: BorshSerialize + ?Sized,
     {
+        let inner_rest = borsh::to_vec(&inner_rest).expect("Failed to serialize");
         let hash = BlockHeader::compute_hash(
             prev_hash,
             &borsh::to_vec(&inner_lite).expect("Failed to serialize"),
-            &borsh::to_vec(&inner_rest).expect("Failed to serialize"),
+            &inner_rest,
         );
-        match signature_source {
-            SignatureSource::Signer(signer) => (hash, signer.sign_bytes(hash.as_ref())),
-            SignatureSource::Signature(signature) => (hash, signature),
-        }
+        let signature = match signature_source {
+            SignatureSource::Signer(signer) => {
+                signer.sign_message(&ValidatorMessage::BlockHeader {
+                    prev_hash,
+                    inner_lite: inner_lite.clone(),
+                    inner_rest,
+                })?
+            }
+            SignatureSource::Signature(signature) => signature,
+        };
+        Ok((hash, signature))
     }
 
     pub fn genesis(
@@ -895,6 +919,7 @@ impl BlockHeader {
             0,                     // prev_height
             Some(ChunkEndorsementsBitmap::genesis()),
         )
+        .expect("using an existing signature cannot fail")
     }
 
     #[inline]
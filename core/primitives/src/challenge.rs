@@ -3,7 +3,7 @@ use crate::merkle::MerklePath;
 use crate::sharding::{EncodedShardChunk, ShardChunk, ShardChunkHeader};
 use crate::state::PartialState;
 use crate::types::AccountId;
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{ValidatorMessage, ValidatorSigner, ValidatorSignerError};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::Signature;
 use near_primitives_core::types::BlockHeight;
@@ -91,10 +91,13 @@ impl Challenge {
         self.hash = CryptoHash::hash_borsh(&self.body);
     }
 
-    pub fn produce(body: ChallengeBody, signer: &ValidatorSigner) -> Self {
+    pub fn produce(
+        body: ChallengeBody,
+        signer: &ValidatorSigner,
+    ) -> Result<Self, ValidatorSignerError> {
         let hash = CryptoHash::hash_borsh(&body);
-        let signature = signer.sign_bytes(hash.as_ref());
-        Self { body, account_id: signer.validator_id().clone(), signature, hash }
+        let signature = signer.sign_message(&ValidatorMessage::Challenge { body: body.clone() })?;
+        Ok(Self { body, account_id: signer.validator_id().clone(), signature, hash })
     }
 }
 
@@ -15,6 +15,7 @@ use crate::bandwidth_scheduler::{
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub enum ChunkApplyStats {
     V0(ChunkApplyStatsV0),
+    V1(ChunkApplyStatsV1),
 }
 
 /// Information gathered during chunk application.
@@ -34,17 +35,68 @@ pub struct ChunkApplyStatsV0 {
     /// Number of incoming receipts to this chunk
     pub incoming_receipts_num: u64,
 
+    /// Receipt sink stats - forwarded receipts, buffered receipts, outgoing limits
+    pub receipt_sink: ReceiptSinkStats,
+    /// Bandwidth scheduler stats
+    pub bandwidth_scheduler: BandwidthSchedulerStats,
+    /// Balance stats - used in balance checker.
+    pub balance: BalanceStatsV0,
+}
+
+/// Information gathered during chunk application, stored by the nodes which audit the balances of
+/// the chunks. Same as [`ChunkApplyStatsV0`] with the extended [`BalanceStats`] and the
+/// [`BalanceAudit`].
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ChunkApplyStatsV1 {
+    /// Height at which the chunk was applied
+    pub height: BlockHeight,
+    /// Shard ID of the chunk
+    pub shard_id: ShardId,
+    /// Was this chunk applied as a new (non-missing) chunk or a missing one (apply_old_chunk)?
+    pub is_new_chunk: bool,
+    /// Number of new transactions in this chunk
+    pub transactions_num: u64,
+    /// Number of incoming receipts to this chunk
+    pub incoming_receipts_num: u64,
+
     /// Receipt sink stats - forwarded receipts, buffered receipts, outgoing limits
     pub receipt_sink: ReceiptSinkStats,
     /// Bandwidth scheduler stats
     pub bandwidth_scheduler: BandwidthSchedulerStats,
     /// Balance stats - used in balance checker.
     pub balance: BalanceStats,
+    /// Balances of the shard before and after the chunk was applied. Only computed when the
+    /// chunk is applied to update a tracked shard and balance audits are enabled, `None`
+    /// otherwise.
+    pub balance_audit: Option<BalanceAudit>,
 }
 
-impl ChunkApplyStatsV0 {
-    pub fn new(height: BlockHeight, shard_id: ShardId) -> ChunkApplyStatsV0 {
-        ChunkApplyStatsV0 {
+impl ChunkApplyStats {
+    pub fn is_new_chunk(&self) -> bool {
+        match self {
+            ChunkApplyStats::V0(stats) => stats.is_new_chunk,
+            ChunkApplyStats::V1(stats) => stats.is_new_chunk,
+        }
+    }
+
+    pub fn transactions_num(&self) -> u64 {
+        match self {
+            ChunkApplyStats::V0(stats) => stats.transactions_num,
+            ChunkApplyStats::V1(stats) => stats.transactions_num,
+        }
+    }
+
+    pub fn incoming_receipts_num(&self) -> u64 {
+        match self {
+            ChunkApplyStats::V0(stats) => stats.incoming_receipts_num,
+            ChunkApplyStats::V1(stats) => stats.incoming_receipts_num,
+        }
+    }
+}
+
+impl ChunkApplyStatsV1 {
+    pub fn new(height: BlockHeight, shard_id: ShardId) -> ChunkApplyStatsV1 {
+        ChunkApplyStatsV1 {
             height: height,
             shard_id: shard_id,
             is_new_chunk: false,
@@ -53,6 +105,7 @@ impl ChunkApplyStatsV0 {
             bandwidth_scheduler: Default::default(),
             balance: Default::default(),
             receipt_sink: Default::default(),
+            balance_audit: None,
         }
     }
 
@@ -65,8 +118,8 @@ impl ChunkApplyStatsV0 {
     }
 
     /// Dummy data for tests.
-    pub fn dummy() -> ChunkApplyStatsV0 {
-        ChunkApplyStatsV0 {
+    pub fn dummy() -> ChunkApplyStatsV1 {
+        ChunkApplyStatsV1 {
             height: 0,
             shard_id: ShardId::new(0),
             is_new_chunk: false,
@@ -75,6 +128,7 @@ impl ChunkApplyStatsV0 {
             bandwidth_scheduler: Default::default(),
             balance: Default::default(),
             receipt_sink: Default::default(),
+            balance_audit: None,
         }
     }
 }
@@ -197,6 +251,18 @@ impl ReceiptsStats {
     }
 }
 
+/// Stats about token balance stored in [`ChunkApplyStatsV0`].
+#[derive(Debug, Clone, Default, BorshSerialize, BorshDeserialize)]
+pub struct BalanceStatsV0 {
+    pub tx_burnt_amount: Balance,
+    pub slashed_burnt_amount: Balance,
+    pub other_burnt_amount: Balance,
+    /// This is a negative amount. This amount was not charged from the account that issued
+    /// the transaction. It's likely due to the delayed queue of the receipts.
+    pub gas_deficit_amount: Balance,
+    pub global_actions_burnt_amount: Balance,
+}
+
 /// Stats about token balance, used in balance checker.
 #[derive(Debug, Clone, Default, BorshSerialize, BorshDeserialize)]
 pub struct BalanceStats {
@@ -207,6 +273,69 @@ pub struct BalanceStats {
     /// the transaction. It's likely due to the delayed queue of the receipts.
     pub gas_deficit_amount: Balance,
     pub global_actions_burnt_amount: Balance,
+    /// Validator rewards and protocol treasury reward added to the accounts of this shard at
+    /// the first block of an epoch.
+    pub validator_rewards_amount: Balance,
+    /// Deposits and unused gas sent back in refund receipts.
+    pub refund_amount: Balance,
+    /// Part of the burnt gas given to the receivers of function calls.
+    pub contract_reward_amount: Balance,
+}
+
+/// Total balances which entered, left or stayed in a shard while a chunk was applied.
+///
+/// Together with [`BalanceStats`] these allow to check that no tokens were created or destroyed
+/// by the chunk, see [`BalanceAudit::is_balanced`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BalanceAudit {
+    /// Value of the receipts applied in the chunk, the incoming receipts and the receipts
+    /// generated by the transactions of the chunk.
+    pub incoming_receipts_balance: Balance,
+    /// Value of the receipts sent to other shards or to the next chunk of this shard.
+    pub outgoing_receipts_balance: Balance,
+    /// Sum of the amount and the locked amount of all accounts touched by the chunk.
+    pub initial_accounts_balance: Balance,
+    pub final_accounts_balance: Balance,
+    /// Value of the delayed, buffered, postponed and yielded receipts touched by the chunk.
+    pub initial_stored_receipts_balance: Balance,
+    pub final_stored_receipts_balance: Balance,
+    /// Balance locked for the storage of the accounts touched by the chunk.
+    pub initial_storage_staked: Balance,
+    pub final_storage_staked: Balance,
+}
+
+impl BalanceAudit {
+    /// Checks that all tokens which entered the shard either stayed in it or left it:
+    ///
+    /// `validator rewards + incoming receipts + initial accounts + initial stored receipts ==
+    /// final accounts + final stored receipts + outgoing receipts + burnt`.
+    ///
+    /// Gas deficit is already subtracted from the transaction burnt amount. Returns `false` on
+    /// overflow.
+    pub fn is_balanced(&self, stats: &BalanceStats) -> bool {
+        let input = [
+            stats.validator_rewards_amount,
+            self.incoming_receipts_balance,
+            self.initial_accounts_balance,
+            self.initial_stored_receipts_balance,
+        ];
+        let output = [
+            self.final_accounts_balance,
+            self.final_stored_receipts_balance,
+            self.outgoing_receipts_balance,
+            stats.tx_burnt_amount,
+            stats.slashed_burnt_amount,
+            stats.other_burnt_amount,
+            stats.global_actions_burnt_amount,
+        ];
+        let sum = |values: &[Balance]| {
+            values.iter().try_fold(0 as Balance, |sum, value| sum.checked_add(*value))
+        };
+        match (sum(&input), sum(&output)) {
+            (Some(input), Some(output)) => input == output,
+            _ => false,
+        }
+    }
 }
 
 /// Convert a bandwidth request from the bitmap representation to a list of requested values.
@@ -1,7 +1,10 @@
+use crate::block::{Approval, ApprovalInner, compute_bp_hash_from_validator_stakes};
 use crate::epoch_block_info::BlockInfo;
 use crate::epoch_info::EpochInfo;
+use crate::hash::CryptoHash;
 use crate::merkle::PartialMerkleTree;
 use crate::types::validator_stake::ValidatorStake;
+use crate::types::{Balance, BlockHeight};
 use crate::utils::compression::CompressedData;
 use crate::version::BLOCK_HEADER_V3_PROTOCOL_VERSION;
 use crate::{block_header::BlockHeader, merkle::MerklePathItem};
@@ -10,6 +13,7 @@ use bytesize::ByteSize;
 use near_crypto::Signature;
 use near_primitives_core::types::ProtocolVersion;
 use near_schema_checker_lib::ProtocolSchema;
+use std::collections::HashSet;
 use std::fmt::Debug;
 
 /// Versioned enum for EpochSyncProof. Because this structure is sent over the network and also
@@ -180,3 +184,203 @@ pub struct EpochSyncProofCurrentEpochData {
     /// block ordinal).
     pub partial_merkle_tree_for_first_block: PartialMerkleTree,
 }
+
+impl EpochSyncProofV1 {
+    /// Verifies the data of all epochs in the proof, in chronological order, and the data around
+    /// the epoch sync boundary. When verifying each epoch, we assume that the previous epoch has
+    /// been verified (thereby giving correctness of all epochs by induction). For each epoch, we
+    /// need to verify the following:
+    ///
+    /// - Its block producers. To verify this, we compare the previous epoch's last final block's
+    ///   next_bp_hash against the hash of the current epoch's block producers, taking into
+    ///   account the use_versioned_bp_hash_format flag.
+    /// - Its last final block. To verify this, we use the endorsements provided for the final
+    ///   block. What we verify is that more than 2/3 of the block producers of the current epoch
+    ///   have endorsed the final block.
+    ///
+    /// The block producers of the first epoch are not verified here, because they are proven
+    /// against the genesis, which the caller has to check against whatever it trusts.
+    ///
+    /// See the comments in `EpochSyncProofEpochData` for more detailed information.
+    pub fn verify(&self) -> Result<(), String> {
+        let Self { all_epochs, last_epoch, current_epoch } = self;
+        if all_epochs.len() < 2 {
+            return Err("need at least two epochs in all_epochs".to_string());
+        }
+        verify_final_block_endorsement(&all_epochs[0])?;
+        for epoch_index in 1..all_epochs.len() {
+            let epoch = &all_epochs[epoch_index];
+            let prev_epoch = &all_epochs[epoch_index - 1];
+            if !verify_block_producer_handoff(
+                &epoch.block_producers,
+                epoch.use_versioned_bp_hash_format,
+                prev_epoch.last_final_block_header.next_bp_hash(),
+            ) {
+                return Err(format!(
+                    "invalid block producer handoff to epoch index {}",
+                    epoch_index
+                ));
+            }
+            verify_final_block_endorsement(epoch)?;
+        }
+
+        verify_epoch_sync_data_hash(last_epoch, &current_epoch.first_block_header_in_epoch)?;
+
+        verify_current_epoch_data(
+            current_epoch,
+            &all_epochs.last().unwrap().last_final_block_header,
+        )?;
+        Ok(())
+    }
+}
+
+fn verify_current_epoch_data(
+    current_epoch: &EpochSyncProofCurrentEpochData,
+    current_epoch_final_block_header: &BlockHeader,
+) -> Result<(), String> {
+    // Verify first_block_header_in_epoch
+    let first_block_header = &current_epoch.first_block_header_in_epoch;
+    if !crate::merkle::verify_hash(
+        *current_epoch_final_block_header.block_merkle_root(),
+        &current_epoch.merkle_proof_for_first_block,
+        *first_block_header.hash(),
+    ) {
+        return Err("invalid merkle_proof_for_first_block".to_string());
+    }
+
+    // Verify partial_merkle_tree_for_first_block. The size needs to match to ensure that
+    // the partial merkle tree is for the right block ordinal, and the partial tree itself
+    // needs to be valid and have the correct root.
+    //
+    // Note that the block_ordinal in the header is 1-based, so we need to add 1 to the size.
+    if current_epoch.partial_merkle_tree_for_first_block.size() + 1
+        != first_block_header.block_ordinal()
+    {
+        return Err("invalid size in partial_merkle_tree_for_first_block".to_string());
+    }
+
+    if !current_epoch.partial_merkle_tree_for_first_block.is_well_formed()
+        || current_epoch.partial_merkle_tree_for_first_block.root()
+            != *first_block_header.block_merkle_root()
+    {
+        return Err("invalid path in partial_merkle_tree_for_first_block".to_string());
+    }
+
+    // Verify the two headers before the first block.
+    if current_epoch.last_block_header_in_prev_epoch.hash()
+        != current_epoch.first_block_header_in_epoch.prev_hash()
+    {
+        return Err("invalid last_block_header_in_prev_epoch".to_string());
+    }
+    if current_epoch.second_last_block_header_in_prev_epoch.hash()
+        != current_epoch.last_block_header_in_prev_epoch.prev_hash()
+    {
+        return Err("invalid second_last_block_header_in_prev_epoch".to_string());
+    }
+
+    Ok(())
+}
+
+/// Verify epoch_sync_data_hash matches current_epoch_first_block_header's epoch_sync_data_hash.
+fn verify_epoch_sync_data_hash(
+    last_epoch: &EpochSyncProofLastEpochData,
+    current_epoch_first_block_header: &BlockHeader,
+) -> Result<(), String> {
+    let epoch_sync_data_hash = CryptoHash::hash_borsh(&(
+        &last_epoch.first_block_in_epoch,
+        &last_epoch.second_last_block_in_epoch,
+        &last_epoch.last_block_in_epoch,
+        &last_epoch.epoch_info,
+        &last_epoch.next_epoch_info,
+        &last_epoch.next_next_epoch_info,
+    ));
+    let expected_epoch_sync_data_hash = current_epoch_first_block_header
+        .epoch_sync_data_hash()
+        .ok_or_else(|| "missing epoch_sync_data_hash".to_string())?;
+    if epoch_sync_data_hash != expected_epoch_sync_data_hash {
+        return Err("invalid epoch_sync_data_hash".to_string());
+    }
+
+    Ok(())
+}
+
+/// Verifies that the given block producers hash to the `next_bp_hash` of a block of the
+/// previous epoch, returning true if they do.
+pub fn verify_block_producer_handoff(
+    block_producers: &Vec<ValidatorStake>,
+    use_versioned_bp_hash_format: bool,
+    prev_epoch_next_bp_hash: &CryptoHash,
+) -> bool {
+    let bp_hash =
+        compute_bp_hash_from_validator_stakes(block_producers, use_versioned_bp_hash_format);
+    bp_hash == *prev_epoch_next_bp_hash
+}
+
+/// Verifies that the epoch's last_final_block_header is sufficiently endorsed by the current
+/// epoch's block producers.
+fn verify_final_block_endorsement(epoch: &EpochSyncProofEpochData) -> Result<(), String> {
+    verify_block_endorsements(
+        *epoch.last_final_block_header.hash(),
+        epoch.last_final_block_header.height(),
+        &epoch.block_producers,
+        &epoch.this_epoch_endorsements_for_last_final_block,
+    )
+}
+
+/// Verifies that the given block is endorsed properly, and with enough stake.
+pub fn verify_block_endorsements(
+    prev_block_hash: CryptoHash,
+    block_height: BlockHeight,
+    block_producers: &[ValidatorStake],
+    endorsements: &[Option<Box<Signature>>],
+) -> Result<(), String> {
+    if endorsements.len() != block_producers.len() {
+        return Err(format!(
+            "Block {} should be provided with {} endorsements but has {}",
+            block_height,
+            block_producers.len(),
+            endorsements.len()
+        ));
+    }
+
+    let message_to_sign =
+        Approval::get_data_for_sig(&ApprovalInner::Endorsement(prev_block_hash), block_height + 1);
+
+    let mut total_stake: Balance = 0;
+    let mut endorsed_stake: Balance = 0;
+
+    for (validator, may_be_signature) in block_producers.iter().zip(endorsements.iter()) {
+        if let Some(signature) = may_be_signature {
+            if !signature.verify(&message_to_sign, validator.public_key()) {
+                return Err(format!(
+                    "Invalid signature for block {} from validator {:?}",
+                    block_height,
+                    validator.account_id()
+                ));
+            }
+            endorsed_stake += validator.stake();
+        }
+        total_stake += validator.stake();
+    }
+
+    if endorsed_stake <= total_stake * 2 / 3 {
+        return Err(format!("Block {} does not have enough endorsements", block_height));
+    }
+
+    Ok(())
+}
+
+/// Gets the ordered list of block producers and their stake from the EpochInfo.
+pub fn get_epoch_info_block_producers(epoch_info: &EpochInfo) -> Vec<ValidatorStake> {
+    // The block producers settlement can actually have duplicates.
+    // The list of block producers used to compute bp_hash is the deduplicated version
+    // of this list, keeping the order of first appearance.
+    let mut block_producers = Vec::new();
+    let mut seen_validators = HashSet::new();
+    for bp_index in epoch_info.block_producers_settlement() {
+        if seen_validators.insert(*bp_index) {
+            block_producers.push(epoch_info.get_validator(*bp_index));
+        }
+    }
+    block_producers
+}
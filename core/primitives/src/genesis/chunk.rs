@@ -109,7 +109,8 @@ fn genesis_chunk(
         BandwidthRequests::default_for_protocol_version(genesis_protocol_version),
         &EmptyValidatorSigner::default().into(),
         genesis_protocol_version,
-    );
+    )
+    .expect("empty signer cannot fail");
     encoded_chunk
 }
 
@@ -143,7 +144,8 @@ pub fn prod_genesis_chunks(
         CryptoHash::default(),
         vec![],
         &EmptyValidatorSigner::default().into(),
-    );
+    )
+    .expect("empty signer cannot fail");
 
     let mut chunk = ShardChunk::V1(ShardChunkV1 {
         chunk_hash: header.chunk_hash(),
@@ -6,7 +6,9 @@ use crate::receipt::Receipt;
 use crate::transaction::SignedTransaction;
 use crate::types::validator_stake::{ValidatorStake, ValidatorStakeIter, ValidatorStakeV1};
 use crate::types::{Balance, BlockHeight, Gas, MerkleHash, ShardId, StateRoot};
-use crate::validator_signer::{EmptyValidatorSigner, ValidatorSigner};
+use crate::validator_signer::{
+    EmptyValidatorSigner, ValidatorMessage, ValidatorSigner, ValidatorSignerError,
+};
 use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::Signature;
@@ -183,7 +185,7 @@ impl ShardChunkHeaderV2 {
         tx_root: CryptoHash,
         prev_validator_proposals: Vec<ValidatorStakeV1>,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = ShardChunkHeaderInnerV1 {
             prev_block_hash,
             prev_state_root,
@@ -200,8 +202,9 @@ impl ShardChunkHeaderV2 {
             prev_validator_proposals,
         };
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_bytes(hash.as_ref());
-        Self { inner, height_included: 0, signature, hash }
+        let signature =
+            signer.sign_message(&ValidatorMessage::ChunkHeaderV1 { inner: inner.clone() })?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -250,7 +253,7 @@ impl ShardChunkHeaderV3 {
         congestion_info: Option<CongestionInfo>,
         bandwidth_requests: Option<BandwidthRequests>,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = if let Some(bandwidth_requests) = bandwidth_requests {
             // `bandwidth_requests` can only be `Some` when bandwidth scheduler is enabled.
             assert!(ProtocolFeature::BandwidthScheduler.enabled(protocol_version));
@@ -314,10 +317,14 @@ impl ShardChunkHeaderV3 {
         Self::from_inner(inner, signer)
     }
 
-    pub fn from_inner(inner: ShardChunkHeaderInner, signer: &ValidatorSigner) -> Self {
+    pub fn from_inner(
+        inner: ShardChunkHeaderInner,
+        signer: &ValidatorSigner,
+    ) -> Result<Self, ValidatorSignerError> {
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_bytes(hash.as_ref());
-        Self { inner, height_included: 0, signature, hash }
+        let signature =
+            signer.sign_message(&ValidatorMessage::ChunkHeaderV3 { inner: inner.clone() })?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -334,25 +341,28 @@ impl ShardChunkHeader {
             .enabled(PROTOCOL_VERSION)
             .then_some(CongestionInfo::default());
 
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new(
-            PROTOCOL_VERSION,
-            prev_block_hash,
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            height,
-            shard_id,
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            congestion_info,
-            BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
-            &EmptyValidatorSigner::default().into(),
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new(
+                PROTOCOL_VERSION,
+                prev_block_hash,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                height,
+                shard_id,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                congestion_info,
+                BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
+                &EmptyValidatorSigner::default().into(),
+            )
+            .expect("empty signer cannot fail"),
+        )
     }
 
     #[inline]
@@ -684,7 +694,7 @@ impl ShardChunkHeaderV1 {
         tx_root: CryptoHash,
         prev_validator_proposals: Vec<ValidatorStakeV1>,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = ShardChunkHeaderInnerV1 {
             prev_block_hash,
             prev_state_root,
@@ -701,8 +711,9 @@ impl ShardChunkHeaderV1 {
             prev_validator_proposals,
         };
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_bytes(hash.as_ref());
-        Self { inner, height_included: 0, signature, hash }
+        let signature =
+            signer.sign_message(&ValidatorMessage::ChunkHeaderV2 { inner: inner.clone() })?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -1221,7 +1232,7 @@ impl EncodedShardChunk {
         bandwidth_requests: Option<BandwidthRequests>,
         signer: &ValidatorSigner,
         protocol_version: ProtocolVersion,
-    ) -> (Self, Vec<MerklePath>) {
+    ) -> Result<(Self, Vec<MerklePath>), ValidatorSignerError> {
         let (transaction_receipts_parts, encoded_length) = crate::reed_solomon::reed_solomon_encode(
             rs,
             &TransactionReceipt(transactions, prev_outgoing_receipts.to_vec()),
@@ -1247,9 +1258,9 @@ impl EncodedShardChunk {
             congestion_info,
             bandwidth_requests,
             signer,
-        );
+        )?;
         let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V3(header), content };
-        (Self::V2(chunk), merkle_paths)
+        Ok((Self::V2(chunk), merkle_paths))
     }
 
     pub fn chunk_hash(&self) -> ChunkHash {
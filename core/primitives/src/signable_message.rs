@@ -1,8 +1,10 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use near_crypto::{Signature, Signer};
+use near_crypto::{PublicKey, Signature, Signer};
 use near_primitives_core::hash::hash;
 use near_primitives_core::types::AccountId;
 use near_schema_checker_lib::ProtocolSchema;
+use serde_with::base64::Base64;
+use serde_with::serde_as;
 
 // These numbers are picked to be compatible with the current protocol and how
 // transactions are defined in it. Introducing this is no protocol change. This
@@ -22,6 +24,7 @@ const MAX_OFF_CHAIN_DISCRIMINANT: u32 = u32::MAX;
 
 // NEPs currently included in the scheme
 const NEP_366_META_TRANSACTIONS: u32 = 366;
+const NEP_413_SIGN_MESSAGE: u32 = 413;
 
 /// Used to distinguish message types that are sign by account keys, to avoid an
 /// abuse of signed messages as something else.
@@ -68,6 +71,8 @@ pub struct SignableMessage<'a, T> {
 pub enum SignableMessageType {
     /// A delegate action, intended for a relayer to included it in an action list of a transaction.
     DelegateAction,
+    /// An off-chain message signed by an account, e.g. to sign in to an application.
+    SignMessage,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -97,10 +102,55 @@ impl<'a, T: BorshSerialize> SignableMessage<'a, T> {
     }
 
     pub fn sign(&self, signer: &Signer) -> Signature {
-        let bytes = borsh::to_vec(&self).expect("Failed to deserialize");
+        let bytes = borsh::to_vec(&self).expect("Failed to serialize");
         let hash = hash(&bytes);
         signer.sign(hash.as_bytes())
     }
+
+    pub fn verify(&self, signature: &Signature, public_key: &PublicKey) -> bool {
+        let bytes = borsh::to_vec(&self).expect("Failed to serialize");
+        let hash = hash(&bytes);
+        signature.verify(hash.as_bytes(), public_key)
+    }
+}
+
+/// Payload of an off-chain message signed as specified by
+/// [NEP-413](https://github.com/near/NEPs/blob/master/neps/nep-0413.md).
+#[serde_as]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct SignMessagePayload {
+    /// The message chosen by the application which requested the signature.
+    pub message: String,
+    /// Random nonce chosen by the application to prevent replays.
+    #[serde_as(as = "Base64")]
+    pub nonce: [u8; 32],
+    /// The application the message is intended for.
+    pub recipient: String,
+    /// Where the wallet redirects to after signing.  Named `callbackUrl` in
+    /// JSON, as wallets send it.
+    #[serde(default, rename = "callbackUrl")]
+    pub callback_url: Option<String>,
+}
+
+impl SignMessagePayload {
+    pub fn sign(&self, signer: &Signer) -> Signature {
+        SignableMessage::new(self, SignableMessageType::SignMessage).sign(signer)
+    }
+
+    /// Checks that the payload is signed with the given key.  It doesn't check
+    /// whether the key belongs to any account.
+    pub fn verify(&self, signature: &Signature, public_key: &PublicKey) -> bool {
+        SignableMessage::new(self, SignableMessageType::SignMessage).verify(signature, public_key)
+    }
 }
 
 impl MessageDiscriminant {
@@ -202,7 +252,10 @@ impl TryFrom<MessageDiscriminant> for SignableMessageType {
                 _ => Err(Self::Error::UnknownOnChainNep(nep)),
             }
         } else if let Some(nep) = discriminant.off_chain_nep() {
-            Err(Self::Error::UnknownOffChainNep(nep))
+            match nep {
+                NEP_413_SIGN_MESSAGE => Ok(Self::SignMessage),
+                _ => Err(Self::Error::UnknownOffChainNep(nep)),
+            }
         } else {
             Err(Self::Error::UnknownMessageType)
         }
@@ -216,6 +269,9 @@ impl From<SignableMessageType> for MessageDiscriminant {
             SignableMessageType::DelegateAction => {
                 MessageDiscriminant::new_on_chain(NEP_366_META_TRANSACTIONS).unwrap()
             }
+            SignableMessageType::SignMessage => {
+                MessageDiscriminant::new_off_chain(NEP_413_SIGN_MESSAGE).unwrap()
+            }
         }
     }
 }
@@ -276,6 +332,44 @@ mod tests {
         assert!(!signed.verify());
     }
 
+    // happy path for NEP-413 signature
+    #[test]
+    fn nep_413_ok() {
+        let signer = InMemorySigner::test_signer(&"alice.near".parse().unwrap());
+        let payload = SignMessagePayload {
+            message: "Sign in to example.com".to_string(),
+            nonce: [7; 32],
+            recipient: "example.com".to_string(),
+            callback_url: None,
+        };
+        let signature = payload.sign(&signer);
+        assert!(payload.verify(&signature, &signer.public_key()));
+
+        // The discriminant is 2^31 + 413, as required by the standard.
+        let bytes =
+            borsh::to_vec(&SignableMessage::new(&payload, SignableMessageType::SignMessage))
+                .unwrap();
+        assert_eq!(bytes[..4], 2147484061u32.to_le_bytes());
+
+        let other = SignMessagePayload { recipient: "evil.com".to_string(), ..payload.clone() };
+        assert!(!other.verify(&signature, &signer.public_key()));
+        let other_signer = InMemorySigner::test_signer(&"bob.near".parse().unwrap());
+        assert!(!payload.verify(&signature, &other_signer.public_key()));
+    }
+
+    #[test]
+    fn nep_413_payload_json() {
+        let payload: SignMessagePayload = serde_json::from_value(serde_json::json!({
+            "message": "Sign in to example.com",
+            "nonce": "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=",
+            "recipient": "example.com",
+            "callbackUrl": "https://example.com/callback",
+        }))
+        .unwrap();
+        assert_eq!(payload.nonce, [7; 32]);
+        assert_eq!(payload.callback_url.as_deref(), Some("https://example.com/callback"));
+    }
+
     fn delegate_action(
         sender_id: AccountId,
         receiver_id: AccountId,
@@ -1,10 +1,11 @@
-use crate::hash::CryptoHash;
+use crate::hash::{CryptoHash, hash};
 use crate::merkle::MerklePath;
 use crate::sharding::{
     ReceiptProof, ShardChunk, ShardChunkHeader, ShardChunkHeaderV1, ShardChunkV1,
 };
 use crate::types::{BlockHeight, EpochId, ShardId, StateRoot, StateRootNode};
 use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, SecretKey, Signature};
 use near_primitives_core::types::EpochHeight;
 use near_schema_checker_lib::ProtocolSchema;
 use std::sync::Arc;
@@ -304,9 +305,91 @@ pub enum StateSyncDumpProgress {
     },
 }
 
+/// Hash and size of a state part listed in a [`StatePartsManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct StatePartInfo {
+    pub hash: CryptoHash,
+    pub size: u64,
+}
+
+impl StatePartInfo {
+    pub fn new(part: &[u8]) -> Self {
+        Self { hash: hash(part), size: part.len() as u64 }
+    }
+}
+
+/// Hashes and sizes of all state parts of a shard dumped to external storage
+/// for an epoch, signed by the node which dumped them.
+///
+/// A part can only be validated against the state root once it is fully
+/// downloaded and its trie nodes are checked.  The manifest lets the nodes
+/// syncing state reject a corrupted part as soon as it arrives.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct StatePartsManifest {
+    pub epoch_id: EpochId,
+    pub shard_id: ShardId,
+    pub state_root: StateRoot,
+    /// Indexed by part id.
+    pub parts: Vec<StatePartInfo>,
+    /// Node key of the node which dumped the parts.
+    pub signer: PublicKey,
+    /// Signature on the hash of all other fields.
+    pub signature: Signature,
+}
+
+impl StatePartsManifest {
+    fn build_hash(
+        epoch_id: &EpochId,
+        shard_id: &ShardId,
+        state_root: &StateRoot,
+        parts: &Vec<StatePartInfo>,
+        signer: &PublicKey,
+    ) -> CryptoHash {
+        CryptoHash::hash_borsh((epoch_id, shard_id, state_root, parts, signer))
+    }
+
+    pub fn new(
+        epoch_id: EpochId,
+        shard_id: ShardId,
+        state_root: StateRoot,
+        parts: Vec<StatePartInfo>,
+        secret_key: &SecretKey,
+    ) -> Self {
+        let signer = secret_key.public_key();
+        let hash = Self::build_hash(&epoch_id, &shard_id, &state_root, &parts, &signer);
+        let signature = secret_key.sign(hash.as_ref());
+        Self { epoch_id, shard_id, state_root, parts, signer, signature }
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        let hash = Self::build_hash(
+            &self.epoch_id,
+            &self.shard_id,
+            &self.state_root,
+            &self.parts,
+            &self.signer,
+        );
+        self.signature.verify(hash.as_ref(), &self.signer)
+    }
+
+    /// Whether the part has the size and hash listed in the manifest.
+    pub fn part_matches(&self, part_id: u64, part: &[u8]) -> bool {
+        let Some(info) = usize::try_from(part_id).ok().and_then(|idx| self.parts.get(idx)) else {
+            return false;
+        };
+        // Comparing the sizes first avoids hashing parts of the wrong size.
+        info.size == part.len() as u64 && info.hash == hash(part)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::state_sync::{STATE_PART_MEMORY_LIMIT, get_num_state_parts};
+    use crate::hash::CryptoHash;
+    use crate::state_sync::{
+        STATE_PART_MEMORY_LIMIT, StatePartInfo, StatePartsManifest, get_num_state_parts,
+    };
+    use crate::types::{EpochId, ShardId};
+    use near_crypto::{KeyType, SecretKey};
 
     #[test]
     fn test_get_num_state_parts() {
@@ -317,4 +400,29 @@ mod tests {
         assert_eq!(get_num_state_parts(STATE_PART_MEMORY_LIMIT.as_u64() * 100), 100);
         assert_eq!(get_num_state_parts(STATE_PART_MEMORY_LIMIT.as_u64() * 100 + 1), 101);
     }
+
+    #[test]
+    fn test_state_parts_manifest() {
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "dumper");
+        let parts = [b"part0".to_vec(), b"part1".to_vec()];
+        let manifest = StatePartsManifest::new(
+            EpochId::default(),
+            ShardId::new(0),
+            CryptoHash::default(),
+            parts.iter().map(|part| StatePartInfo::new(part)).collect(),
+            &secret_key,
+        );
+        assert!(manifest.verify_signature());
+        assert!(manifest.part_matches(0, &parts[0]));
+        assert!(manifest.part_matches(1, &parts[1]));
+        assert!(!manifest.part_matches(0, &parts[1]));
+        assert!(!manifest.part_matches(2, &parts[1]));
+
+        let mut tampered = manifest.clone();
+        tampered.parts.swap(0, 1);
+        assert!(!tampered.verify_signature());
+        let mut tampered = manifest;
+        tampered.signer = SecretKey::from_seed(KeyType::ED25519, "other").public_key();
+        assert!(!tampered.verify_signature());
+    }
 }
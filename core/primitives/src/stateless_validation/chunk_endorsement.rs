@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use crate::sharding::{ChunkHash, ShardChunkHeader};
 use crate::types::{EpochId, SignatureDifferentiator};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{ValidatorMessage, ValidatorSigner, ValidatorSignerError};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{PublicKey, Signature};
 use near_primitives_core::types::{AccountId, BlockHeight, ShardId};
@@ -20,11 +20,21 @@ pub enum ChunkEndorsement {
 }
 
 impl ChunkEndorsement {
+    /// **Panics** if the signer refuses to sign the endorsement.  Use
+    /// [`Self::try_new`] when signing with a remote signer.
     pub fn new(
         epoch_id: EpochId,
         chunk_header: &ShardChunkHeader,
         signer: &ValidatorSigner,
     ) -> ChunkEndorsement {
+        Self::try_new(epoch_id, chunk_header, signer).expect("failed to sign chunk endorsement")
+    }
+
+    pub fn try_new(
+        epoch_id: EpochId,
+        chunk_header: &ShardChunkHeader,
+        signer: &ValidatorSigner,
+    ) -> Result<ChunkEndorsement, ValidatorSignerError> {
         let inner = ChunkEndorsementInner::new(chunk_header.chunk_hash());
         let metadata = ChunkEndorsementMetadata {
             account_id: signer.validator_id().clone(),
@@ -32,10 +42,15 @@ impl ChunkEndorsement {
             epoch_id,
             height_created: chunk_header.height_created(),
         };
-        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap());
-        let metadata_signature = signer.sign_bytes(&borsh::to_vec(&metadata).unwrap());
+        // Both parts are signed in a single request, so that the remote signer
+        // sees the endorsement as one message.
+        let message =
+            ValidatorMessage::ChunkEndorsement { inner: inner.clone(), metadata: metadata.clone() };
+        let [signature, metadata_signature] =
+            <[Signature; 2]>::try_from(signer.sign_payloads(&message)?)
+                .expect("chunk endorsement has two payloads");
         let endorsement = ChunkEndorsementV2 { inner, signature, metadata, metadata_signature };
-        ChunkEndorsement::V2(endorsement)
+        Ok(ChunkEndorsement::V2(endorsement))
     }
 
     pub fn chunk_production_key(&self) -> ChunkProductionKey {
@@ -120,9 +135,9 @@ impl ChunkEndorsementV2 {
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, ProtocolSchema)]
 pub struct ChunkEndorsementMetadata {
     account_id: AccountId,
-    shard_id: ShardId,
+    pub(crate) shard_id: ShardId,
     epoch_id: EpochId,
-    height_created: BlockHeight,
+    pub(crate) height_created: BlockHeight,
 }
 
 /// This is the part of the chunk endorsement that is actually being signed.
@@ -3,7 +3,7 @@ use std::fmt::{Debug, Formatter};
 use super::ChunkProductionKey;
 use crate::sharding::ShardChunkHeader;
 use crate::types::{EpochId, SignatureDifferentiator};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{ValidatorMessage, ValidatorSigner, ValidatorSignerError};
 use borsh::{BorshDeserialize, BorshSerialize};
 use bytesize::ByteSize;
 use near_crypto::{PublicKey, Signature};
@@ -40,6 +40,8 @@ impl Debug for PartialEncodedStateWitness {
 }
 
 impl PartialEncodedStateWitness {
+    /// **Panics** if the signer refuses to sign the witness part.  Use
+    /// [`Self::try_new_parts`] when signing with a remote signer.
     pub fn new(
         epoch_id: EpochId,
         chunk_header: ShardChunkHeader,
@@ -48,15 +50,43 @@ impl PartialEncodedStateWitness {
         encoded_length: usize,
         signer: &ValidatorSigner,
     ) -> Self {
-        let inner = PartialEncodedStateWitnessInner::new(
-            epoch_id,
-            chunk_header,
-            part_ord,
-            part,
-            encoded_length,
-        );
-        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap());
-        Self { inner, signature }
+        let parts = vec![(part_ord, part)];
+        Self::try_new_parts(epoch_id, &chunk_header, parts, encoded_length, signer)
+            .expect("failed to sign state witness part")
+            .pop()
+            .unwrap()
+    }
+
+    /// Creates all parts of the state witness of given chunk.  The parts are
+    /// signed in a single request, so that the remote signer sees the witness
+    /// as one message.
+    pub fn try_new_parts(
+        epoch_id: EpochId,
+        chunk_header: &ShardChunkHeader,
+        parts: Vec<(usize, Vec<u8>)>,
+        encoded_length: usize,
+        signer: &ValidatorSigner,
+    ) -> Result<Vec<Self>, ValidatorSignerError> {
+        let inners = parts
+            .into_iter()
+            .map(|(part_ord, part)| {
+                PartialEncodedStateWitnessInner::new(
+                    epoch_id,
+                    chunk_header,
+                    part_ord,
+                    part,
+                    encoded_length,
+                )
+            })
+            .collect::<Vec<_>>();
+        let message = ValidatorMessage::StateWitnessParts { parts: inners };
+        let signatures = signer.sign_payloads(&message)?;
+        let ValidatorMessage::StateWitnessParts { parts: inners } = message else { unreachable!() };
+        Ok(inners
+            .into_iter()
+            .zip(signatures)
+            .map(|(inner, signature)| Self { inner, signature })
+            .collect())
     }
 
     pub fn chunk_production_key(&self) -> ChunkProductionKey {
@@ -91,9 +121,9 @@ impl PartialEncodedStateWitness {
 
 #[derive(Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, ProtocolSchema)]
 pub struct PartialEncodedStateWitnessInner {
-    epoch_id: EpochId,
-    shard_id: ShardId,
-    height_created: BlockHeight,
+    pub(crate) epoch_id: EpochId,
+    pub(crate) shard_id: ShardId,
+    pub(crate) height_created: BlockHeight,
     part_ord: usize,
     part: Box<[u8]>,
     encoded_length: usize,
@@ -103,7 +133,7 @@ pub struct PartialEncodedStateWitnessInner {
 impl PartialEncodedStateWitnessInner {
     fn new(
         epoch_id: EpochId,
-        chunk_header: ShardChunkHeader,
+        chunk_header: &ShardChunkHeader,
         part_ord: usize,
         part: Vec<u8>,
         encoded_length: usize,
@@ -20,7 +20,7 @@ use crate::transaction::{
 };
 use crate::types::validator_stake::ValidatorStake;
 use crate::types::{AccountId, Balance, EpochId, EpochInfoProvider, Gas, Nonce};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{ValidatorMessage, ValidatorSigner};
 use crate::views::{ExecutionStatusView, FinalExecutionOutcomeView, FinalExecutionStatus};
 use near_crypto::vrf::Value;
 use near_crypto::{EmptySigner, PublicKey, SecretKey, Signature, Signer};
@@ -461,7 +461,13 @@ impl BlockHeader {
             &self.inner_lite_bytes(),
             &self.inner_rest_bytes(),
         );
-        let signature = signer.sign_bytes(hash.as_ref());
+        let signature = signer
+            .sign_message(&ValidatorMessage::BlockHeader {
+                prev_hash: *self.prev_hash(),
+                inner_lite: self.inner_lite().clone(),
+                inner_rest: self.inner_rest_bytes(),
+            })
+            .expect("failed to sign block header");
         match self {
             BlockHeader::BlockHeaderV1(header) => {
                 let header = Arc::make_mut(header);
@@ -925,6 +931,7 @@ impl TestBlockBuilder {
             None,
             None,
         )
+        .unwrap()
     }
 }
 
@@ -224,6 +224,37 @@ impl ValidatedTransaction {
     pub fn new(
         config: &RuntimeConfig,
         signed_tx: SignedTransaction,
+    ) -> Result<Self, (InvalidTxError, SignedTransaction)> {
+        let Self(signed_tx) = Self::new_without_signature_check(config, signed_tx)?;
+        if signed_tx
+            .signature
+            .verify(signed_tx.get_hash().as_ref(), signed_tx.transaction.public_key())
+        {
+            Ok(Self(signed_tx))
+        } else {
+            Err((InvalidTxError::InvalidSignature, signed_tx))
+        }
+    }
+
+    /// Simulates a transaction with a possibly invalid signature: performs
+    /// the checks of `new` except for the signature and passes the result to
+    /// `simulate`.  The unchecked transaction is only lent to the closure, so
+    /// it can't end up in a transaction pool or a chunk.
+    #[allow(clippy::result_large_err)]
+    pub fn simulate_without_signature_check<R>(
+        config: &RuntimeConfig,
+        signed_tx: SignedTransaction,
+        simulate: impl FnOnce(&Self) -> R,
+    ) -> Result<R, (InvalidTxError, SignedTransaction)> {
+        let validated_tx = Self::new_without_signature_check(config, signed_tx)?;
+        Ok(simulate(&validated_tx))
+    }
+
+    /// Same as `new` but accepts transactions with an invalid signature.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn new_without_signature_check(
+        config: &RuntimeConfig,
+        signed_tx: SignedTransaction,
     ) -> Result<Self, (InvalidTxError, SignedTransaction)> {
         // Don't allow V1 currently. This will be changed when the new protocol version is introduced.
         if matches!(signed_tx.transaction, Transaction::V1(_)) {
@@ -237,15 +268,7 @@ impl ValidatedTransaction {
                 signed_tx,
             ));
         }
-
-        if signed_tx
-            .signature
-            .verify(signed_tx.get_hash().as_ref(), signed_tx.transaction.public_key())
-        {
-            Ok(Self(signed_tx))
-        } else {
-            Err((InvalidTxError::InvalidSignature, signed_tx))
-        }
+        Ok(Self(signed_tx))
     }
 
     /// This method should only be used for test purposes.  This is because
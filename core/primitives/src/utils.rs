@@ -456,13 +456,18 @@ pub fn derive_eth_implicit_account_id(public_key: &Secp256K1PublicKey) -> Accoun
 }
 
 /// Returns the block metadata used to create an optimistic block.
+///
+/// Fails if the signer fails to compute the VRF.
 #[cfg(feature = "clock")]
 pub fn get_block_metadata(
     prev_block_header: &BlockHeader,
     signer: &crate::validator_signer::ValidatorSigner,
     clock: near_time::Clock,
     sandbox_delta_time: Option<near_time::Duration>,
-) -> (u64, near_crypto::vrf::Value, near_crypto::vrf::Proof, CryptoHash) {
+) -> Result<
+    (u64, near_crypto::vrf::Value, near_crypto::vrf::Proof, CryptoHash),
+    crate::validator_signer::ValidatorSignerError,
+> {
     let now = clock.now_utc().unix_timestamp_nanos() as u64;
     #[cfg(feature = "sandbox")]
     let now = now + sandbox_delta_time.unwrap().whole_nanoseconds() as u64;
@@ -475,9 +480,9 @@ pub fn get_block_metadata(
     };
 
     let (vrf_value, vrf_proof) =
-        signer.compute_vrf_with_proof(prev_block_header.random_value().as_ref());
+        signer.compute_vrf_with_proof(prev_block_header.random_value().as_ref())?;
     let random_value = hash(vrf_value.0.as_ref());
-    (time, vrf_value, vrf_proof, random_value)
+    Ok((time, vrf_value, vrf_proof, random_value))
 }
 
 #[cfg(test)]
@@ -6,6 +6,28 @@ use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
 
 use crate::types::AccountId;
 
+mod message;
+mod remote;
+
+pub use message::{SignedMessageKind, SignedMessageSlot, ValidatorMessage};
+pub use remote::{
+    AuthenticatedStream, RemoteSignerRequest, RemoteSignerResponse, RemoteValidatorSigner,
+    SignerAuthKey,
+};
+
+/// Error returned when the validator signer fails to sign a message.
+#[derive(thiserror::Error, Debug)]
+pub enum ValidatorSignerError {
+    #[error("failed to communicate with the remote signer: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("remote signer refused to sign: {0}")]
+    Refused(String),
+    #[error("unexpected response from the remote signer: {0}")]
+    UnexpectedResponse(String),
+    #[error("remote signer does not expose the key")]
+    KeyNotAvailable,
+}
+
 /// Enum for validator signer, that holds validator id and key used for signing data.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ValidatorSigner {
@@ -13,6 +35,9 @@ pub enum ValidatorSigner {
     Empty(EmptyValidatorSigner),
     /// Default validator signer that holds data in memory.
     InMemory(InMemoryValidatorSigner),
+    /// Validator signer that sends signing requests to an external signer
+    /// process holding the key.
+    Remote(RemoteValidatorSigner),
 }
 
 /// Validator signer that is used to sign blocks and approvals.
@@ -22,6 +47,7 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(signer) => signer.validator_id(),
             ValidatorSigner::InMemory(signer) => signer.validator_id(),
+            ValidatorSigner::Remote(signer) => signer.validator_id(),
         }
     }
 
@@ -30,23 +56,67 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(signer) => signer.public_key(),
             ValidatorSigner::InMemory(signer) => signer.public_key(),
+            ValidatorSigner::Remote(signer) => signer.public_key(),
         }
     }
 
-    pub fn sign_bytes(&self, data: &[u8]) -> Signature {
+    /// Signs given message and returns the signature of every payload of the
+    /// message, see [`ValidatorMessage::payloads`].
+    ///
+    /// The remote signer refuses to sign the message if it conflicts with a
+    /// message it has signed before, i.e. if it is for a lower height or if it
+    /// is a different message for the same height.  Local signers never fail.
+    pub fn sign_payloads(
+        &self,
+        message: &ValidatorMessage,
+    ) -> Result<Vec<Signature>, ValidatorSignerError> {
         match self {
-            ValidatorSigner::Empty(signer) => signer.noop_signature(),
-            ValidatorSigner::InMemory(signer) => signer.sign_bytes(data),
+            ValidatorSigner::Empty(signer) => Ok(message
+                .payloads(signer.validator_id())
+                .iter()
+                .map(|_| signer.noop_signature())
+                .collect()),
+            ValidatorSigner::InMemory(signer) => Ok(message
+                .payloads(signer.validator_id())
+                .iter()
+                .map(|payload| signer.sign_bytes(payload))
+                .collect()),
+            ValidatorSigner::Remote(signer) => {
+                let signatures = signer.sign(message)?;
+                let expected = message.payloads(signer.validator_id()).len();
+                if signatures.len() != expected {
+                    return Err(ValidatorSignerError::UnexpectedResponse(format!(
+                        "{} signatures instead of {expected}",
+                        signatures.len()
+                    )));
+                }
+                Ok(signatures)
+            }
+        }
+    }
+
+    /// Signs a message with a single payload.
+    pub fn sign_message(
+        &self,
+        message: &ValidatorMessage,
+    ) -> Result<Signature, ValidatorSignerError> {
+        let mut signatures = self.sign_payloads(message)?;
+        match (signatures.pop(), signatures.is_empty()) {
+            (Some(signature), true) => Ok(signature),
+            _ => Err(ValidatorSignerError::UnexpectedResponse(format!(
+                "{message:?} does not have exactly one payload"
+            ))),
         }
     }
 
     pub fn compute_vrf_with_proof(
         &self,
         data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
         match self {
             ValidatorSigner::Empty(_) => unimplemented!(),
-            ValidatorSigner::InMemory(signer) => signer.compute_vrf_with_proof(data),
+            ValidatorSigner::InMemory(signer) => Ok(signer.compute_vrf_with_proof(data)),
+            ValidatorSigner::Remote(signer) => signer.compute_vrf_with_proof(data),
         }
     }
 
@@ -55,6 +125,10 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(_) => unimplemented!(),
             ValidatorSigner::InMemory(signer) => signer.write_to_file(path),
+            ValidatorSigner::Remote(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                ValidatorSignerError::KeyNotAvailable,
+            )),
         }
     }
 }
@@ -0,0 +1,288 @@
+//! Messages signed with the validator key.
+//!
+//! Instead of signing opaque bytes, the validator signer is given the message
+//! itself and derives the signed bytes from it.  This lets the remote signer
+//! learn the height and shard of every message subject to double-sign
+//! protection from the message rather than trusting the node.
+
+use std::fmt::Debug;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::block_header::{Approval, ApprovalInner, BlockHeader, BlockHeaderInnerLite};
+use crate::challenge::ChallengeBody;
+use crate::hash::{CryptoHash, hash};
+use crate::network::{AnnounceAccount, PeerId};
+use crate::optimistic_block::OptimisticBlockInner;
+use crate::sharding::{
+    ShardChunkHeaderInner, ShardChunkHeaderInnerV1, ShardChunkHeaderV1, ShardChunkHeaderV2,
+    ShardChunkHeaderV3,
+};
+use crate::stateless_validation::chunk_endorsement::{
+    ChunkEndorsementInner, ChunkEndorsementMetadata,
+};
+use crate::stateless_validation::contract_distribution::{
+    ChunkContractAccessesInner, ContractCodeRequestInner, PartialEncodedContractDeploysInner,
+};
+use crate::stateless_validation::partial_witness::PartialEncodedStateWitnessInner;
+use crate::types::{AccountId, BlockHeight, EpochId, ShardId};
+
+/// Kind of a message for which the remote signer enforces double-sign
+/// protection.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+pub enum SignedMessageKind {
+    Block,
+    Approval,
+    ChunkHeader,
+    OptimisticBlock,
+    ChunkEndorsement,
+    StateWitness,
+}
+
+/// Height and shard of a message subject to double-sign protection.  At most
+/// one message may be signed for every slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedMessageSlot {
+    pub kind: SignedMessageKind,
+    pub height: BlockHeight,
+    pub shard_id: Option<ShardId>,
+}
+
+/// Message signed with the validator key.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub enum ValidatorMessage {
+    BlockHeader {
+        prev_hash: CryptoHash,
+        inner_lite: BlockHeaderInnerLite,
+        /// Borsh-encoded versioned inner rest of the header.
+        inner_rest: Vec<u8>,
+    },
+    Approval {
+        inner: ApprovalInner,
+        target_height: BlockHeight,
+    },
+    ChunkHeaderV1 {
+        inner: ShardChunkHeaderInnerV1,
+    },
+    ChunkHeaderV2 {
+        inner: ShardChunkHeaderInnerV1,
+    },
+    ChunkHeaderV3 {
+        inner: ShardChunkHeaderInner,
+    },
+    OptimisticBlock {
+        inner: OptimisticBlockInner,
+    },
+    /// Both parts of a chunk endorsement, which are signed separately but
+    /// together form a single endorsement.
+    ChunkEndorsement {
+        inner: ChunkEndorsementInner,
+        metadata: ChunkEndorsementMetadata,
+    },
+    /// All parts of the state witness of a chunk.
+    StateWitnessParts {
+        parts: Vec<PartialEncodedStateWitnessInner>,
+    },
+    AnnounceAccount {
+        peer_id: PeerId,
+        epoch_id: EpochId,
+    },
+    Challenge {
+        body: ChallengeBody,
+    },
+    ChunkContractAccesses {
+        inner: ChunkContractAccessesInner,
+    },
+    ContractCodeRequest {
+        inner: ContractCodeRequestInner,
+    },
+    PartialEncodedContractDeploys {
+        inner: PartialEncodedContractDeploysInner,
+    },
+    /// Protobuf-encoded account data or account ownership proof used by the
+    /// TIER1 network.
+    AccountKeyPayload {
+        payload: Vec<u8>,
+    },
+    /// JSON-encoded telemetry report.
+    Telemetry {
+        content: String,
+    },
+}
+
+impl Debug for ValidatorMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ValidatorMessage::BlockHeader { .. } => "BlockHeader",
+            ValidatorMessage::Approval { .. } => "Approval",
+            ValidatorMessage::ChunkHeaderV1 { .. } => "ChunkHeaderV1",
+            ValidatorMessage::ChunkHeaderV2 { .. } => "ChunkHeaderV2",
+            ValidatorMessage::ChunkHeaderV3 { .. } => "ChunkHeaderV3",
+            ValidatorMessage::OptimisticBlock { .. } => "OptimisticBlock",
+            ValidatorMessage::ChunkEndorsement { .. } => "ChunkEndorsement",
+            ValidatorMessage::StateWitnessParts { .. } => "StateWitnessParts",
+            ValidatorMessage::AnnounceAccount { .. } => "AnnounceAccount",
+            ValidatorMessage::Challenge { .. } => "Challenge",
+            ValidatorMessage::ChunkContractAccesses { .. } => "ChunkContractAccesses",
+            ValidatorMessage::ContractCodeRequest { .. } => "ContractCodeRequest",
+            ValidatorMessage::PartialEncodedContractDeploys { .. } => {
+                "PartialEncodedContractDeploys"
+            }
+            ValidatorMessage::AccountKeyPayload { .. } => "AccountKeyPayload",
+            ValidatorMessage::Telemetry { .. } => "Telemetry",
+        };
+        f.debug_struct(name).field("slot", &self.slot()).finish()
+    }
+}
+
+impl ValidatorMessage {
+    /// Returns the slot of the message if it is subject to double-sign
+    /// protection.
+    ///
+    /// Returns `None` for messages which cannot be used for a double sign,
+    /// including a state witness without parts, which has nothing to sign.
+    pub fn slot(&self) -> Option<SignedMessageSlot> {
+        let (kind, height, shard_id) = match self {
+            ValidatorMessage::BlockHeader { inner_lite, .. } => {
+                (SignedMessageKind::Block, inner_lite.height, None)
+            }
+            ValidatorMessage::Approval { target_height, .. } => {
+                (SignedMessageKind::Approval, *target_height, None)
+            }
+            ValidatorMessage::ChunkHeaderV1 { inner }
+            | ValidatorMessage::ChunkHeaderV2 { inner } => {
+                (SignedMessageKind::ChunkHeader, inner.height_created, Some(inner.shard_id))
+            }
+            ValidatorMessage::ChunkHeaderV3 { inner } => {
+                (SignedMessageKind::ChunkHeader, inner.height_created(), Some(inner.shard_id()))
+            }
+            ValidatorMessage::OptimisticBlock { inner } => {
+                (SignedMessageKind::OptimisticBlock, inner.block_height, None)
+            }
+            ValidatorMessage::ChunkEndorsement { metadata, .. } => (
+                SignedMessageKind::ChunkEndorsement,
+                metadata.height_created,
+                Some(metadata.shard_id),
+            ),
+            ValidatorMessage::StateWitnessParts { parts } => {
+                let part = parts.first()?;
+                (SignedMessageKind::StateWitness, part.height_created, Some(part.shard_id))
+            }
+            ValidatorMessage::AnnounceAccount { .. }
+            | ValidatorMessage::Challenge { .. }
+            | ValidatorMessage::ChunkContractAccesses { .. }
+            | ValidatorMessage::ContractCodeRequest { .. }
+            | ValidatorMessage::PartialEncodedContractDeploys { .. }
+            | ValidatorMessage::AccountKeyPayload { .. }
+            | ValidatorMessage::Telemetry { .. } => return None,
+        };
+        Some(SignedMessageSlot { kind, height, shard_id })
+    }
+
+    /// Checks that the message is well-formed, i.e. that all parts of a state
+    /// witness belong to the same chunk and that opaque payloads cannot be
+    /// mistaken for a message subject to double-sign protection.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ValidatorMessage::StateWitnessParts { parts } => {
+                let Some(first) = parts.first() else {
+                    return Err("state witness without parts".to_string());
+                };
+                let same_chunk = |part: &PartialEncodedStateWitnessInner| {
+                    part.epoch_id == first.epoch_id
+                        && part.shard_id == first.shard_id
+                        && part.height_created == first.height_created
+                };
+                if !parts.iter().all(same_chunk) {
+                    return Err("state witness parts of different chunks".to_string());
+                }
+                Ok(())
+            }
+            ValidatorMessage::AccountKeyPayload { payload } => {
+                if is_guarded_payload(payload) {
+                    return Err("account key payload looks like a consensus message".to_string());
+                }
+                Ok(())
+            }
+            ValidatorMessage::Telemetry { content } => {
+                if is_guarded_payload(content.as_bytes()) {
+                    return Err("telemetry looks like a consensus message".to_string());
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the bytes to sign, one entry per signature.  Most messages have
+    /// a single signature, chunk endorsements have two and state witnesses one
+    /// per part.
+    pub fn payloads(&self, account_id: &AccountId) -> Vec<Vec<u8>> {
+        let hash_payload = |hash: CryptoHash| vec![hash.as_ref().to_vec()];
+        match self {
+            ValidatorMessage::BlockHeader { prev_hash, inner_lite, inner_rest } => {
+                let inner_lite = borsh::to_vec(inner_lite).expect("Failed to serialize");
+                hash_payload(BlockHeader::compute_hash(*prev_hash, &inner_lite, inner_rest))
+            }
+            ValidatorMessage::Approval { inner, target_height } => {
+                vec![Approval::get_data_for_sig(inner, *target_height)]
+            }
+            ValidatorMessage::ChunkHeaderV1 { inner } => {
+                hash_payload(ShardChunkHeaderV1::compute_hash(inner).0)
+            }
+            ValidatorMessage::ChunkHeaderV2 { inner } => {
+                hash_payload(ShardChunkHeaderV2::compute_hash(inner).0)
+            }
+            ValidatorMessage::ChunkHeaderV3 { inner } => {
+                hash_payload(ShardChunkHeaderV3::compute_hash(inner).0)
+            }
+            ValidatorMessage::OptimisticBlock { inner } => {
+                hash_payload(hash(&borsh::to_vec(inner).expect("Failed to serialize")))
+            }
+            ValidatorMessage::ChunkEndorsement { inner, metadata } => {
+                vec![borsh::to_vec(inner).unwrap(), borsh::to_vec(metadata).unwrap()]
+            }
+            ValidatorMessage::StateWitnessParts { parts } => {
+                parts.iter().map(|part| borsh::to_vec(part).unwrap()).collect()
+            }
+            ValidatorMessage::AnnounceAccount { peer_id, epoch_id } => {
+                hash_payload(AnnounceAccount::build_header_hash(account_id, peer_id, epoch_id))
+            }
+            ValidatorMessage::Challenge { body } => hash_payload(CryptoHash::hash_borsh(body)),
+            ValidatorMessage::ChunkContractAccesses { inner } => {
+                vec![borsh::to_vec(inner).unwrap()]
+            }
+            ValidatorMessage::ContractCodeRequest { inner } => vec![borsh::to_vec(inner).unwrap()],
+            ValidatorMessage::PartialEncodedContractDeploys { inner } => {
+                vec![borsh::to_vec(inner).unwrap()]
+            }
+            ValidatorMessage::AccountKeyPayload { payload } => vec![payload.clone()],
+            ValidatorMessage::Telemetry { content } => vec![content.as_bytes().to_vec()],
+        }
+    }
+}
+
+/// Whether a signature over given bytes would also be a valid signature of a
+/// message subject to double-sign protection.  Blocks, chunk headers and
+/// optimistic blocks are signed by their hash, the other guarded messages by
+/// their borsh encoding.
+fn is_guarded_payload(payload: &[u8]) -> bool {
+    payload.len() == CryptoHash::LENGTH
+        || <(ApprovalInner, BlockHeight)>::try_from_slice(payload).is_ok()
+        || ChunkEndorsementInner::try_from_slice(payload).is_ok()
+        || ChunkEndorsementMetadata::try_from_slice(payload).is_ok()
+        || PartialEncodedStateWitnessInner::try_from_slice(payload).is_ok()
+}
@@ -0,0 +1,407 @@
+//! Client side of the remote validator signer protocol.
+//!
+//! The validator node and the signer exchange borsh-encoded
+//! [`RemoteSignerRequest`] and [`RemoteSignerResponse`] messages over a Unix
+//! or TCP socket.  Each message is prefixed with its length encoded as
+//! a little-endian `u32` and followed by its authentication code.  Every
+//! request is followed by exactly one response.
+//!
+//! Both sides share a secret key.  When a connection is established, each side
+//! sends a random nonce and every message is then authenticated with
+//! HMAC-SHA256 of the shared key over the sender's role, both nonces, the
+//! sequence number of the message and the message itself.  Since each side
+//! picks its own nonce, neither can be made to accept messages recorded on
+//! an earlier connection, so messages cannot be forged, replayed or reordered
+//! without the key.
+
+use std::fmt::Debug;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use hmac::{Hmac, Mac};
+use near_crypto::{PublicKey, Signature};
+use sha2::Sha256;
+
+use super::{ValidatorMessage, ValidatorSignerError};
+use crate::types::AccountId;
+
+/// Maximum size of a single protocol message.  The largest messages are state
+/// witnesses.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Minimum length of the shared secret.
+const MIN_AUTH_KEY_LENGTH: usize = 32;
+
+/// Length of the nonce each side sends when a connection is established.
+const NONCE_LENGTH: usize = 32;
+
+/// Length of the authentication code of a message.
+const MAC_LENGTH: usize = 32;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum RemoteSignerRequest {
+    /// Returns the account id and the public key of the validator.
+    GetPublicKey,
+    /// Signs given message.  Messages subject to double-sign protection are
+    /// refused if they conflict with a message signed before.
+    Sign(ValidatorMessage),
+    /// Computes VRF value and proof for given data.
+    ComputeVrf { data: Vec<u8> },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum RemoteSignerResponse {
+    PublicKey {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    /// Signatures of the payloads of the message, in the order of
+    /// [`ValidatorMessage::payloads`].
+    Signatures(Vec<Signature>),
+    Vrf {
+        value: near_crypto::vrf::Value,
+        proof: near_crypto::vrf::Proof,
+    },
+    /// The signer refused to handle the request, e.g. because signing the
+    /// message would result in a double sign.
+    Refused {
+        reason: String,
+    },
+}
+
+/// Secret shared by the validator node and the remote signer, used to
+/// authenticate the messages they exchange.
+#[derive(Clone)]
+pub struct SignerAuthKey(Vec<u8>);
+
+impl SignerAuthKey {
+    /// Creates the key from a secret of at least 32 bytes.
+    pub fn new(secret: &[u8]) -> io::Result<Self> {
+        if secret.len() < MIN_AUTH_KEY_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("shared key must be at least {MIN_AUTH_KEY_LENGTH} bytes long"),
+            ));
+        }
+        Ok(Self(secret.to_vec()))
+    }
+
+    /// Reads the secret from given file, ignoring surrounding whitespace.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Self::new(std::fs::read(path)?.trim_ascii())
+    }
+
+    /// Returns HMAC-SHA256 of the key over the concatenation of given parts.
+    fn mac(&self, parts: &[&[u8]]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts any key length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac
+    }
+}
+
+impl Debug for SignerAuthKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SignerAuthKey(..)")
+    }
+}
+
+/// Side of an [`AuthenticatedStream`].  It is part of every authentication
+/// code so that a message cannot be reflected back to its sender.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Node = 0,
+    Signer = 1,
+}
+
+/// Connection between the validator node and the remote signer over which
+/// every message is authenticated with the shared key.
+pub struct AuthenticatedStream<S> {
+    stream: S,
+    key: SignerAuthKey,
+    role: Role,
+    /// Nonces sent by the node and by the signer, in this order.
+    nonces: [[u8; NONCE_LENGTH]; 2],
+    sent: u64,
+    received: u64,
+}
+
+impl<S: Read + Write> AuthenticatedStream<S> {
+    /// Sets up the signer side of a new connection by exchanging given random
+    /// nonce for the one of the node.
+    pub fn accept(stream: S, key: SignerAuthKey, nonce: [u8; NONCE_LENGTH]) -> io::Result<Self> {
+        Self::handshake(stream, key, Role::Signer, nonce)
+    }
+
+    /// Sets up the node side of a new connection by exchanging given random
+    /// nonce for the one of the signer.
+    pub fn connect(stream: S, key: SignerAuthKey, nonce: [u8; NONCE_LENGTH]) -> io::Result<Self> {
+        Self::handshake(stream, key, Role::Node, nonce)
+    }
+
+    fn handshake(
+        mut stream: S,
+        key: SignerAuthKey,
+        role: Role,
+        nonce: [u8; NONCE_LENGTH],
+    ) -> io::Result<Self> {
+        stream.write_all(&nonce)?;
+        stream.flush()?;
+        let mut peer_nonce = [0u8; NONCE_LENGTH];
+        stream.read_exact(&mut peer_nonce)?;
+        let nonces = match role {
+            Role::Node => [nonce, peer_nonce],
+            Role::Signer => [peer_nonce, nonce],
+        };
+        Ok(Self { stream, key, role, nonces, sent: 0, received: 0 })
+    }
+
+    fn mac(&self, role: Role, seq: u64, bytes: &[u8]) -> Hmac<Sha256> {
+        let [node_nonce, signer_nonce] = &self.nonces;
+        self.key.mac(&[&[role as u8], node_nonce, signer_nonce, &seq.to_le_bytes(), bytes])
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Writes a length-prefixed borsh-encoded message followed by its
+    /// authentication code.
+    pub fn send(&mut self, message: &impl BorshSerialize) -> io::Result<()> {
+        let bytes = borsh::to_vec(message)?;
+        let len =
+            u32::try_from(bytes.len())
+                .ok()
+                .filter(|&len| len as usize <= MAX_MESSAGE_SIZE)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+        let mac = self.mac(self.role, self.sent, &bytes).finalize().into_bytes();
+        self.sent += 1;
+        self.stream.write_all(&len.to_le_bytes())?;
+        self.stream.write_all(&bytes)?;
+        self.stream.write_all(&mac)?;
+        self.stream.flush()
+    }
+
+    /// Reads a message written by [`Self::send`] on the other side and checks
+    /// its authentication code.
+    pub fn receive<T: BorshDeserialize>(&mut self) -> io::Result<T> {
+        let mut len = [0u8; 4];
+        self.stream.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "message too large"));
+        }
+        let mut bytes = vec![0u8; len];
+        self.stream.read_exact(&mut bytes)?;
+        let mut mac = [0u8; MAC_LENGTH];
+        self.stream.read_exact(&mut mac)?;
+        let peer = match self.role {
+            Role::Node => Role::Signer,
+            Role::Signer => Role::Node,
+        };
+        // The comparison is done in constant time to not leak the expected code.
+        self.mac(peer, self.received, &bytes)
+            .verify_slice(&mac)
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "invalid message code"))?;
+        self.received += 1;
+        T::try_from_slice(&bytes)
+    }
+}
+
+/// Connection to the remote signer.
+enum SignerStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl SignerStream {
+    /// Connects to the signer listening on given address.  The address is
+    /// either `unix:<path>` for a Unix socket or `[tcp:]<host>:<port>`.
+    fn connect(
+        address: &str,
+        timeout: Duration,
+        key: &SignerAuthKey,
+    ) -> io::Result<AuthenticatedStream<Self>> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        getrandom::getrandom(&mut nonce).map_err(io::Error::other)?;
+        #[cfg(unix)]
+        if let Some(path) = address.strip_prefix("unix:") {
+            let stream = UnixStream::connect(path)?;
+            stream.set_read_timeout(Some(timeout))?;
+            stream.set_write_timeout(Some(timeout))?;
+            return AuthenticatedStream::connect(SignerStream::Unix(stream), key.clone(), nonce);
+        }
+        let address = address.strip_prefix("tcp:").unwrap_or(address);
+        let socket_addr = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("cannot resolve {address}"))
+        })?;
+        let stream = TcpStream::connect_timeout(&socket_addr, timeout)?;
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        AuthenticatedStream::connect(SignerStream::Tcp(stream), key.clone(), nonce)
+    }
+}
+
+impl AuthenticatedStream<SignerStream> {
+    fn exchange(&mut self, request: &RemoteSignerRequest) -> io::Result<RemoteSignerResponse> {
+        self.send(request)?;
+        self.receive()
+    }
+}
+
+impl Read for SignerStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            SignerStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for SignerStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            SignerStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            SignerStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Signer that keeps the secret key in an external signer process and sends
+/// it signing requests over a socket.
+#[derive(Clone)]
+pub struct RemoteValidatorSigner {
+    address: String,
+    timeout: Duration,
+    key: SignerAuthKey,
+    account_id: AccountId,
+    public_key: PublicKey,
+    /// Connection to the signer.  It is re-established on the next request
+    /// after an I/O error.
+    connection: Arc<Mutex<Option<AuthenticatedStream<SignerStream>>>>,
+}
+
+impl Debug for RemoteValidatorSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteValidatorSigner")
+            .field("address", &self.address)
+            .field("account_id", &self.account_id)
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl PartialEq for RemoteValidatorSigner {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+            && self.account_id == other.account_id
+            && self.public_key == other.public_key
+    }
+}
+
+impl RemoteValidatorSigner {
+    /// Connects to the signer listening on given address and fetches the
+    /// account id and the public key of the validator.
+    pub fn connect(
+        address: &str,
+        timeout: Duration,
+        key: SignerAuthKey,
+    ) -> Result<super::ValidatorSigner, ValidatorSignerError> {
+        let mut stream = SignerStream::connect(address, timeout, &key)?;
+        let (account_id, public_key) = match stream.exchange(&RemoteSignerRequest::GetPublicKey)? {
+            RemoteSignerResponse::PublicKey { account_id, public_key } => (account_id, public_key),
+            response => return Err(Self::unexpected(response)),
+        };
+        Ok(super::ValidatorSigner::Remote(Self {
+            address: address.to_string(),
+            timeout,
+            key,
+            account_id,
+            public_key,
+            connection: Arc::new(Mutex::new(Some(stream))),
+        }))
+    }
+
+    pub fn validator_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    pub(super) fn sign(
+        &self,
+        message: &ValidatorMessage,
+    ) -> Result<Vec<Signature>, ValidatorSignerError> {
+        match self.call(&RemoteSignerRequest::Sign(message.clone()))? {
+            RemoteSignerResponse::Signatures(signatures) => Ok(signatures),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    pub(super) fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
+        match self.call(&RemoteSignerRequest::ComputeVrf { data: data.to_vec() })? {
+            RemoteSignerResponse::Vrf { value, proof } => Ok((value, proof)),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    /// Sends the request to the signer, reconnecting once if the existing
+    /// connection turns out to be broken.
+    ///
+    /// Retrying a request is safe since the signer accepts signing the very
+    /// same message again.
+    fn call(
+        &self,
+        request: &RemoteSignerRequest,
+    ) -> Result<RemoteSignerResponse, ValidatorSignerError> {
+        let mut connection = self.connection.lock().unwrap();
+        let result = match connection.as_mut() {
+            Some(stream) => stream.exchange(request),
+            None => Err(io::ErrorKind::NotConnected.into()),
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(err) => {
+                tracing::debug!(target: "remote_signer", ?err, address = %self.address, "reconnecting to remote signer");
+                *connection = None;
+                let mut stream = SignerStream::connect(&self.address, self.timeout, &self.key)?;
+                let response = stream.exchange(request)?;
+                *connection = Some(stream);
+                response
+            }
+        };
+        match response {
+            RemoteSignerResponse::Refused { reason } => Err(ValidatorSignerError::Refused(reason)),
+            response => Ok(response),
+        }
+    }
+
+    fn unexpected(response: RemoteSignerResponse) -> ValidatorSignerError {
+        ValidatorSignerError::UnexpectedResponse(format!("{response:?}"))
+    }
+}
@@ -13,6 +13,7 @@ use crate::bandwidth_scheduler::BandwidthRequests;
 use crate::block::{Block, BlockHeader, Tip};
 use crate::block_header::BlockHeaderInnerLite;
 use crate::challenge::{Challenge, ChallengesResult};
+use crate::chunk_apply_stats::ChunkApplyStatsV1;
 use crate::congestion_info::{CongestionInfo, CongestionInfoV1};
 use crate::errors::TxExecutionError;
 use crate::hash::{CryptoHash, hash};
@@ -67,13 +68,17 @@ pub struct AccountView {
     pub amount: Balance,
     #[serde(with = "dec_format")]
     pub locked: Balance,
+    /// Hash of the locally deployed contract code or the default hash if the
+    /// account has no local contract.
     pub code_hash: CryptoHash,
     pub storage_usage: StorageUsage,
     /// TODO(2271): deprecated.
     #[serde(default)]
     pub storage_paid_at: BlockHeight,
+    /// Set if the account uses a global contract identified by its code hash.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub global_contract_hash: Option<CryptoHash>,
+    /// Set if the account uses the global contract deployed by this account.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub global_contract_account_id: Option<AccountId>,
 }
@@ -228,6 +233,46 @@ pub struct ViewStateResult {
 pub struct CallResult {
     pub result: Vec<u8>,
     pub logs: Vec<String>,
+    /// Host functions called by the contract, set only if the call was
+    /// requested with `trace`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_calls: Option<Vec<HostFunctionCallView>>,
+}
+
+/// Host function called by a contract, recorded when tracing of the contract
+/// execution is requested.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct HostFunctionCallView {
+    pub name: String,
+    /// Names and values of the arguments.
+    pub args: Vec<(String, u64)>,
+    /// Value returned by the host function, if it returns one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<u64>,
+    /// Error which aborted the execution of the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub burnt_gas_before: Gas,
+    pub burnt_gas_after: Gas,
+    /// Contents of the registers passed to the host function, read after the
+    /// call.
+    pub registers: Vec<RegisterView>,
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RegisterView {
+    pub register_id: u64,
+    #[serde(rename = "data_base64")]
+    #[serde_as(as = "Base64")]
+    pub data: Vec<u8>,
+}
+
+/// Host functions called while executing a receipt.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ReceiptHostFunctionCallsView {
+    pub receipt_id: CryptoHash,
+    pub host_calls: Vec<HostFunctionCallView>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -322,6 +367,18 @@ pub enum QueryRequest {
         method_name: String,
         #[serde(rename = "args_base64")]
         args: FunctionArgs,
+        /// Record the host functions called by the contract in
+        /// `CallResult::host_calls`.
+        #[serde(default, skip_serializing_if = "is_false")]
+        trace: bool,
+    },
+    /// Global contract code deployed with the given code hash.
+    ViewGlobalContractCode {
+        code_hash: CryptoHash,
+    },
+    /// Global contract code currently deployed by the given account.
+    ViewGlobalContractCodeByAccountId {
+        account_id: AccountId,
     },
 }
 
@@ -334,6 +391,21 @@ pub struct QueryResponse {
     pub kind: QueryResponseKind,
     pub block_height: BlockHeight,
     pub block_hash: CryptoHash,
+    /// Store which served the data.  Only set on archival nodes with split
+    /// storage.
+    pub served_from: Option<StorageSourceView>,
+}
+
+/// Store of a split storage archival node which served the data of a view
+/// request.
+///
+/// Data of the blocks which were already garbage collected from the hot store
+/// is only available in the cold store.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageSourceView {
+    Hot,
+    Cold,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -1818,6 +1890,23 @@ pub struct FinalExecutionOutcomeWithReceiptView {
     pub receipts: Vec<ReceiptView>,
 }
 
+/// Execution outcome of a transaction simulated on top of the state of a block
+/// together with all the subsequent receipts and the state changes they made.
+/// Outcomes of a simulated transaction have no proofs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct SimulatedTransactionView {
+    /// Hash of the block on top of whose state the transaction was executed.
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    #[serde(flatten)]
+    pub outcome: FinalExecutionOutcomeWithReceiptView,
+    pub state_changes: StateChangesView,
+    /// Host functions called by the receipts, only set if tracing was
+    /// requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_calls: Vec<ReceiptHostFunctionCallsView>,
+}
+
 pub mod validator_stake_view {
     pub use super::ValidatorStakeViewV1;
     use crate::types::validator_stake::ValidatorStake;
@@ -2250,7 +2339,7 @@ pub struct GasPriceView {
 ///
 /// [serializable view]: ./index.html
 /// [`StateChangesRequest`]: ../types/struct.StateChangesRequest.html
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequestView {
     AccountChanges {
@@ -2473,6 +2562,56 @@ pub struct SplitStorageInfoView {
     pub hot_db_kind: Option<String>,
 }
 
+/// Transactions waiting in the transaction pools of the node.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MempoolView {
+    pub shards: Vec<ShardMempoolView>,
+    /// Hash of the last listed transaction if there are more transactions left to list.
+    pub next_cursor: Option<CryptoHash>,
+}
+
+/// Transactions waiting in the transaction pool of a single shard.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShardMempoolView {
+    pub shard_id: ShardId,
+    /// Number of all transactions in the pool, including the filtered out ones.
+    pub total_count: u64,
+    /// Total size in bytes of all transactions in the pool, including the filtered out ones.
+    pub total_size: u64,
+    pub transactions: Vec<MempoolTransactionView>,
+    /// Recently evicted or rejected transactions, oldest first.
+    pub dropped_transactions: Vec<DroppedTransactionView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MempoolTransactionView {
+    pub hash: CryptoHash,
+    pub signer_id: AccountId,
+    pub public_key: PublicKey,
+    pub receiver_id: AccountId,
+    pub nonce: Nonce,
+    /// Size of the transaction in bytes.
+    pub size: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DroppedTransactionView {
+    #[serde(flatten)]
+    pub transaction: MempoolTransactionView,
+    pub reason: TransactionDropReasonView,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionDropReasonView {
+    /// Removed from the pool to make space for a transaction with higher or equal priority.
+    Evicted,
+    /// Rejected because the pool was full of transactions with higher priority.
+    NoSpaceLeft,
+    /// Rejected because the signer or the receiver used up its quota of the pool.
+    AccountQuotaExceeded,
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct CongestionInfoView {
     #[serde(with = "dec_format")]
@@ -2531,6 +2670,83 @@ impl CongestionInfoView {
     }
 }
 
+/// Tokens which entered, left or stayed in a shard while a chunk was applied.
+///
+/// `is_balanced` is true when `validator_rewards_amount + incoming_receipts_balance +
+/// initial_accounts_balance + initial_stored_receipts_balance` equals `final_accounts_balance +
+/// final_stored_receipts_balance + outgoing_receipts_balance` plus all burnt amounts.  Account
+/// and stored receipt balances only cover the accounts and receipts touched by the chunk.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChunkBalanceAuditView {
+    pub block_height: BlockHeight,
+    pub shard_id: ShardId,
+    #[serde(with = "dec_format")]
+    pub incoming_receipts_balance: Balance,
+    #[serde(with = "dec_format")]
+    pub outgoing_receipts_balance: Balance,
+    #[serde(with = "dec_format")]
+    pub initial_accounts_balance: Balance,
+    #[serde(with = "dec_format")]
+    pub final_accounts_balance: Balance,
+    /// Value of the delayed, buffered, postponed and yielded receipts.
+    #[serde(with = "dec_format")]
+    pub initial_stored_receipts_balance: Balance,
+    #[serde(with = "dec_format")]
+    pub final_stored_receipts_balance: Balance,
+    /// Part of the accounts balance locked for storage.
+    #[serde(with = "dec_format")]
+    pub initial_storage_staked: Balance,
+    #[serde(with = "dec_format")]
+    pub final_storage_staked: Balance,
+    /// Gas burnt by transactions and receipts, minus the gas deficit and contract rewards.
+    #[serde(with = "dec_format")]
+    pub tx_burnt_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub slashed_burnt_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub other_burnt_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub global_actions_burnt_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub gas_deficit_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub validator_rewards_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub refund_amount: Balance,
+    #[serde(with = "dec_format")]
+    pub contract_reward_amount: Balance,
+    pub is_balanced: bool,
+}
+
+impl ChunkBalanceAuditView {
+    /// Returns `None` if the balances were not audited when the chunk was applied.
+    pub fn from_stats(stats: &ChunkApplyStatsV1) -> Option<Self> {
+        let audit = stats.balance_audit.as_ref()?;
+        let balance = &stats.balance;
+        Some(Self {
+            block_height: stats.height,
+            shard_id: stats.shard_id,
+            incoming_receipts_balance: audit.incoming_receipts_balance,
+            outgoing_receipts_balance: audit.outgoing_receipts_balance,
+            initial_accounts_balance: audit.initial_accounts_balance,
+            final_accounts_balance: audit.final_accounts_balance,
+            initial_stored_receipts_balance: audit.initial_stored_receipts_balance,
+            final_stored_receipts_balance: audit.final_stored_receipts_balance,
+            initial_storage_staked: audit.initial_storage_staked,
+            final_storage_staked: audit.final_storage_staked,
+            tx_burnt_amount: balance.tx_burnt_amount,
+            slashed_burnt_amount: balance.slashed_burnt_amount,
+            other_burnt_amount: balance.other_burnt_amount,
+            global_actions_burnt_amount: balance.global_actions_burnt_amount,
+            gas_deficit_amount: balance.gas_deficit_amount,
+            validator_rewards_amount: balance.validator_rewards_amount,
+            refund_amount: balance.refund_amount,
+            contract_reward_amount: balance.contract_reward_amount,
+            is_balanced: audit.is_balanced(balance),
+        })
+    }
+}
+
 #[cfg(test)]
 #[cfg(not(feature = "nightly"))]
 mod tests {
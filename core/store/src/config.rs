@@ -457,10 +457,14 @@ pub enum ArchivalStorageLocation {
     #[default]
     ColdDB,
     /// Archival data is persisted in the filesystem.
-    /// NOTE: This option not implemented yet.
+    /// In this case, the cold columns are written to a content-addressed
+    /// directory layout (see [`crate::db::FilesystemDB`]) instead of the cold
+    /// RocksDB and `Config.cold_store` must not be set.  The copying process is
+    /// still configured by `Config.split_storage`.
     Filesystem {
-        /// Root directory containing the archival storage files.
-        _path: std::path::PathBuf,
+        /// Root directory containing the archival storage files.  Relative
+        /// paths are resolved against the node’s home directory.
+        path: std::path::PathBuf,
     },
     /// Archival data is persisted in the Google Cloud Storage.
     /// NOTE: This option not implemented yet.
@@ -497,6 +501,15 @@ impl<'a> ArchivalConfig<'a> {
         archive.then_some(Self { archival_store_config, cold_store_config, split_storage_config })
     }
 
+    /// Returns the root directory of the archival storage if the archival data
+    /// is persisted in the filesystem.
+    pub fn filesystem_path(&self) -> Option<&'a std::path::Path> {
+        match self.archival_store_config.map(|config| &config.storage) {
+            Some(ArchivalStorageLocation::Filesystem { path }) => Some(path),
+            _ => None,
+        }
+    }
+
     fn validate_configs(
         archive: bool,
         archival_store_config: Option<&'a ArchivalStoreConfig>,
@@ -504,17 +517,21 @@ impl<'a> ArchivalConfig<'a> {
         split_storage_config: Option<&'a SplitStorageConfig>,
     ) {
         if archive {
-            // Since only ColdDB storage is supported for now, assert that cold storage is configured.
-            // TODO: Change this condition after supporting other archival storage options such as GCS.
-            assert!(
-                cold_store_config.is_some()
-                    && (archival_store_config.is_none()
-                        || matches!(
-                            archival_store_config.unwrap().storage,
-                            ArchivalStorageLocation::ColdDB
-                        )),
-                "Archival storage must be ColdDB and it must be configured with a valid StoreConfig"
-            );
+            let storage = archival_store_config.map(|config| &config.storage);
+            match storage.unwrap_or(&ArchivalStorageLocation::ColdDB) {
+                ArchivalStorageLocation::ColdDB => assert!(
+                    cold_store_config.is_some(),
+                    "Archival storage is ColdDB but it is not configured with a valid StoreConfig"
+                ),
+                ArchivalStorageLocation::Filesystem { .. } => assert!(
+                    cold_store_config.is_none(),
+                    "Cold-store config must not be set if the archival storage is Filesystem"
+                ),
+                // TODO: Change this condition after supporting GCS archival storage.
+                ArchivalStorageLocation::GCloud { .. } => {
+                    panic!("GCloud archival storage is not supported yet")
+                }
+            }
         } else {
             assert!(
                 cold_store_config.is_none()
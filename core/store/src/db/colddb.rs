@@ -22,6 +22,11 @@ impl ColdDB {
         Self { cold }
     }
 
+    /// Returns the underlying database.
+    pub(crate) fn inner(&self) -> &std::sync::Arc<dyn Database> {
+        &self.cold
+    }
+
     fn err_msg(col: DBCol) -> String {
         format!("Reading from column missing from cold storage. {col:?}")
     }
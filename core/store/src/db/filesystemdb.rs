@@ -0,0 +1,719 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use near_primitives::hash::CryptoHash;
+
+use crate::db::{DBIterator, DBOp, DBSlice, DBTransaction, Database, refcount};
+use crate::{DBCol, Mode, StoreStatistics};
+
+/// Name of the directory holding partially written files.
+const TMP_DIR: &str = "tmp";
+
+/// Name of the directory holding the values, named by their hash.
+const OBJECTS_DIR: &str = "objects";
+
+/// Number of leading key bytes which get a directory level of their own.
+const DIR_LEVELS: usize = 2;
+
+/// Name of the directory standing for the end of a key shorter than
+/// `DIR_LEVELS` bytes.
+const END_OF_KEY_DIR: &str = "-";
+
+/// Longest remainder of a key, in bytes, which is kept verbatim in the file
+/// name.  Longer ones are truncated and followed by `~` and their hash, which
+/// keeps the file names within the 255 bytes most filesystems allow.
+const MAX_NAME_KEY_LEN: usize = 90;
+
+/// A database which keeps the values in a content-addressed directory layout on
+/// the filesystem.
+///
+/// This is meant to be used as the backend of the archival storage (see
+/// [`crate::config::ArchivalStorageLocation::Filesystem`]) wrapped in
+/// [`crate::db::ColdDB`].  Every value is kept once in
+/// `<root>/objects/<h0>/<hash>` where `<hash>` is the hex of its SHA-256 hash
+/// and `<h0>` its first byte, so values stored under many keys, e.g. the same
+/// trie node in many state roots, take space only once.  Objects are never
+/// modified once written and their hash is checked when they are read.
+///
+/// The keys are laid out in order as `<root>/<column>/<b0>/<b1>/k<rest>` where
+/// `<b0>` and `<b1>` are the first two bytes of the key in hex, or `-` if the
+/// key is shorter, and `<rest>` is the rest of the key in hex.  Each of these
+/// files holds the length of the key as a little-endian `u32` followed by the
+/// key and the hash of the value.  Keeping the key in the file allows
+/// recovering keys truncated in the file name and detecting hash collisions.
+/// Deleting a key leaves its object in place since other keys may refer to it;
+/// the cold storage doesn't delete data in normal operation anyway.
+///
+/// Iteration lists only the directories which may hold keys within the range
+/// and reads only the files of the keys returned, so prefix lookups such as
+/// the ones of outcomes or state changes of a block stay cheap.
+///
+/// Every file is first written into `<root>/tmp` and then atomically renamed
+/// into place so a reader never observes a partially written value.  A write
+/// first stores and syncs the objects of all its values and then the keys, so
+/// a key never refers to an object lost in a crash, and the directories are
+/// synced once per transaction rather than once per file.  Note that
+/// a transaction as a whole is **not** atomic.  If the node is interrupted
+/// mid-way, some of the operations may have been applied.  This is fine for
+/// the cold storage since the cold head is written last and the copying is
+/// resumed from the cold head on restart; the writes are idempotent.
+pub struct FilesystemDB {
+    root: PathBuf,
+    /// Counter used to generate unique names for temporary files.
+    tmp_counter: AtomicU64,
+}
+
+impl FilesystemDB {
+    /// Opens the database rooted at given directory.
+    ///
+    /// Creates the directory if it is missing and the mode allows creating the
+    /// database.
+    pub fn open(root: &Path, mode: Mode) -> io::Result<Self> {
+        if !root.exists() {
+            if !mode.can_create() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("archival storage directory {} does not exist", root.display()),
+                ));
+            }
+            std::fs::create_dir_all(root)?;
+        } else if mode.must_create() && std::fs::read_dir(root)?.next().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("archival storage directory {} is not empty", root.display()),
+            ));
+        }
+        if mode.read_write() {
+            // Anything left in the temporary directory comes from writes
+            // interrupted before being renamed into place.
+            let tmp = root.join(TMP_DIR);
+            if tmp.exists() {
+                std::fs::remove_dir_all(&tmp)?;
+            }
+            std::fs::create_dir_all(&tmp)?;
+            std::fs::create_dir_all(root.join(OBJECTS_DIR))?;
+        }
+        Ok(Self { root: root.to_path_buf(), tmp_counter: AtomicU64::new(0) })
+    }
+
+    /// Returns path to the directory holding given column.
+    fn col_path(&self, col: DBCol) -> PathBuf {
+        self.root.join(<&str>::from(col))
+    }
+
+    /// Returns path to the file holding the value with given hash.
+    fn object_path(&self, hash: &CryptoHash) -> PathBuf {
+        let hash = hash.as_bytes();
+        self.root.join(OBJECTS_DIR).join(hex::encode(&hash[..1])).join(hex::encode(hash))
+    }
+
+    /// Returns path to the file holding given key.
+    fn entry_path(&self, col: DBCol, key: &[u8]) -> PathBuf {
+        let mut path = self.col_path(col);
+        for level in 0..DIR_LEVELS {
+            match key.get(level) {
+                Some(byte) => path.push(hex::encode([*byte])),
+                None => path.push(END_OF_KEY_DIR),
+            }
+        }
+        path.push(file_name(key.get(DIR_LEVELS..).unwrap_or_default()));
+        path
+    }
+
+    fn read_entry(&self, col: DBCol, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let path = self.entry_path(col, key);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let (stored_key, hash) = decode_entry(&path, &data)?;
+        if stored_key != key {
+            return Err(io::Error::other(format!(
+                "hash collision in {}: stored key {stored_key:?} does not match {key:?}",
+                path.display()
+            )));
+        }
+        self.read_object(&hash).map(Some)
+    }
+
+    fn read_object(&self, hash: &CryptoHash) -> io::Result<Vec<u8>> {
+        let path = self.object_path(hash);
+        let value = std::fs::read(&path)?;
+        if CryptoHash::hash_bytes(&value) != *hash {
+            return Err(io::Error::other(format!("corrupted object {}", path.display())));
+        }
+        Ok(value)
+    }
+
+    /// Writes `data` into a temporary file, syncs it and renames it to `path`.
+    /// The rename is only durable once the directory of `path` is synced,
+    /// which is left to the caller.
+    fn write_file(&self, path: &Path, data: &[&[u8]], dirs: &mut DirsToSync) -> io::Result<()> {
+        use std::io::Write;
+
+        dirs.create_parent(&self.root, path)?;
+        let tmp_name =
+            format!("{}.{}", std::process::id(), self.tmp_counter.fetch_add(1, Ordering::Relaxed));
+        let tmp_path = self.root.join(TMP_DIR).join(tmp_name);
+        let mut file = std::fs::File::create(&tmp_path)?;
+        for data in data {
+            file.write_all(data)?;
+        }
+        file.sync_data()?;
+        std::fs::rename(&tmp_path, path)
+    }
+
+    /// Stores the value unless an object with the same hash already exists and
+    /// returns its hash.
+    fn write_object(&self, value: &[u8], dirs: &mut DirsToSync) -> io::Result<CryptoHash> {
+        let hash = CryptoHash::hash_bytes(value);
+        let path = self.object_path(&hash);
+        if !path.exists() {
+            self.write_file(&path, &[value], dirs)?;
+        }
+        Ok(hash)
+    }
+
+    fn write_entry(
+        &self,
+        col: DBCol,
+        key: &[u8],
+        hash: &CryptoHash,
+        dirs: &mut DirsToSync,
+    ) -> io::Result<()> {
+        let key_len = u32::try_from(key.len()).map_err(io::Error::other)?;
+        let path = self.entry_path(col, key);
+        self.write_file(&path, &[&key_len.to_le_bytes(), key, hash.as_bytes()], dirs)
+    }
+
+    fn delete_entry(&self, col: DBCol, key: &[u8], dirs: &mut DirsToSync) -> io::Result<()> {
+        let path = self.entry_path(col, key);
+        match std::fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            Err(_) => Ok(()),
+            Ok(()) => {
+                dirs.insert(path.parent().unwrap());
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the keys of a column within given bounds in order, along with
+    /// the paths of their files.
+    ///
+    /// The directories are listed lazily as the iteration reaches them and
+    /// skipped if they can't hold keys within the bounds.  Files are only read
+    /// if their key is truncated in the file name.
+    fn iter_keys<'a>(
+        &'a self,
+        col: DBCol,
+        lower: Bound<Vec<u8>>,
+        upper: Bound<Vec<u8>>,
+    ) -> impl Iterator<Item = io::Result<(Vec<u8>, PathBuf)>> + 'a {
+        // Directories still to be listed and keys to be returned, the next one
+        // at the top.
+        let mut stack =
+            vec![Node::Dir { path: self.col_path(col), prefix: vec![], level: 0, exact: false }];
+        std::iter::from_fn(move || {
+            loop {
+                match stack.pop()? {
+                    Node::Entry { key, path } => return Some(Ok((key, path))),
+                    Node::Dir { path, prefix, level, exact } => {
+                        match list_dir(&path, &prefix, level, exact, &lower, &upper) {
+                            Ok(mut children) => {
+                                children.sort_by(|a, b| b.0.cmp(&a.0));
+                                stack.extend(children.into_iter().map(|(_, node)| node));
+                            }
+                            Err(err) => return Some(Err(err)),
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over raw entries of a column within given bounds.
+    fn iter_col<'a>(
+        &'a self,
+        col: DBCol,
+        lower: Bound<Vec<u8>>,
+        upper: Bound<Vec<u8>>,
+    ) -> impl Iterator<Item = io::Result<(Box<[u8]>, Box<[u8]>)>> + 'a {
+        self.iter_keys(col, lower, upper).filter_map(|item| {
+            let (key, path) = match item {
+                Ok(item) => item,
+                Err(err) => return Some(Err(err)),
+            };
+            let data = match std::fs::read(&path) {
+                Ok(data) => data,
+                // Deleted since the directory was listed.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+                Err(err) => return Some(Err(err)),
+            };
+            let value = decode_entry(&path, &data).and_then(|(_, hash)| self.read_object(&hash));
+            Some(value.map(|value| (key.into(), value.into())))
+        })
+    }
+}
+
+/// Directories whose entries changed since they were last synced.
+#[derive(Default)]
+struct DirsToSync(BTreeSet<PathBuf>);
+
+impl DirsToSync {
+    fn insert(&mut self, dir: &Path) {
+        if !self.0.contains(dir) {
+            self.0.insert(dir.to_path_buf());
+        }
+    }
+
+    /// Creates the parent directory of `path` if it is missing, along with
+    /// its missing ancestors within `root`, and marks the directories whose
+    /// entries change to be synced.
+    fn create_parent(&mut self, root: &Path, path: &Path) -> io::Result<()> {
+        let parent = path.parent().unwrap();
+        self.insert(parent);
+        if parent.exists() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(parent)?;
+        for ancestor in parent.ancestors().skip(1) {
+            self.insert(ancestor);
+            if ancestor == root {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Syncs the directories, which makes the renames and deletions of their
+    /// entries durable.
+    fn sync(&mut self) -> io::Result<()> {
+        for dir in std::mem::take(&mut self.0) {
+            std::fs::File::open(&dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+/// A directory or a file found while iterating over a column.
+enum Node {
+    /// Directory at given level holding keys starting with `prefix`, or only
+    /// the key equal to `prefix` if `exact`.
+    Dir {
+        path: PathBuf,
+        prefix: Vec<u8>,
+        level: usize,
+        exact: bool,
+    },
+    Entry {
+        key: Vec<u8>,
+        path: PathBuf,
+    },
+}
+
+/// Lists the children of a directory which may hold keys within the bounds,
+/// along with the keys they are sorted by.
+fn list_dir(
+    path: &Path,
+    prefix: &[u8],
+    level: usize,
+    exact: bool,
+    lower: &Bound<Vec<u8>>,
+    upper: &Bound<Vec<u8>>,
+) -> io::Result<Vec<(Vec<u8>, Node)>> {
+    let dir = match std::fs::read_dir(path) {
+        Ok(dir) => dir,
+        // Missing column or deleted since the parent was listed.
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut children = vec![];
+    for entry in dir {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|name| name.to_str()).ok_or_else(|| {
+            io::Error::other(format!("unexpected file {} in archival storage", path.display()))
+        })?;
+        if level < DIR_LEVELS {
+            let (child_prefix, child_exact) = if exact || name == END_OF_KEY_DIR {
+                (prefix.to_vec(), true)
+            } else {
+                let byte = hex::decode(name).map_err(io::Error::other)?;
+                ([prefix, &byte].concat(), false)
+            };
+            let in_range = if child_exact {
+                key_in_range(&child_prefix, lower, upper)
+            } else {
+                prefix_in_range(&child_prefix, lower, upper)
+            };
+            if in_range {
+                let node = Node::Dir {
+                    path,
+                    prefix: child_prefix.clone(),
+                    level: level + 1,
+                    exact: child_exact,
+                };
+                children.push((child_prefix, node));
+            }
+        } else {
+            let key = match parse_file_name(name)? {
+                Some(rest) => [prefix, &rest].concat(),
+                None => match read_entry_key(&path)? {
+                    Some(key) => key,
+                    None => continue,
+                },
+            };
+            if key_in_range(&key, lower, upper) {
+                children.push((key.clone(), Node::Entry { key, path }));
+            }
+        }
+    }
+    Ok(children)
+}
+
+/// Returns the file name for the part of a key following the directory levels.
+fn file_name(rest: &[u8]) -> String {
+    if rest.len() <= MAX_NAME_KEY_LEN {
+        format!("k{}", hex::encode(rest))
+    } else {
+        let hash = CryptoHash::hash_bytes(rest);
+        format!("k{}~{}", hex::encode(&rest[..MAX_NAME_KEY_LEN]), hex::encode(hash.as_bytes()))
+    }
+}
+
+/// Returns the part of the key encoded in a file name, or `None` if it is
+/// truncated.
+fn parse_file_name(name: &str) -> io::Result<Option<Vec<u8>>> {
+    let Some(encoded) = name.strip_prefix('k') else {
+        return Err(io::Error::other(format!("unexpected file {name} in archival storage")));
+    };
+    if encoded.contains('~') {
+        return Ok(None);
+    }
+    hex::decode(encoded).map(Some).map_err(io::Error::other)
+}
+
+/// Reads the key from an entry file without reading the value.  Returns `None`
+/// if the file no longer exists.
+fn read_entry_key(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    use std::io::Read;
+
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let mut key_len = [0; 4];
+    file.read_exact(&mut key_len)?;
+    let mut key = vec![0; u32::from_le_bytes(key_len) as usize];
+    file.read_exact(&mut key)?;
+    Ok(Some(key))
+}
+
+fn key_in_range(key: &[u8], lower: &Bound<Vec<u8>>, upper: &Bound<Vec<u8>>) -> bool {
+    let above_lower = match lower {
+        Bound::Included(lower) => key >= lower.as_slice(),
+        Bound::Excluded(lower) => key > lower.as_slice(),
+        Bound::Unbounded => true,
+    };
+    above_lower && below_upper(key, upper)
+}
+
+/// Returns whether some key starting with `prefix` may be within the bounds.
+fn prefix_in_range(prefix: &[u8], lower: &Bound<Vec<u8>>, upper: &Bound<Vec<u8>>) -> bool {
+    let above_lower = match lower {
+        // Keys starting with a prefix smaller than the lower bound are all
+        // smaller too, unless the bound itself starts with the prefix.
+        Bound::Included(lower) | Bound::Excluded(lower) => {
+            prefix >= lower.as_slice() || lower.starts_with(prefix)
+        }
+        Bound::Unbounded => true,
+    };
+    above_lower && below_upper(prefix, upper)
+}
+
+fn below_upper(key: &[u8], upper: &Bound<Vec<u8>>) -> bool {
+    match upper {
+        Bound::Included(upper) => key <= upper.as_slice(),
+        Bound::Excluded(upper) => key < upper.as_slice(),
+        Bound::Unbounded => true,
+    }
+}
+
+/// Splits contents of an entry file into key and the hash of the value.
+fn decode_entry<'a>(path: &Path, data: &'a [u8]) -> io::Result<(&'a [u8], CryptoHash)> {
+    let corrupted = || io::Error::other(format!("corrupted entry {}", path.display()));
+    let (key_len, rest) = data.split_first_chunk::<4>().ok_or_else(corrupted)?;
+    let key_len = u32::from_le_bytes(*key_len) as usize;
+    if rest.len() != key_len + 32 {
+        return Err(corrupted());
+    }
+    let (key, hash) = rest.split_at(key_len);
+    Ok((key, CryptoHash(hash.try_into().unwrap())))
+}
+
+impl Database for FilesystemDB {
+    fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        Ok(self.read_entry(col, key)?.map(DBSlice::from_vec))
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        let iterator = self.iter_raw_bytes(col);
+        refcount::iter_with_rc_logic(col, iterator)
+    }
+
+    fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        Box::new(self.iter_col(col, Bound::Unbounded, Bound::Unbounded))
+    }
+
+    fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
+        let iterator = self
+            .iter_col(col, Bound::Included(key_prefix.to_vec()), Bound::Unbounded)
+            .take_while(move |item| match item {
+                Ok((key, _)) => key.starts_with(key_prefix),
+                Err(_) => true,
+            });
+        refcount::iter_with_rc_logic(col, iterator)
+    }
+
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        let lower = lower_bound.map_or(Bound::Unbounded, |f| Bound::Included(f.to_vec()));
+        let upper = upper_bound.map_or(Bound::Unbounded, |f| Bound::Excluded(f.to_vec()));
+        refcount::iter_with_rc_logic(col, self.iter_col(col, lower, upper))
+    }
+
+    fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+        // The objects of the values known upfront are stored first, so that
+        // the keys written below refer to objects which are already durable.
+        let mut object_dirs = DirsToSync::default();
+        let hashes = transaction
+            .ops
+            .iter()
+            .map(|op| match op {
+                DBOp::Set { value, .. } | DBOp::Insert { value, .. } => {
+                    self.write_object(value, &mut object_dirs).map(Some)
+                }
+                _ => Ok(None),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        object_dirs.sync()?;
+
+        let mut entry_dirs = DirsToSync::default();
+        for (op, hash) in transaction.ops.into_iter().zip(hashes) {
+            match op {
+                DBOp::Set { col, key, value: _ } => {
+                    self.write_entry(col, &key, &hash.unwrap(), &mut entry_dirs)?;
+                }
+                DBOp::Insert { col, key, value } => {
+                    if cfg!(debug_assertions) {
+                        if let Some(old_value) = self.read_entry(col, &key)? {
+                            super::assert_no_overwrite(col, &key, &value, &old_value)
+                        }
+                    }
+                    self.write_entry(col, &key, &hash.unwrap(), &mut entry_dirs)?;
+                }
+                DBOp::UpdateRefcount { col, key, value } => {
+                    let existing = self.read_entry(col, &key)?;
+                    let operands = [value.as_slice()];
+                    let merged = refcount::refcount_merge(existing.as_deref(), operands);
+                    if merged.is_empty() {
+                        self.delete_entry(col, &key, &mut entry_dirs)?;
+                    } else {
+                        debug_assert!(
+                            refcount::decode_value_with_rc(&merged).1 > 0,
+                            "Inserting value with non-positive refcount"
+                        );
+                        // The merged value depends on the earlier operations, so
+                        // its object can't be stored upfront.
+                        let hash = self.write_object(&merged, &mut object_dirs)?;
+                        object_dirs.sync()?;
+                        self.write_entry(col, &key, &hash, &mut entry_dirs)?;
+                    }
+                }
+                DBOp::Delete { col, key } => {
+                    self.delete_entry(col, &key, &mut entry_dirs)?;
+                }
+                DBOp::DeleteAll { col } => {
+                    let col_path = self.col_path(col);
+                    if col_path.exists() {
+                        std::fs::remove_dir_all(col_path)?;
+                        entry_dirs.insert(&self.root);
+                    }
+                }
+                DBOp::DeleteRange { col, from, to } => {
+                    let keys = self
+                        .iter_keys(col, Bound::Included(from), Bound::Excluded(to))
+                        .map(|item| item.map(|(key, _)| key))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    for key in keys {
+                        self.delete_entry(col, &key, &mut entry_dirs)?;
+                    }
+                }
+            };
+        }
+        entry_dirs.sync()
+    }
+
+    /// Transactions are synced to disk as they are written so this is a no-op.
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn compact(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get_store_statistics(&self) -> Option<StoreStatistics> {
+        None
+    }
+
+    fn create_checkpoint(
+        &self,
+        _path: &std::path::Path,
+        _columns_to_keep: Option<&[DBCol]>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("checkpoints are not supported by the filesystem archival storage")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ColdDB;
+
+    fn open_test_db() -> (tempfile::TempDir, FilesystemDB) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = FilesystemDB::open(&dir.path().join("archive"), Mode::ReadWrite).unwrap();
+        (dir, db)
+    }
+
+    #[test]
+    fn test_read_write() {
+        let (dir, db) = open_test_db();
+
+        let mut transaction = DBTransaction::new();
+        transaction.insert(DBCol::Block, b"a".to_vec(), b"val_a".to_vec());
+        transaction.set(DBCol::BlockMisc, b"HEAD".to_vec(), b"head".to_vec());
+        db.write(transaction).unwrap();
+
+        assert_eq!(db.get_raw_bytes(DBCol::Block, b"a").unwrap().as_deref(), Some(&b"val_a"[..]));
+        assert!(db.get_raw_bytes(DBCol::Block, b"b").unwrap().is_none());
+
+        let mut transaction = DBTransaction::new();
+        transaction.delete(DBCol::BlockMisc, b"HEAD".to_vec());
+        db.write(transaction).unwrap();
+        assert!(db.get_raw_bytes(DBCol::BlockMisc, b"HEAD").unwrap().is_none());
+
+        // The data survives reopening the database.
+        drop(db);
+        let db = FilesystemDB::open(&dir.path().join("archive"), Mode::ReadOnly).unwrap();
+        assert_eq!(db.get_raw_bytes(DBCol::Block, b"a").unwrap().as_deref(), Some(&b"val_a"[..]));
+    }
+
+    #[test]
+    fn test_iter() {
+        let (_dir, db) = open_test_db();
+
+        let mut transaction = DBTransaction::new();
+        for key in ["cc1", "a", "aa1", "bb1", "aa", "", "aaa"] {
+            transaction.insert(DBCol::Block, key.into(), format!("val_{key}").into());
+        }
+        db.write(transaction).unwrap();
+
+        let keys = |iter: DBIterator| -> Vec<String> {
+            iter.map(|data| String::from_utf8(data.unwrap().0.to_vec()).unwrap()).collect()
+        };
+        assert_eq!(keys(db.iter(DBCol::Block)), vec!["", "a", "aa", "aa1", "aaa", "bb1", "cc1"]);
+        assert_eq!(keys(db.iter_prefix(DBCol::Block, b"aa")), vec!["aa", "aa1", "aaa"]);
+        assert_eq!(
+            keys(db.iter_range(DBCol::Block, Some(b"aa1"), Some(b"cc1"))),
+            vec!["aa1", "aaa", "bb1"]
+        );
+        assert!(db.iter(DBCol::Chunks).next().is_none());
+
+        let mut transaction = DBTransaction::new();
+        transaction.delete_range(DBCol::Block, b"aa".to_vec(), b"bb1".to_vec());
+        db.write(transaction).unwrap();
+        assert_eq!(keys(db.iter(DBCol::Block)), vec!["", "a", "bb1", "cc1"]);
+    }
+
+    /// Values stored under many keys are kept once and outlive deleted keys.
+    #[test]
+    fn test_shared_objects() {
+        let (dir, db) = open_test_db();
+
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::Block, b"a".to_vec(), b"value".to_vec());
+        transaction.set(DBCol::Chunks, b"b".to_vec(), b"value".to_vec());
+        transaction.delete(DBCol::Block, b"a".to_vec());
+        db.write(transaction).unwrap();
+
+        assert!(db.get_raw_bytes(DBCol::Block, b"a").unwrap().is_none());
+        assert_eq!(db.get_raw_bytes(DBCol::Chunks, b"b").unwrap().as_deref(), Some(&b"value"[..]));
+        let objects = walkdir(&dir.path().join("archive").join(OBJECTS_DIR));
+        assert_eq!(objects, vec![db.object_path(&CryptoHash::hash_bytes(b"value"))]);
+    }
+
+    fn walkdir(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() { files.extend(walkdir(&path)) } else { files.push(path) }
+        }
+        files
+    }
+
+    /// Keys too long to fit in a file name are still returned in order.
+    #[test]
+    fn test_iter_long_keys() {
+        let (_dir, db) = open_test_db();
+
+        let long_key = |suffix: u8| [&[7; 200][..], &[suffix]].concat();
+        let mut transaction = DBTransaction::new();
+        for key in [long_key(2), long_key(1), vec![7; 150], vec![7, 7, 8]] {
+            transaction.set(DBCol::StateChanges, key.clone(), key);
+        }
+        db.write(transaction).unwrap();
+
+        let keys: Vec<Vec<u8>> = db
+            .iter_prefix(DBCol::StateChanges, &[7, 7])
+            .map(|item| {
+                let (key, value) = item.unwrap();
+                assert_eq!(key, value);
+                key.to_vec()
+            })
+            .collect();
+        assert_eq!(keys, vec![vec![7; 150], long_key(1), long_key(2), vec![7, 7, 8]]);
+        assert_eq!(
+            db.get_raw_bytes(DBCol::StateChanges, &long_key(1)).unwrap().as_deref(),
+            Some(&long_key(1)[..])
+        );
+    }
+
+    /// Reference counted columns written through the cold database end up with
+    /// the reference count set to one.
+    #[test]
+    fn test_cold_db() {
+        let (_dir, db) = open_test_db();
+        let cold = ColdDB::new(std::sync::Arc::new(db));
+
+        let mut transaction = DBTransaction::new();
+        let value = [&b"FooBar"[..], &2i64.to_le_bytes()].concat();
+        transaction.update_refcount(DBCol::State, b"key".to_vec(), value);
+        cold.write(transaction).unwrap();
+
+        let raw = cold.get_raw_bytes(DBCol::State, b"key").unwrap().unwrap();
+        assert_eq!(&*raw, &[&b"FooBar"[..], &1i64.to_le_bytes()].concat()[..]);
+        let stripped = cold.get_with_rc_stripped(DBCol::State, b"key").unwrap().unwrap();
+        assert_eq!(&*stripped, b"FooBar");
+    }
+}
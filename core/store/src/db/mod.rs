@@ -6,8 +6,10 @@ use std::sync::Arc;
 
 mod colddb;
 mod database_tests;
+mod filesystemdb;
 pub mod metadata;
 mod mixeddb;
+mod overlaydb;
 mod recoverydb;
 pub mod refcount;
 pub(crate) mod rocksdb;
@@ -16,7 +18,9 @@ mod splitdb;
 mod testdb;
 
 pub use self::colddb::ColdDB;
+pub use self::filesystemdb::FilesystemDB;
 pub use self::mixeddb::{MixedDB, ReadOrder};
+pub use self::overlaydb::OverlayDB;
 pub use self::recoverydb::RecoveryDB;
 pub use self::rocksdb::RocksDB;
 pub use self::slice::DBSlice;
@@ -0,0 +1,366 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::Bound;
+use std::sync::{Arc, RwLock};
+
+use itertools::EitherOrBoth;
+
+use crate::db::{DBIterator, DBOp, DBSlice, DBTransaction, Database, refcount};
+use crate::{DBCol, StoreStatistics};
+
+/// Changes made to a single column on top of the base database.
+#[derive(Default)]
+struct ColumnOverlay {
+    /// Whether the whole column has been deleted.  If set, none of the data
+    /// from the base database is visible.
+    cleared: bool,
+    /// Key ranges `[from, to)` deleted from the base database.
+    deleted_ranges: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Values written on top of the base database.  `None` marks a key which
+    /// has been deleted.
+    entries: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl ColumnOverlay {
+    /// Returns whether the base database value for given key has been deleted
+    /// by a `DeleteAll` or `DeleteRange` operation.
+    fn hides_base(&self, key: &[u8]) -> bool {
+        self.cleared
+            || self
+                .deleted_ranges
+                .iter()
+                .any(|(from, to)| (from.as_slice()..to.as_slice()).contains(&key))
+    }
+
+    /// Returns a copy of the part of the overlay which decides whether base
+    /// database entries are visible.
+    fn base_mask(&self) -> ColumnOverlay {
+        Self {
+            cleared: self.cleared,
+            deleted_ranges: self.deleted_ranges.clone(),
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+/// A copy-on-write database which keeps all writes in memory on top of a base
+/// database which is never modified.
+///
+/// Reads see the base database with all the changes written through the
+/// overlay applied.  This lets offline tools (state-viewer, fork-network etc.)
+/// run experiments on top of a production database opened in read-only mode
+/// without taking a snapshot first.  All changes are lost once the overlay is
+/// dropped.
+///
+/// Since the whole write set lives in memory, the overlay is not suitable for
+/// operations which rewrite large portions of the database.
+pub struct OverlayDB {
+    base: Arc<dyn Database>,
+    overlay: RwLock<enum_map::EnumMap<DBCol, ColumnOverlay>>,
+}
+
+impl OverlayDB {
+    pub fn new(base: Arc<dyn Database>) -> Arc<OverlayDB> {
+        Arc::new(Self { base, overlay: Default::default() })
+    }
+
+    /// Returns raw value of given key as seen through the overlay.
+    fn get_raw(
+        &self,
+        column: &ColumnOverlay,
+        col: DBCol,
+        key: &[u8],
+    ) -> io::Result<Option<Vec<u8>>> {
+        if let Some(value) = column.entries.get(key) {
+            return Ok(value.clone());
+        }
+        if column.hides_base(key) {
+            return Ok(None);
+        }
+        Ok(self.base.get_raw_bytes(col, key)?.map(|value| value.to_vec()))
+    }
+
+    /// Merges entries from the base database with the entries from the
+    /// overlay.  Both are expected to be sorted by key.
+    ///
+    /// Overlay entries take precedence over base database entries with the
+    /// same key.  If `strip_rc` is set, the base iterator is expected to have
+    /// reference count logic applied already and the same logic is applied
+    /// to the overlay entries.
+    fn merge_iter<'a>(
+        col: DBCol,
+        base: DBIterator<'a>,
+        base_mask: ColumnOverlay,
+        entries: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+        strip_rc: bool,
+    ) -> DBIterator<'a> {
+        let base = base.filter(move |item| match item {
+            Ok((key, _)) => !base_mask.hides_base(key),
+            Err(_) => true,
+        });
+        let iter = itertools::merge_join_by(base, entries, |base, (key, _)| match base {
+            Ok((base_key, _)) => (**base_key).cmp(key.as_slice()),
+            // Surface errors as soon as possible.
+            Err(_) => Ordering::Less,
+        });
+        let strip_rc = strip_rc && col.is_rc();
+        let iter = iter.filter_map(move |item| match item {
+            EitherOrBoth::Left(item) => Some(item),
+            EitherOrBoth::Right((key, value)) | EitherOrBoth::Both(_, (key, value)) => {
+                let value = if strip_rc { value.and_then(refcount::strip_refcount) } else { value };
+                value.map(|value| Ok((key.into_boxed_slice(), value.into_boxed_slice())))
+            }
+        });
+        Box::new(iter)
+    }
+
+    /// Iterates over entries within given bounds merging base database and
+    /// overlay entries.
+    fn iter_bounds<'a>(
+        &'a self,
+        col: DBCol,
+        base: DBIterator<'a>,
+        lower: Bound<&[u8]>,
+        upper: Bound<&[u8]>,
+        prefix: Option<&[u8]>,
+        strip_rc: bool,
+    ) -> DBIterator<'a> {
+        let overlay = self.overlay.read().unwrap();
+        let column = &overlay[col];
+        let entries = column
+            .entries
+            .range::<[u8], _>((lower, upper))
+            .take_while(|(key, _)| prefix.is_none_or(|prefix| key.starts_with(prefix)))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Self::merge_iter(col, base, column.base_mask(), entries, strip_rc)
+    }
+}
+
+impl Database for OverlayDB {
+    fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        {
+            let overlay = self.overlay.read().unwrap();
+            let column = &overlay[col];
+            if let Some(value) = column.entries.get(key) {
+                return Ok(value.clone().map(DBSlice::from_vec));
+            }
+            if column.hides_base(key) {
+                return Ok(None);
+            }
+        }
+        self.base.get_raw_bytes(col, key)
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        let base = self.base.iter(col);
+        self.iter_bounds(col, base, Bound::Unbounded, Bound::Unbounded, None, true)
+    }
+
+    fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
+        let base = self.base.iter_prefix(col, key_prefix);
+        self.iter_bounds(
+            col,
+            base,
+            Bound::Included(key_prefix),
+            Bound::Unbounded,
+            Some(key_prefix),
+            true,
+        )
+    }
+
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        let base = self.base.iter_range(col, lower_bound, upper_bound);
+        let lower = lower_bound.map_or(Bound::Unbounded, Bound::Included);
+        let upper = upper_bound.map_or(Bound::Unbounded, Bound::Excluded);
+        self.iter_bounds(col, base, lower, upper, None, true)
+    }
+
+    fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        let base = self.base.iter_raw_bytes(col);
+        self.iter_bounds(col, base, Bound::Unbounded, Bound::Unbounded, None, false)
+    }
+
+    /// Applies the transaction to the in-memory overlay.  The base database is
+    /// never written to.
+    fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+        let mut overlay = self.overlay.write().unwrap();
+        for op in transaction.ops {
+            match op {
+                DBOp::Set { col, key, value } => {
+                    overlay[col].entries.insert(key, Some(value));
+                }
+                DBOp::Insert { col, key, value } => {
+                    if cfg!(debug_assertions) {
+                        if let Some(old_value) = self.get_raw(&overlay[col], col, &key)? {
+                            super::assert_no_overwrite(col, &key, &value, &*old_value)
+                        }
+                    }
+                    overlay[col].entries.insert(key, Some(value));
+                }
+                DBOp::UpdateRefcount { col, key, value } => {
+                    let existing = self.get_raw(&overlay[col], col, &key)?;
+                    let operands = [value.as_slice()];
+                    let merged = refcount::refcount_merge(existing.as_deref(), operands);
+                    let merged = if merged.is_empty() { None } else { Some(merged) };
+                    overlay[col].entries.insert(key, merged);
+                }
+                DBOp::Delete { col, key } => {
+                    overlay[col].entries.insert(key, None);
+                }
+                DBOp::DeleteAll { col } => {
+                    overlay[col] = ColumnOverlay { cleared: true, ..Default::default() };
+                }
+                DBOp::DeleteRange { col, from, to } => {
+                    let column = &mut overlay[col];
+                    column.entries.retain(|key, _| !(&from..&to).contains(&key));
+                    column.deleted_ranges.push((from, to));
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn compact(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get_store_statistics(&self) -> Option<StoreStatistics> {
+        self.base.get_store_statistics()
+    }
+
+    /// The overlay only lives in memory so checkpointing it would silently
+    /// drop all the changes.
+    fn create_checkpoint(
+        &self,
+        _path: &std::path::Path,
+        _columns_to_keep: Option<&[DBCol]>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("creating checkpoints of an overlay database is not supported")
+    }
+
+    fn copy_if_test(&self, _columns_to_keep: Option<&[DBCol]>) -> Option<Arc<dyn Database>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::db::TestDB;
+
+    const ONE: &[u8] = &1i64.to_le_bytes();
+    const MINUS_ONE: &[u8] = &(-1i64).to_le_bytes();
+
+    fn write(db: &dyn Database, op: DBOp) {
+        db.write(DBTransaction { ops: vec![op] }).unwrap();
+    }
+
+    fn set(db: &dyn Database, col: DBCol, key: &[u8], value: &[u8]) {
+        write(db, DBOp::Set { col, key: key.to_vec(), value: value.to_vec() });
+    }
+
+    fn get(db: &dyn Database, col: DBCol, key: &[u8]) -> Option<Vec<u8>> {
+        db.get_raw_bytes(col, key).unwrap().map(|value| value.to_vec())
+    }
+
+    fn keys(iter: DBIterator<'_>) -> Vec<Vec<u8>> {
+        iter.map(|item| item.unwrap().0.to_vec()).collect()
+    }
+
+    fn create_base() -> Arc<dyn Database> {
+        let base = TestDB::new();
+        for key in [b"a", b"b", b"c", b"d"] {
+            set(base.as_ref(), DBCol::Block, key, key);
+        }
+        base
+    }
+
+    #[test]
+    fn test_writes_do_not_reach_base() {
+        let base = create_base();
+        let overlay = OverlayDB::new(base.clone());
+
+        set(overlay.as_ref(), DBCol::Block, b"a", b"new");
+        set(overlay.as_ref(), DBCol::Block, b"e", b"e");
+        write(overlay.as_ref(), DBOp::Delete { col: DBCol::Block, key: b"b".to_vec() });
+
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"a"), Some(b"new".to_vec()));
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"b"), None);
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"c"), Some(b"c".to_vec()));
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"e"), Some(b"e".to_vec()));
+
+        assert_eq!(get(base.as_ref(), DBCol::Block, b"a"), Some(b"a".to_vec()));
+        assert_eq!(get(base.as_ref(), DBCol::Block, b"b"), Some(b"b".to_vec()));
+        assert_eq!(get(base.as_ref(), DBCol::Block, b"e"), None);
+
+        assert_eq!(
+            keys(overlay.iter(DBCol::Block)),
+            vec![b"a".to_vec(), b"c".to_vec(), b"d".to_vec(), b"e".to_vec()]
+        );
+        assert_eq!(
+            keys(overlay.iter_range(DBCol::Block, Some(b"b".as_slice()), Some(b"e".as_slice()))),
+            vec![b"c".to_vec(), b"d".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_delete_range_and_all() {
+        let base = create_base();
+        let overlay = OverlayDB::new(base.clone());
+
+        set(overlay.as_ref(), DBCol::Block, b"bb", b"bb");
+        write(
+            overlay.as_ref(),
+            DBOp::DeleteRange { col: DBCol::Block, from: b"b".to_vec(), to: b"d".to_vec() },
+        );
+        // Writes after the range deletion are visible again.
+        set(overlay.as_ref(), DBCol::Block, b"c", b"new");
+        assert_eq!(
+            keys(overlay.iter(DBCol::Block)),
+            vec![b"a".to_vec(), b"c".to_vec(), b"d".to_vec()]
+        );
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"b"), None);
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"c"), Some(b"new".to_vec()));
+
+        write(overlay.as_ref(), DBOp::DeleteAll { col: DBCol::Block });
+        assert!(keys(overlay.iter(DBCol::Block)).is_empty());
+        assert_eq!(get(overlay.as_ref(), DBCol::Block, b"a"), None);
+        assert_eq!(keys(base.iter(DBCol::Block)).len(), 4);
+    }
+
+    #[test]
+    fn test_refcount() {
+        let col = DBCol::State;
+        let base: Arc<dyn Database> = TestDB::new();
+        let value = [b"value".as_slice(), ONE].concat();
+        write(base.as_ref(), DBOp::UpdateRefcount { col, key: b"key".to_vec(), value });
+        let overlay = OverlayDB::new(base.clone());
+
+        // Increasing the refcount merges with the value from the base.
+        let value = [b"value".as_slice(), ONE].concat();
+        write(overlay.as_ref(), DBOp::UpdateRefcount { col, key: b"key".to_vec(), value });
+        let expected = [b"value".as_slice(), &2i64.to_le_bytes()].concat();
+        assert_eq!(get(overlay.as_ref(), col, b"key"), Some(expected));
+
+        // Dropping the refcount to zero hides the value from the base.
+        for _ in 0..2 {
+            let value = MINUS_ONE.to_vec();
+            write(overlay.as_ref(), DBOp::UpdateRefcount { col, key: b"key".to_vec(), value });
+        }
+        assert_eq!(get(overlay.as_ref(), col, b"key"), None);
+        assert!(keys(overlay.iter(col)).is_empty());
+        assert_eq!(keys(base.iter(col)), vec![b"key".to_vec()]);
+    }
+}
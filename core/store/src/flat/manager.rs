@@ -276,6 +276,14 @@ impl FlatStorageManager {
         }
     }
 
+    /// Removes all FlatStorage objects from FlatStorageManager without
+    /// touching the data on disk. Used when the store was replaced wholesale
+    /// and flat storage needs to be re-created from it.
+    pub fn unload_all_flat_storages(&self) {
+        let mut flat_storages = self.0.flat_storages.lock().expect(POISONED_LOCK_ERR);
+        flat_storages.clear();
+    }
+
     /// Returns None if there's no resharding flat storage split in progress
     /// If there is, returns Some(None) if there's at least one child shard that hasn't been split and had its
     /// status set to `CatchingUp`. If they've all been split already and are in the catchup phase,
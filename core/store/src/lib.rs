@@ -33,7 +33,7 @@ pub use crate::node_storage::opener::{
     clear_columns,
 };
 pub use crate::node_storage::{NodeStorage, Temperature};
-pub use crate::store::{Store, StoreUpdate};
+pub use crate::store::{Store, StoreContents, StoreUpdate};
 pub use crate::trie::update::{TrieUpdate, TrieUpdateIterator, TrieUpdateValuePtr};
 pub use crate::trie::{
     ApplyStatePartResult, KeyForStateChanges, KeyLookupMode, NibbleSlice, PartialStorage,
@@ -77,6 +77,46 @@ mod tests {
         test_clear_column(crate::test_utils::create_test_store());
     }
 
+    fn test_restore_contents(store: Store) {
+        {
+            let mut store_update = store.store_update();
+            store_update.increment_refcount(DBCol::State, &[1; 8], &[1]);
+            store_update.set(DBCol::BlockMisc, b"foo", b"bar");
+            store_update.commit().unwrap();
+        }
+        let contents = store.read_contents().unwrap();
+        {
+            let mut store_update = store.store_update();
+            store_update.increment_refcount(DBCol::State, &[1; 8], &[1]);
+            store_update.increment_refcount(DBCol::State, &[2; 8], &[2]);
+            store_update.set(DBCol::BlockMisc, b"foo", b"baz");
+            store_update.set(DBCol::BlockMisc, b"qux", b"quux");
+            store_update.commit().unwrap();
+        }
+        store.restore_contents(&contents).unwrap();
+        assert_eq!(store.get(DBCol::BlockMisc, b"foo").unwrap().as_deref(), Some(&b"bar"[..]));
+        assert_eq!(store.get(DBCol::BlockMisc, b"qux").unwrap(), None);
+        assert_eq!(store.get(DBCol::State, &[2; 8]).unwrap(), None);
+
+        // The refcount must have been restored too so a single decrement
+        // removes the value.
+        let mut store_update = store.store_update();
+        store_update.decrement_refcount(DBCol::State, &[1; 8]);
+        store_update.commit().unwrap();
+        assert_eq!(store.get(DBCol::State, &[1; 8]).unwrap(), None);
+    }
+
+    #[test]
+    fn restore_contents_rocksdb() {
+        let (_tmp_dir, opener) = NodeStorage::test_opener();
+        test_restore_contents(opener.open().unwrap().get_hot_store());
+    }
+
+    #[test]
+    fn restore_contents_testdb() {
+        test_restore_contents(crate::test_utils::create_test_store());
+    }
+
     /// Asserts that elements in the vector are sorted.
     #[track_caller]
     fn assert_sorted(want_count: usize, keys: Vec<Box<[u8]>>) {
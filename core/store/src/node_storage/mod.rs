@@ -212,4 +212,19 @@ impl NodeStorage {
     pub fn cold_db(&self) -> Option<&Arc<crate::db::ColdDB>> {
         self.cold_storage.as_ref()
     }
+
+    /// Wraps the hot and cold databases in [`crate::db::OverlayDB`] so that
+    /// all subsequent writes are kept in memory and never reach the disk.
+    ///
+    /// This is meant for offline tools which want to run experiments on a
+    /// production database without modifying it.  The storage would
+    /// typically be opened in [`crate::Mode::ReadOnly`] mode.
+    pub fn into_overlay(self) -> Self {
+        let hot_storage = crate::db::OverlayDB::new(self.hot_storage);
+        let cold_storage = self.cold_storage.map(|cold_db| {
+            let cold = crate::db::OverlayDB::new(cold_db.inner().clone());
+            Arc::new(crate::db::ColdDB::new(cold))
+        });
+        Self { hot_storage, cold_storage }
+    }
 }
@@ -1,6 +1,7 @@
 use crate::config::ArchivalConfig;
 use crate::db::rocksdb::RocksDB;
 use crate::db::rocksdb::snapshot::{Snapshot, SnapshotError, SnapshotRemoveError};
+use crate::db::{Database, FilesystemDB};
 use crate::metadata::{DB_VERSION, DbKind, DbMetadata, DbVersion};
 use crate::{DBCol, DBTransaction, Mode, NodeStorage, Store, StoreConfig, Temperature};
 use std::sync::Arc;
@@ -171,6 +172,10 @@ pub struct StoreOpener<'a> {
     /// Opener for an instance of Cold RocksDB store if one was configured.
     cold: Option<DBOpener<'a>>,
 
+    /// Root directory of the filesystem archival storage if one was
+    /// configured.  It is used in place of the Cold RocksDB store.
+    cold_filesystem: Option<std::path::PathBuf>,
+
     /// A migrator which performs database migration if the database has old
     /// version.
     migrator: Option<&'a dyn StoreMigrator>,
@@ -215,6 +220,10 @@ impl<'a> StoreOpener<'a> {
                         .map(|config| DBOpener::new(home_dir, config, Temperature::Cold))
                 })
                 .flatten(),
+            cold_filesystem: archival_config
+                .as_ref()
+                .and_then(|config| config.filesystem_path())
+                .map(|path| home_dir.join(path)),
             archival_config,
             migrator: None,
         }
@@ -258,7 +267,10 @@ impl<'a> StoreOpener<'a> {
         let mode = Mode::ReadWrite;
         let hot_db = self.hot.open_unsafe(mode)?;
         let cold_db = self.cold.as_ref().map(|cold| cold.open_unsafe(mode)).transpose()?;
-        let storage = NodeStorage::from_rocksdb(hot_db, cold_db);
+        let storage = match self.open_cold_filesystem(mode)? {
+            Some(cold_db) => NodeStorage::new_with_cold(Arc::new(hot_db), cold_db),
+            None => NodeStorage::from_rocksdb(hot_db, cold_db),
+        };
         Ok(storage)
     }
 
@@ -268,9 +280,10 @@ impl<'a> StoreOpener<'a> {
     ) -> Result<(RocksDB, Snapshot, Option<RocksDB>, Snapshot), StoreOpenerError> {
         {
             let hot_path = self.hot.path.display().to_string();
-            let cold_path = match &self.cold {
-                Some(cold) => cold.path.display().to_string(),
-                None => String::from("none"),
+            let cold_path = match (&self.cold, &self.cold_filesystem) {
+                (Some(cold), _) => cold.path.display().to_string(),
+                (None, Some(path)) => path.display().to_string(),
+                (None, None) => String::from("none"),
             };
             tracing::info!(target: "db_opener", path=hot_path, cold_path=cold_path, "Opening NodeStorage");
         }
@@ -308,7 +321,10 @@ impl<'a> StoreOpener<'a> {
     /// exists.
     pub fn open_in_mode(&self, mode: Mode) -> Result<crate::NodeStorage, StoreOpenerError> {
         let (hot_db, hot_snapshot, cold_db, cold_snapshot) = self.open_dbs(mode)?;
-        let storage = NodeStorage::from_rocksdb(hot_db, cold_db);
+        let storage = match self.open_cold_filesystem(mode)? {
+            Some(cold_db) => NodeStorage::new_with_cold(Arc::new(hot_db), cold_db),
+            None => NodeStorage::from_rocksdb(hot_db, cold_db),
+        };
 
         hot_snapshot.remove()?;
         cold_snapshot.remove()?;
@@ -316,6 +332,84 @@ impl<'a> StoreOpener<'a> {
         Ok(storage)
     }
 
+    /// Opens the filesystem archival storage if one was configured.
+    ///
+    /// The storage holds the same metadata as the Cold RocksDB store.  Its
+    /// version and kind are set when the storage is created and verified
+    /// otherwise.  A storage with an older version is migrated in place with
+    /// the configured migrator, same as the RocksDB databases in
+    /// [`Self::ensure_version`], except that no snapshot is taken beforehand
+    /// since the filesystem storage doesn't support checkpoints.
+    fn open_cold_filesystem(
+        &self,
+        mode: Mode,
+    ) -> Result<Option<Arc<dyn Database>>, StoreOpenerError> {
+        let Some(path) = &self.cold_filesystem else {
+            return Ok(None);
+        };
+        let db: Arc<dyn Database> = Arc::new(FilesystemDB::open(path, mode)?);
+        let store = Store { storage: db.clone() };
+
+        match store.get_db_version()? {
+            None if mode.read_write() => {
+                tracing::info!(target: "db_opener", path=%path.display(), "The filesystem archival storage doesn't exist, creating it.");
+                store.set_db_version(DB_VERSION)?;
+                store.set_db_kind(DbKind::Cold)?;
+            }
+            None => return Err(StoreOpenerError::DbVersionMissing {}),
+            Some(version) if version > DB_VERSION => {
+                return Err(StoreOpenerError::DbVersionTooNew { got: version, want: DB_VERSION });
+            }
+            Some(version) if version < DB_VERSION => {
+                self.migrate_cold_filesystem(mode, path, &store, version)?;
+            }
+            Some(_) => {}
+        }
+
+        let kind = store.get_db_kind()?;
+        if kind != Some(DbKind::Cold) {
+            return Err(StoreOpenerError::DbKindMismatch {
+                which: Temperature::Cold.into(),
+                got: kind,
+                want: DbKind::Cold,
+            });
+        }
+        Ok(Some(db))
+    }
+
+    fn migrate_cold_filesystem(
+        &self,
+        mode: Mode,
+        path: &std::path::Path,
+        store: &Store,
+        version: DbVersion,
+    ) -> Result<(), StoreOpenerError> {
+        if mode.read_only() {
+            return Err(StoreOpenerError::DbVersionMismatchOnRead {
+                got: version,
+                want: DB_VERSION,
+            });
+        }
+        let migrator = self
+            .migrator
+            .ok_or(StoreOpenerError::DbVersionMismatch { got: version, want: DB_VERSION })?;
+        if let Err(release) = migrator.check_support(version) {
+            return Err(StoreOpenerError::DbVersionTooOld {
+                got: version,
+                want: DB_VERSION,
+                latest_release: release,
+            });
+        }
+        for version in version..DB_VERSION {
+            tracing::info!(target: "db_opener", path=%path.display(),
+                           "Migrating the filesystem archival storage from version {} to {}",
+                           version, version + 1);
+            migrator.migrate(store, version).map_err(StoreOpenerError::MigrationError)?;
+            store.set_db_version(version + 1)?;
+        }
+        Ok(())
+    }
+
     pub fn create_snapshots(&self, mode: Mode) -> Result<(Snapshot, Snapshot), StoreOpenerError> {
         {
             let hot_path = self.hot.path.display().to_string();
@@ -680,6 +774,7 @@ pub fn clear_columns<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{ArchivalStorageLocation, ArchivalStoreConfig};
     use std::path::PathBuf;
 
     fn check_keys_existence(store: &Store, column: &DBCol, keys: &Vec<Vec<u8>>, expected: bool) {
@@ -746,4 +841,83 @@ mod tests {
         check_keys_existence(&store.get_hot_store(), &DBCol::Chunks, &keys, false);
         check_keys_existence(&store.get_hot_store(), &DBCol::BlockHeader, &keys, false);
     }
+
+    #[test]
+    fn test_open_filesystem_archival_storage() {
+        let home_dir = tempfile::tempdir().unwrap();
+        let store_config = StoreConfig::test_config();
+        let archival_store_config = ArchivalStoreConfig {
+            storage: ArchivalStorageLocation::Filesystem { path: PathBuf::from("archive") },
+        };
+        let archival_config =
+            || ArchivalConfig::new(true, Some(&archival_store_config), None, None);
+
+        let node_storage =
+            NodeStorage::opener(home_dir.path(), &store_config, archival_config()).open().unwrap();
+        assert_eq!(node_storage.get_hot_store().get_db_kind().unwrap(), Some(DbKind::Hot));
+        let cold_store = node_storage.get_cold_store().unwrap();
+        assert_eq!(cold_store.get_db_kind().unwrap(), Some(DbKind::Cold));
+
+        let mut store_update = cold_store.store_update();
+        store_update.set(DBCol::BlockMisc, crate::db::COLD_HEAD_KEY, b"head");
+        store_update.insert(DBCol::Block, vec![1], vec![42]);
+        store_update.commit().unwrap();
+        drop(node_storage);
+        assert!(home_dir.path().join("archive").join("BlockMisc").is_dir());
+
+        let node_storage = NodeStorage::opener(home_dir.path(), &store_config, archival_config())
+            .open_in_mode(Mode::ReadWriteExisting)
+            .unwrap();
+        let cold_store = node_storage.get_cold_store().unwrap();
+        let head = cold_store.get(DBCol::BlockMisc, crate::db::COLD_HEAD_KEY).unwrap().unwrap();
+        assert_eq!(&*head, b"head");
+        let split_store = node_storage.get_split_store().unwrap();
+        // Cold columns missing from the hot store are served from the archive.
+        assert!(split_store.exists(DBCol::Block, &[1]).unwrap());
+    }
+
+    struct TestMigrator;
+
+    impl StoreMigrator for TestMigrator {
+        fn check_support(&self, _version: DbVersion) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn migrate(&self, store: &Store, version: DbVersion) -> anyhow::Result<()> {
+            let mut store_update = store.store_update();
+            store_update.set(DBCol::BlockMisc, b"MIGRATED", &version.to_le_bytes());
+            store_update.commit()?;
+            Ok(())
+        }
+    }
+
+    /// An older filesystem archival storage is migrated with the migrator.
+    #[test]
+    fn test_migrate_filesystem_archival_storage() {
+        let home_dir = tempfile::tempdir().unwrap();
+        let store_config = StoreConfig::test_config();
+        let archival_store_config = ArchivalStoreConfig {
+            storage: ArchivalStorageLocation::Filesystem { path: PathBuf::from("archive") },
+        };
+        let archival_config =
+            || ArchivalConfig::new(true, Some(&archival_store_config), None, None);
+
+        let node_storage =
+            NodeStorage::opener(home_dir.path(), &store_config, archival_config()).open().unwrap();
+        node_storage.get_cold_store().unwrap().set_db_version(DB_VERSION - 1).unwrap();
+        drop(node_storage);
+
+        let result = NodeStorage::opener(home_dir.path(), &store_config, archival_config())
+            .open_in_mode(Mode::ReadWriteExisting);
+        assert!(matches!(result, Err(StoreOpenerError::DbVersionMismatch { .. })));
+
+        let node_storage = NodeStorage::opener(home_dir.path(), &store_config, archival_config())
+            .with_migrator(&TestMigrator)
+            .open_in_mode(Mode::ReadWriteExisting)
+            .unwrap();
+        let cold_store = node_storage.get_cold_store().unwrap();
+        assert_eq!(cold_store.get_db_version().unwrap(), Some(DB_VERSION));
+        let migrated = cold_store.get(DBCol::BlockMisc, b"MIGRATED").unwrap().unwrap();
+        assert_eq!(&*migrated, &(DB_VERSION - 1).to_le_bytes());
+    }
 }
@@ -5,6 +5,7 @@ use std::{fmt, io};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_fmt::{AbbrBytes, StorageKey};
+use strum::IntoEnumIterator;
 
 use crate::DBCol;
 use crate::adapter::{StoreAdapter, StoreUpdateAdapter};
@@ -14,6 +15,14 @@ use crate::db::{DBIterator, DBOp, DBSlice, DBTransaction, Database, StoreStatist
 const STATE_COLUMNS: [DBCol; 2] = [DBCol::State, DBCol::FlatState];
 const STATE_FILE_END_MARK: u8 = 255;
 
+/// In-memory copy of all columns of a database.
+///
+/// Created with [`Store::read_contents`] and written back with
+/// [`Store::restore_contents`].  Meant for small databases only, e.g. the ones
+/// of sandbox nodes.
+#[derive(Clone)]
+pub struct StoreContents(Vec<(DBCol, Vec<u8>, Vec<u8>)>);
+
 /// Node’s single storage source.
 ///
 /// The Store holds one of the possible databases:
@@ -168,6 +177,34 @@ impl Store {
         self.storage.write(transaction)
     }
 
+    /// Reads raw contents of all columns into memory.
+    pub fn read_contents(&self) -> io::Result<StoreContents> {
+        let mut contents = Vec::new();
+        for column in DBCol::iter() {
+            for item in self.storage.iter_raw_bytes(column) {
+                let (key, value) = item?;
+                contents.push((column, key.to_vec(), value.to_vec()));
+            }
+        }
+        Ok(StoreContents(contents))
+    }
+
+    /// Replaces contents of all columns with ones previously read with
+    /// [`Self::read_contents`].
+    ///
+    /// This only touches the database.  Any in-memory state derived from it
+    /// (caches, flat storage, memtries) has to be reloaded by the caller.
+    pub fn restore_contents(&self, contents: &StoreContents) -> io::Result<()> {
+        let mut transaction = DBTransaction::new();
+        for column in DBCol::iter() {
+            transaction.delete_all(column);
+        }
+        for (column, key, value) in &contents.0 {
+            transaction.set(*column, key.clone(), value.clone());
+        }
+        self.storage.write(transaction)
+    }
+
     /// If the storage is backed by disk, flushes any in-memory data to disk.
     pub fn flush(&self) -> io::Result<()> {
         self.storage.flush()
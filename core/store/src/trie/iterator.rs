@@ -59,6 +59,13 @@ impl<'a> GenericTrieInternalStorage<TrieStorageNodePtr, ValueHandle> for DiskTri
             ValueHandle::InMemory(value) => panic!("Unexpected in-memory value: {:?}", value),
         }
     }
+
+    fn get_value_hash(&self, value_ref: &ValueHandle) -> CryptoHash {
+        match value_ref {
+            ValueHandle::HashAndSize(value) => value.hash,
+            ValueHandle::InMemory(value) => panic!("Unexpected in-memory value: {:?}", value),
+        }
+    }
 }
 
 pub(crate) type DiskTrieIterator<'a> =
@@ -87,6 +94,15 @@ impl<'a> TrieIterator<'a> {
             TrieIterator::Memtrie(iter) => iter.seek_prefix(key),
         }
     }
+
+    /// Like `next`, but returns the hash of the value instead of the value,
+    /// see [`TrieIteratorImpl::next_value_hash`].
+    pub fn next_value_hash(&mut self) -> Option<Result<(Vec<u8>, CryptoHash), StorageError>> {
+        match self {
+            TrieIterator::Disk(iter) => iter.next_value_hash(),
+            TrieIterator::Memtrie(iter) => iter.next_value_hash(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -96,6 +112,7 @@ mod tests {
     use crate::trie::iterator::TrieIterator;
     use crate::trie::nibble_slice::NibbleSlice;
     use itertools::Itertools;
+    use near_primitives::hash::hash;
     use near_primitives::shard_layout::{ShardLayout, ShardUId};
     use rand::Rng;
     use rand::seq::SliceRandom;
@@ -139,6 +156,14 @@ mod tests {
                 let result2: Vec<_> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
                 assert_eq!(result1, result2);
             }
+            {
+                let lock = trie.lock_for_iter();
+                let mut iter = lock.iter().unwrap();
+                let result1: Vec<_> =
+                    std::iter::from_fn(|| iter.next_value_hash()).map(Result::unwrap).collect();
+                let result2: Vec<_> = map.iter().map(|(k, v)| (k.clone(), hash(v))).collect();
+                assert_eq!(result1, result2);
+            }
             test_seek_prefix(&trie, &map, &[], use_memtries);
 
             for (seek_key, _) in trie_changes.iter() {
@@ -57,6 +57,10 @@ impl<'a> GenericTrieInternalStorage<MemTrieNodeId, FlatStateValue> for MemTrieIt
         };
         Ok(value)
     }
+
+    fn get_value_hash(&self, value_ref: &FlatStateValue) -> CryptoHash {
+        value_ref.to_value_ref().hash
+    }
 }
 
 pub type STMemTrieIterator<'a> =
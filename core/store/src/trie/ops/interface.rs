@@ -1,4 +1,5 @@
 use near_primitives::errors::StorageError;
+use near_primitives::hash::CryptoHash;
 use near_primitives::state::FlatStateValue;
 
 use crate::trie::{TRIE_COSTS, ValueHandle};
@@ -261,4 +262,7 @@ pub trait GenericTrieInternalStorage<GenericTrieNodePtr, GenericValueHandle> {
 
     // Get a value from the storage.
     fn get_and_record_value(&self, value_ref: GenericValueHandle) -> Result<Vec<u8>, StorageError>;
+
+    // Get the hash of a value without reading the value from the storage.
+    fn get_value_hash(&self, value_ref: &GenericValueHandle) -> CryptoHash;
 }
@@ -116,6 +116,43 @@ where
         Ok(())
     }
 
+    /// Like `next`, but returns the hash of the value instead of the value.
+    /// The value is neither read from the storage nor recorded, which makes
+    /// this much cheaper when only the keys or hashes of large values, such
+    /// as contract codes, are needed.
+    pub fn next_value_hash(&mut self) -> Option<Result<(Vec<u8>, CryptoHash), StorageError>> {
+        let value_hash = self
+            .next_value_handle()?
+            .map(|value_ref| self.trie_interface.get_value_hash(&value_ref));
+        Some(value_hash.map(|value_hash| (self.key(), value_hash)))
+    }
+
+    /// Advances the iterator to the next value and returns its handle.
+    fn next_value_handle(&mut self) -> Option<Result<V, StorageError>> {
+        loop {
+            let iter_step = self.iter_step()?;
+
+            let should_prune = match self.prune_condition {
+                Some(ref prune_condition) => prune_condition(&self.key_nibbles),
+                None => false,
+            };
+
+            match (iter_step, should_prune) {
+                (IterStep::Continue, _) => {}
+                (IterStep::PopTrail, _) => {
+                    self.trail.pop();
+                }
+                // Skip processing node if it should be pruned.
+                (_, true) => {}
+                (IterStep::Descend(ptr), false) => match self.descend_into_node(Some(ptr)) {
+                    Ok(_) => {}
+                    Err(e) => return Some(Err(e)),
+                },
+                (IterStep::Value(value_ref), false) => return Some(Ok(value_ref)),
+            }
+        }
+    }
+
     /// Returns the hash of the last node.
     fn seek_nibble_slice(
         &mut self,
@@ -280,31 +317,10 @@ where
     type Item = Result<TrieItem, StorageError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            let iter_step = self.iter_step()?;
-
-            let should_prune = match self.prune_condition {
-                Some(ref prune_condition) => prune_condition(&self.key_nibbles),
-                None => false,
-            };
-
-            match (iter_step, should_prune) {
-                (IterStep::Continue, _) => {}
-                (IterStep::PopTrail, _) => {
-                    self.trail.pop();
-                }
-                // Skip processing node if it should be pruned.
-                (_, true) => {}
-                (IterStep::Descend(ptr), false) => match self.descend_into_node(Some(ptr)) {
-                    Ok(_) => {}
-                    Err(e) => return Some(Err(e)),
-                },
-                (IterStep::Value(value_ref), false) => {
-                    let value = self.trie_interface.get_and_record_value(value_ref);
-                    return Some(value.map(|value| (self.key(), value)));
-                }
-            }
-        }
+        let value = self
+            .next_value_handle()?
+            .and_then(|value_ref| self.trie_interface.get_and_record_value(value_ref));
+        Some(value.map(|value| (self.key(), value)))
     }
 }
 
@@ -192,6 +192,19 @@ impl TrieUpdate {
         self.get(&key).map(|opt| opt.map(|code| ContractCode::new(code, Some(code_hash))))
     }
 
+    /// Returns the global contract code with the given identifier.
+    pub fn get_global_code(
+        &self,
+        identifier: GlobalContractCodeIdentifier,
+    ) -> Result<Option<ContractCode>, StorageError> {
+        let code_hash = match &identifier {
+            GlobalContractCodeIdentifier::CodeHash(code_hash) => Some(*code_hash),
+            GlobalContractCodeIdentifier::AccountId(_) => None,
+        };
+        let key = TrieKey::GlobalContractCode { identifier };
+        self.get(&key).map(|opt| opt.map(|code| ContractCode::new(code, code_hash)))
+    }
+
     /// Returns the size (in num bytes) of the contract code for the given account.
     ///
     /// This is different from `get_code` in that it does not read the code from storage.
@@ -1,9 +1,9 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::{Arg, Command};
 
-use near_crypto::{InMemorySigner, KeyType, SecretKey};
+use near_crypto::{InMemorySigner, KEY_PASSPHRASE_ENV_VAR, KeyFile, KeyType, SecretKey};
 use nearcore::get_default_home;
 
 fn generate_key_to_file(account_id: &str, key: SecretKey, path: &PathBuf) -> std::io::Result<()> {
@@ -11,6 +11,51 @@ fn generate_key_to_file(account_id: &str, key: SecretKey, path: &PathBuf) -> std
     signer.write_to_file(path.as_path())
 }
 
+/// Reads the key file passphrase from the environment or prompts for it.
+fn key_passphrase(prompt: &str) -> std::io::Result<String> {
+    match std::env::var(KEY_PASSPHRASE_ENV_VAR) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => rpassword::prompt_password(prompt),
+    }
+}
+
+/// Encrypts a plain key file or, with `decrypt`, decrypts an encrypted one in
+/// place.
+fn convert_key_file(path: &PathBuf, decrypt: bool) -> std::io::Result<()> {
+    let encrypted = KeyFile::is_encrypted_file(path)?;
+    if encrypted != decrypt {
+        println!("{} is already {}", path.display(), if decrypt { "plain" } else { "encrypted" });
+        return Ok(());
+    }
+    if decrypt {
+        let passphrase = key_passphrase("Key file passphrase: ")?;
+        decrypt_key_file(path, &passphrase)
+    } else {
+        let key_file = load_plain_key_file(path)?;
+        let passphrase = key_passphrase("New key file passphrase: ")?;
+        if std::env::var(KEY_PASSPHRASE_ENV_VAR).is_err()
+            && rpassword::prompt_password("Repeat the passphrase: ")? != passphrase
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "passphrases do not match",
+            ));
+        }
+        key_file.write_encrypted_to_file(path, &passphrase)
+    }
+}
+
+/// Loads a plain key file.  Legacy node key files have an empty account id
+/// which [`KeyFile::from_file`] rejects, so they are loaded the way the node
+/// loads them.
+fn load_plain_key_file(path: &Path) -> std::io::Result<KeyFile> {
+    nearcore::config::load_node_key(path)
+}
+
+fn decrypt_key_file(path: &Path, passphrase: &str) -> std::io::Result<()> {
+    KeyFile::from_file_with_passphrase(path, Some(passphrase))?.write_to_file(path)
+}
+
 fn main() {
     let matches = Command::new("Key-pairs generator")
         .subcommand_required(true)
@@ -47,6 +92,25 @@ fn main() {
             Command::new("node-key").about("Generate key for the node communication."),
         )
         .subcommand(Command::new("validator-key").about("Generate staking key."))
+        .subcommand(
+            Command::new("convert-key-file")
+                .about(format!(
+                    "Encrypt a key file with a passphrase taken from {KEY_PASSPHRASE_ENV_VAR} or prompted for."
+                ))
+                .arg(
+                    Arg::new("key-file")
+                        .long("key-file")
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("decrypt")
+                        .long("decrypt")
+                        .help("Decrypt an encrypted key file instead.")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
         .get_matches();
 
     let home_dir = matches.get_one::<PathBuf>("home").unwrap();
@@ -111,6 +175,39 @@ fn main() {
                 }
             }
         }
+        Some(("convert-key-file", args)) => {
+            let path = args.get_one::<PathBuf>("key-file").unwrap();
+            if let Err(e) = convert_key_file(path, args.get_flag("decrypt")) {
+                eprintln!("Error converting {}: {}", path.display(), e);
+            }
+        }
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_legacy_node_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node_key.json");
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "node");
+        let contents = format!(
+            r#"{{"account_id": "", "public_key": "{}", "secret_key": "{secret_key}"}}"#,
+            secret_key.public_key()
+        );
+        fs::write(&path, contents).unwrap();
+
+        load_plain_key_file(&path).unwrap().write_encrypted_to_file(&path, "passphrase").unwrap();
+        assert!(KeyFile::is_encrypted_file(&path).unwrap());
+        decrypt_key_file(&path, "passphrase").unwrap();
+
+        let key_file = KeyFile::from_file(&path).unwrap();
+        assert_eq!(key_file.account_id.as_str(), "node");
+        assert_eq!(key_file.secret_key, secret_key);
+        // Only the key file is left in the directory.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
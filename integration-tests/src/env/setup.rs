@@ -309,6 +309,7 @@ fn setup(
         network_adapter.clone(),
         config.clone(),
         adv.clone(),
+        None,
     );
 
     let client_adapter_for_partial_witness_actor = LateBoundSender::new();
@@ -0,0 +1,76 @@
+use near_chain_configs::Genesis;
+use near_primitives::account::AccountContract;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::AccountId;
+use near_primitives::version::PROTOCOL_VERSION;
+use near_vm_runner::{
+    ContractCode, ContractRuntimeCache, FilesystemContractRuntimeCache, get_contract_cache_key,
+};
+use nearcore::contract_cache::{
+    export_deployed_contracts, import_contracts, precompile_deployed_contracts,
+};
+
+use crate::env::nightshade_setup::TestEnvNightshadeSetupExt;
+use crate::env::test_env::TestEnv;
+
+/// Checks that the contracts deployed at genesis are compiled by
+/// `precompile_deployed_contracts` and can be moved to another node with an
+/// archive.
+#[test]
+fn test_precompile_deployed_contracts() {
+    let account_id: AccountId = "test0".parse().unwrap();
+    let mut genesis = Genesis::test(vec![account_id.clone(), "test1".parse().unwrap()], 1);
+    let code = near_test_contracts::rs_contract().to_vec();
+    let code_hash = *ContractCode::new(code.clone(), None).hash();
+    let records = genesis.force_read_records().as_mut();
+    for record in records.iter_mut() {
+        if let StateRecord::Account { account_id: id, account } = record {
+            if id == &account_id {
+                account.set_contract(AccountContract::Local(code_hash));
+            }
+        }
+    }
+    records.push(StateRecord::Contract { account_id, code });
+
+    let caches: Vec<FilesystemContractRuntimeCache> =
+        (0..2).map(|_| FilesystemContractRuntimeCache::test().unwrap()).collect();
+    let env = TestEnv::builder(&genesis.config)
+        .clients_count(2)
+        .contract_caches(&caches)
+        .nightshade_runtimes(&genesis)
+        .build();
+    let runtime_config = env.clients[0].runtime_adapter.get_runtime_config(PROTOCOL_VERSION);
+    let key = get_contract_cache_key(code_hash, &runtime_config.wasm_config);
+    assert!(!caches[0].has(&key).unwrap());
+
+    let client = &env.clients[0];
+    let stats = precompile_deployed_contracts(
+        client.chain.chain_store(),
+        client.epoch_manager.as_ref(),
+        client.runtime_adapter.as_ref(),
+    )
+    .unwrap();
+    assert_eq!((stats.shards, stats.contracts), (1, 1));
+    assert!(caches[0].has(&key).unwrap());
+
+    let mut archive = vec![];
+    let exported = export_deployed_contracts(
+        client.chain.chain_store(),
+        client.epoch_manager.as_ref(),
+        client.runtime_adapter.as_ref(),
+        &mut archive,
+    )
+    .unwrap();
+    assert_eq!(exported, 1);
+
+    let client = &env.clients[1];
+    let imported = import_contracts(
+        client.chain.chain_store(),
+        client.epoch_manager.as_ref(),
+        client.runtime_adapter.as_ref(),
+        &mut archive.as_slice(),
+    )
+    .unwrap();
+    assert_eq!(imported, 1);
+    assert_eq!(caches[1].get(&key).unwrap(), caches[0].get(&key).unwrap());
+}
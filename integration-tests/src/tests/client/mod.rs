@@ -5,6 +5,7 @@ mod bug_repro;
 mod catching_up;
 mod chunks_management;
 mod cold_storage;
+mod contract_cache;
 mod cross_shard_tx;
 mod doomslug;
 mod flat_storage;
@@ -166,7 +166,8 @@ fn receive_network_block() {
                 Clock::real(),
                 None,
                 None,
-            );
+            )
+            .unwrap();
             actor_handles.client_actor.do_send(
                 BlockResponse { block, peer_id: PeerInfo::random().id, was_requested: false }
                     .with_span_context(),
@@ -257,7 +258,8 @@ fn produce_block_with_approvals() {
                 Clock::real(),
                 None,
                 None,
-            );
+            )
+            .unwrap();
             actor_handles.client_actor.do_send(
                 BlockResponse {
                     block: block.clone(),
@@ -454,7 +456,8 @@ fn invalid_blocks_common(is_requested: bool) {
                 Clock::real(),
                 None,
                 None,
-            );
+            )
+            .unwrap();
             // Send block with invalid chunk mask
             let mut block = valid_block.clone();
             block.mut_header().set_chunk_mask(vec![]);
@@ -3697,6 +3700,7 @@ mod contract_precompilation_tests {
                 "log_something",
                 &[],
                 &mut logs,
+                None,
                 &MockEpochInfoProvider::default(),
             )
             .unwrap();
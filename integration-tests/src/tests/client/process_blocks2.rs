@@ -97,7 +97,8 @@ fn test_bad_shard_id() {
         congestion_info,
         chunk.bandwidth_requests().cloned(),
         &validator_signer,
-    );
+    )
+    .unwrap();
     modified_chunk.height_included = 2;
     chunks[0] = ShardChunkHeader::V3(modified_chunk);
     block.mut_header().set_chunk_headers_root(Block::compute_chunk_headers_root(&chunks).0);
@@ -254,7 +255,8 @@ fn test_bad_congestion_info_impl(mode: BadCongestionInfoMode) {
         Some(congestion_info),
         chunk.bandwidth_requests().cloned(),
         &validator_signer,
-    );
+    )
+    .unwrap();
     modified_chunk_header.height_included = 2;
 
     let modified_chunk = ShardChunkHeader::V3(modified_chunk_header);
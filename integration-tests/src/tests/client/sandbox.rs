@@ -106,3 +106,42 @@ fn test_patch_account() {
     let test1_after = env.query_account("test1".parse().unwrap());
     assert_eq!(test1_after.amount, 10);
 }
+
+#[test]
+fn test_snapshot_revert() {
+    let (mut env, _signer) = test_setup();
+    let head_before = env.clients[0].chain.head().unwrap();
+    let state_before = env.query_state("test0".parse().unwrap());
+    let test1_before = env.query_account("test1".parse().unwrap());
+    let snapshot_id = env.clients[0].sandbox_snapshot().unwrap();
+
+    let mut test1: Account = test1_before.clone().into();
+    test1.set_amount(10);
+    env.clients[0].chain.patch_state(SandboxStatePatch::new(vec![
+        StateRecord::Account { account_id: "test1".parse().unwrap(), account: test1 },
+        StateRecord::Data {
+            account_id: "test0".parse().unwrap(),
+            data_key: b"hello".to_vec().into(),
+            value: b"world".to_vec().into(),
+        },
+    ]));
+    do_blocks(&mut env, 9, 12);
+    assert_eq!(env.query_account("test1".parse().unwrap()).amount, 10);
+    assert_eq!(env.query_state("test0".parse().unwrap()).len(), 2);
+
+    // Reverting rolls back the head and the state.
+    env.clients[0].sandbox_revert(snapshot_id).unwrap();
+    assert_eq!(env.clients[0].chain.head().unwrap(), head_before);
+    assert_eq!(env.query_account("test1".parse().unwrap()), test1_before);
+    assert_eq!(env.query_state("test0".parse().unwrap()), state_before);
+
+    // Heights of the discarded blocks are skipped and new blocks build on the
+    // snapshot head.
+    assert!(env.clients[0].produce_block(12).unwrap().is_none());
+    let block = env.clients[0].produce_block(13).unwrap().unwrap();
+    assert_eq!(block.header().prev_hash(), &head_before.last_block_hash);
+    env.process_block(0, block, Provenance::PRODUCED);
+    do_blocks(&mut env, 14, 16);
+    assert_eq!(env.query_account("test1".parse().unwrap()), test1_before);
+    assert_eq!(env.query_state("test0".parse().unwrap()), state_before);
+}
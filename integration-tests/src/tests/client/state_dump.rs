@@ -1,4 +1,5 @@
 use assert_matches::assert_matches;
+use borsh::BorshDeserialize;
 
 use near_async::futures::ActixArbiterHandleFutureSpawner;
 use near_async::time::{Clock, Duration};
@@ -8,12 +9,13 @@ use near_chain_configs::ExternalStorageLocation::Filesystem;
 use near_chain_configs::{DumpConfig, Genesis, MutableConfigValue, NEAR_BASE};
 use near_client::ProcessTxResponse;
 use near_client::sync::external::{StateFileType, external_storage_location};
-use near_crypto::InMemorySigner;
+use near_crypto::{InMemorySigner, KeyType, SecretKey};
 use near_o11y::testonly::init_test_logger;
 use near_primitives::block::Tip;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::state::FlatStateValue;
 use near_primitives::state_part::PartId;
+use near_primitives::state_sync::StatePartsManifest;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{BlockHeight, ShardId};
 use near_primitives::validator_signer::{EmptyValidatorSigner, InMemoryValidatorSigner};
@@ -60,6 +62,7 @@ fn slow_test_state_dump() {
         "validator_signer",
     );
 
+    let node_key = SecretKey::from_seed(KeyType::ED25519, "test0");
     let arbiter = actix::Arbiter::new();
     let mut state_sync_dumper = StateSyncDumper {
         clock: Clock::real(),
@@ -69,6 +72,7 @@ fn slow_test_state_dump() {
         shard_tracker,
         runtime,
         validator,
+        node_key: node_key.clone(),
         future_spawner: Arc::new(ActixArbiterHandleFutureSpawner(arbiter.handle())),
         handle: None,
     };
@@ -105,6 +109,27 @@ fn slow_test_state_dump() {
                     all_parts_present = false;
                 }
             }
+            // The manifest is uploaded after all parts of the shard.
+            let path = root_dir.path().join(external_storage_location(
+                "unittest",
+                &epoch_id,
+                epoch_height,
+                shard_id,
+                &StateFileType::StatePartsManifest,
+            ));
+            match std::fs::read(&path) {
+                Ok(data) => {
+                    let manifest = StatePartsManifest::try_from_slice(&data).unwrap();
+                    assert!(manifest.verify_signature());
+                    assert_eq!(manifest.signer, node_key.public_key());
+                    assert_eq!(manifest.shard_id, shard_id);
+                    assert_eq!(manifest.parts.len() as u64, num_parts);
+                }
+                Err(_) => {
+                    tracing::info!("Missing {:?}", path);
+                    all_parts_present = false;
+                }
+            }
         }
         if all_parts_present {
             break;
@@ -179,6 +204,7 @@ fn run_state_sync_with_dumped_parts(
         shard_tracker,
         runtime,
         validator,
+        node_key: SecretKey::from_seed(KeyType::ED25519, "test0"),
         future_spawner: Arc::new(ActixArbiterHandleFutureSpawner(arbiter.handle())),
         handle: None,
     };
@@ -389,6 +389,7 @@ fn ultra_slow_test_sync_state_dump() {
                                         num_concurrent_requests: 1,
                                         num_concurrent_requests_during_catchup: 1,
                                         external_storage_fallback_threshold: 0,
+                                        manifest_signers: vec![],
                                     });
 
                                 let nearcore::NearNode {
@@ -131,6 +131,7 @@ fn setup_network_node(
         network_adapter.as_multi_sender(),
         client_config.clone(),
         adv,
+        None,
     );
     let tx_processor_config = TxRequestHandlerConfig {
         handler_threads: client_config.transaction_request_handler_threads,
@@ -124,6 +124,7 @@ fn test_view_call() {
         "run_test",
         &[],
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
 
@@ -153,6 +154,7 @@ fn test_view_call_try_changing_storage() {
         "run_test_with_storage_change",
         &[],
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
     let err = result.unwrap_err();
@@ -186,6 +188,7 @@ fn test_view_call_with_args() {
         "sum_with_input",
         &args,
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
     assert_eq!(view_call_result.unwrap(), 3u64.to_le_bytes().to_vec());
@@ -416,9 +419,45 @@ fn test_log_when_panic() {
             "panic_after_logging",
             &[],
             &mut logs,
+            None,
             &MockEpochInfoProvider::default(),
         )
         .unwrap_err();
 
     assert_eq!(logs, vec!["hello".to_string()]);
 }
+
+#[test]
+fn test_trace_host_calls() {
+    let (viewer, root) = get_test_trie_viewer();
+    let view_state = ViewApplyState {
+        block_height: 1,
+        prev_block_hash: CryptoHash::default(),
+        block_hash: CryptoHash::default(),
+        shard_id: ShardUId::single_shard().shard_id(),
+        epoch_id: EpochId::default(),
+        epoch_height: 0,
+        block_timestamp: 1,
+        current_protocol_version: PROTOCOL_VERSION,
+        cache: None,
+    };
+    let mut host_calls = vec![];
+    viewer
+        .call_function(
+            root,
+            view_state,
+            &"test.contract".parse().unwrap(),
+            "panic_after_logging",
+            &[],
+            &mut vec![],
+            Some(&mut host_calls),
+            &MockEpochInfoProvider::default(),
+        )
+        .unwrap_err();
+
+    let names: Vec<_> = host_calls.iter().map(|call| call.name.as_str()).collect();
+    assert_eq!(names, vec!["log_utf8", "panic_utf8"]);
+    assert_eq!(host_calls[0].args[0], ("len".to_string(), 5));
+    assert!(host_calls[0].error.is_none());
+    assert!(host_calls[1].error.as_ref().unwrap().contains("WAT?"));
+}
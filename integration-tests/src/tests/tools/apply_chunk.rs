@@ -120,6 +120,7 @@ fn test_apply_chunk() {
                     None,
                     Some(rng),
                     StorageSource::Trie,
+                    false,
                 )
                 .unwrap();
                 assert_eq!(apply_result.new_root, new_root);
@@ -225,6 +226,7 @@ fn test_apply_tx_apply_receipt() {
                         store.clone(),
                         receipt.get_hash(),
                         StorageSource::Trie,
+                        false,
                     )
                     .unwrap();
                     assert_eq!(results.len(), 1);
@@ -276,6 +278,7 @@ fn test_apply_tx_apply_receipt() {
                 store.clone(),
                 receipt.get_hash(),
                 StorageSource::Trie,
+                false,
             )
             .unwrap();
             for result in results {
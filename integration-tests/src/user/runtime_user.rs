@@ -210,6 +210,8 @@ impl RuntimeUser {
             migration_flags: MigrationFlags::default(),
             congestion_info,
             bandwidth_requests: BlockBandwidthRequests::empty(),
+            trace_host_calls: false,
+            profile_wasm_functions: false,
         }
     }
 
@@ -347,6 +349,7 @@ impl User for RuntimeUser {
                 method_name,
                 args,
                 &mut result.logs,
+                None,
                 &self.epoch_info_provider,
             )
             .map_err(|err| err.to_string())?;
@@ -46,7 +46,9 @@ use near_primitives::types::{
     ShardId,
 };
 use near_primitives::utils::{from_timestamp, get_num_seats_per_shard};
-use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+use near_primitives::validator_signer::{
+    InMemoryValidatorSigner, RemoteValidatorSigner, SignerAuthKey, ValidatorSigner,
+};
 use near_primitives::version::PROTOCOL_VERSION;
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::RosettaRpcConfig;
@@ -231,12 +233,35 @@ impl Default for Consensus {
     }
 }
 
+fn default_remote_signer_timeout() -> Duration {
+    Duration::seconds(1)
+}
+
+/// Configuration of an external signer process holding the validator key.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RemoteSignerConfig {
+    /// Address of the signer, `unix:<path>` for a Unix socket or `<host>:<port>`
+    /// for a TCP socket.
+    pub address: String,
+    /// Timeout for connecting to the signer and for every signing request.
+    #[serde(default = "default_remote_signer_timeout")]
+    #[serde(with = "near_async::time::serde_duration_as_std")]
+    pub timeout: Duration,
+    /// File with the secret shared with the signer, relative to the home
+    /// directory.  Every request and response is authenticated with it.
+    pub auth_key_file: PathBuf,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 #[serde(default)]
 pub struct Config {
     pub genesis_file: String,
     pub genesis_records_file: Option<String>,
     pub validator_key_file: String,
+    /// If set, the validator key is held by an external signer process and
+    /// `validator_key_file` is not used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<RemoteSignerConfig>,
     pub node_key_file: String,
     #[cfg(feature = "json_rpc")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -309,6 +334,8 @@ pub struct Config {
     /// Setting this value too low (<1MB) on the validator might lead to production of smaller
     /// chunks and underutilized the capacity of the network.
     pub transaction_pool_size_limit: Option<u64>,
+    /// Per-account quotas, eviction and priority ordering of the transaction pool.
+    pub transaction_pool: TransactionPoolConfig,
     // Configuration for resharding.
     pub resharding_config: ReshardingConfig,
     /// If the node is not a chunk producer within that many blocks, then route
@@ -350,6 +377,14 @@ pub struct Config {
     /// as a large number of incoming witnesses could cause denial of service.
     pub save_latest_witnesses: bool,
     pub transaction_request_handler_threads: usize,
+    /// Compile all contracts deployed on the tracked shards in the background
+    /// when the node starts, so that the first calls to them don't pay the
+    /// compilation latency.
+    pub precompile_contracts_on_start: bool,
+    /// Audit the balances of every chunk applied to update a tracked shard, so
+    /// that they are served by `EXPERIMENTAL_chunk_balance_audit` and the
+    /// indexer.  This slows down chunk application.
+    pub audit_chunk_balances: bool,
 }
 
 fn is_false(value: &bool) -> bool {
@@ -361,6 +396,7 @@ impl Default for Config {
             genesis_file: GENESIS_CONFIG_FILENAME.to_string(),
             genesis_records_file: None,
             validator_key_file: VALIDATOR_KEY_FILE.to_string(),
+            remote_signer: None,
             node_key_file: NODE_KEY_FILE.to_string(),
             #[cfg(feature = "json_rpc")]
             rpc: Some(RpcConfig::default()),
@@ -393,6 +429,7 @@ impl Default for Config {
             epoch_sync: default_epoch_sync(),
             state_sync_enabled: default_state_sync_enabled(),
             transaction_pool_size_limit: default_transaction_pool_size_limit(),
+            transaction_pool: TransactionPoolConfig::default(),
             enable_multiline_logging: default_enable_multiline_logging(),
             resharding_config: ReshardingConfig::default(),
             tx_routing_height_horizon: default_tx_routing_height_horizon(),
@@ -404,6 +441,8 @@ impl Default for Config {
             max_loaded_contracts: 256,
             save_latest_witnesses: false,
             transaction_request_handler_threads: 4,
+            precompile_contracts_on_start: false,
+            audit_chunk_balances: false,
         }
     }
 }
@@ -575,6 +614,7 @@ impl NearConfig {
                 state_sync: config.state_sync.unwrap_or_default(),
                 epoch_sync: config.epoch_sync.unwrap_or_default(),
                 transaction_pool_size_limit: config.transaction_pool_size_limit,
+                transaction_pool: config.transaction_pool,
                 enable_multiline_logging: config.enable_multiline_logging.unwrap_or(true),
                 resharding_config: MutableConfigValue::new(
                     config.resharding_config,
@@ -590,6 +630,7 @@ impl NearConfig {
                 orphan_state_witness_max_size: config.orphan_state_witness_max_size,
                 save_latest_witnesses: config.save_latest_witnesses,
                 transaction_request_handler_threads: config.transaction_request_handler_threads,
+                audit_chunk_balances: config.audit_chunk_balances,
             },
             #[cfg(feature = "tx_generator")]
             tx_generator: config.tx_generator,
@@ -651,6 +692,19 @@ impl NightshadeRuntime {
         store: Store,
         config: &NearConfig,
         epoch_manager: Arc<EpochManagerHandle>,
+    ) -> std::io::Result<Arc<NightshadeRuntime>> {
+        Self::from_config_with_wasm_profiling(home_dir, store, config, epoch_manager, false)
+    }
+
+    /// Like `from_config`, but with `profile_wasm_functions` set the runtime
+    /// records the gas burnt by the functions of the called contracts, see
+    /// `ApplyState::profile_wasm_functions`.  Meant for debugging tools only.
+    pub fn from_config_with_wasm_profiling(
+        home_dir: &Path,
+        store: Store,
+        config: &NearConfig,
+        epoch_manager: Arc<EpochManagerHandle>,
+        profile_wasm_functions: bool,
     ) -> std::io::Result<Arc<NightshadeRuntime>> {
         #[allow(clippy::or_fun_call)] // Closure cannot return reference to a temporary value
         let state_snapshot_config =
@@ -681,6 +735,8 @@ impl NightshadeRuntime {
             config.config.gc.gc_num_epochs_to_keep(),
             TrieConfig::from_store_config(&config.config.store),
             state_snapshot_config,
+            config.client_config.audit_chunk_balances,
+            profile_wasm_functions,
         ))
     }
 }
@@ -694,16 +750,23 @@ impl NightshadeRuntime {
 /// If the file does not exist and `account_id` is not `None`, generates a new
 /// key, saves it in the file and returns it.  If `test_seed` is not `None`, the
 /// key generation algorithm is seeded with given string making it fully
-/// deterministic.
+/// deterministic.  If `key_passphrase` is not `None`, the secret key is
+/// encrypted with it.
 fn generate_or_load_key(
     home_dir: &Path,
     filename: &str,
     account_id: Option<AccountId>,
     test_seed: Option<&str>,
+    key_passphrase: Option<&str>,
 ) -> anyhow::Result<Option<Signer>> {
     let path = home_dir.join(filename);
     if path.exists() {
-        let signer = InMemorySigner::from_file(&path)
+        let key_file = match key_passphrase {
+            Some(passphrase) => KeyFile::from_file_with_passphrase(&path, Some(passphrase)),
+            None => KeyFile::from_file(&path),
+        };
+        let signer = key_file
+            .map(|key_file| Signer::from(InMemorySigner::from(key_file)))
             .with_context(|| format!("Failed initializing signer from {}", path.display()))?;
         if let Some(account_id) = account_id {
             if account_id != signer.get_account_id() {
@@ -724,9 +787,13 @@ fn generate_or_load_key(
             InMemorySigner::from_random(account_id, KeyType::ED25519).into()
         };
         info!(target: "near", "Using key {} for {}", signer.public_key(), signer.get_account_id());
-        signer
-            .write_to_file(&path)
-            .with_context(|| anyhow!("Failed saving key to ‘{}’", path.display()))?;
+        match key_passphrase {
+            Some(passphrase) => {
+                KeyFile::from(signer.clone()).write_encrypted_to_file(&path, passphrase)
+            }
+            None => signer.write_to_file(&path),
+        }
+        .with_context(|| anyhow!("Failed saving key to ‘{}’", path.display()))?;
         Ok(Some(signer))
     } else {
         Ok(None)
@@ -741,15 +808,34 @@ fn generate_or_load_keys(
     chain_id: &str,
     account_id: Option<AccountId>,
     test_seed: Option<&str>,
+    key_passphrase: Option<&str>,
 ) -> anyhow::Result<()> {
-    generate_or_load_key(dir, &config.node_key_file, Some("node".parse().unwrap()), None)?;
+    generate_or_load_key(
+        dir,
+        &config.node_key_file,
+        Some("node".parse().unwrap()),
+        None,
+        key_passphrase,
+    )?;
     match chain_id {
         near_primitives::chains::MAINNET | near_primitives::chains::TESTNET => {
-            generate_or_load_key(dir, &config.validator_key_file, account_id, None)?;
+            generate_or_load_key(
+                dir,
+                &config.validator_key_file,
+                account_id,
+                None,
+                key_passphrase,
+            )?;
         }
         _ => {
             let account_id = account_id.unwrap_or_else(|| "test.near".parse().unwrap());
-            generate_or_load_key(dir, &config.validator_key_file, Some(account_id), test_seed)?;
+            generate_or_load_key(
+                dir,
+                &config.validator_key_file,
+                Some(account_id),
+                test_seed,
+                key_passphrase,
+            )?;
         }
     }
     Ok(())
@@ -803,6 +889,7 @@ pub fn init_configs(
     download_config_url: Option<&str>,
     boot_nodes: Option<&str>,
     max_gas_burnt_view: Option<Gas>,
+    key_passphrase: Option<&str>,
 ) -> anyhow::Result<()> {
     fs::create_dir_all(dir).with_context(|| anyhow!("Failed to create directory {:?}", dir))?;
 
@@ -826,7 +913,7 @@ pub fn init_configs(
         })?;
         // Check that `node_key.json` and `validator_key.json` exist.
         // Create if needed and they don't exist.
-        generate_or_load_keys(dir, &config, &chain_id, account_id, test_seed)?;
+        generate_or_load_keys(dir, &config, &chain_id, account_id, test_seed, key_passphrase)?;
         return Ok(());
     }
 
@@ -857,7 +944,7 @@ pub fn init_configs(
     }
 
     // Before finalizing the Config and Genesis, make sure the node and validator keys exist.
-    generate_or_load_keys(dir, &config, &chain_id, account_id, test_seed)?;
+    generate_or_load_keys(dir, &config, &chain_id, account_id, test_seed, key_passphrase)?;
     match chain_id.as_ref() {
         near_primitives::chains::MAINNET | near_primitives::chains::TESTNET => {
             if test_seed.is_some() {
@@ -1350,14 +1437,18 @@ struct NodeKeyFile {
 
 impl NodeKeyFile {
     // the file can be JSON with comments
-    fn from_file(path: &Path) -> std::io::Result<Self> {
+    fn from_file(path: &Path) -> std::io::Result<KeyFile> {
+        // Encrypted node key files are always written with the account id set.
+        if KeyFile::is_encrypted_file(path)? {
+            return KeyFile::from_file(path);
+        }
         let mut file = File::open(path)?;
         let mut json_str = String::new();
         file.read_to_string(&mut json_str)?;
 
         let json_str_without_comments = near_config_utils::strip_comments_from_json_str(&json_str)?;
 
-        Ok(serde_json::from_str(&json_str_without_comments)?)
+        Ok(serde_json::from_str::<Self>(&json_str_without_comments)?.into())
     }
 }
 
@@ -1377,6 +1468,35 @@ impl From<NodeKeyFile> for KeyFile {
     }
 }
 
+/// Loads a node key file.  Unlike [`KeyFile::from_file`] it accepts legacy
+/// node key files with an empty account id, which is replaced with `node`.
+pub fn load_node_key(node_key_file: &Path) -> std::io::Result<KeyFile> {
+    NodeKeyFile::from_file(node_key_file)
+}
+
+/// Connects to the remote signer if one is configured or loads the validator
+/// key from `validator_key_file` otherwise.
+pub fn load_validator_signer(
+    dir: &Path,
+    config: &Config,
+) -> anyhow::Result<Option<Arc<ValidatorSigner>>> {
+    if let Some(remote_signer) = &config.remote_signer {
+        let auth_key_file = dir.join(&remote_signer.auth_key_file);
+        let key = SignerAuthKey::from_file(&auth_key_file)
+            .with_context(|| format!("Failed reading {}", auth_key_file.display()))?;
+        let signer = RemoteValidatorSigner::connect(
+            &remote_signer.address,
+            remote_signer.timeout.unsigned_abs(),
+            key,
+        )
+        .with_context(|| {
+            format!("Failed connecting to the remote signer at {}", remote_signer.address)
+        })?;
+        return Ok(Some(Arc::new(signer)));
+    }
+    load_validator_key(&dir.join(&config.validator_key_file))
+}
+
 pub fn load_validator_key(validator_file: &Path) -> anyhow::Result<Option<Arc<ValidatorSigner>>> {
     if !validator_file.exists() {
         return Ok(None);
@@ -1404,8 +1524,7 @@ pub fn load_config(
         validation_errors.push_errors(e)
     };
 
-    let validator_file: PathBuf = dir.join(&config.validator_key_file);
-    let validator_signer = match load_validator_key(&validator_file) {
+    let validator_signer = match load_validator_signer(dir, &config) {
         Ok(validator_signer) => validator_signer,
         Err(e) => {
             validation_errors.push_validator_key_file_error(e.to_string());
@@ -1417,9 +1536,9 @@ pub fn load_config(
     let network_signer_result = NodeKeyFile::from_file(&node_key_path);
     let network_signer = match network_signer_result {
         Ok(node_key_file) => Some(node_key_file),
-        Err(_) => {
+        Err(err) => {
             let error_message =
-                format!("Failed reading node key file from {}", node_key_path.display());
+                format!("Failed reading node key file from {}: {err}", node_key_path.display());
             validation_errors.push_node_key_file_error(error_message);
             None
         }
@@ -1458,7 +1577,7 @@ pub fn load_config(
     let near_config = NearConfig::new(
         config,
         genesis.unwrap(),
-        network_signer.unwrap().into(),
+        network_signer.unwrap(),
         MutableConfigValue::new(validator_signer, "validator_signer"),
     )?;
     Ok(near_config)
@@ -1498,7 +1617,7 @@ mod tests {
     use itertools::Itertools;
     use near_async::time::Duration;
     use near_chain_configs::{GCConfig, Genesis, GenesisValidationMode};
-    use near_crypto::InMemorySigner;
+    use near_crypto::{InMemorySigner, KeyFile};
     use near_primitives::types::{AccountId, NumShards, ShardId};
     use tempfile::tempdir;
 
@@ -1525,6 +1644,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         )
         .unwrap();
         let genesis = Genesis::from_file(
@@ -1582,6 +1702,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         )
         .unwrap();
 
@@ -1615,6 +1736,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         )
         .unwrap();
 
@@ -1847,6 +1969,7 @@ mod tests {
                 filename,
                 if account.is_empty() { None } else { Some(account.parse().unwrap()) },
                 if seed.is_empty() { None } else { Some(seed) },
+                None,
             )
         };
 
@@ -1866,7 +1989,7 @@ mod tests {
         };
 
         // account_id == None → do nothing, return None
-        assert!(generate_or_load_key(home_dir, "key", None, None).unwrap().is_none());
+        assert!(generate_or_load_key(home_dir, "key", None, None, None).unwrap().is_none());
         assert!(!home_dir.join("key").exists());
 
         // account_id == Some, file doesn’t exist → create new key
@@ -1892,5 +2015,13 @@ mod tests {
             writeln!(file, "not JSON").unwrap();
         }
         test_err("bad_key", "fred", "");
+
+        // key_passphrase == Some → the key file is encrypted and can be loaded back
+        let account = Some("fred".parse().unwrap());
+        let key = generate_or_load_key(home_dir, "enc", account, None, Some("pass")).unwrap();
+        assert!(KeyFile::is_encrypted_file(&home_dir.join("enc")).unwrap());
+        let loaded = generate_or_load_key(home_dir, "enc", None, None, Some("pass")).unwrap();
+        assert!(key == loaded);
+        assert!(generate_or_load_key(home_dir, "enc", None, None, Some("wrong")).is_err());
     }
 }
@@ -1,5 +1,6 @@
 use near_chain_configs::{ExternalStorageLocation, SyncConfig};
 use near_config_utils::{ValidationError, ValidationErrors};
+use near_store::config::ArchivalStorageLocation;
 use std::collections::HashSet;
 use std::path::Path;
 
@@ -46,6 +47,17 @@ impl<'a> ConfigValidator<'a> {
             self.validation_errors.push_config_semantics_error(error_message);
         }
 
+        if self.config.archival_storage.as_ref().is_some_and(|config| {
+            matches!(config.storage, ArchivalStorageLocation::Filesystem { .. })
+        }) && self.config.save_trie_changes != Some(true)
+        {
+            let error_message = format!(
+                "Filesystem archival_storage is configured, but save_trie_changes is {:?}. Trie changes should be saved to support archival storage.",
+                self.config.save_trie_changes
+            );
+            self.validation_errors.push_config_semantics_error(error_message);
+        }
+
         if self.config.consensus.min_block_production_delay
             > self.config.consensus.max_block_production_delay
         {
@@ -124,6 +136,12 @@ impl<'a> ConfigValidator<'a> {
                             self.validation_errors.push_config_semantics_error(error_message);
                         }
                     }
+                    ExternalStorageLocation::Http { .. } => {
+                        let error_message = format!(
+                            "'config.state_sync.dump.location.Http' is not supported because HTTP mirrors are read-only."
+                        );
+                        self.validation_errors.push_config_semantics_error(error_message);
+                    }
                 }
 
                 if let Some(credentials_file) = &dump_config.credentials_file {
@@ -163,6 +181,23 @@ impl<'a> ConfigValidator<'a> {
                                 self.validation_errors.push_config_semantics_error(error_message);
                             }
                         }
+                        ExternalStorageLocation::Http { urls } => {
+                            if urls.is_empty() {
+                                let error_message = format!(
+                                    "'config.state_sync.sync.ExternalStorage.location.Http.urls' needs to contain at least one URL when 'config.state_sync.sync.ExternalStorage.location.Http' is present."
+                                );
+                                self.validation_errors.push_config_semantics_error(error_message);
+                            }
+                            for url in urls {
+                                if !url.starts_with("http://") && !url.starts_with("https://") {
+                                    let error_message = format!(
+                                        "'config.state_sync.sync.ExternalStorage.location.Http.urls' contains '{url}' which is not an http:// or https:// URL."
+                                    );
+                                    self.validation_errors
+                                        .push_config_semantics_error(error_message);
+                                }
+                            }
+                        }
                     }
                     if config.num_concurrent_requests == 0 {
                         let error_message = format!(
@@ -263,6 +298,19 @@ mod tests {
         validate_config(&config).unwrap();
     }
 
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: Filesystem archival_storage is configured, but save_trie_changes is None. Trie changes should be saved to support archival storage."
+    )]
+    fn test_filesystem_archival_storage_without_save_trie_changes() {
+        let mut config = Config::default();
+        config.archive = true;
+        config.archival_storage = Some(near_store::config::ArchivalStoreConfig {
+            storage: ArchivalStorageLocation::Filesystem { path: "archive".into() },
+        });
+        validate_config(&config).unwrap();
+    }
+
     #[test]
     #[should_panic(
         expected = "\\nconfig.json semantic issue: 'config.tx_routing_height_horizon' needs to be at least 2, got 1."
@@ -0,0 +1,185 @@
+//! Pre-warming of the compiled contract cache.
+//!
+//! The cache is normally filled lazily, so the first call to every contract
+//! after a fresh node start pays the full compilation latency.  Precompiling
+//! all contracts deployed on the tracked shards, global contracts included,
+//! avoids that.  Contracts received during state sync are precompiled when
+//! the state parts are applied, see `RuntimeAdapter::apply_state_part`.
+use crate::NearConfig;
+use near_chain::types::RuntimeAdapter;
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_epoch_manager::EpochManagerAdapter;
+use near_primitives::hash::CryptoHash;
+use near_primitives::trie_key::col;
+use near_store::{Store, Trie};
+use near_vm_runner::logic::Config;
+use near_vm_runner::{ContractCode, export_contract_cache, import_contract_cache};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Maximum number of contracts held in memory while waiting for compilation.
+const PRECOMPILE_BATCH_SIZE: usize = 64;
+
+#[derive(Debug, Default)]
+pub struct PrecompileStats {
+    /// Number of shards whose contracts were precompiled.
+    pub shards: usize,
+    /// Number of distinct contracts, including those which were already in
+    /// the cache.
+    pub contracts: usize,
+}
+
+/// Compiles all contracts deployed on the shards tracked at the chain head,
+/// including global contracts, and stores them in the compiled contract cache
+/// of the runtime.  Contracts which are already in the cache are skipped.
+pub fn precompile_deployed_contracts(
+    chain_store: &ChainStore,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+) -> anyhow::Result<PrecompileStats> {
+    let head = chain_store.head()?;
+    let shards = deployed_contracts(chain_store, epoch_manager, runtime)?;
+    let mut stats = PrecompileStats { shards: shards.len(), contracts: 0 };
+    for ShardContracts { trie, contracts } in shards {
+        for chunk in contracts.chunks(PRECOMPILE_BATCH_SIZE) {
+            let mut batch = Vec::with_capacity(chunk.len());
+            for (key, code_hash) in chunk {
+                let code = trie
+                    .get(key)?
+                    .ok_or_else(|| anyhow::anyhow!("missing code of contract {code_hash}"))?;
+                batch.push(ContractCode::new(code, Some(*code_hash)));
+            }
+            stats.contracts += batch.len();
+            runtime.precompile_contracts(&head.epoch_id, batch)?;
+        }
+    }
+    Ok(stats)
+}
+
+/// Writes the compiled contracts deployed on the shards tracked at the chain
+/// head to an archive, see [`export_contract_cache`].  Returns the number of
+/// exported contracts.
+pub fn export_deployed_contracts(
+    chain_store: &ChainStore,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+    writer: &mut impl std::io::Write,
+) -> anyhow::Result<usize> {
+    let code_hashes = deployed_contracts(chain_store, epoch_manager, runtime)?
+        .into_iter()
+        .flat_map(|shard| shard.contracts)
+        .map(|(_, code_hash)| code_hash)
+        .collect();
+    let config = head_vm_config(chain_store, epoch_manager, runtime)?;
+    Ok(export_contract_cache(runtime.compiled_contract_cache(), code_hashes, &config, writer)?)
+}
+
+/// Adds the compiled contracts of an archive written by
+/// [`export_deployed_contracts`] to the cache, see [`import_contract_cache`].
+/// Returns the number of added contracts.
+pub fn import_contracts(
+    chain_store: &ChainStore,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+    reader: &mut impl std::io::Read,
+) -> anyhow::Result<usize> {
+    let config = head_vm_config(chain_store, epoch_manager, runtime)?;
+    Ok(import_contract_cache(runtime.compiled_contract_cache(), &config, reader)?)
+}
+
+/// Returns the VM configuration of the epoch of the chain head, which
+/// determines the keys of the compiled contracts.
+fn head_vm_config(
+    chain_store: &ChainStore,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+) -> anyhow::Result<Arc<Config>> {
+    let head = chain_store.head()?;
+    let protocol_version = epoch_manager.get_epoch_protocol_version(&head.epoch_id)?;
+    Ok(Arc::clone(&runtime.get_runtime_config(protocol_version).wasm_config))
+}
+
+/// Contracts deployed on one of the tracked shards.
+struct ShardContracts {
+    trie: Trie,
+    /// Trie keys and code hashes of the contracts.
+    contracts: Vec<(Vec<u8>, CryptoHash)>,
+}
+
+/// Lists the distinct contracts deployed on the shards tracked at the chain
+/// head, including global contracts.  Only the hashes of the contracts are
+/// read, so that the memtrie lock is held for as short as possible.
+fn deployed_contracts(
+    chain_store: &ChainStore,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+) -> anyhow::Result<Vec<ShardContracts>> {
+    let head = chain_store.head()?;
+    let shard_layout = epoch_manager.get_shard_layout(&head.epoch_id)?;
+    let mut shards = vec![];
+    let mut code_hashes = HashSet::new();
+    for shard_uid in shard_layout.shard_uids() {
+        let chunk_extra = match chain_store.get_chunk_extra(&head.last_block_hash, &shard_uid) {
+            Ok(chunk_extra) => chunk_extra,
+            // The shard is not tracked.
+            Err(near_chain::Error::DBNotFoundErr(_)) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        tracing::debug!(target: "contract_cache", %shard_uid, "reading deployed contracts");
+        let trie = runtime.get_trie_for_shard(
+            shard_uid.shard_id(),
+            &head.last_block_hash,
+            *chunk_extra.state_root(),
+            false,
+        )?;
+        let mut contracts = vec![];
+        let lock = trie.lock_for_iter();
+        // Global contracts are stored in every shard, `code_hashes`
+        // deduplicates them along with the contracts deployed many times.
+        for column in [col::CONTRACT_CODE, col::GLOBAL_CONTRACT_CODE] {
+            let mut iter = lock.iter()?;
+            iter.seek_prefix([column])?;
+            while let Some(item) = iter.next_value_hash() {
+                let (key, code_hash) = item?;
+                if key.first() != Some(&column) {
+                    break;
+                }
+                if code_hashes.insert(code_hash) {
+                    contracts.push((key, code_hash));
+                }
+            }
+        }
+        drop(lock);
+        shards.push(ShardContracts { trie, contracts });
+    }
+    Ok(shards)
+}
+
+/// Spawns a thread running [`precompile_deployed_contracts`] once.
+pub fn spawn_contract_precompilation(
+    config: &NearConfig,
+    store: Store,
+    epoch_manager: Arc<dyn EpochManagerAdapter>,
+    runtime: Arc<dyn RuntimeAdapter>,
+) -> anyhow::Result<()> {
+    let chain_store =
+        ChainStore::new(store, false, config.genesis.config.transaction_validity_period);
+    tracing::info!(target: "contract_cache", "spawning contract precompilation");
+    std::thread::Builder::new().name("contract_precompilation".to_string()).spawn(move || {
+        match precompile_deployed_contracts(&chain_store, epoch_manager.as_ref(), runtime.as_ref())
+        {
+            Ok(stats) => tracing::info!(
+                target: "contract_cache",
+                shards = stats.shards,
+                contracts = stats.contracts,
+                "precompiled deployed contracts"
+            ),
+            Err(err) => tracing::warn!(
+                target: "contract_cache",
+                ?err,
+                "failed to precompile deployed contracts"
+            ),
+        }
+    })?;
+    Ok(())
+}
@@ -110,7 +110,7 @@ fn read_validator_key(
     config: &Config,
 ) -> Result<Option<Arc<ValidatorSigner>>, UpdatableConfigLoaderError> {
     let validator_file: PathBuf = home_dir.join(&config.validator_key_file);
-    match crate::config::load_validator_key(&validator_file) {
+    match crate::config::load_validator_signer(home_dir, config) {
         Ok(Some(validator_signer)) => {
             tracing::info!(target: "neard", "Hot loading validator key {}.", validator_file.display());
             Ok(Some(validator_signer))
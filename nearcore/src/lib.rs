@@ -5,6 +5,7 @@ use crate::entity_debug::EntityDebugHandlerImpl;
 use crate::metrics::spawn_trie_metrics_loop;
 
 use crate::cold_storage::spawn_cold_store_loop;
+use crate::contract_cache::spawn_contract_precompilation;
 use crate::state_sync::StateSyncDumper;
 use actix::{Actor, Addr};
 use actix_rt::ArbiterHandle;
@@ -28,9 +29,9 @@ use near_chunks::shards_manager_actor::start_shards_manager;
 use near_client::adapter::client_sender_for_network;
 use near_client::gc_actor::GCActor;
 use near_client::{
-    ClientActor, ConfigUpdater, PartialWitnessActor, StartClientResult, TxRequestHandlerActor,
-    TxRequestHandlerConfig, ViewClientActor, ViewClientActorInner, spawn_tx_request_handler_actor,
-    start_client,
+    ClientActor, ColdStoreFallback, ConfigUpdater, PartialWitnessActor, StartClientResult,
+    TxRequestHandlerActor, TxRequestHandlerConfig, ViewClientActor, ViewClientActorInner,
+    spawn_tx_request_handler_actor, start_client,
 };
 use near_epoch_manager::EpochManager;
 use near_epoch_manager::EpochManagerAdapter;
@@ -53,6 +54,7 @@ pub mod config;
 #[cfg(test)]
 mod config_duration_test;
 mod config_validate;
+pub mod contract_cache;
 mod download_file;
 pub mod dyn_config;
 #[cfg(feature = "json_rpc")]
@@ -192,13 +194,9 @@ fn get_split_store(config: &NearConfig, storage: &NodeStorage) -> anyhow::Result
         return Ok(None);
     }
 
-    // SplitStore should only be used if cold store is configured.
-    if config.config.cold_store.is_none() {
-        return Ok(None);
-    }
-
-    // SplitStore should only be used in the view client if it is enabled.
-    if !config.config.split_storage.as_ref().is_some_and(|c| c.enable_split_storage_view_client) {
+    // SplitStore should only be used if cold store (either the cold RocksDB or
+    // the filesystem archival storage) is configured.
+    if !storage.has_cold() {
         return Ok(None);
     }
 
@@ -289,11 +287,14 @@ pub fn start_with_config_and_synchronization(
     )
     .context("could not create the transaction runtime")?;
 
-    // Get the split store. If split store is some then create a new set of structures for
-    // the view client. Otherwise just re-use the existing ones.
+    // Get the split store. If split store is some and it is enabled for the view client then
+    // create a new set of structures for the view client. Otherwise just re-use the existing ones.
     let split_store = get_split_store(&config, &storage)?;
+    let view_split_store = split_store.clone().filter(|_| {
+        config.config.split_storage.as_ref().is_some_and(|c| c.enable_split_storage_view_client)
+    });
     let (view_epoch_manager, view_shard_tracker, view_runtime) =
-        if let Some(split_store) = &split_store {
+        if let Some(split_store) = &view_split_store {
             let view_epoch_manager = EpochManager::new_arc_handle(
                 split_store.clone(),
                 &config.genesis.config,
@@ -314,9 +315,42 @@ pub fn start_with_config_and_synchronization(
         } else {
             (epoch_manager.clone(), shard_tracker.clone(), runtime.clone())
         };
+    // Even if the view client doesn't read from the split store, it falls back to it for the
+    // queries about blocks garbage collected from the hot store.
+    let cold_store_fallback = match (&split_store, &view_split_store) {
+        (Some(_), Some(_)) => Some(ColdStoreFallback {
+            epoch_manager: view_epoch_manager.clone(),
+            runtime: view_runtime.clone(),
+        }),
+        (Some(split_store), None) => {
+            let epoch_manager = EpochManager::new_arc_handle(
+                split_store.clone(),
+                &config.genesis.config,
+                Some(home_dir),
+            );
+            let runtime = NightshadeRuntime::from_config(
+                home_dir,
+                split_store.clone(),
+                &config,
+                epoch_manager.clone(),
+            )
+            .context("could not create the transaction runtime")?;
+            Some(ColdStoreFallback { epoch_manager, runtime })
+        }
+        (None, _) => None,
+    };
 
     let cold_store_loop_handle = spawn_cold_store_loop(&config, &storage, epoch_manager.clone())?;
 
+    if config.config.precompile_contracts_on_start {
+        spawn_contract_precompilation(
+            &config,
+            storage.get_hot_store(),
+            epoch_manager.clone(),
+            runtime.clone(),
+        )?;
+    }
+
     let telemetry = ActixWrapper::new(TelemetryActor::new(config.telemetry_config.clone())).start();
     let chain_genesis = ChainGenesis::new(&config.genesis.config);
     let state_roots = near_store::get_genesis_state_roots(runtime.store())?
@@ -349,6 +383,7 @@ pub fn start_with_config_and_synchronization(
         network_adapter.as_multi_sender(),
         config.client_config.clone(),
         adv.clone(),
+        cold_store_fallback,
     );
 
     let state_snapshot_sender = LateBoundSender::new();
@@ -431,7 +466,7 @@ pub fn start_with_config_and_synchronization(
         network_adapter.as_sender(),
         client_adapter_for_shards_manager.as_sender(),
         config.validator_signer.clone(),
-        split_store.unwrap_or_else(|| storage.get_hot_store()),
+        view_split_store.unwrap_or_else(|| storage.get_hot_store()),
         config.client_config.chunk_request_retry_period,
     );
     shards_manager_adapter.bind(shards_manager_actor.with_auto_span_context());
@@ -460,6 +495,7 @@ pub fn start_with_config_and_synchronization(
         shard_tracker: shard_tracker.clone(),
         runtime,
         validator: config.validator_signer.clone(),
+        node_key: config.network_config.node_key.clone(),
         future_spawner: state_sync_spawner,
         handle: None,
     };
@@ -16,12 +16,13 @@ use near_client::sync::external::{
 use near_client::sync::external::{
     StateFileType, create_bucket_read_write, external_storage_location,
 };
+use near_crypto::SecretKey;
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_tracker::ShardTracker;
 use near_primitives::block::BlockHeader;
 use near_primitives::hash::CryptoHash;
 use near_primitives::state_part::PartId;
-use near_primitives::state_sync::StateSyncDumpProgress;
+use near_primitives::state_sync::{StatePartInfo, StatePartsManifest, StateSyncDumpProgress};
 use near_primitives::types::{EpochHeight, EpochId, ShardId, StateRoot};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
@@ -48,6 +49,8 @@ pub struct StateSyncDumper {
     /// Lock the value of mutable validator signer for the duration of a request to ensure consistency.
     /// Please note that the locked value should not be stored anywhere or passed through the thread boundary.
     pub validator: MutableValidatorSigner,
+    /// Node key, signs the manifests of the dumped state parts.
+    pub node_key: SecretKey,
     pub future_spawner: Arc<dyn FutureSpawner>,
     pub handle: Option<Arc<StateSyncDumpHandle>>,
 }
@@ -89,6 +92,9 @@ impl StateSyncDumper {
                     bucket,
                 }
             },
+            ExternalStorageLocation::Http { .. } => {
+                anyhow::bail!("State can't be dumped to read-only HTTP mirrors");
+            }
         };
 
         let chain_id = self.client_config.chain_id.clone();
@@ -122,6 +128,7 @@ impl StateSyncDumper {
                 external,
                 dump_config.iteration_delay.unwrap_or(Duration::seconds(10)),
                 self.validator.clone(),
+                self.node_key.clone(),
                 handle.clone(),
                 self.future_spawner.clone(),
             )
@@ -242,6 +249,8 @@ struct ShardDump {
     // meaning they've already been dumped. We periodically check this (since other processes/machines
     // might have uploaded parts that we didn't) and avoid duplicating work for those parts that have already been updated.
     parts_missing: Arc<RwLock<HashSet<u64>>>,
+    // Hashes and sizes of the parts uploaded by this node, used to build the manifest.
+    part_infos: Arc<Mutex<HashMap<u64, StatePartInfo>>>,
     // This will give Ok(()) when they're all done, or Err() when one gives an error
     // For now the tasks never fail, since we just retry all errors like the old implementation did,
     // but we probably want to make a change to distinguish which errors are actually retryable
@@ -286,7 +295,7 @@ impl DumpState {
     }
 
     /// Waits until all part upload tasks are done for some shard.
-    async fn await_parts_upload(&mut self) -> (ShardId, anyhow::Result<()>) {
+    async fn await_parts_upload(&mut self) -> (ShardId, ShardDump, anyhow::Result<()>) {
         let ((shard_id, result), _, _still_going) =
             futures::future::select_all(self.dump_state.iter_mut().map(|(shard_id, s)| {
                 async {
@@ -299,8 +308,8 @@ impl DumpState {
 
         drop(_still_going);
 
-        self.dump_state.remove(&shard_id);
-        (shard_id, result)
+        let shard_dump = self.dump_state.remove(&shard_id).unwrap();
+        (shard_id, shard_dump, result)
     }
 
     /// Sets the `canceled` variable to true and waits for all tasks to exit
@@ -311,7 +320,7 @@ impl DumpState {
             d.parts_dumped.store(-1, Ordering::SeqCst);
         }
         while !self.dump_state.is_empty() {
-            let (shard_id, result) = self.await_parts_upload().await;
+            let (shard_id, _, result) = self.await_parts_upload().await;
             if let Err(error) = result {
                 tracing::error!(target: "state_sync_dump", epoch_id = ?&self.epoch_id, %shard_id, ?error, "Shard dump failed after cancellation");
             }
@@ -350,6 +359,7 @@ struct StateDumper {
     clock: Clock,
     chain_id: String,
     validator: MutableValidatorSigner,
+    node_key: SecretKey,
     shard_tracker: ShardTracker,
     chain: Chain,
     epoch_manager: Arc<dyn EpochManagerAdapter>,
@@ -379,6 +389,7 @@ struct PartUploader {
     // know not to touch that metric anymore.
     parts_dumped: Arc<AtomicI64>,
     parts_missing: Arc<RwLock<HashSet<u64>>>,
+    part_infos: Arc<Mutex<HashMap<u64, StatePartInfo>>>,
     obtain_parts: Arc<Semaphore>,
     canceled: Arc<AtomicBool>,
 }
@@ -461,6 +472,10 @@ impl PartUploader {
             {
                 Ok(()) => {
                     self.inc_parts_dumped();
+                    self.part_infos
+                        .lock()
+                        .unwrap()
+                        .insert(part_idx, StatePartInfo::new(&state_part));
                     metrics::STATE_SYNC_DUMP_SIZE_TOTAL
                         .with_label_values(&[
                             &self.epoch_height.to_string(),
@@ -584,11 +599,122 @@ impl HeaderUploader {
     }
 }
 
+// Stores needed data for use in manifest upload futures
+struct ManifestUploader {
+    clock: Clock,
+    external: ExternalConnection,
+    runtime: Arc<dyn RuntimeAdapter>,
+    chain_id: String,
+    node_key: SecretKey,
+    epoch_id: EpochId,
+    epoch_height: EpochHeight,
+    shard_id: ShardId,
+    state_root: StateRoot,
+    num_parts: u64,
+    part_infos: Arc<Mutex<HashMap<u64, StatePartInfo>>>,
+    canceled: Arc<AtomicBool>,
+}
+
+impl ManifestUploader {
+    /// Lists the hash and size of every part of the shard in a manifest signed with the node key,
+    /// and uploads it to the external storage. The parts dumped by other nodes are read back and
+    /// validated, since the manifest vouches for them too. Retries errors until the dump is canceled.
+    async fn upload_manifest(self) {
+        let mut parts = Vec::with_capacity(self.num_parts as usize);
+        for part_idx in 0..self.num_parts {
+            let part_info = self.part_infos.lock().unwrap().get(&part_idx).cloned();
+            let part_info = match part_info {
+                Some(part_info) => part_info,
+                None => {
+                    let Some(part) = self.read_part(part_idx).await else {
+                        return;
+                    };
+                    let part_id = PartId::new(part_idx, self.num_parts);
+                    if !self.runtime.validate_state_part(&self.state_root, part_id, &part) {
+                        tracing::error!(
+                            target: "state_sync_dump", shard_id = %self.shard_id, epoch_height = %self.epoch_height, ?part_id,
+                            "Stored state part is invalid. Not uploading the manifest."
+                        );
+                        return;
+                    }
+                    StatePartInfo::new(&part)
+                }
+            };
+            parts.push(part_info);
+        }
+        let manifest = StatePartsManifest::new(
+            self.epoch_id,
+            self.shard_id,
+            self.state_root,
+            parts,
+            &self.node_key,
+        );
+        let data = borsh::to_vec(&manifest).unwrap();
+        let file_type = StateFileType::StatePartsManifest;
+        let location = external_storage_location(
+            &self.chain_id,
+            &self.epoch_id,
+            self.epoch_height,
+            self.shard_id,
+            &file_type,
+        );
+        loop {
+            if self.canceled.load(Ordering::Relaxed) {
+                return;
+            }
+            match self.external.put_file(file_type.clone(), &data, self.shard_id, &location).await {
+                Ok(()) => {
+                    tracing::info!(
+                        target: "state_sync_dump", shard_id = %self.shard_id, epoch_height = %self.epoch_height,
+                        "Manifest saved to external storage."
+                    );
+                    return;
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        target: "state_sync_dump", shard_id = %self.shard_id, epoch_height = %self.epoch_height, ?err,
+                        "Failed to put manifest into external storage. Retrying in 5 seconds."
+                    );
+                    self.clock.sleep(Duration::seconds(5)).await;
+                }
+            }
+        }
+    }
+
+    /// Downloads a part dumped by another node. Returns None if the dump is canceled.
+    async fn read_part(&self, part_idx: u64) -> Option<Vec<u8>> {
+        let file_type = StateFileType::StatePart { part_id: part_idx, num_parts: self.num_parts };
+        let location = external_storage_location(
+            &self.chain_id,
+            &self.epoch_id,
+            self.epoch_height,
+            self.shard_id,
+            &file_type,
+        );
+        loop {
+            if self.canceled.load(Ordering::Relaxed) {
+                return None;
+            }
+            match self.external.get_file(self.shard_id, &location, &file_type).await {
+                Ok(part) => return Some(part),
+                Err(err) => {
+                    tracing::warn!(
+                        target: "state_sync_dump", shard_id = %self.shard_id, epoch_height = %self.epoch_height, part_idx, ?err,
+                        "Failed to read state part from external storage. Retrying in 5 seconds."
+                    );
+                    self.clock.sleep(Duration::seconds(5)).await;
+                }
+            }
+        }
+    }
+}
+
 impl StateDumper {
     fn new(
         clock: Clock,
         chain_id: String,
         validator: MutableValidatorSigner,
+        node_key: SecretKey,
         shard_tracker: ShardTracker,
         chain: Chain,
         epoch_manager: Arc<dyn EpochManagerAdapter>,
@@ -600,6 +726,7 @@ impl StateDumper {
             clock,
             chain_id,
             validator,
+            node_key,
             shard_tracker,
             chain,
             epoch_manager,
@@ -688,6 +815,7 @@ impl StateDumper {
                 num_parts,
                 parts_dumped: Arc::new(AtomicI64::new(0)),
                 parts_missing: Arc::new(RwLock::new((0..num_parts).collect())),
+                part_infos: Arc::new(Mutex::new(HashMap::new())),
                 upload_parts: receiver,
             },
             sender,
@@ -861,6 +989,7 @@ impl StateDumper {
                     num_parts: shard_dump.num_parts,
                     parts_dumped: shard_dump.parts_dumped.clone(),
                     parts_missing: shard_dump.parts_missing.clone(),
+                    part_infos: shard_dump.part_infos.clone(),
                     obtain_parts: self.obtain_parts.clone(),
                     canceled: dump.canceled.clone(),
                 });
@@ -934,11 +1063,27 @@ impl StateDumper {
         let CurrentDump::InProgress(dump) = &mut self.current_dump else {
             return std::future::pending().await;
         };
-        let (shard_id, result) = dump.await_parts_upload().await;
+        let (shard_id, shard_dump, result) = dump.await_parts_upload().await;
 
         match result {
             Ok(()) => {
                 tracing::info!(target: "state_sync_dump", epoch_id = ?&dump.epoch_id, %shard_id, "Shard dump finished");
+                let uploader = ManifestUploader {
+                    clock: self.clock.clone(),
+                    external: self.external.clone(),
+                    runtime: self.runtime.clone(),
+                    chain_id: self.chain_id.clone(),
+                    node_key: self.node_key.clone(),
+                    epoch_id: dump.epoch_id,
+                    epoch_height: dump.epoch_height,
+                    shard_id,
+                    state_root: shard_dump.state_root,
+                    num_parts: shard_dump.num_parts,
+                    part_infos: shard_dump.part_infos,
+                    canceled: dump.canceled.clone(),
+                };
+                self.future_spawner
+                    .spawn_boxed("upload_manifest", uploader.upload_manifest().boxed());
             }
             Err(error) => {
                 tracing::error!(target: "state_sync_dump", epoch_id = ?&dump.epoch_id, %shard_id, ?error, "Shard dump failed");
@@ -1025,6 +1170,7 @@ async fn state_sync_dump(
     external: ExternalConnection,
     iteration_delay: Duration,
     validator: MutableValidatorSigner,
+    node_key: SecretKey,
     keep_running: &AtomicBool,
     future_spawner: Arc<dyn FutureSpawner>,
 ) -> anyhow::Result<()> {
@@ -1034,6 +1180,7 @@ async fn state_sync_dump(
         clock.clone(),
         chain_id,
         validator,
+        node_key,
         shard_tracker,
         chain,
         epoch_manager,
@@ -1085,6 +1232,7 @@ async fn do_state_sync_dump(
     external: ExternalConnection,
     iteration_delay: Duration,
     validator: MutableValidatorSigner,
+    node_key: SecretKey,
     handle: Arc<StateSyncDumpHandle>,
     future_spawner: Arc<dyn FutureSpawner>,
 ) {
@@ -1098,6 +1246,7 @@ async fn do_state_sync_dump(
         external,
         iteration_delay,
         validator,
+        node_key,
         &handle.keep_running,
         future_spawner,
     )
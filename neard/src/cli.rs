@@ -5,6 +5,7 @@ use near_chain_configs::GenesisValidationMode;
 use near_client::ConfigUpdater;
 use near_cold_store_tool::ColdStoreCommand;
 use near_config_utils::DownloadConfigType;
+use near_contract_cache_tool::ContractCacheCommand;
 use near_database_tool::commands::DatabaseCommand;
 use near_dump_test_contract::DumpTestContractCommand;
 use near_dyn_configs::{UpdatableConfigLoader, UpdatableConfigLoaderError, UpdatableConfigs};
@@ -22,6 +23,7 @@ use near_ping::PingCommand;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::compute_root_from_path;
 use near_primitives::types::{Gas, NumSeats, NumShards, ProtocolVersion, ShardId};
+use near_remote_signer::RemoteSignerCommand;
 use near_replay_archive_tool::ReplayArchiveCommand;
 use near_state_parts::cli::StatePartsCommand;
 use near_state_parts_dump_check::cli::StatePartsDumpCheckCommand;
@@ -90,8 +92,15 @@ impl NeardCmd {
             GenesisValidationMode::Full
         };
 
+        let encrypt_keys =
+            matches!(&neard_cmd.subcmd, NeardSubCommand::Init(cmd) if cmd.encrypt_keys);
+        let key_passphrase = neard_cmd.opts.key_passphrase(encrypt_keys)?;
+        if let Some(passphrase) = &key_passphrase {
+            near_crypto::set_key_passphrase(passphrase.clone());
+        }
+
         match neard_cmd.subcmd {
-            NeardSubCommand::Init(cmd) => cmd.run(&home_dir)?,
+            NeardSubCommand::Init(cmd) => cmd.run(&home_dir, key_passphrase.as_deref())?,
             NeardSubCommand::Localnet(cmd) => cmd.run(&home_dir),
             NeardSubCommand::Run(cmd) => cmd.run(
                 &home_dir,
@@ -102,7 +111,13 @@ impl NeardCmd {
 
             NeardSubCommand::StateViewer(cmd) => {
                 let mode = if cmd.read_write { Mode::ReadWrite } else { Mode::ReadOnly };
-                cmd.subcmd.run(&home_dir, genesis_validation, mode, cmd.store_temperature);
+                cmd.subcmd.run(
+                    &home_dir,
+                    genesis_validation,
+                    mode,
+                    cmd.overlay,
+                    cmd.store_temperature,
+                );
             }
 
             NeardSubCommand::VerifyProof(cmd) => {
@@ -152,6 +167,12 @@ impl NeardCmd {
             NeardSubCommand::DumpTestContracts(cmd) => {
                 cmd.run()?;
             }
+            NeardSubCommand::RemoteSigner(cmd) => {
+                cmd.run()?;
+            }
+            NeardSubCommand::ContractCache(cmd) => {
+                cmd.run(&home_dir, genesis_validation)?;
+            }
         };
         Ok(())
     }
@@ -164,6 +185,11 @@ pub(super) struct StateViewerCommand {
     /// In case an operation needs to write to caches, a read-write mode may be needed.
     #[clap(long, short = 'w')]
     read_write: bool,
+    /// Opens the DB in read only mode and keeps all writes in memory, so that commands which
+    /// modify the DB (e.g. `apply-range --save-state`) can be tried out without changing it.
+    /// All changes are discarded when the command finishes.
+    #[clap(long, conflicts_with = "read_write")]
+    overlay: bool,
     /// What store temperature should the state viewer open. Allowed values are hot and cold but
     /// cold is only available when cold_store is configured.
     /// Cold temperature actually means the split store will be used.
@@ -189,6 +215,13 @@ struct NeardOpts {
     /// Enables export of span data using opentelemetry protocol.
     #[clap(flatten)]
     o11y: near_o11y::Options,
+    /// Reads the passphrase of encrypted key files from given file descriptor
+    /// instead of the NEAR_KEY_PASSPHRASE environment variable.
+    #[clap(long)]
+    key_passphrase_fd: Option<u32>,
+    /// Prompts for the passphrase of encrypted key files on the terminal.
+    #[clap(long, conflicts_with = "key_passphrase_fd")]
+    key_passphrase_prompt: bool,
 }
 
 impl NeardOpts {
@@ -199,6 +232,27 @@ impl NeardOpts {
             inner.as_ref().map_or("", String::as_str)
         })
     }
+
+    /// Returns the passphrase of encrypted key files if one was provided.
+    /// With `confirm` the passphrase is prompted for twice, which is used when
+    /// encrypting new key files.
+    fn key_passphrase(&self, confirm: bool) -> anyhow::Result<Option<String>> {
+        if let Some(fd) = self.key_passphrase_fd {
+            let passphrase =
+                std::fs::read_to_string(format!("/dev/fd/{fd}")).with_context(|| {
+                    format!("failed to read key passphrase from file descriptor {fd}")
+                })?;
+            return Ok(Some(passphrase.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        if self.key_passphrase_prompt {
+            let passphrase = rpassword::prompt_password("Key file passphrase: ")?;
+            if confirm && rpassword::prompt_password("Repeat the passphrase: ")? != passphrase {
+                anyhow::bail!("passphrases do not match");
+            }
+            return Ok(Some(passphrase));
+        }
+        Ok(std::env::var(near_crypto::KEY_PASSPHRASE_ENV_VAR).ok())
+    }
 }
 
 #[derive(clap::Parser)]
@@ -261,6 +315,13 @@ pub(super) enum NeardSubCommand {
 
     /// Placeholder for test contracts subcommand
     DumpTestContracts(DumpTestContractCommand),
+
+    /// Runs a signer holding the validator key outside of the validator node.
+    RemoteSigner(RemoteSignerCommand),
+
+    /// Precompiles deployed contracts and exports or imports the cache of
+    /// compiled contracts.
+    ContractCache(ContractCacheCommand),
 }
 
 #[allow(unused)]
@@ -329,6 +390,11 @@ pub(super) struct InitCmd {
     /// from genesis configuration will be taken.
     #[clap(long)]
     max_gas_burnt_view: Option<Gas>,
+    /// Encrypt the generated node and validator key files with a passphrase.
+    /// See --key-passphrase-fd and --key-passphrase-prompt for how the
+    /// passphrase is provided.
+    #[clap(long)]
+    encrypt_keys: bool,
 }
 
 /// Warns if unsupported build of the executable is used on mainnet or testnet.
@@ -367,7 +433,7 @@ fn check_release_build(chain: &str) {
 }
 
 impl InitCmd {
-    pub(super) fn run(self, home_dir: &Path) -> anyhow::Result<()> {
+    pub(super) fn run(self, home_dir: &Path, key_passphrase: Option<&str>) -> anyhow::Result<()> {
         // TODO: Check if `home` exists. If exists check what networks we already have there.
         if (self.download_genesis || self.download_genesis_url.is_some()) && self.genesis.is_some()
         {
@@ -384,6 +450,15 @@ impl InitCmd {
             None
         };
 
+        let key_passphrase = if self.encrypt_keys {
+            Some(key_passphrase.context(
+                "--encrypt-keys requires a passphrase, set NEAR_KEY_PASSPHRASE or use \
+                 --key-passphrase-fd or --key-passphrase-prompt",
+            )?)
+        } else {
+            None
+        };
+
         nearcore::init_configs(
             home_dir,
             self.chain_id,
@@ -399,6 +474,7 @@ impl InitCmd {
             self.download_config_url.as_deref(),
             self.boot_nodes.as_deref(),
             self.max_gas_burnt_view,
+            key_passphrase,
         )
         .context("Failed to initialize configs")
     }
@@ -563,6 +563,77 @@ impl AnyCache {
     }
 }
 
+/// Magic bytes at the start of an archive written by [`export_contract_cache`].
+const CACHE_ARCHIVE_MAGIC: &[u8; 8] = b"nearcc\x00\x02";
+
+/// An entry of a contract cache archive: the hash of the Wasm code, the cache
+/// key it had on the exporting node and the compiled contract.
+type CacheArchiveEntry = (CryptoHash, CryptoHash, CompiledContractInfo);
+
+/// Writes the cached compilation results of the contracts with the given code
+/// hashes to a portable archive, returning their number.  Contracts which
+/// aren't in the cache are skipped.
+pub fn export_contract_cache(
+    cache: &dyn ContractRuntimeCache,
+    code_hashes: impl IntoIterator<Item = CryptoHash>,
+    config: &Config,
+    writer: &mut impl std::io::Write,
+) -> std::io::Result<usize> {
+    writer.write_all(CACHE_ARCHIVE_MAGIC)?;
+    let mut count = 0;
+    for code_hash in code_hashes {
+        let key = get_contract_cache_key(code_hash, config);
+        let Some(entry) = cache.get(&key)? else { continue };
+        borsh::to_writer(&mut *writer, &Some((code_hash, key, entry)))?;
+        count += 1;
+    }
+    borsh::to_writer(&mut *writer, &None::<CacheArchiveEntry>)?;
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Stores the entries of an archive written by [`export_contract_cache`] in
+/// the cache.  Entries which are already present are skipped.  Returns the
+/// number of added entries.
+///
+/// The key of every entry is recomputed from its code hash and `config`, and
+/// the import stops with an error at the first one which doesn't match, which
+/// is the case for archives exported with a different VM, VM configuration or
+/// `neard` build.  The compiled code itself is executed as is, so archives
+/// must only be imported from trusted sources.
+pub fn import_contract_cache(
+    cache: &dyn ContractRuntimeCache,
+    config: &Config,
+    reader: &mut impl std::io::Read,
+) -> std::io::Result<usize> {
+    let mut magic = [0; CACHE_ARCHIVE_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != CACHE_ARCHIVE_MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "not a contract cache archive",
+        ));
+    }
+    let mut count = 0;
+    while let Some((code_hash, key, entry)) =
+        Option::<CacheArchiveEntry>::deserialize_reader(reader)?
+    {
+        if key != get_contract_cache_key(code_hash, config) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "the entry for contract {code_hash} was compiled with a different VM or configuration"
+                ),
+            ));
+        }
+        if !cache.has(&key)? {
+            cache.put(&key, entry)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
 /// Precompiles contract for the current default VM, and stores result to the cache.
 /// Returns `Ok(true)` if compiled code was added to the cache, and `Ok(false)` if element
 /// is already in the cache, or if cache is `None`.
@@ -713,4 +784,47 @@ mod tests {
         // Insert the keys again and assert that the cache can be updated after clear.
         insert_and_assert_keys_exist();
     }
+
+    #[test]
+    fn test_export_import_contract_cache() {
+        let config = crate::tests::test_vm_config();
+        let source = MockContractRuntimeCache::default();
+        let code =
+            CompiledContractInfo { wasm_bytes: 3, compiled: CompiledContract::Code(vec![1]) };
+        let error = CompiledContractInfo {
+            wasm_bytes: 5,
+            compiled: CompiledContract::CompileModuleError(CompilationError::WasmerCompileError {
+                msg: "test".to_string(),
+            }),
+        };
+        let code_hash = CryptoHash::hash_bytes(b"code");
+        let error_hash = CryptoHash::hash_bytes(b"error");
+        let missing_hash = CryptoHash::hash_bytes(b"missing");
+        let code_key = get_contract_cache_key(code_hash, &config);
+        let error_key = get_contract_cache_key(error_hash, &config);
+        source.put(&code_key, code.clone()).unwrap();
+        source.put(&error_key, error.clone()).unwrap();
+
+        let mut archive = vec![];
+        let code_hashes = [code_hash, error_hash, missing_hash];
+        assert_eq!(export_contract_cache(&source, code_hashes, &config, &mut archive).unwrap(), 2);
+
+        // Entries already present in the target cache are not overwritten.
+        let target = MockContractRuntimeCache::default();
+        let existing =
+            CompiledContractInfo { wasm_bytes: 3, compiled: CompiledContract::Code(vec![2]) };
+        target.put(&code_key, existing.clone()).unwrap();
+        assert_eq!(import_contract_cache(&target, &config, &mut archive.as_slice()).unwrap(), 1);
+        assert_eq!(target.get(&code_key).unwrap(), Some(existing));
+        assert_eq!(target.get(&error_key).unwrap(), Some(error));
+
+        // Entries compiled with another configuration are rejected.
+        let mut other_config = config.clone();
+        other_config.limit_config.max_memory_pages += 1;
+        let target = MockContractRuntimeCache::default();
+        import_contract_cache(&target, &other_config, &mut archive.as_slice()).unwrap_err();
+        assert!(!target.has(&get_contract_cache_key(code_hash, &other_config)).unwrap());
+
+        import_contract_cache(&target, &config, &mut &b"not an archive"[..]).unwrap_err();
+    }
 }
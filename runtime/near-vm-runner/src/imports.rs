@@ -86,6 +86,9 @@ imports! {
     @in internal: finite_wasm_gas<[gas: u64] -> []>,
     @in internal: finite_wasm_stack<[operand_size: u64, frame_size: u64] -> []>,
     @in internal: finite_wasm_unstack<[operand_size: u64, frame_size: u64] -> []>,
+    // Per-function gas profiling, see `instrument::wasm_profile`.
+    @in internal: wasm_profile_enter<[function_index: u32] -> []>,
+    @in internal: wasm_profile_exit<[] -> []>,
     // #############
     // # Registers #
     // #############
@@ -305,10 +308,19 @@ pub(crate) const fn should_trace_host_function(host_function: &str) -> bool {
     match host_function {
         _ if str_eq(host_function, "gas") => false,
         _ if str_eq(host_function, "finite_wasm_gas") => false,
+        _ if str_eq(host_function, "wasm_profile_enter") => false,
+        _ if str_eq(host_function, "wasm_profile_exit") => false,
         _ => true,
     }
 }
 
+/// Whether calls of host functions imported from `module` are recorded in the
+/// host call trace.  Functions of the `internal` module are called by the gas
+/// and stack instrumentation rather than by the contract itself.
+pub(crate) const fn should_record_host_call(module: &str) -> bool {
+    !str_eq(module, "internal")
+}
+
 /// Constant-time string equality, work-around for `"foo" == "bar"` not working
 /// in const context yet.
 const fn str_eq(s1: &str, s2: &str) -> bool {
@@ -1,5 +1,8 @@
 //! Instrumentation of wasm code for gas metering and stack limiting.
 //!
+//! The `wasm_profile` module is not from wasm-utils, it adds the optional
+//! per-function gas profiling used by debugging tools.
+//!
 //! The code in this module was originally vendored from MIT/Apache wasm-utils
 //! crate from the parity ecosystem:
 //!
@@ -17,3 +20,4 @@
 pub(crate) mod gas;
 pub(crate) mod rules;
 pub(crate) mod stack_height;
+pub(crate) mod wasm_profile;
@@ -0,0 +1,445 @@
+//! Instrumentation attributing the gas burnt by a contract to its functions.
+//!
+//! Two host functions are imported from the `internal` module and every
+//! function defined by the contract is rewritten into
+//!
+//! ```text
+//! i32.const <function index>
+//! call $wasm_profile_enter
+//! loop end
+//! block <function results>
+//!   <original body, with `call $wasm_profile_exit` before every `return`>
+//! end
+//! call $wasm_profile_exit
+//! ```
+//!
+//! The empty loop starts a new gas metering segment, so the gas of the
+//! original body is charged after the function is entered.  Functions aborted
+//! by a trap are never exited, which is fine as the execution ends anyway.
+//!
+//! The added instructions cost gas as well, so profiling is only meant for
+//! debugging tools, see `prepare_with_wasm_profile`.
+use crate::logic::errors::PrepareError;
+use finite_wasm::wasmparser as wp;
+use std::collections::HashMap;
+use wasm_encoder::{BlockType, Encode, Instruction, Section, SectionId, ValType};
+
+const PROFILE_MODULE: &str = "internal";
+
+struct ProfileContext<'a> {
+    code: &'a [u8],
+    output_code: Vec<u8>,
+    /// Block types matching the results of the types in the type section.
+    block_types: Vec<BlockType>,
+    /// Type indices of the functions defined by the contract.
+    function_types: Vec<u32>,
+    imported_functions: u32,
+    /// Number of function bodies transformed so far.
+    transformed_functions: u32,
+    before_type_section: bool,
+    code_section: wasm_encoder::CodeSection,
+    code_section_count: u32,
+}
+
+impl<'a> ProfileContext<'a> {
+    fn new(code: &'a [u8]) -> Self {
+        Self {
+            code,
+            output_code: Vec::with_capacity(code.len()),
+            block_types: vec![],
+            function_types: vec![],
+            imported_functions: 0,
+            transformed_functions: 0,
+            before_type_section: true,
+            code_section: wasm_encoder::CodeSection::new(),
+            code_section_count: 0,
+        }
+    }
+
+    /// Expects a module produced by `prepare_v2`, so the module is already
+    /// validated and has an import section.
+    fn run(&mut self) -> Result<Vec<u8>, PrepareError> {
+        for payload in wp::Parser::new(0).parse_all(self.code) {
+            let payload = payload.map_err(|_| PrepareError::Deserialization)?;
+            match payload {
+                wp::Payload::Version { range, .. } => self.copy(range)?,
+                wp::Payload::TypeSection(reader) => self.transform_type_section(reader)?,
+                wp::Payload::ImportSection(reader) => self.transform_import_section(reader)?,
+                wp::Payload::FunctionSection(reader) => {
+                    for ty in reader.clone() {
+                        self.function_types.push(ty.map_err(|_| PrepareError::Deserialization)?);
+                    }
+                    self.copy_section(SectionId::Function, reader.range())?;
+                }
+                wp::Payload::TableSection(reader) => {
+                    self.copy_section(SectionId::Table, reader.range())?
+                }
+                wp::Payload::MemorySection(reader) => {
+                    self.copy_section(SectionId::Memory, reader.range())?
+                }
+                wp::Payload::GlobalSection(reader) => {
+                    self.copy_section(SectionId::Global, reader.range())?
+                }
+                wp::Payload::ExportSection(reader) => self.transform_export_section(reader)?,
+                wp::Payload::StartSection { func, .. } => {
+                    let function_index = self.function_index(func);
+                    wasm_encoder::StartSection { function_index }.append_to(&mut self.output_code);
+                }
+                wp::Payload::ElementSection(reader) => {
+                    self.transform_element_section(reader.range())?
+                }
+                wp::Payload::DataCountSection { range, .. } => {
+                    self.copy_section(SectionId::DataCount, range)?
+                }
+                wp::Payload::DataSection(reader) => {
+                    self.copy_section(SectionId::Data, reader.range())?
+                }
+                wp::Payload::CodeSectionStart { count, range, .. } => {
+                    self.code_section_count = count;
+                    if count == 0 {
+                        self.copy_section(SectionId::Code, range)?;
+                    }
+                }
+                wp::Payload::CodeSectionEntry(func) => {
+                    self.transform_function_body(func)?;
+                    if self.transformed_functions == self.code_section_count {
+                        self.code_section.append_to(&mut self.output_code);
+                    }
+                }
+                // Function indices in the name section would be off, so it is
+                // dropped.  The names are reported by `function_names` instead.
+                wp::Payload::CustomSection(reader) if reader.name() == "name" => {}
+                wp::Payload::CustomSection(reader) => {
+                    self.copy_section(SectionId::Custom, reader.range())?
+                }
+                wp::Payload::End(_) => {}
+                _ => return Err(PrepareError::Deserialization),
+            }
+        }
+        Ok(std::mem::take(&mut self.output_code))
+    }
+
+    fn transform_type_section(
+        &mut self,
+        reader: wp::TypeSectionReader,
+    ) -> Result<(), PrepareError> {
+        self.before_type_section = false;
+        let mut new_section = wasm_encoder::TypeSection::new();
+        for ty in reader {
+            let ty = ty.map_err(|_| PrepareError::Deserialization)?;
+            #[allow(unreachable_patterns)]
+            let ty = match ty {
+                wp::Type::Func(ty) => ty,
+                _ => return Err(PrepareError::Deserialization),
+            };
+            let params =
+                ty.params().iter().map(|ty| val_type(*ty)).collect::<Result<Vec<_>, _>>()?;
+            let results =
+                ty.results().iter().map(|ty| val_type(*ty)).collect::<Result<Vec<_>, _>>()?;
+            self.block_types.push(match results.as_slice() {
+                [] => BlockType::Empty,
+                [ty] => BlockType::Result(*ty),
+                // Multi-value is not enabled, see `features.rs`.
+                _ => return Err(PrepareError::Deserialization),
+            });
+            new_section.ty().function(params, results);
+        }
+        Self::add_profile_types(&mut new_section);
+        new_section.append_to(&mut self.output_code);
+        Ok(())
+    }
+
+    /// Types of `wasm_profile_enter` and `wasm_profile_exit`, in this order,
+    /// appended to the types of the contract.
+    fn add_profile_types(section: &mut wasm_encoder::TypeSection) {
+        section.ty().function([ValType::I32], []);
+        section.ty().function([], []);
+    }
+
+    fn transform_import_section(
+        &mut self,
+        reader: wp::ImportSectionReader,
+    ) -> Result<(), PrepareError> {
+        if self.before_type_section {
+            let mut new_section = wasm_encoder::TypeSection::new();
+            Self::add_profile_types(&mut new_section);
+            new_section.append_to(&mut self.output_code);
+        }
+        let mut new_section = wasm_encoder::ImportSection::new();
+        for import in reader {
+            let import = import.map_err(|_| PrepareError::Deserialization)?;
+            let new_type = match import.ty {
+                wp::TypeRef::Func(id) => {
+                    self.imported_functions += 1;
+                    wasm_encoder::EntityType::Function(id)
+                }
+                wp::TypeRef::Memory(memory) => {
+                    wasm_encoder::EntityType::Memory(wasm_encoder::MemoryType {
+                        minimum: memory.initial,
+                        maximum: memory.maximum,
+                        memory64: memory.memory64,
+                        shared: memory.shared,
+                        page_size_log2: None,
+                    })
+                }
+                // Other imports are rejected by `prepare_v2`.
+                _ => return Err(PrepareError::Instantiate),
+            };
+            new_section.import(import.module, import.name, new_type);
+        }
+        let enter_type = self.block_types.len() as u32;
+        new_section.import(
+            PROFILE_MODULE,
+            "wasm_profile_enter",
+            wasm_encoder::EntityType::Function(enter_type),
+        );
+        new_section.import(
+            PROFILE_MODULE,
+            "wasm_profile_exit",
+            wasm_encoder::EntityType::Function(enter_type + 1),
+        );
+        new_section.append_to(&mut self.output_code);
+        Ok(())
+    }
+
+    fn transform_export_section(
+        &mut self,
+        reader: wp::ExportSectionReader,
+    ) -> Result<(), PrepareError> {
+        let mut new_section = wasm_encoder::ExportSection::new();
+        for export in reader {
+            let export = export.map_err(|_| PrepareError::Deserialization)?;
+            let (kind, index) = match export.kind {
+                wp::ExternalKind::Func => {
+                    (wasm_encoder::ExportKind::Func, self.function_index(export.index))
+                }
+                wp::ExternalKind::Table => (wasm_encoder::ExportKind::Table, export.index),
+                wp::ExternalKind::Memory => (wasm_encoder::ExportKind::Memory, export.index),
+                wp::ExternalKind::Global => (wasm_encoder::ExportKind::Global, export.index),
+                wp::ExternalKind::Tag => return Err(PrepareError::Deserialization),
+            };
+            new_section.export(export.name, kind, index);
+        }
+        new_section.append_to(&mut self.output_code);
+        Ok(())
+    }
+
+    /// Without the bulk memory and reference types proposals only active
+    /// segments of function indices are valid, so the section is simple
+    /// enough to be rewritten by hand.
+    fn transform_element_section(
+        &mut self,
+        range: std::ops::Range<usize>,
+    ) -> Result<(), PrepareError> {
+        let data = self.code.get(range.clone()).ok_or(PrepareError::Deserialization)?;
+        let mut reader = wp::BinaryReader::new_with_offset(data, range.start);
+        let mut new_section = vec![];
+        let count = reader.read_var_u32().map_err(|_| PrepareError::Deserialization)?;
+        count.encode(&mut new_section);
+        for _ in 0..count {
+            let flags = reader.read_var_u32().map_err(|_| PrepareError::Deserialization)?;
+            if flags != 0 {
+                return Err(PrepareError::Deserialization);
+            }
+            flags.encode(&mut new_section);
+            let offset_start = reader.original_position();
+            while !matches!(
+                reader.read_operator().map_err(|_| PrepareError::Deserialization)?,
+                wp::Operator::End
+            ) {}
+            let offset = self
+                .code
+                .get(offset_start..reader.original_position())
+                .ok_or(PrepareError::Deserialization)?;
+            new_section.extend(offset);
+            let functions = reader.read_var_u32().map_err(|_| PrepareError::Deserialization)?;
+            functions.encode(&mut new_section);
+            for _ in 0..functions {
+                let index = reader.read_var_u32().map_err(|_| PrepareError::Deserialization)?;
+                self.function_index(index).encode(&mut new_section);
+            }
+        }
+        SectionId::Element.encode(&mut self.output_code);
+        new_section.len().encode(&mut self.output_code);
+        self.output_code.extend(new_section);
+        Ok(())
+    }
+
+    fn transform_function_body(&mut self, body: wp::FunctionBody) -> Result<(), PrepareError> {
+        let function_index = self.imported_functions + self.transformed_functions;
+        let type_index = *self
+            .function_types
+            .get(self.transformed_functions as usize)
+            .ok_or(PrepareError::Deserialization)?;
+        let block_type =
+            *self.block_types.get(type_index as usize).ok_or(PrepareError::Deserialization)?;
+        self.transformed_functions += 1;
+        let (enter, exit) = (self.imported_functions, self.imported_functions + 1);
+
+        let mut operators =
+            body.get_operators_reader().map_err(|_| PrepareError::Deserialization)?;
+        let mut new_body = Vec::with_capacity(body.range().len() + 16);
+        // Local declarations are kept as they are.
+        new_body.extend(
+            self.code
+                .get(body.range().start..operators.original_position())
+                .ok_or(PrepareError::Deserialization)?,
+        );
+        Instruction::I32Const(function_index as i32).encode(&mut new_body);
+        Instruction::Call(enter).encode(&mut new_body);
+        Instruction::Loop(BlockType::Empty).encode(&mut new_body);
+        Instruction::End.encode(&mut new_body);
+        Instruction::Block(block_type).encode(&mut new_body);
+        while !operators.eof() {
+            let start = operators.original_position();
+            let operator = operators.read().map_err(|_| PrepareError::Deserialization)?;
+            match operator {
+                wp::Operator::Call { function_index } => {
+                    Instruction::Call(self.function_index(function_index)).encode(&mut new_body)
+                }
+                wp::Operator::Return => {
+                    Instruction::Call(exit).encode(&mut new_body);
+                    Instruction::Return.encode(&mut new_body);
+                }
+                // The end of the function body, the original one ends the block.
+                wp::Operator::End if operators.eof() => {
+                    Instruction::End.encode(&mut new_body);
+                    Instruction::Call(exit).encode(&mut new_body);
+                    Instruction::End.encode(&mut new_body);
+                }
+                // Not enabled, see `features.rs`, but these would refer to
+                // function indices as well.
+                wp::Operator::ReturnCall { .. }
+                | wp::Operator::ReturnCallIndirect { .. }
+                | wp::Operator::RefFunc { .. } => return Err(PrepareError::Deserialization),
+                _ => new_body.extend(
+                    self.code
+                        .get(start..operators.original_position())
+                        .ok_or(PrepareError::Deserialization)?,
+                ),
+            }
+        }
+        self.code_section.raw(&new_body);
+        Ok(())
+    }
+
+    /// Index of a function once the profiling functions are imported.
+    fn function_index(&self, index: u32) -> u32 {
+        if index < self.imported_functions { index } else { index + 2 }
+    }
+
+    fn copy_section(
+        &mut self,
+        id: SectionId,
+        range: std::ops::Range<usize>,
+    ) -> Result<(), PrepareError> {
+        id.encode(&mut self.output_code);
+        range.len().encode(&mut self.output_code);
+        self.copy(range)
+    }
+
+    fn copy(&mut self, range: std::ops::Range<usize>) -> Result<(), PrepareError> {
+        Ok(self.output_code.extend(self.code.get(range).ok_or(PrepareError::Deserialization)?))
+    }
+}
+
+fn val_type(ty: wp::ValType) -> Result<ValType, PrepareError> {
+    match ty {
+        wp::ValType::I32 => Ok(ValType::I32),
+        wp::ValType::I64 => Ok(ValType::I64),
+        wp::ValType::F32 => Ok(ValType::F32),
+        wp::ValType::F64 => Ok(ValType::F64),
+        // Not enabled, see `features.rs`.
+        _ => Err(PrepareError::Deserialization),
+    }
+}
+
+/// Instruments a module prepared by `prepare_v2` to call
+/// `internal.wasm_profile_enter` and `internal.wasm_profile_exit` when
+/// entering and leaving its functions.
+pub(crate) fn inject_profiling(code: &[u8]) -> Result<Vec<u8>, PrepareError> {
+    ProfileContext::new(code).run()
+}
+
+/// Returns the function names from the name section of the contract, keyed by
+/// the function index in the original contract.
+pub(crate) fn function_names(code: &[u8]) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for payload in wp::Parser::new(0).parse_all(code) {
+        let Ok(payload) = payload else { break };
+        let wp::Payload::CustomSection(reader) = payload else { continue };
+        if reader.name() != "name" {
+            continue;
+        }
+        for name in wp::NameSectionReader::new(reader.data(), reader.data_offset()) {
+            let Ok(wp::Name::Function(map)) = name else { continue };
+            for naming in map.into_iter().flatten() {
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{function_names, inject_profiling};
+    use crate::logic::ContractPrepareVersion;
+
+    const CONTRACT: &str = r#"
+        (module
+          (import "env" "input" (func $input (param i64)))
+          (import "env" "memory" (memory 1))
+          (table 1 funcref)
+          (elem (i32.const 0) $helper)
+          (func $helper (param i32) (result i32)
+            local.get 0
+            i32.eqz
+            if
+              i32.const 1
+              return
+            end
+            local.get 0
+            br 0)
+          (func $main (export "main")
+            i64.const 0
+            call $input
+            i32.const 5
+            call $helper
+            drop)
+        )"#;
+
+    #[test]
+    fn test_inject_profiling() {
+        let code = wat::parse_str(CONTRACT).unwrap();
+        let instrumented = inject_profiling(&code).unwrap();
+
+        let features = crate::features::WasmFeatures::from(ContractPrepareVersion::V2);
+        let mut validator = wasmparser::Validator::new();
+        validator.wasm_features(features.into());
+        validator.validate_all(&instrumented).unwrap();
+
+        let export = wasmparser::Parser::new(0)
+            .parse_all(&instrumented)
+            .find_map(|payload| match payload.unwrap() {
+                wasmparser::Payload::ExportSection(reader) => Some(reader),
+                _ => None,
+            })
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        // One imported function, two profiling functions and `$helper`.
+        assert_eq!(export.index, 4);
+        assert!(function_names(&instrumented).is_empty());
+    }
+
+    #[test]
+    fn test_function_names() {
+        let code = wat::parse_str(CONTRACT).unwrap();
+        let names = function_names(&code);
+        assert_eq!(names.get(&1).map(String::as_str), Some("helper"));
+        assert_eq!(names.get(&2).map(String::as_str), Some("main"));
+    }
+}
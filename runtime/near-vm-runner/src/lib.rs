@@ -29,12 +29,15 @@ pub use crate::logic::with_ext_cost_counter;
 pub use cache::FilesystemContractRuntimeCache;
 pub use cache::{
     CompiledContract, CompiledContractInfo, ContractRuntimeCache, MockContractRuntimeCache,
-    NoContractRuntimeCache, get_contract_cache_key, precompile_contract,
+    NoContractRuntimeCache, export_contract_cache, get_contract_cache_key, import_contract_cache,
+    precompile_contract,
 };
 #[cfg(feature = "metrics")]
 pub use metrics::{report_metrics, reset_metrics};
 pub use near_primitives_core::code::ContractCode;
 pub use profile::ProfileDataV3;
+#[cfg(feature = "prepare")]
+pub use runner::prepare_with_wasm_profile;
 pub use runner::{Contract, PreparedContract, VM, prepare, run};
 
 /// This is public for internal experimentation use only, and should otherwise be considered an
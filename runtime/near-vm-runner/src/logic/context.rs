@@ -54,6 +54,9 @@ pub struct VMContext {
     /// How many `DataReceipt`'s should receive this execution result. This should be empty if
     /// this function call is a part of a batch and it is not the last action.
     pub output_data_receivers: Vec<AccountId>,
+    /// Whether to record every host function called by the contract in
+    /// [`VMOutcome::host_calls`](super::VMOutcome::host_calls).
+    pub trace_host_calls: bool,
 }
 
 impl VMContext {
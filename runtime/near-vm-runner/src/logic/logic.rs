@@ -3,8 +3,12 @@ use super::dependencies::{External, MemSlice, MemoryLike};
 use super::errors::{FunctionCallError, InconsistentStateError};
 use super::gas_counter::GasCounter;
 use super::recorded_storage_counter::RecordedStorageCounter;
+use super::trace::{
+    HostFunctionCall, MAX_TRACED_HOST_CALLS, MAX_TRACED_REGISTER_BYTES, TraceValue,
+};
 use super::types::{PromiseIndex, PromiseResult, ReceiptIndex, ReturnData};
 use super::utils::split_method_names;
+use super::wasm_profile::WasmProfile;
 use super::{HostError, VMLogicError};
 use crate::ProfileDataV3;
 use crate::bls12381_impl;
@@ -44,6 +48,13 @@ pub struct ExecutionResultState {
     current_account_balance: Balance,
     /// Storage usage of the current account at the moment
     current_storage_usage: StorageUsage,
+    /// Host functions called so far, if tracing is enabled.
+    host_calls: Option<Vec<HostFunctionCall>>,
+    /// Total size of the register contents recorded in `host_calls`.
+    host_calls_register_bytes: usize,
+    /// Gas burnt by the contract functions, if the contract is instrumented
+    /// for profiling.  Created when the first function is entered.
+    wasm_profile: Option<WasmProfile>,
 }
 
 impl ExecutionResultState {
@@ -67,6 +78,9 @@ impl ExecutionResultState {
             return_data: ReturnData::None,
             current_account_balance,
             current_storage_usage,
+            host_calls: context.trace_host_calls.then(Vec::new),
+            host_calls_register_bytes: 0,
+            wasm_profile: None,
         }
     }
 
@@ -135,6 +149,8 @@ impl ExecutionResultState {
             logs: self.logs,
             profile,
             aborted: None,
+            host_calls: self.host_calls.unwrap_or_default(),
+            wasm_profile: self.wasm_profile.map(|profile| profile.finish(burnt_gas)),
         }
     }
 }
@@ -262,6 +278,53 @@ impl<'a> VMLogic<'a> {
         &self.result_state.logs
     }
 
+    /// Calls the host function `name` implemented by `f`, recording the call
+    /// if host call tracing is enabled.  `args` are the names and values of
+    /// the arguments passed by the contract.  Calls past
+    /// [`MAX_TRACED_HOST_CALLS`] are executed without being recorded.
+    #[inline]
+    pub fn trace_host_call<T: TraceValue>(
+        &mut self,
+        name: &'static str,
+        args: &[(&'static str, u64)],
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        match &self.result_state.host_calls {
+            Some(host_calls) if host_calls.len() < MAX_TRACED_HOST_CALLS => {}
+            _ => return f(self),
+        }
+        let burnt_gas_before = self.result_state.gas_counter.burnt_gas();
+        let result = f(self);
+        let mut registers = vec![];
+        for &(arg_name, register_id) in args {
+            if arg_name != "register_id" {
+                continue;
+            }
+            let Some(data) = self.registers.get_for_free(register_id) else {
+                continue;
+            };
+            let register_bytes = self.result_state.host_calls_register_bytes + data.len();
+            if register_bytes > MAX_TRACED_REGISTER_BYTES {
+                continue;
+            }
+            self.result_state.host_calls_register_bytes = register_bytes;
+            registers.push((register_id, data.to_vec()));
+        }
+        let call = HostFunctionCall {
+            name,
+            args: args.to_vec(),
+            result: result.as_ref().ok().and_then(TraceValue::trace_value),
+            error: result.as_ref().err().map(ToString::to_string),
+            burnt_gas_before,
+            burnt_gas_after: self.result_state.gas_counter.burnt_gas(),
+            registers,
+        };
+        if let Some(host_calls) = &mut self.result_state.host_calls {
+            host_calls.push(call);
+        }
+        result
+    }
+
     #[cfg(test)]
     pub(super) fn config(&self) -> &Config {
         &self.config
@@ -302,6 +365,26 @@ impl<'a> VMLogic<'a> {
         Ok(())
     }
 
+    // ##################
+    // # Wasm profiling #
+    // ##################
+    pub fn wasm_profile_enter(&mut self, function_index: u32) -> Result<()> {
+        let burnt_gas = self.result_state.gas_counter.burnt_gas();
+        self.result_state
+            .wasm_profile
+            .get_or_insert_with(|| WasmProfile::new(burnt_gas))
+            .enter(function_index, burnt_gas);
+        Ok(())
+    }
+
+    pub fn wasm_profile_exit(&mut self) -> Result<()> {
+        let burnt_gas = self.result_state.gas_counter.burnt_gas();
+        if let Some(profile) = &mut self.result_state.wasm_profile {
+            profile.exit(burnt_gas);
+        }
+        Ok(())
+    }
+
     // #################
     // # Registers API #
     // #################
@@ -3415,6 +3498,13 @@ pub struct VMOutcome {
     /// Data collected from making a contract call
     pub profile: ProfileDataV3,
     pub aborted: Option<FunctionCallError>,
+    /// Host functions called by the contract.  Empty unless
+    /// `VMContext::trace_host_calls` is set, and truncated to
+    /// `MAX_TRACED_HOST_CALLS` entries.
+    pub host_calls: Vec<HostFunctionCall>,
+    /// Gas burnt by the functions of the contract.  Only set if the contract
+    /// was prepared with `near_vm_runner::prepare_with_wasm_profile`.
+    pub wasm_profile: Option<WasmProfile>,
 }
 
 impl VMOutcome {
@@ -3447,6 +3537,8 @@ impl VMOutcome {
             logs: Vec::new(),
             profile: ProfileDataV3::default(),
             aborted: Some(error),
+            host_calls: Vec::new(),
+            wasm_profile: None,
         }
     }
 
@@ -10,9 +10,11 @@ pub mod recorded_storage_counter;
 pub mod test_utils;
 #[cfg(test)]
 mod tests;
+mod trace;
 pub mod types;
 mod utils;
 mod vmstate;
+mod wasm_profile;
 
 pub use context::VMContext;
 pub use dependencies::{External, MemSlice, MemoryLike, StorageAccessTracker, ValuePtr};
@@ -21,4 +23,6 @@ pub use gas_counter::{GasCounter, with_ext_cost_counter};
 pub use logic::{ExecutionResultState, VMLogic, VMOutcome};
 pub use near_parameters::vm::{Config, ContractPrepareVersion, LimitConfig};
 pub use near_primitives_core::types::ProtocolVersion;
+pub use trace::{HostFunctionCall, MAX_TRACED_HOST_CALLS, MAX_TRACED_REGISTER_BYTES, TraceValue};
 pub use types::ReturnData;
+pub use wasm_profile::WasmProfile;
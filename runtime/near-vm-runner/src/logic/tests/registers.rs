@@ -1,5 +1,5 @@
 use crate::logic::tests::vm_logic_builder::VMLogicBuilder;
-use crate::logic::{HostError, VMLogicError};
+use crate::logic::{HostError, MAX_TRACED_HOST_CALLS, MAX_TRACED_REGISTER_BYTES, VMLogicError};
 use crate::tests::test_vm_config;
 
 #[test]
@@ -86,3 +86,27 @@ fn test_register_is_not_used() {
     let mut logic = logic_builder.build();
     assert_eq!(logic.register_len(0), Ok(u64::MAX));
 }
+
+#[test]
+fn test_traced_registers_are_bounded() {
+    let mut logic_builder = VMLogicBuilder::free();
+    logic_builder.context.trace_host_calls = true;
+    logic_builder.context.input = vec![0u8; MAX_TRACED_REGISTER_BYTES * 2 / 3];
+    let mut logic = logic_builder.build();
+
+    for _ in 0..2 {
+        logic.trace_host_call("input", &[("register_id", 0)], |logic| logic.input(0)).unwrap();
+    }
+    for _ in 0..MAX_TRACED_HOST_CALLS {
+        logic
+            .trace_host_call("register_len", &[("register_id", 1)], |logic| logic.register_len(1))
+            .unwrap();
+    }
+
+    let outcome = logic.compute_outcome();
+    assert_eq!(outcome.host_calls.len(), MAX_TRACED_HOST_CALLS);
+    // The second copy of the input doesn't fit in the budget.
+    assert_eq!(outcome.host_calls[0].registers.len(), 1);
+    assert!(outcome.host_calls[1].registers.is_empty());
+    assert_eq!(outcome.host_calls[1].args, vec![("register_id", 0)]);
+}
@@ -0,0 +1,67 @@
+//! Opt-in log of the host functions called by a contract.
+//!
+//! When [`VMContext::trace_host_calls`](super::VMContext::trace_host_calls) is
+//! set, the Wasm runtimes route every host function call through
+//! [`VMLogic::trace_host_call`](super::VMLogic::trace_host_call), which
+//! records its arguments, the returned value, the gas burnt before and after
+//! the call and the contents of the registers the call refers to.  The log
+//! ends up in [`VMOutcome::host_calls`](super::VMOutcome::host_calls).
+//!
+//! Tracing is meant for debugging only, it has no effect on the execution
+//! itself and is never enabled when applying chunks.
+//!
+//! As traces can be requested by untrusted callers through view calls, the
+//! log is bounded: calls past [`MAX_TRACED_HOST_CALLS`] aren't recorded and
+//! register contents are omitted once [`MAX_TRACED_REGISTER_BYTES`] have been
+//! recorded.
+use near_primitives_core::types::Gas;
+
+/// Maximum number of host function calls recorded for one execution.
+pub const MAX_TRACED_HOST_CALLS: usize = 10_000;
+
+/// Maximum total size of the register contents recorded for one execution.
+pub const MAX_TRACED_REGISTER_BYTES: usize = 1024 * 1024;
+
+/// A single host function call made by a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostFunctionCall {
+    /// Name of the host function, as imported by the contract.
+    pub name: &'static str,
+    /// Names and values of the arguments, in the order of declaration.
+    pub args: Vec<(&'static str, u64)>,
+    /// Value returned by the host function, `None` for functions which don't
+    /// return anything or which failed.
+    pub result: Option<u64>,
+    /// Error returned by the host function.  It aborts the execution of the
+    /// contract, so this can only be set for the last call.
+    pub error: Option<String>,
+    pub burnt_gas_before: Gas,
+    pub burnt_gas_after: Gas,
+    /// Contents of the registers passed in `register_id` arguments, read
+    /// after the call.  Registers which don't exist or which don't fit in
+    /// [`MAX_TRACED_REGISTER_BYTES`] are omitted.
+    pub registers: Vec<(u64, Vec<u8>)>,
+}
+
+/// Conversion of values returned by host functions to `u64` for the trace.
+pub trait TraceValue {
+    fn trace_value(&self) -> Option<u64>;
+}
+
+impl TraceValue for () {
+    fn trace_value(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl TraceValue for u32 {
+    fn trace_value(&self) -> Option<u64> {
+        Some(u64::from(*self))
+    }
+}
+
+impl TraceValue for u64 {
+    fn trace_value(&self) -> Option<u64> {
+        Some(*self)
+    }
+}
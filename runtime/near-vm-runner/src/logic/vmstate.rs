@@ -146,7 +146,6 @@ impl Registers {
         }
     }
 
-    #[cfg(test)]
     pub(super) fn get_for_free<'s>(&'s self, register_id: u64) -> Option<&'s [u8]> {
         self.registers.get(&register_id).map(|data| &data[..])
     }
@@ -0,0 +1,110 @@
+//! Opt-in attribution of the gas burnt by a contract to its functions.
+//!
+//! Contracts prepared with [`crate::prepare_with_wasm_profile`] are
+//! instrumented to call `wasm_profile_enter` and `wasm_profile_exit` host
+//! functions around the body of every function, see
+//! `instrument::wasm_profile`.  The gas burnt in between is attributed to the
+//! stack of functions being executed, including the gas of the host
+//! functions they call, and ends up in
+//! [`VMOutcome::wasm_profile`](super::VMOutcome::wasm_profile).
+use near_primitives_core::types::Gas;
+use std::collections::{BTreeMap, HashMap};
+
+/// Gas burnt by each stack of contract functions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WasmProfile {
+    /// Gas burnt with exactly the given functions on the stack, outermost
+    /// function first.
+    stacks: BTreeMap<Vec<u32>, Gas>,
+    call_stack: Vec<u32>,
+    last_burnt_gas: Gas,
+    /// Names of the functions from the name section of the contract.
+    pub(crate) names: HashMap<u32, String>,
+}
+
+impl WasmProfile {
+    pub(crate) fn new(burnt_gas: Gas) -> Self {
+        Self { last_burnt_gas: burnt_gas, ..Self::default() }
+    }
+
+    pub(crate) fn enter(&mut self, function_index: u32, burnt_gas: Gas) {
+        self.record(burnt_gas);
+        self.call_stack.push(function_index);
+    }
+
+    pub(crate) fn exit(&mut self, burnt_gas: Gas) {
+        self.record(burnt_gas);
+        self.call_stack.pop();
+    }
+
+    /// Attributes the gas burnt since the last event to the functions on the
+    /// stack at the end of the execution, which are not exited on a trap.
+    pub(crate) fn finish(mut self, burnt_gas: Gas) -> Self {
+        self.record(burnt_gas);
+        self.call_stack.clear();
+        self
+    }
+
+    /// Gas burnt while no function is on the stack, i.e. before the entry
+    /// point is called, is not attributed to anything.
+    fn record(&mut self, burnt_gas: Gas) {
+        let gas = burnt_gas.saturating_sub(self.last_burnt_gas);
+        self.last_burnt_gas = burnt_gas;
+        if gas > 0 && !self.call_stack.is_empty() {
+            *self.stacks.entry(self.call_stack.clone()).or_default() += gas;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    /// Total gas attributed to the functions of the contract.
+    pub fn total_gas(&self) -> Gas {
+        self.stacks.values().sum()
+    }
+
+    /// Formats the profile as collapsed stacks, one `a;b;c <gas>` line per
+    /// stack, which is the input format of most flamegraph tools.  Functions
+    /// without a name are called `func[<index>]`.
+    pub fn collapsed_stacks(&self) -> Vec<String> {
+        self.stacks
+            .iter()
+            .map(|(stack, gas)| {
+                let frames: Vec<String> = stack
+                    .iter()
+                    .map(|index| match self.names.get(index) {
+                        Some(name) => name.replace(';', ":"),
+                        None => format!("func[{index}]"),
+                    })
+                    .collect();
+                format!("{} {gas}", frames.join(";"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WasmProfile;
+
+    #[test]
+    fn test_collapsed_stacks() {
+        let mut profile = WasmProfile::new(10);
+        profile.enter(3, 15);
+        profile.enter(4, 20);
+        profile.exit(50);
+        profile.enter(4, 60);
+        profile.exit(61);
+        profile.exit(70);
+        profile.enter(5, 100);
+        let mut profile = profile.finish(101);
+        profile.names.insert(3, "main;loop".to_string());
+
+        assert_eq!(profile.total_gas(), 56);
+        assert_eq!(
+            profile.collapsed_stacks(),
+            vec!["main:loop 24", "main:loop;func[4] 31", "func[5] 1"]
+        );
+    }
+}
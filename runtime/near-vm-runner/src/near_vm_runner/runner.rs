@@ -99,6 +99,9 @@ fn translate_runtime_error(
 pub(crate) struct NearVM {
     pub(crate) config: Arc<Config>,
     pub(crate) engine: UniversalEngine,
+    /// Whether to instrument the compiled contracts for profiling, see
+    /// `crate::prepare_with_wasm_profile`.
+    pub(crate) profile_wasm_functions: bool,
 }
 
 impl NearVM {
@@ -138,6 +141,7 @@ impl NearVM {
                 .features(features.into())
                 .code_memory_pool(code_memory_pool)
                 .engine(),
+            profile_wasm_functions: false,
         }
     }
 
@@ -166,8 +170,13 @@ impl NearVM {
     ) -> Result<UniversalExecutable, CompilationError> {
         let _span = tracing::debug_span!(target: "vm", "NearVM::compile_uncached").entered();
         let start = std::time::Instant::now();
-        let prepared_code = prepare::prepare_contract(code.code(), &self.config, VMKind::NearVm)
-            .map_err(CompilationError::PrepareError)?;
+        let prepared_code = prepare::prepare_contract_maybe_profiled(
+            code.code(),
+            &self.config,
+            VMKind::NearVm,
+            self.profile_wasm_functions,
+        )
+        .map_err(CompilationError::PrepareError)?;
 
         debug_assert!(
             matches!(self.engine.validate(&prepared_code), Ok(_)),
@@ -770,11 +779,21 @@ impl<'e, 'l, 'lr> Resolver for NearVmImports<'e, 'l, 'lr> {
                                 tracing::trace_span!(target: "vm::host_function", stringify!($name)).entered()
                             });
 
+                            const RECORD: bool = $crate::imports::should_record_host_call(stringify!($mod));
                             // SAFETY: This code should only be executable within `'vmlogic`
                             // lifetime and so it is safe to dereference the `env` pointer which is
                             // known to be derived from a valid `&'vmlogic mut VMLogic<'_>` in the
                             // first place.
-                            unsafe { (*env).$func( $( $arg_name, )* ) }
+                            let logic = unsafe { &mut *env };
+                            if RECORD {
+                                logic.trace_host_call(
+                                    stringify!($name),
+                                    &[$( (stringify!($arg_name), $arg_name as u64) ),*],
+                                    |logic| logic.$func( $( $arg_name, )* ),
+                                )
+                            } else {
+                                logic.$func( $( $arg_name, )* )
+                            }
                         }));
                         // We want to ensure that the only kind of error that host function calls
                         // return are VMLogicError. This is important because we later attempt to
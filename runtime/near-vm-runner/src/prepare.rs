@@ -23,6 +23,19 @@ pub fn prepare_contract(
     original_code: &[u8],
     config: &Config,
     kind: VMKind,
+) -> Result<Vec<u8>, PrepareError> {
+    prepare_contract_maybe_profiled(original_code, config, kind, false)
+}
+
+/// Like [`prepare_contract`], but with `profile_wasm_functions` set also
+/// instruments the contract for [`crate::prepare_with_wasm_profile`].  Only
+/// `ContractPrepareVersion::V2` supports the instrumentation, the flag is
+/// ignored with the older versions.
+pub(crate) fn prepare_contract_maybe_profiled(
+    original_code: &[u8],
+    config: &Config,
+    kind: VMKind,
+    profile_wasm_functions: bool,
 ) -> Result<Vec<u8>, PrepareError> {
     let prepare = config.limit_config.contract_prepare_version;
     // NearVM => ContractPrepareVersion::V2
@@ -41,9 +54,13 @@ pub fn prepare_contract(
             prepare_v1::validate_contract(original_code, features, config)?;
             prepare_v1::prepare_contract(original_code, config)
         }
-        crate::logic::ContractPrepareVersion::V2 => {
-            prepare_v2::prepare_contract(original_code, features, config, kind)
-        }
+        crate::logic::ContractPrepareVersion::V2 => prepare_v2::prepare_contract(
+            original_code,
+            features,
+            config,
+            kind,
+            profile_wasm_functions,
+        ),
     }
 }
 
@@ -272,8 +272,12 @@ pub(crate) fn prepare_contract(
     features: crate::features::WasmFeatures,
     config: &Config,
     kind: VMKind,
+    profile_wasm_functions: bool,
 ) -> Result<Vec<u8>, PrepareError> {
-    let lightly_steamed = PrepareContext::new(original_code, features, config).run()?;
+    let mut lightly_steamed = PrepareContext::new(original_code, features, config).run()?;
+    if profile_wasm_functions {
+        lightly_steamed = crate::instrument::wasm_profile::inject_profiling(&lightly_steamed)?;
+    }
 
     if kind == VMKind::NearVm {
         // Built-in near-vm code instruments code for itself.
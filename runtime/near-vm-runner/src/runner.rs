@@ -52,6 +52,57 @@ pub fn prepare(
     runtime.prepare(contract, cache, gas_counter, method)
 }
 
+/// Like [`prepare`], but the contract is instrumented to attribute the gas it
+/// burns to its functions, see [`VMOutcome::wasm_profile`].
+///
+/// The instrumentation burns gas too, so this is only meant for debugging
+/// tools.  The instrumented contract is never cached, as it would be stored
+/// under the same key as the regular one.
+#[cfg(feature = "prepare")]
+pub fn prepare_with_wasm_profile(
+    contract: &dyn Contract,
+    wasm_config: Arc<Config>,
+    gas_counter: crate::logic::GasCounter,
+    method: &str,
+) -> Box<dyn crate::PreparedContract> {
+    // The name section is dropped by the instrumentation, so the names are
+    // read from the original code.
+    let names = contract
+        .get_code()
+        .map(|code| crate::instrument::wasm_profile::function_names(code.code()))
+        .unwrap_or_default();
+    let vm_kind = wasm_config.vm_kind;
+    let runtime = make_vm(vm_kind, wasm_config, true)
+        .unwrap_or_else(|| panic!("the {vm_kind:?} runtime has not been enabled at compile time"));
+    let prepared = runtime.prepare(contract, None, gas_counter, method);
+    Box::new(ProfiledContract { prepared, names })
+}
+
+/// Adds the function names to the profile of a contract prepared by
+/// [`prepare_with_wasm_profile`].
+#[cfg(feature = "prepare")]
+struct ProfiledContract {
+    prepared: Box<dyn PreparedContract>,
+    names: std::collections::HashMap<u32, String>,
+}
+
+#[cfg(feature = "prepare")]
+impl PreparedContract for ProfiledContract {
+    fn run(
+        self: Box<Self>,
+        ext: &mut dyn External,
+        context: &VMContext,
+        fees_config: Arc<RuntimeFeesConfig>,
+    ) -> VMResult {
+        let Self { prepared, names } = *self;
+        let mut outcome = prepared.run(ext, context, fees_config)?;
+        if let Some(profile) = &mut outcome.wasm_profile {
+            profile.names = names;
+        }
+        Ok(outcome)
+    }
+}
+
 /// Validate and run the specified contract.
 ///
 /// This is the entry point for executing a NEAR protocol contract. Before the
@@ -170,18 +221,41 @@ impl VMKindExt for VMKind {
         }
     }
     fn runtime(&self, config: std::sync::Arc<Config>) -> Option<Box<dyn VM>> {
-        match self {
-            #[cfg(feature = "wasmtime_vm")]
-            Self::Wasmtime => Some(Box::new(crate::wasmtime_runner::WasmtimeVM::new(config))),
-            #[cfg(all(feature = "wasmer2_vm", target_arch = "x86_64"))]
-            Self::Wasmer2 => Some(Box::new(crate::wasmer2_runner::Wasmer2VM::new(config))),
-            #[cfg(all(feature = "near_vm", target_arch = "x86_64"))]
-            Self::NearVm => Some(Box::new(crate::near_vm_runner::NearVM::new(config))),
-            #[allow(unreachable_patterns)] // reachable when some of the VMs are disabled.
-            _ => {
-                let _ = config;
-                None
-            }
+        make_vm(*self, config, false)
+    }
+}
+
+/// Makes a [`VM`] of the given kind, which instruments the contracts it
+/// compiles for [`prepare_with_wasm_profile`] if `profile_wasm_functions` is
+/// set.
+fn make_vm(
+    vm_kind: VMKind,
+    config: Arc<Config>,
+    profile_wasm_functions: bool,
+) -> Option<Box<dyn VM>> {
+    match vm_kind {
+        #[cfg(feature = "wasmtime_vm")]
+        VMKind::Wasmtime => {
+            let mut vm = crate::wasmtime_runner::WasmtimeVM::new(config);
+            vm.profile_wasm_functions = profile_wasm_functions;
+            Some(Box::new(vm))
+        }
+        #[cfg(all(feature = "wasmer2_vm", target_arch = "x86_64"))]
+        VMKind::Wasmer2 => {
+            let mut vm = crate::wasmer2_runner::Wasmer2VM::new(config);
+            vm.profile_wasm_functions = profile_wasm_functions;
+            Some(Box::new(vm))
+        }
+        #[cfg(all(feature = "near_vm", target_arch = "x86_64"))]
+        VMKind::NearVm => {
+            let mut vm = crate::near_vm_runner::NearVM::new(config);
+            vm.profile_wasm_functions = profile_wasm_functions;
+            Some(Box::new(vm))
+        }
+        #[allow(unreachable_patterns)] // reachable when some of the VMs are disabled.
+        _ => {
+            let _ = (config, profile_wasm_functions);
+            None
         }
     }
 }
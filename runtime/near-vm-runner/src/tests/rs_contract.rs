@@ -77,6 +77,74 @@ pub fn test_read_write() {
     });
 }
 
+#[test]
+pub fn test_trace_host_calls() {
+    let config = Arc::new(test_vm_config());
+    let fees = Arc::new(RuntimeFeesConfig::test());
+    with_vm_variants(&config, |vm_kind: VMKind| {
+        let code = test_contract(vm_kind);
+        let mut fake_external = MockedExternal::with_code(code);
+        let input = encode(&[10u64, 20u64]);
+        let mut context = create_context(input.clone());
+        context.trace_host_calls = true;
+
+        let runtime = vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+        let gas_counter = context.make_gas_counter(&config);
+        let outcome = runtime
+            .prepare(&fake_external, None, gas_counter, "write_key_value")
+            .run(&mut fake_external, &context, Arc::clone(&fees))
+            .expect("Failed execution");
+        let calls = &outcome.host_calls;
+        assert_eq!(calls[0].name, "input");
+        assert_eq!(calls[0].args, vec![("register_id", 0)]);
+        assert_eq!(calls[0].registers, vec![(0, input)]);
+        assert!(calls.iter().any(|call| call.name == "storage_write"));
+        // Calls inserted by the instrumentation are not recorded.
+        assert!(calls.iter().all(|call| !call.name.starts_with("finite_wasm")));
+        assert!(calls.iter().all(|call| call.error.is_none()));
+        for (call, next_call) in calls.iter().zip(&calls[1..]) {
+            assert!(call.burnt_gas_before < call.burnt_gas_after);
+            assert!(call.burnt_gas_after <= next_call.burnt_gas_before);
+        }
+        assert!(calls.last().unwrap().burnt_gas_after <= outcome.burnt_gas);
+
+        context.trace_host_calls = false;
+        let gas_counter = context.make_gas_counter(&config);
+        let outcome = runtime
+            .prepare(&fake_external, None, gas_counter, "write_key_value")
+            .run(&mut fake_external, &context, Arc::clone(&fees))
+            .expect("Failed execution");
+        assert!(outcome.host_calls.is_empty());
+    });
+}
+
+#[test]
+pub fn test_wasm_profile() {
+    let fees = Arc::new(RuntimeFeesConfig::test());
+    with_vm_variants(&test_vm_config(), |vm_kind: VMKind| {
+        let mut config = test_vm_config();
+        config.vm_kind = vm_kind;
+        let config = Arc::new(config);
+        let code = test_contract(vm_kind);
+        let mut fake_external = MockedExternal::with_code(code);
+        let context = create_context(encode(&[10u64, 20u64]));
+
+        let gas_counter = context.make_gas_counter(&config);
+        let prepared = crate::runner::prepare_with_wasm_profile(
+            &fake_external,
+            Arc::clone(&config),
+            gas_counter,
+            "write_key_value",
+        );
+        let outcome = crate::runner::run(prepared, &mut fake_external, &context, Arc::clone(&fees))
+            .expect("Failed execution");
+        assert!(outcome.aborted.is_none());
+        let profile = outcome.wasm_profile.expect("profile should be recorded");
+        assert!(!profile.is_empty());
+        assert!(profile.total_gas() <= outcome.burnt_gas);
+    });
+}
+
 macro_rules! def_test_ext {
     ($name:ident, $method:expr, $expected:expr, $input:expr, $validator:expr) => {
         #[test]
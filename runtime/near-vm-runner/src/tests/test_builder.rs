@@ -28,6 +28,7 @@ pub(crate) fn test_builder() -> TestBuilder {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_calls: false,
     };
     let mut skip = HashSet::new();
     for kind in [VMKind::Wasmer2, VMKind::NearVm, VMKind::Wasmtime] {
@@ -228,6 +228,9 @@ pub(crate) type VMArtifact = Arc<wasmer_engine_universal::UniversalArtifact>;
 pub(crate) struct Wasmer2VM {
     pub(crate) config: Arc<Config>,
     pub(crate) engine: UniversalEngine,
+    /// Whether to instrument the compiled contracts for profiling, see
+    /// `crate::prepare_with_wasm_profile`.
+    pub(crate) profile_wasm_functions: bool,
 }
 
 impl Wasmer2VM {
@@ -242,6 +245,7 @@ impl Wasmer2VM {
         Self {
             config,
             engine: Universal::new(compiler).target(target).features(features.into()).engine(),
+            profile_wasm_functions: false,
         }
     }
 
@@ -269,8 +273,13 @@ impl Wasmer2VM {
         code: &ContractCode,
     ) -> Result<UniversalExecutable, CompilationError> {
         let _span = tracing::debug_span!(target: "vm", "Wasmer2VM::compile_uncached").entered();
-        let prepared_code = prepare::prepare_contract(code.code(), &self.config, VMKind::Wasmer2)
-            .map_err(CompilationError::PrepareError)?;
+        let prepared_code = prepare::prepare_contract_maybe_profiled(
+            code.code(),
+            &self.config,
+            VMKind::Wasmer2,
+            self.profile_wasm_functions,
+        )
+        .map_err(CompilationError::PrepareError)?;
 
         debug_assert!(
             matches!(self.engine.validate(&prepared_code), Ok(_)),
@@ -746,11 +755,21 @@ impl<'e, 'l, 'lr> Resolver for Wasmer2Imports<'e, 'l, 'lr> {
                             tracing::trace_span!(target: "vm::host_function", stringify!($name)).entered()
                         });
 
+                        const RECORD: bool = $crate::imports::should_record_host_call(stringify!($mod));
                         // SAFETY: This code should only be executable within `'vmlogic`
                         // lifetime and so it is safe to dereference the `env` pointer which is
                         // known to be derived from a valid `&'vmlogic mut VMLogic<'_>` in the
                         // first place.
-                        unsafe { (*env).$func( $( $arg_name, )* ) }
+                        let logic = unsafe { &mut *env };
+                        if RECORD {
+                            logic.trace_host_call(
+                                stringify!($name),
+                                &[$( (stringify!($arg_name), $arg_name as u64) ),*],
+                                |logic| logic.$func( $( $arg_name, )* ),
+                            )
+                        } else {
+                            logic.$func( $( $arg_name, )* )
+                        }
                     }));
                     // We want to ensure that the only kind of error that host function calls
                     // return are VMLogicError. This is important because we later attempt to
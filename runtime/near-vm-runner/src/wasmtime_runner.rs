@@ -143,18 +143,30 @@ pub(crate) fn wasmtime_vm_hash() -> u64 {
 pub(crate) struct WasmtimeVM {
     config: Arc<Config>,
     engine: wasmtime::Engine,
+    /// Whether to instrument the compiled contracts for profiling, see
+    /// `crate::prepare_with_wasm_profile`.
+    pub(crate) profile_wasm_functions: bool,
 }
 
 impl WasmtimeVM {
     pub(crate) fn new(config: Arc<Config>) -> Self {
-        Self { engine: get_engine(&default_wasmtime_config(&config)), config }
+        Self {
+            engine: get_engine(&default_wasmtime_config(&config)),
+            config,
+            profile_wasm_functions: false,
+        }
     }
 
     #[tracing::instrument(target = "vm", level = "debug", "WasmtimeVM::compile_uncached", skip_all)]
     fn compile_uncached(&self, code: &ContractCode) -> Result<Vec<u8>, CompilationError> {
         let start = std::time::Instant::now();
-        let prepared_code = prepare::prepare_contract(code.code(), &self.config, VMKind::Wasmtime)
-            .map_err(CompilationError::PrepareError)?;
+        let prepared_code = prepare::prepare_contract_maybe_profiled(
+            code.code(),
+            &self.config,
+            VMKind::Wasmtime,
+            self.profile_wasm_functions,
+        )
+        .map_err(CompilationError::PrepareError)?;
         let serialized = self.engine.precompile_module(&prepared_code).map_err(|err| {
             tracing::error!(?err, "wasmtime failed to compile the prepared code (this is defense-in-depth, the error was recovered from but should be reported to the developers)");
             CompilationError::WasmtimeCompileError { msg: err.to_string() }
@@ -474,7 +486,17 @@ fn link<'a, 'b>(
                     crate::wasmtime_runner::CALLER.with(|runner_caller| *runner_caller.borrow_mut() = std::mem::transmute(caller));
                 }
                 let logic: &mut VMLogic<'_> = unsafe { &mut *(data as *mut VMLogic<'_>) };
-                match logic.$func( $( $arg_name as $arg_type, )* ) {
+                const RECORD: bool = imports::should_record_host_call(stringify!($mod));
+                let result = if RECORD {
+                    logic.trace_host_call(
+                        stringify!($name),
+                        &[$( (stringify!($arg_name), $arg_name as u64) ),*],
+                        |logic| logic.$func( $( $arg_name as $arg_type, )* ),
+                    )
+                } else {
+                    logic.$func( $( $arg_name as $arg_type, )* )
+                };
+                match result {
                     Ok(result) => Ok(result as ($( $returns ),* ) ),
                     Err(err) => {
                         Err(ErrorContainer(std::sync::Mutex::new(Some(err))).into())
@@ -8,7 +8,7 @@ use near_parameters::config::CongestionControlConfig;
 use near_parameters::{ExtCosts, RuntimeConfigStore};
 use near_primitives::apply::ApplyChunkReason;
 use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
-use near_primitives::chunk_apply_stats::ChunkApplyStatsV0;
+use near_primitives::chunk_apply_stats::ChunkApplyStatsV1;
 use near_primitives::congestion_info::{BlockCongestionInfo, ExtendedCongestionInfo};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::Receipt;
@@ -197,6 +197,8 @@ impl<'c> EstimatorContext<'c> {
             migration_flags: MigrationFlags::default(),
             congestion_info,
             bandwidth_requests: BlockBandwidthRequests::empty(),
+            trace_host_calls: false,
+            profile_wasm_functions: false,
         }
     }
 
@@ -485,7 +487,7 @@ impl Testbed<'_> {
         let mut outgoing_receipts = vec![];
         let mut validator_proposals = vec![];
         let mut stats =
-            ChunkApplyStatsV0::new(self.apply_state.block_height, self.apply_state.shard_id);
+            ChunkApplyStatsV1::new(self.apply_state.block_height, self.apply_state.shard_id);
         // TODO: mock is not accurate, potential DB requests are skipped in the mock!
         let epoch_info_provider = MockEpochInfoProvider::default();
         let clock = GasCost::measure(metric);
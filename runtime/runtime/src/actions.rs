@@ -84,6 +84,7 @@ pub(crate) fn execute_function_call(
         random_seed,
         view_config,
         output_data_receivers,
+        trace_host_calls: apply_state.trace_host_calls,
     };
 
     // Enable caching chunk mode for the function call. This allows to charge for nodes touched in a chunk only once for
@@ -273,6 +274,12 @@ pub(crate) fn action_function_call(
     result.compute_usage = safe_add_compute(result.compute_usage, outcome.compute_usage)?;
     result.logs.extend(outcome.logs);
     result.profile.merge(&outcome.profile);
+    result
+        .host_calls
+        .extend(outcome.host_calls.into_iter().map(crate::conversions::Convert::convert));
+    if let Some(wasm_profile) = &outcome.wasm_profile {
+        result.wasm_profile.extend(wasm_profile.collapsed_stacks());
+    }
     if execution_succeeded {
         // Fetch metadata for PromiseYield timeout queue
         let mut promise_yield_indices = get_promise_yield_indices(state_update).unwrap_or_default();
@@ -1405,6 +1412,8 @@ mod tests {
             migration_flags: MigrationFlags::default(),
             congestion_info: BlockCongestionInfo::default(),
             bandwidth_requests: BlockBandwidthRequests::empty(),
+            trace_host_calls: false,
+            profile_wasm_functions: false,
         }
     }
 
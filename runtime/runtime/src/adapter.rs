@@ -1,12 +1,13 @@
 use crate::near_primitives::shard_layout::ShardUId;
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
+use near_primitives::action::GlobalContractIdentifier;
 use near_primitives::hash::CryptoHash;
 use near_primitives::types::{
     AccountId, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, MerkleHash,
 };
 use near_primitives::version::ProtocolVersion;
-use near_primitives::views::ViewStateResult;
+use near_primitives::views::{HostFunctionCallView, ViewStateResult};
 use near_vm_runner::ContractCode;
 
 /// Adapter for querying runtime.
@@ -25,6 +26,13 @@ pub trait ViewRuntimeAdapter {
         account_id: &AccountId,
     ) -> Result<ContractCode, crate::state_viewer::errors::ViewContractCodeError>;
 
+    fn view_global_contract_code(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        identifier: &GlobalContractIdentifier,
+    ) -> Result<ContractCode, crate::state_viewer::errors::ViewContractCodeError>;
+
     fn call_function(
         &self,
         shard_uid: &ShardUId,
@@ -39,6 +47,7 @@ pub trait ViewRuntimeAdapter {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        host_calls: Option<&mut Vec<HostFunctionCallView>>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, crate::state_viewer::errors::CallFunctionError>;
@@ -0,0 +1,176 @@
+//! Audit of the balances moved by a chunk.
+//!
+//! The runtime only counts the tokens burnt or created while a chunk is applied, see
+//! [`BalanceStats`](near_primitives::chunk_apply_stats::BalanceStats).  The balances which stayed
+//! in the shard are computed after the fact from the state changes of the chunk: for every touched
+//! account and stored receipt the value before the chunk is read from the trie of the previous
+//! state root and the value after it is the last change.  Reading the trie here instead of during
+//! the application keeps the storage proof of the chunk unchanged.
+use crate::config::{
+    safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit, total_prepaid_exec_fees,
+    total_prepaid_gas, total_prepaid_send_fees,
+};
+use near_parameters::{ActionCosts, RuntimeConfig};
+use near_primitives::account::Account;
+use near_primitives::chunk_apply_stats::BalanceAudit;
+use near_primitives::errors::{IntegerOverflowError, RuntimeError, StorageError};
+use near_primitives::receipt::{Receipt, ReceiptEnum, ReceiptOrStateStoredReceipt};
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{Balance, RawStateChangesWithTrieKey};
+use near_store::TrieAccess;
+
+/// Computes the balances of the accounts and stored receipts touched by the chunk before and
+/// after it was applied, and the value of its incoming and outgoing receipts.
+///
+/// `initial_state` is the trie at the state root the chunk was applied to and `state_changes`
+/// are the changes made by the chunk.
+pub fn audit_chunk_balance(
+    config: &RuntimeConfig,
+    initial_state: &dyn TrieAccess,
+    state_changes: &[RawStateChangesWithTrieKey],
+    incoming_receipts: &[Receipt],
+    outgoing_receipts: &[Receipt],
+) -> Result<BalanceAudit, RuntimeError> {
+    let mut audit = BalanceAudit {
+        incoming_receipts_balance: receipts_cost(config, incoming_receipts)?,
+        outgoing_receipts_balance: receipts_cost(config, outgoing_receipts)?,
+        ..Default::default()
+    };
+    for change in state_changes {
+        let key = &change.trie_key;
+        let Some(kind) = ValueKind::of(key) else {
+            continue;
+        };
+        let initial_value = initial_state.get(key)?;
+        let final_value = change.changes.last().and_then(|change| change.data.clone());
+        match kind {
+            ValueKind::Account => {
+                let (balance, staked) = account_balance(config, initial_value)?;
+                audit.initial_accounts_balance =
+                    safe_add_balance(audit.initial_accounts_balance, balance)?;
+                audit.initial_storage_staked =
+                    safe_add_balance(audit.initial_storage_staked, staked)?;
+                let (balance, staked) = account_balance(config, final_value)?;
+                audit.final_accounts_balance =
+                    safe_add_balance(audit.final_accounts_balance, balance)?;
+                audit.final_storage_staked = safe_add_balance(audit.final_storage_staked, staked)?;
+            }
+            ValueKind::QueuedReceipt | ValueKind::Receipt => {
+                audit.initial_stored_receipts_balance = safe_add_balance(
+                    audit.initial_stored_receipts_balance,
+                    stored_receipt_cost(config, kind, initial_value)?,
+                )?;
+                audit.final_stored_receipts_balance = safe_add_balance(
+                    audit.final_stored_receipts_balance,
+                    stored_receipt_cost(config, kind, final_value)?,
+                )?;
+            }
+        }
+    }
+    Ok(audit)
+}
+
+#[derive(Clone, Copy)]
+enum ValueKind {
+    Account,
+    /// Delayed or buffered receipt, stored as `ReceiptOrStateStoredReceipt`.
+    QueuedReceipt,
+    /// Postponed or yielded receipt, stored as `Receipt`.
+    Receipt,
+}
+
+impl ValueKind {
+    fn of(key: &TrieKey) -> Option<Self> {
+        match key {
+            TrieKey::Account { .. } => Some(Self::Account),
+            TrieKey::DelayedReceipt { .. } | TrieKey::BufferedReceipt { .. } => {
+                Some(Self::QueuedReceipt)
+            }
+            TrieKey::PostponedReceipt { .. } | TrieKey::PromiseYieldReceipt { .. } => {
+                Some(Self::Receipt)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the total balance of the account and the part of it staked for storage.
+fn account_balance(
+    config: &RuntimeConfig,
+    value: Option<Vec<u8>>,
+) -> Result<(Balance, Balance), RuntimeError> {
+    let Some(value) = value else {
+        return Ok((0, 0));
+    };
+    let account: Account = deserialize(&value)?;
+    let balance = safe_add_balance(account.amount(), account.locked())?;
+    let staked = Balance::from(account.storage_usage())
+        .checked_mul(config.storage_amount_per_byte())
+        .ok_or(IntegerOverflowError)?;
+    Ok((balance, staked))
+}
+
+fn stored_receipt_cost(
+    config: &RuntimeConfig,
+    kind: ValueKind,
+    value: Option<Vec<u8>>,
+) -> Result<Balance, RuntimeError> {
+    let Some(value) = value else {
+        return Ok(0);
+    };
+    let cost = match kind {
+        ValueKind::QueuedReceipt => {
+            let receipt: ReceiptOrStateStoredReceipt = deserialize(&value)?;
+            receipt_cost(config, receipt.get_receipt())?
+        }
+        ValueKind::Receipt => receipt_cost(config, &deserialize(&value)?)?,
+        ValueKind::Account => unreachable!("accounts are not receipts"),
+    };
+    Ok(cost)
+}
+
+fn receipts_cost(config: &RuntimeConfig, receipts: &[Receipt]) -> Result<Balance, RuntimeError> {
+    receipts
+        .iter()
+        .try_fold(0, |total, receipt| Ok(safe_add_balance(total, receipt_cost(config, receipt)?)?))
+}
+
+/// Value carried by the receipt: its deposits and, unless it is a refund, the gas prepaid for it
+/// at the gas price at which it was purchased.
+fn receipt_cost(
+    config: &RuntimeConfig,
+    receipt: &Receipt,
+) -> Result<Balance, IntegerOverflowError> {
+    match receipt.receipt() {
+        ReceiptEnum::Action(action_receipt) | ReceiptEnum::PromiseYield(action_receipt) => {
+            let mut total_cost = total_deposit(&action_receipt.actions)?;
+            if !receipt.predecessor_id().is_system() {
+                let mut total_gas = safe_add_gas(
+                    config.fees.fee(ActionCosts::new_action_receipt).exec_fee(),
+                    total_prepaid_exec_fees(
+                        config,
+                        &action_receipt.actions,
+                        receipt.receiver_id(),
+                    )?,
+                )?;
+                total_gas = safe_add_gas(total_gas, total_prepaid_gas(&action_receipt.actions)?)?;
+                total_gas = safe_add_gas(
+                    total_gas,
+                    total_prepaid_send_fees(config, &action_receipt.actions)?,
+                )?;
+                let total_gas_cost = safe_gas_to_balance(action_receipt.gas_price, total_gas)?;
+                total_cost = safe_add_balance(total_cost, total_gas_cost)?;
+            }
+            Ok(total_cost)
+        }
+        ReceiptEnum::Data(_)
+        | ReceiptEnum::PromiseResume(_)
+        | ReceiptEnum::GlobalContractDistribution(_) => Ok(0),
+    }
+}
+
+fn deserialize<T: borsh::BorshDeserialize>(value: &[u8]) -> Result<T, RuntimeError> {
+    borsh::from_slice(value).map_err(|err| {
+        StorageError::StorageInconsistentState(format!("failed to deserialize value: {err}")).into()
+    })
+}
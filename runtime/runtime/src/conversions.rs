@@ -88,3 +88,29 @@ mod profile_data_v3 {
         }
     }
 }
+
+mod host_function_call {
+    use near_primitives::views::{HostFunctionCallView, RegisterView};
+    use near_vm_runner::logic::HostFunctionCall as From;
+    impl super::Convert<From> for HostFunctionCallView {
+        fn convert(other: From) -> Self {
+            Self {
+                name: other.name.to_string(),
+                args: other
+                    .args
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+                result: other.result,
+                error: other.error,
+                burnt_gas_before: other.burnt_gas_before,
+                burnt_gas_after: other.burnt_gas_after,
+                registers: other
+                    .registers
+                    .into_iter()
+                    .map(|(register_id, data)| RegisterView { register_id, data })
+                    .collect(),
+            }
+        }
+    }
+}
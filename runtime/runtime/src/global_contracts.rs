@@ -6,7 +6,7 @@ use near_primitives::action::{
     DeployGlobalContractAction, GlobalContractDeployMode, GlobalContractIdentifier,
     UseGlobalContractAction,
 };
-use near_primitives::chunk_apply_stats::ChunkApplyStatsV0;
+use near_primitives::chunk_apply_stats::ChunkApplyStatsV1;
 use near_primitives::errors::{ActionErrorKind, RuntimeError};
 use near_primitives::hash::hash;
 use near_primitives::receipt::{GlobalContractDistributionReceipt, Receipt, ReceiptEnum};
@@ -25,7 +25,7 @@ pub(crate) fn action_deploy_global_contract(
     apply_state: &ApplyState,
     deploy_contract: &DeployGlobalContractAction,
     result: &mut ActionResult,
-    stats: &mut ChunkApplyStatsV0,
+    stats: &mut ChunkApplyStatsV1,
 ) -> Result<(), RuntimeError> {
     let _span = tracing::debug_span!(target: "runtime", "action_deploy_global_contract").entered();
 
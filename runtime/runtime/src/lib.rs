@@ -29,7 +29,7 @@ pub use near_primitives;
 use near_primitives::account::{AccessKey, Account, AccountContract};
 use near_primitives::action::GlobalContractIdentifier;
 use near_primitives::bandwidth_scheduler::{BandwidthRequests, BlockBandwidthRequests};
-use near_primitives::chunk_apply_stats::ChunkApplyStatsV0;
+use near_primitives::chunk_apply_stats::{BalanceStats, ChunkApplyStatsV1};
 use near_primitives::congestion_info::{BlockCongestionInfo, CongestionInfo};
 use near_primitives::errors::{
     ActionError, ActionErrorKind, EpochError, IntegerOverflowError, InvalidTxError, RuntimeError,
@@ -59,6 +59,7 @@ use near_primitives::utils::{
     create_receipt_id_from_transaction,
 };
 use near_primitives::version::ProtocolVersion;
+use near_primitives::views::{HostFunctionCallView, ReceiptHostFunctionCallsView};
 use near_primitives_core::apply::ApplyChunkReason;
 use near_primitives_core::version::ProtocolFeature;
 use near_store::trie::receipts_column_helper::DelayedReceiptQueue;
@@ -78,6 +79,7 @@ use near_vm_runner::logic::types::PromiseResult;
 pub use near_vm_runner::with_ext_cost_counter;
 use pipelining::ReceiptPreparationPipeline;
 use rayon::prelude::*;
+pub use simulation::{SimulationError, SimulationResult};
 use std::cmp::max;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
@@ -86,6 +88,7 @@ use verifier::ValidateReceiptMode;
 
 mod actions;
 pub mod adapter;
+pub mod balance_audit;
 mod bandwidth_scheduler;
 pub mod config;
 mod congestion_control;
@@ -96,6 +99,7 @@ pub mod metrics;
 mod pipelining;
 mod prefetch;
 pub mod receipt_manager;
+mod simulation;
 pub mod state_viewer;
 #[cfg(test)]
 mod tests;
@@ -153,6 +157,14 @@ pub struct ApplyState {
     /// Each shard requests some bandwidth to other shards and then the bandwidth scheduler
     /// decides how much each shard is allowed to send.
     pub bandwidth_requests: BlockBandwidthRequests,
+    /// Whether to record the host functions called by contracts in
+    /// `ApplyResult::host_call_traces`.  Meant for debugging tools only.
+    pub trace_host_calls: bool,
+    /// Whether to instrument the called contracts to record the gas burnt by
+    /// their functions in `ApplyResult::wasm_profiles`.  The instrumentation
+    /// burns gas too, so this changes the outcome of the receipts and is only
+    /// meant for debugging tools.
+    pub profile_wasm_functions: bool,
 }
 
 impl ApplyState {
@@ -208,7 +220,7 @@ pub struct ApplyResult {
     pub outgoing_receipts: Vec<Receipt>,
     pub outcomes: Vec<ExecutionOutcomeWithId>,
     pub state_changes: Vec<RawStateChangesWithTrieKey>,
-    pub stats: ChunkApplyStatsV0,
+    pub stats: ChunkApplyStatsV1,
     pub processed_delayed_receipts: Vec<Receipt>,
     pub processed_yield_timeouts: Vec<PromiseYieldTimeout>,
     pub proof: Option<PartialStorage>,
@@ -220,6 +232,20 @@ pub struct ApplyResult {
     pub bandwidth_scheduler_state_hash: CryptoHash,
     /// Contracts accessed and deployed while applying the chunk.
     pub contract_updates: ContractUpdates,
+    /// Host functions called by the executed receipts.  Empty unless
+    /// `ApplyState::trace_host_calls` is set.
+    pub host_call_traces: Vec<ReceiptHostFunctionCallsView>,
+    /// Gas burnt by the contract functions called by the executed receipts.
+    /// Empty unless `ApplyState::profile_wasm_functions` is set.
+    pub wasm_profiles: Vec<ReceiptWasmProfile>,
+}
+
+/// Gas profile of the contract functions called by a receipt, as collapsed
+/// stacks, see `near_vm_runner::logic::WasmProfile::collapsed_stacks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptWasmProfile {
+    pub receipt_id: CryptoHash,
+    pub stacks: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -233,6 +259,8 @@ pub struct ActionResult {
     pub new_receipts: Vec<Receipt>,
     pub validator_proposals: Vec<ValidatorStake>,
     pub profile: Box<ProfileDataV3>,
+    pub host_calls: Vec<HostFunctionCallView>,
+    pub wasm_profile: Vec<String>,
 }
 
 impl ActionResult {
@@ -254,6 +282,8 @@ impl ActionResult {
         self.profile.merge(&next_result.profile);
         self.result = next_result.result;
         self.logs.append(&mut next_result.logs);
+        self.host_calls.append(&mut next_result.host_calls);
+        self.wasm_profile.append(&mut next_result.wasm_profile);
         if let Ok(ReturnData::ReceiptIndex(ref mut receipt_index)) = self.result {
             // Shifting local receipt index to be global receipt index.
             *receipt_index += self.new_receipts.len() as u64;
@@ -281,6 +311,8 @@ impl Default for ActionResult {
             new_receipts: vec![],
             validator_proposals: vec![],
             profile: Default::default(),
+            host_calls: vec![],
+            wasm_profile: vec![],
         }
     }
 }
@@ -351,7 +383,7 @@ impl Runtime {
         apply_state: &ApplyState,
         validated_tx: &ValidatedTransaction,
         transaction_cost: &TransactionCost,
-        stats: &mut ChunkApplyStatsV0,
+        stats: &mut ChunkApplyStatsV1,
     ) -> Result<(Receipt, ExecutionOutcomeWithId), InvalidTxError> {
         let span = tracing::Span::current();
         metrics::TRANSACTION_PROCESSED_TOTAL.inc();
@@ -443,7 +475,7 @@ impl Runtime {
         action_index: usize,
         actions: &[Action],
         epoch_info_provider: &dyn EpochInfoProvider,
-        stats: &mut ChunkApplyStatsV0,
+        stats: &mut ChunkApplyStatsV1,
     ) -> Result<ActionResult, RuntimeError> {
         let _span = tracing::debug_span!(
             target: "runtime",
@@ -651,7 +683,9 @@ impl Runtime {
         receipt: &Receipt,
         receipt_sink: &mut ReceiptSink,
         validator_proposals: &mut Vec<ValidatorStake>,
-        stats: &mut ChunkApplyStatsV0,
+        stats: &mut ChunkApplyStatsV1,
+        host_call_traces: &mut Vec<ReceiptHostFunctionCallsView>,
+        wasm_profiles: &mut Vec<ReceiptWasmProfile>,
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<ExecutionOutcomeWithId, RuntimeError> {
         let _span = tracing::debug_span!(
@@ -793,6 +827,7 @@ impl Runtime {
                 action_receipt,
                 &mut result,
                 &apply_state.config,
+                &mut stats.balance,
             )?
         };
         stats.balance.gas_deficit_amount =
@@ -837,6 +872,8 @@ impl Runtime {
                 // account holder. If the account doesn't exist by the end of the execution, the
                 // validators receive the full reward.
                 tx_burnt_amount -= receiver_reward;
+                stats.balance.contract_reward_amount =
+                    safe_add_balance(stats.balance.contract_reward_amount, receiver_reward)?;
                 account.set_amount(safe_add_balance(account.amount(), receiver_reward)?);
                 set_account(state_update, account_id.clone(), account);
                 state_update.commit(StateChangeCause::ActionReceiptGasReward {
@@ -930,6 +967,18 @@ impl Runtime {
         };
 
         Self::print_log(&result.logs);
+        if !result.host_calls.is_empty() {
+            host_call_traces.push(ReceiptHostFunctionCallsView {
+                receipt_id: *receipt.receipt_id(),
+                host_calls: std::mem::take(&mut result.host_calls),
+            });
+        }
+        if !result.wasm_profile.is_empty() {
+            wasm_profiles.push(ReceiptWasmProfile {
+                receipt_id: *receipt.receipt_id(),
+                stacks: std::mem::take(&mut result.wasm_profile),
+            });
+        }
 
         Ok(ExecutionOutcomeWithId {
             id: *receipt.receipt_id(),
@@ -955,6 +1004,7 @@ impl Runtime {
         action_receipt: &ActionReceipt,
         result: &mut ActionResult,
         config: &RuntimeConfig,
+        balance_stats: &mut BalanceStats,
     ) -> Result<Balance, RuntimeError> {
         let total_deposit = total_deposit(&action_receipt.actions)?;
         let prepaid_gas = safe_add_gas(
@@ -1001,6 +1051,10 @@ impl Runtime {
             )?;
         }
 
+        balance_stats.refund_amount = safe_add_balance(
+            balance_stats.refund_amount,
+            safe_add_balance(deposit_refund, gas_balance_refund)?,
+        )?;
         if deposit_refund > 0 {
             result.new_receipts.push(Receipt::new_balance_refund(
                 receipt.predecessor_id(),
@@ -1034,6 +1088,8 @@ impl Runtime {
             epoch_info_provider,
             ref pipeline_manager,
             ref mut stats,
+            ref mut host_call_traces,
+            ref mut wasm_profiles,
             ..
         } = *processing_state;
         let account_id = receipt.receiver_id();
@@ -1103,6 +1159,8 @@ impl Runtime {
                                 receipt_sink,
                                 validator_proposals,
                                 stats,
+                                host_call_traces,
+                                wasm_profiles,
                                 epoch_info_provider,
                             )
                             .map(Some);
@@ -1158,6 +1216,8 @@ impl Runtime {
                             receipt_sink,
                             validator_proposals,
                             stats,
+                            host_call_traces,
+                            wasm_profiles,
                             epoch_info_provider,
                         )
                         .map(Some);
@@ -1210,6 +1270,8 @@ impl Runtime {
                             receipt_sink,
                             validator_proposals,
                             stats,
+                            host_call_traces,
+                            wasm_profiles,
                             epoch_info_provider,
                         )
                         .map(Some);
@@ -1244,6 +1306,7 @@ impl Runtime {
         &self,
         state_update: &mut TrieUpdate,
         validator_accounts_update: &ValidatorAccountsUpdate,
+        balance_stats: &mut BalanceStats,
     ) -> Result<(), RuntimeError> {
         for (account_id, max_of_stakes) in &validator_accounts_update.stake_info {
             if let Some(mut account) = get_account(state_update, account_id)? {
@@ -1252,6 +1315,8 @@ impl Runtime {
                     account.set_locked(account.locked().checked_add(*reward).ok_or_else(|| {
                         RuntimeError::UnexpectedIntegerOverflow("update_validator_accounts".into())
                     })?);
+                    balance_stats.validator_rewards_amount =
+                        safe_add_balance(balance_stats.validator_rewards_amount, *reward)?;
                 }
 
                 debug!(target: "runtime",
@@ -1328,6 +1393,8 @@ impl Runtime {
                         )
                     },
                 )?);
+                balance_stats.validator_rewards_amount =
+                    safe_add_balance(balance_stats.validator_rewards_amount, treasury_reward)?;
                 set_account(state_update, account_id.clone(), &account);
             }
         }
@@ -1415,6 +1482,7 @@ impl Runtime {
             self.update_validator_accounts(
                 &mut processing_state.state_update,
                 validator_accounts_update,
+                &mut processing_state.stats.balance,
             )?;
         }
 
@@ -2135,6 +2203,8 @@ impl Runtime {
             bandwidth_requests,
             bandwidth_scheduler_state_hash,
             contract_updates,
+            host_call_traces: processing_state.host_call_traces,
+            wasm_profiles: processing_state.wasm_profiles,
         })
     }
 }
@@ -2251,6 +2321,8 @@ fn missing_chunk_apply_result(
             .map(|o| o.scheduler_state_hash)
             .unwrap_or_default(),
         contract_updates,
+        host_call_traces: vec![],
+        wasm_profiles: vec![],
     });
 }
 
@@ -2391,7 +2463,7 @@ struct ApplyProcessingState<'a> {
     state_update: TrieUpdate,
     epoch_info_provider: &'a dyn EpochInfoProvider,
     total: TotalResourceGuard,
-    stats: ChunkApplyStatsV0,
+    stats: ChunkApplyStatsV1,
 }
 
 impl<'a> ApplyProcessingState<'a> {
@@ -2411,7 +2483,7 @@ impl<'a> ApplyProcessingState<'a> {
             gas: 0,
             compute: 0,
         };
-        let stats = ChunkApplyStatsV0::new(apply_state.block_height, apply_state.shard_id);
+        let stats = ChunkApplyStatsV1::new(apply_state.block_height, apply_state.shard_id);
         Self {
             protocol_version,
             apply_state,
@@ -2432,6 +2504,7 @@ impl<'a> ApplyProcessingState<'a> {
             Arc::clone(&self.apply_state.config),
             self.apply_state.cache.as_ref().map(|v| v.handle()),
             self.state_update.contract_storage(),
+            self.apply_state.profile_wasm_functions,
         );
         ApplyProcessingReceiptState {
             pipeline_manager,
@@ -2447,6 +2520,8 @@ impl<'a> ApplyProcessingState<'a> {
             local_receipts: VecDeque::new(),
             incoming_receipts,
             delayed_receipts,
+            host_call_traces: Vec::new(),
+            wasm_profiles: Vec::new(),
         }
     }
 }
@@ -2460,13 +2535,15 @@ struct ApplyProcessingReceiptState<'a> {
     state_update: TrieUpdate,
     epoch_info_provider: &'a dyn EpochInfoProvider,
     total: TotalResourceGuard,
-    stats: ChunkApplyStatsV0,
+    stats: ChunkApplyStatsV1,
     outcomes: Vec<ExecutionOutcomeWithId>,
     metrics: ApplyMetrics,
     local_receipts: VecDeque<Receipt>,
     incoming_receipts: &'a [Receipt],
     delayed_receipts: DelayedReceiptQueueWrapper<'a>,
     pipeline_manager: pipelining::ReceiptPreparationPipeline,
+    host_call_traces: Vec<ReceiptHostFunctionCallsView>,
+    wasm_profiles: Vec<ReceiptWasmProfile>,
 }
 
 trait MaybeRefReceipt {
@@ -2576,7 +2653,7 @@ pub mod estimator {
     use crate::ApplyState;
     use crate::congestion_control::ReceiptSinkV2;
     use crate::pipelining::ReceiptPreparationPipeline;
-    use near_primitives::chunk_apply_stats::{ChunkApplyStatsV0, ReceiptSinkStats};
+    use near_primitives::chunk_apply_stats::{ChunkApplyStatsV1, ReceiptSinkStats};
     use near_primitives::congestion_info::CongestionInfo;
     use near_primitives::errors::RuntimeError;
     use near_primitives::receipt::Receipt;
@@ -2594,7 +2671,7 @@ pub mod estimator {
         receipt: &Receipt,
         outgoing_receipts: &mut Vec<Receipt>,
         validator_proposals: &mut Vec<ValidatorStake>,
-        stats: &mut ChunkApplyStatsV0,
+        stats: &mut ChunkApplyStatsV1,
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<ExecutionOutcomeWithId, RuntimeError> {
         // TODO(congestion_control - edit runtime config parameters for limitless estimator runs
@@ -2626,6 +2703,7 @@ pub mod estimator {
             std::sync::Arc::clone(&apply_state.config),
             apply_state.cache.as_ref().map(|c| c.handle()),
             state_update.contract_storage(),
+            apply_state.profile_wasm_functions,
         );
         let apply_result = Runtime {}.apply_action_receipt(
             state_update,
@@ -2635,6 +2713,8 @@ pub mod estimator {
             &mut receipt_sink,
             validator_proposals,
             stats,
+            &mut vec![],
+            &mut vec![],
             epoch_info_provider,
         );
         let new_outgoing_receipts =
@@ -63,6 +63,10 @@ pub(crate) struct ReceiptPreparationPipeline {
 
     /// Storage for WASM code.
     storage: ContractStorage,
+
+    /// Whether to prepare the contracts for profiling, see
+    /// `ApplyState::profile_wasm_functions`.
+    profile_wasm_functions: bool,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -88,6 +92,7 @@ impl ReceiptPreparationPipeline {
         config: Arc<RuntimeConfig>,
         contract_cache: Option<Box<dyn ContractRuntimeCache>>,
         storage: ContractStorage,
+        profile_wasm_functions: bool,
     ) -> Self {
         Self {
             map: Default::default(),
@@ -96,6 +101,7 @@ impl ReceiptPreparationPipeline {
             config,
             contract_cache,
             storage,
+            profile_wasm_functions,
         }
     }
 
@@ -197,6 +203,7 @@ impl ReceiptPreparationPipeline {
                     let config = Arc::clone(&self.config.wasm_config);
                     let cache = self.contract_cache.as_ref().map(|c| c.handle());
                     let storage = self.storage.clone();
+                    let profile_wasm_functions = self.profile_wasm_functions;
                     let created = Instant::now();
                     let method_name = function_call.method_name.clone();
                     let status = Mutex::new(PrepareTaskStatus::Pending);
@@ -221,6 +228,7 @@ impl ReceiptPreparationPipeline {
                             code_hash,
                             &account_id,
                             &method_name,
+                            profile_wasm_functions,
                         );
 
                         let mut status = task.status.lock().expect("mutex lock");
@@ -295,6 +303,7 @@ impl ReceiptPreparationPipeline {
                 code_hash,
                 &account_id,
                 &function_call.method_name,
+                self.profile_wasm_functions,
             );
             PIPELINING_ACTIONS_NOT_SUBMITTED.inc_by(1);
             PIPELINING_ACTIONS_MAIN_THREAD_WORKING_TIME.inc_by(start.elapsed().as_secs_f64());
@@ -325,6 +334,7 @@ impl ReceiptPreparationPipeline {
                         code_hash,
                         &account_id,
                         &method_name,
+                        self.profile_wasm_functions,
                     );
                     PIPELINING_ACTIONS_PREPARED_IN_MAIN_THREAD.inc_by(1);
                     PIPELINING_ACTIONS_MAIN_THREAD_WORKING_TIME
@@ -375,8 +385,17 @@ fn prepare_function_call(
     code_hash: CryptoHash,
     account_id: &AccountId,
     method_name: &str,
+    profile_wasm_functions: bool,
 ) -> Box<dyn PreparedContract> {
     let code_ext = RuntimeContractExt { storage: contract_storage.clone(), account_id, code_hash };
+    if profile_wasm_functions {
+        return near_vm_runner::prepare_with_wasm_profile(
+            &code_ext,
+            config,
+            gas_counter,
+            method_name,
+        );
+    }
     let contract = near_vm_runner::prepare(&code_ext, config, cache, gas_counter, method_name);
     contract
 }
@@ -0,0 +1,222 @@
+//! Simulation of transactions without committing their effects.
+//!
+//! A simulated transaction is converted to a receipt and then all receipts it
+//! produces, including refunds and receipts sent to other shards, are executed
+//! one by one until there are none left.  The state changes are accumulated in
+//! a `TrieUpdate` of each touched shard which is dropped at the end, so the
+//! underlying storage is never modified.
+use crate::config::tx_cost;
+use crate::congestion_control::{DelayedReceiptQueueWrapper, ReceiptSink, ReceiptSinkV1};
+use crate::verifier::validate_actions;
+use crate::{ApplyProcessingState, ApplyState, Runtime};
+use near_primitives::errors::{InvalidTxError, RuntimeError};
+use near_primitives::receipt::Receipt;
+use near_primitives::runtime::migration_data::MigrationFlags;
+use near_primitives::transaction::{
+    ExecutionOutcomeWithId, SignedTransaction, ValidatedTransaction,
+};
+use near_primitives::types::{EpochInfoProvider, Gas, RawStateChangesWithTrieKey, ShardId};
+use near_primitives::views::ReceiptHostFunctionCallsView;
+use near_store::Trie;
+use near_store::trie::receipts_column_helper::DelayedReceiptQueue;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Upper bound on the number of receipts executed for a single simulated
+/// transaction.  Receipts without function calls burn little gas, so the gas
+/// limit alone doesn't bound the work of a simulation.
+const MAX_SIMULATED_RECEIPTS: usize = 100;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulationError {
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(InvalidTxError),
+    #[error("the node does not track shard {0}")]
+    UnavailableShard(ShardId),
+    #[error("too many receipts, simulation stopped after {0} receipts")]
+    TooManyReceipts(usize),
+    #[error("simulation stopped after burning more than {0} gas")]
+    GasLimitExceeded(Gas),
+    #[error("runtime error: {0}")]
+    Runtime(#[from] RuntimeError),
+}
+
+/// Result of [`Runtime::simulate_transaction`].
+#[derive(Debug)]
+pub struct SimulationResult {
+    pub transaction_outcome: ExecutionOutcomeWithId,
+    /// Receipts in the order in which they were executed.
+    pub receipts: Vec<Receipt>,
+    /// Outcomes of the executed receipts.  Receipts waiting for data or for
+    /// a yield resume don't have an outcome.
+    pub receipt_outcomes: Vec<ExecutionOutcomeWithId>,
+    /// State changes of all touched shards.
+    pub state_changes: Vec<RawStateChangesWithTrieKey>,
+    /// Host functions called by the receipts in the order of execution, only
+    /// recorded if `ApplyState::trace_host_calls` is set.
+    pub host_calls: Vec<ReceiptHostFunctionCallsView>,
+}
+
+impl Runtime {
+    /// Executes the transaction and all receipts it produces on top of given
+    /// tries, one for each shard tracked by the caller.  `apply_state`
+    /// describes the block in which the transaction is executed, its `shard_id`
+    /// is ignored.
+    ///
+    /// Receipts are executed right away, so gas limits and congestion of the
+    /// shards are not taken into account.  Instead, the simulation stops once
+    /// the transaction and its receipts burn more than `max_gas_burnt` in
+    /// total.  Nothing is written to the storage.
+    pub fn simulate_transaction(
+        &self,
+        apply_state: &ApplyState,
+        tries: HashMap<ShardId, Trie>,
+        signed_tx: SignedTransaction,
+        check_signature: bool,
+        max_gas_burnt: Gas,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<SimulationResult, SimulationError> {
+        let config = &apply_state.config;
+        validate_actions(
+            &config.wasm_config.limit_config,
+            signed_tx.transaction.actions(),
+            apply_state.current_protocol_version,
+        )
+        .map_err(|err| {
+            SimulationError::InvalidTransaction(InvalidTxError::ActionsValidation(err))
+        })?;
+        let simulate = |validated_tx: &ValidatedTransaction| {
+            self.simulate_validated_transaction(
+                apply_state,
+                tries,
+                validated_tx,
+                max_gas_burnt,
+                epoch_info_provider,
+            )
+        };
+        if check_signature {
+            let validated_tx = ValidatedTransaction::new(config, signed_tx)
+                .map_err(|(err, _)| SimulationError::InvalidTransaction(err))?;
+            simulate(&validated_tx)
+        } else {
+            ValidatedTransaction::simulate_without_signature_check(config, signed_tx, simulate)
+                .map_err(|(err, _)| SimulationError::InvalidTransaction(err))?
+        }
+    }
+
+    fn simulate_validated_transaction(
+        &self,
+        apply_state: &ApplyState,
+        tries: HashMap<ShardId, Trie>,
+        validated_tx: &ValidatedTransaction,
+        max_gas_burnt: Gas,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<SimulationResult, SimulationError> {
+        let config = &apply_state.config;
+        let cost = tx_cost(config, &validated_tx.to_tx(), apply_state.gas_price)
+            .map_err(|err| SimulationError::InvalidTransaction(err.into()))?;
+
+        let shard_layout =
+            epoch_info_provider.shard_layout(&apply_state.epoch_id).map_err(RuntimeError::from)?;
+        let apply_states: HashMap<ShardId, ApplyState> = tries
+            .keys()
+            .map(|&shard_id| (shard_id, apply_state_for_shard(apply_state, shard_id)))
+            .collect();
+        let mut shards = HashMap::new();
+        for (shard_id, trie) in tries {
+            let state =
+                ApplyProcessingState::new(&apply_states[&shard_id], trie, epoch_info_provider);
+            let delayed_receipts = DelayedReceiptQueueWrapper::new(
+                DelayedReceiptQueue::load(&state.state_update).map_err(RuntimeError::from)?,
+                epoch_info_provider,
+                shard_id,
+                apply_state.epoch_id,
+            );
+            shards.insert(shard_id, state.into_processing_receipt_state(&[], delayed_receipts));
+        }
+
+        let signer_shard_id = shard_layout.account_id_to_shard_id(validated_tx.signer_id());
+        let state = shards
+            .get_mut(&signer_shard_id)
+            .ok_or(SimulationError::UnavailableShard(signer_shard_id))?;
+        let (receipt, transaction_outcome) = self
+            .process_transaction(
+                &mut state.state_update,
+                state.apply_state,
+                validated_tx,
+                &cost,
+                &mut state.stats,
+            )
+            .map_err(SimulationError::InvalidTransaction)?;
+
+        let mut gas_burnt = transaction_outcome.outcome.gas_burnt;
+        let mut pending_receipts = VecDeque::from([receipt]);
+        let mut receipts = vec![];
+        let mut receipt_outcomes = vec![];
+        let mut host_calls = vec![];
+        while let Some(receipt) = pending_receipts.pop_front() {
+            if receipts.len() == MAX_SIMULATED_RECEIPTS {
+                return Err(SimulationError::TooManyReceipts(MAX_SIMULATED_RECEIPTS));
+            }
+            let shard_id = receipt.receiver_shard_id(&shard_layout).map_err(RuntimeError::from)?;
+            let state =
+                shards.get_mut(&shard_id).ok_or(SimulationError::UnavailableShard(shard_id))?;
+            // Unlike `ReceiptSinkV2`, the V1 sink never buffers receipts, so
+            // all produced receipts end up in its outgoing receipts.
+            let mut receipt_sink = ReceiptSink::V1(ReceiptSinkV1 { outgoing_receipts: vec![] });
+            if let Some(outcome) =
+                self.process_receipt(state, &receipt, &mut receipt_sink, &mut vec![])?
+            {
+                gas_burnt = gas_burnt.saturating_add(outcome.outcome.gas_burnt);
+                receipt_outcomes.push(outcome);
+            }
+            if gas_burnt > max_gas_burnt {
+                return Err(SimulationError::GasLimitExceeded(max_gas_burnt));
+            }
+            pending_receipts.extend(
+                receipt_sink.finalize_stats_get_outgoing_receipts(&mut state.stats.receipt_sink),
+            );
+            host_calls.append(&mut state.host_call_traces);
+            receipts.push(receipt);
+        }
+
+        let mut state_changes = vec![];
+        for (_, state) in shards {
+            state_changes
+                .extend(state.state_update.finalize().map_err(RuntimeError::from)?.state_changes);
+        }
+        Ok(SimulationResult {
+            transaction_outcome,
+            receipts,
+            receipt_outcomes,
+            state_changes,
+            host_calls,
+        })
+    }
+}
+
+fn apply_state_for_shard(apply_state: &ApplyState, shard_id: ShardId) -> ApplyState {
+    ApplyState {
+        apply_reason: apply_state.apply_reason.clone(),
+        block_height: apply_state.block_height,
+        prev_block_hash: apply_state.prev_block_hash,
+        block_hash: apply_state.block_hash,
+        shard_id,
+        epoch_id: apply_state.epoch_id,
+        epoch_height: apply_state.epoch_height,
+        gas_price: apply_state.gas_price,
+        block_timestamp: apply_state.block_timestamp,
+        gas_limit: None,
+        random_seed: apply_state.random_seed,
+        current_protocol_version: apply_state.current_protocol_version,
+        config: Arc::clone(&apply_state.config),
+        cache: apply_state.cache.as_ref().map(|cache| cache.handle()),
+        is_new_chunk: true,
+        migration_data: Arc::clone(&apply_state.migration_data),
+        migration_flags: MigrationFlags::default(),
+        congestion_info: apply_state.congestion_info.clone(),
+        bandwidth_requests: apply_state.bandwidth_requests.clone(),
+        trace_host_calls: apply_state.trace_host_calls,
+        profile_wasm_functions: apply_state.profile_wasm_functions,
+    }
+}
@@ -16,6 +16,8 @@ pub enum ViewContractCodeError {
     AccountDoesNotExist { requested_account_id: near_primitives::types::AccountId },
     #[error("Contract code for contract ID #{contract_account_id} does not exist")]
     NoContractCode { contract_account_id: near_primitives::types::AccountId },
+    #[error("Global contract code {identifier:?} does not exist")]
+    NoGlobalContractCode { identifier: near_primitives::action::GlobalContractIdentifier },
     #[error("Internal error: #{error_message}")]
     InternalError { error_message: String },
 }
@@ -1,11 +1,13 @@
 use crate::ApplyState;
 use crate::actions::execute_function_call;
+use crate::conversions::Convert;
 use crate::ext::RuntimeExt;
 use crate::pipelining::ReceiptPreparationPipeline;
 use crate::receipt_manager::ReceiptManager;
 use near_crypto::{KeyType, PublicKey};
 use near_parameters::RuntimeConfigStore;
-use near_primitives::account::{AccessKey, Account};
+use near_primitives::account::{AccessKey, Account, AccountContract};
+use near_primitives::action::GlobalContractIdentifier;
 use near_primitives::apply::ApplyChunkReason;
 use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
 use near_primitives::borsh::BorshDeserialize;
@@ -13,12 +15,12 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum, ReceiptV1};
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
 use near_primitives::transaction::FunctionCallAction;
-use near_primitives::trie_key::trie_key_parsers;
+use near_primitives::trie_key::{GlobalContractCodeIdentifier, trie_key_parsers};
 use near_primitives::types::{
     AccountId, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, Gas, ShardId,
 };
 use near_primitives::version::PROTOCOL_VERSION;
-use near_primitives::views::{StateItem, ViewStateResult};
+use near_primitives::views::{HostFunctionCallView, StateItem, ViewStateResult};
 use near_primitives_core::config::ViewConfig;
 use near_store::{TrieUpdate, get_access_key, get_account};
 use near_vm_runner::logic::{ProtocolVersion, ReturnData};
@@ -74,6 +76,11 @@ impl TrieViewer {
         Self { state_size_limit, max_gas_burnt_view }
     }
 
+    /// Gas limit used when handling call_function queries.
+    pub fn max_gas_burnt_view(&self) -> Gas {
+        self.max_gas_burnt_view
+    }
+
     pub fn view_account(
         &self,
         state_update: &TrieUpdate,
@@ -92,11 +99,28 @@ impl TrieViewer {
         account_id: &AccountId,
     ) -> Result<ContractCode, errors::ViewContractCodeError> {
         let account = self.view_account(state_update, account_id)?;
-        state_update
-            .get_code(account_id.clone(), account.local_contract_hash().unwrap_or_default())?
-            .ok_or_else(|| errors::ViewContractCodeError::NoContractCode {
-                contract_account_id: account_id.clone(),
-            })
+        let code = match account.contract().into_owned() {
+            AccountContract::None | AccountContract::Local(_) => state_update
+                .get_code(account_id.clone(), account.local_contract_hash().unwrap_or_default())?,
+            AccountContract::Global(code_hash) => {
+                state_update.get_global_code(GlobalContractCodeIdentifier::CodeHash(code_hash))?
+            }
+            AccountContract::GlobalByAccount(deployer_id) => state_update
+                .get_global_code(GlobalContractCodeIdentifier::AccountId(deployer_id))?,
+        };
+        code.ok_or_else(|| errors::ViewContractCodeError::NoContractCode {
+            contract_account_id: account_id.clone(),
+        })
+    }
+
+    pub fn view_global_contract_code(
+        &self,
+        state_update: &TrieUpdate,
+        identifier: &GlobalContractIdentifier,
+    ) -> Result<ContractCode, errors::ViewContractCodeError> {
+        state_update.get_global_code(identifier.clone().into())?.ok_or_else(|| {
+            errors::ViewContractCodeError::NoGlobalContractCode { identifier: identifier.clone() }
+        })
     }
 
     pub fn view_access_key(
@@ -192,6 +216,7 @@ impl TrieViewer {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        host_calls: Option<&mut Vec<HostFunctionCallView>>,
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<Vec<u8>, errors::CallFunctionError> {
         let now = Instant::now();
@@ -229,6 +254,8 @@ impl TrieViewer {
             migration_flags: MigrationFlags::default(),
             congestion_info: Default::default(),
             bandwidth_requests: BlockBandwidthRequests::empty(),
+            trace_host_calls: host_calls.is_some(),
+            profile_wasm_functions: false,
         };
         let function_call = FunctionCallAction {
             method_name: method_name.to_string(),
@@ -255,6 +282,7 @@ impl TrieViewer {
             Arc::clone(config),
             apply_state.cache.as_ref().map(|v| v.handle()),
             state_update.contract_storage(),
+            apply_state.profile_wasm_functions,
         );
         let view_config = Some(ViewConfig { max_gas_burnt: self.max_gas_burnt_view });
         let contract = pipeline.get_contract(
@@ -291,6 +319,9 @@ impl TrieViewer {
             view_config,
         )
         .map_err(|e| errors::CallFunctionError::InternalError { error_message: e.to_string() })?;
+        if let Some(host_calls) = host_calls {
+            host_calls.extend(outcome.host_calls.into_iter().map(Convert::convert));
+        }
         let elapsed = now.elapsed();
         let time_ms =
             (elapsed.as_secs() as f64 / 1_000.0) + f64::from(elapsed.subsec_nanos()) / 1_000_000.0;
@@ -1,11 +1,12 @@
 use super::{GAS_PRICE, to_yocto};
+use crate::balance_audit::audit_chunk_balance;
 use crate::config::safe_add_gas;
 use crate::congestion_control::{compute_receipt_congestion_gas, compute_receipt_size};
 use crate::tests::{
     MAX_ATTACHED_GAS, create_receipt_for_create_account, create_receipt_with_actions,
     set_sha256_cost,
 };
-use crate::{ApplyResult, ApplyState, Runtime, ValidatorAccountsUpdate};
+use crate::{ApplyResult, ApplyState, Runtime, SimulationError, ValidatorAccountsUpdate};
 use crate::{SignedValidPeriodTransactions, total_prepaid_exec_fees};
 use assert_matches::assert_matches;
 use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
@@ -19,7 +20,9 @@ use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
 use near_primitives::congestion_info::{
     BlockCongestionInfo, CongestionControl, CongestionInfo, ExtendedCongestionInfo,
 };
-use near_primitives::errors::{ActionErrorKind, FunctionCallError, TxExecutionError};
+use near_primitives::errors::{
+    ActionErrorKind, FunctionCallError, InvalidTxError, TxExecutionError,
+};
 use near_primitives::hash::{CryptoHash, hash};
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum, ReceiptPriority, ReceiptV0};
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
@@ -44,7 +47,7 @@ use near_store::{
     set_account,
 };
 use near_vm_runner::{ContractCode, FilesystemContractRuntimeCache};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use testlib::runtime_utils::{alice_account, bob_account};
 
@@ -177,6 +180,8 @@ fn setup_runtime_for_shard(
         migration_flags: MigrationFlags::default(),
         congestion_info,
         bandwidth_requests: BlockBandwidthRequests::empty(),
+        trace_host_calls: false,
+        profile_wasm_functions: false,
     };
 
     (runtime, tries, root, apply_state, signers)
@@ -214,21 +219,82 @@ fn test_apply_check_balance_validation_rewards() {
         protocol_treasury_account_id: None,
     };
 
-    runtime
+    let receipts =
+        [Receipt::new_balance_refund(&alice_account(), small_refund, ReceiptPriority::NoPriority)];
+    let apply_result = runtime
         .apply(
             tries.get_trie_for_shard(ShardUId::single_shard(), root),
             &Some(validator_accounts_update),
             &apply_state,
-            &[Receipt::new_balance_refund(
-                &alice_account(),
-                small_refund,
-                ReceiptPriority::NoPriority,
-            )],
+            &receipts,
             SignedValidPeriodTransactions::empty(),
             &epoch_info_provider,
             Default::default(),
         )
         .unwrap();
+    assert_eq!(apply_result.stats.balance.validator_rewards_amount, reward);
+
+    let audit = audit_chunk_balance(
+        &apply_state.config,
+        &tries.get_trie_for_shard(ShardUId::single_shard(), root),
+        &apply_result.state_changes,
+        &receipts,
+        &apply_result.outgoing_receipts,
+    )
+    .unwrap();
+    assert_eq!(audit.incoming_receipts_balance, small_refund);
+    assert_eq!(
+        audit.final_accounts_balance,
+        audit.initial_accounts_balance + reward + small_refund
+    );
+    assert!(audit.is_balanced(&apply_result.stats.balance));
+}
+
+#[test]
+fn test_balance_audit_delayed_receipts() {
+    let initial_balance = to_yocto(1_000_000);
+    let initial_locked = to_yocto(500_000);
+    let small_transfer = to_yocto(10_000);
+    let gas_limit = 1;
+    let (runtime, tries, mut root, mut apply_state, _, epoch_info_provider) = setup_runtime(
+        vec![alice_account(), bob_account()],
+        initial_balance,
+        initial_locked,
+        gas_limit,
+    );
+
+    let n = 5;
+    let receipts = generate_receipts(small_transfer, n);
+    let shard_uid = ShardUId::single_shard();
+
+    // The first chunk delays most of the receipts, the next ones process them.
+    for i in 1..=n + 2 {
+        let prev_receipts: &[Receipt] = if i == 1 { &receipts } else { &[] };
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(shard_uid, root),
+                &None,
+                &apply_state,
+                prev_receipts,
+                SignedValidPeriodTransactions::empty(),
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        let audit = audit_chunk_balance(
+            &apply_state.config,
+            &tries.get_trie_for_shard(shard_uid, root),
+            &apply_result.state_changes,
+            prev_receipts,
+            &apply_result.outgoing_receipts,
+        )
+        .unwrap();
+        assert!(audit.is_balanced(&apply_result.stats.balance), "chunk {i}: {audit:?}");
+        if i == 1 {
+            assert!(audit.final_stored_receipts_balance > 0);
+        }
+        root = commit_apply_result(&apply_result, &mut apply_state, &tries, shard_uid);
+    }
 }
 
 #[test]
@@ -3053,3 +3119,70 @@ fn test_expired_transaction() {
         "should have not produced any outcomes for the expired tx"
     );
 }
+
+/// Simulating a transaction executes all its receipts without modifying the state.
+#[test]
+fn test_simulate_transaction() {
+    let initial_balance = to_yocto(1_000_000);
+    let transfer = to_yocto(100);
+    let (runtime, tries, root, apply_state, signers, epoch_info_provider) =
+        setup_runtime(vec![alice_account(), bob_account()], initial_balance, 0, 10u64.pow(15));
+    let shard_uid = ShardUId::single_shard();
+    let simulate_with_limit =
+        |signed_tx: SignedTransaction, check_signature: bool, max_gas_burnt: Gas| {
+            let tries =
+                HashMap::from([(shard_uid.shard_id(), tries.get_trie_for_shard(shard_uid, root))]);
+            runtime.simulate_transaction(
+                &apply_state,
+                tries,
+                signed_tx,
+                check_signature,
+                max_gas_burnt,
+                &epoch_info_provider,
+            )
+        };
+    let simulate = |signed_tx: SignedTransaction, check_signature: bool| {
+        simulate_with_limit(signed_tx, check_signature, Gas::MAX)
+    };
+
+    let tx = SignedTransaction::send_money(
+        1,
+        alice_account(),
+        bob_account(),
+        &*signers[0],
+        transfer,
+        CryptoHash::default(),
+    );
+    let result = simulate(tx.clone(), true).unwrap();
+    assert_eq!(result.transaction_outcome.id, tx.get_hash());
+    assert_eq!(result.receipts[0].receiver_id(), &bob_account());
+    assert_eq!(result.receipt_outcomes[0].outcome.status, ExecutionStatus::SuccessValue(vec![]));
+    assert!(
+        result
+            .state_changes
+            .iter()
+            .any(|change| change.trie_key == TrieKey::Account { account_id: bob_account() })
+    );
+    let state_update = tries.new_trie_update(shard_uid, root);
+    let bob = get_account(&state_update, &bob_account()).unwrap().unwrap();
+    assert_eq!(bob.amount(), initial_balance);
+
+    // The simulation stops once the receipts burn more than the limit.
+    let gas_burnt = result.transaction_outcome.outcome.gas_burnt
+        + result.receipt_outcomes.iter().map(|outcome| outcome.outcome.gas_burnt).sum::<Gas>();
+    simulate_with_limit(tx.clone(), true, gas_burnt).unwrap();
+    assert_matches!(
+        simulate_with_limit(tx.clone(), true, gas_burnt - 1),
+        Err(SimulationError::GasLimitExceeded(_))
+    );
+
+    // The signature is checked only when requested.
+    let mut tx = tx;
+    let other_signer = InMemorySigner::from_seed(alice_account(), KeyType::ED25519, "seed");
+    tx.signature = other_signer.sign(tx.get_hash().as_ref());
+    assert_matches!(
+        simulate(tx.clone(), true),
+        Err(SimulationError::InvalidTransaction(InvalidTxError::InvalidSignature))
+    );
+    simulate(tx, false).unwrap();
+}
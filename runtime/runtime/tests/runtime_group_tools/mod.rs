@@ -125,6 +125,8 @@ impl StandaloneRuntime {
             migration_flags: MigrationFlags::default(),
             congestion_info,
             bandwidth_requests: BlockBandwidthRequests::empty(),
+            trace_host_calls: false,
+            profile_wasm_functions: false,
         };
 
         Self {
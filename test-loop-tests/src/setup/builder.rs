@@ -155,6 +155,13 @@ impl TestLoopBuilder {
         self
     }
 
+    /// Execute the events due at the same time in an order determined by the seed,
+    /// see `TestLoopV2::randomize_event_order`.
+    pub(crate) fn random_event_order(mut self, seed: u64) -> Self {
+        self.test_loop.randomize_event_order(seed);
+        self
+    }
+
     /// Build the test loop environment.
     pub(crate) fn build(self) -> TestLoopEnv {
         self.ensure_genesis().ensure_epoch_config_store().ensure_clients().build_impl()
@@ -310,6 +317,7 @@ impl<'a> NodeStateBuilder<'a> {
                 // the clients transfer state parts "peer to peer" but we wouldn't really
                 // gain anything over having them dump parts to a tempdir.
                 external_storage_fallback_threshold: 0,
+                manifest_signers: vec![],
             }),
         };
 
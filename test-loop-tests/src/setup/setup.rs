@@ -16,8 +16,10 @@ use near_client::client_actor::ClientActorInner;
 use near_client::gc_actor::GCActor;
 use near_client::sync_jobs_actor::SyncJobsActor;
 use near_client::{
-    Client, PartialWitnessActor, TxRequestHandler, TxRequestHandlerConfig, ViewClientActorInner,
+    Client, ColdStoreFallback, PartialWitnessActor, TxRequestHandler, TxRequestHandlerConfig,
+    ViewClientActorInner,
 };
+use near_crypto::{KeyType, SecretKey};
 use near_epoch_manager::EpochManager;
 use near_epoch_manager::shard_tracker::{ShardTracker, TrackedConfig};
 use near_primitives::network::PeerId;
@@ -170,6 +172,10 @@ pub fn setup_client(
     } else {
         (epoch_manager.clone(), shard_tracker.clone(), runtime_adapter.clone())
     };
+    let cold_store_fallback = split_store.as_ref().map(|_| ColdStoreFallback {
+        epoch_manager: view_epoch_manager.clone(),
+        runtime: view_runtime_adapter.clone(),
+    });
     let view_client_actor = ViewClientActorInner::new(
         test_loop.clock(),
         validator_signer.clone(),
@@ -180,6 +186,7 @@ pub fn setup_client(
         network_adapter.as_multi_sender(),
         client_config.clone(),
         near_client::adversarial::Controls::default(),
+        cold_store_fallback,
     )
     .unwrap();
 
@@ -267,6 +274,7 @@ pub fn setup_client(
         shard_tracker,
         runtime: runtime_adapter,
         validator: validator_signer,
+        node_key: SecretKey::from_seed(KeyType::ED25519, account_id.as_str()),
         future_spawner: Arc::new(test_loop.future_spawner(identifier)),
         handle: None,
     };
@@ -9,9 +9,12 @@ use near_chain_configs::GenesisConfig;
 use near_chain_configs::test_genesis::{TestEpochConfigBuilder, ValidatorsSpec};
 use near_client::SetNetworkInfo;
 use near_client::sync::epoch::EpochSync;
+use near_light_client::{LightClientError, LightClientState};
 use near_network::types::NetworkInfo;
 use near_o11y::testonly::init_test_logger;
+use near_primitives::epoch_info::EpochInfo;
 use near_primitives::epoch_sync::EpochSyncProof;
+use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::{AccountId, BlockHeightDelta};
 use near_primitives::utils::compression::CompressedData;
@@ -361,3 +364,46 @@ fn slow_test_epoch_sync_proof_sanity_zero_transaction_validity_period() {
     sanity_check_epoch_sync_proof(&proof, final_head_height, &env.shared_state.genesis.config, 1);
     env.shutdown_and_drain_remaining_events(Duration::seconds(5));
 }
+
+/// Checks that the light client accepts the epoch sync proof of a node and
+/// rejects it when it doesn't check out against the genesis.
+#[test]
+fn slow_test_light_client_bootstrap() {
+    init_test_logger();
+    let env = setup_initial_blockchain(20);
+    let proof = env.derive_epoch_sync_proof(0).into_v1();
+    let client_handle = env.node_datas[0].client_sender.actor_handle();
+    let genesis_header = env.test_loop.data.get(&client_handle).client.chain.genesis().clone();
+    let genesis_hash = *genesis_header.hash();
+
+    let state = LightClientState::bootstrap(genesis_hash, &genesis_header, &proof).unwrap();
+    let last_epoch = proof.all_epochs.last().unwrap();
+    assert_eq!(state.head_hash(), *last_epoch.last_final_block_header.hash());
+    assert_eq!(state.epoch_block_producers, last_epoch.block_producers);
+
+    let wrong_genesis_hash = CryptoHash::hash_bytes(b"other chain");
+    assert_eq!(
+        LightClientState::bootstrap(wrong_genesis_hash, &genesis_header, &proof).unwrap_err(),
+        LightClientError::GenesisHashMismatch {
+            expected: wrong_genesis_hash,
+            actual: genesis_hash
+        }
+    );
+
+    let mut wrong_first_epoch = proof.clone();
+    wrong_first_epoch.all_epochs[0].block_producers.pop();
+    assert_eq!(
+        LightClientState::bootstrap(genesis_hash, &genesis_header, &wrong_first_epoch).unwrap_err(),
+        LightClientError::InvalidEpochSyncProof(
+            "invalid block producers for second epoch after genesis".to_string()
+        )
+    );
+
+    let mut wrong_next_next_epoch = proof;
+    wrong_next_next_epoch.last_epoch.next_next_epoch_info = EpochInfo::default();
+    assert!(matches!(
+        LightClientState::bootstrap(genesis_hash, &genesis_header, &wrong_next_next_epoch),
+        Err(LightClientError::InvalidEpochSyncProof(_))
+    ));
+    env.shutdown_and_drain_remaining_events(Duration::seconds(5));
+}
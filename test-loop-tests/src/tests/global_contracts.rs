@@ -131,6 +131,17 @@ fn test_deploy_and_call_global_contract(deploy_mode: GlobalContractDeployMode) {
             account_state.storage_usage,
             baseline_storage_usage + identifier.len() as StorageUsage
         );
+        match &identifier {
+            GlobalContractIdentifier::CodeHash(hash) => {
+                assert_eq!(account_state.global_contract_hash, Some(*hash));
+                assert_eq!(account_state.global_contract_account_id, None);
+            }
+            GlobalContractIdentifier::AccountId(account_id) => {
+                assert_eq!(account_state.global_contract_hash, None);
+                assert_eq!(account_state.global_contract_account_id, Some(account_id.clone()));
+            }
+        }
+        assert_eq!(env.get_global_contract_code(&account, identifier.clone()), env.contract.code());
 
         env.call_global_contract(&account);
 
@@ -338,6 +349,32 @@ impl GlobalContractsTestEnv {
         account_view
     }
 
+    /// Fetches the global contract code from the shard of the given account.
+    fn get_global_contract_code(
+        &mut self,
+        account: &AccountId,
+        identifier: GlobalContractIdentifier,
+    ) -> Vec<u8> {
+        let clients: Vec<&Client> = self
+            .env
+            .node_datas
+            .iter()
+            .map(|data| &self.env.test_loop.data.get(&data.client_sender.actor_handle()).client)
+            .collect();
+        let query = match identifier {
+            GlobalContractIdentifier::CodeHash(code_hash) => {
+                QueryRequest::ViewGlobalContractCode { code_hash }
+            }
+            GlobalContractIdentifier::AccountId(account_id) => {
+                QueryRequest::ViewGlobalContractCodeByAccountId { account_id }
+            }
+        };
+        let response = clients.runtime_query(account, query);
+        let QueryResponseKind::ViewCode(code_view) = response.kind else { unreachable!() };
+        assert_eq!(code_view.hash, *self.contract.hash());
+        code_view.code
+    }
+
     fn next_nonce(&mut self) -> u64 {
         let ret = self.nonce;
         self.nonce += 1;
@@ -102,6 +102,7 @@ fn make_invalid_ob(env: &TestLoopEnv, adv_type: OptimisticBlockAdvType) -> Optim
         None,
         adv_type,
     )
+    .unwrap()
 }
 
 #[test]
@@ -261,7 +262,8 @@ fn alter_optimistic_block_at_height(
                             OptimisticBlockAdvType::InvalidTimestamp(
                                 optimistic_block.block_timestamp() - 15000000,
                             ),
-                        );
+                        )
+                        .unwrap();
                         return Some(NetworkRequests::OptimisticBlock {
                             optimistic_block: altered_ob,
                         });
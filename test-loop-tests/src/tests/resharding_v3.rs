@@ -18,6 +18,7 @@ use crate::setup::builder::TestLoopBuilder;
 use crate::setup::drop_condition::DropCondition;
 use crate::setup::env::TestLoopEnv;
 use crate::utils::loop_action::{LoopAction, LoopActionStatus};
+use crate::utils::random_event_order::run_with_random_event_order;
 use crate::utils::receipts::{
     ReceiptKind, check_receipts_presence_after_resharding_block,
     check_receipts_presence_at_resharding_block,
@@ -155,6 +156,8 @@ struct TestReshardingParameters {
     num_epochs_to_wait: u64,
     /// If set, proceed with second resharding using the provided boundary account.
     second_resharding_boundary_account: Option<AccountId>,
+    /// If set, execute the events due at the same time in an order determined by this seed.
+    event_order_seed: Option<u64>,
 }
 
 impl TestReshardingParametersBuilder {
@@ -300,6 +303,7 @@ impl TestReshardingParametersBuilder {
             second_resharding_boundary_account: self
                 .second_resharding_boundary_account
                 .unwrap_or(None),
+            event_order_seed: self.event_order_seed.unwrap_or(None),
         }
     }
 
@@ -409,6 +413,9 @@ fn test_resharding_v3_base(params: TestReshardingParameters) {
 
     init_test_logger();
     let mut builder = TestLoopBuilder::new();
+    if let Some(seed) = params.event_order_seed {
+        builder = builder.random_event_order(seed);
+    }
     let tracked_shard_schedule = params.tracked_shard_schedule.clone();
 
     builder = builder.config_modifier(move |config, client_index| {
@@ -698,6 +705,15 @@ fn slow_test_resharding_v3() {
     test_resharding_v3_base(TestReshardingParametersBuilder::default().build());
 }
 
+#[test]
+fn slow_test_resharding_v3_random_event_order() {
+    run_with_random_event_order(3, |seed| {
+        test_resharding_v3_base(
+            TestReshardingParametersBuilder::default().event_order_seed(Some(seed)).build(),
+        );
+    });
+}
+
 #[test]
 fn slow_test_resharding_v3_two_independent_splits() {
     let second_resharding_boundary_account = "account2".parse().unwrap();
@@ -8,7 +8,7 @@ use near_async::time::Duration;
 use near_chain_configs::test_genesis::{TestEpochConfigBuilder, ValidatorsSpec};
 use near_client::{
     GetBlock, GetChunk, GetExecutionOutcomesForBlock, GetProtocolConfig, GetShardChunk,
-    GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
+    GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, Query,
     ViewClientActorInner,
 };
 use near_network::client::BlockHeadersRequest;
@@ -21,8 +21,9 @@ use near_primitives::types::{
 };
 use near_primitives::version::PROTOCOL_VERSION;
 use near_primitives::views::{
-    BlockView, ExecutionOutcomeView, ExecutionOutcomeWithIdView, ExecutionStatusView,
-    StateChangeCauseView, StateChangeKindView, StateChangeValueView, StateChangesRequestView,
+    BlockView, ExecutionOutcomeView, ExecutionOutcomeWithIdView, ExecutionStatusView, QueryRequest,
+    QueryResponseKind, StateChangeCauseView, StateChangeKindView, StateChangeValueView,
+    StateChangesRequestView, StorageSourceView,
 };
 
 use crate::setup::builder::TestLoopBuilder;
@@ -155,6 +156,7 @@ impl<'a> ViewClientTester<'a> {
         self.check_get_state_changes_in_block();
         self.check_get_state_changes();
         self.check_get_execution_outcomes(shard_layout);
+        self.check_query();
     }
 
     fn get_block_at_height(&mut self, height: BlockHeight) -> BlockView {
@@ -428,7 +430,10 @@ impl<'a> ViewClientTester<'a> {
                 account_ids: accounts,
             },
         };
-        let state_changes = self.send(request, ARCHIVAL_CLIENT).unwrap();
+        let response = self.send(request, ARCHIVAL_CLIENT).unwrap();
+        // The block was garbage collected from the hot store.
+        assert_eq!(response.served_from, Some(StorageSourceView::Cold));
+        let state_changes = response.changes;
         assert_eq!(state_changes.len(), 2);
         assert!(matches!(
             state_changes[0].cause,
@@ -441,4 +446,22 @@ impl<'a> ViewClientTester<'a> {
         assert!(matches!(state_changes[0].value, StateChangeValueView::AccountUpdate { .. }));
         assert!(matches!(state_changes[1].value, StateChangeValueView::AccountUpdate { .. }));
     }
+
+    /// Issues [`Query`] requests for a garbage collected and a recent block to the view client
+    /// of the archival node and checks which store served them.
+    fn check_query(&mut self) {
+        let account_id: AccountId = "account0".parse().unwrap();
+        let mut query = |block_reference: BlockReference| {
+            let request = QueryRequest::ViewAccount { account_id: account_id.clone() };
+            self.send(Query::new(block_reference, request), ARCHIVAL_CLIENT).unwrap()
+        };
+
+        let response = query(BlockReference::BlockId(BlockId::Height(6)));
+        assert!(matches!(response.kind, QueryResponseKind::ViewAccount(_)));
+        assert_eq!(response.served_from, Some(StorageSourceView::Cold));
+
+        let response = query(BlockReference::Finality(Finality::Final));
+        assert!(matches!(response.kind, QueryResponseKind::ViewAccount(_)));
+        assert_eq!(response.served_from, Some(StorageSourceView::Hot));
+    }
 }
@@ -0,0 +1,17 @@
+use near_async::test_loop::{TEST_LOOP_SEED_ENV_VAR, seed_from_env};
+use rand::{Rng, thread_rng};
+
+/// Runs the test with `num_seeds` random seeds, which it should pass to
+/// `TestLoopBuilder::random_event_order`, so that every run executes the events in a
+/// different order. If `NEAR_TEST_LOOP_SEED` is set, the test runs once with that seed
+/// instead, which reproduces the order of a failed run.
+pub(crate) fn run_with_random_event_order(num_seeds: usize, test: impl Fn(u64)) {
+    let seeds = match seed_from_env() {
+        Some(seed) => vec![seed],
+        None => (0..num_seeds).map(|_| thread_rng().r#gen()).collect(),
+    };
+    for seed in seeds {
+        println!("Running with seed {seed}, set {TEST_LOOP_SEED_ENV_VAR}={seed} to reproduce.");
+        test(seed);
+    }
+}
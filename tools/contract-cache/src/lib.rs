@@ -0,0 +1,102 @@
+use anyhow::Context;
+use near_chain::ChainStore;
+use near_chain_configs::GenesisValidationMode;
+use near_epoch_manager::EpochManager;
+use near_store::{Mode, NodeStorage};
+use nearcore::contract_cache;
+use nearcore::{NightshadeRuntime, NightshadeRuntimeExt};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Manages the cache of compiled contracts.
+#[derive(clap::Parser)]
+pub struct ContractCacheCommand {
+    #[clap(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum SubCommand {
+    /// Compile all contracts deployed on the tracked shards, including global
+    /// contracts, and store them in the cache.
+    Precompile,
+    /// Write the compiled contracts deployed on the tracked shards to a
+    /// portable archive.
+    Export(ArchiveCmd),
+    /// Add the contents of an archive created with `export` to the cache.
+    ///
+    /// The archive is rejected unless it was exported by a node running the
+    /// same neard binary with the same VM configuration.  The compiled code
+    /// is executed as is, so only import archives from trusted sources.
+    Import(ArchiveCmd),
+}
+
+#[derive(clap::Parser)]
+struct ArchiveCmd {
+    /// Path of the archive.
+    #[clap(long)]
+    file: PathBuf,
+}
+
+impl ContractCacheCommand {
+    pub fn run(
+        self,
+        home_dir: &Path,
+        genesis_validation: GenesisValidationMode,
+    ) -> anyhow::Result<()> {
+        let near_config = nearcore::config::load_config(home_dir, genesis_validation)
+            .context("failed to load config")?;
+        let storage = NodeStorage::opener(
+            home_dir,
+            &near_config.config.store,
+            near_config.config.archival_config(),
+        )
+        .open_in_mode(Mode::ReadOnly)?;
+        let store = storage.get_hot_store();
+        let epoch_manager = EpochManager::new_arc_handle(
+            store.clone(),
+            &near_config.genesis.config,
+            Some(home_dir),
+        );
+        let runtime = NightshadeRuntime::from_config(
+            home_dir,
+            store.clone(),
+            &near_config,
+            epoch_manager.clone(),
+        )
+        .context("could not create the transaction runtime")?;
+        let chain_store =
+            ChainStore::new(store, false, near_config.genesis.config.transaction_validity_period);
+        match self.subcmd {
+            SubCommand::Precompile => {
+                let stats = contract_cache::precompile_deployed_contracts(
+                    &chain_store,
+                    epoch_manager.as_ref(),
+                    runtime.as_ref(),
+                )?;
+                println!("precompiled {} contracts from {} shards", stats.contracts, stats.shards);
+            }
+            SubCommand::Export(cmd) => {
+                let mut writer = BufWriter::new(std::fs::File::create(&cmd.file)?);
+                let count = contract_cache::export_deployed_contracts(
+                    &chain_store,
+                    epoch_manager.as_ref(),
+                    runtime.as_ref(),
+                    &mut writer,
+                )?;
+                println!("exported {count} compiled contracts to {}", cmd.file.display());
+            }
+            SubCommand::Import(cmd) => {
+                let mut reader = BufReader::new(std::fs::File::open(&cmd.file)?);
+                let count = contract_cache::import_contracts(
+                    &chain_store,
+                    epoch_manager.as_ref(),
+                    runtime.as_ref(),
+                    &mut reader,
+                )?;
+                println!("imported {count} compiled contracts from {}", cmd.file.display());
+            }
+        }
+        Ok(())
+    }
+}
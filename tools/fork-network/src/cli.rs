@@ -30,7 +30,7 @@ use near_store::adapter::StoreAdapter;
 use near_store::db::RocksDB;
 use near_store::flat::{BlockInfo, FlatStorageManager, FlatStorageStatus};
 use near_store::{
-    DBCol, FINAL_HEAD_KEY, Store, TrieDBStorage, TrieStorage,
+    DBCol, FINAL_HEAD_KEY, Mode, NodeStorage, Store, TrieDBStorage, TrieStorage,
     checkpoint_hot_storage_and_cleanup_columns,
 };
 use nearcore::{NearConfig, NightshadeRuntime, NightshadeRuntimeExt, load_config, open_storage};
@@ -96,6 +96,10 @@ struct FinalizeCmd;
 struct AmendAccessKeysCmd {
     #[arg(short, long, default_value = "2000000")]
     batch_size: u64,
+    /// Opens the DB in read only mode and keeps all changes in memory, so the
+    /// resulting state roots can be inspected without modifying the DB.
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[derive(clap::Parser)]
@@ -231,8 +235,12 @@ impl ForkNetworkCommand {
             SubCommand::Init(InitCmd { shard_layout_file }) => {
                 self.init(near_config, home_dir, shard_layout_file.as_deref())?;
             }
-            SubCommand::AmendAccessKeys(AmendAccessKeysCmd { batch_size }) => {
-                self.amend_access_keys(*batch_size, near_config, home_dir)?;
+            SubCommand::AmendAccessKeys(AmendAccessKeysCmd { batch_size, dry_run }) => {
+                let new_state_roots =
+                    self.amend_access_keys(*batch_size, *dry_run, near_config, home_dir)?;
+                if *dry_run {
+                    tracing::info!(?new_state_roots, "Dry run finished, the DB was not modified");
+                }
             }
             SubCommand::SetValidators(SetValidatorsCmd {
                 genesis_time,
@@ -406,12 +414,24 @@ impl ForkNetworkCommand {
     fn amend_access_keys(
         &self,
         batch_size: u64,
+        dry_run: bool,
         near_config: &mut NearConfig,
         home_dir: &Path,
     ) -> anyhow::Result<Vec<StateRoot>> {
-        // Open storage with migration
         near_config.config.store.load_memtries_for_tracked_shards = true;
-        let storage = open_storage(&home_dir, near_config).unwrap();
+        let storage = if dry_run {
+            // Keep all the changes in memory on top of the unmodified DB.
+            NodeStorage::opener(
+                home_dir,
+                &near_config.config.store,
+                near_config.config.archival_config(),
+            )
+            .open_in_mode(Mode::ReadOnly)?
+            .into_overlay()
+        } else {
+            // Open storage with migration
+            open_storage(&home_dir, near_config).unwrap()
+        };
         let store = storage.get_hot_store();
 
         let epoch_manager = EpochManager::new_arc_handle(
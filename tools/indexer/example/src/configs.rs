@@ -17,11 +17,35 @@ pub(crate) struct Opts {
 #[allow(clippy::large_enum_variant)]
 pub(crate) enum SubCommand {
     /// Run NEAR Indexer Example. Start observe the network
-    Run,
+    Run(RunArgs),
     /// Initialize necessary configs
     Init(InitConfigArgs),
 }
 
+#[derive(clap::Parser, Debug)]
+pub(crate) struct RunArgs {
+    /// Writes the blocks to the sink instead of only logging them
+    #[clap(long, value_enum)]
+    pub sink: Option<SinkKind>,
+    /// Directory the sink writes to
+    #[clap(long, default_value = "indexer-output")]
+    pub sink_dir: std::path::PathBuf,
+    /// Number of block heights covered by a single file of the `json-lines` sink
+    #[clap(long, default_value = "1000")]
+    pub blocks_per_file: u64,
+    /// Number of unconsumed files after which the `spool` sink pauses the indexer
+    #[clap(long, default_value = "1000")]
+    pub max_pending_files: usize,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum SinkKind {
+    /// Rotating zstd-compressed JSON-lines files partitioned by block height
+    JsonLines,
+    /// One JSON file per block in a directory other processes can tail
+    Spool,
+}
+
 #[derive(clap::Parser, Debug)]
 pub(crate) struct InitConfigArgs {
     /// chain/network id (localnet, testnet, devnet, betanet)
@@ -5,12 +5,16 @@ use clap::Parser;
 use tokio::sync::mpsc;
 use tracing::info;
 
-use configs::{Opts, SubCommand};
+use configs::{Opts, RunArgs, SinkKind, SubCommand};
 use near_indexer;
 
 mod configs;
+mod sinks;
 
-async fn listen_blocks(mut stream: mpsc::Receiver<near_indexer::StreamerMessage>) {
+async fn listen_blocks(
+    mut stream: mpsc::Receiver<near_indexer::StreamerMessage>,
+    ack_handle: near_indexer::AckHandle,
+) {
     while let Some(streamer_message) = stream.recv().await {
         // TODO: handle data as you need
         // Example of `StreamerMessage` with all the data (the data is synthetic)
@@ -253,9 +257,27 @@ async fn listen_blocks(mut stream: mpsc::Receiver<near_indexer::StreamerMessage>
             streamer_message.shards.iter().map(|shard| if let Some(chunk) = &shard.chunk { chunk.receipts.len() } else { 0usize }).sum::<usize>(),
             streamer_message.shards.iter().map(|shard| shard.receipt_execution_outcomes.len()).sum::<usize>(),
         );
+        // Acknowledge the block once it's fully handled so that the indexer
+        // resumes right after it when restarted.
+        ack_handle
+            .ack(streamer_message.block.header.height)
+            .await
+            .expect("failed to acknowledge block");
     }
 }
 
+fn open_sink(args: &RunArgs) -> Result<Option<Box<dyn sinks::Sink>>> {
+    Ok(match args.sink {
+        Some(SinkKind::JsonLines) => {
+            Some(Box::new(sinks::JsonLinesSink::new(&args.sink_dir, args.blocks_per_file)?))
+        }
+        Some(SinkKind::Spool) => {
+            Some(Box::new(sinks::SpoolSink::new(&args.sink_dir, args.max_pending_files)?))
+        }
+        None => None,
+    })
+}
+
 fn main() -> Result<()> {
     // We use it to automatically search the for root certificates to perform HTTPS calls
     // (sending telemetry and downloading genesis)
@@ -270,19 +292,27 @@ fn main() -> Result<()> {
     let home_dir = opts.home_dir.unwrap_or_else(near_indexer::get_default_home);
 
     match opts.subcmd {
-        SubCommand::Run => {
+        SubCommand::Run(args) => {
             let indexer_config = near_indexer::IndexerConfig {
                 home_dir,
                 sync_mode: near_indexer::SyncModeEnum::FromInterruption,
                 await_for_node_synced: near_indexer::AwaitForNodeSyncedEnum::WaitForFullSync,
                 finality: near_primitives::types::Finality::Final,
                 validate_genesis: true,
+                filter: Default::default(),
             };
             let system = actix::System::new();
             system.block_on(async move {
                 let indexer = near_indexer::Indexer::new(indexer_config).expect("Indexer::new()");
                 let stream = indexer.streamer();
-                actix::spawn(listen_blocks(stream));
+                match open_sink(&args).expect("failed to open the sink") {
+                    Some(sink) => {
+                        sinks::spawn(stream, sink, indexer.ack_handle());
+                    }
+                    None => {
+                        actix::spawn(listen_blocks(stream, indexer.ack_handle()));
+                    }
+                }
             });
             system.run()?;
         }
@@ -0,0 +1,174 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use near_indexer::StreamerMessage;
+use near_indexer::near_primitives::types::BlockHeight;
+use tracing::info;
+
+use super::{Sink, padded_height};
+
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Writes the messages as zstd-compressed JSON lines, one message per line.
+///
+/// The files are partitioned by block height: a file never spans over
+/// a multiple of `blocks_per_file`.  A file being written is named
+/// `<first height>.jsonl.zst.partial` and once complete it is renamed to
+/// `<first height>-<last height>.jsonl.zst` (heights are zero-padded so the
+/// files sort by height).  Only messages in complete files are reported as
+/// durable so partial files left behind by a crash are removed on start and
+/// their contents streamed again.
+pub(crate) struct JsonLinesSink {
+    dir: PathBuf,
+    blocks_per_file: u64,
+    current: Option<Partition>,
+    durable_height: Option<BlockHeight>,
+}
+
+struct Partition {
+    first_height: BlockHeight,
+    last_height: BlockHeight,
+    encoder: zstd::stream::write::Encoder<'static, BufWriter<File>>,
+}
+
+impl JsonLinesSink {
+    pub(crate) fn new(dir: &Path, blocks_per_file: u64) -> anyhow::Result<Self> {
+        anyhow::ensure!(blocks_per_file > 0, "blocks_per_file must be positive");
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.to_string_lossy().ends_with(PARTIAL_SUFFIX) {
+                info!(target: "indexer_example", path = %path.display(), "removing partial file");
+                std::fs::remove_file(&path)?;
+            }
+        }
+        Ok(Self { dir: dir.to_path_buf(), blocks_per_file, current: None, durable_height: None })
+    }
+
+    fn partial_path(&self, first_height: BlockHeight) -> PathBuf {
+        self.dir.join(format!("{}.jsonl.zst{PARTIAL_SUFFIX}", padded_height(first_height)))
+    }
+
+    fn open_partition(&self, first_height: BlockHeight) -> anyhow::Result<Partition> {
+        let file = File::create(self.partial_path(first_height))?;
+        let encoder = zstd::stream::write::Encoder::new(BufWriter::new(file), 0)?;
+        Ok(Partition { first_height, last_height: first_height, encoder })
+    }
+
+    /// Completes the current file and renames it to its final name.
+    fn rotate(&mut self) -> anyhow::Result<()> {
+        let Some(partition) = self.current.take() else {
+            return Ok(());
+        };
+        let file = partition.encoder.finish()?.into_inner().map_err(|err| err.into_error())?;
+        file.sync_all()?;
+        let path = self.dir.join(format!(
+            "{}-{}.jsonl.zst",
+            padded_height(partition.first_height),
+            padded_height(partition.last_height)
+        ));
+        std::fs::rename(self.partial_path(partition.first_height), &path)?;
+        info!(target: "indexer_example", path = %path.display(), "completed file");
+        self.durable_height = Some(partition.last_height);
+        Ok(())
+    }
+}
+
+impl Sink for JsonLinesSink {
+    fn write(&mut self, message: &StreamerMessage) -> anyhow::Result<()> {
+        let height = message.block.header.height;
+        let partition_of = |height: BlockHeight| height / self.blocks_per_file;
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| partition_of(current.first_height) != partition_of(height))
+        {
+            self.rotate()?;
+        }
+        if self.current.is_none() {
+            self.current = Some(self.open_partition(height)?);
+        }
+        let partition = self.current.as_mut().unwrap();
+        serde_json::to_writer(&mut partition.encoder, message)?;
+        partition.encoder.write_all(b"\n")?;
+        partition.last_height = height;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.rotate()
+    }
+
+    fn durable_height(&self) -> Option<BlockHeight> {
+        self.durable_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::test_message;
+    use std::io::BufRead;
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn read_heights(path: &Path) -> Vec<BlockHeight> {
+        let decoder = zstd::stream::read::Decoder::new(File::open(path).unwrap()).unwrap();
+        std::io::BufReader::new(decoder)
+            .lines()
+            .map(|line| {
+                let message: StreamerMessage = serde_json::from_str(&line.unwrap()).unwrap();
+                message.block.header.height
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = JsonLinesSink::new(dir.path(), 10).unwrap();
+        for height in [5, 7, 9] {
+            sink.write(&test_message(height)).unwrap();
+        }
+        assert_eq!(sink.durable_height(), None);
+        assert_eq!(file_names(dir.path()), vec![format!("{}.jsonl.zst.partial", padded_height(5))]);
+
+        // Crossing a multiple of `blocks_per_file` completes the file even
+        // though there were gaps in the heights.
+        sink.write(&test_message(12)).unwrap();
+        assert_eq!(sink.durable_height(), Some(9));
+        let completed = format!("{}-{}.jsonl.zst", padded_height(5), padded_height(9));
+        assert_eq!(read_heights(&dir.path().join(&completed)), vec![5, 7, 9]);
+
+        sink.flush().unwrap();
+        assert_eq!(sink.durable_height(), Some(12));
+        assert_eq!(
+            file_names(dir.path()),
+            vec![completed, format!("{}-{}.jsonl.zst", padded_height(12), padded_height(12))]
+        );
+    }
+
+    #[test]
+    fn test_partial_files_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = JsonLinesSink::new(dir.path(), 10).unwrap();
+        sink.write(&test_message(5)).unwrap();
+        drop(sink);
+
+        // A restart drops the partial file as its messages were never
+        // reported as durable.
+        let sink = JsonLinesSink::new(dir.path(), 10).unwrap();
+        assert_eq!(sink.durable_height(), None);
+        assert!(file_names(dir.path()).is_empty());
+    }
+}
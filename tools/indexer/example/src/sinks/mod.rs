@@ -0,0 +1,99 @@
+//! Reusable destinations for the `StreamerMessage`s produced by the indexer.
+//!
+//! A [`Sink`] is driven by [`spawn`] on a dedicated thread.  Messages are
+//! handed to the sink one by one and the next one is only taken from the
+//! stream once the previous one was written.  Since the stream returned by
+//! `Indexer::streamer()` is a bounded channel, a slow sink fills it up and
+//! pauses the streamer instead of buffering an unbounded number of messages.
+//!
+//! Whenever a sink reports that more messages are durably stored, their
+//! height is acknowledged via `AckHandle` so that the indexer resumes right
+//! after them when restarted.
+
+use near_indexer::near_primitives::types::BlockHeight;
+use near_indexer::{AckHandle, StreamerMessage};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+mod json_lines;
+mod spool;
+
+pub(crate) use json_lines::JsonLinesSink;
+pub(crate) use spool::SpoolSink;
+
+pub(crate) trait Sink: Send {
+    /// Writes the message.
+    ///
+    /// Messages are written in the order of their heights.  Blocking in this
+    /// method pauses the streamer.
+    fn write(&mut self, message: &StreamerMessage) -> anyhow::Result<()>;
+
+    /// Stores everything written so far durably.  Called once the stream
+    /// ends.
+    fn flush(&mut self) -> anyhow::Result<()>;
+
+    /// Returns the height of the last message which is durably stored.
+    fn durable_height(&self) -> Option<BlockHeight>;
+}
+
+/// Drains the stream into the sink on a dedicated thread.
+///
+/// Stops the actix system if the sink fails so that the node doesn't keep
+/// running without anybody consuming the blocks.
+pub(crate) fn spawn(
+    stream: mpsc::Receiver<StreamerMessage>,
+    sink: Box<dyn Sink>,
+    ack_handle: AckHandle,
+) -> std::thread::JoinHandle<()> {
+    let system = actix::System::current();
+    std::thread::Builder::new()
+        .name("indexer_sink".to_string())
+        .spawn(move || {
+            if let Err(err) = run(stream, sink, &ack_handle) {
+                error!(target: "indexer_example", ?err, "sink failed, stopping");
+                system.stop();
+            }
+        })
+        .expect("failed to spawn the sink thread")
+}
+
+fn run(
+    mut stream: mpsc::Receiver<StreamerMessage>,
+    mut sink: Box<dyn Sink>,
+    ack_handle: &AckHandle,
+) -> anyhow::Result<()> {
+    while let Some(message) = stream.blocking_recv() {
+        sink.write(&message)?;
+        ack_durable(sink.as_ref(), ack_handle)?;
+    }
+    info!(target: "indexer_example", "stream ended, flushing the sink");
+    sink.flush()?;
+    ack_durable(sink.as_ref(), ack_handle)
+}
+
+fn ack_durable(sink: &dyn Sink, ack_handle: &AckHandle) -> anyhow::Result<()> {
+    if let Some(height) = sink.durable_height() {
+        ack_handle.ack_blocking(height)?;
+    }
+    Ok(())
+}
+
+/// Formats the height for a file name.  Heights are zero-padded to the width
+/// of the largest height so that the files sort by height.
+fn padded_height(height: BlockHeight) -> String {
+    format!("{height:020}")
+}
+
+#[cfg(test)]
+fn test_message(height: BlockHeight) -> StreamerMessage {
+    use near_indexer::near_primitives::block::Block;
+    use near_indexer::near_primitives::version::PROTOCOL_VERSION;
+    use near_indexer::near_primitives::views::BlockView;
+
+    let block =
+        Block::genesis(PROTOCOL_VERSION, vec![], near_time::Utc::UNIX_EPOCH, height, 0, 0, &vec![]);
+    StreamerMessage {
+        block: BlockView::from_author_block("test.near".parse().unwrap(), block),
+        shards: vec![],
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use near_indexer::StreamerMessage;
+use near_indexer::near_primitives::types::BlockHeight;
+use tracing::{debug, info};
+
+use super::{Sink, padded_height};
+
+const TMP_SUFFIX: &str = ".json.tmp";
+const SPOOL_SUFFIX: &str = ".json";
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Spools every message into its own JSON file for other processes to tail.
+///
+/// A message is first written to `<height>.json.tmp` and then atomically
+/// renamed to `<height>.json`, so a consumer never observes a partially
+/// written file.  Heights are zero-padded so processing the files in the
+/// order of their names processes the blocks in order.  Consumers are
+/// expected to delete the files once they handled them.
+///
+/// Once there are `max_pending` files in the directory the sink waits for
+/// the consumers to catch up which in turn pauses the streamer.
+pub(crate) struct SpoolSink {
+    dir: PathBuf,
+    max_pending: usize,
+    durable_height: Option<BlockHeight>,
+}
+
+impl SpoolSink {
+    pub(crate) fn new(dir: &Path, max_pending: usize) -> anyhow::Result<Self> {
+        anyhow::ensure!(max_pending > 0, "max_pending must be positive");
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.to_string_lossy().ends_with(TMP_SUFFIX) {
+                info!(target: "indexer_example", path = %path.display(), "removing temporary file");
+                std::fs::remove_file(&path)?;
+            }
+        }
+        Ok(Self { dir: dir.to_path_buf(), max_pending, durable_height: None })
+    }
+
+    fn pending_files(&self) -> anyhow::Result<usize> {
+        let mut count = 0;
+        for entry in std::fs::read_dir(&self.dir)? {
+            if entry?.file_name().to_string_lossy().ends_with(SPOOL_SUFFIX) {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn wait_for_consumers(&self) -> anyhow::Result<()> {
+        let mut waiting = false;
+        while self.pending_files()? >= self.max_pending {
+            if !waiting {
+                debug!(target: "indexer_example", max_pending = self.max_pending, "spool is full, waiting for consumers");
+                waiting = true;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        Ok(())
+    }
+}
+
+impl Sink for SpoolSink {
+    fn write(&mut self, message: &StreamerMessage) -> anyhow::Result<()> {
+        self.wait_for_consumers()?;
+        let height = message.block.header.height;
+        let tmp_path = self.dir.join(format!("{}{TMP_SUFFIX}", padded_height(height)));
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, message)?;
+        writer.flush()?;
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+        std::fs::rename(
+            &tmp_path,
+            self.dir.join(format!("{}{SPOOL_SUFFIX}", padded_height(height))),
+        )?;
+        // The rename is only durable once the directory is synced.
+        File::open(&self.dir)?.sync_all()?;
+        self.durable_height = Some(height);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn durable_height(&self) -> Option<BlockHeight> {
+        self.durable_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::test_message;
+
+    fn spool_path(dir: &Path, height: BlockHeight) -> PathBuf {
+        dir.join(format!("{}{SPOOL_SUFFIX}", padded_height(height)))
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmp_path = dir.path().join(format!("{}{TMP_SUFFIX}", padded_height(3)));
+        std::fs::write(&tmp_path, "partial").unwrap();
+
+        let mut sink = SpoolSink::new(dir.path(), 10).unwrap();
+        assert!(!tmp_path.exists());
+        sink.write(&test_message(3)).unwrap();
+        sink.write(&test_message(4)).unwrap();
+        assert_eq!(sink.durable_height(), Some(4));
+
+        let message: StreamerMessage =
+            serde_json::from_slice(&std::fs::read(spool_path(dir.path(), 3)).unwrap()).unwrap();
+        assert_eq!(message.block.header.height, 3);
+        assert!(spool_path(dir.path(), 4).exists());
+    }
+
+    #[test]
+    fn test_backpressure() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = SpoolSink::new(dir.path(), 1).unwrap();
+        sink.write(&test_message(1)).unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let writer = std::thread::spawn(move || {
+            sink.write(&test_message(2)).unwrap();
+            sender.send(sink.durable_height()).unwrap();
+        });
+        // The spool is full so the write waits for the consumers.
+        assert!(receiver.recv_timeout(5 * POLL_INTERVAL).is_err());
+        assert!(!spool_path(dir.path(), 2).exists());
+
+        std::fs::remove_file(spool_path(dir.path(), 1)).unwrap();
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(10)).unwrap(), Some(2));
+        writer.join().unwrap();
+        assert!(spool_path(dir.path(), 2).exists());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::store::LightClientStore;
+use crate::verifier::{LightClientState, verify_execution_proof};
+use anyhow::Context;
+use near_jsonrpc_client_internal::{JsonRpcClient, new_client};
+use near_jsonrpc_primitives::types::light_client::{
+    RpcLightClientExecutionProofRequest, RpcLightClientNextBlockRequest,
+};
+use near_primitives::block_header::BlockHeader;
+use near_primitives::epoch_sync::CompressedEpochSyncProof;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, TransactionOrReceiptId};
+use near_primitives::utils::compression::CompressedData;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Light client following a NEAR chain through an untrusted RPC node.
+#[derive(clap::Parser)]
+pub struct LightClientCommand {
+    /// Directory with the state and the verified headers of the light client.
+    #[clap(long)]
+    home: PathBuf,
+    #[clap(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum SubCommand {
+    /// Verify an epoch sync proof, as dumped by `neard view-state
+    /// dump-epoch-sync-proof`, and start following the chain from the most
+    /// recent epoch in it.
+    Init(InitCmd),
+    /// Follow the chain by fetching and verifying light client blocks.
+    Run(RunCmd),
+    /// Verify the outcome of a transaction.
+    VerifyTx(VerifyTxCmd),
+    /// Verify the outcome of a receipt.
+    VerifyReceipt(VerifyReceiptCmd),
+}
+
+#[derive(clap::Parser)]
+struct InitCmd {
+    /// Hash of the genesis block of the chain, which is the only input the
+    /// light client trusts.
+    #[clap(long)]
+    genesis_hash: CryptoHash,
+    /// File with the borsh-serialized genesis block header.
+    #[clap(long)]
+    genesis_header: PathBuf,
+    /// File with the borsh-serialized compressed epoch sync proof.
+    #[clap(long)]
+    epoch_sync_proof: PathBuf,
+}
+
+#[derive(clap::Parser)]
+struct RunCmd {
+    /// Address of the RPC node.
+    #[clap(long)]
+    rpc_url: String,
+    /// How often to ask for a new block, in seconds.
+    #[clap(long, default_value = "10")]
+    poll_interval: u64,
+}
+
+#[derive(clap::Parser)]
+struct VerifyTxCmd {
+    /// Address of the RPC node.
+    #[clap(long)]
+    rpc_url: String,
+    #[clap(long)]
+    transaction_hash: CryptoHash,
+    #[clap(long)]
+    sender_id: AccountId,
+    /// Verified header to verify the proof against, the head by default.
+    #[clap(long)]
+    head: Option<CryptoHash>,
+}
+
+#[derive(clap::Parser)]
+struct VerifyReceiptCmd {
+    /// Address of the RPC node.
+    #[clap(long)]
+    rpc_url: String,
+    #[clap(long)]
+    receipt_id: CryptoHash,
+    #[clap(long)]
+    receiver_id: AccountId,
+    /// Verified header to verify the proof against, the head by default.
+    #[clap(long)]
+    head: Option<CryptoHash>,
+}
+
+impl LightClientCommand {
+    pub fn run(self) -> anyhow::Result<()> {
+        let store = LightClientStore::open(&self.home)?;
+        match self.subcmd {
+            SubCommand::Init(cmd) => cmd.run(&store),
+            SubCommand::Run(cmd) => {
+                let state = load_state(&store)?;
+                actix::System::new().block_on(cmd.run(&store, state))
+            }
+            SubCommand::VerifyTx(cmd) => {
+                let id = TransactionOrReceiptId::Transaction {
+                    transaction_hash: cmd.transaction_hash,
+                    sender_id: cmd.sender_id,
+                };
+                actix::System::new().block_on(verify(&store, &cmd.rpc_url, id, cmd.head))
+            }
+            SubCommand::VerifyReceipt(cmd) => {
+                let id = TransactionOrReceiptId::Receipt {
+                    receipt_id: cmd.receipt_id,
+                    receiver_id: cmd.receiver_id,
+                };
+                actix::System::new().block_on(verify(&store, &cmd.rpc_url, id, cmd.head))
+            }
+        }
+    }
+}
+
+impl InitCmd {
+    fn run(self, store: &LightClientStore) -> anyhow::Result<()> {
+        if store.load_state()?.is_some() {
+            anyhow::bail!("light client is already initialized");
+        }
+        let genesis_header: BlockHeader = borsh::from_slice(
+            &std::fs::read(&self.genesis_header).context("failed to read genesis header")?,
+        )?;
+        let proof: CompressedEpochSyncProof = borsh::from_slice(
+            &std::fs::read(&self.epoch_sync_proof).context("failed to read epoch sync proof")?,
+        )?;
+        let proof = proof.decode().context("failed to decompress epoch sync proof")?.0.into_v1();
+        let state = LightClientState::bootstrap(self.genesis_hash, &genesis_header, &proof)?;
+        store.save_state(&state)?;
+        println!(
+            "Initialized light client at block {} at height {}",
+            state.head_hash(),
+            state.head.inner_lite.height
+        );
+        Ok(())
+    }
+}
+
+impl RunCmd {
+    async fn run(
+        self,
+        store: &LightClientStore,
+        mut state: LightClientState,
+    ) -> anyhow::Result<()> {
+        let client = new_client(&self.rpc_url);
+        let poll_interval = Duration::from_secs(self.poll_interval);
+        loop {
+            match fetch_next_block(&client, &mut state).await {
+                // Keep going until caught up with the chain.
+                Ok(true) => {
+                    store.save_state(&state)?;
+                    tracing::info!(
+                        target: "light_client",
+                        height = state.head.inner_lite.height,
+                        hash = %state.head_hash(),
+                        epoch_id = %state.head.inner_lite.epoch_id,
+                        "verified new head"
+                    );
+                    continue;
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::warn!(target: "light_client", ?err, "failed to get the next block")
+                }
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+/// Fetches the block following the head and makes it the new head if it's
+/// valid.  Returns whether there was a new block.
+async fn fetch_next_block(
+    client: &JsonRpcClient,
+    state: &mut LightClientState,
+) -> anyhow::Result<bool> {
+    let response = client
+        .next_light_client_block(RpcLightClientNextBlockRequest {
+            last_block_hash: state.head_hash(),
+        })
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    let Some(block) = response.light_client_block else {
+        return Ok(false);
+    };
+    state.apply_block(&block)?;
+    Ok(true)
+}
+
+async fn verify(
+    store: &LightClientStore,
+    rpc_url: &str,
+    id: TransactionOrReceiptId,
+    head: Option<CryptoHash>,
+) -> anyhow::Result<()> {
+    let head_hash = match head {
+        Some(head) => head,
+        None => load_state(store)?.head_hash(),
+    };
+    let light_client_head =
+        store.get_header(&head_hash)?.with_context(|| format!("unknown header {head_hash}"))?;
+    let outcome_id = match &id {
+        TransactionOrReceiptId::Transaction { transaction_hash, .. } => *transaction_hash,
+        TransactionOrReceiptId::Receipt { receipt_id, .. } => *receipt_id,
+    };
+    let proof = new_client(rpc_url)
+        .EXPERIMENTAL_light_client_proof(RpcLightClientExecutionProofRequest {
+            id,
+            light_client_head: head_hash,
+        })
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    verify_execution_proof(&outcome_id, &proof, &light_client_head)?;
+    println!(
+        "Verified outcome of {} in block {} at height {}: {:?}",
+        outcome_id,
+        proof.outcome_proof.block_hash,
+        proof.block_header_lite.inner_lite.height,
+        proof.outcome_proof.outcome.status
+    );
+    Ok(())
+}
+
+fn load_state(store: &LightClientStore) -> anyhow::Result<LightClientState> {
+    store.load_state()?.context("light client is not initialized, run `init` first")
+}
@@ -0,0 +1,10 @@
+//! Light client which bootstraps from an epoch sync proof, follows the chain
+//! through the light client blocks of an RPC node and verifies the outcomes of
+//! transactions and receipts against the blocks it verified, without trusting
+//! the RPC node.
+pub use cli::LightClientCommand;
+pub use verifier::{LightClientError, LightClientState, verify_execution_proof};
+
+mod cli;
+mod store;
+mod verifier;
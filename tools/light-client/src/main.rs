@@ -0,0 +1,8 @@
+use clap::Parser;
+use near_light_client::LightClientCommand;
+
+fn main() -> anyhow::Result<()> {
+    let env_filter = near_o11y::EnvFilterBuilder::from_env().finish()?;
+    let _subscriber = near_o11y::default_subscriber(env_filter, &Default::default()).global();
+    LightClientCommand::parse().run()
+}
@@ -0,0 +1,70 @@
+use crate::verifier::LightClientState;
+use anyhow::Context;
+use near_primitives::hash::CryptoHash;
+use near_primitives::views::LightClientBlockLiteView;
+use std::path::{Path, PathBuf};
+
+const STATE_FILENAME: &str = "state";
+const HEADERS_DIR: &str = "headers";
+
+/// Verified headers and the state of the light client, kept in a directory as
+/// borsh-serialized files:
+///
+/// * `state` - the [`LightClientState`] with the current head,
+/// * `headers/<hash>` - every head the client ever had, which proofs can be
+///   verified against.
+pub struct LightClientStore {
+    dir: PathBuf,
+}
+
+impl LightClientStore {
+    pub fn open(dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir.join(HEADERS_DIR))
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        Ok(Self { dir: dir.to_path_buf() })
+    }
+
+    pub fn load_state(&self) -> anyhow::Result<Option<LightClientState>> {
+        self.read(&self.dir.join(STATE_FILENAME))
+    }
+
+    /// Saves the state along with its head.  The head is written first, so the
+    /// header of the head of a saved state can always be found.
+    pub fn save_state(&self, state: &LightClientState) -> anyhow::Result<()> {
+        self.write(&self.header_path(&state.head_hash()), &state.head)?;
+        self.write(&self.dir.join(STATE_FILENAME), state)
+    }
+
+    pub fn get_header(
+        &self,
+        hash: &CryptoHash,
+    ) -> anyhow::Result<Option<LightClientBlockLiteView>> {
+        self.read(&self.header_path(hash))
+    }
+
+    fn header_path(&self, hash: &CryptoHash) -> PathBuf {
+        self.dir.join(HEADERS_DIR).join(hash.to_string())
+    }
+
+    fn read<T: borsh::BorshDeserialize>(&self, path: &Path) -> anyhow::Result<Option<T>> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Some(
+                borsh::from_slice(&bytes)
+                    .with_context(|| format!("failed to deserialize {}", path.display()))?,
+            )),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Writes to a temporary file and renames it, so an interrupted write
+    /// doesn't leave a truncated file behind.
+    fn write<T: borsh::BorshSerialize>(&self, path: &Path, value: &T) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, borsh::to_vec(value)?)
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, path)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,431 @@
+//! Verification of everything the light client learns from an untrusted RPC node.
+//!
+//! The only trusted input is the hash of the genesis block.  The genesis header
+//! proves the block producers of the first epoch, the epoch sync proof proves
+//! the block producers and the last final block of every epoch since, and from
+//! there the light client blocks are verified as described in the
+//! [light client spec](https://nomicon.io/ChainSpec/LightClient).
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse;
+use near_primitives::block::{Approval, ApprovalInner};
+use near_primitives::block_header::BlockHeader;
+use near_primitives::epoch_sync::{
+    EpochSyncProofV1, get_epoch_info_block_producers, verify_block_producer_handoff,
+};
+use near_primitives::hash::CryptoHash;
+use near_primitives::merkle::{combine_hash, compute_root_from_path};
+use near_primitives::types::validator_stake::ValidatorStake;
+use near_primitives::types::{AccountId, Balance, BlockHeight};
+use near_primitives::views::{LightClientBlockLiteView, LightClientBlockView};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum LightClientError {
+    #[error("genesis header hash {actual} doesn't match the trusted genesis hash {expected}")]
+    GenesisHashMismatch { expected: CryptoHash, actual: CryptoHash },
+    #[error("invalid epoch sync proof: {0}")]
+    InvalidEpochSyncProof(String),
+    #[error("block at height {height} is not newer than the head at height {head_height}")]
+    BlockNotNewer { height: BlockHeight, head_height: BlockHeight },
+    #[error("block epoch {0} is neither the epoch of the head nor the next one")]
+    UnknownEpoch(CryptoHash),
+    #[error("first block of the next epoch doesn't have the next block producers")]
+    MissingNextBlockProducers,
+    #[error("next block producers don't match the next_bp_hash of the block")]
+    InvalidNextBlockProducers,
+    #[error("invalid approval signature from {0}")]
+    InvalidApprovalSignature(AccountId),
+    #[error("block is approved by {approved_stake} out of {total_stake} stake")]
+    NotEnoughApprovals { approved_stake: Balance, total_stake: Balance },
+    #[error("proof is for outcome {actual} instead of {expected}")]
+    OutcomeIdMismatch { expected: CryptoHash, actual: CryptoHash },
+    #[error("invalid outcome root proof")]
+    InvalidOutcomeRootProof,
+    #[error("invalid block hash proof")]
+    InvalidBlockHashProof,
+    #[error("block {0} is not in the chain of the light client head")]
+    InvalidBlockProof(CryptoHash),
+}
+
+/// Verified state of the light client.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct LightClientState {
+    /// Hash of the genesis block of the chain followed by the client.
+    pub genesis_hash: CryptoHash,
+    /// Latest verified block.
+    pub head: LightClientBlockLiteView,
+    /// Block producers of the epoch of the head.
+    pub epoch_block_producers: Vec<ValidatorStake>,
+    /// Block producers of the epoch after the epoch of the head.
+    pub next_epoch_block_producers: Vec<ValidatorStake>,
+}
+
+impl LightClientState {
+    /// Verifies the epoch sync proof against the genesis header and starts the
+    /// client at the last final block of the most recent epoch in the proof.
+    pub fn bootstrap(
+        genesis_hash: CryptoHash,
+        genesis_header: &BlockHeader,
+        proof: &EpochSyncProofV1,
+    ) -> Result<Self, LightClientError> {
+        if *genesis_header.hash() != genesis_hash {
+            return Err(LightClientError::GenesisHashMismatch {
+                expected: genesis_hash,
+                actual: *genesis_header.hash(),
+            });
+        }
+        // The first two epochs are both run by the genesis validators, so the
+        // block producers of the first epoch in the proof are the ones
+        // committed to in the genesis header.
+        let first_epoch = proof.all_epochs.first().ok_or_else(|| {
+            LightClientError::InvalidEpochSyncProof("all_epochs is empty".to_string())
+        })?;
+        if !block_producers_match(&first_epoch.block_producers, genesis_header.next_bp_hash()) {
+            return Err(LightClientError::InvalidEpochSyncProof(
+                "invalid block producers for second epoch after genesis".to_string(),
+            ));
+        }
+        proof.verify().map_err(LightClientError::InvalidEpochSyncProof)?;
+
+        // Verified by `verify` against the endorsements of the block producers.
+        let last_epoch = proof.all_epochs.last().unwrap();
+        let head = &last_epoch.last_final_block_header;
+        // Verified by `verify` against the epoch sync data hash of the first
+        // block of the epoch of the head.
+        let next_epoch_block_producers =
+            get_epoch_info_block_producers(&proof.last_epoch.next_next_epoch_info);
+        if !block_producers_match(&next_epoch_block_producers, head.next_bp_hash()) {
+            return Err(LightClientError::InvalidEpochSyncProof(
+                "invalid block producers for the epoch after the last one".to_string(),
+            ));
+        }
+        Ok(Self {
+            genesis_hash,
+            head: head.clone().into(),
+            epoch_block_producers: last_epoch.block_producers.clone(),
+            next_epoch_block_producers,
+        })
+    }
+
+    pub fn head_hash(&self) -> CryptoHash {
+        self.head.hash()
+    }
+
+    /// Verifies a block returned by `next_light_client_block` and makes it the
+    /// new head.  Returns the verified block.
+    pub fn apply_block(
+        &mut self,
+        block: &LightClientBlockView,
+    ) -> Result<LightClientBlockLiteView, LightClientError> {
+        let head_height = self.head.inner_lite.height;
+        let height = block.inner_lite.height;
+        if height <= head_height {
+            return Err(LightClientError::BlockNotNewer { height, head_height });
+        }
+
+        let epoch_id = block.inner_lite.epoch_id;
+        let is_next_epoch = epoch_id == self.head.inner_lite.next_epoch_id;
+        let block_producers = if epoch_id == self.head.inner_lite.epoch_id {
+            &self.epoch_block_producers
+        } else if is_next_epoch {
+            &self.next_epoch_block_producers
+        } else {
+            return Err(LightClientError::UnknownEpoch(epoch_id));
+        };
+        if is_next_epoch && block.next_bps.is_none() {
+            return Err(LightClientError::MissingNextBlockProducers);
+        }
+
+        let block_lite = LightClientBlockLiteView {
+            prev_block_hash: block.prev_block_hash,
+            inner_rest_hash: block.inner_rest_hash,
+            inner_lite: block.inner_lite.clone(),
+        };
+        // The approvals are included in the block after the next one, so they
+        // endorse the next block, whose hash is derived from the hash of this one.
+        let next_block_hash = combine_hash(&block.next_block_inner_hash, &block_lite.hash());
+        verify_approvals(
+            &Approval::get_data_for_sig(&ApprovalInner::Endorsement(next_block_hash), height + 2),
+            block_producers,
+            &block.approvals_after_next,
+        )?;
+
+        let next_bps = block
+            .next_bps
+            .as_ref()
+            .map(|next_bps| {
+                let next_bps: Vec<ValidatorStake> =
+                    next_bps.iter().cloned().map(|view| view.into_validator_stake()).collect();
+                if !block_producers_match(&next_bps, &block.inner_lite.next_bp_hash) {
+                    return Err(LightClientError::InvalidNextBlockProducers);
+                }
+                Ok(next_bps)
+            })
+            .transpose()?;
+
+        if is_next_epoch {
+            self.epoch_block_producers = std::mem::take(&mut self.next_epoch_block_producers);
+        }
+        if let Some(next_bps) = next_bps {
+            self.next_epoch_block_producers = next_bps;
+        }
+        self.head = block_lite.clone();
+        Ok(block_lite)
+    }
+}
+
+/// Verifies the result of `EXPERIMENTAL_light_client_proof` for the transaction
+/// or receipt `id` against a verified block of the light client, which must be
+/// the `light_client_head` the proof was requested for.
+pub fn verify_execution_proof(
+    id: &CryptoHash,
+    proof: &RpcLightClientExecutionProofResponse,
+    light_client_head: &LightClientBlockLiteView,
+) -> Result<(), LightClientError> {
+    let outcome_proof = &proof.outcome_proof;
+    if outcome_proof.id != *id {
+        return Err(LightClientError::OutcomeIdMismatch {
+            expected: *id,
+            actual: outcome_proof.id,
+        });
+    }
+
+    let outcome_hash = CryptoHash::hash_borsh(&outcome_proof.to_hashes());
+    let shard_outcome_root = compute_root_from_path(&outcome_proof.proof, outcome_hash);
+    let block_outcome_root = compute_root_from_path(
+        &proof.outcome_root_proof,
+        CryptoHash::hash_borsh(shard_outcome_root),
+    );
+    if proof.block_header_lite.inner_lite.outcome_root != block_outcome_root {
+        return Err(LightClientError::InvalidOutcomeRootProof);
+    }
+
+    let block_hash = outcome_proof.block_hash;
+    if proof.block_header_lite.hash() != block_hash {
+        return Err(LightClientError::InvalidBlockHashProof);
+    }
+
+    if compute_root_from_path(&proof.block_proof, block_hash)
+        != light_client_head.inner_lite.block_merkle_root
+    {
+        return Err(LightClientError::InvalidBlockProof(block_hash));
+    }
+    Ok(())
+}
+
+/// Verifies that more than 2/3 of the stake of the block producers signed the
+/// message.  Approvals beyond the block producers belong to the block producers
+/// of the next epoch and are ignored.
+fn verify_approvals(
+    message: &[u8],
+    block_producers: &[ValidatorStake],
+    approvals: &[Option<Box<near_crypto::Signature>>],
+) -> Result<(), LightClientError> {
+    let mut total_stake: Balance = 0;
+    let mut approved_stake: Balance = 0;
+    for (index, block_producer) in block_producers.iter().enumerate() {
+        total_stake += block_producer.stake();
+        let Some(Some(signature)) = approvals.get(index) else {
+            continue;
+        };
+        if !signature.verify(message, block_producer.public_key()) {
+            return Err(LightClientError::InvalidApprovalSignature(
+                block_producer.account_id().clone(),
+            ));
+        }
+        approved_stake += block_producer.stake();
+    }
+    if approved_stake <= total_stake * 2 / 3 {
+        return Err(LightClientError::NotEnoughApprovals { approved_stake, total_stake });
+    }
+    Ok(())
+}
+
+/// Returns whether the block producers hash to `bp_hash`.  Depending on the
+/// protocol version the hash is computed over one of two encodings, which
+/// can't collide, see `EpochSyncProofEpochData::use_versioned_bp_hash_format`.
+fn block_producers_match(block_producers: &Vec<ValidatorStake>, bp_hash: &CryptoHash) -> bool {
+    verify_block_producer_handoff(block_producers, true, bp_hash)
+        || verify_block_producer_handoff(block_producers, false, bp_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, SecretKey};
+    use near_primitives::block::compute_bp_hash_from_validator_stakes;
+    use near_primitives::views::validator_stake_view::ValidatorStakeView;
+    use near_primitives::views::{
+        BlockHeaderInnerLiteView, ExecutionOutcomeView, ExecutionOutcomeWithIdView,
+        ExecutionStatusView,
+    };
+
+    fn keys(names: &[&str]) -> Vec<(SecretKey, ValidatorStake)> {
+        names
+            .iter()
+            .map(|name| {
+                let secret_key = SecretKey::from_seed(KeyType::ED25519, name);
+                let stake =
+                    ValidatorStake::new(name.parse().unwrap(), secret_key.public_key(), 100);
+                (secret_key, stake)
+            })
+            .collect()
+    }
+
+    fn state(block_producers: Vec<ValidatorStake>) -> LightClientState {
+        LightClientState {
+            genesis_hash: CryptoHash::default(),
+            head: LightClientBlockLiteView {
+                prev_block_hash: CryptoHash::hash_bytes(b"prev"),
+                inner_rest_hash: CryptoHash::hash_bytes(b"rest"),
+                inner_lite: BlockHeaderInnerLiteView {
+                    height: 10,
+                    epoch_id: CryptoHash::hash_bytes(b"epoch"),
+                    next_epoch_id: CryptoHash::hash_bytes(b"next_epoch"),
+                    prev_state_root: CryptoHash::default(),
+                    outcome_root: CryptoHash::default(),
+                    timestamp: 0,
+                    timestamp_nanosec: 0,
+                    next_bp_hash: CryptoHash::default(),
+                    block_merkle_root: CryptoHash::default(),
+                },
+            },
+            epoch_block_producers: block_producers.clone(),
+            next_epoch_block_producers: block_producers,
+        }
+    }
+
+    /// Builds the next block of the head in the given epoch, approved by the
+    /// given signers.
+    fn block(
+        state: &LightClientState,
+        epoch_id: CryptoHash,
+        next_bps: Option<&[ValidatorStake]>,
+        signers: &[Option<&SecretKey>],
+    ) -> LightClientBlockView {
+        let mut inner_lite = state.head.inner_lite.clone();
+        inner_lite.height += 1;
+        inner_lite.epoch_id = epoch_id;
+        if let Some(next_bps) = next_bps {
+            inner_lite.next_bp_hash =
+                compute_bp_hash_from_validator_stakes(&next_bps.to_vec(), true);
+        }
+        let mut block = LightClientBlockView {
+            prev_block_hash: state.head_hash(),
+            next_block_inner_hash: CryptoHash::hash_bytes(b"next"),
+            inner_lite,
+            inner_rest_hash: CryptoHash::hash_bytes(b"rest"),
+            next_bps: next_bps
+                .map(|bps| bps.iter().cloned().map(ValidatorStakeView::from).collect()),
+            approvals_after_next: vec![],
+        };
+        let block_hash = LightClientBlockLiteView {
+            prev_block_hash: block.prev_block_hash,
+            inner_rest_hash: block.inner_rest_hash,
+            inner_lite: block.inner_lite.clone(),
+        }
+        .hash();
+        let message = Approval::get_data_for_sig(
+            &ApprovalInner::Endorsement(combine_hash(&block.next_block_inner_hash, &block_hash)),
+            block.inner_lite.height + 2,
+        );
+        block.approvals_after_next = signers
+            .iter()
+            .map(|signer| signer.map(|signer| Box::new(signer.sign(&message))))
+            .collect();
+        block
+    }
+
+    #[test]
+    fn test_apply_block() {
+        let keys = keys(&["test0", "test1", "test2"]);
+        let block_producers: Vec<_> = keys.iter().map(|(_, stake)| stake.clone()).collect();
+        let mut state = state(block_producers);
+        let epoch_id = state.head.inner_lite.epoch_id;
+        let next_epoch_id = state.head.inner_lite.next_epoch_id;
+
+        // Two out of three is not more than 2/3 of the stake.
+        let two = [Some(&keys[0].0), Some(&keys[1].0), None];
+        let all = [Some(&keys[0].0), Some(&keys[1].0), Some(&keys[2].0)];
+        assert_eq!(
+            state.apply_block(&block(&state, epoch_id, None, &two)).unwrap_err(),
+            LightClientError::NotEnoughApprovals { approved_stake: 200, total_stake: 300 }
+        );
+        let forged = [Some(&keys[0].0), Some(&keys[0].0), Some(&keys[2].0)];
+        assert_eq!(
+            state.apply_block(&block(&state, epoch_id, None, &forged)).unwrap_err(),
+            LightClientError::InvalidApprovalSignature("test1".parse().unwrap())
+        );
+        let first_block = block(&state, epoch_id, None, &all);
+        let head = state.apply_block(&first_block).unwrap();
+        assert_eq!(state.head.inner_lite.height, 11);
+        assert_eq!(head.hash(), state.head_hash());
+        assert_eq!(
+            state.apply_block(&first_block).unwrap_err(),
+            LightClientError::BlockNotNewer { height: 11, head_height: 11 }
+        );
+
+        // Moving to the next epoch requires the block producers of the one after.
+        assert_eq!(
+            state.apply_block(&block(&state, next_epoch_id, None, &all)).unwrap_err(),
+            LightClientError::MissingNextBlockProducers
+        );
+        let new_block_producers = vec![keys[0].1.clone()];
+        let mut tampered = block(&state, next_epoch_id, Some(&new_block_producers), &all);
+        tampered.next_bps = Some(vec![keys[1].1.clone().into()]);
+        assert_eq!(
+            state.apply_block(&tampered).unwrap_err(),
+            LightClientError::InvalidNextBlockProducers
+        );
+        state.apply_block(&block(&state, next_epoch_id, Some(&new_block_producers), &all)).unwrap();
+        assert_eq!(state.next_epoch_block_producers, new_block_producers);
+        let unknown_epoch_id = CryptoHash::hash_bytes(b"unknown");
+        assert_eq!(
+            state.apply_block(&block(&state, unknown_epoch_id, None, &all)).unwrap_err(),
+            LightClientError::UnknownEpoch(unknown_epoch_id)
+        );
+    }
+
+    #[test]
+    fn test_verify_execution_proof() {
+        let id = CryptoHash::hash_bytes(b"tx");
+        let outcome = ExecutionOutcomeView {
+            logs: vec!["log".to_string()],
+            receipt_ids: vec![],
+            gas_burnt: 100,
+            tokens_burnt: 10,
+            executor_id: "test0".parse().unwrap(),
+            status: ExecutionStatusView::SuccessValue(vec![]),
+            metadata: Default::default(),
+        };
+        // A block with a single shard with a single outcome, so the proofs are empty.
+        let mut block_header_lite = state(vec![]).head;
+        block_header_lite.inner_lite.outcome_root =
+            CryptoHash::hash_borsh(CryptoHash::hash_borsh(outcome.to_hashes(id)));
+        let block_hash = block_header_lite.hash();
+        let mut light_client_head = block_header_lite.clone();
+        light_client_head.inner_lite.block_merkle_root = block_hash;
+        let mut proof = RpcLightClientExecutionProofResponse {
+            outcome_proof: ExecutionOutcomeWithIdView { proof: vec![], block_hash, id, outcome },
+            outcome_root_proof: vec![],
+            block_header_lite,
+            block_proof: vec![],
+        };
+        verify_execution_proof(&id, &proof, &light_client_head).unwrap();
+
+        let other_id = CryptoHash::hash_bytes(b"other");
+        assert_eq!(
+            verify_execution_proof(&other_id, &proof, &light_client_head).unwrap_err(),
+            LightClientError::OutcomeIdMismatch { expected: other_id, actual: id }
+        );
+        light_client_head.inner_lite.block_merkle_root = CryptoHash::default();
+        assert_eq!(
+            verify_execution_proof(&id, &proof, &light_client_head).unwrap_err(),
+            LightClientError::InvalidBlockProof(block_hash)
+        );
+        proof.outcome_proof.outcome.tokens_burnt = 20;
+        assert_eq!(
+            verify_execution_proof(&id, &proof, &light_client_head).unwrap_err(),
+            LightClientError::InvalidOutcomeRootProof
+        );
+    }
+}
@@ -311,6 +311,7 @@ impl From<QueryError> for ChainError {
             QueryError::UnavailableShard { .. }
             | QueryError::UnknownAccount { .. }
             | QueryError::NoContractCode { .. }
+            | QueryError::NoGlobalContractCode { .. }
             | QueryError::UnknownAccessKey { .. }
             | QueryError::GarbageCollectedBlock { .. }
             | QueryError::UnknownBlock { .. } => Self::Unknown,
@@ -324,6 +325,7 @@ impl From<RuntimeQueryError> for ChainError {
         match err {
             RuntimeQueryError::UnknownAccount { .. }
             | RuntimeQueryError::NoContractCode { .. }
+            | RuntimeQueryError::NoGlobalContractCode { .. }
             | RuntimeQueryError::UnknownAccessKey { .. } => Self::Unknown,
             _ => Self::other(err),
         }
@@ -1773,6 +1775,7 @@ impl<T: ChainAccess> TxMirror<T> {
             await_for_node_synced: near_indexer::AwaitForNodeSyncedEnum::StreamWhileSyncing,
             finality: Finality::Final,
             validate_genesis: false,
+            filter: Default::default(),
         })
         .context("failed to start target chain indexer")?;
         let (target_view_client, target_client, tx_processor) = target_indexer.client_actors();
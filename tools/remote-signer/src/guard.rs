@@ -0,0 +1,165 @@
+use anyhow::Context;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::validator_signer::{SignedMessageKind, SignedMessageSlot};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The last message signed for given message kind and shard.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+struct LastSigned {
+    kind: SignedMessageKind,
+    shard_id: Option<ShardId>,
+    height: BlockHeight,
+    payload_hash: CryptoHash,
+}
+
+/// Double-sign protection of the remote signer.
+///
+/// Keeps track of the last signed height for every message kind (and shard,
+/// for per-shard messages) and refuses to sign messages for lower heights or
+/// different messages for the same height.  A message with several payloads,
+/// i.e. a chunk endorsement or the parts of a state witness, is signed as
+/// a whole, so it is a single message for the purpose of this check.  The
+/// state is persisted to a file before any signature is handed out, so the
+/// protection survives restarts of the signer.
+pub struct DoubleSignGuard {
+    path: PathBuf,
+    last_signed: BTreeMap<(SignedMessageKind, Option<ShardId>), LastSigned>,
+}
+
+impl DoubleSignGuard {
+    /// Loads the state from given file.  A missing file is treated as empty
+    /// state.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let entries: Vec<LastSigned> = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        let last_signed =
+            entries.into_iter().map(|entry| ((entry.kind, entry.shard_id), entry)).collect();
+        Ok(Self { path: path.to_path_buf(), last_signed })
+    }
+
+    /// Checks that signing the message with given slot and payloads cannot
+    /// result in a double sign and records it as signed.
+    pub fn check_and_record(
+        &mut self,
+        slot: SignedMessageSlot,
+        payloads: &[Vec<u8>],
+    ) -> anyhow::Result<()> {
+        let SignedMessageSlot { kind, height, shard_id } = slot;
+        let payload_hash = CryptoHash::hash_borsh(payloads);
+        if let Some(last) = self.last_signed.get(&(kind, shard_id)) {
+            if height < last.height {
+                anyhow::bail!(
+                    "{kind:?} at height {height} is below the last signed height {}",
+                    last.height
+                );
+            }
+            if height == last.height {
+                if payload_hash == last.payload_hash {
+                    return Ok(());
+                }
+                anyhow::bail!("conflicting {kind:?} at already signed height {height}");
+            }
+        }
+        self.last_signed
+            .insert((kind, shard_id), LastSigned { kind, shard_id, height, payload_hash });
+        self.persist()
+    }
+
+    fn persist(&self) -> anyhow::Result<()> {
+        let entries: Vec<&LastSigned> = self.last_signed.values().collect();
+        let tmp_path = self.path.with_extension("tmp");
+        let file = std::fs::File::create(&tmp_path)
+            .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+        serde_json::to_writer(&file, &entries)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(
+        kind: SignedMessageKind,
+        height: BlockHeight,
+        shard_id: Option<ShardId>,
+    ) -> SignedMessageSlot {
+        SignedMessageSlot { kind, height, shard_id }
+    }
+
+    fn payloads(payloads: &[&[u8]]) -> Vec<Vec<u8>> {
+        payloads.iter().map(|payload| payload.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_refuses_double_sign() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signer_state.json");
+        let mut guard = DoubleSignGuard::load(&path).unwrap();
+        let block = |height| slot(SignedMessageKind::Block, height, None);
+        let approval = |height| slot(SignedMessageKind::Approval, height, None);
+
+        guard.check_and_record(block(10), &payloads(&[b"a"])).unwrap();
+        // Signing the very same block again is fine.
+        guard.check_and_record(block(10), &payloads(&[b"a"])).unwrap();
+        // A different block at the same height or a block at a lower height is not.
+        guard.check_and_record(block(10), &payloads(&[b"b"])).unwrap_err();
+        guard.check_and_record(block(9), &payloads(&[b"c"])).unwrap_err();
+        // Other message kinds are tracked separately.
+        guard.check_and_record(approval(9), &payloads(&[b"c"])).unwrap();
+        guard.check_and_record(block(11), &payloads(&[b"d"])).unwrap();
+
+        // The state survives a restart.
+        let mut guard = DoubleSignGuard::load(&path).unwrap();
+        guard.check_and_record(block(11), &payloads(&[b"e"])).unwrap_err();
+        guard.check_and_record(approval(8), &payloads(&[b"f"])).unwrap_err();
+    }
+
+    #[test]
+    fn test_multi_payload_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut guard = DoubleSignGuard::load(&dir.path().join("signer_state.json")).unwrap();
+        let shard_0 = Some(ShardId::new(0));
+        let shard_1 = Some(ShardId::new(1));
+        let witness = |height, shard_id| slot(SignedMessageKind::StateWitness, height, shard_id);
+        let endorsement =
+            |height, shard_id| slot(SignedMessageKind::ChunkEndorsement, height, shard_id);
+
+        // All parts of a state witness are signed at once.
+        guard.check_and_record(witness(10, shard_0), &payloads(&[b"part 0", b"part 1"])).unwrap();
+        guard.check_and_record(witness(10, shard_0), &payloads(&[b"part 0", b"part 1"])).unwrap();
+        guard.check_and_record(witness(5, shard_1), &payloads(&[b"part 0"])).unwrap();
+        // A different witness for the same chunk is refused, even if it
+        // shares some of the parts.
+        guard
+            .check_and_record(witness(10, shard_0), &payloads(&[b"part 0", b"part 2"]))
+            .unwrap_err();
+        guard.check_and_record(witness(10, shard_0), &payloads(&[b"part 1"])).unwrap_err();
+        guard.check_and_record(witness(9, shard_0), &payloads(&[b"part 0"])).unwrap_err();
+
+        // Both parts of a chunk endorsement are checked together.
+        guard
+            .check_and_record(endorsement(10, shard_0), &payloads(&[b"inner", b"metadata"]))
+            .unwrap();
+        guard
+            .check_and_record(endorsement(10, shard_0), &payloads(&[b"inner", b"metadata"]))
+            .unwrap();
+        guard
+            .check_and_record(endorsement(10, shard_0), &payloads(&[b"inner", b"other"]))
+            .unwrap_err();
+        guard
+            .check_and_record(endorsement(10, shard_0), &payloads(&[b"other", b"metadata"]))
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::Context;
+use near_primitives::validator_signer::SignerAuthKey;
+use std::path::PathBuf;
+
+mod guard;
+mod server;
+
+pub use guard::DoubleSignGuard;
+pub use server::RemoteSigner;
+
+/// Runs a signer holding the validator key outside of the validator node.
+///
+/// The node connects to the signer when `remote_signer` is set in its
+/// config.json.  Both sides share a secret key, read from `--auth-key-file`
+/// here and from `remote_signer.auth_key_file` on the node, and the signer
+/// only handles requests authenticated with it.  The signer refuses to sign
+/// blocks, approvals, chunk headers, optimistic blocks, chunk endorsements and
+/// state witnesses which could result in a double sign.
+#[derive(clap::Parser)]
+pub struct RemoteSignerCommand {
+    /// Path to the validator key file.
+    #[clap(long)]
+    key_file: PathBuf,
+    /// Address to listen on, `unix:<path>` for a Unix socket or `<host>:<port>`
+    /// for a TCP socket.
+    #[clap(long)]
+    listen: String,
+    /// File in which the last signed heights are persisted.  Must be kept
+    /// across restarts of the signer.
+    #[clap(long)]
+    state_file: PathBuf,
+    /// File with the secret shared with the validator node, at least 32
+    /// bytes long.
+    #[clap(long)]
+    auth_key_file: PathBuf,
+}
+
+impl RemoteSignerCommand {
+    pub fn run(self) -> anyhow::Result<()> {
+        let signer = near_crypto::InMemorySigner::from_file(&self.key_file)
+            .with_context(|| format!("failed to load {}", self.key_file.display()))?;
+        let key = SignerAuthKey::from_file(&self.auth_key_file)
+            .with_context(|| format!("failed to load {}", self.auth_key_file.display()))?;
+        let guard = DoubleSignGuard::load(&self.state_file)?;
+        tracing::info!(target: "remote_signer", account_id = %signer.get_account_id(), listen = %self.listen, "Starting remote signer");
+        let remote_signer = RemoteSigner::new(signer, guard, key);
+
+        #[cfg(unix)]
+        if let Some(path) = self.listen.strip_prefix("unix:") {
+            let listener = std::os::unix::net::UnixListener::bind(path)
+                .with_context(|| format!("failed to listen on {}", self.listen))?;
+            remote_signer.serve(listener.incoming())?;
+            return Ok(());
+        }
+        let address = self.listen.strip_prefix("tcp:").unwrap_or(&self.listen);
+        let listener = std::net::TcpListener::bind(address)
+            .with_context(|| format!("failed to listen on {}", self.listen))?;
+        remote_signer.serve(listener.incoming())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::guard::DoubleSignGuard;
+use near_crypto::Signer;
+use near_primitives::validator_signer::{
+    AuthenticatedStream, RemoteSignerRequest, RemoteSignerResponse, SignerAuthKey, ValidatorMessage,
+};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+
+/// Signer process holding the validator key and serving signing requests of
+/// validator nodes.
+pub struct RemoteSigner {
+    signer: Signer,
+    guard: Mutex<DoubleSignGuard>,
+    /// Key shared with the validator nodes, used to authenticate every
+    /// request.
+    key: SignerAuthKey,
+}
+
+impl RemoteSigner {
+    pub fn new(signer: Signer, guard: DoubleSignGuard, key: SignerAuthKey) -> Arc<Self> {
+        Arc::new(Self { signer, guard: Mutex::new(guard), key })
+    }
+
+    pub fn handle_request(&self, request: RemoteSignerRequest) -> RemoteSignerResponse {
+        match request {
+            RemoteSignerRequest::GetPublicKey => RemoteSignerResponse::PublicKey {
+                account_id: self.signer.get_account_id(),
+                public_key: self.signer.public_key(),
+            },
+            RemoteSignerRequest::Sign(message) => match self.sign(&message) {
+                Ok(signatures) => RemoteSignerResponse::Signatures(signatures),
+                Err(err) => {
+                    tracing::warn!(target: "remote_signer", ?message, ?err, "Refusing to sign");
+                    RemoteSignerResponse::Refused { reason: format!("{err:#}") }
+                }
+            },
+            RemoteSignerRequest::ComputeVrf { data } => {
+                let (value, proof) = self.signer.compute_vrf_with_proof(&data);
+                RemoteSignerResponse::Vrf { value, proof }
+            }
+        }
+    }
+
+    /// Signs every payload of the message, unless doing so could result in
+    /// a double sign.
+    fn sign(&self, message: &ValidatorMessage) -> anyhow::Result<Vec<near_crypto::Signature>> {
+        message.validate().map_err(|err| anyhow::anyhow!("invalid message: {err}"))?;
+        let payloads = message.payloads(&self.signer.get_account_id());
+        let sign_all = || payloads.iter().map(|payload| self.signer.sign(payload)).collect();
+        let Some(slot) = message.slot() else {
+            return Ok(sign_all());
+        };
+        // Hold the lock while signing so that concurrent requests cannot race
+        // between the check and the signature.
+        let mut guard = self.guard.lock().unwrap();
+        guard.check_and_record(slot, &payloads)?;
+        Ok(sign_all())
+    }
+
+    /// Serves requests sent over the connection until the client disconnects
+    /// or sends a message which is not authenticated with the shared key.
+    pub fn handle_connection(&self, stream: impl Read + Write) -> io::Result<()> {
+        let mut stream = AuthenticatedStream::accept(stream, self.key.clone(), rand::random())?;
+        loop {
+            let request = match stream.receive() {
+                Ok(request) => request,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(err) => return Err(err),
+            };
+            let response = self.handle_request(request);
+            stream.send(&response)?;
+        }
+    }
+
+    /// Handles every incoming connection on a separate thread.
+    pub fn serve<S: Read + Write + Send + 'static>(
+        self: &Arc<Self>,
+        incoming: impl Iterator<Item = io::Result<S>>,
+    ) -> io::Result<()> {
+        for stream in incoming {
+            let stream = stream?;
+            let this = self.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = this.handle_connection(stream) {
+                    tracing::warn!(target: "remote_signer", ?err, "Connection closed");
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::block_header::{Approval, ApprovalInner};
+    use near_primitives::hash::hash;
+    use near_primitives::validator_signer::{RemoteValidatorSigner, ValidatorSignerError};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+
+    fn start_signer(signer: Signer, key: SignerAuthKey) -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = DoubleSignGuard::load(&dir.path().join("signer_state.json")).unwrap();
+        let remote_signer = RemoteSigner::new(signer, guard, key);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || remote_signer.serve(listener.incoming()));
+        (dir, address)
+    }
+
+    #[test]
+    fn test_remote_validator_signer() {
+        let signer = InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test");
+        let key = SignerAuthKey::new(&[7; 32]).unwrap();
+        let (_dir, address) = start_signer(signer.clone(), key.clone());
+
+        let validator_signer =
+            RemoteValidatorSigner::connect(&address, Duration::from_secs(5), key).unwrap();
+        assert_eq!(validator_signer.validator_id(), &signer.get_account_id());
+        assert_eq!(validator_signer.public_key(), signer.public_key());
+
+        let inner = ApprovalInner::Endorsement(hash(b"block"));
+        let approval = ValidatorMessage::Approval { inner: inner.clone(), target_height: 10 };
+        let signature = validator_signer.sign_message(&approval).unwrap();
+        assert!(signature.verify(&Approval::get_data_for_sig(&inner, 10), &signer.public_key()));
+        // Signing the same approval again is fine, a conflicting one is not.
+        validator_signer.sign_message(&approval).unwrap();
+        let conflicting = ValidatorMessage::Approval {
+            inner: ApprovalInner::Endorsement(hash(b"other block")),
+            target_height: 10,
+        };
+        let err = validator_signer.sign_message(&conflicting).unwrap_err();
+        assert!(matches!(err, ValidatorSignerError::Refused(_)));
+
+        // Opaque payloads which could be mistaken for a guarded message are
+        // refused as well.
+        let payload = Approval::get_data_for_sig(&ApprovalInner::Skip(9), 10);
+        let err = validator_signer
+            .sign_message(&ValidatorMessage::AccountKeyPayload { payload })
+            .unwrap_err();
+        assert!(matches!(err, ValidatorSignerError::Refused(_)));
+    }
+
+    #[test]
+    fn test_rejects_wrong_key() {
+        let signer = InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test");
+        let (_dir, address) = start_signer(signer, SignerAuthKey::new(&[7; 32]).unwrap());
+
+        let wrong_key = SignerAuthKey::new(&[8; 32]).unwrap();
+        RemoteValidatorSigner::connect(&address, Duration::from_secs(5), wrong_key).unwrap_err();
+    }
+
+    /// Stream which records everything written to it.
+    struct RecordingStream {
+        stream: TcpStream,
+        written: Vec<u8>,
+    }
+
+    impl Read for RecordingStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.stream.read(buf)
+        }
+    }
+
+    impl Write for RecordingStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = self.stream.write(buf)?;
+            self.written.extend_from_slice(&buf[..written]);
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.stream.flush()
+        }
+    }
+
+    /// Messages recorded on one connection are rejected on another, even if
+    /// the node's nonce is replayed too.
+    #[test]
+    fn test_rejects_replayed_messages() {
+        let signer = InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test");
+        let key = SignerAuthKey::new(&[7; 32]).unwrap();
+        let (_dir, address) = start_signer(signer, key.clone());
+
+        let stream =
+            RecordingStream { stream: TcpStream::connect(&address).unwrap(), written: vec![] };
+        let mut stream = AuthenticatedStream::connect(stream, key, [1; 32]).unwrap();
+        stream.send(&RemoteSignerRequest::GetPublicKey).unwrap();
+        let response: RemoteSignerResponse = stream.receive().unwrap();
+        assert!(matches!(response, RemoteSignerResponse::PublicKey { .. }));
+        let recorded = stream.into_inner().written;
+
+        let mut replay = TcpStream::connect(&address).unwrap();
+        replay.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        replay.write_all(&recorded).unwrap();
+        let mut nonce = [0u8; 32];
+        replay.read_exact(&mut nonce).unwrap();
+        // The signer drops the connection without responding.
+        let mut response = Vec::new();
+        replay.read_to_end(&mut response).unwrap();
+        assert!(response.is_empty());
+    }
+}
@@ -206,6 +206,7 @@ fn apply_block_from_range(
                     gas_limit: chunk_inner.gas_limit(),
                     is_new_chunk: true,
                     is_first_block_with_chunk_of_version,
+                    trace_host_calls: false,
                 },
                 ApplyChunkBlockContext::from_header(
                     block.header(),
@@ -233,6 +234,7 @@ fn apply_block_from_range(
                     gas_limit: chunk_extra.gas_limit(),
                     is_new_chunk: false,
                     is_first_block_with_chunk_of_version: false,
+                    trace_host_calls: false,
                 },
                 ApplyChunkBlockContext::from_header(
                     block.header(),
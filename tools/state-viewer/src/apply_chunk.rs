@@ -97,6 +97,7 @@ pub fn apply_chunk(
     target_height: Option<u64>,
     rng: Option<StdRng>,
     storage: StorageSource,
+    trace_host_calls: bool,
 ) -> anyhow::Result<(ApplyChunkResult, Gas)> {
     let chunk = chain_store.get_chunk(&chunk_hash)?;
     let chunk_header = chunk.cloned_header();
@@ -192,6 +193,7 @@ pub fn apply_chunk(
                 gas_limit: chunk_header.gas_limit(),
                 is_first_block_with_chunk_of_version,
                 is_new_chunk: true,
+                trace_host_calls,
             },
             ApplyChunkBlockContext {
                 height: target_height,
@@ -276,6 +278,7 @@ fn apply_tx_in_block(
                     runtime,
                     chain_store,
                     storage,
+                    false,
                 );
                 check_apply_block_result(
                     &block,
@@ -356,8 +359,16 @@ fn apply_tx_in_chunk(
             "found tx in chunk {}. Equivalent command (which will run faster than apply_tx):\nview_state apply_chunk --chunk_hash {}\n",
             &chunk_hash.0, &chunk_hash.0
         );
-        let (apply_result, gas_limit) =
-            apply_chunk(epoch_manager, runtime, chain_store, chunk_hash, None, None, storage)?;
+        let (apply_result, gas_limit) = apply_chunk(
+            epoch_manager,
+            runtime,
+            chain_store,
+            chunk_hash,
+            None,
+            None,
+            storage,
+            false,
+        )?;
         println!(
             "resulting chunk extra:\n{:?}",
             resulting_chunk_extra(&apply_result, gas_limit, protocol_version)
@@ -400,6 +411,7 @@ fn apply_receipt_in_block(
     id: &CryptoHash,
     block_hash: CryptoHash,
     storage: StorageSource,
+    trace_host_calls: bool,
 ) -> anyhow::Result<ApplyChunkResult> {
     match find_tx_or_receipt(id, &block_hash, epoch_manager, chain_store)? {
         Some((hash_type, shard_id)) => match hash_type {
@@ -423,6 +435,7 @@ fn apply_receipt_in_block(
                     runtime,
                     chain_store,
                     storage,
+                    trace_host_calls,
                 );
                 check_apply_block_result(
                     &block,
@@ -452,6 +465,7 @@ fn apply_receipt_in_chunk(
     chain_store: &mut ChainStore,
     id: &CryptoHash,
     storage: StorageSource,
+    trace_host_calls: bool,
 ) -> anyhow::Result<Vec<ApplyChunkResult>> {
     println!("Receipt is not indexed; searching in chunks that haven't been applied...");
 
@@ -523,6 +537,7 @@ fn apply_receipt_in_chunk(
             None,
             None,
             storage,
+            trace_host_calls,
         )?;
         let chunk_extra = resulting_chunk_extra(&apply_result, gas_limit, protocol_version);
         println!("resulting chunk extra:\n{:?}", chunk_extra);
@@ -538,6 +553,7 @@ pub fn apply_receipt(
     store: Store,
     id: CryptoHash,
     storage: StorageSource,
+    trace_host_calls: bool,
 ) -> anyhow::Result<Vec<ApplyChunkResult>> {
     let mut chain_store =
         ChainStore::new(store.clone(), false, genesis_config.transaction_validity_period);
@@ -550,8 +566,17 @@ pub fn apply_receipt(
             &id,
             outcome.block_hash,
             storage,
+            trace_host_calls,
         )?])
     } else {
-        apply_receipt_in_chunk(epoch_manager, runtime, store, &mut chain_store, &id, storage)
+        apply_receipt_in_chunk(
+            epoch_manager,
+            runtime,
+            store,
+            &mut chain_store,
+            &id,
+            storage,
+            trace_host_calls,
+        )
     }
 }
@@ -2,6 +2,7 @@ use crate::commands::*;
 use crate::congestion_control::CongestionControlCmd;
 use crate::contract_accounts::ContractAccountFilter;
 use crate::replay_headers::replay_headers;
+use crate::reshard_plan::ReshardPlanCmd;
 use crate::rocksdb_stats::get_rocksdb_stats;
 use crate::trie_iteration_benchmark::TrieIterationBenchmarkCmd;
 
@@ -69,6 +70,9 @@ pub enum StateViewerSubCommand {
     /// Dump deployed contract code of given account to wasm file.
     #[clap(alias = "dump_code")]
     DumpCode(DumpCodeCmd),
+    /// Dump the genesis block header and an epoch sync proof for a recent epoch, which a light
+    /// client can be bootstrapped from.
+    DumpEpochSyncProof(DumpEpochSyncProofCmd),
     /// Generate a genesis file from the current state of the DB.
     #[clap(alias = "dump_state")]
     DumpState(DumpStateCmd),
@@ -91,6 +95,10 @@ pub enum StateViewerSubCommand {
     Receipts(ReceiptsCmd),
     /// Replay block headers from chain.
     ReplayHeaders(ReplayHeadersCmd),
+    /// Report how the state and the recent gas load would be spread over the
+    /// shards of a layout with the given boundary accounts, or propose boundary
+    /// accounts for a number of shards, balancing the state size or the load.
+    ReshardPlan(ReshardPlanCmd),
     /// Dump stats for the RocksDB storage.
     #[clap(name = "rocksdb-stats", alias = "rocksdb_stats")]
     RocksDBStats(RocksDBStatsCmd),
@@ -141,6 +149,7 @@ impl StateViewerSubCommand {
         home_dir: &Path,
         genesis_validation: GenesisValidationMode,
         mode: Mode,
+        overlay: bool,
         temperature: Temperature,
     ) {
         let near_config = load_config(home_dir, genesis_validation)
@@ -153,6 +162,7 @@ impl StateViewerSubCommand {
         );
 
         let storage = store_opener.open_in_mode(mode).unwrap();
+        let storage = if overlay { storage.into_overlay() } else { storage };
         let store = match temperature {
             Temperature::Hot => storage.get_hot_store(),
             // Cold store on it's own is useless in majority of subcommands
@@ -178,6 +188,7 @@ impl StateViewerSubCommand {
             }
             StateViewerSubCommand::DumpAccountStorage(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::DumpCode(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::DumpEpochSyncProof(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::DumpState(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::DumpStateRedis(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::DumpTx(cmd) => cmd.run(home_dir, near_config, store),
@@ -186,6 +197,7 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::PartialChunks(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::Receipts(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::ReplayHeaders(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::ReshardPlan(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(store_opener.path()),
             StateViewerSubCommand::ScanDbColumn(cmd) => cmd.run(store),
             StateViewerSubCommand::State => state(home_dir, near_config, store),
@@ -359,12 +371,30 @@ pub struct ApplyReceiptCmd {
     hash: String,
     #[clap(long, default_value = "trie")]
     storage: StorageSource,
+    /// Print the host functions called by the receipt.
+    #[clap(long)]
+    trace: bool,
+    /// Write the gas burnt by the contract functions called by the receipt to
+    /// the given file, as collapsed stacks accepted by flamegraph tools.  The
+    /// profiling instrumentation burns some gas too, so the total is slightly
+    /// higher than without profiling.
+    #[clap(long)]
+    wasm_profile: Option<PathBuf>,
 }
 
 impl ApplyReceiptCmd {
     pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
         let hash = CryptoHash::from_str(&self.hash).unwrap();
-        apply_receipt(home_dir, near_config, store, hash, self.storage).unwrap();
+        apply_receipt(
+            home_dir,
+            near_config,
+            store,
+            hash,
+            self.storage,
+            self.trace,
+            self.wasm_profile.as_deref(),
+        )
+        .unwrap();
     }
 }
 
@@ -526,6 +556,22 @@ impl DumpCodeCmd {
     }
 }
 
+#[derive(clap::Parser)]
+pub struct DumpEpochSyncProofCmd {
+    /// File to write the borsh-serialized genesis block header to.
+    #[clap(long, value_parser)]
+    genesis_header: PathBuf,
+    /// File to write the borsh-serialized compressed epoch sync proof to.
+    #[clap(long, value_parser)]
+    epoch_sync_proof: PathBuf,
+}
+
+impl DumpEpochSyncProofCmd {
+    pub fn run(self, near_config: NearConfig, store: Store) {
+        dump_epoch_sync_proof(&self.genesis_header, &self.epoch_sync_proof, near_config, store);
+    }
+}
+
 #[derive(clap::Parser)]
 pub struct DumpStateCmd {
     /// Optionally, can specify at which height to dump state.
@@ -26,6 +26,7 @@ use near_chain::{
     get_incoming_receipts_for_shard,
 };
 use near_chain_configs::GenesisChangeConfig;
+use near_client::sync::epoch::EpochSync;
 use near_epoch_manager::shard_assignment::{shard_id_to_index, shard_id_to_uid};
 use near_epoch_manager::{EpochManager, EpochManagerAdapter};
 use near_primitives::account::id::AccountId;
@@ -44,6 +45,8 @@ use near_primitives::trie_key::TrieKey;
 use near_primitives::trie_key::col::COLUMNS_WITH_ACCOUNT_ID_IN_KEY;
 use near_primitives::types::{BlockHeight, EpochId, ShardId};
 use near_primitives::version::PROTOCOL_VERSION;
+use near_primitives::views::HostFunctionCallView;
+use near_primitives_core::serialize::to_base64;
 use near_primitives_core::types::{Balance, EpochHeight};
 use near_store::TrieStorage;
 use near_store::adapter::StoreAdapter;
@@ -71,6 +74,7 @@ pub(crate) fn apply_block(
     runtime: &dyn RuntimeAdapter,
     chain_store: &mut ChainStore,
     storage: StorageSource,
+    trace_host_calls: bool,
 ) -> (Block, ApplyChunkResult) {
     let block = chain_store.get_block(&block_hash).unwrap();
     let height = block.header().height();
@@ -118,6 +122,7 @@ pub(crate) fn apply_block(
                     gas_limit: chunk_inner.gas_limit(),
                     is_new_chunk: true,
                     is_first_block_with_chunk_of_version,
+                    trace_host_calls,
                 },
                 ApplyChunkBlockContext::from_header(
                     block.header(),
@@ -144,6 +149,7 @@ pub(crate) fn apply_block(
                     gas_limit: chunk_extra.gas_limit(),
                     is_new_chunk: false,
                     is_first_block_with_chunk_of_version: false,
+                    trace_host_calls,
                 },
                 ApplyChunkBlockContext::from_header(
                     block.header(),
@@ -189,6 +195,7 @@ pub(crate) fn apply_block_at_height(
         runtime.as_ref(),
         &mut read_chain_store,
         storage,
+        false,
     );
     check_apply_block_result(
         &block,
@@ -239,6 +246,7 @@ pub(crate) fn apply_chunk(
         target_height,
         None,
         storage,
+        false,
     )?;
     let protocol_version = if let Some(height) = target_height {
         // Retrieve the protocol version at the given height.
@@ -309,25 +317,68 @@ pub(crate) fn apply_receipt(
     store: Store,
     hash: CryptoHash,
     storage: StorageSource,
+    trace: bool,
+    wasm_profile: Option<&Path>,
 ) -> anyhow::Result<()> {
     let epoch_manager =
         EpochManager::new_arc_handle(store.clone(), &near_config.genesis.config, Some(home_dir));
-    let runtime = NightshadeRuntime::from_config(
+    let runtime = NightshadeRuntime::from_config_with_wasm_profiling(
         home_dir,
         store.clone(),
         &near_config,
         epoch_manager.clone(),
+        wasm_profile.is_some(),
     )
     .context("could not create the transaction runtime")?;
-    apply_chunk::apply_receipt(
+    let results = apply_chunk::apply_receipt(
         &near_config.genesis.config,
         epoch_manager.as_ref(),
         runtime.as_ref(),
         store,
         hash,
         storage,
-    )
-    .map(|_| ())
+        trace,
+    )?;
+    if trace {
+        let host_calls = results
+            .iter()
+            .flat_map(|result| &result.host_call_traces)
+            .filter(|trace| trace.receipt_id == hash)
+            .flat_map(|trace| &trace.host_calls);
+        println!("host function calls:");
+        for call in host_calls {
+            print_host_function_call(call);
+        }
+    }
+    if let Some(path) = wasm_profile {
+        let mut file = File::create(path)?;
+        let stacks = results
+            .iter()
+            .flat_map(|result| &result.wasm_profiles)
+            .filter(|profile| profile.receipt_id == hash)
+            .flat_map(|profile| &profile.stacks);
+        for line in stacks {
+            writeln!(file, "{line}")?;
+        }
+        println!("wasm profile written to {}", path.display());
+    }
+    Ok(())
+}
+
+fn print_host_function_call(call: &HostFunctionCallView) {
+    let args = call.args.iter().map(|(name, value)| format!("{name}={value}")).join(", ");
+    let result = match (&call.result, &call.error) {
+        (_, Some(error)) => format!(" -> error: {error}"),
+        (Some(result), None) => format!(" -> {result}"),
+        (None, None) => String::new(),
+    };
+    println!(
+        "  {}({args}){result} [gas {} -> {}]",
+        call.name, call.burnt_gas_before, call.burnt_gas_after
+    );
+    for register in &call.registers {
+        println!("    register {} (base64): {}", register.register_id, to_base64(&register.data));
+    }
 }
 
 pub(crate) fn apply_tx(
@@ -439,6 +490,36 @@ pub(crate) fn dump_code(
     );
 }
 
+pub(crate) fn dump_epoch_sync_proof(
+    genesis_header_path: &Path,
+    proof_path: &Path,
+    near_config: NearConfig,
+    store: Store,
+) {
+    let chain_store = ChainStore::new(
+        store.clone(),
+        near_config.client_config.save_trie_changes,
+        near_config.genesis.config.transaction_validity_period,
+    );
+    let genesis_hash =
+        chain_store.get_block_hash_by_height(near_config.genesis.config.genesis_height).unwrap();
+    let genesis_header = chain_store.get_block_header(&genesis_hash).unwrap();
+    let proof = EpochSync::derive_epoch_sync_proof(
+        store,
+        near_config.genesis.config.transaction_validity_period,
+        Default::default(),
+    )
+    .unwrap();
+    fs::write(genesis_header_path, borsh::to_vec(&genesis_header).unwrap()).unwrap();
+    fs::write(proof_path, borsh::to_vec(&proof).unwrap()).unwrap();
+    println!(
+        "Dumped genesis header {} into file {} and epoch sync proof into file {}",
+        genesis_hash,
+        genesis_header_path.display(),
+        proof_path.display()
+    );
+}
+
 pub(crate) fn dump_state(
     height: Option<BlockHeight>,
     stream: bool,
@@ -1369,7 +1450,7 @@ fn print_state_stats_for_shard_uid(
 /// Gets the flat state iterator from the chunk view, rearranges it to be sorted
 /// by the account id, rather than type, account id and finally groups the
 /// records by account id while collecting aggregate statistics.
-fn get_state_stats_group_by<'a>(
+pub(crate) fn get_state_stats_group_by<'a>(
     chunk_view: &'a FlatStorageChunkView,
     trie_storage: &'a TrieDBStorage,
 ) -> GroupBy<
@@ -1509,10 +1590,10 @@ impl StateStats {
 /// StateStatsStateRecord stores the state record and associated information.
 /// It's used as a helper struct for merging state records from different record types.
 #[derive(Eq, PartialEq)]
-struct StateStatsStateRecord {
-    state_record: StateRecord,
-    account_id: AccountId,
-    size: ByteSize,
+pub(crate) struct StateStatsStateRecord {
+    pub(crate) state_record: StateRecord,
+    pub(crate) account_id: AccountId,
+    pub(crate) size: ByteSize,
 }
 
 impl Ord for StateStatsStateRecord {
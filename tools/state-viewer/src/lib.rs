@@ -10,6 +10,7 @@ mod epoch_info;
 mod latest_witnesses;
 pub mod progress_reporter;
 mod replay_headers;
+mod reshard_plan;
 mod rocksdb_stats;
 mod scan_db;
 mod state_changes;
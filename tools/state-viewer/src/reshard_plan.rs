@@ -0,0 +1,404 @@
+//! Planning of shard layouts with a different number of shards, see
+//! [`ReshardPlanCmd`].
+use crate::commands::get_state_stats_group_by;
+use crate::util::load_trie;
+use anyhow::Context;
+use bytesize::ByteSize;
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_epoch_manager::EpochManagerAdapter;
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::ShardLayout;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::{AccountId, Gas, NumShards, ShardId};
+use near_store::adapter::StoreAdapter;
+use near_store::{Store, TrieDBStorage};
+use nearcore::{NearConfig, NightshadeRuntime, NightshadeRuntimeExt};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(clap::Parser)]
+pub struct ReshardPlanCmd {
+    /// Number of shards to propose boundary accounts for.
+    #[clap(long, required_unless_present = "boundary_accounts")]
+    num_shards: Option<NumShards>,
+    /// Comma separated boundary accounts to evaluate instead of proposing new
+    /// ones.
+    #[clap(long, value_delimiter = ',', conflicts_with = "num_shards")]
+    boundary_accounts: Vec<AccountId>,
+    /// What the proposed shards should be balanced by.
+    #[clap(long, value_enum, default_value_t = BalanceBy::Size)]
+    balance_by: BalanceBy,
+    /// Number of blocks before the head to measure the gas load over.
+    #[clap(long, default_value_t = 1000)]
+    num_blocks: u64,
+    /// File to write the resulting shard layout to, as JSON which can be
+    /// passed to `fork-network init --shard-layout-file`.
+    #[clap(long)]
+    shard_layout_file: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+#[clap(rename_all = "kebab_case")]
+enum BalanceBy {
+    /// Size of the state of the accounts in flat storage.
+    Size,
+    /// Gas burnt by the receipts and transactions executed by the accounts.
+    Gas,
+}
+
+impl ReshardPlanCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        self.run_impl(home_dir, near_config, store).unwrap();
+    }
+
+    fn run_impl(
+        self,
+        home_dir: &Path,
+        near_config: NearConfig,
+        store: Store,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(self.num_shards != Some(0), "--num-shards must be at least 1");
+        let (epoch_manager, runtime, _, block_header) =
+            load_trie(store.clone(), home_dir, &near_config);
+        let block_hash = *block_header.hash();
+        let shard_layout = epoch_manager.get_shard_layout_from_prev_block(&block_hash)?;
+
+        let chain_store = ChainStore::new(
+            store.clone(),
+            near_config.client_config.save_trie_changes,
+            near_config.genesis.config.transaction_validity_period,
+        );
+        let load = RecentLoad::collect(&chain_store, block_hash, self.num_blocks);
+        load.print();
+
+        // Flat storage is walked only once, as it takes a while for large
+        // shards.
+        let state = StateSource { store, runtime, block_hash, shard_layout, load };
+        let accounts = state.collect_accounts();
+        let boundary_accounts = match self.num_shards {
+            Some(num_shards) => {
+                propose_boundaries(&state.shard_layout, &accounts, num_shards, self.balance_by)?
+            }
+            None => {
+                let mut boundary_accounts = self.boundary_accounts;
+                boundary_accounts.sort();
+                boundary_accounts.dedup();
+                boundary_accounts
+            }
+        };
+
+        let mut shards = vec![ShardStats::default(); boundary_accounts.len() + 1];
+        for account in &accounts {
+            let shard_index =
+                boundary_accounts.partition_point(|boundary| boundary <= &account.account_id);
+            shards[shard_index].push(account);
+        }
+        print_shards(&boundary_accounts, &shards);
+
+        if let Some(shard_layout_file) = self.shard_layout_file {
+            let num_shards = boundary_accounts.len() as u64 + 1;
+            let shard_ids = (0..num_shards).map(ShardId::new).collect();
+            let shard_layout = ShardLayout::v2(boundary_accounts, shard_ids, None);
+            std::fs::write(&shard_layout_file, serde_json::to_string_pretty(&shard_layout)?)
+                .with_context(|| format!("failed to write {}", shard_layout_file.display()))?;
+            println!("Shard layout written to {}", shard_layout_file.display());
+        }
+        Ok(())
+    }
+}
+
+/// Walks the accounts in flat storage at the given block.
+struct StateSource {
+    store: Store,
+    runtime: Arc<NightshadeRuntime>,
+    block_hash: CryptoHash,
+    shard_layout: ShardLayout,
+    load: RecentLoad,
+}
+
+impl StateSource {
+    /// Returns the stats of every account with any state.  With boundary
+    /// accounts in the shard layout, the shards are ordered by accounts and so
+    /// are the returned accounts.
+    fn collect_accounts(&self) -> Vec<AccountStats> {
+        let mut accounts = vec![];
+        let flat_storage_manager = self.runtime.get_flat_storage_manager();
+        for shard_uid in self.shard_layout.shard_uids() {
+            flat_storage_manager.create_flat_storage_for_shard(shard_uid).unwrap();
+            let trie_storage = TrieDBStorage::new(self.store.trie_store(), shard_uid);
+            let chunk_view = flat_storage_manager.chunk_view(shard_uid, self.block_hash).unwrap();
+            let group_by = get_state_stats_group_by(&chunk_view, &trie_storage);
+            for (account_id, group) in &group_by {
+                let mut account = AccountStats {
+                    gas: self.load.accounts_gas.get(&account_id).copied().unwrap_or_default(),
+                    account_id,
+                    size: ByteSize::b(0),
+                    is_account: false,
+                    has_contract: false,
+                };
+                for record in group {
+                    account.size += record.size;
+                    match record.state_record {
+                        StateRecord::Account { .. } => account.is_account = true,
+                        StateRecord::Contract { .. } => account.has_contract = true,
+                        _ => {}
+                    }
+                }
+                accounts.push(account);
+            }
+        }
+        accounts
+    }
+}
+
+/// Proposes boundary accounts for the accounts returned by
+/// [`StateSource::collect_accounts`].
+fn propose_boundaries(
+    shard_layout: &ShardLayout,
+    accounts: &[AccountStats],
+    num_shards: NumShards,
+    balance_by: BalanceBy,
+) -> anyhow::Result<Vec<AccountId>> {
+    if let ShardLayout::V0(_) = shard_layout {
+        anyhow::bail!("proposing boundary accounts requires a shard layout ordered by accounts");
+    }
+    let total_weight: u128 = accounts.iter().map(|account| account.weight(balance_by)).sum();
+    if total_weight == 0 {
+        anyhow::bail!("nothing to balance, the total {balance_by:?} is zero");
+    }
+
+    let mut proposer = BoundaryProposer::new(num_shards, total_weight);
+    for account in accounts {
+        proposer.push(account.account_id.clone(), account.weight(balance_by));
+    }
+    let boundary_accounts = proposer.finish();
+    if (boundary_accounts.len() as u64) + 1 < num_shards {
+        tracing::warn!(
+            target: "state_viewer",
+            num_shards = boundary_accounts.len() + 1,
+            "could not split the state into the requested number of shards"
+        );
+    }
+    Ok(boundary_accounts)
+}
+
+struct AccountStats {
+    account_id: AccountId,
+    size: ByteSize,
+    /// Whether the state has an `Account` record rather than just leftover
+    /// records of the account.
+    is_account: bool,
+    has_contract: bool,
+    gas: Gas,
+}
+
+impl AccountStats {
+    fn weight(&self, balance_by: BalanceBy) -> u128 {
+        match balance_by {
+            BalanceBy::Size => self.size.as_u64().into(),
+            BalanceBy::Gas => self.gas.into(),
+        }
+    }
+}
+
+/// Proposes boundary accounts splitting the accounts, pushed in order, into
+/// shards of similar total weight.  A new shard starts at the first account at
+/// which the weight of the accounts before it reaches the next multiple of
+/// `total_weight / num_shards`.
+struct BoundaryProposer {
+    num_shards: u128,
+    total_weight: u128,
+    leading_weight: u128,
+    boundary_accounts: Vec<AccountId>,
+}
+
+impl BoundaryProposer {
+    fn new(num_shards: NumShards, total_weight: u128) -> Self {
+        Self {
+            num_shards: num_shards.into(),
+            total_weight,
+            leading_weight: 0,
+            boundary_accounts: vec![],
+        }
+    }
+
+    fn push(&mut self, account_id: AccountId, weight: u128) {
+        let next_shard = self.boundary_accounts.len() as u128 + 1;
+        if next_shard < self.num_shards
+            && self.leading_weight > 0
+            && self.leading_weight * self.num_shards >= self.total_weight * next_shard
+        {
+            self.boundary_accounts.push(account_id);
+        }
+        self.leading_weight += weight;
+    }
+
+    fn finish(self) -> Vec<AccountId> {
+        self.boundary_accounts
+    }
+}
+
+#[derive(Clone, Default)]
+struct ShardStats {
+    size: ByteSize,
+    accounts: u64,
+    contracts: u64,
+    gas: Gas,
+}
+
+impl ShardStats {
+    fn push(&mut self, account: &AccountStats) {
+        self.size += account.size;
+        self.accounts += u64::from(account.is_account);
+        self.contracts += u64::from(account.has_contract);
+        self.gas += account.gas;
+    }
+}
+
+/// Load of the chain over the last blocks: the gas burnt by every account,
+/// from the execution outcomes, and per shard of the current layout, from the
+/// outcomes and the `ChunkApplyStats`.
+#[derive(Default)]
+struct RecentLoad {
+    num_blocks: u64,
+    accounts_gas: HashMap<AccountId, Gas>,
+    shards: BTreeMap<ShardId, ShardLoad>,
+}
+
+#[derive(Default)]
+struct ShardLoad {
+    new_chunks: u64,
+    transactions: u64,
+    incoming_receipts: u64,
+    gas: Gas,
+}
+
+impl RecentLoad {
+    /// Goes back from the given block for up to `num_blocks` blocks, as long
+    /// as the outcomes of the blocks weren't garbage collected.
+    fn collect(chain_store: &ChainStore, head: CryptoHash, num_blocks: u64) -> Self {
+        let mut load = Self::default();
+        let mut block_hash = head;
+        while load.num_blocks < num_blocks {
+            let header = match chain_store.get_block_header(&block_hash) {
+                Ok(header) => header,
+                Err(err) => {
+                    tracing::warn!(target: "state_viewer", ?block_hash, ?err, "missing block header");
+                    break;
+                }
+            };
+            if header.is_genesis() {
+                break;
+            }
+            let outcomes = match chain_store.get_block_execution_outcomes(&block_hash) {
+                Ok(outcomes) => outcomes,
+                Err(err) => {
+                    tracing::warn!(target: "state_viewer", ?block_hash, ?err, "missing block");
+                    break;
+                }
+            };
+            for (shard_id, outcomes) in outcomes {
+                let shard = load.shards.entry(shard_id).or_default();
+                for outcome in outcomes {
+                    let outcome = outcome.outcome_with_id.outcome;
+                    shard.gas += outcome.gas_burnt;
+                    *load.accounts_gas.entry(outcome.executor_id).or_default() += outcome.gas_burnt;
+                }
+                let stats = match chain_store.get_chunk_apply_stats(&block_hash, &shard_id) {
+                    Ok(Some(stats)) => stats,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        tracing::warn!(target: "state_viewer", ?block_hash, %shard_id, ?err, "failed to read chunk apply stats");
+                        continue;
+                    }
+                };
+                shard.new_chunks += u64::from(stats.is_new_chunk());
+                shard.transactions += stats.transactions_num();
+                shard.incoming_receipts += stats.incoming_receipts_num();
+            }
+            load.num_blocks += 1;
+            block_hash = *header.prev_hash();
+        }
+        load
+    }
+
+    fn print(&self) {
+        println!("Load of the current shards over the last {} blocks:", self.num_blocks);
+        println!(
+            "{:>8} {:>10} {:>12} {:>12} {:>20}",
+            "shard", "chunks", "transactions", "receipts", "gas"
+        );
+        for (shard_id, shard) in &self.shards {
+            println!(
+                "{:>8} {:>10} {:>12} {:>12} {:>20}",
+                shard_id.to_string(),
+                shard.new_chunks,
+                shard.transactions,
+                shard.incoming_receipts,
+                shard.gas
+            );
+        }
+        println!();
+    }
+}
+
+fn print_shards(boundary_accounts: &[AccountId], shards: &[ShardStats]) {
+    let total_size: u64 = shards.iter().map(|shard| shard.size.as_u64()).sum();
+    let total_gas: Gas = shards.iter().map(|shard| shard.gas).sum();
+    let percent = |value: u128, total: u128| if total == 0 { 0 } else { 100 * value / total };
+
+    println!("Boundary accounts: {boundary_accounts:?}");
+    println!(
+        "{:>6} {:>12} {:>5} {:>10} {:>10} {:>20} {:>5}  {}",
+        "shard", "size", "%", "accounts", "contracts", "gas", "%", "first account"
+    );
+    for (shard_index, shard) in shards.iter().enumerate() {
+        let first_account = match shard_index {
+            0 => "-".to_string(),
+            _ => boundary_accounts[shard_index - 1].to_string(),
+        };
+        println!(
+            "{:>6} {:>12} {:>5} {:>10} {:>10} {:>20} {:>5}  {}",
+            shard_index,
+            shard.size.to_string(),
+            percent(shard.size.as_u64().into(), total_size.into()),
+            shard.accounts,
+            shard.contracts,
+            shard.gas,
+            percent(shard.gas.into(), total_gas.into()),
+            first_account,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundaryProposer;
+    use near_primitives::types::AccountId;
+
+    fn propose(weights: &[(&str, u128)], num_shards: u64) -> Vec<String> {
+        let total_weight = weights.iter().map(|(_, weight)| weight).sum();
+        let mut proposer = BoundaryProposer::new(num_shards, total_weight);
+        for (account_id, weight) in weights {
+            proposer.push(account_id.parse::<AccountId>().unwrap(), *weight);
+        }
+        proposer.finish().iter().map(|account_id| account_id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_propose_boundaries() {
+        let even = [("a", 1), ("b", 1), ("c", 1), ("d", 1), ("e", 1), ("f", 1)];
+        assert_eq!(propose(&even, 1), Vec::<String>::new());
+        assert_eq!(propose(&even, 2), vec!["d"]);
+        assert_eq!(propose(&even, 3), vec!["c", "e"]);
+
+        // A shard is never cut before a large account, only after it.
+        let skewed = [("a", 1), ("b", 10), ("c", 1), ("d", 1)];
+        assert_eq!(propose(&skewed, 2), vec!["c"]);
+        assert_eq!(propose(&skewed, 3), vec!["c", "d"]);
+
+        // There are never more shards than accounts.
+        assert_eq!(propose(&[("a", 1), ("b", 1)], 4), vec!["b"]);
+    }
+}